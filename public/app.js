@@ -9,6 +9,8 @@ const setupLeadEl = document.getElementById('setupLead');
 const setupStepsEl = document.getElementById('setupSteps');
 const startDictationBtn = document.getElementById('startDictation');
 const stopDictationBtn = document.getElementById('stopDictation');
+const pauseDictationBtn = document.getElementById('pauseDictation');
+const scratchDictationBtn = document.getElementById('scratchDictation');
 const clearTranscriptBtn = document.getElementById('clearTranscript');
 const transcriptInput = document.getElementById('transcriptInput');
 const dictationWaveformEl = document.getElementById('dictationWaveform');
@@ -39,6 +41,7 @@ const recordDictationHotkeyBtn = document.getElementById('recordDictationHotkey'
 const saveDictationHotkeyBtn = document.getElementById('saveDictationHotkey');
 const resetDictationHotkeyBtn = document.getElementById('resetDictationHotkey');
 const clearDictationHotkeyBtn = document.getElementById('clearDictationHotkey');
+const reregisterDictationHotkeyBtn = document.getElementById('reregisterDictationHotkey');
 const dictationHotkeyStatusEl = document.getElementById('dictationHotkeyStatus');
 const dictationHotkeyPresetsEl = document.getElementById('dictationHotkeyPresets');
 const focusedFieldInsertCardEl = document.getElementById('focusedFieldInsertCard');
@@ -47,6 +50,7 @@ const focusedFieldInsertStatusEl = document.getElementById('focusedFieldInsertSt
 const dictationPermissionsCardEl = document.getElementById('dictationPermissionsCard');
 const dictationPermissionSummaryEl = document.getElementById('dictationPermissionSummary');
 const dictationPermissionListEl = document.getElementById('dictationPermissionList');
+const requestMicrophonePermissionBtn = document.getElementById('requestMicrophonePermission');
 const quickDictationFab = document.getElementById('quickDictationFab');
 const dictationWaveformBars = Array.from({ length: 12 }, (_, index) => document.getElementById(`dictationWaveBar${index}`)).filter(Boolean);
 
@@ -56,6 +60,32 @@ const HOTKEY_MODIFIER_ORDER = ['CmdOrCtrl', 'Cmd', 'Ctrl', 'Alt', 'Shift', 'Supe
 const DICTATION_HOTKEY_EVENT = 'dictation:hotkey-triggered';
 const DICTATION_STATE_EVENT = 'dictation:state-changed';
 const DICTATION_AUDIO_LEVEL_EVENT = 'dictation:audio-level';
+const DICTATION_PROGRESS_EVENT = 'dictation:progress';
+const DICTATION_PARTIAL_EVENT = 'dictation:partial';
+const DICTATION_NEEDS_ONBOARDING_EVENT = 'dictation:needs-onboarding';
+const MODEL_DOWNLOAD_RETRY_EVENT = 'model:download-retry';
+const DICTATION_OUTPUT_MODE_EVENT = 'dictation:output-mode';
+const DICTATION_SLOW_EVENT = 'dictation:slow';
+const DICTATION_TOO_SHORT_EVENT = 'dictation:too-short';
+// Emitted by Tauri itself when files are dropped on the window; payload `{ paths, position }`.
+const FILE_DROP_EVENT = 'tauri://drag-drop';
+const NEEDS_ONBOARDING_MESSAGES = {
+  no_model: 'Install a local dictation model to start dictating.',
+  model_missing: 'The selected dictation model file is missing. Reinstall or pick another model.',
+  cli_missing: 'whisper-cli is not available. Finish setup to start dictating.'
+};
+// Spoken commands the backend ran in command_mode instead of returning a transcript.
+const NATIVE_COMMAND_STATUS = {
+  scratch: 'Scratched that. Nothing was inserted.',
+  copy: 'Copied to the clipboard instead of inserting.'
+};
+// Backend error codes that mean setup is incomplete rather than a one-off failure.
+const SETUP_ERROR_CODES = new Set([
+  'MODEL_NOT_SELECTED',
+  'MODEL_MISSING',
+  'WHISPER_CLI_UNAVAILABLE',
+  'WHISPER_CLI_PLACEHOLDER'
+]);
 const NATIVE_HOLD_HOTKEYS = new Set(['Fn', 'F19']);
 const MAC_DESKTOP_ONLY_MESSAGE = 'Desktop MVP currently supports macOS only. Current mobile focus is iPhone (iOS).';
 const PILL_STATUS_EVENT = 'dicktaint://pill-status';
@@ -86,6 +116,7 @@ let currentOnboarding = null;
 let setupScreenMode = 'onboarding';
 let savedDictationHotkey = null;
 let defaultDictationHotkey = DEFAULT_DICTATION_HOTKEY;
+let fnTriggerSupported = true;
 let preferredInputDevice = null;
 let activeHotkeySpec = null;
 let pendingDictationHotkey = '';
@@ -93,16 +124,30 @@ let isCapturingDictationHotkey = false;
 let dictationTriggerMode = 'disabled';
 let dictationTriggerStatus = 'Hotkey disabled.';
 let dictationTriggerPermissionHint = '';
+// `local` means the backend's in-app Fn monitor already routes Fn edges, so the webview must not.
+let fnListenerMode = 'unavailable';
 let focusedFieldInsertEnabled = false;
 let focusedFieldInsertPermissionGranted = false;
 let focusedFieldInsertPermissionStatus = 'Focused-field insertion is disabled.';
 let isSavingFocusedFieldInsertSetting = false;
 let isSavingInputDevice = false;
+let isRequestingMicrophonePermission = false;
+let microphonePermissionGranted = false;
+let microphonePermissionStatus = '';
+let microphonePermissionBlocked = false;
 let lastHotkeyToggleAtMs = 0;
 let nativeHotkeyActionInFlight = false;
 let nativeFnHoldActive = false;
 let nativeFnStopRequested = false;
 let nativeStopRequestInFlight = false;
+let nativeScratchSessionActive = false;
+let nativeCommandResult = '';
+// Hint from `dictation:too-short` for a stop request still waiting on its empty result.
+let nativeTooShortHint = '';
+let fileTranscriptionInFlight = false;
+let isNativeDictationPaused = false;
+let nativePartialTranscript = '';
+let nativeCompletionNote = '';
 let pendingNativeStartAfterStop = false;
 let pendingNativeStartTrigger = null;
 let activeNativeSessionId = null;
@@ -411,7 +456,7 @@ function eventMatchesHotkey(event, spec) {
 }
 
 function getSuggestedHotkeyOptions() {
-  if (isFocusedMacDesktopMode()) {
+  if (isFocusedMacDesktopMode() && fnTriggerSupported) {
     return HOTKEY_PRESET_OPTIONS;
   }
   return HOTKEY_PRESET_OPTIONS.filter((option) => option.value !== 'Fn');
@@ -518,8 +563,12 @@ function renderPermissionGuidance() {
 
   const items = [
     {
-      tone: nativeDictationModelReady ? 'ok' : 'neutral',
-      text: 'Microphone: macOS asks the first time you start dictation. If audio fails later, relaunch after changing permission.'
+      tone: microphonePermissionGranted
+        ? 'ok'
+        : (microphonePermissionStatus ? 'warn' : 'neutral'),
+      text: microphonePermissionStatus
+        ? `Microphone: ${microphonePermissionStatus}`
+        : 'Microphone: use Grant Microphone Access now, or macOS asks the first time you start dictation. If audio fails later, relaunch after changing permission.'
     }
   ];
 
@@ -668,6 +717,7 @@ function summarizeHotkeyPillStatus(message, tone = 'neutral') {
     return `Listening - press ${hotkeyLabel} again`;
   }
   if (tone === 'working') {
+    if (normalized.includes('still transcribing')) return 'Still transcribing - cancel to stop';
     if (normalized.includes('transcrib')) return 'Transcribing...';
     if (normalized.includes('microphone') || normalized.includes('starting') || normalized.includes('opening')) {
       return 'Starting dictation...';
@@ -733,6 +783,14 @@ function syncFlowForSetupReadiness() {
   }
 }
 
+// Best-effort warm-up so the first dictation skips the model load; the backend ignores repeats
+// for the same model, so this can run after every onboarding refresh.
+function preloadNativeDictationModel() {
+  const tauriInvoke = getTauriInvoke();
+  if (!tauriInvoke || !isFocusedMacDesktopMode()) return;
+  tauriInvoke('preload_model').catch(() => {});
+}
+
 function setDictationModelStatus(message, tone = 'neutral') {
   if (!dictationModelStatusEl) return;
   dictationModelStatusEl.textContent = message;
@@ -789,6 +847,29 @@ function syncSetupHealthPills() {
   }
 }
 
+// A measured benchmark replaces the backend's core-count speed estimate.
+function benchmarkMetaParts(benchmark, estimatedRealTimeFactor) {
+  const seconds = Number(benchmark?.processing_seconds);
+  const speed = Number(benchmark?.real_time_factor);
+  if (!Number.isFinite(seconds) || seconds <= 0) {
+    const estimate = Number(estimatedRealTimeFactor);
+    return Number.isFinite(estimate) && estimate > 0
+      ? [`~${estimate.toFixed(1)}x real time (estimated)`]
+      : [];
+  }
+  const parts = [`~${seconds.toFixed(1)}s on your machine`];
+  if (Number.isFinite(speed) && speed > 0) parts.push(`${speed.toFixed(1)}x real time`);
+  if (benchmark.matches_expected === false) parts.push('benchmark transcript was off');
+  return parts;
+}
+
+function installedModelMetaPart(model) {
+  if (!model?.size_suspect) return 'downloaded locally';
+  const actualMb = Math.round(Number(model.actual_size_bytes) / 1_048_576);
+  const onDisk = Number.isFinite(actualMb) ? ` (${actualMb} MB on disk)` : '';
+  return `download looks incomplete${onDisk}, delete and download again`;
+}
+
 function refreshSelectedModelMeta() {
   if (!dictationModelMetaEl) return;
   const selected = getSelectedDictationModel();
@@ -807,12 +888,21 @@ function refreshSelectedModelMeta() {
     sizeLabel,
     selected.speed_note || 'speed unknown',
     selected.quality_note || 'quality unknown',
-    selected.installed ? 'downloaded locally' : 'not downloaded',
-    selected.recommended ? 'recommended for this machine' : (selected.likely_runnable ? 'fits this machine' : 'likely heavy on this machine')
+    selected.installed ? installedModelMetaPart(selected) : 'not downloaded',
+    ...benchmarkMetaParts(selected.benchmark, selected.estimated_real_time_factor),
+    selected.recommended ? 'recommended for this machine' : (selected.likely_runnable ? 'fits this machine' : 'likely heavy on this machine'),
+    ...runnabilityMetaParts(selected.runnability)
   ];
   dictationModelMetaEl.textContent = parts.join(' • ');
 }
 
+function runnabilityMetaParts(runnability) {
+  if (!runnability) return [];
+  return [runnability.ram, runnability.cpu, runnability.disk]
+    .filter((check) => check && check.status !== 'pass' && check.detail)
+    .map((check) => check.detail);
+}
+
 function getErrorMessage(error) {
   if (!error) return 'Unknown error';
   const normalize = (value) => {
@@ -840,6 +930,12 @@ function getErrorMessage(error) {
   return fallback || 'Unknown error';
 }
 
+// Commands reject with `{ code, message }`; anything else (older builds, JS errors) has no code.
+function getErrorCode(error) {
+  const code = error && typeof error === 'object' ? error.code : '';
+  return typeof code === 'string' ? code.trim() : '';
+}
+
 function getSelectedDictationModel() {
   const selectedId = (dictationModelSelect?.value || '').trim();
   if (!selectedId) return null;
@@ -889,13 +985,24 @@ function syncControls() {
     || isStartingDictation
     || nativeStopRequestInFlight
     || dictationModelMissing
+    || microphonePermissionBlocked
   );
+  if (scratchDictationBtn) {
+    scratchDictationBtn.disabled = startDictationBtn.disabled || !isFocusedMacDesktopMode();
+  }
   stopDictationBtn.disabled = (
     lockControls
     || !hasCaptureSupport
     || nativeStopRequestInFlight
     || (!isDictating && !isStartingDictation)
   );
+  if (pauseDictationBtn) {
+    pauseDictationBtn.disabled = lockControls
+      || !isFocusedMacDesktopMode()
+      || !isDictating
+      || nativeStopRequestInFlight;
+    pauseDictationBtn.textContent = isNativeDictationPaused ? 'Resume' : 'Pause';
+  }
   clearTranscriptBtn.disabled = lockControls || nativeStopRequestInFlight;
 
   if (installDictationModelBtn) {
@@ -915,6 +1022,12 @@ function syncControls() {
   if (retryWhisperCheckBtn) {
     retryWhisperCheckBtn.disabled = lockControls;
   }
+  if (requestMicrophonePermissionBtn) {
+    requestMicrophonePermissionBtn.disabled = lockControls
+      || isRequestingMicrophonePermission
+      || isDictating
+      || isStartingDictation;
+  }
   if (dictationModelSelect) {
     dictationModelSelect.disabled = lockControls;
   }
@@ -942,6 +1055,9 @@ function syncControls() {
   if (clearDictationHotkeyBtn) {
     clearDictationHotkeyBtn.disabled = hotkeyDisabled || !normalizedSaved;
   }
+  if (reregisterDictationHotkeyBtn) {
+    reregisterDictationHotkeyBtn.disabled = hotkeyDisabled || !normalizedSaved;
+  }
   if (focusedFieldInsertToggleEl) {
     focusedFieldInsertToggleEl.disabled = hotkeyDisabled || isSavingFocusedFieldInsertSetting;
     focusedFieldInsertToggleEl.checked = focusedFieldInsertEnabled;
@@ -1078,6 +1194,7 @@ function historySourceLabel(source) {
   const value = String(source || '').trim().toLowerCase();
   if (value === 'web') return 'WEB';
   if (value === 'native' || value === 'native-event') return 'DESKTOP';
+  if (value === 'file') return 'FILE';
   return 'DICTATION';
 }
 
@@ -1204,7 +1321,8 @@ function appendTranscriptChunk(chunk, { source = 'native', nativeSessionId = nul
   appendToDraftTranscript(trimmed);
   rejectNextNativeAppend = false;
   pushDictationHistory(trimmed, source);
-  void maybeInsertTranscriptIntoFocusedField(trimmed);
+  // The backend's output_suffix (space/newline) is only meaningful for the paste target.
+  void maybeInsertTranscriptIntoFocusedField(String(chunk).trimStart());
   return true;
 }
 
@@ -1290,14 +1408,23 @@ function describeDeviceProfile(device) {
   const ram = Number(device.total_memory_gb) || 0;
   const cores = Number(device.logical_cpu_cores) || 1;
   const machine = describeMachineLabel(device).replace(/^This /u, '');
-  return `${machine} • ${ram} GB RAM • ${cores} logical CPU cores • ${device.os || 'unknown os'}`;
+  const summary = `${machine} • ${ram} GB RAM • ${cores} logical CPU cores • ${device.os || 'unknown os'}`;
+  if (device.simd_support === 'none') {
+    return `${summary} • no AVX/NEON vector support, so smaller models are suggested`;
+  }
+  if (device.simd_support === 'partial') {
+    return `${summary} • no AVX2/FMA, so smaller models are suggested`;
+  }
+  return summary;
 }
 
 function buildDictationModelLabel(model) {
   const fit = model.recommended
     ? 'Recommended'
-    : (model.likely_runnable ? 'Likely runnable' : 'Heavy for this machine');
-  const local = model.installed ? 'Installed' : `${model.approx_size_gb} GB`;
+    : (model.runnability?.summary || (model.likely_runnable ? 'Likely runnable' : 'Heavy for this machine'));
+  const local = model.installed
+    ? (model.size_suspect ? 'Installed (incomplete?)' : 'Installed')
+    : `${model.approx_size_gb} GB`;
   return `${modelDisplayName(model)} • ${local} • ${fit}`;
 }
 
@@ -1353,15 +1480,22 @@ function renderInputDeviceOptions(devices, selectedDeviceName) {
   for (const device of available) {
     const name = String(device?.name || '').trim();
     if (!name) continue;
+    const label = String(device?.label || '').trim() || name;
     const option = document.createElement('option');
     option.value = name;
-    option.textContent = device?.is_default ? `${name} (Default)` : name;
+    option.textContent = device?.is_default ? `${label} (Default)` : label;
     dictationInputSelectEl.appendChild(option);
   }
 
   dictationInputSelectEl.value = selectedDeviceName || '';
 
-  if (selectedDeviceName) {
+  const selectedDevice = available.find((device) => device?.name === selectedDeviceName);
+  if (selectedDevice?.loopback) {
+    setDictationInputStatus(
+      `Capturing ${selectedDevice.label || selectedDeviceName} instead of a microphone.`,
+      'ok'
+    );
+  } else if (selectedDeviceName) {
     setDictationInputStatus(`Preferred microphone: ${selectedDeviceName}.`, 'ok');
   } else if (systemDefault) {
     setDictationInputStatus(`Using the system default microphone: ${systemDefault}.`, 'neutral');
@@ -1417,6 +1551,9 @@ function applyDictationHotkeyPayload(payload, { preservePending = false } = {})
       || ''
   ).trim();
 
+  fnTriggerSupported = payload?.fn_trigger_supported !== false;
+  fnListenerMode = String(payload?.fn_listener_mode || 'unavailable').trim() || 'unavailable';
+
   const rawDefault = String(
     payload?.default_trigger
       || payload?.default_dictation_trigger
@@ -1452,6 +1589,23 @@ function applyDictationHotkeyPayload(payload, { preservePending = false } = {})
   syncControls();
 }
 
+function applyPermissionsPayload(payload) {
+  if (!payload) return;
+  const microphone = String(payload.microphone || '').trim();
+  microphonePermissionBlocked = payload.can_record === false;
+  if (microphone === 'granted') {
+    microphonePermissionGranted = true;
+    if (!microphonePermissionStatus) microphonePermissionStatus = 'access granted.';
+  } else if (microphonePermissionBlocked) {
+    microphonePermissionGranted = false;
+    microphonePermissionStatus = microphone === 'restricted'
+      ? 'restricted by system policy. Check Privacy & Security > Microphone.'
+      : 'denied. Allow dicktaint in System Settings > Privacy & Security > Microphone, then relaunch.';
+  }
+  renderPermissionGuidance();
+  syncControls();
+}
+
 function applyFocusedFieldInsertPayload(payload) {
   const enabled = Boolean(
     payload?.focused_field_insert_enabled
@@ -1530,11 +1684,11 @@ async function maybeInsertTranscriptIntoFocusedField(chunk) {
   if (!tauriInvoke || !isFocusedMacDesktopMode() || !focusedFieldInsertEnabled) return;
   if (typeof document.hasFocus === 'function' && document.hasFocus()) return;
 
-  const trimmed = String(chunk || '').trim();
-  if (!trimmed) return;
+  const text = String(chunk || '').trimStart();
+  if (!text.trim()) return;
 
   try {
-    await tauriInvoke('insert_text_into_focused_field', { text: trimmed });
+    await tauriInvoke('insert_text_into_focused_field', { text });
   } catch (error) {
     const details = getErrorMessage(error);
     setFocusedFieldInsertStatus(`Insert failed: ${details}`, 'error');
@@ -1603,7 +1757,24 @@ async function clearDictationHotkey() {
   }
 }
 
-async function loadDictationOnboarding({ quietStatus = false } = {}) {
+async function reregisterDictationHotkey() {
+  const tauriInvoke = getTauriInvoke();
+  if (!tauriInvoke || !isNativeDesktopMode()) return;
+
+  try {
+    const payload = await tauriInvoke('reregister_hotkey');
+    applyDictationHotkeyPayload(payload);
+    setStatus('Dictation hotkey re-registered.', 'ok');
+  } catch (error) {
+    const details = getErrorMessage(error);
+    setDictationHotkeyStatus(details, 'error');
+    setStatus(details, 'error');
+  } finally {
+    syncControls();
+  }
+}
+
+async function loadDictationOnboarding({ quietStatus = false, applyHotkey = false } = {}) {
   // Web/mobile bypass desktop onboarding gates and run with browser/manual input paths.
   if (!isNativeDesktopMode()) {
     nativeDictationModelReady = true;
@@ -1725,7 +1896,11 @@ async function loadDictationOnboarding({ quietStatus = false } = {}) {
     }
     setDictationModelBusy('');
 
-    const onboarding = await tauriInvoke('get_dictation_onboarding');
+    // Only startup re-registers the saved trigger; later refreshes just read it so a settings
+    // screen mid-edit never grabs the shortcut globally.
+    const onboarding = await tauriInvoke(
+      applyHotkey ? 'apply_dictation_hotkey' : 'get_dictation_onboarding'
+    );
     currentOnboarding = onboarding;
     currentDeviceProfile = onboarding.device || null;
     preferredInputDevice = onboarding.preferred_input_device || null;
@@ -1755,12 +1930,19 @@ async function loadDictationOnboarding({ quietStatus = false } = {}) {
     renderInputDeviceOptions(onboarding.available_input_devices, onboarding.preferred_input_device);
     applyDictationHotkeyPayload(onboarding);
     applyFocusedFieldInsertPayload(onboarding);
+    applyPermissionsPayload(onboarding.permissions);
 
     if (openWhisperSetupBtn) {
       openWhisperSetupBtn.hidden = Boolean(onboarding.whisper_cli_available);
     }
 
-    if (!onboarding.whisper_cli_available && !onboarding.selected_model_exists) {
+    if (!onboarding.whisper_cli_available && onboarding.whisper_cli_is_placeholder) {
+      setDictationModelStatus(
+        `The bundled whisper-cli is a placeholder, not a real build. Replace ${onboarding.whisper_cli_placeholder_path || onboarding.whisper_cli_path} with a real whisper-cli binary (bun run whisper:sidecar) or set WHISPER_CLI_PATH, then click "Refresh Setup".`,
+        'error'
+      );
+      nativeDictationModelReady = false;
+    } else if (!onboarding.whisper_cli_available && !onboarding.selected_model_exists) {
       setDictationModelStatus(
         `whisper-cli is unavailable. Packaged builds should include it. In tauri:dev, click "Open CLI Setup (dev)", then "Refresh Setup". Checked: ${onboarding.whisper_cli_path}`,
         'error'
@@ -1792,6 +1974,7 @@ async function loadDictationOnboarding({ quietStatus = false } = {}) {
 
     syncFlowForSetupReadiness();
     syncControls();
+    if (nativeDictationModelReady) preloadNativeDictationModel();
     return onboarding;
   } catch (error) {
     nativeDictationModelReady = false;
@@ -1873,7 +2056,9 @@ async function installSelectedDictationModel() {
       setStatus(`Downloading ${modelDisplayName(selected)} model...`, 'working');
     }
 
-    await tauriInvoke('install_dictation_model', { model: selected.id });
+    const selection = isAlreadyInstalled
+      ? await tauriInvoke('select_dictation_model', { modelId: selected.id })
+      : await tauriInvoke('install_dictation_model', { model: selected.id });
     const onboarding = await loadDictationOnboarding({ quietStatus: true });
     onboardingAfterInstall = onboarding;
 
@@ -1890,7 +2075,9 @@ async function installSelectedDictationModel() {
     const selectedAfter = (onboarding.models || []).find((item) => item.id === onboarding.selected_model_id);
     setDictationModelBusy('');
     setUiMode('idle');
-    setStatus(`Ready: ${modelDisplayName(selectedAfter) || modelDisplayName(selected)} is active for local dictation.`, 'ok');
+    const downloadSource = String(selection?.download_source || '').trim();
+    const sourceNote = downloadSource ? ` Downloaded from ${downloadSource}.` : '';
+    setStatus(`Ready: ${modelDisplayName(selectedAfter) || modelDisplayName(selected)} is active for local dictation.${sourceNote}`, 'ok');
     if (setupScreenMode === 'onboarding') {
       setAppScreen('dictation');
     }
@@ -2010,7 +2197,30 @@ async function openWhisperSetupPage() {
   }
 }
 
-async function startNativeDesktopDictation(trigger = 'button', shouldRetryOnConflict = true) {
+async function requestMicrophonePermission() {
+  const tauriInvoke = getTauriInvoke();
+  if (!tauriInvoke || !isFocusedMacDesktopMode()) return;
+
+  try {
+    isRequestingMicrophonePermission = true;
+    syncControls();
+    setStatus('Requesting microphone access...', 'working');
+    const payload = await tauriInvoke('request_microphone_permission');
+    microphonePermissionGranted = Boolean(payload?.granted);
+    if (microphonePermissionGranted) microphonePermissionBlocked = false;
+    microphonePermissionStatus = String(payload?.status || '');
+    setStatus(microphonePermissionStatus, microphonePermissionGranted ? 'ok' : 'error');
+  } catch (error) {
+    const details = getErrorMessage(error);
+    setStatus(`Could not request microphone access: ${details}`, 'error');
+  } finally {
+    isRequestingMicrophonePermission = false;
+    renderPermissionGuidance();
+    syncControls();
+  }
+}
+
+async function startNativeDesktopDictation(trigger = 'button', shouldRetryOnConflict = true, { scratch = false } = {}) {
   const tauriInvoke = getTauriInvoke();
   if (!tauriInvoke || !isFocusedMacDesktopMode()) return;
   if (nativeStopRequestInFlight) {
@@ -2029,13 +2239,18 @@ async function startNativeDesktopDictation(trigger = 'button', shouldRetryOnConf
     isStartingDictation = true;
     nativeSessionIdToIgnore = null;
     rejectNextNativeAppend = false;
+    nativeScratchSessionActive = scratch;
     syncControls();
     setUiMode('loading');
     setStatus('Requesting microphone access...', 'working');
     await ensureMicrophoneAccess();
     setStatus('Opening microphone...', 'working');
     activeNativeSessionId = null;
-    await tauriInvoke('start_native_dictation');
+    if (scratch) {
+      await tauriInvoke('start_native_dictation', { scratch: true });
+    } else {
+      await tauriInvoke('start_native_dictation');
+    }
     isStartingDictation = false;
     setDictationState(true);
     setUiMode('listening');
@@ -2051,7 +2266,7 @@ async function startNativeDesktopDictation(trigger = 'button', shouldRetryOnConf
       setDictationState(false);
       try {
         await tauriInvoke('cancel_native_dictation');
-        return startNativeDesktopDictation(trigger, false);
+        return startNativeDesktopDictation(trigger, false, { scratch });
       } catch (recoverError) {
         isStartingDictation = false;
         setDictationState(false);
@@ -2064,11 +2279,41 @@ async function startNativeDesktopDictation(trigger = 'button', shouldRetryOnConf
     isStartingDictation = false;
     setDictationState(false);
     activeNativeSessionId = null;
+    nativeScratchSessionActive = false;
+    if (SETUP_ERROR_CODES.has(getErrorCode(error))) {
+      nativeDictationModelReady = false;
+      setUiMode('idle');
+      setSetupScreenMode('onboarding');
+      setAppScreen('onboarding');
+      setStatus(details, 'error');
+      syncControls();
+      return;
+    }
     setUiMode('error');
     setStatus(`Could not start dictation: ${details}`, 'error');
   }
 }
 
+// Retry notice and input warnings from the backend `idle` event ride along on the completion status.
+function withNativeCompletionNote(message) {
+  const note = nativeCompletionNote;
+  nativeCompletionNote = '';
+  return note ? `${message} ${note}` : message;
+}
+
+async function toggleNativeDictationPause() {
+  const tauriInvoke = getTauriInvoke();
+  if (!tauriInvoke || !isDictating || nativeStopRequestInFlight) return;
+
+  const command = isNativeDictationPaused ? 'resume_native_dictation' : 'pause_native_dictation';
+  try {
+    await tauriInvoke(command);
+  } catch (error) {
+    const details = getErrorMessage(error);
+    setStatus(`Could not ${isNativeDictationPaused ? 'resume' : 'pause'} dictation: ${details}`, 'error');
+  }
+}
+
 async function stopNativeDesktopDictation(trigger = 'button') {
   const tauriInvoke = getTauriInvoke();
   if (!tauriInvoke || (!isDictating && !isStartingDictation)) return;
@@ -2076,17 +2321,35 @@ async function stopNativeDesktopDictation(trigger = 'button') {
 
   nativeStopRequestInFlight = true;
   const sessionId = activeNativeSessionId;
+  const scratch = nativeScratchSessionActive;
   try {
     setUiMode('loading');
     setStatus('Transcribing captured audio...', 'working');
     const transcript = await tauriInvoke('stop_native_dictation');
+    if (nativeTooShortHint) {
+      setUiMode('idle');
+      setStatus(nativeTooShortHint, 'neutral');
+      nativeTooShortHint = '';
+      return;
+    }
+    if (nativeCommandResult) {
+      setUiMode('idle');
+      setStatus(nativeCommandResult, 'ok');
+      nativeCommandResult = '';
+      return;
+    }
+    if (scratch) {
+      setUiMode('idle');
+      setStatus(withNativeCompletionNote(`Scratch take (not saved): ${String(transcript || '').trim()}`), 'ok');
+      return;
+    }
     const didAppendTranscript = appendTranscriptChunk(transcript, {
       source: 'native',
       nativeSessionId: sessionId
     });
     setUiMode('idle');
     if (didAppendTranscript) {
-      setStatus(completedStatusForTrigger(trigger), 'ok');
+      setStatus(withNativeCompletionNote(completedStatusForTrigger(trigger)), 'ok');
     } else {
       setStatus('No new dictation content to save.', 'neutral');
     }
@@ -2102,6 +2365,7 @@ async function stopNativeDesktopDictation(trigger = 'button') {
     setStatus(`Could not stop dictation: ${details}`, 'error');
   } finally {
     nativeStopRequestInFlight = false;
+    nativeScratchSessionActive = false;
     isStartingDictation = false;
     setDictationState(false);
     void maybeStartQueuedNativeDictation();
@@ -2166,6 +2430,7 @@ function applyNativeFnHoldState(pressed) {
 function handleNativeHoldKeydown(event) {
   if (!isFocusedMacDesktopMode()) return;
   if (dictationTriggerMode !== 'focused-window-hold') return;
+  if (fnListenerMode === 'local') return;
   if (event.repeat) return;
   if (event.metaKey || event.ctrlKey || event.altKey || event.shiftKey) return;
   if (!isNativeHoldHotkeyEvent(event)) return;
@@ -2179,6 +2444,7 @@ function handleNativeHoldKeydown(event) {
 function handleNativeHoldKeyup(event) {
   if (!isFocusedMacDesktopMode()) return;
   if (dictationTriggerMode !== 'focused-window-hold') return;
+  if (fnListenerMode === 'local') return;
   if (!isNativeHoldHotkeyEvent(event)) return;
   if (activeHotkeySpec?.ok && activeHotkeySpec.key !== 'Fn') return;
 
@@ -2249,9 +2515,27 @@ function maybeCaptureDictationHotkeyEvent(event) {
   isCapturingDictationHotkey = false;
   setDictationHotkeyStatus(`Pending hotkey: ${parsed.display}. Click "Save Hotkey" to apply.`, 'neutral');
   syncControls();
+  void checkPendingDictationHotkeyAvailability(parsed.display);
   return true;
 }
 
+async function checkPendingDictationHotkeyAvailability(display) {
+  const tauriInvoke = getTauriInvoke();
+  if (!tauriInvoke || !isNativeDesktopMode()) return;
+
+  try {
+    const result = await tauriInvoke('validate_dictation_trigger', { trigger: display });
+    // A newer capture or a save may have replaced this combo while the probe ran.
+    if (pendingDictationHotkey !== display || isCapturingDictationHotkey) return;
+    if (!result?.valid || !result?.available) {
+      const details = result?.error || 'it is already in use';
+      setDictationHotkeyStatus(`Pending hotkey ${display} can't be used: ${details}`, 'error');
+    }
+  } catch {
+    // Saving still reports registration failures, so a failed probe keeps the pending message.
+  }
+}
+
 function handleDictationHotkeyEvent(event) {
   if (!isNativeDesktopMode()) return;
 
@@ -2270,12 +2554,29 @@ function handleNativeDictationStatePayload(payload) {
   if (s === 'listening') {
     activeNativeSessionId = payloadSessionId || activeNativeSessionId;
     isStartingDictation = false;
+    isNativeDictationPaused = false;
+    nativePartialTranscript = '';
     setDictationState(true);
     setUiMode('listening');
     setStatus('Listening\u2026 click Stop to transcribe.', 'live');
     return;
   }
 
+  if (s === 'paused') {
+    if (!sessionMatchesCurrent) return;
+    activeNativeSessionId = payloadSessionId || activeNativeSessionId;
+    isStartingDictation = false;
+    isNativeDictationPaused = true;
+    setDictationState(true);
+    setUiMode('paused');
+    setStatus('Paused. Click Resume to keep dictating or Stop to transcribe.', 'neutral');
+    return;
+  }
+
+  if (sessionMatchesCurrent) {
+    isNativeDictationPaused = false;
+    if (s !== 'processing') nativePartialTranscript = '';
+  }
   if (s === 'processing') {
     if (payloadSessionId && !activeNativeSessionId) {
       activeNativeSessionId = payloadSessionId;
@@ -2288,7 +2589,28 @@ function handleNativeDictationStatePayload(payload) {
 
   if (s === 'idle') {
     const transcriptSessionId = payloadSessionId || activeNativeSessionId;
-    const didAppendTranscript = nativeStopRequestInFlight
+    const commandStatus = NATIVE_COMMAND_STATUS[String(payload?.command || '')];
+    if (commandStatus && sessionMatchesCurrent) {
+      isStartingDictation = false;
+      setDictationState(false);
+      setUiMode('idle');
+      activeNativeSessionId = null;
+      nativeScratchSessionActive = false;
+      // The stop request may still be waiting on its (empty) result; let it report the command.
+      if (nativeStopRequestInFlight) {
+        nativeCommandResult = commandStatus;
+      }
+      setStatus(commandStatus, 'ok');
+      return;
+    }
+    const isScratchTake = Boolean(payload?.scratch);
+    if (sessionMatchesCurrent) {
+      nativeCompletionNote = [
+        payload?.retried ? 'Re-checked for accuracy.' : '',
+        String(payload?.warning || '').trim()
+      ].filter(Boolean).join(' ');
+    }
+    const didAppendTranscript = nativeStopRequestInFlight || isScratchTake
       ? false
       : appendTranscriptChunk(payload?.transcript, {
         source: 'native-event',
@@ -2303,8 +2625,12 @@ function handleNativeDictationStatePayload(payload) {
     if (sessionMatchesCurrent) {
       activeNativeSessionId = null;
     }
+    if (isScratchTake && sessionMatchesCurrent && !nativeStopRequestInFlight) {
+      nativeScratchSessionActive = false;
+      setStatus(withNativeCompletionNote(`Scratch take (not saved): ${String(payload?.transcript || '').trim()}`), 'ok');
+    }
     if (didAppendTranscript && sessionMatchesCurrent) {
-      setStatus('Dictation captured and transcribed.', 'ok');
+      setStatus(withNativeCompletionNote('Dictation captured and transcribed.'), 'ok');
     }
     return;
   }
@@ -2334,6 +2660,114 @@ function handleNativeDictationAudioLevelPayload(payload) {
   updateDictationWaveform(level, bars, 'listening');
 }
 
+function handleNativeNeedsOnboardingPayload(payload) {
+  const reason = String(payload?.reason || '').trim();
+  const message = String(payload?.error || '').trim()
+    || NEEDS_ONBOARDING_MESSAGES[reason]
+    || 'Finish setup to start dictating.';
+  nativeDictationModelReady = false;
+  setSetupScreenMode('onboarding');
+  setAppScreen('onboarding');
+  setStatus(message, 'error');
+  syncControls();
+}
+
+function handleNativeDictationProgressPayload(payload) {
+  const payloadSessionId = normalizeNativeSessionId(payload?.session_id);
+  // Progress only arrives while whisper runs; once the session is settled late events are stale.
+  if (!activeNativeSessionId || payloadSessionId !== activeNativeSessionId) return;
+
+  const percent = Number(payload?.percent);
+  if (!Number.isFinite(percent)) return;
+  const clamped = Math.min(100, Math.max(0, Math.round(percent)));
+  setStatus(withNativePartialTranscript(`Transcribing captured audio... ${clamped}%`), 'working');
+}
+
+// Slow is a warning, not a failure: whisper keeps running and Clear cancels the session.
+function handleNativeDictationSlowPayload(payload) {
+  const payloadSessionId = normalizeNativeSessionId(payload?.session_id);
+  if (!activeNativeSessionId || payloadSessionId !== activeNativeSessionId) return;
+
+  const elapsedSeconds = Math.round(Number(payload?.elapsed_ms) / 1000);
+  const timeoutSeconds = Math.round(Number(payload?.timeout_ms) / 1000);
+  const limit = Number.isFinite(timeoutSeconds) && timeoutSeconds > 0
+    ? ` It stops on its own after ${timeoutSeconds}s.`
+    : '';
+  setStatus(
+    `Still transcribing after ${elapsedSeconds}s.${limit} Click Clear to cancel.`,
+    'working'
+  );
+}
+
+// A capture under `min_transcribe_seconds` is a nudge, not a failure; the `idle` that follows
+// carries no transcript, so this status stays up.
+function handleNativeDictationTooShortPayload(payload) {
+  const payloadSessionId = normalizeNativeSessionId(payload?.session_id);
+  if (activeNativeSessionId && payloadSessionId !== activeNativeSessionId) return;
+
+  const hint = String(payload?.message || '').trim() || 'Too short to transcribe. Hold a bit longer and try again.';
+  if (nativeStopRequestInFlight) {
+    nativeTooShortHint = hint;
+  }
+  setStatus(hint, 'neutral');
+}
+
+function withNativePartialTranscript(message) {
+  return nativePartialTranscript ? `${message} So far: ${nativePartialTranscript}` : message;
+}
+
+// Chunked transcription reports cumulative text; the final `idle` transcript replaces it.
+function handleNativeDictationPartialPayload(payload) {
+  const payloadSessionId = normalizeNativeSessionId(payload?.session_id);
+  if (!activeNativeSessionId || payloadSessionId !== activeNativeSessionId) return;
+
+  const text = String(payload?.text || '').trim();
+  if (!text) return;
+  nativePartialTranscript = text;
+  const done = Number(payload?.chunks_done);
+  const count = Number(payload?.chunk_count);
+  const step = Number.isFinite(done) && Number.isFinite(count) && count > 0 ? ` (${done}/${count})` : '';
+  setStatus(withNativePartialTranscript(`Transcribing captured audio...${step}`), 'working');
+}
+
+function handleModelDownloadRetryPayload(payload) {
+  const attempt = Number(payload?.attempt);
+  const max = Number(payload?.max_attempts);
+  const step = Number.isFinite(attempt) && Number.isFinite(max) ? ` (${attempt}/${max})` : '';
+  const message = `Model download interrupted; retrying${step}...`;
+  setDictationModelBusy(message);
+  setStatus(message, 'working');
+}
+
+async function transcribeDroppedAudioFile(path) {
+  const tauriInvoke = getTauriInvoke();
+  const filePath = String(path || '').trim();
+  if (!tauriInvoke || !filePath) return;
+  if (isDictating || isStartingDictation || fileTranscriptionInFlight) {
+    setStatus('Finish the current dictation before transcribing a file.', 'error');
+    return;
+  }
+
+  const fileName = filePath.split(/[\\/]/).pop() || filePath;
+  fileTranscriptionInFlight = true;
+  try {
+    setStatus(`Transcribing ${fileName}...`, 'working');
+    const transcript = String(await tauriInvoke('transcribe_file', { path: filePath }) || '').trim();
+    if (!transcript) {
+      setStatus(`No speech found in ${fileName}.`, 'neutral');
+      return;
+    }
+    // File transcripts go to the draft only; pasting into whatever app is focused would surprise.
+    appendToDraftTranscript(transcript);
+    pushDictationHistory(transcript, 'file');
+    setStatus(`Transcribed ${fileName}.`, 'ok');
+  } catch (error) {
+    setStatus(`Could not transcribe ${fileName}: ${getErrorMessage(error)}`, 'error');
+  } finally {
+    fileTranscriptionInFlight = false;
+  }
+}
+
 function initDictation() {
   const tauriEventApi = window.__TAURI__?.event || null;
   if (isNativeDesktopMode() && tauriEventApi?.listen) {
@@ -2363,6 +2797,61 @@ function initDictation() {
     }).catch(err => {
       console.error('Failed to register DICTATION_AUDIO_LEVEL_EVENT listener', err);
     });
+
+    tauriEventApi.listen(DICTATION_PROGRESS_EVENT, ({ payload }) => {
+      handleNativeDictationProgressPayload(payload);
+    }).catch(err => {
+      console.error('Failed to register DICTATION_PROGRESS_EVENT listener', err);
+    });
+
+    tauriEventApi.listen(DICTATION_SLOW_EVENT, ({ payload }) => {
+      handleNativeDictationSlowPayload(payload);
+    }).catch(err => {
+      console.error('Failed to register DICTATION_SLOW_EVENT listener', err);
+    });
+
+    tauriEventApi.listen(DICTATION_TOO_SHORT_EVENT, ({ payload }) => {
+      handleNativeDictationTooShortPayload(payload);
+    }).catch(err => {
+      console.error('Failed to register DICTATION_TOO_SHORT_EVENT listener', err);
+    });
+
+    tauriEventApi.listen(FILE_DROP_EVENT, ({ payload }) => {
+      const [path] = Array.isArray(payload?.paths) ? payload.paths : [];
+      void transcribeDroppedAudioFile(path);
+    }).catch(err => {
+      console.error('Failed to register FILE_DROP_EVENT listener', err);
+    });
+
+    tauriEventApi.listen(DICTATION_PARTIAL_EVENT, ({ payload }) => {
+      handleNativeDictationPartialPayload(payload);
+    }).catch(err => {
+      console.error('Failed to register DICTATION_PARTIAL_EVENT listener', err);
+    });
+
+    tauriEventApi.listen(DICTATION_NEEDS_ONBOARDING_EVENT, ({ payload }) => {
+      handleNativeNeedsOnboardingPayload(payload);
+    }).catch(err => {
+      console.error('Failed to register DICTATION_NEEDS_ONBOARDING_EVENT listener', err);
+    });
+
+    tauriEventApi.listen(DICTATION_OUTPUT_MODE_EVENT, ({ payload }) => {
+      applyFocusedFieldInsertPayload(payload);
+      setStatus(
+        payload?.mode === 'paste'
+          ? 'Output mode: paste into the focused app.'
+          : 'Output mode: show text only.',
+        'ok'
+      );
+    }).catch(err => {
+      console.error('Failed to register DICTATION_OUTPUT_MODE_EVENT listener', err);
+    });
+
+    tauriEventApi.listen(MODEL_DOWNLOAD_RETRY_EVENT, ({ payload }) => {
+      handleModelDownloadRetryPayload(payload);
+    }).catch(err => {
+      console.error('Failed to register MODEL_DOWNLOAD_RETRY_EVENT listener', err);
+    });
   }
 
   document.addEventListener('keydown', handleDictationHotkeyEvent);
@@ -2455,6 +2944,9 @@ function initDictation() {
     if (openWhisperSetupBtn) {
       openWhisperSetupBtn.addEventListener('click', openWhisperSetupPage);
     }
+    if (requestMicrophonePermissionBtn) {
+      requestMicrophonePermissionBtn.addEventListener('click', requestMicrophonePermission);
+    }
     if (retryWhisperCheckBtn) {
       retryWhisperCheckBtn.addEventListener('click', () => {
         loadDictationOnboarding();
@@ -2506,6 +2998,9 @@ function initDictation() {
     if (clearDictationHotkeyBtn) {
       clearDictationHotkeyBtn.addEventListener('click', clearDictationHotkey);
     }
+    if (reregisterDictationHotkeyBtn) {
+      reregisterDictationHotkeyBtn.addEventListener('click', reregisterDictationHotkey);
+    }
     if (focusedFieldInsertToggleEl) {
       focusedFieldInsertToggleEl.addEventListener('change', (event) => {
         const next = Boolean(event?.currentTarget?.checked);
@@ -2552,6 +3047,18 @@ function initDictation() {
       void stopNativeDesktopDictation('button');
     });
 
+    if (pauseDictationBtn) {
+      pauseDictationBtn.addEventListener('click', () => {
+        void toggleNativeDictationPause();
+      });
+    }
+
+    if (scratchDictationBtn) {
+      scratchDictationBtn.addEventListener('click', () => {
+        startNativeDesktopDictation('button', true, { scratch: true });
+      });
+    }
+
     window.addEventListener('keydown', handleNativeHoldKeydown, true);
     window.addEventListener('keyup', handleNativeHoldKeyup, true);
     syncControls();
@@ -2663,7 +3170,7 @@ function initDictation() {
 }
 
 async function initApp() {
-  await loadDictationOnboarding();
+  await loadDictationOnboarding({ applyHotkey: true });
 }
 
 function getDictationTestState() {
@@ -2679,8 +3186,11 @@ function getDictationTestState() {
     activeNativeSessionId,
     isDictating,
     isStartingDictation,
+    isNativeDictationPaused,
+    microphonePermissionBlocked,
     dictationTriggerMode,
     dictationTriggerStatus,
+    fnListenerMode,
     savedDictationHotkey,
     pendingDictationHotkey
   };
@@ -2693,6 +3203,11 @@ function resetDictationStateForTests() {
   isStartingDictation = false;
   shouldKeepDictating = false;
   nativeStopRequestInFlight = false;
+  nativeScratchSessionActive = false;
+  isNativeDictationPaused = false;
+  nativePartialTranscript = '';
+  nativeCompletionNote = '';
+  microphonePermissionBlocked = false;
   pendingNativeStartAfterStop = false;
   pendingNativeStartTrigger = null;
   activeNativeSessionId = null;
@@ -2703,11 +3218,13 @@ function resetDictationStateForTests() {
   dictationTriggerMode = 'disabled';
   dictationTriggerStatus = 'Hotkey disabled.';
   dictationTriggerPermissionHint = '';
+  fnListenerMode = 'unavailable';
   focusedFieldInsertPermissionGranted = false;
   focusedFieldInsertPermissionStatus = 'Focused-field insertion is disabled.';
   savedDictationHotkey = null;
   pendingDictationHotkey = '';
   activeHotkeySpec = null;
+  fnTriggerSupported = true;
   currentDeviceProfile = { os: 'macos', architecture: 'aarch64' };
   liveAudioLevel = 0;
   liveAudioBars = defaultLiveAudioBars();
@@ -2728,6 +3245,12 @@ if (typeof globalThis !== 'undefined' && globalThis.__DICKTAINT_EXPOSE_TEST_API_
     summarizeHotkeyPillStatus,
     handleNativeDictationStatePayload,
     handleNativeDictationAudioLevelPayload,
+    handleNativeDictationProgressPayload,
+    handleNativeDictationPartialPayload,
+    handleNativeNeedsOnboardingPayload,
+    applyPermissionsPayload,
+    getErrorCode,
+    getErrorMessage,
     getState: getDictationTestState,
     resetState: resetDictationStateForTests,
     setNativeFlags(next = {}) {
@@ -0,0 +1,34 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+TARGET="$ROOT_DIR/src-tauri/resources/benchmark-jfk.wav"
+# Pinned to a release tag so the clip always matches BENCHMARK_EXPECTED_TEXT.
+CLIP_URL="https://raw.githubusercontent.com/ggml-org/whisper.cpp/v1.7.4/samples/jfk.wav"
+SOURCE="${BENCHMARK_CLIP_SOURCE:-}"
+
+TMP_FILE="$(mktemp)"
+trap 'rm -f "$TMP_FILE"' EXIT
+
+if [[ -n "$SOURCE" ]]; then
+  cp "$SOURCE" "$TMP_FILE"
+elif [[ -f "/opt/homebrew/opt/whisper-cpp/share/whisper-cpp/jfk.wav" ]]; then
+  cp "/opt/homebrew/opt/whisper-cpp/share/whisper-cpp/jfk.wav" "$TMP_FILE"
+else
+  curl -L --fail -o "$TMP_FILE" "$CLIP_URL"
+fi
+
+if [[ "$(head -c 4 "$TMP_FILE")" != "RIFF" ]]; then
+  echo "Benchmark clip is not a WAV file." >&2
+  exit 1
+fi
+# 11.0s of 16-bit 16 kHz mono is 352000 bytes of samples plus the header.
+SIZE="$(wc -c <"$TMP_FILE" | tr -d ' ')"
+if (( SIZE < 336000 || SIZE > 368000 )); then
+  echo "Benchmark clip is $SIZE bytes, not the ~11s jfk.wav reference." >&2
+  exit 1
+fi
+
+mkdir -p "$(dirname "$TARGET")"
+cp "$TMP_FILE" "$TARGET"
+echo "Installed benchmark clip: $TARGET"
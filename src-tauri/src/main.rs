@@ -5,13 +5,18 @@ use cpal::{SampleFormat, Stream};
 #[cfg(target_os = "macos")]
 use objc2::rc::Retained;
 #[cfg(target_os = "macos")]
-use objc2_app_kit::{NSPasteboard, NSPasteboardTypeString};
+use objc2::runtime::AnyObject;
+#[cfg(target_os = "macos")]
+use objc2_app_kit::{
+    NSApplicationActivationOptions, NSEvent, NSEventMask, NSPasteboard, NSPasteboardTypeString,
+    NSRunningApplication, NSWorkspace,
+};
 #[cfg(target_os = "macos")]
 use objc2_av_foundation::{AVAuthorizationStatus, AVCaptureDevice, AVMediaTypeAudio};
 #[cfg(target_os = "macos")]
 use objc2_foundation::NSString;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 #[cfg(target_os = "macos")]
 use std::ffi::c_void;
 use std::fs;
@@ -20,10 +25,12 @@ use std::io::Write;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
+#[cfg(target_os = "macos")]
+use std::ptr::NonNull;
 use std::str::FromStr;
 #[cfg(target_os = "macos")]
-use std::sync::atomic::{AtomicBool, AtomicPtr};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::AtomicPtr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
@@ -35,32 +42,399 @@ const WHISPER_SAMPLE_RATE: u32 = 16_000;
 const APP_SETTINGS_DIR: &str = ".dicktaint";
 const APP_SETTINGS_FILE: &str = "dictation-settings.json";
 const APP_MODELS_DIR: &str = "whisper-models";
+const APP_RECORDINGS_DIR: &str = "recordings";
+// Lives directly in the Tauri app data dir, outside `.dicktaint`, so it stays put when storage
+// moves and can be read before the settings file is located.
+const STORAGE_LOCATION_FILE: &str = "storage-location.json";
+// Written next to the settings file by `get_debug_report(save: true)`.
+const DEBUG_REPORT_FILE: &str = "debug-report.json";
+const DEFAULT_MAX_RECORDINGS: u32 = 50;
+const MAX_RECORDINGS_RANGE: std::ops::RangeInclusive<u32> = 1..=10_000;
+const DEFAULT_MAX_RECORDINGS_TOTAL_MB: u64 = 500;
+// Opus at voice bitrates is ~0.2 MB/min versus ~1.9 MB/min for 16 kHz 16-bit WAV.
+const RECORDING_OPUS_BITRATE: &str = "24k";
+const MAX_RECORDINGS_TOTAL_MB_RANGE: std::ops::RangeInclusive<u64> = 1..=100_000;
 const DEFAULT_WHISPER_CLI_PATH: &str = "whisper-cli";
+const DEFAULT_MEMORY_GUARD_MARGIN_MB: u64 = 1024;
+const MEMORY_GUARD_MARGIN_MB_RANGE: std::ops::RangeInclusive<u64> = 0..=65_536;
+// whisper.cpp keeps roughly the model file resident plus compute buffers.
+const MODEL_RUNTIME_OVERHEAD_MB: u64 = 512;
+// Catalog sizes are rounded, but a real model file never lands below half its estimate; anything
+// smaller is almost always an interrupted or truncated download.
+const MODEL_SIZE_SUSPECT_FRACTION: f64 = 0.5;
+// Audio seconds a tiny-model decode gets through per wall-clock second per CPU core; a rough CPU
+// baseline, so GPU-accelerated machines run faster than estimated.
+const REAL_TIME_FACTOR_PER_CORE: f32 = 5.0;
+// whisper.cpp scales poorly past this many threads, so extra cores add little.
+const REAL_TIME_FACTOR_MAX_CORES: usize = 8;
+// A recommended model should at least keep up with speech.
+const MIN_RECOMMENDED_REAL_TIME_FACTOR: f32 = 1.0;
+// On a CPU without full vector support, a model estimated slower than this is not marked runnable
+// even when it fits in RAM.
+const MIN_RUNNABLE_REAL_TIME_FACTOR: f32 = 0.25;
+// Free space beyond the model size a download should leave, so the disk check warns before the
+// drive is left full.
+const MODEL_DOWNLOAD_DISK_HEADROOM_BYTES: u64 = 512 * 1024 * 1024;
+// The tiny and base tiers; `recommendation_bias: speed` recommends from these when one runs.
+const SPEED_BIAS_MAX_RELATIVE_COMPUTE: f32 = 2.0;
+const HYPOTHETICAL_RAM_GB_RANGE: std::ops::RangeInclusive<u64> = 1..=1024;
+// Bump when a LocalSettings field changes shape, and teach migrate_settings how
+// to upgrade the previous version. Files without a version are v0.
+const SETTINGS_SCHEMA_VERSION: u32 = 1;
 #[cfg(target_os = "macos")]
 const DEFAULT_DICTATION_TRIGGER: &str = "Fn";
 #[cfg(not(target_os = "macos"))]
 const DEFAULT_DICTATION_TRIGGER: &str = "CmdOrCtrl+Shift+D";
+// Only macOS has a listener for the bare Fn / Globe key (CGEventTap); other
+// platforms reject it up front instead of saving a trigger that never fires.
+const FN_TRIGGER_SUPPORTED: bool = cfg!(target_os = "macos");
+// Counted in characters; the byte bound (4 bytes per UTF-8 char) only stops pathological input
+// before it is walked.
 const MAX_DICTATION_TRIGGER_LENGTH: usize = 64;
+const MAX_DICTATION_TRIGGER_BYTES: usize = MAX_DICTATION_TRIGGER_LENGTH * 4;
+// Canonical modifier names in the order normalized triggers list them.
+const TRIGGER_MODIFIER_ORDER: [&str; 6] = ["CmdOrCtrl", "Cmd", "Ctrl", "Alt", "Shift", "Super"];
+const TRIGGER_FUNCTION_KEY_RANGE: std::ops::RangeInclusive<u8> = 1..=24;
 const DICTATION_STATE_EVENT: &str = "dictation:state-changed";
 const DICTATION_AUDIO_LEVEL_EVENT: &str = "dictation:audio-level";
+const DICTATION_PROGRESS_EVENT: &str = "dictation:progress";
+const DICTATION_PARTIAL_EVENT: &str = "dictation:partial";
+const DICTATION_NEEDS_ONBOARDING_EVENT: &str = "dictation:needs-onboarding";
+const DICTATION_DOUBLE_TAP_EVENT: &str = "dictation:double-tap";
+const MODEL_DOWNLOAD_RETRY_EVENT: &str = "model:download-retry";
+const DICTATION_OUTPUT_MODE_EVENT: &str = "dictation:output-mode";
+const DICTATION_SLOW_EVENT: &str = "dictation:slow";
+const DICTATION_TOO_SHORT_EVENT: &str = "dictation:too-short";
+const DEFAULT_DOUBLE_TAP_MS: u64 = 350;
+const DOUBLE_TAP_MS_RANGE: std::ops::RangeInclusive<u64> = 150..=1_000;
+// Hold triggers released sooner than this cancel instead of transcribing; 0 disables the gate.
+const DEFAULT_MIN_HOLD_MS: u64 = 150;
+// Captures shorter than this go back to idle with a `dictation:too-short` hint instead of being
+// sent to whisper; the floor keeps quick "yes"/"no" possible without passing bare clicks through.
+const DEFAULT_MIN_TRANSCRIBE_SECONDS: f32 = 0.30;
+const MIN_TRANSCRIBE_SECONDS_RANGE: std::ops::RangeInclusive<f32> = 0.1..=5.0;
+const MIN_HOLD_MS_RANGE: std::ops::RangeInclusive<u64> = 0..=1_000;
+// Wait between re-activating the recording's target app and sending the paste keystrokes.
+const DEFAULT_INSERT_DELAY_MS: u64 = 120;
+const INSERT_DELAY_MS_RANGE: std::ops::RangeInclusive<u64> = 0..=2_000;
+// Pasted by `test_insert` so the user can see where auto-paste lands.
+const INSERT_TEST_MARKER: &str = "dicktaint insert test";
 const PILL_STATUS_EVENT: &str = "dicktaint://pill-status";
 const WHISPER_CPP_SETUP_URL: &str = "https://github.com/ggml-org/whisper.cpp#quick-start";
 const START_HIDDEN_ENV: &str = "DICKTAINT_START_HIDDEN";
 const PILL_WINDOW_LABEL_PREFIX: &str = "pill";
+#[cfg(target_os = "macos")]
 const PILL_WINDOW_BASE_WIDTH: f64 = 108.0;
+#[cfg(target_os = "macos")]
 const PILL_WINDOW_MIN_WIDTH: f64 = 92.0;
+#[cfg(target_os = "macos")]
 const PILL_WINDOW_HEIGHT: f64 = 26.0;
-const PILL_WINDOW_BOTTOM_MARGIN: i32 = 14;
+const DEFAULT_PILL_WINDOW_MARGIN: u32 = 14;
+const PILL_WINDOW_MARGIN_RANGE: std::ops::RangeInclusive<u32> = 0..=400;
 const MAX_PILL_WINDOWS: usize = 6;
+// Move/resize events arrive per frame while dragging; only the settled geometry is written.
+const WINDOW_GEOMETRY_SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+// A restored window must keep at least this much of itself on a connected display.
+const WINDOW_GEOMETRY_MIN_VISIBLE_PX: i32 = 100;
+// Tauri exposes no display-change event, so the overlay polls monitor geometry.
+#[cfg(target_os = "macos")]
+const PILL_MONITOR_POLL_INTERVAL: Duration = Duration::from_secs(2);
 const MIN_TRANSCRIPTION_AUDIO_PEAK: f32 = 0.008;
+const CLIPPING_SAMPLE_LEVEL: f32 = 0.99;
+// A handful of full-scale samples is a plosive; one in a thousand is a gain problem.
+const CLIPPING_WARNING_FRACTION: f32 = 0.001;
 const MIN_TRANSCRIPTION_AUDIO_RMS: f32 = 0.0008;
 const TARGET_TRANSCRIPTION_AUDIO_PEAK: f32 = 0.85;
+const DEFAULT_TARGET_TRANSCRIPTION_AUDIO_RMS: f32 = 0.1;
+const NORMALIZATION_TARGET_LEVEL_RANGE: std::ops::RangeInclusive<f32> = 0.01..=0.95;
+const NORMALIZATION_TARGET_RMS_RANGE: std::ops::RangeInclusive<f32> = 0.01..=0.5;
+// RMS gain can push transients past full scale; above this level samples are soft-limited.
+const RMS_LIMITER_THRESHOLD: f32 = 0.8;
 const MAX_TRANSCRIPTION_AUDIO_GAIN: f32 = 16.0;
+// Edge trimming classifies 20ms frames by short-term energy and zero-crossing rate.
+const VAD_FRAME_MS: u32 = 20;
+const SILENCE_TRIM_PAD_MS: u32 = 200;
+// Linear fade at both ends of the prepared audio so a click at capture start/stop is not decoded.
+const EDGE_FADE_MS: u32 = 10;
+const DEFAULT_CAPTURE_PREVIEW_SECONDS: f32 = 3.0;
+const CAPTURE_PREVIEW_SECONDS_RANGE: std::ops::RangeInclusive<f32> = 1.0..=10.0;
+const DEFAULT_MIC_PLAYBACK_SECONDS: f32 = 3.0;
+const MIC_PLAYBACK_SECONDS_RANGE: std::ops::RangeInclusive<f32> = 1.0..=10.0;
+// Extra wait after the last frame so the device drains its buffer before the stream is dropped.
+const MIC_PLAYBACK_TAIL: Duration = Duration::from_millis(200);
+// Sound cues are rendered at this rate and resampled to the output device like the mic playback.
+const SOUND_CUE_SAMPLE_RATE: u32 = 48_000;
+const SOUND_CUE_LEVEL: f32 = 0.18;
+// Per-tone fade in/out so the cue does not click.
+const SOUND_CUE_FADE_MS: u32 = 6;
+// `transcribe_file` reads `.wav` directly and hands these to ffmpeg.
+const FFMPEG_AUDIO_EXTENSIONS: [&str; 16] = [
+    "mp3", "m4a", "aac", "mp4", "mov", "mkv", "webm", "ogg", "oga", "opus", "flac", "aif", "aiff",
+    "caf", "wma", "amr",
+];
+const FFMPEG_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const FFMPEG_CONVERT_TIMEOUT: Duration = Duration::from_secs(600);
+// Voiced speech crosses zero far less often than hiss or fricatives (~0.5 for white noise).
+const VAD_VOICED_MAX_ZCR: f32 = 0.25;
+// Frames quieter than this fraction of the loudest frame are background, not speech.
+const VAD_RELATIVE_SPEECH_LEVEL: f32 = 0.05;
+// Noisy (high-ZCR) frames only count as speech when this far above the noise floor.
+const VAD_NOISE_FLOOR_RATIO: f32 = 3.0;
+const VAD_NOISE_FLOOR_PERCENTILE: f32 = 0.1;
+// Optional expander for fan hum and keyboard clicks between words: VAD frames under the
+// threshold RMS are attenuated rather than cut, so timing and word tails survive.
+const DEFAULT_NOISE_GATE_THRESHOLD: f32 = 0.01;
+const NOISE_GATE_THRESHOLD_RANGE: std::ops::RangeInclusive<f32> = 0.001..=0.2;
+const NOISE_GATE_ATTENUATION: f32 = 0.1;
+// Loud runs shorter than this are clicks, not syllables, and stay gated.
+const NOISE_GATE_MIN_OPEN_MS: u32 = 60;
+// The gate opens this early and closes this late around speech so onsets and tails are kept.
+const NOISE_GATE_HOLD_MS: u32 = 80;
+// Prepared audio longer than `chunk_seconds` is transcribed in pieces cut inside silences, each
+// at least half the chunk length, so whisper never sees a multi-minute WAV.
+const DEFAULT_CHUNK_SECONDS: u32 = 120;
+const CHUNK_SECONDS_RANGE: std::ops::RangeInclusive<u32> = 30..=900;
 const LIVE_AUDIO_BAR_COUNT: usize = 12;
 const LIVE_AUDIO_EMIT_INTERVAL_MS: u64 = 45;
 const INPUT_STREAM_PROBE_TIMEOUT_MS: u64 = 1_500;
 const INPUT_STREAM_PROBE_POLL_INTERVAL_MS: u64 = 40;
 const INPUT_STREAM_PROBE_MIN_DURATION_MS: u32 = 120;
+// Device-list names for WASAPI loopback sources carry this prefix so they can't collide with (or
+// fall back to) a microphone of the same name.
+const LOOPBACK_DEVICE_PREFIX: &str = "loopback:";
+// Virtual devices that carry other apps' output; on macOS these are the only route to system audio.
+const LOOPBACK_DEVICE_NAME_HINTS: &[&str] = &[
+    "blackhole",
+    "soundflower",
+    "loopback audio",
+    "monitor of",
+    "stereo mix",
+    "cable output",
+];
+const MICROPHONE_PERMISSION_PROBE_MS: u64 = 250;
+const DEFAULT_WHISPER_TIMEOUT_SECONDS: u64 = 60;
+const WHISPER_TIMEOUT_SECONDS_RANGE: std::ops::RangeInclusive<u64> = 5..=3_600;
+const WHISPER_TIMEOUT_SECONDS_PER_AUDIO_SECOND: f32 = 2.0;
+// Processing longer than this (or half the whisper timeout, if sooner) gets a `dictation:slow`.
+const DICTATION_SLOW_AFTER: Duration = Duration::from_secs(10);
+const WHISPER_PROCESS_POLL_INTERVAL_MS: u64 = 25;
+// whisper.cpp's HTTP server keeps a model loaded between requests; it ships next to whisper-cli.
+const WHISPER_SERVER_FILE_NAME: &str = if cfg!(windows) {
+    "whisper-server.exe"
+} else {
+    "whisper-server"
+};
+// Large models can take a while to load, especially on first launch after a download. The wait is
+// further capped by the run's whisper timeout so a slow load can't outlast it.
+const WARM_WHISPER_START_TIMEOUT: Duration = Duration::from_secs(120);
+// whisper-server binds the port we reserved only after loading, so another process can take it in
+// between; an early exit is retried on a fresh port this many times in total.
+const WARM_WHISPER_START_ATTEMPTS: u32 = 3;
+// Transcription language for both the spawned whisper-cli and the warm whisper-server.
+const WHISPER_LANGUAGE: &str = "en";
+const WARM_WHISPER_SHUTDOWN_WAIT: Duration = Duration::from_secs(2);
+const FAST_DECODE_PROFILE: WhisperDecodeProfile = WhisperDecodeProfile {
+    beam_size: 1,
+    best_of: 1,
+};
+// Same as whisper-cli's own `-bs` / `-bo` defaults, so the default single pass decodes exactly
+// like a bare whisper-cli run.
+const ACCURATE_DECODE_PROFILE: WhisperDecodeProfile = WhisperDecodeProfile {
+    beam_size: 5,
+    best_of: 5,
+};
+// Each extra beam adds decoder work roughly linearly, so 8 is already several times slower.
+const DECODE_OVERRIDE_RANGE: std::ops::RangeInclusive<u8> = 1..=8;
+// Clips shorter than this rarely hold enough speech for the information score to mean anything.
+const LOW_CONFIDENCE_RETRY_SECONDS: f32 = 1.5;
+const LOW_CONFIDENCE_SCORE_THRESHOLD: f32 = 0.35;
+// Higher thresholds retry more often; near 1.0 almost every clip would pay for a second pass.
+const LOW_CONFIDENCE_SCORE_THRESHOLD_RANGE: std::ops::RangeInclusive<f32> = 0.05..=0.95;
+const EXPECTED_WORDS_PER_SECOND: f32 = 1.0;
+// Transcripts scoring below `min_confidence` are rejected instead of pasted. The score is the
+// mean whisper token probability when available, otherwise the information score.
+const MIN_CONFIDENCE_RANGE: std::ops::RangeInclusive<f32> = 0.05..=0.95;
+const MAX_EXTRA_WHISPER_CLI_PATHS: usize = 16;
+const MAX_EXTRA_WHISPER_ARGS: usize = 32;
+// Thread cap while battery saver is on and the machine is unplugged.
+const BATTERY_SAVER_MAX_WHISPER_THREADS: usize = 2;
+const MAX_EXTRA_WHISPER_ARG_LENGTH: usize = 256;
+// Tried in order after any custom `model_download_mirror` as `{host}/{repo}/resolve/{revision}`;
+// the model file name is appended.
+const KNOWN_MODEL_DOWNLOAD_HOSTS: [&str; 2] = ["https://huggingface.co", "https://hf-mirror.com"];
+const DEFAULT_MODEL_REPO: &str = "ggerganov/whisper.cpp";
+const DEFAULT_MODEL_REVISION: &str = "main";
+const MAX_MODEL_REVISION_LENGTH: usize = 128;
+const MAX_MODEL_DOWNLOAD_MIRROR_LENGTH: usize = 512;
+// Attempts per source for network-level failures; the wait doubles after each one.
+const MODEL_DOWNLOAD_ATTEMPTS: u32 = 3;
+const MODEL_DOWNLOAD_RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+// A live transcription rewrites its temp files within seconds, so an hour-old one is orphaned.
+// How long the last prepared capture stays in memory for `export_last_recording_wav`; 0 keeps none.
+const DEFAULT_LAST_RECORDING_RETENTION_SECONDS: u64 = 300;
+const MAX_LAST_RECORDING_RETENTION_SECONDS: u64 = 3_600;
+const TEMP_ARTIFACT_STALE_AGE: Duration = Duration::from_secs(60 * 60);
+// curl exit codes for DNS, connect, partial transfer, timeout, TLS handshake, and dropped
+// connections; anything else (bad URL, disk errors) will fail the same way again.
+const RETRYABLE_CURL_EXIT_CODES: [i32; 9] = [6, 7, 18, 28, 35, 52, 55, 56, 92];
+// The whisper.cpp sample clip (also used by the sidecar smoke test), shipped as a bundle resource
+// so benchmarks run offline. `bun run benchmark:clip` installs it over the committed placeholder.
+const BENCHMARK_CLIP_FILE_NAME: &str = "benchmark-jfk.wav";
+// jfk.wav is 11.0s of 16 kHz mono; anything else is not the clip BENCHMARK_EXPECTED_TEXT describes.
+const BENCHMARK_CLIP_SECONDS: std::ops::RangeInclusive<f32> = 10.5..=11.5;
+// Imported models live in a subdirectory so catalog cleanup never touches them.
+const IMPORTED_MODELS_DIR_NAME: &str = "imported";
+// No catalog id starts with this, so imported ids cannot collide with catalog ones.
+const IMPORTED_MODEL_ID_PREFIX: &str = "imported-";
+const MAX_IMPORTED_MODEL_NAME_LENGTH: usize = 64;
+const BENCHMARK_EXPECTED_TEXT: &str = "And so my fellow Americans, ask not what your country can do for you, ask what you can do for your country.";
+const BENCHMARK_MATCH_ACCURACY: f32 = 0.9;
+// Fewer words than this is more likely a fragment or a command than a sentence.
+const AUTO_FORMAT_MIN_SENTENCE_WORDS: usize = 3;
+// ggml model files start with the magic 0x67676d6c written little-endian.
+const GGML_MODEL_MAGIC: [u8; 4] = *b"lmgg";
+// Upper bound on channels kept apart for diarization; more than this is an audio interface, not a mic.
+const MAX_PRESERVED_CHANNELS: u16 = 8;
+// Covers telephone-band mics through 192k interfaces; anything else is a typo, not a device rate.
+const PREFERRED_SAMPLE_RATE_RANGE: std::ops::RangeInclusive<u32> = 8_000..=192_000;
+// Multi-channel companions live in a subdirectory so recording listing and pruning keys stay mono.
+const CHANNEL_RECORDINGS_DIR_NAME: &str = "channels";
+// Always stripped when whisper emits them as bare or bracketed tokens; saved extras add to these.
+const DEFAULT_TRANSCRIPT_ARTIFACT_TOKENS: [&str; 4] = ["BLANK_AUDIO", "NOISE", "MUSIC", "SILENCE"];
+const MAX_TRANSCRIPT_ARTIFACT_TOKENS: usize = 64;
+const MAX_TRANSCRIPT_ARTIFACT_TOKEN_LENGTH: usize = 32;
+const MAX_PROFANITY_WORDS: usize = 256;
+const MAX_PROFANITY_WORD_LENGTH: usize = 64;
+const DEFAULT_PROFANITY_WORDS: [&str; 14] = [
+    "arsehole",
+    "asshole",
+    "bastard",
+    "bitch",
+    "bullshit",
+    "cock",
+    "cunt",
+    "dick",
+    "fuck",
+    "fucked",
+    "fucking",
+    "motherfucker",
+    "shit",
+    "twat",
+];
+// Stripped only at the very start or end of a transcript, never mid-sentence.
+const DEFAULT_FILLER_WORDS: [&str; 9] = ["uh", "um", "umm", "uhm", "er", "erm", "ah", "hmm", "mm"];
+// Whisper's classic near-silence hallucinations; removed only as the transcript's last sentence.
+const DEFAULT_FILLER_TRAILING_PHRASES: [&str; 5] = [
+    "thank you",
+    "thanks for watching",
+    "thank you for watching",
+    "thank you so much for watching",
+    "please subscribe",
+];
+// With dedup_repeats on, a word or phrase of up to DEDUP_MAX_NGRAM_WORDS words repeated back to
+// back more than `dedup_max_repeats` times is kept once; "very very good" stays under the default.
+const DEFAULT_DEDUP_MAX_REPEATS: u32 = 3;
+const DEDUP_MAX_REPEATS_RANGE: std::ops::RangeInclusive<u32> = 2..=10;
+const DEDUP_MAX_NGRAM_WORDS: usize = 4;
+const MAX_FILLER_ENTRIES: usize = 64;
+const MAX_FILLER_ENTRY_LENGTH: usize = 64;
+const MAX_SPOKEN_COMMANDS: usize = 64;
+const MAX_SPOKEN_COMMAND_WORDS: usize = 4;
+const MAX_SPOKEN_COMMAND_REPLACEMENT_CHARS: usize = 8;
+const DEFAULT_SPOKEN_COMMANDS_LOCALE: &str = "en";
+const SPOKEN_COMMAND_LOCALES: [&str; 4] = ["en", "es", "fr", "de"];
+const EN_SPOKEN_COMMANDS: [(&str, &str); 15] = [
+    ("comma", ","),
+    ("period", "."),
+    ("full stop", "."),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("colon", ":"),
+    ("semicolon", ";"),
+    ("new line", "\n"),
+    ("new paragraph", "\n\n"),
+    ("open paren", "("),
+    ("close paren", ")"),
+    ("open bracket", "["),
+    ("close bracket", "]"),
+    ("ellipsis", "..."),
+];
+const ES_SPOKEN_COMMANDS: [(&str, &str); 10] = [
+    ("coma", ","),
+    ("punto", "."),
+    ("punto y coma", ";"),
+    ("dos puntos", ":"),
+    ("signo de interrogación", "?"),
+    ("signo de exclamación", "!"),
+    ("nueva línea", "\n"),
+    ("nuevo párrafo", "\n\n"),
+    ("abrir paréntesis", "("),
+    ("cerrar paréntesis", ")"),
+];
+const FR_SPOKEN_COMMANDS: [(&str, &str); 10] = [
+    ("virgule", ","),
+    ("point", "."),
+    ("point-virgule", ";"),
+    ("deux-points", ":"),
+    ("point d'interrogation", "?"),
+    ("point d'exclamation", "!"),
+    ("à la ligne", "\n"),
+    ("nouveau paragraphe", "\n\n"),
+    ("ouvrir la parenthèse", "("),
+    ("fermer la parenthèse", ")"),
+];
+const DE_SPOKEN_COMMANDS: [(&str, &str); 10] = [
+    ("komma", ","),
+    ("punkt", "."),
+    ("semikolon", ";"),
+    ("doppelpunkt", ":"),
+    ("fragezeichen", "?"),
+    ("ausrufezeichen", "!"),
+    ("neue zeile", "\n"),
+    ("neuer absatz", "\n\n"),
+    ("klammer auf", "("),
+    ("klammer zu", ")"),
+];
+// Flags the app sets itself (model, input, output location/format, language, threads, decode
+// profile, timestamps, prints, GPU). Extra output formats are also refused because their files
+// would never be cleaned up.
+const MANAGED_WHISPER_FLAGS: [&str; 34] = [
+    "-m",
+    "--model",
+    "-f",
+    "--file",
+    "-of",
+    "--output-file",
+    "-otxt",
+    "--output-txt",
+    "-oj",
+    "--output-json",
+    "-ojf",
+    "--output-json-full",
+    "-osrt",
+    "--output-srt",
+    "-ovtt",
+    "--output-vtt",
+    "-ocsv",
+    "--output-csv",
+    "-h",
+    "--help",
+    "-l",
+    "--language",
+    "-t",
+    "--threads",
+    "-bs",
+    "--beam-size",
+    "-bo",
+    "--best-of",
+    "-nt",
+    "--no-timestamps",
+    "-np",
+    "--no-prints",
+    "-ng",
+    "--no-gpu",
+];
 
 #[derive(Clone, Serialize)]
 struct DictationStatePayload {
@@ -68,6 +442,158 @@ struct DictationStatePayload {
     error: Option<String>,
     transcript: Option<String>,
     session_id: Option<u64>,
+    scratch: bool,
+    warning: Option<String>,
+    // Set on `idle` when the low-confidence retry ran an accurate second pass.
+    retried: bool,
+    // Set on `idle` only with debug_audio_info on.
+    audio_info: Option<AudioInfoPayload>,
+    // Set on `idle` with a transcript: words in the bare transcript and prepared audio length.
+    word_count: Option<usize>,
+    audio_seconds: Option<f32>,
+    // Set on `idle` when command_mode ran a spoken command (`scratch`, `copy`) instead of
+    // returning a transcript.
+    command: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct DictationProgressPayload {
+    session_id: u64,
+    percent: u8,
+}
+
+#[derive(Clone, Serialize)]
+struct DictationTooShortPayload {
+    code: &'static str,
+    session_id: u64,
+    captured_seconds: f32,
+    min_seconds: f32,
+    message: String,
+}
+
+#[derive(Clone, Serialize)]
+struct DictationSlowPayload {
+    session_id: u64,
+    elapsed_ms: u64,
+    // Estimated from the captured length; whisper is stopped once this runs out.
+    timeout_ms: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct ModelDownloadRetryPayload {
+    model_id: String,
+    url: String,
+    // The attempt about to start, 2..=max_attempts.
+    attempt: u32,
+    max_attempts: u32,
+    delay_ms: u64,
+    error: String,
+}
+
+#[derive(Clone, Serialize)]
+struct DictationPartialPayload {
+    session_id: u64,
+    // Cumulative text of every chunk finished so far.
+    text: String,
+    chunks_done: usize,
+    chunk_count: usize,
+}
+
+#[derive(Clone, Debug)]
+struct PartialTranscript {
+    text: String,
+    chunks_done: usize,
+    chunk_count: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OnboardingReason {
+    NoModel,
+    ModelMissing,
+    CliMissing,
+}
+
+impl OnboardingReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoModel => "no_model",
+            Self::ModelMissing => "model_missing",
+            Self::CliMissing => "cli_missing",
+        }
+    }
+}
+
+// Stable identifiers the frontend can branch on; messages stay free to change wording.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorCode {
+    // Any failure without a more specific code.
+    CommandFailed,
+    // Only the macOS AVFoundation check can tell a permission denial apart.
+    #[cfg(target_os = "macos")]
+    MicPermissionDenied,
+    #[cfg(target_os = "macos")]
+    MicPermissionRestricted,
+    MicNotFound,
+    MicOpenFailed,
+    ModelNotSelected,
+    ModelMissing,
+    WhisperCliUnavailable,
+    WhisperCliPlaceholder,
+}
+
+impl ErrorCode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::CommandFailed => "COMMAND_FAILED",
+            #[cfg(target_os = "macos")]
+            Self::MicPermissionDenied => "MIC_PERMISSION_DENIED",
+            #[cfg(target_os = "macos")]
+            Self::MicPermissionRestricted => "MIC_PERMISSION_RESTRICTED",
+            Self::MicNotFound => "MIC_NOT_FOUND",
+            Self::MicOpenFailed => "MIC_OPEN_FAILED",
+            Self::ModelNotSelected => "MODEL_NOT_SELECTED",
+            Self::ModelMissing => "MODEL_MISSING",
+            Self::WhisperCliUnavailable => "WHISPER_CLI_UNAVAILABLE",
+            Self::WhisperCliPlaceholder => "WHISPER_CLI_PLACEHOLDER",
+        }
+    }
+}
+
+// Error type of every command, serialized as `{ code, message }`. Plain `String` errors convert
+// with `CommandFailed`, and converting back to `String` keeps only the message, so helpers that
+// still return `Result<_, String>` compose with `?` in both directions.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct DictationError {
+    code: &'static str,
+    message: String,
+}
+
+impl DictationError {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code: code.as_str(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<String> for DictationError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::CommandFailed, message)
+    }
+}
+
+impl From<DictationError> for String {
+    fn from(error: DictationError) -> Self {
+        error.message
+    }
+}
+
+// Emitted alongside the start error so the frontend can open onboarding without matching text.
+#[derive(Clone, Serialize)]
+struct DictationNeedsOnboardingPayload {
+    reason: String,
+    error: String,
 }
 
 #[derive(Clone, Serialize)]
@@ -91,18 +617,62 @@ struct AppConfig {
     whisper_model_path_override: Option<String>,
     whisper_cli_path_override: Option<String>,
     bundled_whisper_cli_path: Option<String>,
+    whisper_cli_capabilities: WhisperCliCapabilityCache,
 }
 
 struct DictationState {
     active_recording: Mutex<Option<ActiveRecording>>,
+    // Session whose recording was stopped and is still transcribing; lets a repeated stop
+    // report `processing` instead of claiming the app is idle.
+    processing_session: Mutex<Option<u64>>,
     next_session_id: AtomicU64,
+    last_transcription: Mutex<Option<LastTranscriptionInfo>>,
+    // App that was frontmost when the latest session started; focused-field paste goes back to it.
+    #[cfg(target_os = "macos")]
+    insert_target: Mutex<Option<InsertTarget>>,
+    // Set by `set_session_insert_wrap`; overrides the saved insert_wrap until cleared or quit.
+    insert_wrap_override: Mutex<Option<InsertWrap>>,
+    // Model path and warm-server flag of the last started `preload_model`; a repeat is a no-op.
+    preloaded_model: Mutex<Option<(PathBuf, bool)>>,
+    // Prepared audio of the latest non-scratch capture, dropped after the retention window.
+    last_capture: Arc<Mutex<Option<CachedCapture>>>,
+    // Last emitted dictation state, so idempotent re-emits do not replay a sound cue.
+    last_cue_state: Mutex<Option<String>>,
+}
+
+#[cfg(target_os = "macos")]
+#[derive(Clone, Debug)]
+struct InsertTarget {
+    pid: i32,
+    name: String,
+}
+
+// 16 kHz mono samples exactly as whisper received them (resampled, trimmed, normalized).
+#[derive(Debug)]
+struct CachedCapture {
+    samples: Vec<f32>,
+    captured_at: Instant,
+}
+
+struct LastTranscriptionInfo {
+    session_id: u64,
+    model_id: String,
+    info: TranscriptionInfo,
 }
 
 impl Default for DictationState {
     fn default() -> Self {
         Self {
             active_recording: Mutex::new(None),
+            processing_session: Mutex::new(None),
             next_session_id: AtomicU64::new(1),
+            last_transcription: Mutex::new(None),
+            #[cfg(target_os = "macos")]
+            insert_target: Mutex::new(None),
+            insert_wrap_override: Mutex::new(None),
+            preloaded_model: Mutex::new(None),
+            last_capture: Arc::new(Mutex::new(None)),
+            last_cue_state: Mutex::new(None),
         }
     }
 }
@@ -116,6 +686,13 @@ struct WhisperModelSpec {
     approx_size_gb: f32,
     min_ram_gb: u64,
     recommended_ram_gb: u64,
+    // Rough CPU decode cost relative to tiny; turbo keeps the large encoder but a 4-layer decoder.
+    relative_compute: f32,
+    // Fast-pass beam search under `two_pass_decode` when beam_size/best_of are not overridden;
+    // only the cheap tiers widen it, larger models stay greedy so dictation keeps up. The default
+    // single pass ignores these and decodes with whisper-cli's own profile.
+    default_beam: u8,
+    default_best_of: u8,
     speed_note: &'static str,
     quality_note: &'static str,
 }
@@ -129,6 +706,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 0.08,
         min_ram_gb: 4,
         recommended_ram_gb: 8,
+        relative_compute: 1.0,
+        default_beam: 3,
+        default_best_of: 3,
         speed_note: "Fastest",
         quality_note: "Lowest accuracy",
     },
@@ -140,6 +720,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 0.15,
         min_ram_gb: 6,
         recommended_ram_gb: 8,
+        relative_compute: 1.0,
+        default_beam: 3,
+        default_best_of: 3,
         speed_note: "Very fast",
         quality_note: "Low accuracy",
     },
@@ -151,6 +734,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 0.15,
         min_ram_gb: 6,
         recommended_ram_gb: 10,
+        relative_compute: 2.0,
+        default_beam: 2,
+        default_best_of: 2,
         speed_note: "Fast",
         quality_note: "Balanced",
     },
@@ -162,6 +748,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 0.29,
         min_ram_gb: 8,
         recommended_ram_gb: 12,
+        relative_compute: 2.0,
+        default_beam: 2,
+        default_best_of: 2,
         speed_note: "Fast",
         quality_note: "Balanced multilingual",
     },
@@ -173,6 +762,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 0.46,
         min_ram_gb: 8,
         recommended_ram_gb: 16,
+        relative_compute: 6.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Medium",
         quality_note: "Better accuracy",
     },
@@ -184,6 +776,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 0.93,
         min_ram_gb: 10,
         recommended_ram_gb: 18,
+        relative_compute: 6.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Medium",
         quality_note: "Better multilingual accuracy",
     },
@@ -195,6 +790,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 1.5,
         min_ram_gb: 16,
         recommended_ram_gb: 24,
+        relative_compute: 16.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Slowest in starter set",
         quality_note: "Best accuracy in starter set",
     },
@@ -206,6 +804,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 1.5,
         min_ram_gb: 18,
         recommended_ram_gb: 28,
+        relative_compute: 16.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Slower",
         quality_note: "Strong multilingual accuracy",
     },
@@ -217,6 +818,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 2.9,
         min_ram_gb: 24,
         recommended_ram_gb: 32,
+        relative_compute: 32.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Heavy",
         quality_note: "High accuracy",
     },
@@ -228,6 +832,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 2.9,
         min_ram_gb: 24,
         recommended_ram_gb: 32,
+        relative_compute: 32.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Heavy",
         quality_note: "High accuracy",
     },
@@ -239,6 +846,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 3.1,
         min_ram_gb: 32,
         recommended_ram_gb: 48,
+        relative_compute: 32.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Heaviest",
         quality_note: "Top accuracy",
     },
@@ -250,6 +860,9 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
         approx_size_gb: 1.62,
         min_ram_gb: 20,
         recommended_ram_gb: 32,
+        relative_compute: 12.0,
+        default_beam: 1,
+        default_best_of: 1,
         speed_note: "Fast large-class",
         quality_note: "Great quality/speed tradeoff",
     },
@@ -257,18 +870,252 @@ const WHISPER_MODEL_CATALOG: [WhisperModelSpec; 12] = [
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 struct LocalSettings {
+    schema_version: Option<u32>,
     selected_model_id: Option<String>,
     selected_model_path: Option<String>,
     preferred_input_device: Option<String>,
+    audio_host: Option<String>,
     dictation_trigger: Option<String>,
     dictation_trigger_enabled: Option<bool>,
     focused_field_insert_enabled: Option<bool>,
+    insert_delay_ms: Option<u64>,
+    acceleration: Option<String>,
+    whisper_timeout_seconds: Option<u64>,
+    normalization_mode: Option<String>,
+    normalization_target_level: Option<f32>,
+    normalization_target_rms: Option<f32>,
+    trailing_punctuation: Option<String>,
+    save_recordings: Option<bool>,
+    max_recordings: Option<u32>,
+    max_recordings_total_mb: Option<u64>,
+    recording_format: Option<String>,
+    extra_whisper_args: Option<Vec<String>>,
+    extra_whisper_cli_paths: Option<Vec<String>>,
+    hotkey_activation: Option<String>,
+    double_tap_ms: Option<u64>,
+    min_hold_ms: Option<u64>,
+    memory_guard_mode: Option<String>,
+    memory_guard_margin_mb: Option<u64>,
+    pill_position: Option<String>,
+    pill_margin: Option<u32>,
+    pill_enabled: Option<bool>,
+    pill_visibility: Option<String>,
+    downmix_mode: Option<String>,
+    profanity_filter: Option<bool>,
+    profanity_words: Option<Vec<String>>,
+    dedup_repeats: Option<bool>,
+    dedup_max_repeats: Option<u32>,
+    min_transcribe_seconds: Option<f32>,
+    sound_cues: Option<bool>,
+    // Cue names (see SoundCue) muted while sound_cues is on.
+    disabled_sound_cues: Option<Vec<String>>,
+    strip_fillers: Option<bool>,
+    filler_words: Option<Vec<String>>,
+    filler_trailing_phrases: Option<Vec<String>>,
+    spoken_commands: Option<bool>,
+    spoken_commands_locale: Option<String>,
+    // Custom phrase -> replacement entries on top of the locale defaults; an empty
+    // replacement turns a default phrase off.
+    spoken_command_map: Option<BTreeMap<String, String>>,
+    // Extra non-speech markers stripped like the defaults, uppercase; see
+    // DEFAULT_TRANSCRIPT_ARTIFACT_TOKENS.
+    transcript_artifact_tokens: Option<Vec<String>>,
+    show_progress: Option<bool>,
+    partial_transcripts: Option<bool>,
+    fallback_on_oom: Option<bool>,
+    model_benchmarks: Option<BTreeMap<String, ModelBenchmarkResult>>,
+    imported_models: Option<Vec<ImportedModel>>,
+    recommendation_bias: Option<String>,
+    usage_stats: Option<bool>,
+    usage_totals: Option<UsageTotals>,
+    command_mode: Option<bool>,
+    last_recording_retention_seconds: Option<u64>,
+    model_download_mirror: Option<String>,
+    model_repo: Option<String>,
+    model_revision: Option<String>,
+    auto_format: Option<bool>,
+    auto_format_append_period: Option<bool>,
+    output_suffix: Option<String>,
+    insert_wrap: Option<String>,
+    preserve_channels: Option<u16>,
+    preferred_sample_rate: Option<u32>,
+    retry_on_low_confidence: Option<bool>,
+    low_confidence_threshold: Option<f32>,
+    two_pass_decode: Option<bool>,
+    whisper_threads: Option<usize>,
+    beam_size: Option<u8>,
+    best_of: Option<u8>,
+    battery_saver: Option<bool>,
+    whisper_cli_path_override: Option<String>,
+    debug_audio_info: Option<bool>,
+    trim_silence: Option<bool>,
+    noise_gate: Option<bool>,
+    noise_gate_threshold: Option<f32>,
+    min_confidence: Option<f32>,
+    keep_whisper_warm: Option<bool>,
+    chunk_seconds: Option<u32>,
+    window_geometry: Option<WindowGeometry>,
 }
 
-struct LocalModelState {
+// Main window outer position and inner size, in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+// A monitor's work area, in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MonitorRect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+struct WindowGeometryState {
+    tx: Mutex<mpsc::Sender<WindowGeometry>>,
+}
+
+// What a running whisper-server was started with; a request with a different config restarts it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct WarmWhisperConfig {
+    server_path: PathBuf,
+    model_path: PathBuf,
+    server_args: Vec<String>,
+}
+
+struct WarmWhisperRequest {
+    config: WarmWhisperConfig,
+    wav_path: PathBuf,
+    profile: WhisperDecodeProfile,
+    timeout: Duration,
+    // `None` tells the caller to fall back to spawning whisper-cli.
+    reply: mpsc::Sender<Option<String>>,
+}
+
+enum WarmWhisperMessage {
+    Transcribe(WarmWhisperRequest),
+    // Stops the server and acknowledges once the process is gone.
+    Shutdown(mpsc::Sender<()>),
+}
+
+// Owned by the worker thread; dropping it stops the server.
+struct WarmWhisperServer {
+    config: WarmWhisperConfig,
+    child: std::process::Child,
+    port: u16,
+}
+
+impl Drop for WarmWhisperServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+struct WarmWhisperState {
+    tx: Mutex<mpsc::Sender<WarmWhisperMessage>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+struct StorageLocation {
+    base_dir: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct StorageLocationPayload {
+    base_dir: String,
+    settings_path: String,
+    models_dir: String,
+    recordings_dir: String,
+    is_default: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct StoragePaths {
     settings_path: PathBuf,
     models_dir: PathBuf,
+    recordings_dir: PathBuf,
+}
+
+struct LocalModelState {
+    // Behind a lock so `set_storage_dir` can relocate storage without a restart; read through
+    // the accessors, which return owned copies.
+    paths: Mutex<StoragePaths>,
     settings: Arc<Mutex<LocalSettings>>,
+    // Running model downloads; storage moves and bulk deletes refuse to run underneath one.
+    active_downloads: Arc<AtomicUsize>,
+    storage_moving: Arc<AtomicBool>,
+}
+
+// Counts one running download until dropped.
+struct ActiveDownloadGuard(Arc<AtomicUsize>);
+
+impl Drop for ActiveDownloadGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Marks a storage move in progress until dropped.
+struct StorageMoveGuard(Arc<AtomicBool>);
+
+impl Drop for StorageMoveGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+impl LocalModelState {
+    // The counter is bumped before the move flag is read (and the reverse in
+    // `begin_storage_move`), so a download and a move can never both get past the check.
+    fn begin_download(&self) -> Result<ActiveDownloadGuard, String> {
+        self.active_downloads.fetch_add(1, Ordering::SeqCst);
+        let guard = ActiveDownloadGuard(Arc::clone(&self.active_downloads));
+        if self.storage_moving.load(Ordering::SeqCst) {
+            return Err(
+                "Wait for the storage move to finish before downloading models.".to_string(),
+            );
+        }
+        Ok(guard)
+    }
+
+    fn download_active(&self) -> bool {
+        self.active_downloads.load(Ordering::SeqCst) > 0
+    }
+
+    fn begin_storage_move(&self) -> Result<StorageMoveGuard, String> {
+        if self.storage_moving.swap(true, Ordering::SeqCst) {
+            return Err("Storage is already being moved.".to_string());
+        }
+        let guard = StorageMoveGuard(Arc::clone(&self.storage_moving));
+        if self.download_active() {
+            return Err("Wait for the model download to finish before moving storage.".to_string());
+        }
+        Ok(guard)
+    }
+
+    fn paths(&self) -> StoragePaths {
+        self.paths
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn settings_path(&self) -> PathBuf {
+        self.paths().settings_path
+    }
+
+    fn models_dir(&self) -> PathBuf {
+        self.paths().models_dir
+    }
+
+    fn recordings_dir(&self) -> PathBuf {
+        self.paths().recordings_dir
+    }
 }
 
 #[derive(Clone, Default)]
@@ -291,3576 +1138,18752 @@ impl HotkeyDeliveryMode {
     }
 }
 
-#[derive(Clone)]
-struct TriggerRuntimeDetails {
-    mode: HotkeyDeliveryMode,
-    status: String,
-    permission_hint: Option<String>,
-}
-
-#[derive(Default)]
-struct GlobalHotkeyState {
-    registered_trigger: Mutex<Option<String>>,
-    runtime_details: Mutex<TriggerRuntimeDetails>,
-    #[cfg(target_os = "macos")]
-    macos_fn_listener: Mutex<Option<MacFnGlobalListener>>,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum WhisperAcceleration {
+    #[default]
+    Auto,
+    Cpu,
+    Metal,
+    CoreMl,
+    Cuda,
 }
 
-#[cfg(target_os = "macos")]
-type CFAllocatorRef = *mut c_void;
-#[cfg(target_os = "macos")]
-type CFMachPortRef = *mut c_void;
-#[cfg(target_os = "macos")]
-type CFRunLoopRef = *mut c_void;
-#[cfg(target_os = "macos")]
-type CFRunLoopSourceRef = *mut c_void;
-#[cfg(target_os = "macos")]
-type CFStringRef = *const c_void;
-#[cfg(target_os = "macos")]
-type CGEventRef = *const c_void;
-#[cfg(target_os = "macos")]
-type CGEventTapProxy = *const c_void;
-#[cfg(target_os = "macos")]
-type CGEventMask = u64;
-#[cfg(target_os = "macos")]
-type CGEventFlags = u64;
-
-#[cfg(target_os = "macos")]
-const CG_EVENT_TAP_LOCATION_SESSION: u32 = 1;
-#[cfg(target_os = "macos")]
-const CG_EVENT_TAP_LOCATION_HID: u32 = 0;
-#[cfg(target_os = "macos")]
-const CG_EVENT_TAP_PLACEMENT_HEAD_INSERT: u32 = 0;
-#[cfg(target_os = "macos")]
-const CG_EVENT_TAP_OPTION_LISTEN_ONLY: u32 = 1;
-#[cfg(target_os = "macos")]
-const CG_EVENT_TYPE_FLAGS_CHANGED: u32 = 12;
-#[cfg(target_os = "macos")]
-const CG_EVENT_TYPE_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFF_FFFE;
-#[cfg(target_os = "macos")]
-const CG_EVENT_TYPE_TAP_DISABLED_BY_USER_INPUT: u32 = 0xFFFF_FFFF;
+impl WhisperAcceleration {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Cpu => "cpu",
+            Self::Metal => "metal",
+            Self::CoreMl => "coreml",
+            Self::Cuda => "cuda",
+        }
+    }
 
-#[cfg(target_os = "macos")]
-const MACOS_COMMAND_FLAG_MASK: CGEventFlags = 1 << 20;
-#[cfg(target_os = "macos")]
-const MACOS_FN_FLAG_MASK: CGEventFlags = 1 << 23;
-#[cfg(target_os = "macos")]
-const MACOS_NON_FN_MODIFIER_MASK: CGEventFlags = (1 << 17) | (1 << 18) | (1 << 19) | (1 << 20);
-#[cfg(target_os = "macos")]
-const KEYCODE_COMMAND: u16 = 0x37;
-#[cfg(target_os = "macos")]
-const KEYCODE_V: u16 = 0x09;
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "cpu" => Some(Self::Cpu),
+            "metal" => Some(Self::Metal),
+            "coreml" | "core-ml" => Some(Self::CoreMl),
+            "cuda" => Some(Self::Cuda),
+            _ => None,
+        }
+    }
+}
 
-#[cfg(target_os = "macos")]
-type MacFnEventTapCallback =
-    unsafe extern "C" fn(CGEventTapProxy, u32, CGEventRef, *mut c_void) -> CGEventRef;
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct WhisperCliCapabilities {
+    gpu_toggle: bool,
+    flash_attention: bool,
+}
 
-#[cfg(target_os = "macos")]
-#[link(name = "CoreGraphics", kind = "framework")]
-extern "C" {
-    fn CGEventTapCreate(
-        tap: u32,
-        place: u32,
-        options: u32,
-        events_of_interest: CGEventMask,
-        callback: MacFnEventTapCallback,
-        user_info: *mut c_void,
-    ) -> CFMachPortRef;
-    fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
-    fn CGEventGetFlags(event: CGEventRef) -> CGEventFlags;
-    fn CGEventCreateKeyboardEvent(
-        source: *const c_void,
-        virtual_key: u16,
-        key_down: bool,
-    ) -> CGEventRef;
-    fn CGEventSetFlags(event: CGEventRef, flags: CGEventFlags);
-    fn CGEventPost(tap: u32, event: CGEventRef);
+// `--help` probe results keyed by the resolved executable, so decodes do not spawn an extra
+// whisper-cli each time. Cleared whenever the saved whisper-cli override changes.
+type WhisperCliCapabilityCache = Arc<Mutex<HashMap<PathBuf, WhisperCliCapabilities>>>;
+
+#[derive(Clone, Debug)]
+struct WhisperRunOptions {
+    acceleration: WhisperAcceleration,
+    timeout_seconds: u64,
+    extra_args: Vec<String>,
+    // `None` leaves the thread count to whisper-cli's own default.
+    threads: Option<usize>,
+    // Set only when show_progress is on; whisper then runs with --print-progress and parsed
+    // percentages are forwarded here.
+    progress: Option<mpsc::Sender<u8>>,
+    output_format: WhisperOutputFormat,
+    // Also write whisper's full JSON (`-ojf`) for plain-text runs so token probabilities can be
+    // read back. Only requested when a minimum confidence is configured.
+    token_confidence: bool,
+    // Set for dictation when keep_whisper_warm is on; runs that need whisper-cli-only output
+    // (progress, JSON, subtitles, extra args) still spawn the CLI.
+    warm_worker: Option<mpsc::Sender<WarmWhisperMessage>>,
+    // The app-wide cache from AppConfig; a fresh one just probes on every run.
+    capability_cache: WhisperCliCapabilityCache,
 }
 
-#[cfg(target_os = "macos")]
-#[link(name = "CoreFoundation", kind = "framework")]
-extern "C" {
-    static kCFAllocatorDefault: CFAllocatorRef;
-    static kCFRunLoopCommonModes: CFStringRef;
+struct WhisperRunOutput {
+    text: String,
+    // Mean probability of the spoken tokens; `None` unless `token_confidence` was requested and
+    // whisper produced readable JSON.
+    token_confidence: Option<f32>,
+}
 
-    fn CFMachPortCreateRunLoopSource(
-        allocator: CFAllocatorRef,
-        port: CFMachPortRef,
-        order: isize,
-    ) -> CFRunLoopSourceRef;
-    fn CFMachPortInvalidate(port: CFMachPortRef);
-    fn CFRunLoopGetMain() -> CFRunLoopRef;
-    fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
-    fn CFRunLoopRemoveSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
-    fn CFRelease(cf: *const c_void);
+// Dictation always reads plain text; subtitle formats are only requested by
+// `transcribe_to_subtitles`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum WhisperOutputFormat {
+    #[default]
+    Txt,
+    Srt,
+    Vtt,
 }
 
-#[cfg(target_os = "macos")]
-#[link(name = "ApplicationServices", kind = "framework")]
-extern "C" {
-    fn AXIsProcessTrusted() -> bool;
-}
+impl WhisperOutputFormat {
+    fn flag(&self) -> &'static str {
+        match self {
+            Self::Txt => "-otxt",
+            Self::Srt => "-osrt",
+            Self::Vtt => "-ovtt",
+        }
+    }
 
-#[cfg(target_os = "macos")]
-struct MacFnCallbackContext {
-    app: tauri::AppHandle,
-    enabled: AtomicBool,
-    fn_down: AtomicBool,
-    tap: AtomicPtr<c_void>,
-}
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Txt => "txt",
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+        }
+    }
 
-#[cfg(target_os = "macos")]
-struct MacFnGlobalListener {
-    tap: CFMachPortRef,
-    source: CFRunLoopSourceRef,
-    callback_ctx: Arc<MacFnCallbackContext>,
-    callback_ctx_raw: *const MacFnCallbackContext,
+    fn parse_subtitle(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "srt" => Some(Self::Srt),
+            "vtt" | "webvtt" => Some(Self::Vtt),
+            _ => None,
+        }
+    }
 }
 
-#[cfg(target_os = "macos")]
-unsafe impl Send for MacFnGlobalListener {}
-#[cfg(target_os = "macos")]
-unsafe impl Sync for MacFnGlobalListener {}
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum PillPosition {
+    #[default]
+    BottomCenter,
+    BottomLeft,
+    BottomRight,
+    TopCenter,
+    TopLeft,
+    TopRight,
+}
 
-#[cfg(target_os = "macos")]
-impl MacFnGlobalListener {
-    fn new(app: &tauri::AppHandle) -> Result<Self, String> {
-        let callback_ctx = Arc::new(MacFnCallbackContext {
-            app: app.clone(),
-            enabled: AtomicBool::new(false),
-            fn_down: AtomicBool::new(false),
-            tap: AtomicPtr::new(std::ptr::null_mut()),
-        });
-        let callback_ctx_raw = Arc::into_raw(Arc::clone(&callback_ctx));
+impl PillPosition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::BottomCenter => "bottom-center",
+            Self::BottomLeft => "bottom-left",
+            Self::BottomRight => "bottom-right",
+            Self::TopCenter => "top-center",
+            Self::TopLeft => "top-left",
+            Self::TopRight => "top-right",
+        }
+    }
 
-        let event_mask = 1_u64 << CG_EVENT_TYPE_FLAGS_CHANGED;
-        let tap = unsafe {
-            CGEventTapCreate(
-                CG_EVENT_TAP_LOCATION_SESSION,
-                CG_EVENT_TAP_PLACEMENT_HEAD_INSERT,
-                CG_EVENT_TAP_OPTION_LISTEN_ONLY,
-                event_mask,
-                macos_fn_event_tap_callback,
-                callback_ctx_raw as *mut c_void,
-            )
-        };
-        if tap.is_null() {
-            unsafe {
-                drop(Arc::from_raw(callback_ctx_raw));
-            }
-            return Err("Global Fn listener unavailable. macOS may be blocking event taps. Allow Input Monitoring for this app/terminal in System Settings > Privacy & Security > Input Monitoring.".to_string());
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "bottom-center" | "bottom" => Some(Self::BottomCenter),
+            "bottom-left" => Some(Self::BottomLeft),
+            "bottom-right" => Some(Self::BottomRight),
+            "top-center" | "top" => Some(Self::TopCenter),
+            "top-left" => Some(Self::TopLeft),
+            "top-right" => Some(Self::TopRight),
+            _ => None,
         }
+    }
+}
 
-        callback_ctx
-            .tap
-            .store(tap.cast::<c_void>(), Ordering::SeqCst);
+// Container for retained recordings. FLAC is encoded in-process and lossless; Opus goes through
+// ffmpeg (libopus) and is lossy but far smaller.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum RecordingFormat {
+    #[default]
+    Wav,
+    Flac,
+    Opus,
+}
 
-        let source = unsafe { CFMachPortCreateRunLoopSource(kCFAllocatorDefault, tap, 0) };
-        if source.is_null() {
-            unsafe {
-                CFMachPortInvalidate(tap);
-                CFRelease(tap as *const c_void);
-                drop(Arc::from_raw(callback_ctx_raw));
-            }
-            return Err(
-                "Failed to create macOS run loop source for global Fn listener.".to_string(),
-            );
-        }
+impl RecordingFormat {
+    const ALL: [Self; 3] = [Self::Wav, Self::Flac, Self::Opus];
 
-        unsafe {
-            let run_loop = CFRunLoopGetMain();
-            CFRunLoopAddSource(run_loop, source, kCFRunLoopCommonModes);
-            CGEventTapEnable(tap, true);
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::Opus => "opus",
         }
-
-        Ok(Self {
-            tap,
-            source,
-            callback_ctx,
-            callback_ctx_raw,
-        })
     }
 
-    fn set_enabled(&self, enabled: bool) {
-        self.callback_ctx.enabled.store(enabled, Ordering::SeqCst);
-        if !enabled {
-            self.callback_ctx.fn_down.store(false, Ordering::SeqCst);
+    fn parse(value: &str) -> Option<Self> {
+        match value
+            .trim()
+            .trim_start_matches('.')
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "wav" | "wave" => Some(Self::Wav),
+            "flac" => Some(Self::Flac),
+            "opus" | "ogg" => Some(Self::Opus),
+            _ => None,
         }
     }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(extension))
+    }
 }
 
-#[cfg(target_os = "macos")]
-impl Drop for MacFnGlobalListener {
-    fn drop(&mut self) {
-        unsafe {
-            let run_loop = CFRunLoopGetMain();
-            CFRunLoopRemoveSource(run_loop, self.source, kCFRunLoopCommonModes);
-            CFRelease(self.source as *const c_void);
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum PillVisibility {
+    #[default]
+    Always,
+    // Shown while listening, paused, or transcribing; hidden (not destroyed) otherwise.
+    RecordingOnly,
+    Hidden,
+}
 
-            CFMachPortInvalidate(self.tap);
-            CFRelease(self.tap as *const c_void);
+impl PillVisibility {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::RecordingOnly => "recording_only",
+            Self::Hidden => "hidden",
+        }
+    }
 
-            drop(Arc::from_raw(self.callback_ctx_raw));
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "always" => Some(Self::Always),
+            "recording_only" | "recording" => Some(Self::RecordingOnly),
+            "hidden" | "off" => Some(Self::Hidden),
+            _ => None,
         }
     }
 }
 
-#[cfg(target_os = "macos")]
-unsafe extern "C" fn macos_fn_event_tap_callback(
-    _proxy: CGEventTapProxy,
-    event_type: u32,
-    event: CGEventRef,
-    user_info: *mut c_void,
-) -> CGEventRef {
-    if user_info.is_null() {
-        return event;
-    }
+// Short tones for dictation transitions, mainly for users who cannot see the pill.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SoundCue {
+    Start,
+    Stop,
+    Success,
+    Error,
+}
 
-    let callback_ctx = &*(user_info as *const MacFnCallbackContext);
-    if event_type == CG_EVENT_TYPE_TAP_DISABLED_BY_TIMEOUT
-        || event_type == CG_EVENT_TYPE_TAP_DISABLED_BY_USER_INPUT
-    {
-        let tap = callback_ctx.tap.load(Ordering::Relaxed);
-        if !tap.is_null() {
-            CGEventTapEnable(tap.cast::<c_void>(), true);
+impl SoundCue {
+    const ALL: [Self; 4] = [Self::Start, Self::Stop, Self::Success, Self::Error];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Success => "success",
+            Self::Error => "error",
         }
-        callback_ctx.fn_down.store(false, Ordering::Relaxed);
-        return event;
     }
 
-    if event.is_null() || event_type != CG_EVENT_TYPE_FLAGS_CHANGED {
-        return event;
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "start" | "listening" => Some(Self::Start),
+            "stop" | "processing" => Some(Self::Stop),
+            "success" | "done" => Some(Self::Success),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
     }
 
-    if !callback_ctx.enabled.load(Ordering::Relaxed) {
-        return event;
+    // (frequency Hz, duration ms) steps; a 0 Hz step is a gap. Start rises, stop is one mid
+    // tone, success rises higher, error is two low beeps, so each is recognizable by ear.
+    fn tones(&self) -> &'static [(f32, u32)] {
+        match self {
+            Self::Start => &[(660.0, 70), (880.0, 90)],
+            Self::Stop => &[(587.0, 110)],
+            Self::Success => &[(784.0, 70), (1175.0, 110)],
+            Self::Error => &[(233.0, 120), (0.0, 60), (233.0, 120)],
+        }
     }
+}
 
-    let flags = CGEventGetFlags(event);
-    let fn_down = (flags & MACOS_FN_FLAG_MASK) != 0;
-    let was_fn_down = callback_ctx.fn_down.swap(fn_down, Ordering::Relaxed);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PillLayout {
+    position: PillPosition,
+    margin: u32,
+    enabled: bool,
+    visibility: PillVisibility,
+}
 
-    let has_non_fn_modifiers = (flags & MACOS_NON_FN_MODIFIER_MASK) != 0;
-    if fn_down != was_fn_down && !has_non_fn_modifiers {
-        dispatch_backend_hotkey_action(
-            &callback_ctx.app,
-            if fn_down {
-                BackendHotkeyAction::HoldStart
-            } else {
-                BackendHotkeyAction::HoldStop
-            },
-        );
+// How much of ggml's vectorized math this CPU can use. Without AVX2+FMA (x86) or NEON (ARM),
+// whisper.cpp falls back to much slower code paths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SimdSupport {
+    Full,
+    // x86 with AVX but not AVX2+FMA, or an architecture ggml has no tuned kernels for.
+    Partial,
+    None,
+}
+
+impl SimdSupport {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Partial => "partial",
+            Self::None => "none",
+        }
     }
 
-    event
-}
+    fn detect(architecture: &str, cpu_features: &[&str]) -> Self {
+        let has = |feature: &str| cpu_features.contains(&feature);
+        match architecture {
+            "x86" | "x86_64" if has("avx2") && has("fma") => Self::Full,
+            "x86" | "x86_64" if has("avx") => Self::Partial,
+            "x86" | "x86_64" => Self::None,
+            "aarch64" | "arm" if has("neon") => Self::Full,
+            "aarch64" | "arm" => Self::None,
+            _ => Self::Partial,
+        }
+    }
 
-#[derive(Serialize)]
-struct DeviceProfile {
-    total_memory_gb: u64,
-    logical_cpu_cores: usize,
-    architecture: String,
-    os: String,
+    // Rough multiplier on the per-core real-time estimate.
+    fn speed_factor(&self) -> f32 {
+        match self {
+            Self::Full => 1.0,
+            Self::Partial => 0.5,
+            Self::None => 0.25,
+        }
+    }
 }
 
-#[derive(Serialize)]
-struct DictationModelOption {
-    id: String,
-    display_name: String,
-    whisper_ref: String,
-    file_name: String,
-    path: String,
-    installed: bool,
-    likely_runnable: bool,
-    recommended: bool,
-    approx_size_gb: f32,
-    min_ram_gb: u64,
-    recommended_ram_gb: u64,
-    speed_note: String,
-    quality_note: String,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InputSourceKind {
+    Microphone,
+    // A virtual input device (BlackHole, a PulseAudio monitor) that carries system output.
+    LoopbackInput,
+    // An output device opened for WASAPI loopback; its formats come from the output side.
+    LoopbackOutput,
 }
 
-#[derive(Serialize)]
-struct DictationOnboardingPayload {
-    onboarding_required: bool,
-    selected_model_id: Option<String>,
-    selected_model_path: Option<String>,
-    selected_model_exists: bool,
-    available_input_devices: Vec<DictationInputDevice>,
-    preferred_input_device: Option<String>,
-    dictation_trigger: Option<String>,
-    default_dictation_trigger: String,
-    dictation_trigger_mode: String,
-    dictation_trigger_status: String,
-    dictation_trigger_permission_hint: Option<String>,
-    focused_field_insert_enabled: bool,
-    focused_field_insert_permission_granted: bool,
-    focused_field_insert_permission_status: String,
-    whisper_cli_available: bool,
-    whisper_cli_path: String,
-    models_dir: String,
-    device: DeviceProfile,
-    models: Vec<DictationModelOption>,
+#[derive(Clone, Copy, Debug)]
+struct InputStreamOptions {
+    downmix: DownmixMode,
+    preferred_rate: Option<u32>,
+    source: InputSourceKind,
 }
 
-#[derive(Serialize)]
-struct DictationTriggerPayload {
-    trigger: Option<String>,
-    default_trigger: String,
-    trigger_mode: String,
-    trigger_status: String,
-    trigger_permission_hint: Option<String>,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum DownmixMode {
+    #[default]
+    Average,
+    Dominant,
+    Left,
+    Right,
 }
 
-#[derive(Serialize)]
-struct FocusedFieldInsertPayload {
-    enabled: bool,
-    permission_granted: bool,
-    permission_status: String,
+impl DownmixMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Average => "average",
+            Self::Dominant => "dominant",
+            Self::Left => "left",
+            Self::Right => "right",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "average" | "mean" => Some(Self::Average),
+            "dominant" | "loudest" => Some(Self::Dominant),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Clone)]
-struct FocusedFieldInsertPermissionStatus {
-    granted: bool,
-    status: String,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum RecommendationBias {
+    Speed,
+    #[default]
+    Balanced,
+    Accuracy,
 }
 
-#[derive(Clone, Serialize)]
-struct DictationInputDevice {
-    name: String,
-    is_default: bool,
+impl RecommendationBias {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Speed => "speed",
+            Self::Balanced => "balanced",
+            Self::Accuracy => "accuracy",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "speed" | "fast" => Some(Self::Speed),
+            "balanced" | "default" => Some(Self::Balanced),
+            "accuracy" | "quality" => Some(Self::Accuracy),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Serialize)]
-struct DictationModelSelection {
-    selected_model_id: String,
-    selected_model_path: String,
-    installed: bool,
+// Off by default: probing free memory spawns vm_stat / PowerShell ahead of every recording.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum MemoryGuardMode {
+    #[default]
+    Off,
+    Warn,
+    Block,
 }
 
-#[derive(Serialize)]
-struct DictationModelDeletion {
-    deleted_model_id: String,
-    selected_model_id: Option<String>,
-    selected_model_path: Option<String>,
-}
+impl MemoryGuardMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Warn => "warn",
+            Self::Block => "block",
+        }
+    }
 
-#[derive(Clone, Copy, Debug)]
-struct AudioSignalStats {
-    peak_abs: f32,
-    rms: f32,
-    duration_secs: f32,
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" => Some(Self::Off),
+            "warn" => Some(Self::Warn),
+            "block" | "hard" => Some(Self::Block),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Clone)]
-struct LiveAudioMeter {
-    app: tauri::AppHandle,
-    session_id: u64,
-    last_emitted_at: Arc<Mutex<Option<Instant>>>,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum AudioNormalizationMode {
+    #[default]
+    Peak,
+    Rms,
+    Off,
 }
 
-struct ActiveRecording {
-    session_id: u64,
-    input_device_name: String,
-    stop_tx: mpsc::Sender<()>,
-    thread_handle: thread::JoinHandle<()>,
-    samples: Arc<Mutex<Vec<f32>>>,
-    sample_rate: u32,
+impl AudioNormalizationMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Peak => "peak",
+            Self::Rms => "rms",
+            Self::Off => "off",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "peak" => Some(Self::Peak),
+            "rms" => Some(Self::Rms),
+            "off" | "none" => Some(Self::Off),
+            _ => None,
+        }
+    }
+
+    fn default_target_level(&self) -> f32 {
+        match self {
+            Self::Peak | Self::Off => TARGET_TRANSCRIPTION_AUDIO_PEAK,
+            Self::Rms => DEFAULT_TARGET_TRANSCRIPTION_AUDIO_RMS,
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
-enum BackendHotkeyAction {
-    Toggle,
-    HoldStart,
-    HoldStop,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum TrailingPunctuation {
+    #[default]
+    Keep,
+    Period,
+    None,
 }
 
-fn parse_truthy_env(value: &str) -> bool {
-    let normalized = value.trim().to_ascii_lowercase();
-    !matches!(normalized.as_str(), "" | "0" | "false" | "no" | "off")
+impl TrailingPunctuation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keep => "keep",
+            Self::Period => "period",
+            Self::None => "none",
+        }
+    }
+
+    // "chat" and "docs" are accepted as profile-style shorthands for the two common contexts.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "keep" => Some(Self::Keep),
+            "period" | "docs" | "prose" => Some(Self::Period),
+            "none" | "chat" => Some(Self::None),
+            _ => None,
+        }
+    }
 }
 
-fn should_start_hidden() -> bool {
-    std::env::var(START_HIDDEN_ENV)
-        .map(|value| parse_truthy_env(&value))
-        .unwrap_or(false)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OutputSuffix {
+    #[default]
+    None,
+    Space,
+    Newline,
 }
 
-fn show_main_window(app: &tauri::AppHandle) {
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.show();
-        let _ = window.unminimize();
-        let _ = window.set_focus();
+impl OutputSuffix {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Space => "space",
+            Self::Newline => "newline",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "space" => Some(Self::Space),
+            "newline" => Some(Self::Newline),
+            _ => None,
+        }
     }
 }
 
-fn sync_pill_after_main_window_hide(app: &tauri::AppHandle) {
-    let state = if dictation_is_running(app).unwrap_or(false) {
-        "listening"
-    } else {
-        "idle"
-    };
-    sync_pill_for_dictation_state(app, state, None);
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum InsertWrap {
+    #[default]
+    None,
+    InlineCode,
+    CodeBlock,
+    Quote,
 }
 
-fn active_hotkey_label(app: &tauri::AppHandle) -> String {
-    let hotkey_state = app.state::<GlobalHotkeyState>();
-    let trigger = current_registered_hotkey(hotkey_state.inner())
-        .ok()
-        .flatten()
-        .unwrap_or_else(default_dictation_trigger);
-    if trigger == "Fn" {
-        "Fn / Globe".to_string()
-    } else {
-        trigger
+impl InsertWrap {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::InlineCode => "inline_code",
+            Self::CodeBlock => "code_block",
+            Self::Quote => "quote",
+        }
     }
-}
 
-fn idle_pill_message(app: &tauri::AppHandle) -> String {
-    let hotkey_state = app.state::<GlobalHotkeyState>();
-    let runtime = current_trigger_runtime_details(hotkey_state.inner()).unwrap_or_default();
-    let label = active_hotkey_label(app);
-    match runtime.mode {
-        HotkeyDeliveryMode::GlobalHold | HotkeyDeliveryMode::FocusedWindowHold => {
-            format!("Hold {label} to dictate")
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "none" => Some(Self::None),
+            "inline_code" => Some(Self::InlineCode),
+            "code_block" => Some(Self::CodeBlock),
+            "quote" => Some(Self::Quote),
+            _ => None,
         }
-        HotkeyDeliveryMode::GlobalToggle => format!("Press {label} to dictate"),
-        HotkeyDeliveryMode::Disabled => "Hotkey disabled".to_string(),
     }
 }
 
-fn emit_pill_status(
-    app: &tauri::AppHandle,
-    message: impl Into<String>,
-    state: impl Into<String>,
-    visible: bool,
-) {
-    app.emit(
-        PILL_STATUS_EVENT,
-        PillStatusPayload {
-            message: message.into(),
-            state: state.into(),
-            visible,
-        },
-    )
-    .ok();
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct AutoFormatOptions {
+    enabled: bool,
+    append_period: bool,
 }
 
-fn sync_pill_for_dictation_state(app: &tauri::AppHandle, state: &str, error: Option<&str>) {
-    let hotkey_state = app.state::<GlobalHotkeyState>();
-    let runtime = current_trigger_runtime_details(hotkey_state.inner()).unwrap_or_default();
-    let label = active_hotkey_label(app);
+// Only consulted by the `two_pass_decode` flow; the default single pass never retries.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct LowConfidenceRetryOptions {
+    enabled: bool,
+    score_threshold: f32,
+}
 
-    let (message, pill_state) = match state {
-        "listening" => {
-            let message = match runtime.mode {
-                HotkeyDeliveryMode::GlobalHold | HotkeyDeliveryMode::FocusedWindowHold => {
-                    format!("Listening - release {label}")
-                }
-                HotkeyDeliveryMode::GlobalToggle => format!("Listening - press {label} again"),
-                HotkeyDeliveryMode::Disabled => "Listening...".to_string(),
-            };
-            (message, "live")
+impl Default for LowConfidenceRetryOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            score_threshold: LOW_CONFIDENCE_SCORE_THRESHOLD,
         }
-        "processing" => ("Transcribing...".to_string(), "working"),
-        "error" => (
-            if error
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-                .is_some()
-            {
-                "Dictation error - check status".to_string()
-            } else {
-                idle_pill_message(app)
-            },
-            if error
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-                .is_some()
-            {
-                "error"
-            } else {
-                "idle"
-            },
-        ),
-        _ => (idle_pill_message(app), "idle"),
-    };
+    }
+}
 
-    emit_pill_status(app, message, pill_state, true);
+#[derive(Clone, Copy, Debug)]
+struct AudioPrepOptions {
+    normalization_mode: AudioNormalizationMode,
+    normalization_target_level: f32,
+    trim_silence: bool,
+    // Threshold RMS when the noise gate is on.
+    noise_gate: Option<f32>,
 }
 
-fn emit_dictation_state(
-    app: &tauri::AppHandle,
-    state: &str,
-    error: Option<String>,
+#[derive(Clone, Debug)]
+struct RecordingArchiveOptions {
+    dir: PathBuf,
+    max_recordings: u32,
+    max_total_bytes: u64,
+    format: RecordingFormat,
+}
+
+#[derive(Clone, Debug)]
+struct TranscriptionOptions {
+    whisper: WhisperRunOptions,
+    // Saved beam_size/best_of; unset values fall back to the decoding model's default profile.
+    decode_overrides: DecodeOverrides,
+    audio: AudioPrepOptions,
+    trailing_punctuation: TrailingPunctuation,
+    auto_format: AutoFormatOptions,
+    low_confidence_retry: LowConfidenceRetryOptions,
+    // Opts into the model-default fast pass plus low-confidence retry instead of one pass.
+    two_pass_decode: bool,
+    min_confidence: Option<f32>,
+    chunk_seconds: u32,
+    // Defaults plus saved extras, uppercase.
+    artifact_tokens: Vec<String>,
+    // Empty when the profanity filter is off.
+    profanity_words: Vec<String>,
+    // None when dedup_repeats is off.
+    dedup_max_repeats: Option<usize>,
+    // Both empty when strip_fillers is off; entries are normalized (lowercase, single-spaced).
+    filler_words: Vec<String>,
+    filler_trailing_phrases: Vec<String>,
+    // Empty when spoken commands are off; longest phrases first.
+    spoken_commands: Vec<SpokenCommand>,
+    // Set only when partial_transcripts is on; the chunked path sends the text so far after
+    // every chunk but the last.
+    partial: Option<mpsc::Sender<PartialTranscript>>,
+    recordings: Option<RecordingArchiveOptions>,
+    // Set only when fallback_on_oom is on and a smaller installed model exists; used for one
+    // retry when whisper runs out of memory with the selected model.
+    oom_fallback: Option<PathBuf>,
+    // Saved fallback_on_oom; only picks the hint on an out-of-memory error, since `oom_fallback`
+    // stays unset when no smaller model is installed.
+    fallback_on_oom: bool,
+    // Set only for live, non-scratch captures with a nonzero last_recording_retention_seconds.
+    capture_cache: Option<LastCaptureCache>,
+}
+
+#[derive(Clone, Debug)]
+struct LastCaptureCache {
+    slot: Arc<Mutex<Option<CachedCapture>>>,
+    retention: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SpokenCommand {
+    // Lowercased words matched against whole transcript tokens.
+    phrase: Vec<String>,
+    replacement: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct RecordingEntry {
+    file_name: String,
+    path: String,
+    model_id: String,
+    recorded_at_ms: u64,
+    size_bytes: u64,
     transcript: Option<String>,
-    session_id: Option<u64>,
-) {
-    sync_pill_for_dictation_state(app, state, error.as_deref());
-    app.emit(
-        DICTATION_STATE_EVENT,
-        DictationStatePayload {
-            state: state.to_string(),
-            error,
-            transcript,
-            session_id,
-        },
-    )
-    .ok();
 }
 
-fn current_active_session_id(app: &tauri::AppHandle) -> Result<Option<u64>, String> {
-    let dictation = app.state::<DictationState>();
-    dictation
-        .active_recording
-        .lock()
-        .map_err(|_| "Failed to lock dictation state".to_string())
-        .map(|guard| guard.as_ref().map(|recording| recording.session_id))
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct WhisperDecodeProfile {
+    beam_size: u8,
+    best_of: u8,
 }
 
-fn dictation_is_running(app: &tauri::AppHandle) -> Result<bool, String> {
-    current_active_session_id(app).map(|value| value.is_some())
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct DecodeOverrides {
+    beam_size: Option<u8>,
+    best_of: Option<u8>,
 }
 
-fn dispatch_backend_hotkey_action(app: &tauri::AppHandle, action: BackendHotkeyAction) {
-    let handle = app.clone();
-    tauri::async_runtime::spawn(async move {
-        let result: Result<(), String> = match action {
-            BackendHotkeyAction::Toggle => match dictation_is_running(&handle) {
-                Ok(true) => stop_native_dictation_inner(handle.clone())
-                    .await
-                    .map(|_| ()),
-                Ok(false) => start_native_dictation_inner(&handle).map(|_| ()),
-                Err(error) => Err(error),
-            },
-            BackendHotkeyAction::HoldStart => match dictation_is_running(&handle) {
-                Ok(true) => Ok(()),
-                Ok(false) => start_native_dictation_inner(&handle).map(|_| ()),
-                Err(error) => Err(error),
-            },
-            BackendHotkeyAction::HoldStop => match dictation_is_running(&handle) {
-                Ok(true) => stop_native_dictation_inner(handle.clone())
-                    .await
-                    .map(|_| ()),
-                Ok(false) => Ok(()),
-                Err(error) => Err(error),
-            },
-        };
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DecodePath {
+    // Default: one pass with whisper-cli's own profile (or the saved overrides).
+    Single,
+    Fast,
+    Retry,
+}
 
-        if let Err(error) = result {
-            let trimmed = error.trim();
-            let benign =
-                trimmed == "Dictation already running." || trimmed == "Dictation is not running.";
-            if !benign {
-                log::warn!("Global hotkey action failed: {error}");
-                emit_dictation_state(&handle, "error", Some(error), None, None);
-            }
+impl DecodePath {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Fast => "fast",
+            Self::Retry => "retry",
         }
-    });
+    }
 }
 
-#[cfg(target_os = "macos")]
-fn pill_window_width_for_monitor(monitor: &tauri::Monitor) -> f64 {
-    let clamped_scale = monitor.scale_factor().clamp(1.0, 2.0);
-    PILL_WINDOW_MIN_WIDTH + (clamped_scale - 1.0) * (PILL_WINDOW_BASE_WIDTH - PILL_WINDOW_MIN_WIDTH)
+#[derive(Clone, Debug, PartialEq)]
+struct TranscriptionInfo {
+    decode_path: DecodePath,
+    fast_score: f32,
+    retry_score: Option<f32>,
+    audio_seconds: f32,
+    token_confidence: Option<f32>,
 }
 
-#[cfg(target_os = "macos")]
-fn create_pill_overlay_window_for_monitor(
-    app: &tauri::AppHandle,
-    label: &str,
-    monitor: &tauri::Monitor,
-) -> Result<(), String> {
-    if app.get_webview_window(label).is_some() {
-        return Ok(());
-    }
+struct TranscriptionOutcome {
+    transcript: String,
+    info: TranscriptionInfo,
+    warning: Option<String>,
+    // Set only for live captures; re-transcribed recordings have no capture to describe.
+    audio_info: Option<AudioInfoPayload>,
+    // The smaller model that produced the transcript after the selected one ran out of memory.
+    fallback_model: Option<PathBuf>,
+}
 
-    let work_area = monitor.work_area();
-    let work_x = work_area.position.x;
-    let work_y = work_area.position.y;
-    let work_w = work_area.size.width as i32;
-    let work_h = work_area.size.height as i32;
-    let width = pill_window_width_for_monitor(monitor);
-    let width_i = width as i32;
-    let height_i = PILL_WINDOW_HEIGHT as i32;
+// What the backend did with a live capture before whisper saw it. Levels are measured after
+// resampling and edge trimming, before normalization.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct AudioInfoPayload {
+    input_sample_rate: u32,
+    input_channels: u16,
+    resampled: bool,
+    captured_seconds: f32,
+    prepared_seconds: f32,
+    peak: f32,
+    rms: f32,
+}
 
-    let x = work_x + (work_w - width_i).max(0) / 2;
-    let y = work_y + (work_h - height_i - PILL_WINDOW_BOTTOM_MARGIN).max(0);
+// Result of `preview_capture`: the same preparation a dictation capture gets, without whisper.
+// `peak`/`rms` describe the prepared audio, or the untrimmed audio when a gate rejected it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct CapturePreviewPayload {
+    device_name: String,
+    input_sample_rate: u32,
+    input_channels: u16,
+    resampled: bool,
+    captured_seconds: f32,
+    prepared_seconds: f32,
+    trimmed_seconds: f32,
+    trim_silence: bool,
+    peak: f32,
+    rms: f32,
+    passes_speech_gate: bool,
+    rejection: Option<String>,
+}
 
-    let window =
-        tauri::WebviewWindowBuilder::new(app, label, tauri::WebviewUrl::App("pill.html".into()))
-            .title("dicktaint overlay")
-            .decorations(false)
-            .transparent(true)
-            .shadow(false)
-            .resizable(false)
-            .focusable(false)
-            .skip_taskbar(true)
-            .always_on_top(true)
-            .visible_on_all_workspaces(true)
-            .inner_size(width, PILL_WINDOW_HEIGHT)
-            .position(x as f64, y as f64)
-            .build()
-            .map_err(|e| format!("Failed to create overlay window '{label}': {e}"))?;
-
-    let _ = window.set_ignore_cursor_events(true);
-    let _ = window.set_always_on_top(true);
-    let _ = window.set_visible_on_all_workspaces(true);
-    Ok(())
-}
-
-#[cfg(target_os = "macos")]
-fn create_pill_overlay_windows(app: &tauri::AppHandle) -> Result<(), String> {
-    let monitors = app
-        .available_monitors()
-        .map_err(|e| format!("Failed to enumerate monitors for overlay pill: {e}"))?;
-    if monitors.is_empty() {
-        return Err("No monitors found while creating overlay pill windows.".to_string());
-    }
-
-    for (index, monitor) in monitors.iter().enumerate().take(MAX_PILL_WINDOWS) {
-        let label = format!("{PILL_WINDOW_LABEL_PREFIX}-{index}");
-        create_pill_overlay_window_for_monitor(app, &label, monitor)?;
-    }
-
-    Ok(())
+// `peak`/`rms` describe the raw capture; `too_quiet` uses the same gate as dictation.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct MicPlaybackPayload {
+    input_device_name: String,
+    output_device_name: String,
+    captured_seconds: f32,
+    peak: f32,
+    rms: f32,
+    too_quiet: bool,
 }
 
-#[cfg(not(target_os = "macos"))]
-fn create_pill_overlay_windows(_app: &tauri::AppHandle) -> Result<(), String> {
-    Ok(())
+enum CommandWaitError {
+    Spawn(std::io::Error),
+    TimedOut,
 }
 
-fn resolve_whisper_model_path(path: Option<&str>) -> Result<PathBuf, String> {
-    let raw = path
-    .map(str::trim)
-    .filter(|v| !v.is_empty())
-    .ok_or_else(|| {
-      "WHISPER_MODEL_PATH is not set. Point it to a local GGML Whisper model file (example: ggml-base.en.bin).".to_string()
-    })?;
-
-    let model_path = PathBuf::from(raw);
-    if !model_path.exists() {
-        return Err(format!(
-            "WHISPER_MODEL_PATH file not found: {}",
-            model_path.display()
-        ));
-    }
-
-    Ok(model_path)
+#[derive(Clone)]
+struct TriggerRuntimeDetails {
+    mode: HotkeyDeliveryMode,
+    status: String,
+    permission_hint: Option<String>,
 }
 
-fn canonicalize_trigger_modifier(token: &str) -> Option<&'static str> {
-    match token.to_ascii_lowercase().as_str() {
-        "cmdorctrl" | "commandorcontrol" | "mod" | "primary" => Some("CmdOrCtrl"),
-        "cmd" | "command" => Some("Cmd"),
-        "ctrl" | "control" => Some("Ctrl"),
-        "alt" | "option" => Some("Alt"),
-        "shift" => Some("Shift"),
-        "super" | "meta" | "win" | "windows" => Some("Super"),
-        _ => None,
-    }
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum HotkeyActivationMode {
+    #[default]
+    Single,
+    Double,
 }
 
-fn canonicalize_trigger_key(token: &str) -> Option<String> {
-    let trimmed = token.trim();
-    let single_char = {
-        let mut chars = trimmed.chars();
-        match (chars.next(), chars.next()) {
-            (Some(ch), None) if ch.is_ascii_alphanumeric() => Some(ch.to_ascii_uppercase()),
-            _ => None,
+impl HotkeyActivationMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Double => "double",
         }
-    };
-    if let Some(ch) = single_char {
-        return Some(ch.to_string());
-    }
-
-    let lower = trimmed.to_ascii_lowercase();
-    let special = match lower.as_str() {
-        "fn" | "function" | "globe" => Some("Fn"),
-        "space" => Some("Space"),
-        "tab" => Some("Tab"),
-        "enter" | "return" => Some("Enter"),
-        "escape" | "esc" => Some("Escape"),
-        "backspace" => Some("Backspace"),
-        "delete" | "del" => Some("Delete"),
-        "up" | "arrowup" => Some("Up"),
-        "down" | "arrowdown" => Some("Down"),
-        "left" | "arrowleft" => Some("Left"),
-        "right" | "arrowright" => Some("Right"),
-        "home" => Some("Home"),
-        "end" => Some("End"),
-        "pageup" => Some("PageUp"),
-        "pagedown" => Some("PageDown"),
-        "insert" => Some("Insert"),
-        _ => None,
-    };
-    if let Some(name) = special {
-        return Some(name.to_string());
     }
 
-    if lower.starts_with('f') {
-        let function_num = lower
-            .strip_prefix('f')
-            .and_then(|num| num.parse::<u8>().ok())?;
-        if (1..=24).contains(&function_num) {
-            return Some(format!("F{function_num}"));
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "single" => Some(Self::Single),
+            "double" | "double-tap" => Some(Self::Double),
+            _ => None,
         }
     }
+}
 
-    None
+// Fires on the release of the second short press. Any press longer than the window counts as a
+// hold, which cancels a pending first tap so tap-then-hold never reads as a double tap.
+#[derive(Default)]
+struct DoubleTapDetector {
+    press_started_at: Option<Instant>,
+    last_tap_released_at: Option<Instant>,
+    second_press_in_window: bool,
 }
 
-fn normalize_dictation_trigger(trigger: &str) -> Result<String, String> {
-    let trimmed = trigger.trim();
-    if trimmed.is_empty() {
-        return Err("Dictation trigger cannot be empty.".to_string());
-    }
-    if trimmed.len() > MAX_DICTATION_TRIGGER_LENGTH {
-        return Err(format!(
-            "Dictation trigger is too long (max {MAX_DICTATION_TRIGGER_LENGTH} characters)."
-        ));
+impl DoubleTapDetector {
+    fn press(&mut self, now: Instant, window: Duration) {
+        self.second_press_in_window = self
+            .last_tap_released_at
+            .is_some_and(|released_at| now.duration_since(released_at) <= window);
+        self.press_started_at = Some(now);
     }
 
-    let mut modifiers = HashSet::<String>::new();
-    let mut key: Option<String> = None;
-    for token in trimmed.split('+').map(str::trim) {
-        if token.is_empty() {
-            return Err("Dictation trigger contains an empty token.".to_string());
-        }
-
-        if let Some(modifier) = canonicalize_trigger_modifier(token) {
-            if key.is_some() {
-                return Err("Modifier keys must come before the main trigger key.".to_string());
-            }
-            modifiers.insert(modifier.to_string());
-            continue;
+    fn release(&mut self, now: Instant, window: Duration) -> bool {
+        let Some(pressed_at) = self.press_started_at.take() else {
+            return false;
+        };
+        let second_press_in_window = std::mem::take(&mut self.second_press_in_window);
+        if now.duration_since(pressed_at) > window {
+            self.last_tap_released_at = None;
+            return false;
         }
-
-        if key.is_some() {
-            return Err("Dictation trigger can only contain one main key.".to_string());
+        if second_press_in_window {
+            self.last_tap_released_at = None;
+            return true;
         }
-        key = Some(
-            canonicalize_trigger_key(token).ok_or_else(|| {
-                format!(
-                    "Unsupported trigger key '{token}'. Use Fn (macOS), letters/numbers, F1-F24, arrows, or common navigation keys."
-                )
-            })?,
-        );
+        self.last_tap_released_at = Some(now);
+        false
     }
+}
 
-    let key = key.ok_or_else(|| "Dictation trigger is missing its main key.".to_string())?;
-    if key == "Fn" {
-        if !modifiers.is_empty() {
-            return Err("Fn trigger must be used by itself.".to_string());
-        }
-        return Ok("Fn".to_string());
-    }
+// Reports a hold-to-talk release that came before `min_hold`: an accidental tap, discarded
+// rather than sent to whisper as a clip too short to transcribe.
+#[derive(Default)]
+struct HoldCancelDetector {
+    pressed_at: Option<Instant>,
+}
 
-    if modifiers.is_empty() {
-        return Err("Dictation trigger must include at least one modifier key (or use Fn by itself on macOS).".to_string());
-    }
-    if modifiers.contains("CmdOrCtrl") && (modifiers.contains("Cmd") || modifiers.contains("Ctrl"))
-    {
-        return Err("Use CmdOrCtrl by itself, or use Cmd/Ctrl explicitly.".to_string());
+impl HoldCancelDetector {
+    fn press(&mut self, now: Instant) {
+        self.pressed_at = Some(now);
     }
 
-    let order = ["CmdOrCtrl", "Cmd", "Ctrl", "Alt", "Shift", "Super"];
-    let mut parts: Vec<String> = order
-        .iter()
-        .filter(|name| modifiers.contains(**name))
-        .map(|name| (*name).to_string())
-        .collect();
-    parts.push(key);
-    Ok(parts.join("+"))
+    fn release(&mut self, now: Instant, min_hold: Duration) -> bool {
+        self.pressed_at
+            .take()
+            .is_some_and(|pressed_at| now.duration_since(pressed_at) < min_hold)
+    }
 }
 
-fn default_dictation_trigger() -> String {
-    normalize_dictation_trigger(DEFAULT_DICTATION_TRIGGER)
-        .unwrap_or_else(|_| DEFAULT_DICTATION_TRIGGER.to_string())
+struct HotkeyActivationState {
+    mode: HotkeyActivationMode,
+    window: Duration,
+    detector: DoubleTapDetector,
+    min_hold: Duration,
+    hold: HoldCancelDetector,
 }
 
-fn resolve_effective_dictation_trigger(settings: &LocalSettings) -> Option<String> {
-    if let Some(configured) = settings
-        .dictation_trigger
-        .as_deref()
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-    {
-        match normalize_dictation_trigger(configured) {
-            Ok(normalized) => return Some(normalized),
-            Err(error) => {
-                log::warn!("Ignoring invalid persisted dictation trigger '{configured}': {error}");
-            }
+impl Default for HotkeyActivationState {
+    fn default() -> Self {
+        Self {
+            mode: HotkeyActivationMode::Single,
+            window: Duration::from_millis(DEFAULT_DOUBLE_TAP_MS),
+            detector: DoubleTapDetector::default(),
+            min_hold: Duration::from_millis(DEFAULT_MIN_HOLD_MS),
+            hold: HoldCancelDetector::default(),
         }
     }
-
-    if matches!(settings.dictation_trigger_enabled, Some(false)) {
-        return None;
-    }
-
-    Some(default_dictation_trigger())
 }
 
-fn focused_field_insert_enabled(settings: &LocalSettings) -> bool {
-    matches!(settings.focused_field_insert_enabled, Some(true))
+#[derive(Default)]
+struct GlobalHotkeyState {
+    registered_trigger: Mutex<Option<String>>,
+    runtime_details: Mutex<TriggerRuntimeDetails>,
+    activation: Mutex<HotkeyActivationState>,
+    #[cfg(target_os = "macos")]
+    macos_fn_listener: Mutex<Option<MacFnGlobalListener>>,
+    #[cfg(target_os = "macos")]
+    macos_fn_local_monitor: Mutex<Option<MacFnLocalMonitor>>,
 }
 
 #[cfg(target_os = "macos")]
-fn macos_accessibility_permission_granted() -> bool {
-    unsafe { AXIsProcessTrusted() }
-}
-
-#[cfg(not(target_os = "macos"))]
-fn macos_accessibility_permission_granted() -> bool {
-    false
-}
+type CFAllocatorRef = *mut c_void;
+#[cfg(target_os = "macos")]
+type CFMachPortRef = *mut c_void;
+#[cfg(target_os = "macos")]
+type CFRunLoopRef = *mut c_void;
+#[cfg(target_os = "macos")]
+type CFRunLoopSourceRef = *mut c_void;
+#[cfg(target_os = "macos")]
+type CFStringRef = *const c_void;
+#[cfg(target_os = "macos")]
+type CGEventRef = *const c_void;
+#[cfg(target_os = "macos")]
+type CGEventTapProxy = *const c_void;
+#[cfg(target_os = "macos")]
+type CGEventMask = u64;
+#[cfg(target_os = "macos")]
+type CGEventFlags = u64;
 
 #[cfg(target_os = "macos")]
-fn open_accessibility_settings() -> Result<(), String> {
-    let status = Command::new("open")
-        .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility")
-        .status()
-        .map_err(|e| format!("Failed to open macOS Accessibility settings: {e}"))?;
-    if status.success() {
-        Ok(())
-    } else {
-        Err("Failed to open macOS Accessibility settings.".to_string())
-    }
-}
+const CG_EVENT_TAP_LOCATION_SESSION: u32 = 1;
+#[cfg(target_os = "macos")]
+const CG_EVENT_TAP_LOCATION_HID: u32 = 0;
+#[cfg(target_os = "macos")]
+const CG_EVENT_TAP_PLACEMENT_HEAD_INSERT: u32 = 0;
+#[cfg(target_os = "macos")]
+const CG_EVENT_TAP_OPTION_LISTEN_ONLY: u32 = 1;
+#[cfg(target_os = "macos")]
+const CG_EVENT_TYPE_FLAGS_CHANGED: u32 = 12;
+#[cfg(target_os = "macos")]
+const CG_EVENT_TYPE_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFF_FFFE;
+#[cfg(target_os = "macos")]
+const CG_EVENT_TYPE_TAP_DISABLED_BY_USER_INPUT: u32 = 0xFFFF_FFFF;
 
-fn focused_field_insert_permission_status(
-    enabled: bool,
-    prompt_if_missing: bool,
-) -> FocusedFieldInsertPermissionStatus {
-    #[cfg(target_os = "macos")]
-    {
-        let granted = macos_accessibility_permission_granted();
-        if granted {
-            return FocusedFieldInsertPermissionStatus {
-                granted: true,
-                status: if enabled {
-                    "Accessibility permission granted. Finished transcripts can be pasted into the focused field of other apps."
-                        .to_string()
-                } else {
-                    "Accessibility permission granted. Enable focused-field insertion to paste dictated text into other apps."
-                        .to_string()
-                },
-            };
-        }
-
-        if prompt_if_missing {
-            if let Err(error) = open_accessibility_settings() {
-                log::warn!("Failed to open Accessibility settings: {error}");
-            }
-        }
+#[cfg(target_os = "macos")]
+const MACOS_COMMAND_FLAG_MASK: CGEventFlags = 1 << 20;
+#[cfg(target_os = "macos")]
+const MACOS_FN_FLAG_MASK: CGEventFlags = 1 << 23;
+#[cfg(target_os = "macos")]
+const MACOS_NON_FN_MODIFIER_MASK: CGEventFlags = (1 << 17) | (1 << 18) | (1 << 19) | (1 << 20);
+#[cfg(target_os = "macos")]
+const KEYCODE_COMMAND: u16 = 0x37;
+#[cfg(target_os = "macos")]
+const KEYCODE_V: u16 = 0x09;
 
-        return FocusedFieldInsertPermissionStatus {
-            granted: false,
-            status: "Focused-field insertion needs Accessibility permission. Opened System Settings > Privacy & Security > Accessibility. Allow dicktaint, then retry the paste."
-                .to_string(),
-        };
-    }
+#[cfg(target_os = "macos")]
+type MacFnEventTapCallback =
+    unsafe extern "C" fn(CGEventTapProxy, u32, CGEventRef, *mut c_void) -> CGEventRef;
 
-    #[allow(unreachable_code)]
-    FocusedFieldInsertPermissionStatus {
-        granted: false,
-        status: if enabled {
-            "Focused-field insertion is currently supported on macOS desktop only.".to_string()
-        } else {
-            "Focused-field insertion is unavailable on this platform.".to_string()
-        },
-    }
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventTapCreate(
+        tap: u32,
+        place: u32,
+        options: u32,
+        events_of_interest: CGEventMask,
+        callback: MacFnEventTapCallback,
+        user_info: *mut c_void,
+    ) -> CFMachPortRef;
+    fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+    fn CGEventGetFlags(event: CGEventRef) -> CGEventFlags;
+    fn CGEventCreateKeyboardEvent(
+        source: *const c_void,
+        virtual_key: u16,
+        key_down: bool,
+    ) -> CGEventRef;
+    fn CGEventSetFlags(event: CGEventRef, flags: CGEventFlags);
+    fn CGEventPost(tap: u32, event: CGEventRef);
 }
 
-impl Default for TriggerRuntimeDetails {
-    fn default() -> Self {
-        Self {
-            mode: HotkeyDeliveryMode::Disabled,
-            status: "Hotkey disabled.".to_string(),
-            permission_hint: None,
-        }
-    }
-}
+#[cfg(target_os = "macos")]
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFAllocatorDefault: CFAllocatorRef;
+    static kCFRunLoopCommonModes: CFStringRef;
 
-fn global_toggle_status(trigger: &str) -> String {
-    format!("Press {trigger} anywhere to start or stop dictation.")
+    fn CFMachPortCreateRunLoopSource(
+        allocator: CFAllocatorRef,
+        port: CFMachPortRef,
+        order: isize,
+    ) -> CFRunLoopSourceRef;
+    fn CFMachPortInvalidate(port: CFMachPortRef);
+    fn CFRunLoopGetMain() -> CFRunLoopRef;
+    fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRunLoopRemoveSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRelease(cf: *const c_void);
 }
 
-fn global_hold_status(trigger: &str) -> String {
-    format!("Hold {trigger} anywhere to dictate, then release to transcribe.")
+#[cfg(target_os = "macos")]
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
 }
 
-fn focused_window_hold_status(trigger: &str) -> String {
-    format!(
-        "Hold {trigger} to dictate while dicktaint is focused. Grant Input Monitoring for global hold-to-talk."
-    )
-}
+#[cfg(target_os = "macos")]
+const IOHID_REQUEST_TYPE_LISTEN_EVENT: u32 = 1;
+#[cfg(target_os = "macos")]
+const IOHID_ACCESS_TYPE_GRANTED: u32 = 0;
+#[cfg(target_os = "macos")]
+const IOHID_ACCESS_TYPE_DENIED: u32 = 1;
 
-fn fn_permission_hint() -> String {
-    "System Settings > Privacy & Security > Input Monitoring: allow dicktaint (or Terminal while running tauri:dev), then relaunch dicktaint.".to_string()
+#[cfg(target_os = "macos")]
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOHIDCheckAccess(request_type: u32) -> u32;
 }
 
-fn set_trigger_runtime_details(
-    hotkey_state: &GlobalHotkeyState,
-    details: TriggerRuntimeDetails,
-) -> Result<(), String> {
-    let mut guard = hotkey_state
-        .runtime_details
-        .lock()
-        .map_err(|_| "Failed to lock dictation trigger runtime details".to_string())?;
-    *guard = details;
-    Ok(())
+#[cfg(target_os = "macos")]
+struct MacFnCallbackContext {
+    app: tauri::AppHandle,
+    enabled: AtomicBool,
+    fn_down: AtomicBool,
+    tap: AtomicPtr<c_void>,
 }
 
-fn current_trigger_runtime_details(
-    hotkey_state: &GlobalHotkeyState,
-) -> Result<TriggerRuntimeDetails, String> {
-    hotkey_state
-        .runtime_details
-        .lock()
-        .map_err(|_| "Failed to lock dictation trigger runtime details".to_string())
-        .map(|guard| guard.clone())
+#[cfg(target_os = "macos")]
+struct MacFnGlobalListener {
+    tap: CFMachPortRef,
+    source: CFRunLoopSourceRef,
+    callback_ctx: Arc<MacFnCallbackContext>,
+    callback_ctx_raw: *const MacFnCallbackContext,
 }
 
-fn runtime_details_for_trigger(
-    trigger: Option<&str>,
-    mode: HotkeyDeliveryMode,
-) -> TriggerRuntimeDetails {
-    let normalized = trigger.map(str::trim).filter(|value| !value.is_empty());
-    match (normalized, mode) {
-        (Some(value), HotkeyDeliveryMode::GlobalToggle) => TriggerRuntimeDetails {
-            mode: HotkeyDeliveryMode::GlobalToggle,
-            status: global_toggle_status(value),
-            permission_hint: None,
-        },
-        (Some(value), HotkeyDeliveryMode::GlobalHold) => TriggerRuntimeDetails {
-            mode: HotkeyDeliveryMode::GlobalHold,
-            status: global_hold_status(value),
-            permission_hint: None,
-        },
-        (Some(value), HotkeyDeliveryMode::FocusedWindowHold) => TriggerRuntimeDetails {
-            mode: HotkeyDeliveryMode::FocusedWindowHold,
-            status: focused_window_hold_status(value),
-            permission_hint: Some(fn_permission_hint()),
-        },
-        _ => TriggerRuntimeDetails::default(),
-    }
-}
+#[cfg(target_os = "macos")]
+unsafe impl Send for MacFnGlobalListener {}
+#[cfg(target_os = "macos")]
+unsafe impl Sync for MacFnGlobalListener {}
 
-fn onboarding_runtime_details(
-    trigger: Option<&str>,
-    registered_trigger: Option<&str>,
-    registered_runtime: Option<&TriggerRuntimeDetails>,
-) -> TriggerRuntimeDetails {
-    let normalized = trigger.map(str::trim).filter(|value| !value.is_empty());
-    if normalized.is_none() {
-        return TriggerRuntimeDetails::default();
-    }
+#[cfg(target_os = "macos")]
+impl MacFnGlobalListener {
+    fn new(app: &tauri::AppHandle) -> Result<Self, String> {
+        let callback_ctx = Arc::new(MacFnCallbackContext {
+            app: app.clone(),
+            enabled: AtomicBool::new(false),
+            fn_down: AtomicBool::new(false),
+            tap: AtomicPtr::new(std::ptr::null_mut()),
+        });
+        let callback_ctx_raw = Arc::into_raw(Arc::clone(&callback_ctx));
 
-    if normalized == registered_trigger {
-        if let Some(runtime) = registered_runtime {
-            return runtime.clone();
+        let event_mask = 1_u64 << CG_EVENT_TYPE_FLAGS_CHANGED;
+        let tap = unsafe {
+            CGEventTapCreate(
+                CG_EVENT_TAP_LOCATION_SESSION,
+                CG_EVENT_TAP_PLACEMENT_HEAD_INSERT,
+                CG_EVENT_TAP_OPTION_LISTEN_ONLY,
+                event_mask,
+                macos_fn_event_tap_callback,
+                callback_ctx_raw as *mut c_void,
+            )
+        };
+        if tap.is_null() {
+            unsafe {
+                drop(Arc::from_raw(callback_ctx_raw));
+            }
+            return Err("Global Fn listener unavailable. macOS may be blocking event taps. Allow Input Monitoring for this app/terminal in System Settings > Privacy & Security > Input Monitoring.".to_string());
         }
-    }
 
-    #[cfg(target_os = "macos")]
-    let mode = if normalized == Some("Fn") {
-        HotkeyDeliveryMode::FocusedWindowHold
-    } else {
-        HotkeyDeliveryMode::GlobalToggle
-    };
+        callback_ctx
+            .tap
+            .store(tap.cast::<c_void>(), Ordering::SeqCst);
 
-    #[cfg(not(target_os = "macos"))]
-    let mode = HotkeyDeliveryMode::GlobalToggle;
+        let source = unsafe { CFMachPortCreateRunLoopSource(kCFAllocatorDefault, tap, 0) };
+        if source.is_null() {
+            unsafe {
+                CFMachPortInvalidate(tap);
+                CFRelease(tap as *const c_void);
+                drop(Arc::from_raw(callback_ctx_raw));
+            }
+            return Err(
+                "Failed to create macOS run loop source for global Fn listener.".to_string(),
+            );
+        }
 
-    runtime_details_for_trigger(normalized, mode)
-}
+        unsafe {
+            let run_loop = CFRunLoopGetMain();
+            CFRunLoopAddSource(run_loop, source, kCFRunLoopCommonModes);
+            CGEventTapEnable(tap, true);
+        }
 
-fn dictation_trigger_payload(
-    settings: &LocalSettings,
-    runtime: TriggerRuntimeDetails,
-) -> DictationTriggerPayload {
-    DictationTriggerPayload {
-        trigger: resolve_effective_dictation_trigger(settings),
-        default_trigger: default_dictation_trigger(),
-        trigger_mode: runtime.mode.as_str().to_string(),
-        trigger_status: runtime.status,
-        trigger_permission_hint: runtime.permission_hint,
+        Ok(Self {
+            tap,
+            source,
+            callback_ctx,
+            callback_ctx_raw,
+        })
     }
-}
 
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
-fn shortcut_from_dictation_trigger(trigger: &str) -> Result<Shortcut, String> {
-    let normalized = normalize_dictation_trigger(trigger)?;
-    let accelerator = normalized
-        .replace("CmdOrCtrl", "CommandOrControl")
-        .replace("Cmd", "Command");
-    Shortcut::from_str(&accelerator)
-        .map_err(|e| format!("Could not parse hotkey '{normalized}' for global registration: {e}"))
+    fn set_enabled(&self, enabled: bool) {
+        self.callback_ctx.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.callback_ctx.fn_down.store(false, Ordering::SeqCst);
+        }
+    }
 }
 
-fn set_registered_hotkey_state(
-    hotkey_state: &GlobalHotkeyState,
-    next: Option<String>,
-) -> Result<(), String> {
-    let mut guard = hotkey_state
-        .registered_trigger
-        .lock()
-        .map_err(|_| "Failed to lock global hotkey state".to_string())?;
-    *guard = next;
-    Ok(())
-}
+#[cfg(target_os = "macos")]
+impl Drop for MacFnGlobalListener {
+    fn drop(&mut self) {
+        unsafe {
+            let run_loop = CFRunLoopGetMain();
+            CFRunLoopRemoveSource(run_loop, self.source, kCFRunLoopCommonModes);
+            CFRelease(self.source as *const c_void);
 
-fn update_hotkey_state(
-    hotkey_state: &GlobalHotkeyState,
-    trigger: Option<String>,
-    runtime: TriggerRuntimeDetails,
-) -> Result<(), String> {
-    set_registered_hotkey_state(hotkey_state, trigger)?;
-    set_trigger_runtime_details(hotkey_state, runtime)
-}
+            CFMachPortInvalidate(self.tap);
+            CFRelease(self.tap as *const c_void);
 
-fn current_registered_hotkey(hotkey_state: &GlobalHotkeyState) -> Result<Option<String>, String> {
-    hotkey_state
-        .registered_trigger
-        .lock()
-        .map_err(|_| "Failed to lock global hotkey state".to_string())
-        .map(|guard| guard.clone())
+            drop(Arc::from_raw(self.callback_ctx_raw));
+        }
+    }
 }
 
+// In-app fallback for when Input Monitoring blocks the event tap: AppKit still delivers our own
+// flagsChanged events, so Fn works while a dicktaint window is key. Installed once at startup on
+// the main thread and toggled like the global listener; it is never removed.
 #[cfg(target_os = "macos")]
-fn should_register_global_hotkey(trigger: &str) -> bool {
-    trigger != "Fn"
-}
-
-#[cfg(not(target_os = "macos"))]
-fn should_register_global_hotkey(_trigger: &str) -> bool {
-    true
+struct MacFnLocalMonitor {
+    _monitor: Retained<AnyObject>,
+    callback_ctx: Arc<MacFnCallbackContext>,
 }
 
 #[cfg(target_os = "macos")]
-fn set_macos_fn_listener_enabled(
-    app: &tauri::AppHandle,
-    hotkey_state: &GlobalHotkeyState,
-    enabled: bool,
-) -> Result<(), String> {
-    let mut guard = hotkey_state
-        .macos_fn_listener
-        .lock()
-        .map_err(|_| "Failed to lock macOS Fn listener state".to_string())?;
+unsafe impl Send for MacFnLocalMonitor {}
+#[cfg(target_os = "macos")]
+unsafe impl Sync for MacFnLocalMonitor {}
 
-    if enabled {
-        if guard.is_none() {
-            *guard = Some(MacFnGlobalListener::new(app)?);
+#[cfg(target_os = "macos")]
+impl MacFnLocalMonitor {
+    fn install(app: &tauri::AppHandle) -> Result<Self, String> {
+        let callback_ctx = Arc::new(MacFnCallbackContext {
+            app: app.clone(),
+            enabled: AtomicBool::new(false),
+            fn_down: AtomicBool::new(false),
+            tap: AtomicPtr::new(std::ptr::null_mut()),
+        });
+        let handler_ctx = Arc::clone(&callback_ctx);
+        let handler = RcBlock::new(move |event: NonNull<NSEvent>| -> *mut NSEvent {
+            let flags = unsafe { event.as_ref() }.modifierFlags().0 as CGEventFlags;
+            route_macos_fn_flags(&handler_ctx, flags);
+            event.as_ptr()
+        });
+        let monitor = unsafe {
+            NSEvent::addLocalMonitorForEventsMatchingMask_handler(
+                NSEventMask::FlagsChanged,
+                &handler,
+            )
         }
+        .ok_or_else(|| "Failed to install the in-app Fn key monitor.".to_string())?;
+
+        Ok(Self {
+            _monitor: monitor,
+            callback_ctx,
+        })
     }
 
-    if let Some(listener) = guard.as_ref() {
-        listener.set_enabled(enabled);
+    fn set_enabled(&self, enabled: bool) {
+        self.callback_ctx.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.callback_ctx.fn_down.store(false, Ordering::SeqCst);
+        }
     }
 
-    Ok(())
+    fn is_enabled(&self) -> bool {
+        self.callback_ctx.enabled.load(Ordering::SeqCst)
+    }
 }
 
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
-fn apply_registered_hotkey(
-    app: &tauri::AppHandle,
-    hotkey_state: &GlobalHotkeyState,
-    trigger: Option<&str>,
-) -> Result<TriggerRuntimeDetails, String> {
-    let next = match trigger.map(str::trim).filter(|value| !value.is_empty()) {
-        Some(value) => Some(normalize_dictation_trigger(value)?),
-        None => None,
-    };
-    let previous = current_registered_hotkey(hotkey_state)?;
+// NSEventModifierFlags and CGEventFlags share bit positions, so both listeners decode the same way.
+#[cfg(target_os = "macos")]
+fn route_macos_fn_flags(callback_ctx: &MacFnCallbackContext, flags: CGEventFlags) {
+    if !callback_ctx.enabled.load(Ordering::Relaxed) {
+        return;
+    }
 
-    if previous == next && next.as_deref() != Some("Fn") {
-        return current_trigger_runtime_details(hotkey_state);
+    let fn_down = (flags & MACOS_FN_FLAG_MASK) != 0;
+    let was_fn_down = callback_ctx.fn_down.swap(fn_down, Ordering::Relaxed);
+
+    let has_non_fn_modifiers = (flags & MACOS_NON_FN_MODIFIER_MASK) != 0;
+    if fn_down != was_fn_down && !has_non_fn_modifiers {
+        route_hotkey_edge(&callback_ctx.app, fn_down, true);
     }
+}
 
-    #[cfg(target_os = "macos")]
-    if previous.as_deref() == Some("Fn") {
-        if let Err(error) = set_macos_fn_listener_enabled(app, hotkey_state, false) {
-            log::warn!("Failed to disable global Fn listener: {error}");
-        }
+#[cfg(target_os = "macos")]
+unsafe extern "C" fn macos_fn_event_tap_callback(
+    _proxy: CGEventTapProxy,
+    event_type: u32,
+    event: CGEventRef,
+    user_info: *mut c_void,
+) -> CGEventRef {
+    if user_info.is_null() {
+        return event;
     }
 
-    if let Some(previous_trigger) = previous.as_deref() {
-        if should_register_global_hotkey(previous_trigger) {
-            let previous_shortcut = shortcut_from_dictation_trigger(previous_trigger)?;
-            app.global_shortcut()
-                .unregister(previous_shortcut)
-                .map_err(|e| {
-                    format!("Failed to unregister previous global hotkey '{previous_trigger}': {e}")
-                })?;
+    let callback_ctx = &*(user_info as *const MacFnCallbackContext);
+    if event_type == CG_EVENT_TYPE_TAP_DISABLED_BY_TIMEOUT
+        || event_type == CG_EVENT_TYPE_TAP_DISABLED_BY_USER_INPUT
+    {
+        let tap = callback_ctx.tap.load(Ordering::Relaxed);
+        if !tap.is_null() {
+            CGEventTapEnable(tap.cast::<c_void>(), true);
         }
+        callback_ctx.fn_down.store(false, Ordering::Relaxed);
+        return event;
     }
 
-    if let Some(next_trigger) = next.as_deref() {
-        if should_register_global_hotkey(next_trigger) {
-            let next_shortcut = shortcut_from_dictation_trigger(next_trigger)?;
-            if let Err(error) = app.global_shortcut().register(next_shortcut) {
-                if let Some(previous_trigger) = previous.as_deref() {
-                    if should_register_global_hotkey(previous_trigger) {
-                        if let Ok(previous_shortcut) =
-                            shortcut_from_dictation_trigger(previous_trigger)
-                        {
-                            if let Err(recovery_error) =
-                                app.global_shortcut().register(previous_shortcut)
-                            {
-                                update_hotkey_state(
-                                    hotkey_state,
-                                    None,
-                                    TriggerRuntimeDetails::default(),
-                                )?;
-                                return Err(format!(
-                                    "Could not register global hotkey '{next_trigger}': {error}. Also failed to restore previous hotkey '{previous_trigger}': {recovery_error}"
-                                ));
-                            }
-                            update_hotkey_state(
-                                hotkey_state,
-                                Some(previous_trigger.to_string()),
-                                runtime_details_for_trigger(
-                                    Some(previous_trigger),
-                                    HotkeyDeliveryMode::GlobalToggle,
-                                ),
-                            )?;
-                            #[cfg(target_os = "macos")]
-                            if previous_trigger == "Fn" {
-                                if let Err(listener_error) =
-                                    set_macos_fn_listener_enabled(app, hotkey_state, true)
-                                {
-                                    update_hotkey_state(
-                                        hotkey_state,
-                                        Some(previous_trigger.to_string()),
-                                        runtime_details_for_trigger(
-                                            Some(previous_trigger),
-                                            HotkeyDeliveryMode::FocusedWindowHold,
-                                        ),
-                                    )?;
-                                    log::warn!(
-                                        "Failed to re-enable global Fn listener after hotkey restore: {listener_error}"
-                                    );
-                                }
-                            }
-                        } else {
-                            update_hotkey_state(
-                                hotkey_state,
-                                None,
-                                TriggerRuntimeDetails::default(),
-                            )?;
-                        }
-                    } else {
-                        #[cfg(target_os = "macos")]
-                        let restored_runtime = if previous_trigger == "Fn" {
-                            match set_macos_fn_listener_enabled(app, hotkey_state, true) {
-                                Ok(()) => runtime_details_for_trigger(
-                                    Some(previous_trigger),
-                                    HotkeyDeliveryMode::GlobalHold,
-                                ),
-                                Err(listener_error) => {
-                                    log::warn!(
-                                        "Failed to re-enable global Fn listener after hotkey restore: {listener_error}"
-                                    );
-                                    runtime_details_for_trigger(
-                                        Some(previous_trigger),
-                                        HotkeyDeliveryMode::FocusedWindowHold,
-                                    )
-                                }
-                            }
-                        } else {
-                            runtime_details_for_trigger(
-                                Some(previous_trigger),
-                                HotkeyDeliveryMode::GlobalToggle,
-                            )
-                        };
+    if event.is_null() || event_type != CG_EVENT_TYPE_FLAGS_CHANGED {
+        return event;
+    }
 
-                        #[cfg(not(target_os = "macos"))]
-                        let restored_runtime = runtime_details_for_trigger(
-                            Some(previous_trigger),
-                            HotkeyDeliveryMode::GlobalToggle,
-                        );
+    route_macos_fn_flags(callback_ctx, CGEventGetFlags(event));
+    event
+}
 
-                        update_hotkey_state(
-                            hotkey_state,
-                            Some(previous_trigger.to_string()),
-                            restored_runtime,
-                        )?;
-                    }
-                } else {
-                    update_hotkey_state(hotkey_state, None, TriggerRuntimeDetails::default())?;
-                }
-                return Err(format!(
-                    "Could not register global hotkey '{next_trigger}': {error}"
-                ));
-            }
-        }
-    }
+#[derive(Serialize)]
+struct DeviceProfile {
+    total_memory_gb: u64,
+    logical_cpu_cores: usize,
+    architecture: String,
+    // Vector extensions whisper.cpp benefits from, as detected at runtime.
+    cpu_features: Vec<&'static str>,
+    simd_support: String,
+    os: String,
+}
+
+#[derive(Serialize)]
+struct ModelRecommendationPayload {
+    ram_gb: u64,
+    logical_cpu_cores: usize,
+    recommended_model_id: Option<String>,
+    recommended_display_name: Option<String>,
+    rationale: String,
+    runnable_model_ids: Vec<String>,
+    comfortable_model_ids: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ModelBenchmarkResult {
+    model_id: String,
+    audio_seconds: f32,
+    processing_seconds: f32,
+    // Audio seconds per processing second; above 1.0 means faster than real time.
+    real_time_factor: f32,
+    transcript: String,
+    word_accuracy: f32,
+    matches_expected: bool,
+    measured_at_ms: u64,
+}
+
+// A user-supplied ggml model copied into the models directory. `source_path` records where it was
+// imported from and is informational only.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ImportedModel {
+    id: String,
+    display_name: String,
+    source_path: String,
+    added_at_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SelfTestStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+impl SelfTestStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Fail => "fail",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SelfTestStage {
+    name: &'static str,
+    status: &'static str,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SelfTestReport {
+    passed: bool,
+    stages: Vec<SelfTestStage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WhisperCliCandidateStatus {
+    Ok,
+    NotFound,
+    NotExecutable,
+    WrongHelpOutput,
+    Placeholder,
+}
+
+impl WhisperCliCandidateStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::NotFound => "not_found",
+            Self::NotExecutable => "not_executable",
+            Self::WrongHelpOutput => "wrong_help_output",
+            Self::Placeholder => "placeholder",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WhisperCliCandidateReport {
+    candidate: String,
+    resolved_path: Option<String>,
+    status: &'static str,
+    detail: String,
+    probe_summary: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WhisperCliDiagnosis {
+    active_path: String,
+    candidates: Vec<WhisperCliCandidateReport>,
+}
+
+#[derive(Serialize)]
+struct DebugReport {
+    generated_at_ms: u64,
+    app_version: &'static str,
+    device: DeviceProfile,
+    whisper_cli_path: String,
+    whisper_cli_error: Option<String>,
+    selected_model_id: Option<String>,
+    model_path: Option<String>,
+    model_error: Option<String>,
+    dictation_trigger: Option<String>,
+    dictation_trigger_mode: String,
+    dictation_trigger_status: String,
+    audio_host: String,
+    settings_path: String,
+    models_dir: String,
+    recordings_dir: String,
+    // App captured as the paste target when the latest session started (macOS only).
+    insert_target_app: Option<String>,
+    settings: LocalSettings,
+    saved_path: Option<String>,
+}
+
+// `source` is `frontmost` when another app was in front, or `last_session` when dicktaint was
+// frontmost and the test fell back to the latest session's paste target.
+#[derive(Clone, Debug, Serialize)]
+struct InsertTestPayload {
+    target_app: String,
+    target_pid: i32,
+    source: &'static str,
+    marker: &'static str,
+    delay_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct RunnabilityCheck {
+    status: CheckStatus,
+    detail: String,
+}
+
+// `verdict` is the worst of the three checks; `likely_runnable` is true unless RAM or CPU fails.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct ModelRunnability {
+    verdict: CheckStatus,
+    summary: String,
+    ram: RunnabilityCheck,
+    cpu: RunnabilityCheck,
+    disk: RunnabilityCheck,
+}
+
+#[derive(Serialize)]
+struct DictationModelOption {
+    id: String,
+    display_name: String,
+    whisper_ref: String,
+    file_name: String,
+    path: String,
+    installed: bool,
+    likely_runnable: bool,
+    runnability: ModelRunnability,
+    recommended: bool,
+    approx_size_gb: f32,
+    estimated_real_time_factor: f32,
+    actual_size_bytes: Option<u64>,
+    size_suspect: bool,
+    min_ram_gb: u64,
+    recommended_ram_gb: u64,
+    speed_note: String,
+    quality_note: String,
+    benchmark: Option<ModelBenchmarkResult>,
+    imported: bool,
+    // Set for imported models only.
+    source_path: Option<String>,
+    added_at_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct DictationOnboardingPayload {
+    onboarding_required: bool,
+    selected_model_id: Option<String>,
+    selected_model_path: Option<String>,
+    selected_model_exists: bool,
+    available_input_devices: Vec<DictationInputDevice>,
+    preferred_input_device: Option<String>,
+    audio_host: String,
+    dictation_trigger: Option<String>,
+    default_dictation_trigger: String,
+    fn_trigger_supported: bool,
+    fn_listener_mode: String,
+    dictation_trigger_mode: String,
+    dictation_trigger_status: String,
+    dictation_trigger_permission_hint: Option<String>,
+    focused_field_insert_enabled: bool,
+    output_mode: String,
+    recommendation_bias: String,
+    insert_delay_ms: u64,
+    focused_field_insert_permission_granted: bool,
+    focused_field_insert_permission_status: String,
+    whisper_cli_available: bool,
+    whisper_cli_path: String,
+    whisper_cli_path_override: Option<String>,
+    whisper_cli_is_placeholder: bool,
+    whisper_cli_placeholder_path: Option<String>,
+    whisper_acceleration: String,
+    whisper_timeout_seconds: u64,
+    whisper_threads: WhisperThreadsPayload,
+    decode_overrides: DecodeOverridesPayload,
+    show_progress: bool,
+    partial_transcripts: bool,
+    fallback_on_oom: bool,
+    debug_audio_info: bool,
+    keep_whisper_warm: bool,
+    chunk_seconds: u32,
+    trim_silence: bool,
+    noise_gate: NoiseGatePayload,
+    extra_whisper_args: Vec<String>,
+    extra_whisper_cli_paths: Vec<String>,
+    hotkey_activation: HotkeyActivationPayload,
+    audio_normalization: AudioNormalizationPayload,
+    memory_guard: MemoryGuardPayload,
+    model_download_mirror: ModelDownloadMirrorPayload,
+    auto_format: AutoFormatPayload,
+    low_confidence_retry: LowConfidenceRetryPayload,
+    two_pass_decode: bool,
+    min_confidence: Option<f32>,
+    profanity_filter: ProfanityFilterPayload,
+    dedup_repeats: DedupRepeatsPayload,
+    sound_cues: SoundCuesPayload,
+    min_transcribe_seconds: f32,
+    strip_fillers: FillerStrippingPayload,
+    spoken_commands: SpokenCommandsPayload,
+    command_mode: bool,
+    last_recording_retention_seconds: u64,
+    transcript_artifact_tokens: Vec<String>,
+    pill_overlay: PillOverlayPayload,
+    downmix_mode: String,
+    preserve_channels: u16,
+    preferred_sample_rate: Option<u32>,
+    permissions: PermissionsPayload,
+    trailing_punctuation: String,
+    output_suffix: String,
+    insert_wrap: String,
+    recording_archive: RecordingArchivePayload,
+    models_dir: String,
+    device: DeviceProfile,
+    models: Vec<DictationModelOption>,
+}
+
+#[derive(Serialize)]
+struct TranscriptionInfoPayload {
+    session_id: u64,
+    model_id: String,
+    decode_path: String,
+    fast_score: f32,
+    retry_score: Option<f32>,
+    retried: bool,
+    audio_seconds: f32,
+    token_confidence: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct MicrophonePermissionPayload {
+    granted: bool,
+    status: String,
+    input_device_name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PermissionState {
+    Granted,
+    #[cfg(any(target_os = "macos", test))]
+    Denied,
+    #[cfg(any(target_os = "macos", test))]
+    NotDetermined,
+    #[cfg(any(target_os = "macos", test))]
+    Restricted,
+    // macOS only reports trusted / not trusted for Accessibility, so "never asked"
+    // and "denied" cannot be told apart there.
+    NotGranted,
+    Unavailable,
+}
+
+impl PermissionState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Granted => "granted",
+            #[cfg(any(target_os = "macos", test))]
+            Self::Denied => "denied",
+            #[cfg(any(target_os = "macos", test))]
+            Self::NotDetermined => "not-determined",
+            #[cfg(any(target_os = "macos", test))]
+            Self::Restricted => "restricted",
+            Self::NotGranted => "not-granted",
+            Self::Unavailable => "unavailable",
+        }
+    }
+
+    fn blocks_use(&self) -> bool {
+        match self {
+            #[cfg(any(target_os = "macos", test))]
+            Self::Denied | Self::Restricted => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PermissionKind {
+    Microphone,
+    InputMonitoring,
+    Accessibility,
+}
+
+impl PermissionKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "microphone" | "mic" => Some(Self::Microphone),
+            "input-monitoring" | "listen-event" => Some(Self::InputMonitoring),
+            "accessibility" => Some(Self::Accessibility),
+            _ => None,
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn settings_pane(&self) -> &'static str {
+        match self {
+            Self::Microphone => "Privacy_Microphone",
+            Self::InputMonitoring => "Privacy_ListenEvent",
+            Self::Accessibility => "Privacy_Accessibility",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PermissionsPayload {
+    microphone: String,
+    input_monitoring: String,
+    accessibility: String,
+    can_record: bool,
+}
+
+#[derive(Serialize)]
+struct ModelPreloadPayload {
+    model_path: String,
+    // True when this model was already preloaded (or a preload is still running); nothing ran.
+    already_warm: bool,
+    // Whether the preload went to the kept-warm whisper-server instead of a one-off whisper-cli.
+    warm_server: bool,
+    elapsed_ms: u64,
+}
+
+#[derive(Serialize)]
+struct PillOverlayPayload {
+    position: String,
+    margin: u32,
+    enabled: bool,
+    visibility: String,
+}
+
+#[derive(Serialize)]
+struct AutoFormatPayload {
+    enabled: bool,
+    append_period: bool,
+}
+
+#[derive(Serialize)]
+struct WhisperThreadsPayload {
+    threads: Option<usize>,
+    max_threads: usize,
+    battery_saver: bool,
+    on_battery: Option<bool>,
+    effective_threads: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct DecodeOverridesPayload {
+    beam_size: Option<u8>,
+    best_of: Option<u8>,
+    effective_beam_size: u8,
+    effective_best_of: u8,
+}
+
+#[derive(Serialize)]
+struct LowConfidenceRetryPayload {
+    enabled: bool,
+    threshold: f32,
+}
+
+#[derive(Serialize)]
+struct NoiseGatePayload {
+    enabled: bool,
+    threshold: f32,
+}
+
+#[derive(Serialize)]
+struct ProfanityFilterPayload {
+    enabled: bool,
+    words: Vec<String>,
+    using_default_words: bool,
+}
+
+#[derive(Serialize)]
+struct SoundCuesPayload {
+    enabled: bool,
+    disabled_cues: Vec<String>,
+    available_cues: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct DedupRepeatsPayload {
+    enabled: bool,
+    max_repeats: u32,
+}
+
+#[derive(Serialize)]
+struct FillerStrippingPayload {
+    enabled: bool,
+    words: Vec<String>,
+    trailing_phrases: Vec<String>,
+    using_default_words: bool,
+    using_default_phrases: bool,
+}
+
+#[derive(Serialize)]
+struct SpokenCommandsPayload {
+    enabled: bool,
+    locale: String,
+    locales: Vec<String>,
+    // Effective map: the locale defaults with custom entries applied.
+    commands: BTreeMap<String, String>,
+    custom_commands: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct MemoryGuardPayload {
+    mode: String,
+    margin_mb: u64,
+}
+
+// Lifetime totals, only accumulated while `usage_stats` is on.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct UsageTotals {
+    words: u64,
+    audio_seconds: f64,
+    sessions: u64,
+}
+
+#[derive(Serialize)]
+struct UsageStatsPayload {
+    enabled: bool,
+    words: u64,
+    audio_seconds: f64,
+    sessions: u64,
+}
+
+#[derive(Serialize)]
+struct AudioNormalizationPayload {
+    mode: String,
+    target_level: f32,
+    target_rms: f32,
+}
+
+#[derive(Serialize)]
+struct SupportedTriggerKeysPayload {
+    modifiers: Vec<String>,
+    keys: Vec<String>,
+    // Keys that must be used without modifiers; every other key needs at least one.
+    standalone_keys: Vec<String>,
+    fn_trigger_supported: bool,
+}
+
+#[derive(Serialize)]
+struct DictationTriggerPayload {
+    trigger: Option<String>,
+    default_trigger: String,
+    fn_trigger_supported: bool,
+    fn_listener_mode: String,
+    trigger_mode: String,
+    trigger_status: String,
+    trigger_permission_hint: Option<String>,
+}
+
+#[derive(Serialize)]
+struct DictationTriggerValidationPayload {
+    // Normalized form of the candidate; `None` when it did not parse.
+    trigger: Option<String>,
+    valid: bool,
+    available: bool,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct FocusedFieldInsertPayload {
+    // `paste` when finished transcripts go into the focused app, `event` when they are only shown.
+    mode: &'static str,
+    enabled: bool,
+    permission_granted: bool,
+    permission_status: String,
+}
+
+#[derive(Clone)]
+struct FocusedFieldInsertPermissionStatus {
+    granted: bool,
+    status: String,
+}
+
+#[derive(Clone, Serialize)]
+struct DictationInputDevice {
+    name: String,
+    is_default: bool,
+    // Captures system/app output instead of a microphone.
+    loopback: bool,
+    label: String,
+}
+
+#[derive(Serialize)]
+struct AudioHostOption {
+    name: String,
+    is_default: bool,
+    // Compiled in but not usable right now (e.g. no JACK server running).
+    available: bool,
+}
+
+#[derive(Serialize)]
+struct AudioHostsPayload {
+    hosts: Vec<AudioHostOption>,
+    // Saved choice; `None` means the platform default.
+    selected: Option<String>,
+    // Host capture actually uses after falling back.
+    active: String,
+}
+
+#[derive(Serialize)]
+struct DictationModelSelection {
+    selected_model_id: String,
+    selected_model_path: String,
+    installed: bool,
+    // Base URL the model was fetched from; None when the file was already on disk.
+    download_source: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ModelDownloadMirrorPayload {
+    custom_mirror: Option<String>,
+    repo: String,
+    revision: String,
+    sources: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct DictationModelDeletion {
+    deleted_model_id: String,
+    selected_model_id: Option<String>,
+    selected_model_path: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct DeletedModelFile {
+    model_id: String,
+    path: String,
+    size_bytes: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct UndeletedModelFile {
+    model_id: String,
+    path: String,
+    reason: String,
+}
+
+// Failures do not stop the sweep; every catalog file ends up in exactly one list.
+#[derive(Debug, Default, Serialize)]
+struct AllModelsDeletion {
+    deleted: Vec<DeletedModelFile>,
+    not_deleted: Vec<UndeletedModelFile>,
+    bytes_freed: u64,
+    selected_model_id: Option<String>,
+    selected_model_path: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct TempFileCleanup {
+    removed: Vec<String>,
+    bytes_freed: u64,
+    // Stale-looking files whose owning process is still running (another instance, or this one).
+    skipped_in_use: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct AudioSignalStats {
+    peak_abs: f32,
+    rms: f32,
+    duration_secs: f32,
+}
+
+#[derive(Clone)]
+struct LiveAudioMeter {
+    app: tauri::AppHandle,
+    session_id: u64,
+    last_emitted_at: Arc<Mutex<Option<Instant>>>,
+}
+
+struct ActiveRecording {
+    session_id: u64,
+    input: OpenedInput,
+    // None while paused: the stream is dropped so nothing is captured until resume.
+    capture: Option<CaptureThread>,
+    samples: Arc<Mutex<Vec<f32>>>,
+    channel_capture: Option<ChannelCapture>,
+    options: DictationSessionOptions,
+}
+
+struct CaptureThread {
+    stop_tx: mpsc::Sender<()>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl CaptureThread {
+    // Returns false when the capture thread panicked.
+    fn stop(self) -> bool {
+        let _ = self.stop_tx.send(());
+        self.handle.join().is_ok()
+    }
+}
+
+// What the microphone stream actually opened with, reported back from the capture thread.
+#[derive(Clone, Debug)]
+struct OpenedInput {
+    sample_rate: u32,
+    channels: u16,
+    device_name: String,
+}
+
+// Per-channel copies of the raw input kept next to the mono downmix. Only the first
+// `max_channels` device channels are stored; buffers are sized on the first chunk.
+#[derive(Clone)]
+struct ChannelCapture {
+    max_channels: usize,
+    buffers: Arc<Mutex<Vec<Vec<f32>>>>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct DictationSessionOptions {
+    trailing_punctuation: Option<TrailingPunctuation>,
+    // Scratch sessions run the full pipeline but skip the archive and
+    // last-transcription bookkeeping, and tell the frontend not to keep them.
+    scratch: bool,
+}
+
+#[derive(Clone, Copy)]
+enum BackendHotkeyAction {
+    Toggle,
+    HoldStart,
+    HoldStop,
+    HoldCancel,
+}
+
+fn parse_truthy_env(value: &str) -> bool {
+    let normalized = value.trim().to_ascii_lowercase();
+    !matches!(normalized.as_str(), "" | "0" | "false" | "no" | "off")
+}
+
+fn should_start_hidden() -> bool {
+    std::env::var(START_HIDDEN_ENV)
+        .map(|value| parse_truthy_env(&value))
+        .unwrap_or(false)
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn monitor_rect(monitor: &tauri::Monitor) -> MonitorRect {
+    let area = monitor.work_area();
+    MonitorRect {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    }
+}
+
+fn visible_overlap(geometry: WindowGeometry, monitor: MonitorRect) -> (i64, i64) {
+    let left = i64::from(geometry.x).max(i64::from(monitor.x));
+    let right = (i64::from(geometry.x) + i64::from(geometry.width))
+        .min(i64::from(monitor.x) + i64::from(monitor.width));
+    let top = i64::from(geometry.y).max(i64::from(monitor.y));
+    let bottom = (i64::from(geometry.y) + i64::from(geometry.height))
+        .min(i64::from(monitor.y) + i64::from(monitor.height));
+    ((right - left).max(0), (bottom - top).max(0))
+}
+
+// Keeps a saved window on a display that still exists: the size is capped to the monitor it
+// lands on, and a window that would be (nearly) off-screen is centered on `monitors[0]` instead.
+fn clamp_window_geometry(
+    geometry: WindowGeometry,
+    monitors: &[MonitorRect],
+) -> Option<WindowGeometry> {
+    let min_visible = i64::from(WINDOW_GEOMETRY_MIN_VISIBLE_PX);
+    let host = monitors.iter().copied().find(|monitor| {
+        let (width, height) = visible_overlap(geometry, *monitor);
+        width >= min_visible && height >= min_visible
+    });
+    let monitor = host.or_else(|| monitors.first().copied())?;
+    let width = geometry.width.min(monitor.width);
+    let height = geometry.height.min(monitor.height);
+    if host.is_some() {
+        let max_x = monitor.x + (monitor.width - width) as i32;
+        let max_y = monitor.y + (monitor.height - height) as i32;
+        return Some(WindowGeometry {
+            x: geometry.x.clamp(monitor.x, max_x),
+            y: geometry.y.clamp(monitor.y, max_y),
+            width,
+            height,
+        });
+    }
+    Some(WindowGeometry {
+        x: monitor.x + ((monitor.width - width) / 2) as i32,
+        y: monitor.y + ((monitor.height - height) / 2) as i32,
+        width,
+        height,
+    })
+}
+
+// Runs in setup before the start-hidden check, so a hidden launch is repositioned but not shown.
+fn restore_main_window_geometry(app: &tauri::AppHandle, geometry: Option<WindowGeometry>) {
+    let Some(geometry) = geometry.filter(|value| value.width > 0 && value.height > 0) else {
+        return;
+    };
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let monitors = window
+        .available_monitors()
+        .map(|monitors| monitors.iter().map(monitor_rect).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let Some(geometry) = clamp_window_geometry(geometry, &monitors) else {
+        return;
+    };
+    let _ = window.set_size(tauri::PhysicalSize::new(geometry.width, geometry.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(geometry.x, geometry.y));
+}
+
+fn main_window_geometry(window: &tauri::Window) -> Option<WindowGeometry> {
+    if window.is_minimized().unwrap_or(false) || window.is_fullscreen().unwrap_or(false) {
+        return None;
+    }
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    (size.width > 0 && size.height > 0).then_some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+// Collapses bursts of move/resize updates and persists only the last one.
+fn spawn_window_geometry_saver(app: &tauri::AppHandle) -> mpsc::Sender<WindowGeometry> {
+    let (tx, rx) = mpsc::channel::<WindowGeometry>();
+    let app = app.clone();
+    thread::spawn(move || {
+        while let Ok(mut geometry) = rx.recv() {
+            while let Ok(next) = rx.recv_timeout(WINDOW_GEOMETRY_SAVE_DEBOUNCE) {
+                geometry = next;
+            }
+            let model_state = app.state::<LocalModelState>();
+            let Ok(mut settings) = model_state.settings.lock() else {
+                continue;
+            };
+            if settings.window_geometry == Some(geometry) {
+                continue;
+            }
+            let previous = settings.window_geometry;
+            settings.window_geometry = Some(geometry);
+            if let Err(error) = save_local_settings(&model_state.settings_path(), &settings) {
+                settings.window_geometry = previous;
+                log::warn!("Failed to save main window geometry: {error}");
+            }
+        }
+    });
+    tx
+}
+
+fn sync_pill_after_main_window_hide(app: &tauri::AppHandle) {
+    let state = if dictation_is_running(app).unwrap_or(false) {
+        "listening"
+    } else {
+        "idle"
+    };
+    sync_pill_for_dictation_state(app, state, None);
+}
+
+fn active_hotkey_label(app: &tauri::AppHandle) -> String {
+    let hotkey_state = app.state::<GlobalHotkeyState>();
+    let trigger = current_registered_hotkey(hotkey_state.inner())
+        .ok()
+        .flatten()
+        .unwrap_or_else(default_dictation_trigger);
+    if trigger == "Fn" {
+        "Fn / Globe".to_string()
+    } else {
+        trigger
+    }
+}
+
+fn idle_pill_message(app: &tauri::AppHandle) -> String {
+    let hotkey_state = app.state::<GlobalHotkeyState>();
+    let runtime = current_trigger_runtime_details(hotkey_state.inner()).unwrap_or_default();
+    let label = active_hotkey_label(app);
+    let double_tap = hotkey_activation_mode(app) == HotkeyActivationMode::Double;
+    match runtime.mode {
+        HotkeyDeliveryMode::GlobalHold | HotkeyDeliveryMode::GlobalToggle if double_tap => {
+            format!("Double-tap {label} to dictate")
+        }
+        HotkeyDeliveryMode::GlobalHold | HotkeyDeliveryMode::FocusedWindowHold => {
+            format!("Hold {label} to dictate")
+        }
+        HotkeyDeliveryMode::GlobalToggle => format!("Press {label} to dictate"),
+        HotkeyDeliveryMode::Disabled => "Hotkey disabled".to_string(),
+    }
+}
+
+fn emit_pill_status(
+    app: &tauri::AppHandle,
+    message: impl Into<String>,
+    state: impl Into<String>,
+    visible: bool,
+) {
+    app.emit(
+        PILL_STATUS_EVENT,
+        PillStatusPayload {
+            message: message.into(),
+            state: state.into(),
+            visible,
+        },
+    )
+    .ok();
+}
+
+fn sync_pill_for_dictation_state(app: &tauri::AppHandle, state: &str, error: Option<&str>) {
+    let hotkey_state = app.state::<GlobalHotkeyState>();
+    let runtime = current_trigger_runtime_details(hotkey_state.inner()).unwrap_or_default();
+    let label = active_hotkey_label(app);
+    let double_tap = hotkey_activation_mode(app) == HotkeyActivationMode::Double;
+
+    let (message, pill_state) = match state {
+        "listening" => {
+            let message = match runtime.mode {
+                HotkeyDeliveryMode::GlobalHold | HotkeyDeliveryMode::GlobalToggle if double_tap => {
+                    format!("Listening - double-tap {label} to stop")
+                }
+                HotkeyDeliveryMode::GlobalHold | HotkeyDeliveryMode::FocusedWindowHold => {
+                    format!("Listening - release {label}")
+                }
+                HotkeyDeliveryMode::GlobalToggle => format!("Listening - press {label} again"),
+                HotkeyDeliveryMode::Disabled => "Listening...".to_string(),
+            };
+            (message, "live")
+        }
+        "paused" => ("Paused - resume to keep dictating".to_string(), "working"),
+        "processing" => ("Transcribing...".to_string(), "working"),
+        "error" => (
+            if error
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .is_some()
+            {
+                "Dictation error - check status".to_string()
+            } else {
+                idle_pill_message(app)
+            },
+            if error
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .is_some()
+            {
+                "error"
+            } else {
+                "idle"
+            },
+        ),
+        _ => (idle_pill_message(app), "idle"),
+    };
+
+    emit_pill_status(app, message, pill_state, true);
+    if current_pill_layout(app).visibility == PillVisibility::RecordingOnly {
+        set_pill_windows_visible(app, dictation_state_shows_recording_pill(state));
+    }
+}
+
+fn dictation_state_shows_recording_pill(state: &str) -> bool {
+    matches!(state, "listening" | "paused" | "processing")
+}
+
+// Shows or hides every pill window that exists; which monitors have one is left to
+// `create_pill_overlay_windows`.
+fn set_pill_windows_visible(app: &tauri::AppHandle, visible: bool) {
+    for index in 0..MAX_PILL_WINDOWS {
+        let label = format!("{PILL_WINDOW_LABEL_PREFIX}-{index}");
+        if let Some(window) = app.get_webview_window(&label) {
+            let result = if visible {
+                window.show()
+            } else {
+                window.hide()
+            };
+            if let Err(error) = result {
+                log::warn!("Failed to change visibility of overlay window '{label}': {error}");
+            }
+        }
+    }
+}
+
+fn emit_dictation_state(
+    app: &tauri::AppHandle,
+    state: &str,
+    error: Option<String>,
+    transcript: Option<String>,
+    session_id: Option<u64>,
+) {
+    emit_dictation_state_payload(
+        app,
+        DictationStatePayload {
+            state: state.to_string(),
+            error,
+            transcript,
+            session_id,
+            scratch: false,
+            warning: None,
+            retried: false,
+            audio_info: None,
+            word_count: None,
+            audio_seconds: None,
+            command: None,
+        },
+    );
+}
+
+fn emit_dictation_state_payload(app: &tauri::AppHandle, payload: DictationStatePayload) {
+    sync_pill_for_dictation_state(app, &payload.state, payload.error.as_deref());
+    play_sound_cue_for_state(app, &payload);
+    app.emit(DICTATION_STATE_EVENT, payload).ok();
+}
+
+// Only real transitions cue, and success needs a transcript reached from `processing` (cancel
+// and scratch go idle quietly). `listening` never cues here: the start cue is played by
+// `play_start_cue_before_capture` before the microphone opens.
+fn sound_cue_for_transition(
+    previous: Option<&str>,
+    payload: &DictationStatePayload,
+) -> Option<SoundCue> {
+    let has_text = |value: Option<&str>| value.is_some_and(|text| !text.trim().is_empty());
+    match payload.state.as_str() {
+        "processing" if previous != Some("processing") => Some(SoundCue::Stop),
+        "idle"
+            if previous == Some("processing")
+                && !payload.scratch
+                && has_text(payload.transcript.as_deref()) =>
+        {
+            Some(SoundCue::Success)
+        }
+        "error" if previous != Some("error") && has_text(payload.error.as_deref()) => {
+            Some(SoundCue::Error)
+        }
+        _ => None,
+    }
+}
+
+fn sound_cue_enabled(settings: &LocalSettings, cue: SoundCue) -> bool {
+    settings.sound_cues.unwrap_or(false) && !disabled_sound_cues(settings).contains(&cue)
+}
+
+fn disabled_sound_cues(settings: &LocalSettings) -> Vec<SoundCue> {
+    settings
+        .disabled_sound_cues
+        .iter()
+        .flatten()
+        .filter_map(|name| SoundCue::parse(name))
+        .collect()
+}
+
+fn render_sound_cue(cue: SoundCue, sample_rate: u32) -> Vec<f32> {
+    let rate = sample_rate.max(1) as f32;
+    let fade = (sample_rate * SOUND_CUE_FADE_MS / 1000).max(1) as usize;
+    let mut samples = Vec::new();
+    for &(frequency, duration_ms) in cue.tones() {
+        let len = (sample_rate as u64 * u64::from(duration_ms) / 1000) as usize;
+        for i in 0..len {
+            let envelope = (i.min(len - 1 - i) as f32 / fade as f32).min(1.0);
+            let phase = 2.0 * std::f32::consts::PI * frequency * i as f32 / rate;
+            samples.push(if frequency > 0.0 {
+                phase.sin() * SOUND_CUE_LEVEL * envelope
+            } else {
+                0.0
+            });
+        }
+    }
+    samples
+}
+
+// Runs on every state emit, so it never blocks: the cue plays on its own thread and a missing
+// output device is only logged.
+fn play_sound_cue_for_state(app: &tauri::AppHandle, payload: &DictationStatePayload) {
+    let previous = match app.state::<DictationState>().last_cue_state.lock() {
+        Ok(mut last) => last.replace(payload.state.clone()),
+        Err(_) => return,
+    };
+    let Some(cue) = sound_cue_for_transition(previous.as_deref(), payload) else {
+        return;
+    };
+    let Some(host) = sound_cue_host(app, cue) else {
+        return;
+    };
+    thread::spawn(move || play_sound_cue(&host, cue));
+}
+
+// Blocks until the start cue has finished, so the tone is over before the input stream opens
+// and never reaches the audio sent to whisper.
+fn play_start_cue_before_capture(app: &tauri::AppHandle) {
+    if let Some(host) = sound_cue_host(app, SoundCue::Start) {
+        play_sound_cue(&host, SoundCue::Start);
+    }
+}
+
+fn sound_cue_host(app: &tauri::AppHandle, cue: SoundCue) -> Option<cpal::Host> {
+    let model_state = app.state::<LocalModelState>();
+    let settings = model_state.settings.lock().ok()?;
+    sound_cue_enabled(&settings, cue).then(|| audio_host(&settings))
+}
+
+fn play_sound_cue(host: &cpal::Host, cue: SoundCue) {
+    let samples = render_sound_cue(cue, SOUND_CUE_SAMPLE_RATE);
+    if let Err(error) = play_samples_on_default_output(host, &samples, SOUND_CUE_SAMPLE_RATE) {
+        log::warn!("Failed to play the {} sound cue: {error}", cue.as_str());
+    }
+}
+
+fn emit_needs_onboarding(app: &tauri::AppHandle, reason: OnboardingReason, error: &str) {
+    app.emit(
+        DICTATION_NEEDS_ONBOARDING_EVENT,
+        DictationNeedsOnboardingPayload {
+            reason: reason.as_str().to_string(),
+            error: error.to_string(),
+        },
+    )
+    .ok();
+}
+
+// Why `resolve_active_model_path` failed: nothing saved yet versus a saved or overridden path
+// that no longer resolves.
+fn model_onboarding_reason(settings: &LocalSettings, has_model_override: bool) -> OnboardingReason {
+    let has_selection = settings
+        .selected_model_path
+        .as_deref()
+        .is_some_and(|path| !path.trim().is_empty());
+    if has_model_override || has_selection {
+        OnboardingReason::ModelMissing
+    } else {
+        OnboardingReason::NoModel
+    }
+}
+
+fn current_active_session_id(app: &tauri::AppHandle) -> Result<Option<u64>, String> {
+    let dictation = app.state::<DictationState>();
+    dictation
+        .active_recording
+        .lock()
+        .map_err(|_| "Failed to lock dictation state".to_string())
+        .map(|guard| guard.as_ref().map(|recording| recording.session_id))
+}
+
+fn dictation_is_running(app: &tauri::AppHandle) -> Result<bool, String> {
+    current_active_session_id(app).map(|value| value.is_some())
+}
+
+#[derive(Clone, Serialize)]
+struct DictationDoubleTapPayload {
+    trigger: Option<String>,
+}
+
+fn hotkey_activation_mode(app: &tauri::AppHandle) -> HotkeyActivationMode {
+    app.state::<GlobalHotkeyState>()
+        .activation
+        .lock()
+        .map(|activation| activation.mode)
+        .unwrap_or_default()
+}
+
+// Translates raw press/release edges from the Fn listener or global shortcut into dictation
+// actions. Single activation keeps hold-to-talk / press-to-toggle; double activation ignores
+// lone taps and toggles only on a completed double tap.
+fn route_hotkey_edge(app: &tauri::AppHandle, pressed: bool, hold_trigger: bool) {
+    let mut short_hold = false;
+    let double_tap = {
+        let hotkey_state = app.state::<GlobalHotkeyState>();
+        let Ok(mut activation) = hotkey_state.activation.lock() else {
+            return;
+        };
+        match activation.mode {
+            HotkeyActivationMode::Single => {
+                if hold_trigger {
+                    let now = Instant::now();
+                    if pressed {
+                        activation.hold.press(now);
+                    } else {
+                        let min_hold = activation.min_hold;
+                        short_hold = activation.hold.release(now, min_hold);
+                    }
+                }
+                None
+            }
+            HotkeyActivationMode::Double => {
+                let now = Instant::now();
+                let window = activation.window;
+                Some(if pressed {
+                    activation.detector.press(now, window);
+                    false
+                } else {
+                    activation.detector.release(now, window)
+                })
+            }
+        }
+    };
+
+    match double_tap {
+        None if hold_trigger => dispatch_backend_hotkey_action(
+            app,
+            if pressed {
+                BackendHotkeyAction::HoldStart
+            } else if short_hold {
+                BackendHotkeyAction::HoldCancel
+            } else {
+                BackendHotkeyAction::HoldStop
+            },
+        ),
+        None if pressed => dispatch_backend_hotkey_action(app, BackendHotkeyAction::Toggle),
+        None => {}
+        Some(true) => {
+            let trigger = current_registered_hotkey(app.state::<GlobalHotkeyState>().inner())
+                .ok()
+                .flatten();
+            app.emit(
+                DICTATION_DOUBLE_TAP_EVENT,
+                DictationDoubleTapPayload { trigger },
+            )
+            .ok();
+            dispatch_backend_hotkey_action(app, BackendHotkeyAction::Toggle);
+        }
+        Some(false) => {}
+    }
+}
+
+fn dispatch_backend_hotkey_action(app: &tauri::AppHandle, action: BackendHotkeyAction) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result: Result<(), String> = match action {
+            BackendHotkeyAction::Toggle => match dictation_is_running(&handle) {
+                Ok(true) => stop_native_dictation_inner(handle.clone())
+                    .await
+                    .map(|_| ())
+                    .map_err(String::from),
+                Ok(false) => {
+                    start_native_dictation_inner(&handle, DictationSessionOptions::default())
+                        .map(|_| ())
+                        .map_err(String::from)
+                }
+                Err(error) => Err(error),
+            },
+            BackendHotkeyAction::HoldStart => match dictation_is_running(&handle) {
+                Ok(true) => Ok(()),
+                Ok(false) => {
+                    start_native_dictation_inner(&handle, DictationSessionOptions::default())
+                        .map(|_| ())
+                        .map_err(String::from)
+                }
+                Err(error) => Err(error),
+            },
+            BackendHotkeyAction::HoldStop => match dictation_is_running(&handle) {
+                Ok(true) => stop_native_dictation_inner(handle.clone())
+                    .await
+                    .map(|_| ())
+                    .map_err(String::from),
+                Ok(false) => Ok(()),
+                Err(error) => Err(error),
+            },
+            BackendHotkeyAction::HoldCancel => match dictation_is_running(&handle) {
+                Ok(true) => {
+                    log::info!("Hold released before min_hold_ms; discarding the recording");
+                    cancel_native_dictation_inner(&handle)
+                }
+                Ok(false) => Ok(()),
+                Err(error) => Err(error),
+            },
+        };
+
+        if let Err(error) = result {
+            log::warn!("Global hotkey action failed: {error}");
+            emit_dictation_state(&handle, "error", Some(error), None, None);
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn pill_window_width_for_monitor(monitor: &tauri::Monitor) -> f64 {
+    let clamped_scale = monitor.scale_factor().clamp(1.0, 2.0);
+    PILL_WINDOW_MIN_WIDTH + (clamped_scale - 1.0) * (PILL_WINDOW_BASE_WIDTH - PILL_WINDOW_MIN_WIDTH)
+}
+
+// Top-left corner for a pill of `size` inside a monitor work area, keeping `margin`
+// from the anchored edges. Oversized pills clamp to the work-area origin.
+#[cfg(any(target_os = "macos", test))]
+fn pill_window_origin(
+    layout: PillLayout,
+    work_origin: (i32, i32),
+    work_size: (i32, i32),
+    size: (i32, i32),
+) -> (i32, i32) {
+    let (work_x, work_y) = work_origin;
+    let (work_w, work_h) = work_size;
+    let (width, height) = size;
+    let margin = layout.margin as i32;
+    let free_w = (work_w - width).max(0);
+    let free_h = (work_h - height).max(0);
+
+    let x = match layout.position {
+        PillPosition::BottomCenter | PillPosition::TopCenter => free_w / 2,
+        PillPosition::BottomLeft | PillPosition::TopLeft => margin.min(free_w),
+        PillPosition::BottomRight | PillPosition::TopRight => (free_w - margin).max(0),
+    };
+    let y = match layout.position {
+        PillPosition::TopCenter | PillPosition::TopLeft | PillPosition::TopRight => {
+            margin.min(free_h)
+        }
+        _ => (free_h - margin).max(0),
+    };
+    (work_x + x, work_y + y)
+}
+
+fn current_pill_layout(app: &tauri::AppHandle) -> PillLayout {
+    app.state::<LocalModelState>()
+        .settings
+        .lock()
+        .map(|settings| pill_layout_from_settings(&settings))
+        .unwrap_or_else(|_| pill_layout_from_settings(&LocalSettings::default()))
+}
+
+#[cfg(target_os = "macos")]
+fn create_pill_overlay_window_for_monitor(
+    app: &tauri::AppHandle,
+    label: &str,
+    monitor: &tauri::Monitor,
+    layout: PillLayout,
+    visible: bool,
+) -> Result<(), String> {
+    let work_area = monitor.work_area();
+    let width = pill_window_width_for_monitor(monitor);
+    let (x, y) = pill_window_origin(
+        layout,
+        (work_area.position.x, work_area.position.y),
+        (work_area.size.width as i32, work_area.size.height as i32),
+        (width as i32, PILL_WINDOW_HEIGHT as i32),
+    );
+
+    if let Some(window) = app.get_webview_window(label) {
+        // Same units as the builder below so moved and fresh pills land in the same spot.
+        let _ = window.set_size(tauri::LogicalSize::new(width, PILL_WINDOW_HEIGHT));
+        window
+            .set_position(tauri::LogicalPosition::new(x as f64, y as f64))
+            .map_err(|e| format!("Failed to move overlay window '{label}': {e}"))?;
+        return Ok(());
+    }
+
+    let window =
+        tauri::WebviewWindowBuilder::new(app, label, tauri::WebviewUrl::App("pill.html".into()))
+            .title("dicktaint overlay")
+            .decorations(false)
+            .transparent(true)
+            .shadow(false)
+            .resizable(false)
+            .focusable(false)
+            .skip_taskbar(true)
+            .always_on_top(true)
+            .visible_on_all_workspaces(true)
+            .inner_size(width, PILL_WINDOW_HEIGHT)
+            .position(x as f64, y as f64)
+            .visible(visible)
+            .build()
+            .map_err(|e| format!("Failed to create overlay window '{label}': {e}"))?;
+
+    let _ = window.set_ignore_cursor_events(true);
+    let _ = window.set_always_on_top(true);
+    let _ = window.set_visible_on_all_workspaces(true);
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn close_pill_overlay_windows(app: &tauri::AppHandle) {
+    for index in 0..MAX_PILL_WINDOWS {
+        let label = format!("{PILL_WINDOW_LABEL_PREFIX}-{index}");
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.destroy();
+        }
+    }
+}
+
+// Creates or repositions one pill per monitor from the saved layout; when the
+// pill is disabled, any existing overlay windows are torn down instead.
+#[cfg(target_os = "macos")]
+fn create_pill_overlay_windows(app: &tauri::AppHandle) -> Result<(), String> {
+    let layout = current_pill_layout(app);
+    if !layout.enabled {
+        close_pill_overlay_windows(app);
+        return Ok(());
+    }
+
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to enumerate monitors for overlay pill: {e}"))?;
+    if monitors.is_empty() {
+        return Err("No monitors found while creating overlay pill windows.".to_string());
+    }
+
+    // Pills for a newly attached display start hidden in recording_only mode unless a
+    // recording is already live; existing windows keep their current visibility.
+    let visible = layout.visibility != PillVisibility::RecordingOnly
+        || dictation_is_running(app).unwrap_or(false);
+    let active = monitors.len().min(MAX_PILL_WINDOWS);
+    for (index, monitor) in monitors.iter().enumerate().take(active) {
+        let label = format!("{PILL_WINDOW_LABEL_PREFIX}-{index}");
+        create_pill_overlay_window_for_monitor(app, &label, monitor, layout, visible)?;
+    }
+    // Displays that went away (unplugged, lid closed) leave higher-index pills behind.
+    for index in active..MAX_PILL_WINDOWS {
+        let label = format!("{PILL_WINDOW_LABEL_PREFIX}-{index}");
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.destroy();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn pill_monitor_signature(monitors: &[tauri::Monitor]) -> Vec<(i32, i32, u32, u32, u64)> {
+    monitors
+        .iter()
+        .take(MAX_PILL_WINDOWS)
+        .map(|monitor| {
+            let work_area = monitor.work_area();
+            (
+                work_area.position.x,
+                work_area.position.y,
+                work_area.size.width,
+                work_area.size.height,
+                monitor.scale_factor().to_bits(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn spawn_pill_monitor_watcher(app: &tauri::AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || {
+        let mut last_signature = app
+            .available_monitors()
+            .map(|monitors| pill_monitor_signature(&monitors))
+            .unwrap_or_default();
+        loop {
+            thread::sleep(PILL_MONITOR_POLL_INTERVAL);
+            let Ok(monitors) = app.available_monitors() else {
+                continue;
+            };
+            // While displays reconfigure (e.g. lid closing with nothing attached yet) the list
+            // can be briefly empty; keep the current pills until a real layout shows up.
+            if monitors.is_empty() {
+                continue;
+            }
+            let signature = pill_monitor_signature(&monitors);
+            if signature == last_signature {
+                continue;
+            }
+            last_signature = signature;
+            let handle = app.clone();
+            let _ = app.run_on_main_thread(move || {
+                if let Err(error) = create_pill_overlay_windows(&handle) {
+                    log::warn!(
+                        "Failed to refresh pill overlay windows after monitor change: {error}"
+                    );
+                } else {
+                    sync_pill_after_main_window_hide(&handle);
+                }
+            });
+        }
+    });
+}
+
+#[cfg(not(target_os = "macos"))]
+fn spawn_pill_monitor_watcher(_app: &tauri::AppHandle) {}
+
+#[cfg(not(target_os = "macos"))]
+fn create_pill_overlay_windows(_app: &tauri::AppHandle) -> Result<(), String> {
+    Ok(())
+}
+
+fn resolve_whisper_model_path(path: Option<&str>) -> Result<PathBuf, String> {
+    let raw = path
+    .map(str::trim)
+    .filter(|v| !v.is_empty())
+    .ok_or_else(|| {
+      "WHISPER_MODEL_PATH is not set. Point it to a local GGML Whisper model file (example: ggml-base.en.bin).".to_string()
+    })?;
+
+    let model_path = PathBuf::from(raw);
+    if !model_path.exists() {
+        return Err(format!(
+            "WHISPER_MODEL_PATH file not found: {}",
+            model_path.display()
+        ));
+    }
+
+    Ok(model_path)
+}
+
+fn canonicalize_trigger_modifier(token: &str) -> Option<&'static str> {
+    match token.to_ascii_lowercase().as_str() {
+        "cmdorctrl" | "commandorcontrol" | "mod" | "primary" => Some("CmdOrCtrl"),
+        "cmd" | "command" => Some("Cmd"),
+        "ctrl" | "control" => Some("Ctrl"),
+        "alt" | "option" => Some("Alt"),
+        "shift" => Some("Shift"),
+        "super" | "meta" | "win" | "windows" => Some("Super"),
+        _ => None,
+    }
+}
+
+fn canonicalize_trigger_key(token: &str) -> Option<String> {
+    let trimmed = token.trim();
+    let single_char = {
+        let mut chars = trimmed.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) if ch.is_ascii_alphanumeric() => Some(ch.to_ascii_uppercase()),
+            _ => None,
+        }
+    };
+    if let Some(ch) = single_char {
+        return Some(ch.to_string());
+    }
+
+    let lower = trimmed.to_ascii_lowercase();
+    let special = match lower.as_str() {
+        "fn" | "function" | "globe" => Some("Fn"),
+        "space" => Some("Space"),
+        "tab" => Some("Tab"),
+        "enter" | "return" => Some("Enter"),
+        "escape" | "esc" => Some("Escape"),
+        "backspace" => Some("Backspace"),
+        "delete" | "del" => Some("Delete"),
+        "up" | "arrowup" => Some("Up"),
+        "down" | "arrowdown" => Some("Down"),
+        "left" | "arrowleft" => Some("Left"),
+        "right" | "arrowright" => Some("Right"),
+        "home" => Some("Home"),
+        "end" => Some("End"),
+        "pageup" => Some("PageUp"),
+        "pagedown" => Some("PageDown"),
+        "insert" => Some("Insert"),
+        _ => None,
+    };
+    if let Some(name) = special {
+        return Some(name.to_string());
+    }
+
+    if lower.starts_with('f') {
+        let function_num = lower
+            .strip_prefix('f')
+            .and_then(|num| num.parse::<u8>().ok())?;
+        if TRIGGER_FUNCTION_KEY_RANGE.contains(&function_num) {
+            return Some(format!("F{function_num}"));
+        }
+    }
+
+    None
+}
+
+fn normalize_dictation_trigger(trigger: &str) -> Result<String, String> {
+    let trimmed = trigger.trim();
+    if trimmed.is_empty() {
+        return Err("Dictation trigger cannot be empty.".to_string());
+    }
+    if trimmed.len() > MAX_DICTATION_TRIGGER_BYTES
+        || trimmed.chars().count() > MAX_DICTATION_TRIGGER_LENGTH
+    {
+        return Err(format!(
+            "Dictation trigger is too long (max {MAX_DICTATION_TRIGGER_LENGTH} characters)."
+        ));
+    }
+
+    let mut modifiers = HashSet::<String>::new();
+    let mut key: Option<String> = None;
+    for token in trimmed.split('+').map(str::trim) {
+        if token.is_empty() {
+            return Err("Dictation trigger contains an empty token.".to_string());
+        }
+
+        if let Some(modifier) = canonicalize_trigger_modifier(token) {
+            if key.is_some() {
+                return Err("Modifier keys must come before the main trigger key.".to_string());
+            }
+            modifiers.insert(modifier.to_string());
+            continue;
+        }
+
+        if key.is_some() {
+            return Err("Dictation trigger can only contain one main key.".to_string());
+        }
+        key = Some(
+            canonicalize_trigger_key(token).ok_or_else(|| {
+                format!(
+                    "Unsupported trigger key '{token}'. Use Fn (macOS), letters/numbers, F1-F24, arrows, or common navigation keys."
+                )
+            })?,
+        );
+    }
+
+    let key = key.ok_or_else(|| "Dictation trigger is missing its main key.".to_string())?;
+    if key == "Fn" {
+        if !FN_TRIGGER_SUPPORTED {
+            return Err(format!(
+                "Fn trigger is only supported on macOS. Use a modifier combo such as {DEFAULT_DICTATION_TRIGGER} instead."
+            ));
+        }
+        if !modifiers.is_empty() {
+            return Err("Fn trigger must be used by itself.".to_string());
+        }
+        return Ok("Fn".to_string());
+    }
+
+    if modifiers.is_empty() {
+        return Err("Dictation trigger must include at least one modifier key (or use Fn by itself on macOS).".to_string());
+    }
+    if modifiers.contains("CmdOrCtrl") && (modifiers.contains("Cmd") || modifiers.contains("Ctrl"))
+    {
+        return Err("Use CmdOrCtrl by itself, or use Cmd/Ctrl explicitly.".to_string());
+    }
+
+    let mut parts: Vec<String> = TRIGGER_MODIFIER_ORDER
+        .iter()
+        .filter(|name| modifiers.contains(**name))
+        .map(|name| (*name).to_string())
+        .collect();
+    parts.push(key);
+    Ok(parts.join("+"))
+}
+
+// Named keys `canonicalize_trigger_key` accepts besides letters, digits, and function keys.
+const TRIGGER_NAMED_KEYS: [&str; 15] = [
+    "Space",
+    "Tab",
+    "Enter",
+    "Escape",
+    "Backspace",
+    "Delete",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Insert",
+];
+
+fn supported_trigger_keys() -> SupportedTriggerKeysPayload {
+    let standalone_keys: Vec<String> = if FN_TRIGGER_SUPPORTED {
+        vec!["Fn".to_string()]
+    } else {
+        Vec::new()
+    };
+    let mut keys = standalone_keys.clone();
+    keys.extend(('A'..='Z').chain('0'..='9').map(String::from));
+    keys.extend(TRIGGER_FUNCTION_KEY_RANGE.map(|num| format!("F{num}")));
+    keys.extend(TRIGGER_NAMED_KEYS.iter().map(|name| (*name).to_string()));
+    SupportedTriggerKeysPayload {
+        modifiers: TRIGGER_MODIFIER_ORDER
+            .iter()
+            .map(|name| (*name).to_string())
+            .collect(),
+        keys,
+        standalone_keys,
+        fn_trigger_supported: FN_TRIGGER_SUPPORTED,
+    }
+}
+
+fn default_dictation_trigger() -> String {
+    normalize_dictation_trigger(DEFAULT_DICTATION_TRIGGER)
+        .unwrap_or_else(|_| DEFAULT_DICTATION_TRIGGER.to_string())
+}
+
+fn resolve_effective_dictation_trigger(settings: &LocalSettings) -> Option<String> {
+    if let Some(configured) = settings
+        .dictation_trigger
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        match normalize_dictation_trigger(configured) {
+            Ok(normalized) => return Some(normalized),
+            Err(error) => {
+                log::warn!("Ignoring invalid persisted dictation trigger '{configured}': {error}");
+            }
+        }
+    }
+
+    if matches!(settings.dictation_trigger_enabled, Some(false)) {
+        return None;
+    }
+
+    Some(default_dictation_trigger())
+}
+
+fn focused_field_insert_enabled(settings: &LocalSettings) -> bool {
+    matches!(settings.focused_field_insert_enabled, Some(true))
+}
+
+fn output_mode_name(focused_field_insert_enabled: bool) -> &'static str {
+    if focused_field_insert_enabled {
+        "paste"
+    } else {
+        "event"
+    }
+}
+
+fn output_mode_pill_message(focused_field_insert_enabled: bool) -> &'static str {
+    if focused_field_insert_enabled {
+        "Output: paste into the focused app"
+    } else {
+        "Output: show text only"
+    }
+}
+
+fn insert_delay_ms(settings: &LocalSettings) -> u64 {
+    settings
+        .insert_delay_ms
+        .filter(|value| INSERT_DELAY_MS_RANGE.contains(value))
+        .unwrap_or(DEFAULT_INSERT_DELAY_MS)
+}
+
+fn whisper_acceleration(settings: &LocalSettings) -> WhisperAcceleration {
+    settings
+        .acceleration
+        .as_deref()
+        .and_then(WhisperAcceleration::parse)
+        .unwrap_or_default()
+}
+
+fn whisper_timeout_seconds(settings: &LocalSettings) -> u64 {
+    settings
+        .whisper_timeout_seconds
+        .filter(|value| WHISPER_TIMEOUT_SECONDS_RANGE.contains(value))
+        .unwrap_or(DEFAULT_WHISPER_TIMEOUT_SECONDS)
+}
+
+fn last_recording_retention_seconds(settings: &LocalSettings) -> u64 {
+    settings
+        .last_recording_retention_seconds
+        .map(|value| value.min(MAX_LAST_RECORDING_RETENTION_SECONDS))
+        .unwrap_or(DEFAULT_LAST_RECORDING_RETENTION_SECONDS)
+}
+
+fn logical_cpu_cores() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+fn configured_whisper_threads(settings: &LocalSettings) -> Option<usize> {
+    settings
+        .whisper_threads
+        .filter(|value| (1..=logical_cpu_cores()).contains(value))
+}
+
+fn effective_whisper_threads(configured: Option<usize>, on_battery_saver: bool) -> Option<usize> {
+    if on_battery_saver {
+        Some(
+            configured
+                .unwrap_or(BATTERY_SAVER_MAX_WHISPER_THREADS)
+                .min(BATTERY_SAVER_MAX_WHISPER_THREADS),
+        )
+    } else {
+        configured
+    }
+}
+
+// Power source is only probed when battery saver is on; undetectable counts as plugged in.
+fn whisper_threads(settings: &LocalSettings) -> Option<usize> {
+    let on_battery_saver =
+        settings.battery_saver.unwrap_or(false) && running_on_battery() == Some(true);
+    effective_whisper_threads(configured_whisper_threads(settings), on_battery_saver)
+}
+
+fn pill_layout_from_settings(settings: &LocalSettings) -> PillLayout {
+    PillLayout {
+        position: settings
+            .pill_position
+            .as_deref()
+            .and_then(PillPosition::parse)
+            .unwrap_or_default(),
+        margin: settings
+            .pill_margin
+            .filter(|value| PILL_WINDOW_MARGIN_RANGE.contains(value))
+            .unwrap_or(DEFAULT_PILL_WINDOW_MARGIN),
+        enabled: pill_visibility(settings) != PillVisibility::Hidden,
+        visibility: pill_visibility(settings),
+    }
+}
+
+// The older pill_enabled flag still turns the pill off; otherwise pill_visibility decides.
+fn pill_visibility(settings: &LocalSettings) -> PillVisibility {
+    if settings.pill_enabled == Some(false) {
+        return PillVisibility::Hidden;
+    }
+    settings
+        .pill_visibility
+        .as_deref()
+        .and_then(PillVisibility::parse)
+        .unwrap_or_default()
+}
+
+fn downmix_mode(settings: &LocalSettings) -> DownmixMode {
+    settings
+        .downmix_mode
+        .as_deref()
+        .and_then(DownmixMode::parse)
+        .unwrap_or_default()
+}
+
+// 0 keeps capture mono-only; out-of-range values fall back to that.
+fn preserved_channel_count(settings: &LocalSettings) -> usize {
+    settings
+        .preserve_channels
+        .filter(|count| (2..=MAX_PRESERVED_CHANNELS).contains(count))
+        .unwrap_or(0) as usize
+}
+
+fn preferred_sample_rate(settings: &LocalSettings) -> Option<u32> {
+    settings
+        .preferred_sample_rate
+        .filter(|rate| PREFERRED_SAMPLE_RATE_RANGE.contains(rate))
+}
+
+fn channel_capture(settings: &LocalSettings) -> Option<ChannelCapture> {
+    match preserved_channel_count(settings) {
+        0 => None,
+        max_channels => Some(ChannelCapture {
+            max_channels,
+            buffers: Arc::new(Mutex::new(Vec::new())),
+        }),
+    }
+}
+
+fn memory_guard_from_settings(settings: &LocalSettings) -> (MemoryGuardMode, u64) {
+    let mode = settings
+        .memory_guard_mode
+        .as_deref()
+        .and_then(MemoryGuardMode::parse)
+        .unwrap_or_default();
+    let margin_mb = settings
+        .memory_guard_margin_mb
+        .filter(|value| MEMORY_GUARD_MARGIN_MB_RANGE.contains(value))
+        .unwrap_or(DEFAULT_MEMORY_GUARD_MARGIN_MB);
+    (mode, margin_mb)
+}
+
+fn min_hold_ms(settings: &LocalSettings) -> u64 {
+    settings
+        .min_hold_ms
+        .filter(|value| MIN_HOLD_MS_RANGE.contains(value))
+        .unwrap_or(DEFAULT_MIN_HOLD_MS)
+}
+
+fn hotkey_activation_from_settings(settings: &LocalSettings) -> (HotkeyActivationMode, u64) {
+    let mode = settings
+        .hotkey_activation
+        .as_deref()
+        .and_then(HotkeyActivationMode::parse)
+        .unwrap_or_default();
+    let double_tap_ms = settings
+        .double_tap_ms
+        .filter(|value| DOUBLE_TAP_MS_RANGE.contains(value))
+        .unwrap_or(DEFAULT_DOUBLE_TAP_MS);
+    (mode, double_tap_ms)
+}
+
+fn apply_hotkey_activation(hotkey_state: &GlobalHotkeyState, settings: &LocalSettings) {
+    let (mode, double_tap_ms) = hotkey_activation_from_settings(settings);
+    if let Ok(mut activation) = hotkey_state.activation.lock() {
+        activation.mode = mode;
+        activation.window = Duration::from_millis(double_tap_ms);
+        activation.detector = DoubleTapDetector::default();
+        activation.min_hold = Duration::from_millis(min_hold_ms(settings));
+        activation.hold = HoldCancelDetector::default();
+    }
+}
+
+fn whisper_run_options(settings: &LocalSettings) -> WhisperRunOptions {
+    WhisperRunOptions {
+        acceleration: whisper_acceleration(settings),
+        timeout_seconds: whisper_timeout_seconds(settings),
+        extra_args: extra_whisper_args(settings),
+        threads: whisper_threads(settings),
+        progress: None,
+        output_format: WhisperOutputFormat::Txt,
+        token_confidence: min_confidence(settings).is_some(),
+        warm_worker: None,
+        capability_cache: WhisperCliCapabilityCache::default(),
+    }
+}
+
+fn chunk_seconds(settings: &LocalSettings) -> u32 {
+    settings
+        .chunk_seconds
+        .filter(|value| CHUNK_SECONDS_RANGE.contains(value))
+        .unwrap_or(DEFAULT_CHUNK_SECONDS)
+}
+
+fn min_confidence(settings: &LocalSettings) -> Option<f32> {
+    settings
+        .min_confidence
+        .filter(|value| MIN_CONFIDENCE_RANGE.contains(value))
+}
+
+fn validate_extra_whisper_args(args: &[String]) -> Result<Vec<String>, String> {
+    let cleaned: Vec<String> = args
+        .iter()
+        .map(|arg| arg.trim().to_string())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    if cleaned.len() > MAX_EXTRA_WHISPER_ARGS {
+        return Err(format!(
+            "At most {MAX_EXTRA_WHISPER_ARGS} extra whisper arguments are allowed."
+        ));
+    }
+    for arg in &cleaned {
+        if arg.len() > MAX_EXTRA_WHISPER_ARG_LENGTH {
+            return Err(format!(
+                "Extra whisper argument is longer than {MAX_EXTRA_WHISPER_ARG_LENGTH} characters: {arg}"
+            ));
+        }
+        let flag = arg.split_once('=').map(|(flag, _)| flag).unwrap_or(arg);
+        if MANAGED_WHISPER_FLAGS.contains(&flag) {
+            return Err(format!(
+                "'{flag}' is managed by dicktaint and cannot be passed as an extra whisper argument."
+            ));
+        }
+    }
+    Ok(cleaned)
+}
+
+fn auto_format_options(settings: &LocalSettings) -> AutoFormatOptions {
+    AutoFormatOptions {
+        enabled: settings.auto_format.unwrap_or(false),
+        append_period: settings.auto_format_append_period.unwrap_or(true),
+    }
+}
+
+fn low_confidence_retry_options(settings: &LocalSettings) -> LowConfidenceRetryOptions {
+    LowConfidenceRetryOptions {
+        enabled: settings.retry_on_low_confidence.unwrap_or(true),
+        score_threshold: settings
+            .low_confidence_threshold
+            .filter(|value| LOW_CONFIDENCE_SCORE_THRESHOLD_RANGE.contains(value))
+            .unwrap_or(LOW_CONFIDENCE_SCORE_THRESHOLD),
+    }
+}
+
+fn validate_transcript_artifact_tokens(tokens: &[String]) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for token in tokens {
+        let token = token
+            .trim()
+            .trim_matches(['[', ']', '(', ')'])
+            .to_ascii_uppercase();
+        if token.is_empty()
+            || cleaned.contains(&token)
+            || DEFAULT_TRANSCRIPT_ARTIFACT_TOKENS.contains(&token.as_str())
+        {
+            continue;
+        }
+        if token.len() > MAX_TRANSCRIPT_ARTIFACT_TOKEN_LENGTH {
+            return Err(format!(
+                "Artifact tokens must be at most {MAX_TRANSCRIPT_ARTIFACT_TOKEN_LENGTH} characters: {token}"
+            ));
+        }
+        if token
+            .chars()
+            .any(|ch| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        {
+            return Err(format!(
+                "Artifact tokens may only contain letters, digits, and underscores: {token}"
+            ));
+        }
+        cleaned.push(token);
+    }
+    if cleaned.len() > MAX_TRANSCRIPT_ARTIFACT_TOKENS {
+        return Err(format!(
+            "At most {MAX_TRANSCRIPT_ARTIFACT_TOKENS} extra artifact tokens are allowed."
+        ));
+    }
+    Ok(cleaned)
+}
+
+fn transcript_artifact_tokens(settings: &LocalSettings) -> Vec<String> {
+    let extras = settings
+        .transcript_artifact_tokens
+        .as_deref()
+        .map(|saved| {
+            validate_transcript_artifact_tokens(saved).unwrap_or_else(|error| {
+                log::warn!("transcript_artifact_tokens: ignoring saved extras: {error}");
+                Vec::new()
+            })
+        })
+        .unwrap_or_default();
+    DEFAULT_TRANSCRIPT_ARTIFACT_TOKENS
+        .iter()
+        .map(|token| token.to_string())
+        .chain(extras)
+        .collect()
+}
+
+fn validate_profanity_words(words: &[String]) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for word in words {
+        let word = word.trim().to_lowercase();
+        if word.is_empty() || cleaned.contains(&word) {
+            continue;
+        }
+        if word.chars().count() > MAX_PROFANITY_WORD_LENGTH {
+            return Err(format!(
+                "Filtered words must be at most {MAX_PROFANITY_WORD_LENGTH} characters: {word}"
+            ));
+        }
+        if word.chars().any(|ch| !is_profanity_word_char(ch)) {
+            return Err(format!(
+                "Filtered words must be single words without spaces or punctuation: {word}"
+            ));
+        }
+        cleaned.push(word);
+    }
+    if cleaned.len() > MAX_PROFANITY_WORDS {
+        return Err(format!(
+            "At most {MAX_PROFANITY_WORDS} filtered words are allowed."
+        ));
+    }
+    Ok(cleaned)
+}
+
+// Words and phrases compare in this form, so "Thank you!" matches a "thank you" entry.
+fn normalize_filler_entry(entry: &str) -> String {
+    entry
+        .split_whitespace()
+        .map(filler_token_core)
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn validate_filler_entries(
+    entries: &[String],
+    kind: &str,
+    single_word: bool,
+) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for entry in entries {
+        let normalized = normalize_filler_entry(entry);
+        if normalized.is_empty() || cleaned.contains(&normalized) {
+            continue;
+        }
+        if normalized.chars().count() > MAX_FILLER_ENTRY_LENGTH {
+            return Err(format!(
+                "Filler {kind} must be at most {MAX_FILLER_ENTRY_LENGTH} characters: {normalized}"
+            ));
+        }
+        if single_word && normalized.contains(' ') {
+            return Err(format!(
+                "Filler words must be single words; add phrases as trailing phrases: {normalized}"
+            ));
+        }
+        cleaned.push(normalized);
+    }
+    if cleaned.len() > MAX_FILLER_ENTRIES {
+        return Err(format!(
+            "At most {MAX_FILLER_ENTRIES} filler {kind} are allowed."
+        ));
+    }
+    Ok(cleaned)
+}
+
+fn saved_filler_entries(
+    saved: Option<&[String]>,
+    defaults: &[&str],
+    kind: &str,
+    single_word: bool,
+) -> Vec<String> {
+    let defaults = || defaults.iter().map(|entry| entry.to_string()).collect();
+    let Some(saved) = saved else {
+        return defaults();
+    };
+    validate_filler_entries(saved, kind, single_word).unwrap_or_else(|error| {
+        log::warn!("filler {kind}: falling back to the default list: {error}");
+        defaults()
+    })
+}
+
+fn filler_words(settings: &LocalSettings) -> Vec<String> {
+    saved_filler_entries(
+        settings.filler_words.as_deref(),
+        &DEFAULT_FILLER_WORDS,
+        "words",
+        true,
+    )
+}
+
+fn filler_trailing_phrases(settings: &LocalSettings) -> Vec<String> {
+    saved_filler_entries(
+        settings.filler_trailing_phrases.as_deref(),
+        &DEFAULT_FILLER_TRAILING_PHRASES,
+        "phrases",
+        false,
+    )
+}
+
+fn profanity_words(settings: &LocalSettings) -> Vec<String> {
+    let Some(saved) = settings.profanity_words.as_deref() else {
+        return DEFAULT_PROFANITY_WORDS
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+    };
+    validate_profanity_words(saved).unwrap_or_else(|error| {
+        log::warn!("profanity_words: falling back to the default list: {error}");
+        DEFAULT_PROFANITY_WORDS
+            .iter()
+            .map(|word| word.to_string())
+            .collect()
+    })
+}
+
+fn spoken_commands_locale(settings: &LocalSettings) -> &'static str {
+    let saved = settings
+        .spoken_commands_locale
+        .as_deref()
+        .unwrap_or_default();
+    SPOKEN_COMMAND_LOCALES
+        .iter()
+        .find(|locale| locale.eq_ignore_ascii_case(saved.trim()))
+        .copied()
+        .unwrap_or(DEFAULT_SPOKEN_COMMANDS_LOCALE)
+}
+
+fn locale_spoken_commands(locale: &str) -> &'static [(&'static str, &'static str)] {
+    match locale {
+        "es" => &ES_SPOKEN_COMMANDS,
+        "fr" => &FR_SPOKEN_COMMANDS,
+        "de" => &DE_SPOKEN_COMMANDS,
+        _ => &EN_SPOKEN_COMMANDS,
+    }
+}
+
+fn normalize_spoken_command_phrase(phrase: &str) -> Result<String, String> {
+    let words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() {
+        return Err("Spoken command phrases cannot be empty.".to_string());
+    }
+    if words.len() > MAX_SPOKEN_COMMAND_WORDS {
+        return Err(format!(
+            "Spoken command phrases can have at most {MAX_SPOKEN_COMMAND_WORDS} words: {phrase}"
+        ));
+    }
+    if words
+        .iter()
+        .any(|word| word != spoken_command_core(word).as_str())
+    {
+        return Err(format!(
+            "Spoken command phrases must be words without surrounding punctuation: {phrase}"
+        ));
+    }
+    Ok(words.join(" "))
+}
+
+fn validate_spoken_command_map(
+    commands: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    if commands.len() > MAX_SPOKEN_COMMANDS {
+        return Err(format!(
+            "At most {MAX_SPOKEN_COMMANDS} custom spoken commands are allowed."
+        ));
+    }
+    let mut cleaned = BTreeMap::new();
+    for (phrase, replacement) in commands {
+        let phrase = normalize_spoken_command_phrase(phrase)?;
+        if replacement.chars().count() > MAX_SPOKEN_COMMAND_REPLACEMENT_CHARS {
+            return Err(format!(
+                "Spoken command replacements must be at most {MAX_SPOKEN_COMMAND_REPLACEMENT_CHARS} characters: {phrase}"
+            ));
+        }
+        cleaned.insert(phrase, replacement.clone());
+    }
+    Ok(cleaned)
+}
+
+fn custom_spoken_command_map(settings: &LocalSettings) -> BTreeMap<String, String> {
+    let Some(saved) = settings.spoken_command_map.as_ref() else {
+        return BTreeMap::new();
+    };
+    validate_spoken_command_map(saved).unwrap_or_else(|error| {
+        log::warn!("spoken_command_map: ignoring custom commands: {error}");
+        BTreeMap::new()
+    })
+}
+
+fn spoken_command_map(settings: &LocalSettings) -> BTreeMap<String, String> {
+    let mut commands: BTreeMap<String, String> =
+        locale_spoken_commands(spoken_commands_locale(settings))
+            .iter()
+            .map(|(phrase, replacement)| (phrase.to_string(), replacement.to_string()))
+            .collect();
+    commands.extend(custom_spoken_command_map(settings));
+    commands.retain(|_, replacement| !replacement.is_empty());
+    commands
+}
+
+fn spoken_commands(settings: &LocalSettings) -> Vec<SpokenCommand> {
+    let mut commands: Vec<SpokenCommand> = spoken_command_map(settings)
+        .into_iter()
+        .map(|(phrase, replacement)| SpokenCommand {
+            phrase: phrase.split(' ').map(str::to_string).collect(),
+            replacement,
+        })
+        .collect();
+    commands.sort_by_key(|command| std::cmp::Reverse(command.phrase.len()));
+    commands
+}
+
+fn extra_whisper_args(settings: &LocalSettings) -> Vec<String> {
+    let Some(saved) = settings.extra_whisper_args.as_deref() else {
+        return Vec::new();
+    };
+    match validate_extra_whisper_args(saved) {
+        Ok(args) => args,
+        Err(error) => {
+            log::warn!("extra_whisper_args: ignoring saved arguments: {error}");
+            Vec::new()
+        }
+    }
+}
+
+fn audio_normalization_mode(settings: &LocalSettings) -> AudioNormalizationMode {
+    settings
+        .normalization_mode
+        .as_deref()
+        .and_then(AudioNormalizationMode::parse)
+        .unwrap_or_default()
+}
+
+// Older settings stored the RMS target in normalization_target_level, so it is still honored
+// when no dedicated RMS target has been saved.
+fn normalization_target_rms(settings: &LocalSettings) -> f32 {
+    settings
+        .normalization_target_rms
+        .filter(|value| NORMALIZATION_TARGET_RMS_RANGE.contains(value))
+        .or_else(|| {
+            (audio_normalization_mode(settings) == AudioNormalizationMode::Rms)
+                .then_some(settings.normalization_target_level)
+                .flatten()
+                .filter(|value| NORMALIZATION_TARGET_RMS_RANGE.contains(value))
+        })
+        .unwrap_or(DEFAULT_TARGET_TRANSCRIPTION_AUDIO_RMS)
+}
+
+fn audio_prep_options(settings: &LocalSettings) -> AudioPrepOptions {
+    let normalization_mode = audio_normalization_mode(settings);
+    let normalization_target_level = match normalization_mode {
+        AudioNormalizationMode::Rms => normalization_target_rms(settings),
+        AudioNormalizationMode::Peak | AudioNormalizationMode::Off => settings
+            .normalization_target_level
+            .filter(|value| NORMALIZATION_TARGET_LEVEL_RANGE.contains(value))
+            .unwrap_or_else(|| normalization_mode.default_target_level()),
+    };
+    AudioPrepOptions {
+        normalization_mode,
+        normalization_target_level,
+        trim_silence: settings.trim_silence.unwrap_or(true),
+        noise_gate: settings
+            .noise_gate
+            .unwrap_or(false)
+            .then(|| noise_gate_threshold(settings)),
+    }
+}
+
+fn noise_gate_threshold(settings: &LocalSettings) -> f32 {
+    settings
+        .noise_gate_threshold
+        .filter(|value| NOISE_GATE_THRESHOLD_RANGE.contains(value))
+        .unwrap_or(DEFAULT_NOISE_GATE_THRESHOLD)
+}
+
+fn decode_overrides(settings: &LocalSettings) -> DecodeOverrides {
+    let valid = |value: &u8| DECODE_OVERRIDE_RANGE.contains(value);
+    DecodeOverrides {
+        beam_size: settings.beam_size.filter(valid),
+        best_of: settings.best_of.filter(valid),
+    }
+}
+
+// Imported and other non-catalog models have no known tier, so they keep the greedy profile.
+fn model_default_decode_profile(spec: Option<&WhisperModelSpec>) -> WhisperDecodeProfile {
+    spec.map_or(FAST_DECODE_PROFILE, |spec| WhisperDecodeProfile {
+        beam_size: spec.default_beam,
+        best_of: spec.default_best_of,
+    })
+}
+
+// Overrides only replace the primary pass; the low-confidence retry keeps the accurate profile.
+fn primary_decode_profile(
+    overrides: DecodeOverrides,
+    spec: Option<&WhisperModelSpec>,
+) -> WhisperDecodeProfile {
+    let defaults = model_default_decode_profile(spec);
+    WhisperDecodeProfile {
+        beam_size: overrides.beam_size.unwrap_or(defaults.beam_size),
+        best_of: overrides.best_of.unwrap_or(defaults.best_of),
+    }
+}
+
+fn catalog_spec_for_model_path(model_path: &Path) -> Option<WhisperModelSpec> {
+    let file_name = model_path.file_name()?.to_string_lossy();
+    WHISPER_MODEL_CATALOG
+        .iter()
+        .copied()
+        .find(|spec| spec.file_name == file_name)
+}
+
+fn transcription_options(settings: &LocalSettings, recordings_dir: &Path) -> TranscriptionOptions {
+    TranscriptionOptions {
+        whisper: whisper_run_options(settings),
+        decode_overrides: decode_overrides(settings),
+        audio: audio_prep_options(settings),
+        trailing_punctuation: trailing_punctuation(settings),
+        auto_format: auto_format_options(settings),
+        low_confidence_retry: low_confidence_retry_options(settings),
+        two_pass_decode: settings.two_pass_decode.unwrap_or(false),
+        min_confidence: min_confidence(settings),
+        chunk_seconds: chunk_seconds(settings),
+        artifact_tokens: transcript_artifact_tokens(settings),
+        profanity_words: if settings.profanity_filter.unwrap_or(false) {
+            profanity_words(settings)
+        } else {
+            Vec::new()
+        },
+        dedup_max_repeats: settings
+            .dedup_repeats
+            .unwrap_or(false)
+            .then(|| dedup_max_repeats(settings) as usize),
+        filler_words: if settings.strip_fillers.unwrap_or(false) {
+            filler_words(settings)
+        } else {
+            Vec::new()
+        },
+        filler_trailing_phrases: if settings.strip_fillers.unwrap_or(false) {
+            filler_trailing_phrases(settings)
+        } else {
+            Vec::new()
+        },
+        spoken_commands: if settings.spoken_commands.unwrap_or(false) {
+            spoken_commands(settings)
+        } else {
+            Vec::new()
+        },
+        partial: None,
+        oom_fallback: None,
+        fallback_on_oom: settings.fallback_on_oom.unwrap_or(false),
+        capture_cache: None,
+        recordings: settings
+            .save_recordings
+            .unwrap_or(false)
+            .then(|| recording_archive_options(settings, recordings_dir)),
+    }
+}
+
+fn recording_archive_options(
+    settings: &LocalSettings,
+    recordings_dir: &Path,
+) -> RecordingArchiveOptions {
+    let max_total_mb = settings
+        .max_recordings_total_mb
+        .filter(|value| MAX_RECORDINGS_TOTAL_MB_RANGE.contains(value))
+        .unwrap_or(DEFAULT_MAX_RECORDINGS_TOTAL_MB);
+    RecordingArchiveOptions {
+        dir: recordings_dir.to_path_buf(),
+        max_recordings: settings
+            .max_recordings
+            .filter(|value| MAX_RECORDINGS_RANGE.contains(value))
+            .unwrap_or(DEFAULT_MAX_RECORDINGS),
+        max_total_bytes: max_total_mb.saturating_mul(1024 * 1024),
+        format: recording_format(settings),
+    }
+}
+
+fn recording_format(settings: &LocalSettings) -> RecordingFormat {
+    settings
+        .recording_format
+        .as_deref()
+        .and_then(RecordingFormat::parse)
+        .unwrap_or_default()
+}
+
+fn output_suffix(settings: &LocalSettings) -> OutputSuffix {
+    settings
+        .output_suffix
+        .as_deref()
+        .and_then(OutputSuffix::parse)
+        .unwrap_or_default()
+}
+
+// Any whitespace whisper left at the end is replaced rather than stacked, so the suffix is
+// always exactly one separator.
+fn apply_output_suffix(transcript: String, suffix: OutputSuffix) -> String {
+    let separator = match suffix {
+        OutputSuffix::None => return transcript,
+        OutputSuffix::Space => " ",
+        OutputSuffix::Newline => "\n",
+    };
+    let mut output = transcript.trim_end().to_string();
+    if !output.is_empty() {
+        output.push_str(separator);
+    }
+    output
+}
+
+fn insert_wrap(settings: &LocalSettings) -> InsertWrap {
+    settings
+        .insert_wrap
+        .as_deref()
+        .and_then(InsertWrap::parse)
+        .unwrap_or_default()
+}
+
+// Inline code uses a backtick run longer than any run inside the transcript so the span
+// can't close early; blocks and quotes wrap the trimmed text line by line as-is.
+fn apply_insert_wrap(transcript: String, wrap: InsertWrap) -> String {
+    let text = transcript.trim();
+    if text.is_empty() || wrap == InsertWrap::None {
+        return transcript;
+    }
+    match wrap {
+        InsertWrap::None => transcript,
+        InsertWrap::InlineCode => {
+            let longest_run = text.split(|ch| ch != '`').map(str::len).max().unwrap_or(0);
+            let fence = "`".repeat(longest_run + 1);
+            let pad = if text.starts_with('`') || text.ends_with('`') {
+                " "
+            } else {
+                ""
+            };
+            format!("{fence}{pad}{text}{pad}{fence}")
+        }
+        InsertWrap::CodeBlock => {
+            let longest_run = text.split(|ch| ch != '`').map(str::len).max().unwrap_or(0);
+            let fence = "`".repeat(longest_run.max(2) + 1);
+            format!("{fence}\n{text}\n{fence}")
+        }
+        InsertWrap::Quote => text
+            .lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    ">".to_string()
+                } else {
+                    format!("> {line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn unsupported_insert_wrap_error(value: &str) -> String {
+    format!(
+        "Unsupported insert wrap '{}'. Use none, inline_code, code_block, or quote.",
+        value.trim()
+    )
+}
+
+fn trailing_punctuation(settings: &LocalSettings) -> TrailingPunctuation {
+    settings
+        .trailing_punctuation
+        .as_deref()
+        .and_then(TrailingPunctuation::parse)
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn macos_accessibility_permission_granted() -> bool {
+    unsafe { AXIsProcessTrusted() }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn macos_accessibility_permission_granted() -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+fn open_accessibility_settings() -> Result<(), String> {
+    let status = Command::new("open")
+        .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility")
+        .status()
+        .map_err(|e| format!("Failed to open macOS Accessibility settings: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("Failed to open macOS Accessibility settings.".to_string())
+    }
+}
+
+fn focused_field_insert_permission_status(
+    enabled: bool,
+    prompt_if_missing: bool,
+) -> FocusedFieldInsertPermissionStatus {
+    #[cfg(target_os = "macos")]
+    {
+        let granted = macos_accessibility_permission_granted();
+        if granted {
+            return FocusedFieldInsertPermissionStatus {
+                granted: true,
+                status: if enabled {
+                    "Accessibility permission granted. Finished transcripts can be pasted into the focused field of other apps."
+                        .to_string()
+                } else {
+                    "Accessibility permission granted. Enable focused-field insertion to paste dictated text into other apps."
+                        .to_string()
+                },
+            };
+        }
+
+        if prompt_if_missing {
+            if let Err(error) = open_accessibility_settings() {
+                log::warn!("Failed to open Accessibility settings: {error}");
+            }
+        }
+
+        return FocusedFieldInsertPermissionStatus {
+            granted: false,
+            status: "Focused-field insertion needs Accessibility permission. Opened System Settings > Privacy & Security > Accessibility. Allow dicktaint, then retry the paste."
+                .to_string(),
+        };
+    }
+
+    #[allow(unreachable_code)]
+    FocusedFieldInsertPermissionStatus {
+        granted: false,
+        status: if enabled {
+            "Focused-field insertion is currently supported on macOS desktop only.".to_string()
+        } else {
+            "Focused-field insertion is unavailable on this platform.".to_string()
+        },
+    }
+}
+
+impl Default for TriggerRuntimeDetails {
+    fn default() -> Self {
+        Self {
+            mode: HotkeyDeliveryMode::Disabled,
+            status: "Hotkey disabled.".to_string(),
+            permission_hint: None,
+        }
+    }
+}
+
+fn global_toggle_status(trigger: &str) -> String {
+    format!("Press {trigger} anywhere to start or stop dictation.")
+}
+
+fn global_hold_status(trigger: &str) -> String {
+    format!("Hold {trigger} anywhere to dictate, then release to transcribe.")
+}
+
+fn focused_window_hold_status(trigger: &str) -> String {
+    format!(
+        "Hold {trigger} to dictate while dicktaint is focused. Without Input Monitoring, macOS hides {trigger} from dicktaint while other apps are in front; grant it for global hold-to-talk."
+    )
+}
+
+fn fn_permission_hint() -> String {
+    "System Settings > Privacy & Security > Input Monitoring: allow dicktaint (or Terminal while running tauri:dev), then relaunch dicktaint.".to_string()
+}
+
+fn set_trigger_runtime_details(
+    hotkey_state: &GlobalHotkeyState,
+    details: TriggerRuntimeDetails,
+) -> Result<(), String> {
+    let mut guard = hotkey_state
+        .runtime_details
+        .lock()
+        .map_err(|_| "Failed to lock dictation trigger runtime details".to_string())?;
+    *guard = details;
+    Ok(())
+}
+
+fn current_trigger_runtime_details(
+    hotkey_state: &GlobalHotkeyState,
+) -> Result<TriggerRuntimeDetails, String> {
+    hotkey_state
+        .runtime_details
+        .lock()
+        .map_err(|_| "Failed to lock dictation trigger runtime details".to_string())
+        .map(|guard| guard.clone())
+}
+
+fn runtime_details_for_trigger(
+    trigger: Option<&str>,
+    mode: HotkeyDeliveryMode,
+) -> TriggerRuntimeDetails {
+    let normalized = trigger.map(str::trim).filter(|value| !value.is_empty());
+    match (normalized, mode) {
+        (Some(value), HotkeyDeliveryMode::GlobalToggle) => TriggerRuntimeDetails {
+            mode: HotkeyDeliveryMode::GlobalToggle,
+            status: global_toggle_status(value),
+            permission_hint: None,
+        },
+        (Some(value), HotkeyDeliveryMode::GlobalHold) => TriggerRuntimeDetails {
+            mode: HotkeyDeliveryMode::GlobalHold,
+            status: global_hold_status(value),
+            permission_hint: None,
+        },
+        (Some(value), HotkeyDeliveryMode::FocusedWindowHold) => TriggerRuntimeDetails {
+            mode: HotkeyDeliveryMode::FocusedWindowHold,
+            status: focused_window_hold_status(value),
+            permission_hint: Some(fn_permission_hint()),
+        },
+        _ => TriggerRuntimeDetails::default(),
+    }
+}
+
+fn onboarding_runtime_details(
+    trigger: Option<&str>,
+    registered_trigger: Option<&str>,
+    registered_runtime: Option<&TriggerRuntimeDetails>,
+) -> TriggerRuntimeDetails {
+    let normalized = trigger.map(str::trim).filter(|value| !value.is_empty());
+    if normalized.is_none() {
+        return TriggerRuntimeDetails::default();
+    }
+
+    if normalized == registered_trigger {
+        if let Some(runtime) = registered_runtime {
+            return runtime.clone();
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    let mode = if normalized == Some("Fn") {
+        HotkeyDeliveryMode::FocusedWindowHold
+    } else {
+        HotkeyDeliveryMode::GlobalToggle
+    };
+
+    #[cfg(not(target_os = "macos"))]
+    let mode = HotkeyDeliveryMode::GlobalToggle;
+
+    runtime_details_for_trigger(normalized, mode)
+}
+
+fn dictation_trigger_payload(
+    settings: &LocalSettings,
+    runtime: TriggerRuntimeDetails,
+    fn_listener_mode: &str,
+) -> DictationTriggerPayload {
+    DictationTriggerPayload {
+        trigger: resolve_effective_dictation_trigger(settings),
+        default_trigger: default_dictation_trigger(),
+        fn_trigger_supported: FN_TRIGGER_SUPPORTED,
+        fn_listener_mode: fn_listener_mode.to_string(),
+        trigger_mode: runtime.mode.as_str().to_string(),
+        trigger_status: runtime.status,
+        trigger_permission_hint: runtime.permission_hint,
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn shortcut_from_dictation_trigger(trigger: &str) -> Result<Shortcut, String> {
+    let normalized = normalize_dictation_trigger(trigger)?;
+    let accelerator = normalized
+        .replace("CmdOrCtrl", "CommandOrControl")
+        .replace("Cmd", "Command");
+    Shortcut::from_str(&accelerator)
+        .map_err(|e| format!("Could not parse hotkey '{normalized}' for global registration: {e}"))
+}
+
+fn set_registered_hotkey_state(
+    hotkey_state: &GlobalHotkeyState,
+    next: Option<String>,
+) -> Result<(), String> {
+    let mut guard = hotkey_state
+        .registered_trigger
+        .lock()
+        .map_err(|_| "Failed to lock global hotkey state".to_string())?;
+    *guard = next;
+    Ok(())
+}
+
+fn update_hotkey_state(
+    hotkey_state: &GlobalHotkeyState,
+    trigger: Option<String>,
+    runtime: TriggerRuntimeDetails,
+) -> Result<(), String> {
+    set_registered_hotkey_state(hotkey_state, trigger)?;
+    set_trigger_runtime_details(hotkey_state, runtime)
+}
+
+fn current_registered_hotkey(hotkey_state: &GlobalHotkeyState) -> Result<Option<String>, String> {
+    hotkey_state
+        .registered_trigger
+        .lock()
+        .map_err(|_| "Failed to lock global hotkey state".to_string())
+        .map(|guard| guard.clone())
+}
+
+#[cfg(target_os = "macos")]
+fn should_register_global_hotkey(trigger: &str) -> bool {
+    trigger != "Fn"
+}
+
+#[cfg(not(target_os = "macos"))]
+fn should_register_global_hotkey(_trigger: &str) -> bool {
+    true
+}
+
+// Registers and immediately releases `trigger` to learn whether the OS or another app already owns
+// it. The currently registered hotkey is never touched, so nothing needs restoring afterwards.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn probe_global_hotkey_available(
+    app: &tauri::AppHandle,
+    hotkey_state: &GlobalHotkeyState,
+    trigger: &str,
+) -> Result<(), String> {
+    if !should_register_global_hotkey(trigger)
+        || current_registered_hotkey(hotkey_state)?.as_deref() == Some(trigger)
+    {
+        return Ok(());
+    }
+    let shortcut = shortcut_from_dictation_trigger(trigger)?;
+    app.global_shortcut()
+        .register(shortcut)
+        .map_err(|e| format!("Global hotkey '{trigger}' is unavailable: {e}"))?;
+    if let Err(error) = app.global_shortcut().unregister(shortcut) {
+        log::warn!(
+            "probe_global_hotkey_available: failed to release probe for '{trigger}': {error}"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn probe_global_hotkey_available(
+    _app: &tauri::AppHandle,
+    _hotkey_state: &GlobalHotkeyState,
+    _trigger: &str,
+) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn set_macos_fn_listener_enabled(
+    app: &tauri::AppHandle,
+    hotkey_state: &GlobalHotkeyState,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut guard = hotkey_state
+        .macos_fn_listener
+        .lock()
+        .map_err(|_| "Failed to lock macOS Fn listener state".to_string())?;
+
+    if enabled && guard.is_none() {
+        match MacFnGlobalListener::new(app) {
+            Ok(listener) => *guard = Some(listener),
+            Err(error) => {
+                // Without the event tap, fall back to the in-app monitor so Fn still works while
+                // dicktaint is focused.
+                set_macos_fn_local_monitor_enabled(hotkey_state, true)?;
+                return Err(error);
+            }
+        }
+    }
+
+    if let Some(listener) = guard.as_ref() {
+        listener.set_enabled(enabled);
+    }
+    set_macos_fn_local_monitor_enabled(hotkey_state, false)?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn set_macos_fn_local_monitor_enabled(
+    hotkey_state: &GlobalHotkeyState,
+    enabled: bool,
+) -> Result<(), String> {
+    let guard = hotkey_state
+        .macos_fn_local_monitor
+        .lock()
+        .map_err(|_| "Failed to lock macOS Fn local monitor state".to_string())?;
+    match guard.as_ref() {
+        Some(monitor) => monitor.set_enabled(enabled),
+        None if enabled => log::warn!("In-app Fn key monitor is not installed; Fn is unavailable"),
+        None => {}
+    }
+    Ok(())
+}
+
+// `global` when the event tap is live, `local` when only the in-app monitor is listening, and
+// `unavailable` otherwise (including when the trigger is not Fn).
+#[cfg(target_os = "macos")]
+fn fn_listener_mode(hotkey_state: &GlobalHotkeyState) -> &'static str {
+    let global = hotkey_state
+        .macos_fn_listener
+        .lock()
+        .map(|guard| {
+            guard
+                .as_ref()
+                .is_some_and(|listener| listener.callback_ctx.enabled.load(Ordering::SeqCst))
+        })
+        .unwrap_or(false);
+    let local = hotkey_state
+        .macos_fn_local_monitor
+        .lock()
+        .map(|guard| guard.as_ref().is_some_and(MacFnLocalMonitor::is_enabled))
+        .unwrap_or(false);
+    if global {
+        "global"
+    } else if local {
+        "local"
+    } else {
+        "unavailable"
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn fn_listener_mode(_hotkey_state: &GlobalHotkeyState) -> &'static str {
+    "unavailable"
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn apply_registered_hotkey(
+    app: &tauri::AppHandle,
+    hotkey_state: &GlobalHotkeyState,
+    trigger: Option<&str>,
+) -> Result<TriggerRuntimeDetails, String> {
+    let next = match trigger.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => Some(normalize_dictation_trigger(value)?),
+        None => None,
+    };
+    let previous = current_registered_hotkey(hotkey_state)?;
+
+    if previous == next && next.as_deref() != Some("Fn") {
+        return current_trigger_runtime_details(hotkey_state);
+    }
+
+    #[cfg(target_os = "macos")]
+    if previous.as_deref() == Some("Fn") {
+        if let Err(error) = set_macos_fn_listener_enabled(app, hotkey_state, false) {
+            log::warn!("Failed to disable global Fn listener: {error}");
+        }
+    }
+
+    if let Some(previous_trigger) = previous.as_deref() {
+        if should_register_global_hotkey(previous_trigger) {
+            let previous_shortcut = shortcut_from_dictation_trigger(previous_trigger)?;
+            app.global_shortcut()
+                .unregister(previous_shortcut)
+                .map_err(|e| {
+                    format!("Failed to unregister previous global hotkey '{previous_trigger}': {e}")
+                })?;
+        }
+    }
+
+    if let Some(next_trigger) = next.as_deref() {
+        if should_register_global_hotkey(next_trigger) {
+            let next_shortcut = shortcut_from_dictation_trigger(next_trigger)?;
+            if let Err(error) = app.global_shortcut().register(next_shortcut) {
+                if let Some(previous_trigger) = previous.as_deref() {
+                    if should_register_global_hotkey(previous_trigger) {
+                        if let Ok(previous_shortcut) =
+                            shortcut_from_dictation_trigger(previous_trigger)
+                        {
+                            if let Err(recovery_error) =
+                                app.global_shortcut().register(previous_shortcut)
+                            {
+                                update_hotkey_state(
+                                    hotkey_state,
+                                    None,
+                                    TriggerRuntimeDetails::default(),
+                                )?;
+                                return Err(format!(
+                                    "Could not register global hotkey '{next_trigger}': {error}. Also failed to restore previous hotkey '{previous_trigger}': {recovery_error}"
+                                ));
+                            }
+                            update_hotkey_state(
+                                hotkey_state,
+                                Some(previous_trigger.to_string()),
+                                runtime_details_for_trigger(
+                                    Some(previous_trigger),
+                                    HotkeyDeliveryMode::GlobalToggle,
+                                ),
+                            )?;
+                            #[cfg(target_os = "macos")]
+                            if previous_trigger == "Fn" {
+                                if let Err(listener_error) =
+                                    set_macos_fn_listener_enabled(app, hotkey_state, true)
+                                {
+                                    update_hotkey_state(
+                                        hotkey_state,
+                                        Some(previous_trigger.to_string()),
+                                        runtime_details_for_trigger(
+                                            Some(previous_trigger),
+                                            HotkeyDeliveryMode::FocusedWindowHold,
+                                        ),
+                                    )?;
+                                    log::warn!(
+                                        "Failed to re-enable global Fn listener after hotkey restore: {listener_error}"
+                                    );
+                                }
+                            }
+                        } else {
+                            update_hotkey_state(
+                                hotkey_state,
+                                None,
+                                TriggerRuntimeDetails::default(),
+                            )?;
+                        }
+                    } else {
+                        #[cfg(target_os = "macos")]
+                        let restored_runtime = if previous_trigger == "Fn" {
+                            match set_macos_fn_listener_enabled(app, hotkey_state, true) {
+                                Ok(()) => runtime_details_for_trigger(
+                                    Some(previous_trigger),
+                                    HotkeyDeliveryMode::GlobalHold,
+                                ),
+                                Err(listener_error) => {
+                                    log::warn!(
+                                        "Failed to re-enable global Fn listener after hotkey restore: {listener_error}"
+                                    );
+                                    runtime_details_for_trigger(
+                                        Some(previous_trigger),
+                                        HotkeyDeliveryMode::FocusedWindowHold,
+                                    )
+                                }
+                            }
+                        } else {
+                            runtime_details_for_trigger(
+                                Some(previous_trigger),
+                                HotkeyDeliveryMode::GlobalToggle,
+                            )
+                        };
+
+                        #[cfg(not(target_os = "macos"))]
+                        let restored_runtime = runtime_details_for_trigger(
+                            Some(previous_trigger),
+                            HotkeyDeliveryMode::GlobalToggle,
+                        );
+
+                        update_hotkey_state(
+                            hotkey_state,
+                            Some(previous_trigger.to_string()),
+                            restored_runtime,
+                        )?;
+                    }
+                } else {
+                    update_hotkey_state(hotkey_state, None, TriggerRuntimeDetails::default())?;
+                }
+                return Err(format!(
+                    "Could not register global hotkey '{next_trigger}': {error}"
+                ));
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    let runtime = if let Some(next_trigger) = next.as_deref() {
+        if next_trigger == "Fn" {
+            match set_macos_fn_listener_enabled(app, hotkey_state, true) {
+                Ok(()) => {
+                    runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalHold)
+                }
+                Err(error) => {
+                    log::warn!(
+                        "Global Fn listener unavailable; falling back to in-app Fn hotkey handling: {error}"
+                    );
+                    runtime_details_for_trigger(
+                        Some(next_trigger),
+                        HotkeyDeliveryMode::FocusedWindowHold,
+                    )
+                }
+            }
+        } else {
+            runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalToggle)
+        }
+    } else {
+        TriggerRuntimeDetails::default()
+    };
+
+    #[cfg(not(target_os = "macos"))]
+    let runtime = if let Some(next_trigger) = next.as_deref() {
+        runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalToggle)
+    } else {
+        TriggerRuntimeDetails::default()
+    };
+
+    update_hotkey_state(hotkey_state, next, runtime.clone())?;
+    Ok(runtime)
+}
+
+// Drops dicktaint's registration without touching settings, so the next
+// `apply_registered_hotkey` registers from scratch instead of seeing an unchanged trigger. An
+// unregister failure is expected when the OS already let the shortcut go.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn release_registered_hotkey(
+    app: &tauri::AppHandle,
+    hotkey_state: &GlobalHotkeyState,
+) -> Result<(), String> {
+    let Some(previous) = current_registered_hotkey(hotkey_state)? else {
+        return Ok(());
+    };
+    if should_register_global_hotkey(&previous) {
+        let shortcut = shortcut_from_dictation_trigger(&previous)?;
+        if let Err(error) = app.global_shortcut().unregister(shortcut) {
+            log::info!("release_registered_hotkey: '{previous}' was not registered: {error}");
+        }
+    }
+    #[cfg(target_os = "macos")]
+    if previous == "Fn" {
+        if let Err(error) = set_macos_fn_listener_enabled(app, hotkey_state, false) {
+            log::warn!("Failed to disable global Fn listener: {error}");
+        }
+    }
+    update_hotkey_state(hotkey_state, None, TriggerRuntimeDetails::default())
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn release_registered_hotkey(
+    _app: &tauri::AppHandle,
+    hotkey_state: &GlobalHotkeyState,
+) -> Result<(), String> {
+    update_hotkey_state(hotkey_state, None, TriggerRuntimeDetails::default())
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn apply_registered_hotkey(
+    _app: &tauri::AppHandle,
+    hotkey_state: &GlobalHotkeyState,
+    trigger: Option<&str>,
+) -> Result<TriggerRuntimeDetails, String> {
+    let next = match trigger.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => Some(normalize_dictation_trigger(value)?),
+        None => None,
+    };
+    let runtime = if let Some(next_trigger) = next.as_deref() {
+        runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalToggle)
+    } else {
+        TriggerRuntimeDetails::default()
+    };
+    update_hotkey_state(hotkey_state, next, runtime.clone())?;
+    Ok(runtime)
+}
+
+fn resolve_whisper_cli_path(
+    override_path: Option<&str>,
+    saved_path: Option<&str>,
+    bundled_path: Option<&str>,
+    extra_paths: &[String],
+) -> String {
+    probe_whisper_cli_path(override_path, saved_path, bundled_path, extra_paths).0
+}
+
+// Same resolution as `resolve_whisper_cli_path`, also reporting whether the returned path passed the
+// `--help` probe so callers don't spawn whisper-cli a second time to find out.
+fn probe_whisper_cli_path(
+    override_path: Option<&str>,
+    saved_path: Option<&str>,
+    bundled_path: Option<&str>,
+    extra_paths: &[String],
+) -> (String, bool) {
+    let override_path = override_path.map(str::trim).filter(|v| !v.is_empty());
+    // A working explicit override always wins over user-configured search locations.
+    if let Some(path) = override_path.filter(|path| can_execute_command(path)) {
+        return (path.to_string(), true);
+    }
+    // The in-app path was validated when saved; it beats the bundled sidecar while it still runs.
+    if let Some(path) = saved_path
+        .map(str::trim)
+        .filter(|path| !path.is_empty() && can_execute_command(path))
+    {
+        return (path.to_string(), true);
+    }
+
+    let preferred = preferred_whisper_cli_path(override_path, bundled_path);
+    // `preferred` is always one of the detection candidates, so falling back to it means it failed.
+    match detect_whisper_cli_path(&preferred, extra_paths) {
+        Some(path) => (path, true),
+        None => (preferred, false),
+    }
+}
+
+fn preferred_whisper_cli_path(override_path: Option<&str>, bundled_path: Option<&str>) -> String {
+    if let Some(path) = override_path.map(str::trim).filter(|v| !v.is_empty()) {
+        path.to_string()
+    } else if let Some(path) = bundled_path.map(str::trim).filter(|v| !v.is_empty()) {
+        path.to_string()
+    } else {
+        DEFAULT_WHISPER_CLI_PATH.to_string()
+    }
+}
+
+// Lists every location `resolve_whisper_cli_path` may check, in the order it checks them.
+fn whisper_cli_search_order(
+    override_path: Option<&str>,
+    saved_path: Option<&str>,
+    bundled_path: Option<&str>,
+    extra_paths: &[String],
+) -> Vec<String> {
+    let preferred = preferred_whisper_cli_path(override_path, bundled_path);
+    let mut ordered = Vec::<String>::new();
+    for candidate in [override_path, saved_path]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .chain(candidate_whisper_cli_paths(&preferred, extra_paths))
+    {
+        if !ordered.contains(&candidate) {
+            ordered.push(candidate);
+        }
+    }
+    ordered
+}
+
+fn diagnose_whisper_cli_candidate(candidate: &str) -> WhisperCliCandidateReport {
+    let report = |status: WhisperCliCandidateStatus,
+                  resolved_path: Option<&Path>,
+                  detail: String,
+                  probe_summary: Option<String>| WhisperCliCandidateReport {
+        candidate: candidate.to_string(),
+        resolved_path: resolved_path.map(|path| path.to_string_lossy().to_string()),
+        status: status.as_str(),
+        detail,
+        probe_summary,
+    };
+
+    let executable = match validate_whisper_cli_candidate(candidate) {
+        Ok(path) => path,
+        Err(detail) => {
+            let resolved = resolve_command_path(candidate).filter(|path| path.exists());
+            let status = if resolved.is_some() {
+                WhisperCliCandidateStatus::NotExecutable
+            } else {
+                WhisperCliCandidateStatus::NotFound
+            };
+            return report(status, resolved.as_deref(), detail, None);
+        }
+    };
+    let output = match run_help_probe(&executable) {
+        Ok(output) => output,
+        Err(e) => {
+            return report(
+                WhisperCliCandidateStatus::NotExecutable,
+                Some(&executable),
+                format!("failed to run --help: {e}"),
+                None,
+            );
+        }
+    };
+
+    let probe_summary = Some(help_probe_summary(&output));
+    if help_probe_looks_like_whisper_cli(&output) {
+        report(
+            WhisperCliCandidateStatus::Ok,
+            Some(&executable),
+            "recognized whisper-cli help output".to_string(),
+            probe_summary,
+        )
+    } else if help_probe_is_placeholder(&output) {
+        report(
+            WhisperCliCandidateStatus::Placeholder,
+            Some(&executable),
+            "placeholder whisper-cli sidecar, not a real build".to_string(),
+            probe_summary,
+        )
+    } else {
+        report(
+            WhisperCliCandidateStatus::WrongHelpOutput,
+            Some(&executable),
+            format!(
+                "probe exited with status {} and did not return recognizable whisper-cli help output",
+                output.status
+            ),
+            probe_summary,
+        )
+    }
+}
+
+fn active_whisper_cli_path(
+    config: &AppConfig,
+    model_state: &LocalModelState,
+) -> Result<String, String> {
+    let (saved_path, extra_paths) = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        (
+            settings.whisper_cli_path_override.clone(),
+            extra_whisper_cli_paths(&settings),
+        )
+    };
+    Ok(resolve_whisper_cli_path(
+        config.whisper_cli_path_override.as_deref(),
+        saved_path.as_deref(),
+        config.bundled_whisper_cli_path.as_deref(),
+        &extra_paths,
+    ))
+}
+
+fn normalize_extra_whisper_cli_paths(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut cleaned = Vec::<String>::new();
+    for path in paths
+        .iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+    {
+        if !cleaned.iter().any(|existing| existing == path) {
+            cleaned.push(path.to_string());
+        }
+    }
+    if cleaned.len() > MAX_EXTRA_WHISPER_CLI_PATHS {
+        return Err(format!(
+            "At most {MAX_EXTRA_WHISPER_CLI_PATHS} extra whisper-cli locations are allowed."
+        ));
+    }
+    Ok(cleaned)
+}
+
+fn extra_whisper_cli_paths(settings: &LocalSettings) -> Vec<String> {
+    settings
+        .extra_whisper_cli_paths
+        .as_deref()
+        .and_then(|paths| normalize_extra_whisper_cli_paths(paths).ok())
+        .unwrap_or_default()
+}
+
+fn ensure_whisper_cli_available(whisper_cli_path: &str) -> Result<(), DictationError> {
+    let executable = validate_whisper_cli_candidate(whisper_cli_path).map_err(|detail| {
+        DictationError::new(
+            ErrorCode::WhisperCliUnavailable,
+            format!(
+                "Could not execute '{whisper_cli_path}': {detail}. Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH."
+            ),
+        )
+    })?;
+    let output = run_help_probe(&executable).map_err(|e| {
+        DictationError::new(
+            ErrorCode::WhisperCliUnavailable,
+            format!(
+                "Could not execute '{whisper_cli_path}' (resolved to {}): {e}. Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH.",
+                executable.display()
+            ),
+        )
+    })?;
+    if help_probe_looks_like_whisper_cli(&output) {
+        return Ok(());
+    }
+    if help_probe_is_placeholder(&output) {
+        return Err(DictationError::new(
+            ErrorCode::WhisperCliPlaceholder,
+            placeholder_whisper_cli_error(whisper_cli_path, &executable),
+        ));
+    }
+
+    let probe_summary = help_probe_summary(&output);
+    Err(DictationError::new(
+        ErrorCode::WhisperCliUnavailable,
+        format!(
+            "Could not execute '{whisper_cli_path}' (resolved to {}): probe exited with status {} and did not return recognizable whisper-cli help output ({probe_summary}). Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH.",
+            executable.display(),
+            output.status
+        ),
+    ))
+}
+
+fn can_execute_command(executable: &str) -> bool {
+    let path = match validate_whisper_cli_candidate(executable) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    run_help_probe(&path)
+        .map(|output| help_probe_looks_like_whisper_cli(&output))
+        .unwrap_or(false)
+}
+
+fn run_help_probe(executable: &Path) -> Result<Output, std::io::Error> {
+    Command::new(executable).arg("--help").output()
+}
+
+fn help_probe_looks_like_whisper_cli(output: &Output) -> bool {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    whisper_help_text_looks_valid(&stdout, &stderr)
+}
+
+fn help_probe_is_placeholder(output: &Output) -> bool {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    whisper_help_text_is_placeholder(&stdout, &stderr)
+}
+
+// Returns the resolved file when `executable` is the stub sidecar shipped in `binaries/`, so
+// callers can point at exactly which file needs replacing.
+fn whisper_cli_is_placeholder(executable: &str) -> Option<PathBuf> {
+    let path = validate_whisper_cli_candidate(executable).ok()?;
+    run_help_probe(&path)
+        .ok()
+        .filter(help_probe_is_placeholder)
+        .map(|_| path)
+}
+
+fn placeholder_whisper_cli_error(whisper_cli_path: &str, resolved: &Path) -> String {
+    format!(
+        "'{whisper_cli_path}' (resolved to {}) is the placeholder whisper-cli sidecar, not a real build. Replace that file with a real whisper-cli binary (bun run whisper:sidecar) or set WHISPER_CLI_PATH.",
+        resolved.display()
+    )
+}
+
+fn help_probe_summary(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if let Some(line) = stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
+        return line.to_string();
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if let Some(line) = stdout.lines().map(str::trim).find(|line| !line.is_empty()) {
+        return line.to_string();
+    }
+
+    "no output".to_string()
+}
+
+fn whisper_help_text_is_placeholder(stdout: &str, stderr: &str) -> bool {
+    let normalized = format!("{stdout}\n{stderr}").to_ascii_lowercase();
+    normalized.contains("placeholder")
+        && normalized.contains("replace")
+        && normalized.contains("whisper-cli")
+}
+
+fn whisper_help_text_looks_valid(stdout: &str, stderr: &str) -> bool {
+    let normalized = format!("{stdout}\n{stderr}").trim().to_ascii_lowercase();
+    if normalized.is_empty() || whisper_help_text_is_placeholder(stdout, stderr) {
+        return false;
+    }
+
+    let has_usage = normalized.contains("usage") || normalized.contains("options");
+    let has_model_flag = normalized.contains("--model")
+        || normalized.contains("\n-m ")
+        || normalized.contains(" -m ");
+    has_usage && has_model_flag
+}
+
+fn whisper_help_text_capabilities(stdout: &str, stderr: &str) -> WhisperCliCapabilities {
+    if !whisper_help_text_looks_valid(stdout, stderr) {
+        return WhisperCliCapabilities::default();
+    }
+
+    let normalized = format!("{stdout}\n{stderr}").to_ascii_lowercase();
+    WhisperCliCapabilities {
+        gpu_toggle: normalized.contains("--no-gpu"),
+        flash_attention: normalized.contains("--flash-attn"),
+    }
+}
+
+// A failed probe is not cached, so a binary that is briefly unavailable is probed again next run.
+fn probe_whisper_cli_capabilities(
+    cache: &WhisperCliCapabilityCache,
+    whisper_cli_path: &str,
+) -> WhisperCliCapabilities {
+    let Ok(executable) = validate_whisper_cli_candidate(whisper_cli_path) else {
+        return WhisperCliCapabilities::default();
+    };
+    if let Some(capabilities) = cache
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&executable).copied())
+    {
+        return capabilities;
+    }
+    let Ok(output) = run_help_probe(&executable) else {
+        return WhisperCliCapabilities::default();
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let capabilities = whisper_help_text_capabilities(&stdout, &stderr);
+    if let Ok(mut cache) = cache.lock() {
+        cache.insert(executable, capabilities);
+    }
+    capabilities
+}
+
+fn coreml_encoder_path(model_path: &Path) -> Option<PathBuf> {
+    let stem = model_path.file_stem()?.to_str()?;
+    Some(model_path.with_file_name(format!("{stem}-encoder.mlmodelc")))
+}
+
+// GPU backends (Metal/CUDA) are compiled into whisper-cli and enabled by default, so the only
+// runtime lever is opting out with `-ng`. Explicit backend picks fall back to the default path
+// when the binary or platform cannot honor them.
+fn resolve_acceleration_args(
+    requested: WhisperAcceleration,
+    capabilities: WhisperCliCapabilities,
+    model_path: &Path,
+) -> (WhisperAcceleration, Vec<&'static str>) {
+    let gpu_args = || {
+        if capabilities.flash_attention {
+            vec!["-fa"]
+        } else {
+            Vec::new()
+        }
+    };
+
+    match requested {
+        WhisperAcceleration::Auto => (WhisperAcceleration::Auto, Vec::new()),
+        WhisperAcceleration::Cpu => {
+            if capabilities.gpu_toggle {
+                (WhisperAcceleration::Cpu, vec!["-ng"])
+            } else {
+                (WhisperAcceleration::Cpu, Vec::new())
+            }
+        }
+        WhisperAcceleration::Metal if cfg!(target_os = "macos") && capabilities.gpu_toggle => {
+            (WhisperAcceleration::Metal, gpu_args())
+        }
+        WhisperAcceleration::Cuda if !cfg!(target_os = "macos") && capabilities.gpu_toggle => {
+            (WhisperAcceleration::Cuda, gpu_args())
+        }
+        WhisperAcceleration::CoreMl
+            if cfg!(target_os = "macos")
+                && coreml_encoder_path(model_path).is_some_and(|path| path.exists()) =>
+        {
+            (WhisperAcceleration::CoreMl, Vec::new())
+        }
+        unsupported => {
+            log::warn!(
+                "whisper acceleration '{}' is not available with this whisper-cli build or model; using auto.",
+                unsupported.as_str()
+            );
+            (WhisperAcceleration::Auto, Vec::new())
+        }
+    }
+}
+
+fn validate_whisper_cli_candidate(candidate: &str) -> Result<PathBuf, String> {
+    let resolved_path = resolve_command_path(candidate).ok_or_else(|| {
+        if is_explicit_path(candidate) {
+            format!(
+                "whisper-cli file not found at {}",
+                Path::new(candidate).display()
+            )
+        } else {
+            format!("whisper-cli command '{candidate}' was not found in PATH")
+        }
+    })?;
+
+    let metadata = fs::metadata(&resolved_path).map_err(|e| {
+        format!(
+            "failed to read file metadata for {}: {e}",
+            resolved_path.display()
+        )
+    })?;
+    if !metadata.is_file() {
+        return Err(format!(
+            "{} exists but is not a file",
+            resolved_path.display()
+        ));
+    }
+
+    #[cfg(unix)]
+    {
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!(
+                "{} is not executable (missing execute permission bits)",
+                resolved_path.display()
+            ));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let extension = resolved_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        let has_executable_extension = matches!(extension.as_str(), "exe" | "com" | "bat" | "cmd");
+        if !has_executable_extension {
+            return Err(format!(
+                "{} is not an executable file (expected .exe/.com/.bat/.cmd)",
+                resolved_path.display()
+            ));
+        }
+    }
+
+    Ok(resolved_path)
+}
+
+fn resolve_command_path(candidate: &str) -> Option<PathBuf> {
+    let trimmed = candidate.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    if is_explicit_path(trimmed) {
+        return Some(PathBuf::from(trimmed));
+    }
+
+    let path_var = std::env::var_os("PATH")?;
+
+    #[cfg(target_os = "windows")]
+    {
+        let has_extension = Path::new(trimmed).extension().is_some();
+        let extensions = std::env::var("PATHEXT")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| ".COM;.EXE;.BAT;.CMD".to_string())
+            .split(';')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                if value.starts_with('.') {
+                    value.to_string()
+                } else {
+                    format!(".{value}")
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for dir in std::env::split_paths(&path_var) {
+            if has_extension {
+                let candidate_path = dir.join(trimmed);
+                if candidate_path.exists() {
+                    return Some(candidate_path);
+                }
+                continue;
+            }
+
+            for extension in &extensions {
+                let candidate_path = dir.join(format!("{trimmed}{extension}"));
+                if candidate_path.exists() {
+                    return Some(candidate_path);
+                }
+            }
+        }
+        return None;
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        for dir in std::env::split_paths(&path_var) {
+            let candidate_path = dir.join(trimmed);
+            if candidate_path.exists() {
+                return Some(candidate_path);
+            }
+        }
+    }
+
+    None
+}
+
+fn is_explicit_path(value: &str) -> bool {
+    Path::new(value).is_absolute() || value.contains('/') || value.contains('\\')
+}
+
+fn preferred_arch_variants() -> Vec<&'static str> {
+    let primary = std::env::consts::ARCH;
+    let mut variants = vec![primary];
+
+    #[cfg(target_os = "macos")]
+    {
+        for fallback in ["aarch64", "x86_64"] {
+            if !variants.contains(&fallback) {
+                variants.push(fallback);
+            }
+        }
+    }
+
+    variants
+}
+
+fn preferred_whisper_cli_names() -> Vec<String> {
+    let os = std::env::consts::OS;
+    let mut names = Vec::<String>::new();
+
+    if os == "windows" {
+        for arch in preferred_arch_variants() {
+            names.push(format!("whisper-cli-{arch}-pc-windows-msvc.exe"));
+        }
+        names.push("whisper-cli.exe".to_string());
+    } else if os == "macos" {
+        for arch in preferred_arch_variants() {
+            names.push(format!("whisper-cli-{arch}-apple-darwin"));
+        }
+        names.push("whisper-cli".to_string());
+    } else if os == "linux" {
+        for arch in preferred_arch_variants() {
+            names.push(format!("whisper-cli-{arch}-unknown-linux-gnu"));
+        }
+        names.push("whisper-cli".to_string());
+    } else {
+        names.push("whisper-cli".to_string());
+    }
+
+    names
+}
+
+fn find_whisper_cli_in_dir(dir: &Path) -> Option<PathBuf> {
+    for name in preferred_whisper_cli_names() {
+        let preferred = dir.join(name);
+        if preferred.is_file() {
+            return Some(preferred);
+        }
+    }
+    None
+}
+
+fn resolve_bundled_whisper_cli_path(app: &tauri::AppHandle) -> Option<String> {
+    let mut candidate_dirs = Vec::<PathBuf>::new();
+
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        candidate_dirs.push(resource_dir.clone());
+        candidate_dirs.push(resource_dir.join("bin"));
+        candidate_dirs.push(resource_dir.join("binaries"));
+    }
+
+    if let Ok(exe) = std::env::current_exe() {
+        if let Some(parent) = exe.parent() {
+            candidate_dirs.push(parent.to_path_buf());
+            if cfg!(target_os = "macos") {
+                candidate_dirs.push(parent.join("../Resources"));
+                candidate_dirs.push(parent.join("../Resources/bin"));
+            }
+        }
+    }
+
+    // In tauri:dev, sidecar binaries usually live in src-tauri/binaries.
+    candidate_dirs.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("binaries"));
+
+    let mut deduped = Vec::<PathBuf>::new();
+    for dir in candidate_dirs {
+        if !deduped.iter().any(|seen| seen == &dir) {
+            deduped.push(dir);
+        }
+    }
+
+    for dir in deduped {
+        if let Some(path) = find_whisper_cli_in_dir(&dir) {
+            return Some(path.to_string_lossy().to_string());
+        }
+    }
+
+    None
+}
+
+fn local_dev_sidecar_candidates() -> Vec<String> {
+    let binaries_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("binaries");
+    preferred_whisper_cli_names()
+        .into_iter()
+        .map(|name| binaries_dir.join(name).to_string_lossy().to_string())
+        .collect()
+}
+
+fn candidate_whisper_cli_paths(configured_path: &str, extra_paths: &[String]) -> Vec<String> {
+    let mut candidates = Vec::<String>::new();
+
+    candidates.extend(
+        extra_paths
+            .iter()
+            .map(|path| path.trim())
+            .filter(|path| !path.is_empty())
+            .map(str::to_string),
+    );
+
+    if !configured_path.trim().is_empty() {
+        candidates.push(configured_path.trim().to_string());
+    }
+    candidates.extend(local_dev_sidecar_candidates());
+    if configured_path.trim() != DEFAULT_WHISPER_CLI_PATH {
+        candidates.push(DEFAULT_WHISPER_CLI_PATH.to_string());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        candidates.push("/opt/homebrew/bin/whisper-cli".to_string());
+        candidates.push("/usr/local/bin/whisper-cli".to_string());
+        candidates.push("/opt/homebrew/opt/whisper-cpp/bin/whisper-cli".to_string());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        candidates.push("/usr/local/bin/whisper-cli".to_string());
+        candidates.push("/usr/bin/whisper-cli".to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        candidates.push("C:\\Program Files\\whisper.cpp\\whisper-cli.exe".to_string());
+        candidates.push("C:\\Program Files (x86)\\whisper.cpp\\whisper-cli.exe".to_string());
+    }
+
+    let mut deduped = Vec::new();
+    for candidate in candidates {
+        if !deduped.contains(&candidate) {
+            deduped.push(candidate);
+        }
+    }
+    deduped
+}
+
+fn detect_whisper_cli_path(configured_path: &str, extra_paths: &[String]) -> Option<String> {
+    candidate_whisper_cli_paths(configured_path, extra_paths)
+        .into_iter()
+        .find(|candidate| can_execute_command(candidate))
+}
+
+// Mirrors the whisper-cli probe: an explicit FFMPEG_PATH, then PATH, then common install
+// locations, since apps launched from Finder do not inherit the shell's PATH.
+fn candidate_ffmpeg_paths() -> Vec<String> {
+    let mut candidates = Vec::<String>::new();
+    if let Some(path) = std::env::var("FFMPEG_PATH")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    {
+        candidates.push(path);
+    }
+    candidates.push("ffmpeg".to_string());
+
+    #[cfg(target_os = "macos")]
+    {
+        candidates.push("/opt/homebrew/bin/ffmpeg".to_string());
+        candidates.push("/usr/local/bin/ffmpeg".to_string());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        candidates.push("/usr/local/bin/ffmpeg".to_string());
+        candidates.push("/usr/bin/ffmpeg".to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        candidates.push("C:\\ffmpeg\\bin\\ffmpeg.exe".to_string());
+        candidates.push("C:\\Program Files\\ffmpeg\\bin\\ffmpeg.exe".to_string());
+    }
+
+    candidates
+}
+
+fn ffmpeg_version_looks_valid(stdout: &str) -> bool {
+    stdout.trim_start().starts_with("ffmpeg version")
+}
+
+fn detect_ffmpeg_path() -> Result<PathBuf, String> {
+    let candidates = candidate_ffmpeg_paths();
+    for candidate in &candidates {
+        let Some(path) = resolve_command_path(candidate).filter(|path| path.is_file()) else {
+            continue;
+        };
+        let mut command = Command::new(&path);
+        command.arg("-version");
+        if run_command_with_timeout(command, FFMPEG_PROBE_TIMEOUT, None).is_ok_and(|output| {
+            ffmpeg_version_looks_valid(&String::from_utf8_lossy(&output.stdout))
+        }) {
+            return Ok(path);
+        }
+    }
+    Err(format!(
+        "ffmpeg is needed to read this file but was not found (tried {}). Install ffmpeg (for example `brew install ffmpeg`), set FFMPEG_PATH, or convert the file to WAV first.",
+        candidates.join(", ")
+    ))
+}
+
+// An unreadable file or a chosen folder that is gone (an unplugged drive) falls back to the app
+// data dir for this launch; the saved choice is kept, so the next launch with the drive back
+// uses it again.
+fn load_storage_base_dir(app_data_dir: &Path) -> PathBuf {
+    let location_path = app_data_dir.join(STORAGE_LOCATION_FILE);
+    let Some(base_dir) = fs::read_to_string(&location_path)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<StorageLocation>(&contents).ok())
+        .and_then(|location| location.base_dir)
+        .map(PathBuf::from)
+    else {
+        return app_data_dir.to_path_buf();
+    };
+    if base_dir.is_dir() {
+        return base_dir;
+    }
+    log::warn!(
+        "Storage folder {} is unavailable; using {} for this launch.",
+        base_dir.display(),
+        app_data_dir.display()
+    );
+    app_data_dir.to_path_buf()
+}
+
+fn save_storage_location(app_data_dir: &Path, base_dir: Option<&Path>) -> Result<(), String> {
+    let location_path = app_data_dir.join(STORAGE_LOCATION_FILE);
+    let Some(base_dir) = base_dir else {
+        return match fs::remove_file(&location_path) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(format!(
+                "Failed to remove {}: {error}",
+                location_path.display()
+            )),
+            _ => Ok(()),
+        };
+    };
+    let location = StorageLocation {
+        base_dir: Some(base_dir.to_string_lossy().to_string()),
+    };
+    let serialized = serde_json::to_string_pretty(&location)
+        .map_err(|e| format!("Failed to serialize storage location: {e}"))?;
+    let temp_path = location_path.with_extension("json.tmp");
+    fs::write(&temp_path, serialized)
+        .and_then(|()| fs::rename(&temp_path, &location_path))
+        .map_err(|e| format!("Failed to write {}: {e}", location_path.display()))
+}
+
+fn copy_dir_recursive(source: &Path, target: &Path) -> Result<(), String> {
+    fs::create_dir_all(target)
+        .map_err(|e| format!("Failed to create {}: {e}", target.display()))?;
+    let entries =
+        fs::read_dir(source).map_err(|e| format!("Failed to read {}: {e}", source.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {e}", source.display()))?;
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        if from.is_dir() {
+            copy_dir_recursive(&from, &to)?;
+        } else {
+            fs::copy(&from, &to).map_err(|e| {
+                format!("Failed to copy {} to {}: {e}", from.display(), to.display())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+// rename is atomic on one filesystem but fails across filesystems (an external drive), so the
+// tree is then copied and the source removed only after the whole copy succeeded. A failed
+// copy removes the partial target and leaves the source untouched.
+fn move_storage_dir(source: &Path, target: &Path) -> Result<(), String> {
+    if !source.exists() {
+        return fs::create_dir_all(target)
+            .map_err(|e| format!("Failed to create {}: {e}", target.display()));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    // An empty leftover target would make rename fail on some platforms.
+    let _ = fs::remove_dir(target);
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    if let Err(error) = copy_dir_recursive(source, target) {
+        let _ = fs::remove_dir_all(target);
+        return Err(error);
+    }
+    if let Err(error) = fs::remove_dir_all(source) {
+        log::warn!(
+            "Copied storage to {} but could not remove the old folder {}: {error}",
+            target.display(),
+            source.display()
+        );
+    }
+    Ok(())
+}
+
+fn rebase_path(path: &str, old_root: &Path, new_root: &Path) -> Option<String> {
+    Path::new(path)
+        .strip_prefix(old_root)
+        .ok()
+        .map(|relative| new_root.join(relative).to_string_lossy().to_string())
+}
+
+fn storage_location_payload(paths: &StoragePaths, app_data_dir: &Path) -> StorageLocationPayload {
+    let app_dir = paths
+        .settings_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let base_dir = app_dir.parent().map(Path::to_path_buf).unwrap_or_default();
+    StorageLocationPayload {
+        is_default: base_dir == app_data_dir,
+        base_dir: base_dir.to_string_lossy().to_string(),
+        settings_path: paths.settings_path.to_string_lossy().to_string(),
+        models_dir: paths.models_dir.to_string_lossy().to_string(),
+        recordings_dir: paths.recordings_dir.to_string_lossy().to_string(),
+    }
+}
+
+fn resolve_local_paths(base_data_dir: &Path) -> Result<(PathBuf, PathBuf, PathBuf), String> {
+    let app_dir = base_data_dir.join(APP_SETTINGS_DIR);
+    let models_dir = app_dir.join(APP_MODELS_DIR);
+    let settings_path = app_dir.join(APP_SETTINGS_FILE);
+    // Created lazily on first save so users who never archive audio don't get an empty folder.
+    let recordings_dir = app_dir.join(APP_RECORDINGS_DIR);
+
+    fs::create_dir_all(&app_dir).map_err(|e| {
+        format!(
+            "Failed to create local app settings directory {}: {e}",
+            app_dir.display()
+        )
+    })?;
+    fs::create_dir_all(&models_dir).map_err(|e| {
+        format!(
+            "Failed to create local model directory {}: {e}",
+            models_dir.display()
+        )
+    })?;
+
+    Ok((models_dir, settings_path, recordings_dir))
+}
+
+struct SettingsMigration {
+    settings: LocalSettings,
+    from_version: u32,
+    dropped_fields: Vec<String>,
+}
+
+fn settings_schema_version(value: &serde_json::Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(0)
+}
+
+fn settings_field_parses(key: &str, value: &serde_json::Value) -> bool {
+    let mut single = serde_json::Map::new();
+    single.insert(key.to_string(), value.clone());
+    serde_json::from_value::<LocalSettings>(serde_json::Value::Object(single)).is_ok()
+}
+
+// Older builds (and hand edits) stored some scalars as strings, e.g.
+// `"whisper_timeout_seconds": "90"`; try the natural typed reading.
+fn coerce_legacy_settings_value(value: &serde_json::Value) -> Vec<serde_json::Value> {
+    let serde_json::Value::String(raw) = value else {
+        return Vec::new();
+    };
+    let trimmed = raw.trim();
+    let mut candidates = Vec::new();
+    match trimmed.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" => candidates.push(serde_json::Value::Bool(true)),
+        "false" | "no" | "off" => candidates.push(serde_json::Value::Bool(false)),
+        _ => {}
+    }
+    if let Ok(number) = trimmed.parse::<u64>() {
+        candidates.push(serde_json::Value::from(number));
+    }
+    if let Some(number) = trimmed
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        candidates.push(serde_json::Value::Number(number));
+    }
+    candidates.push(serde_json::Value::Array(vec![value.clone()]));
+    candidates
+}
+
+fn migrate_settings(value: serde_json::Value) -> Result<SettingsMigration, String> {
+    let from_version = settings_schema_version(&value);
+    let serde_json::Value::Object(fields) = value else {
+        return Err("Settings file is not a JSON object.".to_string());
+    };
+
+    // v0 -> v1: keep every field that still fits, coerce stringly-typed
+    // scalars, and drop only the individual fields that cannot be salvaged.
+    let mut migrated = serde_json::Map::new();
+    let mut dropped_fields = Vec::new();
+    for (key, field_value) in fields {
+        if key == "schema_version" {
+            continue;
+        }
+        if settings_field_parses(&key, &field_value) {
+            migrated.insert(key, field_value);
+            continue;
+        }
+        match coerce_legacy_settings_value(&field_value)
+            .into_iter()
+            .find(|candidate| settings_field_parses(&key, candidate))
+        {
+            Some(candidate) => {
+                migrated.insert(key, candidate);
+            }
+            None => dropped_fields.push(key),
+        }
+    }
+
+    let mut settings = serde_json::from_value::<LocalSettings>(serde_json::Value::Object(migrated))
+        .map_err(|e| format!("Failed to rebuild migrated settings: {e}"))?;
+    settings.schema_version = Some(SETTINGS_SCHEMA_VERSION);
+    Ok(SettingsMigration {
+        settings,
+        from_version,
+        dropped_fields,
+    })
+}
+
+fn settings_backup_path(settings_path: &Path, version: u32) -> PathBuf {
+    let file_name = settings_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("dictation-settings.json");
+    settings_path.with_file_name(format!("{file_name}.v{version}.bak"))
+}
+
+fn load_local_settings(settings_path: &Path) -> LocalSettings {
+    let raw = match fs::read_to_string(settings_path) {
+        Ok(value) => value,
+        Err(_) => return LocalSettings::default(),
+    };
+
+    let value = match serde_json::from_str::<serde_json::Value>(&raw) {
+        Ok(value) => value,
+        Err(error) => {
+            log::warn!(
+                "load_local_settings: failed to parse LocalSettings from {}: {}",
+                settings_path.display(),
+                error
+            );
+            return LocalSettings::default();
+        }
+    };
+
+    if settings_schema_version(&value) >= SETTINGS_SCHEMA_VERSION {
+        if let Ok(settings) = serde_json::from_value::<LocalSettings>(value.clone()) {
+            return settings;
+        }
+    }
+
+    let migration = match migrate_settings(value) {
+        Ok(migration) => migration,
+        Err(error) => {
+            log::warn!(
+                "load_local_settings: failed to migrate LocalSettings from {}: {}",
+                settings_path.display(),
+                error
+            );
+            return LocalSettings::default();
+        }
+    };
+    if !migration.dropped_fields.is_empty() {
+        log::warn!(
+            "load_local_settings: dropped unreadable settings fields during migration: {}",
+            migration.dropped_fields.join(", ")
+        );
+    }
+    if migration.from_version > SETTINGS_SCHEMA_VERSION {
+        // Written by a newer build; read what we understand but leave the file alone.
+        return migration.settings;
+    }
+
+    let backup_path = settings_backup_path(settings_path, migration.from_version);
+    if !backup_path.exists() {
+        if let Err(error) = fs::write(&backup_path, &raw) {
+            log::warn!(
+                "load_local_settings: failed to back up settings to {}: {}",
+                backup_path.display(),
+                error
+            );
+            return migration.settings;
+        }
+    }
+    if let Err(error) = save_local_settings(settings_path, &migration.settings) {
+        log::warn!("load_local_settings: failed to save migrated settings: {error}");
+    }
+    migration.settings
+}
+
+fn save_local_settings(settings_path: &Path, settings: &LocalSettings) -> Result<(), String> {
+    let parent = settings_path.parent().ok_or_else(|| {
+        format!(
+            "Failed to determine settings directory for {}",
+            settings_path.display()
+        )
+    })?;
+    fs::create_dir_all(parent).map_err(|e| {
+        format!(
+            "Failed to create settings directory {}: {e}",
+            parent.display()
+        )
+    })?;
+
+    let versioned = LocalSettings {
+        schema_version: Some(SETTINGS_SCHEMA_VERSION),
+        ..settings.clone()
+    };
+    let serialized = serde_json::to_string_pretty(&versioned)
+        .map_err(|e| format!("Failed to serialize dictation settings: {e}"))?;
+
+    let timestamp_nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let target_name = settings_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("dictation-settings.json");
+    // Write-then-rename keeps settings updates atomic across crashes/interruption.
+    let temp_path = parent.join(format!(
+        ".{}.tmp-{}-{}",
+        target_name,
+        std::process::id(),
+        timestamp_nanos
+    ));
+
+    let mut temp_file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .map_err(|e| {
+            format!(
+                "Failed to create temp settings file {}: {e}",
+                temp_path.display()
+            )
+        })?;
+    temp_file.write_all(serialized.as_bytes()).map_err(|e| {
+        format!(
+            "Failed to write temp settings file {}: {e}",
+            temp_path.display()
+        )
+    })?;
+    temp_file.flush().map_err(|e| {
+        format!(
+            "Failed to flush temp settings file {}: {e}",
+            temp_path.display()
+        )
+    })?;
+    temp_file.sync_all().map_err(|e| {
+        format!(
+            "Failed to sync temp settings file {}: {e}",
+            temp_path.display()
+        )
+    })?;
+    drop(temp_file);
+
+    fs::rename(&temp_path, settings_path).map_err(|e| {
+        format!(
+            "Failed to replace dictation settings file {} with temp file {}: {e}",
+            settings_path.display(),
+            temp_path.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+fn whisper_model_catalog() -> &'static [WhisperModelSpec] {
+    &WHISPER_MODEL_CATALOG
+}
+
+fn find_whisper_model_spec(id: &str) -> Option<WhisperModelSpec> {
+    whisper_model_catalog()
+        .iter()
+        .copied()
+        .find(|spec| spec.id == id)
+}
+
+fn total_memory_bytes() -> Option<u64> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("sysctl")
+            .args(["-n", "hw.memsize"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        return raw.parse::<u64>().ok();
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let content = fs::read_to_string("/proc/meminfo").ok()?;
+        let line = content
+            .lines()
+            .find(|entry| entry.starts_with("MemTotal:"))?;
+        let kib = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|value| value.parse::<u64>().ok())?;
+        return Some(kib.saturating_mul(1024));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("powershell.exe")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let value = String::from_utf8(output.stdout)
+            .ok()?
+            .trim_matches(|c| c == '\r' || c == '\n' || c == ' ')
+            .to_string();
+        return value.parse::<u64>().ok();
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+fn running_on_battery() -> Option<bool> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let raw = String::from_utf8_lossy(&output.stdout);
+        return Some(raw.contains("'Battery Power'"));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut saw_battery = false;
+        for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+            let path = entry.path();
+            let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+            match kind.trim() {
+                "Mains" | "USB" => {
+                    let online = fs::read_to_string(path.join("online")).unwrap_or_default();
+                    if online.trim() == "1" {
+                        return Some(false);
+                    }
+                }
+                "Battery" => saw_battery = true,
+                _ => {}
+            }
+        }
+        return saw_battery.then_some(true);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // BatteryStatus 1 means discharging; desktops without a battery print nothing.
+        let output = Command::new("powershell.exe")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "(Get-CimInstance Win32_Battery).BatteryStatus",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if value.is_empty() {
+            return None;
+        }
+        return Some(value == "1");
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+fn available_memory_bytes() -> Option<u64> {
+    #[cfg(target_os = "macos")]
+    {
+        // Free + inactive + speculative pages are reclaimable without swapping.
+        let output = Command::new("vm_stat").output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let raw = String::from_utf8_lossy(&output.stdout);
+        let page_size = raw
+            .lines()
+            .next()
+            .and_then(|line| line.split("page size of ").nth(1))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(4096);
+        let pages_for = |label: &str| {
+            raw.lines()
+                .find(|line| line.starts_with(label))
+                .and_then(|line| line.split(':').nth(1))
+                .and_then(|value| value.trim().trim_end_matches('.').parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        let pages =
+            pages_for("Pages free") + pages_for("Pages inactive") + pages_for("Pages speculative");
+        return Some(pages.saturating_mul(page_size));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let content = fs::read_to_string("/proc/meminfo").ok()?;
+        let line = content
+            .lines()
+            .find(|entry| entry.starts_with("MemAvailable:"))?;
+        let kib = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|value| value.parse::<u64>().ok())?;
+        return Some(kib.saturating_mul(1024));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("powershell.exe")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "(Get-CimInstance Win32_OperatingSystem).FreePhysicalMemory",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let kib = String::from_utf8(output.stdout)
+            .ok()?
+            .trim_matches(|c| c == '\r' || c == '\n' || c == ' ')
+            .parse::<u64>()
+            .ok()?;
+        return Some(kib.saturating_mul(1024));
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+fn model_memory_requirement_bytes(model_path: &Path) -> u64 {
+    let file_name = model_path
+        .file_name()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let model_bytes = WHISPER_MODEL_CATALOG
+        .iter()
+        .find(|spec| spec.file_name == file_name)
+        .map(|spec| (f64::from(spec.approx_size_gb) * 1_073_741_824.0) as u64)
+        .or_else(|| fs::metadata(model_path).ok().map(|meta| meta.len()))
+        .unwrap_or(0);
+    model_bytes.saturating_add(MODEL_RUNTIME_OVERHEAD_MB * 1_048_576)
+}
+
+fn model_size_is_suspect(spec: WhisperModelSpec, actual_size_bytes: u64) -> bool {
+    let expected_bytes = f64::from(spec.approx_size_gb) * 1_073_741_824.0;
+    (actual_size_bytes as f64) < expected_bytes * MODEL_SIZE_SUSPECT_FRACTION
+}
+
+fn format_memory_mb(bytes: u64) -> String {
+    format!("{} MB", bytes / 1_048_576)
+}
+
+// Returns a warning to log for `warn`, an error for `block`, and `None` when
+// memory is sufficient, the guard is off, or available memory is unknown.
+fn check_memory_guard(
+    mode: MemoryGuardMode,
+    margin_mb: u64,
+    required_bytes: u64,
+    available_bytes: Option<u64>,
+) -> Result<Option<String>, String> {
+    if mode == MemoryGuardMode::Off {
+        return Ok(None);
+    }
+    let Some(available_bytes) = available_bytes else {
+        return Ok(None);
+    };
+    let needed_bytes = required_bytes.saturating_add(margin_mb.saturating_mul(1_048_576));
+    if available_bytes >= needed_bytes {
+        return Ok(None);
+    }
+
+    let message = format!(
+        "Only {} of memory is available, but the selected model needs about {} plus a {margin_mb} MB safety margin. Close other apps or pick a smaller model.",
+        format_memory_mb(available_bytes),
+        format_memory_mb(required_bytes)
+    );
+    match mode {
+        MemoryGuardMode::Block => Err(message),
+        _ => Ok(Some(message)),
+    }
+}
+
+fn enforce_memory_guard(model_state: &LocalModelState, model_path: &Path) -> Result<(), String> {
+    let (mode, margin_mb) = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        memory_guard_from_settings(&settings)
+    };
+    if mode == MemoryGuardMode::Off {
+        return Ok(());
+    }
+    if let Some(warning) = check_memory_guard(
+        mode,
+        margin_mb,
+        model_memory_requirement_bytes(model_path),
+        available_memory_bytes(),
+    )? {
+        log::warn!("memory guard: {warning}");
+    }
+    Ok(())
+}
+
+fn system_memory_gb() -> u64 {
+    let total_bytes = total_memory_bytes().unwrap_or(8 * 1_073_741_824);
+    (((total_bytes as f64) / 1_073_741_824.0).round() as u64).max(1)
+}
+
+fn detect_cpu_features() -> Vec<&'static str> {
+    #[allow(unused_mut)]
+    let mut features = Vec::new();
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    for (name, detected) in [
+        ("sse4.1", std::arch::is_x86_feature_detected!("sse4.1")),
+        ("avx", std::arch::is_x86_feature_detected!("avx")),
+        ("avx2", std::arch::is_x86_feature_detected!("avx2")),
+        ("fma", std::arch::is_x86_feature_detected!("fma")),
+        ("f16c", std::arch::is_x86_feature_detected!("f16c")),
+        ("avx512f", std::arch::is_x86_feature_detected!("avx512f")),
+    ] {
+        if detected {
+            features.push(name);
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    for (name, detected) in [
+        ("neon", std::arch::is_aarch64_feature_detected!("neon")),
+        (
+            "dotprod",
+            std::arch::is_aarch64_feature_detected!("dotprod"),
+        ),
+        ("fp16", std::arch::is_aarch64_feature_detected!("fp16")),
+    ] {
+        if detected {
+            features.push(name);
+        }
+    }
+    // Runtime detection is unstable on 32-bit ARM; rely on what the build targets.
+    #[cfg(all(target_arch = "arm", target_feature = "neon"))]
+    features.push("neon");
+    features
+}
+
+fn host_simd_support() -> SimdSupport {
+    SimdSupport::detect(std::env::consts::ARCH, &detect_cpu_features())
+}
+
+fn build_device_profile() -> DeviceProfile {
+    let cpu_features = detect_cpu_features();
+    DeviceProfile {
+        total_memory_gb: system_memory_gb(),
+        logical_cpu_cores: logical_cpu_cores(),
+        architecture: std::env::consts::ARCH.to_string(),
+        simd_support: SimdSupport::detect(std::env::consts::ARCH, &cpu_features)
+            .as_str()
+            .to_string(),
+        cpu_features,
+        os: std::env::consts::OS.to_string(),
+    }
+}
+
+fn model_path_for_spec(models_dir: &Path, spec: WhisperModelSpec) -> PathBuf {
+    models_dir.join(spec.file_name)
+}
+
+fn model_fit_level(spec: WhisperModelSpec, total_memory_gb: u64) -> u8 {
+    if total_memory_gb >= spec.recommended_ram_gb {
+        2
+    } else if total_memory_gb >= spec.min_ram_gb {
+        1
+    } else {
+        0
+    }
+}
+
+// Audio seconds transcribed per processing second (same convention as benchmarks).
+fn estimated_real_time_factor(
+    spec: WhisperModelSpec,
+    logical_cpu_cores: usize,
+    simd: SimdSupport,
+) -> f32 {
+    let cores = logical_cpu_cores.clamp(1, REAL_TIME_FACTOR_MAX_CORES) as f32;
+    REAL_TIME_FACTOR_PER_CORE * cores * simd.speed_factor() / spec.relative_compute
+}
+
+fn keeps_up_with_speech(
+    spec: WhisperModelSpec,
+    logical_cpu_cores: usize,
+    simd: SimdSupport,
+) -> bool {
+    estimated_real_time_factor(spec, logical_cpu_cores, simd) >= MIN_RECOMMENDED_REAL_TIME_FACTOR
+}
+
+// RAM is the hard limit; on a CPU without full vector support, models too slow to be usable are
+// excluded as well.
+fn model_likely_runnable(
+    spec: WhisperModelSpec,
+    total_memory_gb: u64,
+    logical_cpu_cores: usize,
+    simd: SimdSupport,
+) -> bool {
+    model_fit_level(spec, total_memory_gb) > 0
+        && (simd == SimdSupport::Full
+            || estimated_real_time_factor(spec, logical_cpu_cores, simd)
+                >= MIN_RUNNABLE_REAL_TIME_FACTOR)
+}
+
+fn check(status: CheckStatus, detail: String) -> RunnabilityCheck {
+    RunnabilityCheck { status, detail }
+}
+
+// One explainable verdict per model. `spec` is None for an imported file too small to match a
+// catalog tier; its RAM and CPU needs are then unknown and only warned about.
+fn model_runnability(
+    spec: Option<WhisperModelSpec>,
+    installed: bool,
+    total_memory_gb: u64,
+    logical_cpu_cores: usize,
+    simd: SimdSupport,
+    free_disk_bytes: Option<u64>,
+) -> ModelRunnability {
+    let ram = match spec {
+        Some(spec) => match model_fit_level(spec, total_memory_gb) {
+            2 => check(
+                CheckStatus::Pass,
+                format!(
+                    "{total_memory_gb} GB RAM meets the {} GB recommended.",
+                    spec.recommended_ram_gb
+                ),
+            ),
+            1 => check(
+                CheckStatus::Warn,
+                format!(
+                    "{total_memory_gb} GB RAM meets the {} GB minimum but not the {} GB recommended; other apps may be squeezed.",
+                    spec.min_ram_gb, spec.recommended_ram_gb
+                ),
+            ),
+            _ => check(
+                CheckStatus::Fail,
+                format!(
+                    "{total_memory_gb} GB RAM is below the {} GB minimum.",
+                    spec.min_ram_gb
+                ),
+            ),
+        },
+        None => check(
+            CheckStatus::Warn,
+            "RAM needs are unknown for this model file.".to_string(),
+        ),
+    };
+    let cpu = match spec {
+        Some(spec) => {
+            let factor = estimated_real_time_factor(spec, logical_cpu_cores, simd);
+            let estimate =
+                format!("Estimated {factor:.1}x real time on {logical_cpu_cores} CPU cores.");
+            if factor >= MIN_RECOMMENDED_REAL_TIME_FACTOR {
+                check(CheckStatus::Pass, estimate)
+            } else if factor >= MIN_RUNNABLE_REAL_TIME_FACTOR || simd == SimdSupport::Full {
+                check(
+                    CheckStatus::Warn,
+                    format!("{estimate} Transcripts will take longer than the speech."),
+                )
+            } else {
+                check(
+                    CheckStatus::Fail,
+                    format!("{estimate} Too slow to be usable without AVX2/NEON."),
+                )
+            }
+        }
+        None => check(
+            CheckStatus::Warn,
+            "Speed is unknown for this model file.".to_string(),
+        ),
+    };
+    let disk = if installed {
+        check(CheckStatus::Pass, "Already downloaded.".to_string())
+    } else if let Some(spec) = spec {
+        let needed = (f64::from(spec.approx_size_gb) * 1_073_741_824.0) as u64;
+        let gb = |bytes: u64| bytes as f64 / 1_073_741_824.0;
+        match free_disk_bytes {
+            Some(free) if free >= needed + MODEL_DOWNLOAD_DISK_HEADROOM_BYTES => check(
+                CheckStatus::Pass,
+                format!(
+                    "{:.1} GB free for a {:.1} GB download.",
+                    gb(free),
+                    gb(needed)
+                ),
+            ),
+            Some(free) if free >= needed => check(
+                CheckStatus::Warn,
+                format!(
+                    "{:.1} GB free for a {:.1} GB download; the drive will be nearly full.",
+                    gb(free),
+                    gb(needed)
+                ),
+            ),
+            Some(free) => check(
+                CheckStatus::Fail,
+                format!(
+                    "Only {:.1} GB free; the download needs {:.1} GB.",
+                    gb(free),
+                    gb(needed)
+                ),
+            ),
+            None => check(
+                CheckStatus::Warn,
+                "Free disk space could not be checked.".to_string(),
+            ),
+        }
+    } else {
+        check(
+            CheckStatus::Fail,
+            "The imported model file is missing; import it again.".to_string(),
+        )
+    };
+
+    let verdict = ram.status.max(cpu.status).max(disk.status);
+    let summary = if ram.status == CheckStatus::Fail {
+        "Won't fit in RAM"
+    } else if cpu.status == CheckStatus::Fail {
+        "Too slow on this CPU"
+    } else if disk.status == CheckStatus::Fail {
+        if installed || spec.is_some() {
+            "Not enough disk to download"
+        } else {
+            "Model file missing"
+        }
+    } else if cpu.status == CheckStatus::Warn && spec.is_some() {
+        "Runnable but slow"
+    } else if ram.status == CheckStatus::Warn && spec.is_some() {
+        "Runnable with little RAM headroom"
+    } else if verdict == CheckStatus::Warn {
+        "Likely runnable"
+    } else {
+        "Runs well on this machine"
+    }
+    .to_string();
+
+    ModelRunnability {
+        verdict,
+        summary,
+        ram,
+        cpu,
+        disk,
+    }
+}
+
+// `df -Pk` prints one header line, then `filesystem blocks used available capacity mount`.
+fn parse_df_available_kib(raw: &str) -> Option<u64> {
+    raw.lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse::<u64>()
+        .ok()
+}
+
+// Free space on the volume holding `path` (or its nearest existing ancestor).
+fn available_disk_bytes(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+
+    #[cfg(unix)]
+    {
+        let output = Command::new("df").arg("-Pk").arg(existing).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let kib = parse_df_available_kib(&String::from_utf8_lossy(&output.stdout))?;
+        return Some(kib.saturating_mul(1024));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let literal = existing.to_string_lossy().replace('\'', "''");
+        let output = Command::new("powershell.exe")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &format!("(Get-Item -LiteralPath '{literal}').PSDrive.Free"),
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        return String::from_utf8(output.stdout)
+            .ok()?
+            .trim_matches(|c| c == '\r' || c == '\n' || c == ' ')
+            .parse::<u64>()
+            .ok();
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+fn recommendation_bias(settings: &LocalSettings) -> RecommendationBias {
+    settings
+        .recommendation_bias
+        .as_deref()
+        .and_then(RecommendationBias::parse)
+        .unwrap_or_default()
+}
+
+// Prefers models that keep up with speech on this CPU, then the strongest RAM fit, so low-core
+// machines and CPUs without AVX2/NEON get a faster model even when RAM would allow a larger one.
+// `speed` only considers the tiny/base tier (when one runs); `accuracy` drops the preference for
+// comfortable RAM headroom so the largest model that meets its minimum wins.
+fn pick_recommended_model_id(
+    total_memory_gb: u64,
+    logical_cpu_cores: usize,
+    simd: SimdSupport,
+    bias: RecommendationBias,
+) -> Option<&'static str> {
+    let runnable: Vec<WhisperModelSpec> = whisper_model_catalog()
+        .iter()
+        .copied()
+        .filter(|spec| model_likely_runnable(*spec, total_memory_gb, logical_cpu_cores, simd))
+        .collect();
+    let fast_tier: Vec<WhisperModelSpec> = runnable
+        .iter()
+        .copied()
+        .filter(|spec| spec.relative_compute <= SPEED_BIAS_MAX_RELATIVE_COMPUTE)
+        .collect();
+    let candidates = if bias == RecommendationBias::Speed && !fast_tier.is_empty() {
+        fast_tier
+    } else {
+        runnable
+    };
+    let fit_rank = |spec: WhisperModelSpec| match bias {
+        RecommendationBias::Accuracy => 0,
+        _ => model_fit_level(spec, total_memory_gb),
+    };
+
+    candidates
+        .into_iter()
+        .max_by(|a, b| {
+            // Prefer strongest runnable model for the machine, not merely the smallest.
+            let a_key = (
+                keeps_up_with_speech(*a, logical_cpu_cores, simd),
+                fit_rank(*a),
+                a.recommended_ram_gb,
+                a.approx_size_gb.to_bits(),
+            );
+            let b_key = (
+                keeps_up_with_speech(*b, logical_cpu_cores, simd),
+                fit_rank(*b),
+                b.recommended_ram_gb,
+                b.approx_size_gb.to_bits(),
+            );
+            a_key.cmp(&b_key)
+        })
+        .map(|spec| spec.id)
+}
+
+fn model_recommendation_for_ram(
+    ram_gb: u64,
+    logical_cpu_cores: usize,
+    simd: SimdSupport,
+    bias: RecommendationBias,
+) -> ModelRecommendationPayload {
+    let catalog = whisper_model_catalog();
+    let ids_with_fit = |level: u8| -> Vec<String> {
+        catalog
+            .iter()
+            .filter(|spec| model_likely_runnable(**spec, ram_gb, logical_cpu_cores, simd))
+            .filter(|spec| model_fit_level(**spec, ram_gb) >= level)
+            .map(|spec| spec.id.to_string())
+            .collect()
+    };
+    let recommended = pick_recommended_model_id(ram_gb, logical_cpu_cores, simd, bias)
+        .and_then(find_whisper_model_spec);
+
+    let rationale = match recommended {
+        Some(spec) if model_fit_level(spec, ram_gb) >= 2 => format!(
+            "{ram_gb} GB meets the {} GB recommended for {}, the strongest model that runs comfortably at this size.",
+            spec.recommended_ram_gb, spec.display_name
+        ),
+        Some(spec) => format!(
+            "{ram_gb} GB meets the {} GB minimum for {} (recommended {} GB); no model fits comfortably, so this is the strongest one that should still run.",
+            spec.min_ram_gb, spec.display_name, spec.recommended_ram_gb
+        ),
+        None => {
+            let smallest = catalog.iter().min_by_key(|spec| spec.min_ram_gb);
+            match smallest {
+                Some(spec) => format!(
+                    "{ram_gb} GB is below the {} GB minimum of the smallest model ({}).",
+                    spec.min_ram_gb, spec.display_name
+                ),
+                None => "No models are available in the catalog.".to_string(),
+            }
+        }
+    };
+    let cpu_capped = recommended.is_some_and(|picked| {
+        catalog.iter().any(|spec| {
+            model_fit_level(*spec, ram_gb) > 0
+                && spec.recommended_ram_gb > picked.recommended_ram_gb
+                && !keeps_up_with_speech(*spec, logical_cpu_cores, simd)
+        })
+    });
+    let rationale = if cpu_capped {
+        format!(
+            "{rationale} Larger models that fit in RAM would likely run slower than real time on {logical_cpu_cores} CPU cores."
+        )
+    } else {
+        rationale
+    };
+    let rationale = match simd {
+        SimdSupport::Full => rationale,
+        SimdSupport::Partial => format!(
+            "{rationale} This CPU lacks AVX2/FMA, so whisper is expected to run at about half speed."
+        ),
+        SimdSupport::None => format!(
+            "{rationale} This CPU has no AVX or NEON vector support, so whisper is expected to run at about a quarter of normal speed."
+        ),
+    };
+    let rationale = match bias {
+        RecommendationBias::Balanced => rationale,
+        RecommendationBias::Speed => format!(
+            "{rationale} Biased toward speed, so tiny and base models are preferred."
+        ),
+        RecommendationBias::Accuracy => format!(
+            "{rationale} Biased toward accuracy, so the largest model that meets its RAM minimum is preferred over a comfortable fit."
+        ),
+    };
+
+    ModelRecommendationPayload {
+        ram_gb,
+        logical_cpu_cores,
+        recommended_model_id: recommended.map(|spec| spec.id.to_string()),
+        recommended_display_name: recommended.map(|spec| spec.display_name.to_string()),
+        rationale,
+        runnable_model_ids: ids_with_fit(1),
+        comfortable_model_ids: ids_with_fit(2),
+    }
+}
+
+fn build_model_options(
+    models_dir: &Path,
+    total_memory_gb: u64,
+    logical_cpu_cores: usize,
+    simd: SimdSupport,
+    selected_model_id: Option<&str>,
+    settings: &LocalSettings,
+) -> Vec<DictationModelOption> {
+    let recommended_model_id = pick_recommended_model_id(
+        total_memory_gb,
+        logical_cpu_cores,
+        simd,
+        recommendation_bias(settings),
+    );
+    let benchmarks = settings.model_benchmarks.as_ref();
+    let imported_models = settings.imported_models.as_deref().unwrap_or_default();
+    let free_disk_bytes = available_disk_bytes(models_dir);
+
+    whisper_model_catalog()
+        .iter()
+        .map(|spec| {
+            let path = model_path_for_spec(models_dir, *spec);
+            let installed = path.exists();
+            let actual_size_bytes = fs::metadata(&path)
+                .ok()
+                .filter(|meta| meta.is_file())
+                .map(|meta| meta.len());
+            let likely_runnable =
+                model_likely_runnable(*spec, total_memory_gb, logical_cpu_cores, simd);
+            let runnability = model_runnability(
+                Some(*spec),
+                installed,
+                total_memory_gb,
+                logical_cpu_cores,
+                simd,
+                free_disk_bytes,
+            );
+            let recommended = recommended_model_id.is_some_and(|id| id == spec.id);
+            let is_selected = selected_model_id.is_some_and(|id| id == spec.id);
+
+            DictationModelOption {
+                id: spec.id.to_string(),
+                display_name: if is_selected {
+                    format!("{} (Selected)", spec.display_name)
+                } else {
+                    spec.display_name.to_string()
+                },
+                whisper_ref: spec.whisper_ref.to_string(),
+                file_name: spec.file_name.to_string(),
+                path: path.to_string_lossy().to_string(),
+                installed,
+                likely_runnable,
+                runnability,
+                recommended,
+                approx_size_gb: spec.approx_size_gb,
+                estimated_real_time_factor: estimated_real_time_factor(
+                    *spec,
+                    logical_cpu_cores,
+                    simd,
+                ),
+                actual_size_bytes,
+                size_suspect: actual_size_bytes
+                    .is_some_and(|bytes| model_size_is_suspect(*spec, bytes)),
+                min_ram_gb: spec.min_ram_gb,
+                recommended_ram_gb: spec.recommended_ram_gb,
+                speed_note: spec.speed_note.to_string(),
+                quality_note: spec.quality_note.to_string(),
+                benchmark: benchmarks.and_then(|saved| saved.get(spec.id)).cloned(),
+                imported: false,
+                source_path: None,
+                added_at_ms: None,
+            }
+        })
+        .chain(imported_models.iter().map(|model| {
+            let path = imported_model_path(models_dir, &model.id);
+            let actual_size_bytes = fs::metadata(&path)
+                .ok()
+                .filter(|meta| meta.is_file())
+                .map(|meta| meta.len());
+            let estimate = actual_size_bytes.and_then(closest_catalog_spec_by_size);
+            let is_selected = selected_model_id.is_some_and(|id| id == model.id);
+
+            DictationModelOption {
+                id: model.id.clone(),
+                display_name: if is_selected {
+                    format!("{} (Selected)", model.display_name)
+                } else {
+                    model.display_name.clone()
+                },
+                whisper_ref: String::new(),
+                file_name: format!("{}.bin", model.id),
+                path: path.to_string_lossy().to_string(),
+                installed: actual_size_bytes.is_some(),
+                likely_runnable: estimate.is_some_and(|spec| {
+                    model_likely_runnable(spec, total_memory_gb, logical_cpu_cores, simd)
+                }),
+                runnability: model_runnability(
+                    estimate,
+                    actual_size_bytes.is_some(),
+                    total_memory_gb,
+                    logical_cpu_cores,
+                    simd,
+                    free_disk_bytes,
+                ),
+                recommended: false,
+                approx_size_gb: actual_size_bytes
+                    .map_or(0.0, |bytes| (bytes as f64 / 1_073_741_824.0) as f32),
+                estimated_real_time_factor: estimate.map_or(0.0, |spec| {
+                    estimated_real_time_factor(spec, logical_cpu_cores, simd)
+                }),
+                actual_size_bytes,
+                size_suspect: false,
+                min_ram_gb: estimate.map_or(0, |spec| spec.min_ram_gb),
+                recommended_ram_gb: estimate.map_or(0, |spec| spec.recommended_ram_gb),
+                speed_note: "Imported model; speed and RAM are estimated from the file size."
+                    .to_string(),
+                quality_note: format!("Imported from {}.", model.source_path),
+                benchmark: None,
+                imported: true,
+                source_path: Some(model.source_path.clone()),
+                added_at_ms: Some(model.added_at_ms),
+            }
+        }))
+        .collect()
+}
+
+fn imported_model_path(models_dir: &Path, model_id: &str) -> PathBuf {
+    models_dir
+        .join(IMPORTED_MODELS_DIR_NAME)
+        .join(format!("{model_id}.bin"))
+}
+
+fn find_imported_model<'a>(
+    settings: &'a LocalSettings,
+    model_id: &str,
+) -> Option<&'a ImportedModel> {
+    settings
+        .imported_models
+        .iter()
+        .flatten()
+        .find(|model| model.id == model_id)
+}
+
+// Imported files carry no catalog metadata; the catalog entry closest in size stands in for the
+// RAM and speed estimates.
+fn closest_catalog_spec_by_size(size_bytes: u64) -> Option<WhisperModelSpec> {
+    let size_gb = size_bytes as f64 / 1_073_741_824.0;
+    whisper_model_catalog().iter().copied().min_by(|a, b| {
+        let distance = |spec: &WhisperModelSpec| (f64::from(spec.approx_size_gb) - size_gb).abs();
+        distance(a).total_cmp(&distance(b))
+    })
+}
+
+fn validate_imported_model_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Model name cannot be empty.".to_string());
+    }
+    if name.chars().count() > MAX_IMPORTED_MODEL_NAME_LENGTH {
+        return Err(format!(
+            "Model names must be at most {MAX_IMPORTED_MODEL_NAME_LENGTH} characters."
+        ));
+    }
+    Ok(name.to_string())
+}
+
+// `imported-` plus a slug of the file name, numbered until it is unique among imported models.
+fn new_imported_model_id(source_path: &Path, settings: &LocalSettings) -> String {
+    let stem = source_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let slug = stem
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = format!(
+        "{IMPORTED_MODEL_ID_PREFIX}{}",
+        if slug.is_empty() { "model" } else { &slug }
+    );
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while find_imported_model(settings, &id).is_some() {
+        id = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+    id
+}
+
+// Matches what `new_imported_model_id` produces, so an id can never step outside the
+// imported models folder once it is joined into a path.
+fn is_imported_model_id(id: &str) -> bool {
+    id.strip_prefix(IMPORTED_MODEL_ID_PREFIX)
+        .is_some_and(|slug| {
+            !slug.is_empty()
+                && slug.split('-').all(|part| {
+                    !part.is_empty()
+                        && part
+                            .chars()
+                            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit())
+                })
+        })
+}
+
+fn pick_best_installed_model(
+    models_dir: &Path,
+    total_memory_gb: u64,
+    exclude_model_id: Option<&str>,
+) -> Option<(WhisperModelSpec, PathBuf)> {
+    whisper_model_catalog()
+        .iter()
+        .copied()
+        .filter(|spec| !exclude_model_id.is_some_and(|exclude| exclude == spec.id))
+        .filter_map(|spec| {
+            let path = model_path_for_spec(models_dir, spec);
+            if path.exists() {
+                Some((spec, path))
+            } else {
+                None
+            }
+        })
+        .max_by_key(|(spec, _)| installed_model_rank(*spec, total_memory_gb))
+}
+
+fn installed_model_rank(spec: WhisperModelSpec, total_memory_gb: u64) -> (u8, u64, u32) {
+    (
+        model_fit_level(spec, total_memory_gb),
+        spec.recommended_ram_gb,
+        spec.approx_size_gb.to_bits(),
+    )
+}
+
+// Best installed catalog model whose file is smaller than `failed_model_path`, ranked like
+// `pick_best_installed_model`. Works for imported selections too since it compares file sizes.
+fn pick_oom_fallback_model(
+    models_dir: &Path,
+    total_memory_gb: u64,
+    failed_model_path: &Path,
+) -> Option<(WhisperModelSpec, PathBuf)> {
+    let failed_size = fs::metadata(failed_model_path).ok()?.len();
+    whisper_model_catalog()
+        .iter()
+        .copied()
+        .map(|spec| (spec, model_path_for_spec(models_dir, spec)))
+        .filter(|(_, path)| fs::metadata(path).is_ok_and(|meta| meta.len() < failed_size))
+        .max_by_key(|(spec, _)| installed_model_rank(*spec, total_memory_gb))
+}
+
+fn resolve_active_model_path(
+    config: &AppConfig,
+    model_state: &LocalModelState,
+) -> Result<PathBuf, DictationError> {
+    if let Some(path) = &config.whisper_model_path_override {
+        return resolve_whisper_model_path(Some(path.as_str()))
+            .map_err(|error| DictationError::new(ErrorCode::ModelMissing, error));
+    }
+
+    let settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let saved_path = settings
+        .selected_model_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| {
+            DictationError::new(
+                ErrorCode::ModelNotSelected,
+                "No local dictation model selected yet. Install one in onboarding before starting dictation.",
+            )
+        })?;
+
+    let path = PathBuf::from(saved_path);
+    if !path.exists() {
+        return Err(DictationError::new(
+            ErrorCode::ModelMissing,
+            format!(
+                "Selected dictation model file is missing: {}. Reinstall/select a model in onboarding.",
+                path.display()
+            ),
+        ));
+    }
+
+    Ok(path)
+}
+
+fn validate_model_download_mirror(url: &str) -> Result<String, String> {
+    let trimmed = url.trim().trim_end_matches('/');
+    let host = trimmed
+        .strip_prefix("https://")
+        .ok_or_else(|| {
+            format!(
+                "Model download mirror must be an https:// URL: {}",
+                url.trim()
+            )
+        })?
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    if host.is_empty() || trimmed.chars().any(char::is_whitespace) {
+        return Err(format!(
+            "Model download mirror is not a valid URL: {}",
+            url.trim()
+        ));
+    }
+    if trimmed.len() > MAX_MODEL_DOWNLOAD_MIRROR_LENGTH {
+        return Err(format!(
+            "Model download mirror must be at most {MAX_MODEL_DOWNLOAD_MIRROR_LENGTH} characters."
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn custom_model_download_mirror(settings: &LocalSettings) -> Option<String> {
+    let saved = settings.model_download_mirror.as_deref()?;
+    match validate_model_download_mirror(saved) {
+        Ok(mirror) => Some(mirror),
+        Err(error) => {
+            log::warn!("model_download_mirror: ignoring saved mirror: {error}");
+            None
+        }
+    }
+}
+
+fn is_model_repo_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.')
+}
+
+// `owner/name`, as HuggingFace spells repo ids.
+fn validate_model_repo(repo: &str) -> Result<String, String> {
+    let trimmed = repo.trim().trim_matches('/');
+    let valid = trimmed.split_once('/').is_some_and(|(owner, name)| {
+        [owner, name].iter().all(|part| {
+            !part.is_empty() && !part.starts_with('.') && part.chars().all(is_model_repo_name_char)
+        })
+    });
+    if !valid {
+        return Err(format!(
+            "Model repo must look like owner/name (letters, digits, '-', '_', '.'): {}",
+            repo.trim()
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+// A branch, tag, or commit. `/` is allowed for refs like `refs/pr/1` and percent-encoded when the
+// URL is built.
+fn validate_model_revision(revision: &str) -> Result<String, String> {
+    let trimmed = revision.trim();
+    let valid = !trimmed.is_empty()
+        && trimmed.len() <= MAX_MODEL_REVISION_LENGTH
+        && trimmed
+            .split('/')
+            .all(|part| !part.is_empty() && !part.starts_with('.'))
+        && trimmed
+            .chars()
+            .all(|ch| is_model_repo_name_char(ch) || ch == '/');
+    if !valid {
+        return Err(format!(
+            "Model revision must be a branch, tag, or commit of at most {MAX_MODEL_REVISION_LENGTH} characters (letters, digits, '-', '_', '.', '/'): {}",
+            revision.trim()
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn saved_model_source_part(
+    saved: Option<&str>,
+    validate: fn(&str) -> Result<String, String>,
+    default: &str,
+    label: &str,
+) -> String {
+    let Some(saved) = saved else {
+        return default.to_string();
+    };
+    validate(saved).unwrap_or_else(|error| {
+        log::warn!("{label}: ignoring saved value: {error}");
+        default.to_string()
+    })
+}
+
+fn model_repo(settings: &LocalSettings) -> String {
+    saved_model_source_part(
+        settings.model_repo.as_deref(),
+        validate_model_repo,
+        DEFAULT_MODEL_REPO,
+        "model_repo",
+    )
+}
+
+fn model_revision(settings: &LocalSettings) -> String {
+    saved_model_source_part(
+        settings.model_revision.as_deref(),
+        validate_model_revision,
+        DEFAULT_MODEL_REVISION,
+        "model_revision",
+    )
+}
+
+// Builds the base URL on `host` and runs it through the mirror check so every source is https
+// and well-formed.
+fn model_repo_download_base(host: &str, repo: &str, revision: &str) -> Result<String, String> {
+    validate_model_download_mirror(&format!(
+        "{host}/{repo}/resolve/{}",
+        revision.replace('/', "%2F")
+    ))
+}
+
+fn known_model_download_mirrors(settings: &LocalSettings) -> Vec<String> {
+    let (repo, revision) = (model_repo(settings), model_revision(settings));
+    KNOWN_MODEL_DOWNLOAD_HOSTS
+        .iter()
+        .filter_map(
+            |host| match model_repo_download_base(host, &repo, &revision) {
+                Ok(base) => Some(base),
+                Err(error) => {
+                    log::warn!("model download source on {host} skipped: {error}");
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+fn model_download_sources(settings: &LocalSettings) -> Vec<String> {
+    let mut sources: Vec<String> = custom_model_download_mirror(settings).into_iter().collect();
+    for mirror in known_model_download_mirrors(settings) {
+        if !sources.contains(&mirror) {
+            sources.push(mirror);
+        }
+    }
+    sources
+}
+
+fn model_download_url(base_url: &str, file_name: &str) -> String {
+    format!("{}/{file_name}", base_url.trim_end_matches('/'))
+}
+
+// Tries each source in order and returns the base URL that worked. Network-level failures are
+// retried on the same source (resuming the partial file) up to MODEL_DOWNLOAD_ATTEMPTS times,
+// calling `on_retry` before each wait; a fatal failure or exhausted retries removes the partial
+// file so the next source starts clean.
+fn download_whisper_model(
+    model_spec: WhisperModelSpec,
+    target_path: &Path,
+    sources: &[String],
+    on_retry: &dyn Fn(&ModelDownloadRetryPayload),
+) -> Result<String, String> {
+    // Download beside the final name so a force-quit mid-download never leaves a truncated file
+    // that looks installed; startup reconciliation removes the leftover `.part`.
+    let partial_path = partial_download_path(target_path);
+    let mut failures = Vec::new();
+    for source in sources {
+        let model_url = model_download_url(source, model_spec.file_name);
+        let mut attempt = 1;
+        loop {
+            match download_url_to_path(&model_url, &partial_path, attempt > 1) {
+                Ok(()) => {
+                    fs::rename(&partial_path, target_path).map_err(|e| {
+                        let _ = fs::remove_file(&partial_path);
+                        format!(
+                            "Failed to save downloaded model to {}: {e}",
+                            target_path.display()
+                        )
+                    })?;
+                    return Ok(source.clone());
+                }
+                Err(failure) if failure.retryable && attempt < MODEL_DOWNLOAD_ATTEMPTS => {
+                    let delay = model_download_retry_delay(attempt);
+                    attempt += 1;
+                    log::warn!(
+                        "download_whisper_model: {model_url} failed ({}); retrying ({attempt}/{MODEL_DOWNLOAD_ATTEMPTS}) in {}ms",
+                        failure.detail,
+                        delay.as_millis()
+                    );
+                    on_retry(&ModelDownloadRetryPayload {
+                        model_id: model_spec.id.to_string(),
+                        url: model_url.clone(),
+                        attempt,
+                        max_attempts: MODEL_DOWNLOAD_ATTEMPTS,
+                        delay_ms: delay.as_millis() as u64,
+                        error: failure.detail,
+                    });
+                    thread::sleep(delay);
+                }
+                Err(failure) => {
+                    log::warn!(
+                        "download_whisper_model: {model_url} failed: {}",
+                        failure.detail
+                    );
+                    let _ = fs::remove_file(&partial_path);
+                    failures.push(format!("{model_url}: {}", failure.detail));
+                    break;
+                }
+            }
+        }
+    }
+    Err(format!(
+        "Could not download whisper model '{}' from any source. {}",
+        model_spec.id,
+        failures.join("; ")
+    ))
+}
+
+fn partial_download_path(target_path: &Path) -> PathBuf {
+    let mut file_name = target_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    file_name.push(".part");
+    target_path.with_file_name(file_name)
+}
+
+// Removes `.part` files an interrupted download (force-quit, crash) left in the model directory.
+fn remove_partial_downloads(models_dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(models_dir) else {
+        return Vec::new();
+    };
+    let mut removed = Vec::new();
+    for path in read_dir.filter_map(Result::ok).map(|entry| entry.path()) {
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("part") {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => removed.push(path),
+            Err(error) => log::warn!(
+                "remove_partial_downloads: failed to remove {}: {error}",
+                path.display()
+            ),
+        }
+    }
+    removed
+}
+
+// Deletes every catalog model file (and any leftover `.part` download) in `models_dir`, except
+// `protected_path`, which is the WHISPER_MODEL_PATH override when it resolves inside the folder.
+fn delete_catalog_model_files(
+    models_dir: &Path,
+    protected_path: Option<&Path>,
+) -> AllModelsDeletion {
+    let protected_path = protected_path.and_then(|path| path.canonicalize().ok());
+    let mut deletion = AllModelsDeletion::default();
+    for spec in whisper_model_catalog() {
+        let path = model_path_for_spec(models_dir, *spec);
+        let partial_path = partial_download_path(&path);
+        for candidate in [path, partial_path] {
+            let Ok(metadata) = fs::metadata(&candidate) else {
+                continue;
+            };
+            let path_string = candidate.to_string_lossy().to_string();
+            if protected_path.is_some() && candidate.canonicalize().ok() == protected_path {
+                deletion.not_deleted.push(UndeletedModelFile {
+                    model_id: spec.id.to_string(),
+                    path: path_string,
+                    reason: "In use via WHISPER_MODEL_PATH.".to_string(),
+                });
+                continue;
+            }
+            match fs::remove_file(&candidate) {
+                Ok(()) => {
+                    deletion.bytes_freed = deletion.bytes_freed.saturating_add(metadata.len());
+                    deletion.deleted.push(DeletedModelFile {
+                        model_id: spec.id.to_string(),
+                        path: path_string,
+                        size_bytes: metadata.len(),
+                    });
+                }
+                Err(error) => deletion.not_deleted.push(UndeletedModelFile {
+                    model_id: spec.id.to_string(),
+                    path: path_string,
+                    reason: error.to_string(),
+                }),
+            }
+        }
+    }
+    deletion
+}
+
+// Points a selection whose model file disappeared at the best installed model, or clears it.
+// Returns true when the settings changed.
+fn reconcile_selected_model(
+    settings: &mut LocalSettings,
+    models_dir: &Path,
+    total_memory_gb: u64,
+) -> bool {
+    let Some(selected_path) = settings.selected_model_path.clone() else {
+        return false;
+    };
+    if Path::new(&selected_path).is_file() {
+        return false;
+    }
+
+    match pick_best_installed_model(models_dir, total_memory_gb, None) {
+        Some((spec, path)) => {
+            log::warn!(
+                "Selected model {selected_path} is missing; switching to installed model '{}'.",
+                spec.id
+            );
+            settings.selected_model_id = Some(spec.id.to_string());
+            settings.selected_model_path = Some(path.to_string_lossy().to_string());
+        }
+        None => {
+            log::warn!("Selected model {selected_path} is missing; clearing the selection.");
+            settings.selected_model_id = None;
+            settings.selected_model_path = None;
+        }
+    }
+    true
+}
+
+// Startup cleanup after a crash or force-quit, so the first onboarding payload is accurate.
+fn reconcile_local_models(models_dir: &Path, settings_path: &Path, settings: &mut LocalSettings) {
+    for removed in remove_partial_downloads(models_dir) {
+        log::info!("Removed partial model download {}", removed.display());
+    }
+    if reconcile_selected_model(settings, models_dir, system_memory_gb()) {
+        if let Err(error) = save_local_settings(settings_path, settings) {
+            log::warn!("Failed to save reconciled model selection: {error}");
+        }
+    }
+}
+
+fn model_download_retry_delay(failed_attempt: u32) -> Duration {
+    MODEL_DOWNLOAD_RETRY_BASE_DELAY * 2_u32.pow(failed_attempt.saturating_sub(1))
+}
+
+struct DownloadFailure {
+    detail: String,
+    retryable: bool,
+}
+
+// The HTTP status in curl's `The requested URL returned error: 404` or PowerShell's
+// `(404) Not Found`.
+fn download_error_http_status(detail: &str) -> Option<u16> {
+    let status = |code: &str| {
+        (code.len() == 3 && code.chars().all(|ch| ch.is_ascii_digit()))
+            .then(|| code.parse().ok())
+            .flatten()
+    };
+    if let Some((_, rest)) = detail.split_once("returned error: ") {
+        return status(rest.get(..3)?);
+    }
+    detail
+        .split('(')
+        .skip(1)
+        .find_map(|part| part.split_once(')').and_then(|(code, _)| status(code)))
+}
+
+// HTTP 408/429/5xx and connection-level curl failures are worth another try; other 4xx answers
+// (a bad file name, a private repo) will not change.
+fn download_failure_is_retryable(exit_code: Option<i32>, detail: &str) -> bool {
+    if let Some(status) = download_error_http_status(detail) {
+        return matches!(status, 408 | 429) || status >= 500;
+    }
+    if cfg!(target_os = "windows") {
+        return true;
+    }
+    exit_code.is_some_and(|code| RETRYABLE_CURL_EXIT_CODES.contains(&code))
+}
+
+// Invoke-WebRequest has no resume in Windows PowerShell 5.1, so the request is built by hand: a
+// Range from `offset` is appended when the server answers 206, and any other answer rewrites the
+// file from the start.
+#[cfg(any(target_os = "windows", test))]
+fn powershell_download_script(url: &str, target_path: &str, offset: u64) -> String {
+    let quote = |value: &str| format!("'{}'", value.replace('\'', "''"));
+    format!(
+        "$ErrorActionPreference = 'Stop'; \
+         [System.Net.ServicePointManager]::SecurityProtocol = \
+         [System.Net.ServicePointManager]::SecurityProtocol -bor [System.Net.SecurityProtocolType]::Tls12; \
+         $request = [System.Net.HttpWebRequest]::Create({url}); \
+         if ({offset} -gt 0) {{ $request.AddRange([int64]{offset}) }}; \
+         $response = $request.GetResponse(); \
+         $mode = if ({offset} -gt 0 -and [int]$response.StatusCode -eq 206) {{ 'Append' }} else {{ 'Create' }}; \
+         $source = $response.GetResponseStream(); \
+         $file = [System.IO.File]::Open({target}, [System.IO.FileMode]::$mode); \
+         try {{ $source.CopyTo($file) }} finally {{ $file.Close(); $source.Close(); $response.Close() }}",
+        url = quote(url),
+        target = quote(target_path),
+    )
+}
+
+// Errors carry only the downloader's output so callers can say what was being fetched. `resume`
+// continues an existing partial file (curl `-C -`, or a Range request under PowerShell).
+fn download_url_to_path(
+    url: &str,
+    target_path: &Path,
+    resume: bool,
+) -> Result<(), DownloadFailure> {
+    let target_str = target_path.to_string_lossy().to_string();
+
+    #[cfg(target_os = "windows")]
+    let output = {
+        let offset = if resume {
+            fs::metadata(target_path).map_or(0, |metadata| metadata.len())
+        } else {
+            0
+        };
+        Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &powershell_download_script(url, &target_str, offset),
+            ])
+            .output()
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let output = {
+        let mut command = Command::new("curl");
+        command.args(["-L", "--fail"]);
+        if resume && target_path.exists() {
+            command.args(["-C", "-"]);
+        }
+        command.args(["--output", &target_str, url]).output()
+    };
+
+    match output {
+        Ok(result) if result.status.success() && target_path.exists() => Ok(()),
+        Ok(result) => {
+            let stderr = String::from_utf8_lossy(&result.stderr).trim().to_string();
+            let stdout = String::from_utf8_lossy(&result.stdout).trim().to_string();
+            let detail = if !stderr.is_empty() {
+                stderr
+            } else if !stdout.is_empty() {
+                stdout
+            } else {
+                "no output".to_string()
+            };
+            Err(DownloadFailure {
+                retryable: download_failure_is_retryable(result.status.code(), &detail),
+                detail,
+            })
+        }
+        Err(e) => Err(DownloadFailure {
+            detail: format!(
+                "could not start the download command. Install curl or PowerShell support and retry: {e}"
+            ),
+            retryable: false,
+        }),
+    }
+}
+
+fn benchmark_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|ch| ch.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+// 1 - word error rate against the expected text, floored at 0.
+fn benchmark_word_accuracy(transcript: &str, expected: &str) -> f32 {
+    let actual = benchmark_words(transcript);
+    let expected = benchmark_words(expected);
+    if expected.is_empty() {
+        return if actual.is_empty() { 1.0 } else { 0.0 };
+    }
+
+    let mut previous: Vec<usize> = (0..=actual.len()).collect();
+    for (i, expected_word) in expected.iter().enumerate() {
+        let mut current = vec![i + 1; actual.len() + 1];
+        for (j, actual_word) in actual.iter().enumerate() {
+            let substitution = previous[j] + usize::from(expected_word != actual_word);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    let errors = previous[actual.len()] as f32;
+    (1.0 - errors / expected.len() as f32).max(0.0)
+}
+
+fn benchmark_result(
+    model_id: &str,
+    transcript: String,
+    audio_seconds: f32,
+    elapsed: Duration,
+    measured_at_ms: u64,
+) -> ModelBenchmarkResult {
+    let processing_seconds = elapsed.as_secs_f32();
+    let word_accuracy = benchmark_word_accuracy(&transcript, BENCHMARK_EXPECTED_TEXT);
+    ModelBenchmarkResult {
+        model_id: model_id.to_string(),
+        audio_seconds,
+        processing_seconds,
+        real_time_factor: if processing_seconds > 0.0 {
+            audio_seconds / processing_seconds
+        } else {
+            0.0
+        },
+        transcript,
+        word_accuracy,
+        matches_expected: word_accuracy >= BENCHMARK_MATCH_ACCURACY,
+        measured_at_ms,
+    }
+}
+
+fn self_test_stage(
+    name: &'static str,
+    status: SelfTestStatus,
+    message: impl Into<String>,
+) -> SelfTestStage {
+    SelfTestStage {
+        name,
+        status: status.as_str(),
+        message: message.into(),
+    }
+}
+
+fn self_test_stage_from_result(
+    name: &'static str,
+    result: Result<String, String>,
+) -> SelfTestStage {
+    match result {
+        Ok(message) => self_test_stage(name, SelfTestStatus::Pass, message),
+        Err(message) => self_test_stage(name, SelfTestStatus::Fail, message),
+    }
+}
+
+// Skipped stages do not fail the report on their own; the failure that caused the skip does.
+fn self_test_report(stages: Vec<SelfTestStage>) -> SelfTestReport {
+    SelfTestReport {
+        passed: stages
+            .iter()
+            .all(|stage| stage.status != SelfTestStatus::Fail.as_str()),
+        stages,
+    }
+}
+
+fn check_model_file_readable(model_path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(model_path).map_err(|e| {
+        format!(
+            "Model file {} cannot be opened: {e}. Reinstall the model in onboarding.",
+            model_path.display()
+        )
+    })?;
+    let mut magic = [0_u8; 4];
+    std::io::Read::read_exact(&mut file, &mut magic).map_err(|e| {
+        format!(
+            "Model file {} is truncated or unreadable: {e}. Delete and reinstall it.",
+            model_path.display()
+        )
+    })?;
+    if magic != GGML_MODEL_MAGIC {
+        return Err(format!(
+            "{} is not a ggml whisper model (unexpected file header). Delete and reinstall it.",
+            model_path.display()
+        ));
+    }
+    Ok(format!("Model file {} is readable.", model_path.display()))
+}
+
+fn validate_benchmark_clip(path: &Path) -> Result<(), String> {
+    let mut header = [0_u8; 4];
+    let is_riff = fs::File::open(path)
+        .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut header))
+        .is_ok()
+        && &header == b"RIFF";
+    if !is_riff {
+        return Err(format!(
+            "{} is the placeholder benchmark clip, not a real build. Install whisper.cpp's jfk.wav with `bun run benchmark:clip` and rebuild.",
+            path.display()
+        ));
+    }
+    let seconds = prepared_wav_duration_secs(path)?;
+    if !BENCHMARK_CLIP_SECONDS.contains(&seconds) {
+        return Err(format!(
+            "{} is {seconds:.1}s long, not the ~11s whisper.cpp jfk.wav reference clip.",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn bundled_benchmark_clip(resource_dir: &Path) -> Result<PathBuf, String> {
+    let clip_path = resource_dir.join(BENCHMARK_CLIP_FILE_NAME);
+    if !clip_path.is_file() {
+        return Err(format!(
+            "The benchmark clip is missing from the app bundle (expected {}).",
+            clip_path.display()
+        ));
+    }
+    validate_benchmark_clip(&clip_path)?;
+    Ok(clip_path)
+}
+
+fn app_resource_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to resolve the app resource directory: {e}"))
+}
+
+fn build_onboarding_payload(
+    config: &AppConfig,
+    model_state: &LocalModelState,
+    hotkey_state: &GlobalHotkeyState,
+) -> Result<DictationOnboardingPayload, String> {
+    let device = build_device_profile();
+    let settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?
+        .clone();
+    let override_model_path = config
+        .whisper_model_path_override
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string());
+    let override_model_exists = override_model_path
+        .as_deref()
+        .map(|value| Path::new(value).exists())
+        .unwrap_or(false);
+
+    let selected_model_path = if override_model_path.is_some() {
+        override_model_path.clone()
+    } else {
+        settings.selected_model_path.clone()
+    };
+    let selected_model_exists = if override_model_path.is_some() {
+        override_model_exists
+    } else {
+        settings
+            .selected_model_path
+            .as_deref()
+            .map(|value| Path::new(value).exists())
+            .unwrap_or(false)
+    };
+    let selected_model_id = if override_model_path.is_some() {
+        Some("env-override".to_string())
+    } else {
+        settings.selected_model_id.clone()
+    };
+    let dictation_trigger = resolve_effective_dictation_trigger(&settings);
+    let registered_trigger = current_registered_hotkey(hotkey_state).ok().flatten();
+    let registered_runtime = current_trigger_runtime_details(hotkey_state).ok();
+    let trigger_runtime = onboarding_runtime_details(
+        dictation_trigger.as_deref(),
+        registered_trigger.as_deref(),
+        registered_runtime.as_ref(),
+    );
+    let list_selected_model_id = if override_model_path.is_some() {
+        None
+    } else {
+        settings.selected_model_id.as_deref()
+    };
+    let models = build_model_options(
+        &model_state.models_dir(),
+        device.total_memory_gb,
+        device.logical_cpu_cores,
+        SimdSupport::detect(&device.architecture, &device.cpu_features),
+        list_selected_model_id,
+        &settings,
+    );
+    let (whisper_cli_path, whisper_cli_available) = probe_whisper_cli_path(
+        config.whisper_cli_path_override.as_deref(),
+        settings.whisper_cli_path_override.as_deref(),
+        config.bundled_whisper_cli_path.as_deref(),
+        &extra_whisper_cli_paths(&settings),
+    );
+    // Resolution falls back past a placeholder sidecar, so check the bundled path as well as the
+    // one that was finally picked.
+    let whisper_cli_placeholder_path = if whisper_cli_available {
+        None
+    } else {
+        std::iter::once(whisper_cli_path.as_str())
+            .chain(config.bundled_whisper_cli_path.as_deref())
+            .find_map(whisper_cli_is_placeholder)
+            .map(|path| path.to_string_lossy().to_string())
+    };
+    let onboarding_required = !selected_model_exists || !whisper_cli_available;
+    let focused_field_permission =
+        focused_field_insert_permission_status(focused_field_insert_enabled(&settings), false);
+    let host = audio_host(&settings);
+    let available_input_devices = list_input_devices(&host);
+
+    Ok(DictationOnboardingPayload {
+        onboarding_required,
+        selected_model_id,
+        selected_model_path,
+        selected_model_exists,
+        available_input_devices,
+        preferred_input_device: settings.preferred_input_device.clone(),
+        audio_host: host.id().name().to_string(),
+        dictation_trigger,
+        default_dictation_trigger: default_dictation_trigger(),
+        fn_trigger_supported: FN_TRIGGER_SUPPORTED,
+        fn_listener_mode: fn_listener_mode(hotkey_state).to_string(),
+        dictation_trigger_mode: trigger_runtime.mode.as_str().to_string(),
+        dictation_trigger_status: trigger_runtime.status,
+        dictation_trigger_permission_hint: trigger_runtime.permission_hint,
+        focused_field_insert_enabled: focused_field_insert_enabled(&settings),
+        output_mode: output_mode_name(focused_field_insert_enabled(&settings)).to_string(),
+        recommendation_bias: recommendation_bias(&settings).as_str().to_string(),
+        insert_delay_ms: insert_delay_ms(&settings),
+        focused_field_insert_permission_granted: focused_field_permission.granted,
+        focused_field_insert_permission_status: focused_field_permission.status,
+        whisper_cli_available,
+        whisper_cli_path,
+        whisper_cli_path_override: settings.whisper_cli_path_override.clone(),
+        whisper_cli_is_placeholder: whisper_cli_placeholder_path.is_some(),
+        whisper_cli_placeholder_path,
+        whisper_acceleration: whisper_acceleration(&settings).as_str().to_string(),
+        whisper_timeout_seconds: whisper_timeout_seconds(&settings),
+        whisper_threads: whisper_threads_payload(&settings),
+        decode_overrides: decode_overrides_payload(&settings),
+        show_progress: settings.show_progress.unwrap_or(false),
+        partial_transcripts: settings.partial_transcripts.unwrap_or(false),
+        fallback_on_oom: settings.fallback_on_oom.unwrap_or(false),
+        debug_audio_info: settings.debug_audio_info.unwrap_or(false),
+        keep_whisper_warm: settings.keep_whisper_warm.unwrap_or(false),
+        chunk_seconds: chunk_seconds(&settings),
+        trim_silence: settings.trim_silence.unwrap_or(true),
+        noise_gate: noise_gate_payload(&settings),
+        extra_whisper_args: extra_whisper_args(&settings),
+        extra_whisper_cli_paths: extra_whisper_cli_paths(&settings),
+        hotkey_activation: hotkey_activation_payload(&settings),
+        audio_normalization: audio_normalization_payload(&settings),
+        memory_guard: memory_guard_payload(&settings),
+        model_download_mirror: model_download_mirror_payload(&settings),
+        auto_format: auto_format_payload(&settings),
+        low_confidence_retry: low_confidence_retry_payload(&settings),
+        two_pass_decode: settings.two_pass_decode.unwrap_or(false),
+        min_confidence: min_confidence(&settings),
+        profanity_filter: profanity_filter_payload(&settings),
+        dedup_repeats: dedup_repeats_payload(&settings),
+        sound_cues: sound_cues_payload(&settings),
+        min_transcribe_seconds: min_transcribe_seconds(&settings),
+        strip_fillers: filler_stripping_payload(&settings),
+        spoken_commands: spoken_commands_payload(&settings),
+        command_mode: settings.command_mode.unwrap_or(false),
+        last_recording_retention_seconds: last_recording_retention_seconds(&settings),
+        transcript_artifact_tokens: transcript_artifact_tokens(&settings),
+        pill_overlay: pill_overlay_payload(&settings),
+        downmix_mode: downmix_mode(&settings).as_str().to_string(),
+        preserve_channels: preserved_channel_count(&settings) as u16,
+        preferred_sample_rate: preferred_sample_rate(&settings),
+        permissions: permissions_payload(),
+        trailing_punctuation: trailing_punctuation(&settings).as_str().to_string(),
+        output_suffix: output_suffix(&settings).as_str().to_string(),
+        insert_wrap: insert_wrap(&settings).as_str().to_string(),
+        recording_archive: recording_archive_payload(&settings, &model_state.recordings_dir()),
+        models_dir: model_state.models_dir().to_string_lossy().to_string(),
+        device,
+        models,
+    })
+}
+
+#[tauri::command]
+fn open_whisper_setup_page() -> Result<(), DictationError> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut cmd = Command::new("open");
+        cmd.arg(WHISPER_CPP_SETUP_URL);
+        cmd
+    };
+
+    #[cfg(target_os = "linux")]
+    let mut command = {
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(WHISPER_CPP_SETUP_URL);
+        cmd
+    };
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", "", WHISPER_CPP_SETUP_URL]);
+        cmd
+    };
+
+    let status = command
+        .status()
+        .map_err(|e| format!("Failed to open download page: {e}"))?;
+    if !status.success() {
+        return Err(format!(
+            "Could not open setup page automatically. Open {WHISPER_CPP_SETUP_URL} manually."
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+fn downmix_samples<T, F>(data: &[T], channels: usize, mode: DownmixMode, to_f32: F) -> Vec<f32>
+where
+    T: Copy,
+    F: Fn(T) -> f32,
+{
+    if channels == 0 || data.is_empty() {
+        return Vec::new();
+    }
+
+    let picked_channel = match mode {
+        DownmixMode::Average => None,
+        DownmixMode::Left => Some(0),
+        DownmixMode::Right => Some(1.min(channels - 1)),
+        // Multi-mic arrays often carry speech on one capsule; follow whichever channel has
+        // the most energy in this buffer instead of diluting it with the quieter ones.
+        DownmixMode::Dominant => {
+            let mut energy = vec![0.0_f32; channels];
+            for frame in data.chunks(channels) {
+                for (channel, sample) in frame.iter().enumerate() {
+                    let value = to_f32(*sample);
+                    energy[channel] += value * value;
+                }
+            }
+            energy
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(channel, _)| channel)
+        }
+    };
+
+    let mut mono = Vec::with_capacity(data.len() / channels.max(1));
+    for frame in data.chunks(channels) {
+        match picked_channel {
+            Some(channel) => mono.push(to_f32(frame[channel.min(frame.len() - 1)])),
+            None => {
+                let sum: f32 = frame.iter().map(|sample| to_f32(*sample)).sum();
+                mono.push(sum / frame.len() as f32);
+            }
+        }
+    }
+
+    mono
+}
+
+fn store_captured_samples(target: &Arc<Mutex<Vec<f32>>>, samples: &[f32]) {
+    if samples.is_empty() {
+        return;
+    }
+
+    if let Ok(mut guard) = target.lock() {
+        guard.extend_from_slice(samples);
+    }
+}
+
+fn store_channel_samples<T, F>(capture: &ChannelCapture, data: &[T], channels: usize, to_f32: F)
+where
+    T: Copy,
+    F: Fn(T) -> f32,
+{
+    let kept = channels.min(capture.max_channels);
+    if kept == 0 || data.is_empty() {
+        return;
+    }
+
+    if let Ok(mut guard) = capture.buffers.lock() {
+        if guard.len() != kept {
+            guard.resize_with(kept, Vec::new);
+        }
+        for frame in data.chunks_exact(channels) {
+            for (buffer, sample) in guard.iter_mut().zip(frame) {
+                buffer.push(to_f32(*sample));
+            }
+        }
+    }
+}
+
+fn audio_level_from_stats(stats: AudioSignalStats) -> f32 {
+    let peak = (stats.peak_abs / 0.18).clamp(0.0, 1.0);
+    let rms = (stats.rms / 0.06).clamp(0.0, 1.0);
+    ((peak * 0.68) + (rms * 0.32)).clamp(0.0, 1.0)
+}
+
+fn waveform_bins_from_samples(samples: &[f32], count: usize) -> Vec<f32> {
+    if count == 0 {
+        return Vec::new();
+    }
+    if samples.is_empty() {
+        return vec![0.0; count];
+    }
+
+    let chunk_len = (samples.len() / count).max(1);
+    let mut bins = Vec::with_capacity(count);
+    for index in 0..count {
+        let start = index * chunk_len;
+        let end = ((index + 1) * chunk_len).min(samples.len());
+        let slice = if start < samples.len() {
+            &samples[start..end.max(start + 1).min(samples.len())]
+        } else {
+            &samples[samples.len().saturating_sub(1)..]
+        };
+        let peak = slice
+            .iter()
+            .map(|sample| sample.abs())
+            .fold(0.0_f32, f32::max);
+        let normalized = (peak / 0.18).sqrt().clamp(0.0, 1.0);
+        bins.push(normalized);
+    }
+
+    bins
+}
+
+impl LiveAudioMeter {
+    fn emit_samples(&self, samples: &[f32], sample_rate: u32) {
+        if samples.is_empty() || sample_rate == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let should_emit = if let Ok(mut guard) = self.last_emitted_at.lock() {
+            match *guard {
+                Some(last)
+                    if now.duration_since(last)
+                        < Duration::from_millis(LIVE_AUDIO_EMIT_INTERVAL_MS) =>
+                {
+                    false
+                }
+                _ => {
+                    *guard = Some(now);
+                    true
+                }
+            }
+        } else {
+            false
+        };
+
+        if !should_emit {
+            return;
+        }
+
+        let stats = analyze_audio_signal(samples, sample_rate);
+        let payload = DictationAudioLevelPayload {
+            session_id: self.session_id,
+            peak_abs: stats.peak_abs,
+            rms: stats.rms,
+            level: audio_level_from_stats(stats),
+            bars: waveform_bins_from_samples(samples, LIVE_AUDIO_BAR_COUNT),
+        };
+        self.app.emit(DICTATION_AUDIO_LEVEL_EVENT, payload).ok();
+    }
+}
+
+// Where one input callback's frames go: the mono downmix, plus per-channel copies when enabled.
+#[derive(Clone, Copy)]
+struct CaptureSink<'a> {
+    mono: &'a Arc<Mutex<Vec<f32>>>,
+    channels: Option<&'a ChannelCapture>,
+}
+
+fn handle_input_chunk<T, F>(
+    data: &[T],
+    channels: usize,
+    downmix: DownmixMode,
+    sink: CaptureSink<'_>,
+    sample_rate: u32,
+    meter: &LiveAudioMeter,
+    to_f32: F,
+) where
+    T: Copy,
+    F: Fn(T) -> f32,
+{
+    if let Some(capture) = sink.channels {
+        store_channel_samples(capture, data, channels, &to_f32);
+    }
+    let mono = downmix_samples(data, channels, downmix, to_f32);
+    if mono.is_empty() {
+        return;
+    }
+
+    store_captured_samples(sink.mono, &mono);
+    meter.emit_samples(&mono, sample_rate);
+}
+
+fn sample_format_rank(sample_format: SampleFormat) -> u8 {
+    match sample_format {
+        SampleFormat::F32 => 3,
+        SampleFormat::I16 => 2,
+        SampleFormat::U16 => 1,
+        _ => 0,
+    }
+}
+
+// Picks the config whose range holds `rate`, or failing that the one that can get closest to it;
+// ties go to the better sample format.
+fn input_config_for_rate(
+    ranges: impl IntoIterator<Item = cpal::SupportedStreamConfigRange>,
+    rate: u32,
+) -> Option<cpal::SupportedStreamConfig> {
+    let mut best: Option<(u32, u8, cpal::SupportedStreamConfig)> = None;
+    for range in ranges {
+        let format_rank = sample_format_rank(range.sample_format());
+        if format_rank == 0 {
+            continue;
+        }
+        let nearest = rate.clamp(range.min_sample_rate().0, range.max_sample_rate().0);
+        let distance = nearest.abs_diff(rate);
+        let replace = match &best {
+            Some((best_distance, best_rank, _)) => {
+                distance < *best_distance
+                    || (distance == *best_distance && format_rank > *best_rank)
+            }
+            None => true,
+        };
+        if replace {
+            best = Some((
+                distance,
+                format_rank,
+                range.with_sample_rate(cpal::SampleRate(nearest)),
+            ));
+        }
+    }
+    best.map(|(_, _, config)| config)
+}
+
+fn supported_capture_configs(
+    device: &cpal::Device,
+    output_side: bool,
+) -> Result<Vec<cpal::SupportedStreamConfigRange>, String> {
+    if output_side {
+        device
+            .supported_output_configs()
+            .map(Iterator::collect)
+            .map_err(|e| e.to_string())
+    } else {
+        device
+            .supported_input_configs()
+            .map(Iterator::collect)
+            .map_err(|e| e.to_string())
+    }
+}
+
+// WASAPI loopback streams are opened on an output device, so `output_side` reads its formats
+// from the output configs.
+fn choose_input_config(
+    device: &cpal::Device,
+    preferred_rate: Option<u32>,
+    output_side: bool,
+) -> Result<cpal::SupportedStreamConfig, String> {
+    if let Some(rate) = preferred_rate {
+        let preferred = supported_capture_configs(device, output_side)
+            .map(|ranges| input_config_for_rate(ranges, rate));
+        match preferred {
+            Ok(Some(config)) => {
+                log::info!(
+                    "preferred_sample_rate {rate} Hz: using {} Hz",
+                    config.sample_rate().0
+                );
+                return Ok(config);
+            }
+            Ok(None) => log::warn!(
+                "preferred_sample_rate {rate} Hz: no compatible input config; using the device default"
+            ),
+            Err(error) => log::warn!(
+                "preferred_sample_rate {rate} Hz: failed to query input configs ({error}); using the device default"
+            ),
+        }
+    }
+
+    let default_config = if output_side {
+        device.default_output_config()
+    } else {
+        device.default_input_config()
+    };
+    if let Ok(default_config) = default_config {
+        return Ok(default_config);
+    }
+
+    let mut best: Option<(u8, u32, cpal::SupportedStreamConfig)> = None;
+    let ranges = supported_capture_configs(device, output_side)
+        .map_err(|e| format!("Failed to query supported input configs: {e}"))?;
+
+    for range in ranges {
+        let candidate = range.with_max_sample_rate();
+        let format_rank = sample_format_rank(candidate.sample_format());
+        if format_rank == 0 {
+            continue;
+        }
+        let candidate_rate = candidate.sample_rate().0;
+
+        let replace = match &best {
+            Some((best_rank, best_rate, _)) => {
+                format_rank > *best_rank
+                    || (format_rank == *best_rank && candidate_rate > *best_rate)
+            }
+            None => true,
+        };
+        if replace {
+            best = Some((format_rank, candidate_rate, candidate));
+        }
+    }
+
+    best.map(|(_, _, config)| config).ok_or_else(|| {
+        "No compatible microphone input config found. Try a different input device.".to_string()
+    })
+}
+
+fn device_name(device: &cpal::Device, fallback: &str) -> String {
+    device.name().unwrap_or_else(|_| fallback.to_string())
+}
+
+fn find_audio_host_id(name: &str) -> Option<cpal::HostId> {
+    cpal::ALL_HOSTS
+        .iter()
+        .copied()
+        .find(|id| id.name().eq_ignore_ascii_case(name.trim()))
+}
+
+// A saved host that is missing from this build or can't be opened right now falls back to the
+// platform default, so a stale choice never blocks dictation.
+fn audio_host(settings: &LocalSettings) -> cpal::Host {
+    let Some(saved) = settings.audio_host.as_deref() else {
+        return cpal::default_host();
+    };
+    let host = find_audio_host_id(saved)
+        .ok_or_else(|| "not compiled into this build".to_string())
+        .and_then(|id| cpal::host_from_id(id).map_err(|e| e.to_string()));
+    host.unwrap_or_else(|error| {
+        log::warn!("audio_host '{saved}' is unavailable ({error}); using the default host");
+        cpal::default_host()
+    })
+}
+
+fn saved_audio_host(model_state: &LocalModelState) -> cpal::Host {
+    model_state
+        .settings
+        .lock()
+        .map(|settings| audio_host(&settings))
+        .unwrap_or_else(|_| cpal::default_host())
+}
+
+fn audio_hosts_payload(settings: &LocalSettings) -> AudioHostsPayload {
+    let default_id = cpal::default_host().id();
+    let available = cpal::available_hosts();
+    AudioHostsPayload {
+        hosts: cpal::ALL_HOSTS
+            .iter()
+            .map(|id| AudioHostOption {
+                name: id.name().to_string(),
+                is_default: *id == default_id,
+                available: available.contains(id),
+            })
+            .collect(),
+        selected: settings.audio_host.clone(),
+        active: audio_host(settings).id().name().to_string(),
+    }
+}
+
+fn list_input_devices(host: &cpal::Host) -> Vec<DictationInputDevice> {
+    let default_name = host
+        .default_input_device()
+        .map(|device| device_name(&device, "default input"));
+    let mut devices = Vec::<DictationInputDevice>::new();
+    let mut seen_names = HashSet::<String>::new();
+
+    if let Some(name) = default_name.clone() {
+        seen_names.insert(name.clone());
+        devices.push(input_device_entry(name, true));
+    }
+
+    if let Ok(inputs) = host.input_devices() {
+        for device in inputs {
+            let name = device_name(&device, "unknown input");
+            if !seen_names.insert(name.clone()) {
+                continue;
+            }
+            let is_default = default_name.as_deref() == Some(name.as_str());
+            devices.push(input_device_entry(name, is_default));
+        }
+    }
+
+    // WASAPI can open any output device in loopback mode; other hosts need a virtual device,
+    // which already shows up as an input above.
+    #[cfg(target_os = "windows")]
+    if host.id() == cpal::HostId::Wasapi {
+        if let Ok(outputs) = host.output_devices() {
+            for device in outputs {
+                let name = format!(
+                    "{LOOPBACK_DEVICE_PREFIX}{}",
+                    device_name(&device, "unknown output")
+                );
+                if seen_names.insert(name.clone()) {
+                    devices.push(input_device_entry(name, false));
+                }
+            }
+        }
+    }
+
+    devices
+}
+
+fn loopback_output_name(name: &str) -> Option<&str> {
+    name.strip_prefix(LOOPBACK_DEVICE_PREFIX)
+}
+
+fn device_name_is_loopback(name: &str) -> bool {
+    if loopback_output_name(name).is_some() {
+        return true;
+    }
+    let lowered = name.to_ascii_lowercase();
+    LOOPBACK_DEVICE_NAME_HINTS
+        .iter()
+        .any(|hint| lowered.contains(hint))
+}
+
+fn input_device_entry(name: String, is_default: bool) -> DictationInputDevice {
+    let loopback = device_name_is_loopback(&name);
+    let label = match loopback_output_name(&name) {
+        Some(output) => format!("System audio: {output}"),
+        None if loopback => format!("System audio ({name})"),
+        None => name.clone(),
+    };
+    DictationInputDevice {
+        name,
+        is_default,
+        loopback,
+        label,
+    }
+}
+
+fn loopback_unavailable_message(name: &str) -> String {
+    let output = loopback_output_name(name).unwrap_or(name);
+    if cfg!(target_os = "windows") {
+        format!(
+            "System audio source '{output}' is not available. Loopback capture needs the WASAPI audio host and an enabled output device; pick another System audio entry or a microphone."
+        )
+    } else if cfg!(target_os = "macos") {
+        format!(
+            "System audio source '{output}' is not available. macOS has no built-in loopback capture: install a virtual device such as BlackHole, route output to it (a Multi-Output Device keeps your speakers working), and pick it from the input list."
+        )
+    } else {
+        format!(
+            "System audio source '{output}' is not available. On Linux pick a PulseAudio/PipeWire 'Monitor of ...' input to capture what is playing."
+        )
+    }
+}
+
+fn create_input_stream_for_device(
+    device: &cpal::Device,
+    device_name: &str,
+    samples: Arc<Mutex<Vec<f32>>>,
+    channel_capture: Option<ChannelCapture>,
+    meter: LiveAudioMeter,
+    options: InputStreamOptions,
+) -> Result<(Stream, u32, u16), String> {
+    let InputStreamOptions {
+        downmix,
+        preferred_rate,
+        source,
+    } = options;
+    let output_side = source == InputSourceKind::LoopbackOutput;
+    let supported_config = choose_input_config(device, preferred_rate, output_side)
+        .map_err(|e| format!("Failed to resolve input config: {e}"))?;
+    let sample_rate = supported_config.sample_rate().0;
+    log::info!("opening input '{device_name}' at {sample_rate} Hz");
+    let channels = supported_config.channels() as usize;
+    let config: cpal::StreamConfig = supported_config.clone().into();
+    let probe_start_len = samples.lock().map(|guard| guard.len()).unwrap_or(0);
+    let err_fn = |err| {
+        eprintln!("microphone stream error: {err}");
+    };
+
+    let stream = match supported_config.sample_format() {
+        SampleFormat::F32 => {
+            let sink = Arc::clone(&samples);
+            let channel_sink = channel_capture.clone();
+            let live_meter = meter.clone();
+            device
+                .build_input_stream(
+                    &config,
+                    move |data: &[f32], _| {
+                        handle_input_chunk(
+                            data,
+                            channels,
+                            downmix,
+                            CaptureSink {
+                                mono: &sink,
+                                channels: channel_sink.as_ref(),
+                            },
+                            sample_rate,
+                            &live_meter,
+                            |v| v,
+                        );
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| format!("Failed to open f32 input stream: {e}"))?
+        }
+        SampleFormat::I16 => {
+            let sink = Arc::clone(&samples);
+            let channel_sink = channel_capture.clone();
+            let live_meter = meter.clone();
+            device
+                .build_input_stream(
+                    &config,
+                    move |data: &[i16], _| {
+                        handle_input_chunk(
+                            data,
+                            channels,
+                            downmix,
+                            CaptureSink {
+                                mono: &sink,
+                                channels: channel_sink.as_ref(),
+                            },
+                            sample_rate,
+                            &live_meter,
+                            |v| v as f32 / i16::MAX as f32,
+                        );
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| format!("Failed to open i16 input stream: {e}"))?
+        }
+        SampleFormat::U16 => {
+            let sink = Arc::clone(&samples);
+            let channel_sink = channel_capture.clone();
+            let live_meter = meter.clone();
+            device
+                .build_input_stream(
+                    &config,
+                    move |data: &[u16], _| {
+                        handle_input_chunk(
+                            data,
+                            channels,
+                            downmix,
+                            CaptureSink {
+                                mono: &sink,
+                                channels: channel_sink.as_ref(),
+                            },
+                            sample_rate,
+                            &live_meter,
+                            |v| (v as f32 / u16::MAX as f32) * 2.0 - 1.0,
+                        );
+                    },
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| format!("Failed to open u16 input stream: {e}"))?
+        }
+        sample_format => {
+            return Err(format!(
+                "Unsupported input sample format: {sample_format:?}. Try a different input device."
+            ));
+        }
+    };
+
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start microphone stream: {e}"))?;
+
+    // Nothing playing is a valid loopback state, so only microphones get the silence probe.
+    if source != InputSourceKind::Microphone {
+        return Ok((stream, sample_rate, channels as u16));
+    }
+
+    if let Err(error) =
+        wait_for_non_silent_input(&samples, probe_start_len, sample_rate, device_name)
+    {
+        if let Ok(mut guard) = samples.lock() {
+            guard.truncate(probe_start_len);
+        }
+        if let Some(capture) = channel_capture.as_ref() {
+            if let Ok(mut guard) = capture.buffers.lock() {
+                guard.clear();
+            }
+        }
+        drop(stream);
+        return Err(error);
+    }
+
+    Ok((stream, sample_rate, channels as u16))
+}
+
+fn wait_for_non_silent_input(
+    samples: &Arc<Mutex<Vec<f32>>>,
+    start_len: usize,
+    sample_rate: u32,
+    device_name: &str,
+) -> Result<(), String> {
+    if sample_rate == 0 {
+        return Ok(());
+    }
+
+    let min_samples =
+        ((sample_rate as u64 * INPUT_STREAM_PROBE_MIN_DURATION_MS as u64) / 1000).max(32) as usize;
+    let deadline = Instant::now() + Duration::from_millis(INPUT_STREAM_PROBE_TIMEOUT_MS);
+    let mut saw_any_frames = false;
+
+    loop {
+        let observed = if let Ok(guard) = samples.lock() {
+            if guard.len() <= start_len {
+                None
+            } else {
+                let captured = &guard[start_len..];
+                Some((
+                    captured.len(),
+                    analyze_audio_signal(captured, sample_rate).peak_abs,
+                ))
+            }
+        } else {
+            None
+        };
+
+        if let Some((captured_len, peak_abs)) = observed {
+            saw_any_frames = true;
+            if peak_abs > 0.0 {
+                return Ok(());
+            }
+            if captured_len >= min_samples {
+                return Err(format!(
+                    "Microphone '{}' opened but only produced silent audio frames. On macOS this usually means the selected input route is stale or muted. In Sound > Input, switch to another microphone and back, or choose System Default and retry.",
+                    device_name
+                ));
+            }
+        }
+
+        if Instant::now() >= deadline {
+            if !saw_any_frames {
+                return Err(format!(
+                    "Microphone '{}' did not deliver any audio frames after opening. In macOS Settings > Privacy & Security > Microphone, allow this app and retry.",
+                    device_name
+                ));
+            }
+            return Err(format!(
+                "Microphone '{}' opened but only produced silent audio frames. On macOS this usually means microphone access is blocked or the input route is stale. In Privacy & Security > Microphone, allow this app, then check Sound > Input and retry.",
+                device_name
+            ));
+        }
+
+        thread::sleep(Duration::from_millis(INPUT_STREAM_PROBE_POLL_INTERVAL_MS));
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn microphone_media_type() -> Result<&'static objc2_av_foundation::AVMediaType, String> {
+    unsafe {
+        AVMediaTypeAudio.ok_or_else(|| {
+            "AVFoundation did not expose AVMediaTypeAudio on this macOS build.".to_string()
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn microphone_permission_denied_error() -> DictationError {
+    DictationError::new(
+        ErrorCode::MicPermissionDenied,
+        "Microphone permission is denied for this app. In macOS Settings > Privacy & Security > Microphone, allow dicktaint and relaunch the app.",
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn microphone_permission_restricted_error() -> DictationError {
+    DictationError::new(
+        ErrorCode::MicPermissionRestricted,
+        "Microphone access is restricted by macOS for this app. Check Privacy & Security > Microphone or system policy restrictions and retry.",
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn should_focus_main_window_for_microphone_prompt(status: AVAuthorizationStatus) -> bool {
+    status == AVAuthorizationStatus::NotDetermined
+}
+
+#[cfg(target_os = "macos")]
+fn ensure_microphone_access_authorized(app: &tauri::AppHandle) -> Result<(), DictationError> {
+    let (tx, rx) = mpsc::channel::<Result<(), DictationError>>();
+    let tx_main = tx.clone();
+    let app_handle = app.clone();
+
+    app.run_on_main_thread(move || {
+        let media_type = match microphone_media_type() {
+            Ok(value) => value,
+            Err(error) => {
+                let _ = tx_main.send(Err(error.into()));
+                return;
+            }
+        };
+
+        let status = unsafe { AVCaptureDevice::authorizationStatusForMediaType(media_type) };
+        if status == AVAuthorizationStatus::Authorized {
+            let _ = tx_main.send(Ok(()));
+            return;
+        }
+        if status == AVAuthorizationStatus::Denied {
+            let _ = tx_main.send(Err(microphone_permission_denied_error()));
+            return;
+        }
+        if status == AVAuthorizationStatus::Restricted {
+            let _ = tx_main.send(Err(microphone_permission_restricted_error()));
+            return;
+        }
+        if !should_focus_main_window_for_microphone_prompt(status) {
+            let _ = tx_main.send(Err(format!(
+                "Microphone access returned an unknown AVFoundation authorization state ({}).",
+                status.0
+            )
+            .into()));
+            return;
+        }
+
+        show_main_window(&app_handle);
+
+        let tx_request = tx_main.clone();
+        let handler = RcBlock::new(move |granted| {
+            let _ = tx_request.send(if bool::from(granted) {
+                Ok(())
+            } else {
+                Err(microphone_permission_denied_error())
+            });
+        });
+        unsafe {
+            AVCaptureDevice::requestAccessForMediaType_completionHandler(media_type, &handler);
+        }
+    })
+    .map_err(|e| format!("Failed to request microphone access on the macOS main thread: {e}"))?;
+
+    match rx.recv_timeout(Duration::from_secs(15)) {
+        Ok(result) => result,
+        Err(_) => Err(
+            "Timed out waiting for macOS microphone permission. Bring dicktaint to the foreground, approve access in Privacy & Security > Microphone, then retry."
+                .to_string()
+                .into(),
+        ),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn ensure_microphone_access_authorized(_app: &tauri::AppHandle) -> Result<(), DictationError> {
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn microphone_permission_state() -> PermissionState {
+    let Ok(media_type) = microphone_media_type() else {
+        return PermissionState::Unavailable;
+    };
+    let status = unsafe { AVCaptureDevice::authorizationStatusForMediaType(media_type) };
+    if status == AVAuthorizationStatus::Authorized {
+        PermissionState::Granted
+    } else if status == AVAuthorizationStatus::Denied {
+        PermissionState::Denied
+    } else if status == AVAuthorizationStatus::Restricted {
+        PermissionState::Restricted
+    } else if status == AVAuthorizationStatus::NotDetermined {
+        PermissionState::NotDetermined
+    } else {
+        PermissionState::Unavailable
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn microphone_permission_state() -> PermissionState {
+    PermissionState::Unavailable
+}
+
+#[cfg(target_os = "macos")]
+fn input_monitoring_permission_state() -> PermissionState {
+    match unsafe { IOHIDCheckAccess(IOHID_REQUEST_TYPE_LISTEN_EVENT) } {
+        IOHID_ACCESS_TYPE_GRANTED => PermissionState::Granted,
+        IOHID_ACCESS_TYPE_DENIED => PermissionState::Denied,
+        _ => PermissionState::NotDetermined,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn input_monitoring_permission_state() -> PermissionState {
+    PermissionState::Unavailable
+}
+
+fn accessibility_permission_state() -> PermissionState {
+    if !cfg!(target_os = "macos") {
+        return PermissionState::Unavailable;
+    }
+    if macos_accessibility_permission_granted() {
+        PermissionState::Granted
+    } else {
+        PermissionState::NotGranted
+    }
+}
+
+fn permissions_payload() -> PermissionsPayload {
+    let microphone = microphone_permission_state();
+    PermissionsPayload {
+        microphone: microphone.as_str().to_string(),
+        input_monitoring: input_monitoring_permission_state().as_str().to_string(),
+        accessibility: accessibility_permission_state().as_str().to_string(),
+        can_record: !microphone.blocks_use(),
+    }
+}
+
+#[tauri::command]
+fn check_permissions() -> PermissionsPayload {
+    permissions_payload()
+}
+
+#[tauri::command]
+fn open_permission_settings(permission: String) -> Result<(), DictationError> {
+    let kind = PermissionKind::parse(&permission).ok_or_else(|| {
+        format!(
+            "Unsupported permission '{}'. Use microphone, input-monitoring, or accessibility.",
+            permission.trim()
+        )
+    })?;
+    open_permission_settings_pane(kind).map_err(Into::into)
+}
+
+#[cfg(target_os = "macos")]
+fn open_permission_settings_pane(kind: PermissionKind) -> Result<(), String> {
+    let url = format!(
+        "x-apple.systempreferences:com.apple.preference.security?{}",
+        kind.settings_pane()
+    );
+    let status = Command::new("open")
+        .arg(&url)
+        .status()
+        .map_err(|e| format!("Failed to open macOS privacy settings: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("Failed to open macOS privacy settings.".to_string())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn open_permission_settings_pane(_kind: PermissionKind) -> Result<(), String> {
+    Err("Permission settings shortcuts are only available on macOS.".to_string())
+}
+
+// Opening and playing a throwaway stream is what makes macOS attach the app to the microphone
+// privacy list, so onboarding can surface the prompt before the first real dictation.
+fn probe_default_input_stream(host: &cpal::Host) -> Result<String, String> {
+    let device = host.default_input_device().ok_or_else(|| {
+        "No microphone input device found. In macOS Settings > Sound > Input, select a microphone and retry."
+            .to_string()
+    })?;
+    let name = device_name(&device, "default input");
+    let supported_config = choose_input_config(&device, None, false)?;
+    let config: cpal::StreamConfig = supported_config.clone().into();
+    let stream = device
+        .build_input_stream_raw(
+            &config,
+            supported_config.sample_format(),
+            |_data: &cpal::Data, _| {},
+            |err| log::warn!("Microphone permission probe stream error: {err}"),
+            None,
+        )
+        .map_err(|e| format!("Failed to open microphone '{name}': {e}"))?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start microphone '{name}': {e}"))?;
+    thread::sleep(Duration::from_millis(MICROPHONE_PERMISSION_PROBE_MS));
+    drop(stream);
+    Ok(name)
+}
+
+fn create_input_stream(
+    samples: Arc<Mutex<Vec<f32>>>,
+    channel_capture: Option<ChannelCapture>,
+    meter: LiveAudioMeter,
+) -> Result<(Stream, OpenedInput), DictationError> {
+    let (host, preferred_input_name, downmix, preferred_rate) = {
+        let model_state = meter.app.state::<LocalModelState>();
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        (
+            audio_host(&settings),
+            settings.preferred_input_device.clone(),
+            downmix_mode(&settings),
+            preferred_sample_rate(&settings),
+        )
+    };
+    let options = InputStreamOptions {
+        downmix,
+        preferred_rate,
+        source: InputSourceKind::Microphone,
+    };
+    // A chosen system-audio source never falls back to a microphone: recording the room instead
+    // of the meeting would be worse than failing.
+    if let Some(preferred_name) = preferred_input_name
+        .as_deref()
+        .filter(|name| device_name_is_loopback(name))
+    {
+        return create_loopback_input_stream(
+            &host,
+            preferred_name,
+            samples,
+            channel_capture,
+            meter,
+            options,
+        );
+    }
+
+    let mut candidate_devices: Vec<(String, cpal::Device)> = Vec::new();
+
+    let default_name = host
+        .default_input_device()
+        .map(|device| device_name(&device, "default input"));
+
+    if let Some(preferred_name) = preferred_input_name.as_deref() {
+        if let Ok(devices) = host.input_devices() {
+            for device in devices {
+                let name = device_name(&device, "unknown input");
+                if name == preferred_name {
+                    candidate_devices.push((name, device));
+                }
+            }
+        }
+    }
+
+    if let Some(default_device) = host.default_input_device() {
+        let name = device_name(&default_device, "default input");
+        candidate_devices.push((name, default_device));
+    }
+
+    if let Ok(devices) = host.input_devices() {
+        for device in devices {
+            candidate_devices.push((device_name(&device, "unknown input"), device));
+        }
+    }
+
+    if candidate_devices.is_empty() {
+        return Err(DictationError::new(
+            ErrorCode::MicNotFound,
+            "No microphone input device found. In macOS Settings > Sound > Input, select a microphone and retry.",
+        ));
+    }
+
+    let mut attempts: Vec<String> = Vec::new();
+    for (name, device) in candidate_devices {
+        match create_input_stream_for_device(
+            &device,
+            &name,
+            Arc::clone(&samples),
+            channel_capture.clone(),
+            meter.clone(),
+            options,
+        ) {
+            Ok((stream, sample_rate, channels)) => {
+                return Ok((
+                    stream,
+                    OpenedInput {
+                        sample_rate,
+                        channels,
+                        device_name: name,
+                    },
+                ))
+            }
+            Err(err) => attempts.push(format!("{name}: {err}")),
+        }
+    }
+
+    let preferred_detail = preferred_input_name
+        .as_deref()
+        .map(|name| format!(" Preferred input: {name}."))
+        .unwrap_or_default();
+    let default_detail = default_name
+        .as_deref()
+        .map(|name| format!(" Default input: {name}."))
+        .unwrap_or_default();
+
+    Err(DictationError::new(
+        ErrorCode::MicOpenFailed,
+        format!(
+            "Could not open microphone input on this machine. Tried: {}. \
+In macOS Settings > Privacy & Security > Microphone, allow this app/terminal, then pick an input device in Settings > Sound > Input and retry.{}{}",
+            attempts.join(" | "),
+            preferred_detail,
+            default_detail
+        ),
+    ))
+}
+
+fn create_loopback_input_stream(
+    host: &cpal::Host,
+    source_name: &str,
+    samples: Arc<Mutex<Vec<f32>>>,
+    channel_capture: Option<ChannelCapture>,
+    meter: LiveAudioMeter,
+    options: InputStreamOptions,
+) -> Result<(Stream, OpenedInput), DictationError> {
+    let device = match loopback_output_name(source_name) {
+        Some(output_name) => host.output_devices().ok().and_then(|mut devices| {
+            devices.find(|device| device_name(device, "unknown output") == output_name)
+        }),
+        None => host.input_devices().ok().and_then(|mut devices| {
+            devices.find(|device| device_name(device, "unknown input") == source_name)
+        }),
+    };
+    // Only WASAPI turns an input stream on an output device into loopback capture.
+    let device = device
+        .filter(|_| loopback_output_name(source_name).is_none() || cfg!(target_os = "windows"));
+    let Some(device) = device else {
+        return Err(DictationError::new(
+            ErrorCode::MicNotFound,
+            loopback_unavailable_message(source_name),
+        ));
+    };
+
+    let source = if loopback_output_name(source_name).is_some() {
+        InputSourceKind::LoopbackOutput
+    } else {
+        InputSourceKind::LoopbackInput
+    };
+    let options = InputStreamOptions { source, ..options };
+    let (stream, sample_rate, channels) = create_input_stream_for_device(
+        &device,
+        source_name,
+        samples,
+        channel_capture,
+        meter,
+        options,
+    )
+    .map_err(|error| {
+        DictationError::new(
+            ErrorCode::MicOpenFailed,
+            format!("{error} {}", loopback_unavailable_message(source_name)),
+        )
+    })?;
+    Ok((
+        stream,
+        OpenedInput {
+            sample_rate,
+            channels,
+            device_name: source_name.to_string(),
+        },
+    ))
+}
+
+fn spawn_recording_thread(
+    samples: Arc<Mutex<Vec<f32>>>,
+    channel_capture: Option<ChannelCapture>,
+    app: tauri::AppHandle,
+    session_id: u64,
+) -> Result<(CaptureThread, OpenedInput), DictationError> {
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let (init_tx, init_rx) = mpsc::channel::<Result<OpenedInput, DictationError>>();
+    let capture_samples = Arc::clone(&samples);
+    let meter = LiveAudioMeter {
+        app,
+        session_id,
+        last_emitted_at: Arc::new(Mutex::new(None)),
+    };
+
+    let handle = thread::spawn(move || {
+        let stream_result = create_input_stream(capture_samples, channel_capture, meter);
+        match stream_result {
+            Ok((stream, opened)) => {
+                let _ = init_tx.send(Ok(opened));
+                let _ = stop_rx.recv();
+                drop(stream);
+            }
+            Err(e) => {
+                let _ = init_tx.send(Err(e));
+            }
+        }
+    });
+
+    let opened = match init_rx.recv_timeout(Duration::from_secs(5)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(e)) => {
+            let _ = handle.join();
+            return Err(e);
+        }
+        Err(_) => {
+            let _ = stop_tx.send(());
+            let _ = handle.join();
+            return Err(DictationError::new(
+                ErrorCode::MicOpenFailed,
+                "Timed out while opening microphone stream.",
+            ));
+        }
+    };
+
+    Ok((CaptureThread { stop_tx, handle }, opened))
+}
+
+fn resample_linear(samples: &[f32], source_rate: u32, target_rate: u32) -> Vec<f32> {
+    if samples.is_empty() || source_rate == 0 {
+        return Vec::new();
+    }
+    if source_rate == target_rate {
+        return samples.to_vec();
+    }
+
+    let ratio = target_rate as f32 / source_rate as f32;
+    let out_len = ((samples.len() as f32) * ratio).round().max(1.0) as usize;
+    let mut out = Vec::with_capacity(out_len);
+
+    for i in 0..out_len {
+        let src_pos = i as f32 / ratio;
+        let idx = src_pos.floor() as usize;
+        let frac = src_pos - idx as f32;
+
+        let a = samples.get(idx).copied().unwrap_or(0.0);
+        let b = samples.get(idx + 1).copied().unwrap_or(a);
+        out.push(a + (b - a) * frac);
+    }
+
+    out
+}
+
+// Writes the same mono sample to every channel of each output frame, then silence once
+// `frames` runs out. Returns the index of the next unplayed frame.
+fn fill_playback_buffer<T: cpal::Sample + cpal::FromSample<f32>>(
+    data: &mut [T],
+    channels: usize,
+    frames: &[f32],
+    next: usize,
+) -> usize {
+    let mut next = next;
+    for frame in data.chunks_mut(channels.max(1)) {
+        let value = frames.get(next).copied().unwrap_or(0.0).clamp(-1.0, 1.0);
+        next += 1;
+        for slot in frame {
+            *slot = T::from_sample(value);
+        }
+    }
+    next
+}
+
+fn build_playback_stream<T: cpal::SizedSample + cpal::FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    frames: Vec<f32>,
+) -> Result<Stream, cpal::BuildStreamError> {
+    let channels = config.channels as usize;
+    let mut next = 0;
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            next = fill_playback_buffer(data, channels, &frames, next);
+        },
+        |err| log::warn!("Playback stream error: {err}"),
+        None,
+    )
+}
+
+// Plays mono `samples` on the host's default output and blocks until they have finished.
+fn play_samples_on_default_output(
+    host: &cpal::Host,
+    samples: &[f32],
+    sample_rate: u32,
+) -> Result<String, String> {
+    let device = host.default_output_device().ok_or_else(|| {
+        "No audio output device found to play the test recording back. Connect speakers or headphones (or choose an output in your system sound settings) and retry."
+            .to_string()
+    })?;
+    let name = device_name(&device, "default output");
+    let supported = device
+        .default_output_config()
+        .map_err(|e| format!("Failed to resolve output config for '{name}': {e}"))?;
+    let output_rate = supported.sample_rate().0;
+    let config: cpal::StreamConfig = supported.clone().into();
+    let frames = resample_linear(samples, sample_rate, output_rate);
+    let duration = Duration::from_secs_f32(frames.len() as f32 / output_rate.max(1) as f32);
+
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_playback_stream::<f32>(&device, &config, frames),
+        SampleFormat::I16 => build_playback_stream::<i16>(&device, &config, frames),
+        SampleFormat::U16 => build_playback_stream::<u16>(&device, &config, frames),
+        sample_format => {
+            return Err(format!(
+                "Unsupported output sample format on '{name}': {sample_format:?}."
+            ));
+        }
+    }
+    .map_err(|e| format!("Failed to open output '{name}': {e}"))?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start output '{name}': {e}"))?;
+    thread::sleep(duration + MIC_PLAYBACK_TAIL);
+    drop(stream);
+    Ok(name)
+}
+
+// Any PCM or float WAV hound understands, averaged down to mono at the file's own rate.
+fn read_wav_as_mono(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Failed to read wav file {}: {e}", path.display()))?;
+    let spec = reader.spec();
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to decode wav file {}: {e}", path.display()))?,
+        hound::SampleFormat::Int => {
+            let scale = (1_i64 << (spec.bits_per_sample.clamp(1, 32) - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|sample| sample.map(|value| value as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to decode wav file {}: {e}", path.display()))?
+        }
+    };
+    let channels = usize::from(spec.channels.max(1));
+    let mono = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok((mono, spec.sample_rate))
+}
+
+fn convert_with_ffmpeg(ffmpeg: &Path, source: &Path) -> Result<(Vec<f32>, u32), String> {
+    let wav_path = std::env::temp_dir().join(format!("{}-import.wav", temp_artifact_base_name()));
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-nostdin", "-hide_banner", "-loglevel", "error", "-y", "-i"])
+        .arg(source)
+        .args(["-vn", "-ac", "1", "-ar"])
+        .arg(WHISPER_SAMPLE_RATE.to_string())
+        .args(["-c:a", "pcm_s16le"])
+        .arg(&wav_path);
+    let result = match run_command_with_timeout(command, FFMPEG_CONVERT_TIMEOUT, None) {
+        Ok(output) if output.status.success() => read_wav_as_mono(&wav_path),
+        Ok(output) => Err(format!(
+            "ffmpeg could not convert {}: {}",
+            source.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(CommandWaitError::Spawn(e)) => Err(format!("Failed to run ffmpeg: {e}")),
+        Err(CommandWaitError::TimedOut) => Err(format!(
+            "ffmpeg took longer than {}s to convert {}.",
+            FFMPEG_CONVERT_TIMEOUT.as_secs(),
+            source.display()
+        )),
+    };
+    let _ = std::fs::remove_file(&wav_path);
+    result
+}
+
+fn read_flac_as_mono(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let mut reader = claxon::FlacReader::open(path)
+        .map_err(|e| format!("Failed to read flac file {}: {e}", path.display()))?;
+    let info = reader.streaminfo();
+    let scale = (1_i64 << (info.bits_per_sample.clamp(1, 32) - 1)) as f32;
+    let mut interleaved: Vec<f32> = reader
+        .samples()
+        .map(|sample| sample.map(|value| value as f32 / scale))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to decode flac file {}: {e}", path.display()))?;
+    let channels = info.channels.max(1) as usize;
+    // Encoders may pad the final block; the stream header holds the real length.
+    if let Some(total) = info.samples {
+        interleaved.truncate((total as usize).saturating_mul(channels));
+    }
+    let mono = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok((mono, info.sample_rate))
+}
+
+// Mono 16-bit, the same depth `write_wav` stores.
+fn write_flac(path: &Path, samples: &[f32], sample_rate: u32) -> Result<(), String> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    if samples.is_empty() {
+        return Err("No audio to encode as FLAC.".to_string());
+    }
+    let pcm: Vec<i32> = samples
+        .iter()
+        .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i32)
+        .collect();
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| format!("Invalid FLAC encoder config: {e}"))?;
+    let source = flacenc::source::MemSource::from_samples(&pcm, 1, 16, sample_rate as usize);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| format!("Failed to encode FLAC: {e:?}"))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| format!("Failed to encode FLAC: {e}"))?;
+    fs::write(path, sink.as_slice()).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn encode_opus_with_ffmpeg(ffmpeg: &Path, wav_path: &Path, target: &Path) -> Result<(), String> {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-nostdin", "-hide_banner", "-loglevel", "error", "-y", "-i"])
+        .arg(wav_path)
+        .args([
+            "-c:a",
+            "libopus",
+            "-b:a",
+            RECORDING_OPUS_BITRATE,
+            "-application",
+            "voip",
+        ])
+        .arg(target);
+    match run_command_with_timeout(command, FFMPEG_CONVERT_TIMEOUT, None) {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => {
+            let _ = fs::remove_file(target);
+            Err(format!(
+                "ffmpeg could not encode {}: {}",
+                target.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+        Err(CommandWaitError::Spawn(e)) => Err(format!("Failed to run ffmpeg: {e}")),
+        Err(CommandWaitError::TimedOut) => {
+            let _ = fs::remove_file(target);
+            Err(format!(
+                "ffmpeg took longer than {}s to encode {}.",
+                FFMPEG_CONVERT_TIMEOUT.as_secs(),
+                target.display()
+            ))
+        }
+    }
+}
+
+// `wav_path` is the sanitized 16 kHz mono clip whisper decoded.
+fn encode_recording(wav_path: &Path, target: &Path, format: RecordingFormat) -> Result<(), String> {
+    match format {
+        RecordingFormat::Wav => {
+            // The temp dir may live on another volume, so fall back to copying when rename fails.
+            if fs::rename(wav_path, target).is_err() {
+                fs::copy(wav_path, target).map_err(|e| {
+                    format!("Failed to save recording to {}: {e}", target.display())
+                })?;
+            }
+            Ok(())
+        }
+        RecordingFormat::Flac => {
+            let (samples, sample_rate) = read_wav_as_mono(wav_path)?;
+            write_flac(target, &samples, sample_rate)
+        }
+        RecordingFormat::Opus => encode_opus_with_ffmpeg(&detect_ffmpeg_path()?, wav_path, target),
+    }
+}
+
+// Saved FLAC/Opus recordings are decoded back to a temporary 16 kHz mono WAV for whisper; the
+// bool is true when the returned path is such a temp file the caller must remove.
+fn saved_recording_wav(recording_path: &Path) -> Result<(PathBuf, bool), String> {
+    if RecordingFormat::from_path(recording_path) == Some(RecordingFormat::Wav) {
+        return Ok((recording_path.to_path_buf(), false));
+    }
+    let (samples, sample_rate) = decode_audio_file(recording_path)?;
+    let samples = resample_linear(&samples, sample_rate, WHISPER_SAMPLE_RATE);
+    let wav_path =
+        std::env::temp_dir().join(format!("{}-recording.wav", temp_artifact_base_name()));
+    write_wav(&wav_path, &samples, 1, WHISPER_SAMPLE_RATE)?;
+    Ok((wav_path, true))
+}
+
+// WAV and FLAC are read in-process; a file either rejects (e.g. an unusual codec) still gets an
+// ffmpeg try.
+fn decode_audio_file(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    if matches!(extension.as_str(), "wav" | "wave") {
+        return read_wav_as_mono(path).or_else(|wav_error| {
+            let ffmpeg = detect_ffmpeg_path().map_err(|_| wav_error)?;
+            convert_with_ffmpeg(&ffmpeg, path)
+        });
+    }
+    if extension == "flac" {
+        return read_flac_as_mono(path).or_else(|flac_error| {
+            let ffmpeg = detect_ffmpeg_path().map_err(|_| flac_error)?;
+            convert_with_ffmpeg(&ffmpeg, path)
+        });
+    }
+    if !FFMPEG_AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+        let shown = if extension.is_empty() {
+            "files without an extension".to_string()
+        } else {
+            format!("'.{extension}' files")
+        };
+        return Err(format!(
+            "Cannot transcribe {shown}. Supported formats: wav, {}.",
+            FFMPEG_AUDIO_EXTENSIONS.join(", ")
+        ));
+    }
+    convert_with_ffmpeg(&detect_ffmpeg_path()?, path)
+}
+
+// `samples` are interleaved frames of `channels` samples each.
+fn write_wav(path: &Path, samples: &[f32], channels: u16, sample_rate: u32) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::create(path, spec)
+        .map_err(|e| format!("Failed to create wav file {}: {e}", path.display()))?;
+    for sample in samples {
+        let clipped = sample.clamp(-1.0, 1.0);
+        let pcm = (clipped * i16::MAX as f32) as i16;
+        writer
+            .write_sample(pcm)
+            .map_err(|e| format!("Failed to write wav sample: {e}"))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize wav file: {e}"))?;
+
+    Ok(())
+}
+
+fn analyze_audio_signal(samples: &[f32], sample_rate: u32) -> AudioSignalStats {
+    if samples.is_empty() || sample_rate == 0 {
+        return AudioSignalStats {
+            peak_abs: 0.0,
+            rms: 0.0,
+            duration_secs: 0.0,
+        };
+    }
+
+    let mut peak_abs = 0.0_f32;
+    let mut energy = 0.0_f64;
+    for sample in samples {
+        let abs = sample.abs();
+        if abs > peak_abs {
+            peak_abs = abs;
+        }
+        energy += f64::from(*sample) * f64::from(*sample);
+    }
+
+    AudioSignalStats {
+        peak_abs,
+        rms: (energy / samples.len() as f64).sqrt() as f32,
+        duration_secs: samples.len() as f32 / sample_rate as f32,
+    }
+}
+
+fn frame_zero_crossing_rate(frame: &[f32]) -> f32 {
+    if frame.len() < 2 {
+        return 0.0;
+    }
+    let crossings = frame
+        .windows(2)
+        .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+        .count();
+    crossings as f32 / (frame.len() - 1) as f32
+}
+
+// Marks each VAD frame as speech or not. Low-ZCR frames only need to clear the relative level;
+// high-ZCR frames must also stand out from the noise floor, so steady hiss never counts while
+// breathy consonants over a quiet room still do.
+fn classify_speech_frames(samples: &[f32], frame_len: usize) -> Vec<bool> {
+    classify_frame_levels(&frame_levels(samples, frame_len))
+}
+
+// (rms, zero-crossing rate) per VAD frame.
+fn frame_levels(samples: &[f32], frame_len: usize) -> Vec<(f32, f32)> {
+    samples
+        .chunks(frame_len)
+        .map(|frame| {
+            let energy = frame
+                .iter()
+                .map(|sample| f64::from(*sample) * f64::from(*sample))
+                .sum::<f64>();
+            let rms = (energy / frame.len() as f64).sqrt() as f32;
+            (rms, frame_zero_crossing_rate(frame))
+        })
+        .collect()
+}
+
+fn classify_frame_levels(frames: &[(f32, f32)]) -> Vec<bool> {
+    if frames.is_empty() {
+        return Vec::new();
+    }
+
+    let mut levels = frames.iter().map(|(rms, _)| *rms).collect::<Vec<_>>();
+    levels.sort_by(f32::total_cmp);
+    let loudest = levels[levels.len() - 1];
+    let floor_index = ((levels.len() - 1) as f32 * VAD_NOISE_FLOOR_PERCENTILE) as usize;
+    let noise_floor = levels[floor_index];
+    let speech_level = (loudest * VAD_RELATIVE_SPEECH_LEVEL).max(MIN_TRANSCRIPTION_AUDIO_RMS);
+
+    frames
+        .iter()
+        .map(|(rms, zcr)| {
+            *rms >= speech_level
+                && (*zcr <= VAD_VOICED_MAX_ZCR || *rms >= noise_floor * VAD_NOISE_FLOOR_RATIO)
+        })
+        .collect()
+}
+
+// Ramps the first and last EDGE_FADE_MS from silence to full level; buffers shorter than two fades
+// split the ramp so the two ends never overlap.
+fn apply_edge_fade(mut samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+    let fade_len = ((sample_rate * EDGE_FADE_MS / 1_000) as usize).min(samples.len() / 2);
+    if fade_len == 0 {
+        return samples;
+    }
+    let len = samples.len();
+    for index in 0..fade_len {
+        let gain = index as f32 / fade_len as f32;
+        samples[index] *= gain;
+        samples[len - 1 - index] *= gain;
+    }
+    samples
+}
+
+// Cuts leading and trailing non-speech, keeping SILENCE_TRIM_PAD_MS around the first and last
+// speech frames. Returns an empty buffer when no frame looks like speech.
+fn trim_silence_edges(mut samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+    let frame_len = (sample_rate * VAD_FRAME_MS / 1_000).max(1) as usize;
+    let speech = classify_speech_frames(&samples, frame_len);
+    let (Some(first), Some(last)) = (
+        speech.iter().position(|is_speech| *is_speech),
+        speech.iter().rposition(|is_speech| *is_speech),
+    ) else {
+        return Vec::new();
+    };
+
+    let pad = (sample_rate * SILENCE_TRIM_PAD_MS / 1_000) as usize;
+    let start = (first * frame_len).saturating_sub(pad);
+    let end = ((last + 1) * frame_len + pad).min(samples.len());
+    samples.truncate(end);
+    samples.drain(..start);
+    samples
+}
+
+// Attenuates every VAD frame outside the gate's open runs by NOISE_GATE_ATTENUATION. A run opens on
+// frames at or above `threshold` RMS lasting at least NOISE_GATE_MIN_OPEN_MS and is widened by
+// NOISE_GATE_HOLD_MS each side; gain ramps across a frame on each change so edges do not click.
+fn apply_noise_gate(mut samples: Vec<f32>, sample_rate: u32, threshold: f32) -> Vec<f32> {
+    let frame_len = (sample_rate * VAD_FRAME_MS / 1_000).max(1) as usize;
+    let loud = frame_levels(&samples, frame_len)
+        .iter()
+        .map(|(rms, _)| *rms >= threshold)
+        .collect::<Vec<_>>();
+    let min_open = (NOISE_GATE_MIN_OPEN_MS / VAD_FRAME_MS).max(1) as usize;
+    let hold = (NOISE_GATE_HOLD_MS / VAD_FRAME_MS) as usize;
+    let mut open = vec![false; loud.len()];
+    let mut index = 0;
+    while index < loud.len() {
+        if !loud[index] {
+            index += 1;
+            continue;
+        }
+        let run_start = index;
+        while index < loud.len() && loud[index] {
+            index += 1;
+        }
+        if index - run_start >= min_open {
+            let from = run_start.saturating_sub(hold);
+            let to = (index + hold).min(loud.len());
+            open[from..to].iter_mut().for_each(|frame| *frame = true);
+        }
+    }
+
+    let gain_for = |is_open: bool| if is_open { 1.0 } else { NOISE_GATE_ATTENUATION };
+    let mut previous_gain = gain_for(open.first().copied().unwrap_or(true));
+    for (frame, is_open) in samples.chunks_mut(frame_len).zip(open) {
+        let gain = gain_for(is_open);
+        let len = frame.len() as f32;
+        for (offset, sample) in frame.iter_mut().enumerate() {
+            let progress = (offset + 1) as f32 / len;
+            *sample *= previous_gain + (gain - previous_gain) * progress;
+        }
+        previous_gain = gain;
+    }
+    samples
+}
+
+// `cut_in_pause` is false for the first chunk and for chunks that start at a quietest-frame
+// fallback cut, which may fall inside a word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct AudioChunk {
+    range: std::ops::Range<usize>,
+    cut_in_pause: bool,
+}
+
+// Splits audio longer than `chunk_seconds` into contiguous sample ranges. Each cut lands in the
+// middle of the longest non-speech run in the back half of the chunk (or its quietest frame when
+// the speaker never pauses), so no word straddles two chunks and no audio is repeated.
+fn silence_chunk_bounds(samples: &[f32], sample_rate: u32, chunk_seconds: u32) -> Vec<AudioChunk> {
+    let max_len = sample_rate as usize * chunk_seconds as usize;
+    let frame_len = (sample_rate * VAD_FRAME_MS / 1_000).max(1) as usize;
+    let max_frames = max_len / frame_len;
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    let mut cut_in_pause = false;
+    if samples.len() <= max_len || max_frames < 2 {
+        bounds.push(AudioChunk {
+            range: start..samples.len(),
+            cut_in_pause,
+        });
+        return bounds;
+    }
+
+    let frames = frame_levels(samples, frame_len);
+    let speech = classify_frame_levels(&frames);
+    while frames.len() - start > max_frames {
+        let window = start + max_frames / 2..start + max_frames;
+        let mut best: Option<(usize, usize)> = None;
+        let mut run_start = None;
+        for index in window.clone() {
+            if !speech[index] {
+                run_start.get_or_insert(index);
+            }
+            let run_ended = speech[index] || index + 1 == window.end;
+            if let (true, Some(begin)) = (run_ended, run_start) {
+                let end = if speech[index] { index } else { index + 1 };
+                if best.map_or(true, |(best_begin, best_end)| {
+                    end - begin > best_end - best_begin
+                }) {
+                    best = Some((begin, end));
+                }
+                run_start = None;
+            }
+        }
+        let cut = match best {
+            Some((begin, end)) => (begin + end) / 2,
+            None => window
+                .clone()
+                .min_by(|a, b| frames[*a].0.total_cmp(&frames[*b].0))
+                .unwrap_or(window.end),
+        };
+        bounds.push(AudioChunk {
+            range: start * frame_len..cut * frame_len,
+            cut_in_pause,
+        });
+        cut_in_pause = best.is_some();
+        start = cut;
+    }
+    bounds.push(AudioChunk {
+        range: start * frame_len..samples.len(),
+        cut_in_pause,
+    });
+    bounds
+}
+
+fn clipping_fraction(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let clipped = samples
+        .iter()
+        .filter(|sample| sample.abs() >= CLIPPING_SAMPLE_LEVEL)
+        .count();
+    clipped as f32 / samples.len() as f32
+}
+
+fn clipping_warning(fraction: f32) -> Option<String> {
+    (fraction >= CLIPPING_WARNING_FRACTION).then(|| {
+        format!(
+            "Input was clipping ({:.1}% of samples at full scale); lower your mic gain for better accuracy.",
+            fraction * 100.0
+        )
+    })
+}
+
+fn audio_signal_is_too_quiet(stats: AudioSignalStats) -> bool {
+    stats.peak_abs < MIN_TRANSCRIPTION_AUDIO_PEAK && stats.rms < MIN_TRANSCRIPTION_AUDIO_RMS
+}
+
+fn normalize_audio_gain(samples: Vec<f32>, stats: AudioSignalStats, target_peak: f32) -> Vec<f32> {
+    if stats.peak_abs <= 0.0 {
+        return samples;
+    }
+
+    let gain = (target_peak / stats.peak_abs).min(MAX_TRANSCRIPTION_AUDIO_GAIN);
+    if gain <= 1.0 {
+        return samples;
+    }
+
+    apply_audio_gain(samples, gain)
+}
+
+// Unlike peak mode, RMS mode also attenuates hot input so every clip lands near the same loudness.
+fn normalize_audio_rms(samples: Vec<f32>, stats: AudioSignalStats, target_rms: f32) -> Vec<f32> {
+    if stats.rms <= 0.0 {
+        return samples;
+    }
+
+    let gain = (target_rms / stats.rms).min(MAX_TRANSCRIPTION_AUDIO_GAIN);
+    samples
+        .into_iter()
+        .map(|sample| soft_limit_sample(sample * gain))
+        .collect()
+}
+
+// Leaves samples below RMS_LIMITER_THRESHOLD untouched and bends anything louder smoothly toward
+// (but never reaching) full scale, so a boosted plosive does not flat-top.
+fn soft_limit_sample(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= RMS_LIMITER_THRESHOLD {
+        return sample;
+    }
+    let headroom = 1.0 - RMS_LIMITER_THRESHOLD;
+    let limited =
+        RMS_LIMITER_THRESHOLD + headroom * ((magnitude - RMS_LIMITER_THRESHOLD) / headroom).tanh();
+    limited.copysign(sample)
+}
+
+fn apply_audio_gain(samples: Vec<f32>, gain: f32) -> Vec<f32> {
+    samples
+        .into_iter()
+        .map(|sample| (sample * gain).clamp(-1.0, 1.0))
+        .collect()
+}
+
+fn normalize_audio_level(
+    samples: Vec<f32>,
+    stats: AudioSignalStats,
+    options: AudioPrepOptions,
+) -> Vec<f32> {
+    match options.normalization_mode {
+        AudioNormalizationMode::Peak => {
+            normalize_audio_gain(samples, stats, options.normalization_target_level)
+        }
+        AudioNormalizationMode::Rms => {
+            normalize_audio_rms(samples, stats, options.normalization_target_level)
+        }
+        AudioNormalizationMode::Off => samples,
+    }
+}
+
+fn quiet_audio_error(stats: AudioSignalStats, input_device_name: &str) -> String {
+    format!(
+        "Captured audio from '{}' was too quiet to transcribe (peak {:.4}, rms {:.4}, {:.1}s). Check macOS Sound > Input, confirm the selected microphone, and retry.",
+        input_device_name, stats.peak_abs, stats.rms, stats.duration_secs
+    )
+}
+
+fn is_transcript_artifact_token(token: &str, artifact_tokens: &[String]) -> bool {
+    let normalized = token.trim_matches(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_');
+    let upper = normalized.to_ascii_uppercase();
+    artifact_tokens.contains(&upper)
+}
+
+// Whisper marks other non-speech as a bracketed or parenthesized span ("[APPLAUSE]", "(sighs)",
+// "[_TT_150]", "(door closes)"). Only spans that stand alone as words are dropped, along with
+// punctuation glued to their end, so "f(x)" and "(s)he" survive.
+fn strip_bracketed_non_speech(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find(['[', '(']) {
+        let (before, from) = rest.split_at(start);
+        out.push_str(before);
+        let close = if from.starts_with('[') { ']' } else { ')' };
+        let starts_word = out.chars().last().map_or(true, char::is_whitespace);
+        let span_end = from[1..].find(close).map(|end| end + 2);
+        let after = span_end.map(|end| {
+            from[end..]
+                .trim_start_matches(|ch: char| ch.is_ascii_punctuation() && ch != '(' && ch != '[')
+        });
+        match after {
+            Some(after)
+                if starts_word && after.chars().next().map_or(true, char::is_whitespace) =>
+            {
+                rest = after;
+            }
+            _ => {
+                out.push_str(&from[..1]);
+                rest = &from[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn normalize_transcript_text(raw: &str, artifact_tokens: &[String]) -> String {
+    strip_bracketed_non_speech(raw)
+        .split_whitespace()
+        .filter(|token| !is_transcript_artifact_token(token, artifact_tokens))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn auto_format_word_core(token: &str) -> &str {
+    token
+        .trim_start_matches(['"', '\'', '(', '['])
+        .trim_end_matches(['"', '\'', ')', ']', ',', ';', ':', '.', '!', '?', '…'])
+}
+
+// Anything other than a plain lowercase word (digits, symbols, flags, paths, existing capitals)
+// is treated as deliberate and makes the whole sentence off-limits.
+fn auto_format_token_is_technical(token: &str) -> bool {
+    let core = auto_format_word_core(token);
+    core.starts_with(['-', '\''])
+        || core
+            .chars()
+            .any(|ch| !(ch.is_lowercase() || ch == '\'' || ch == '-'))
+}
+
+// Abbreviations like "e.g." keep their inner dots and do not end a sentence.
+fn auto_format_token_ends_sentence(token: &str) -> bool {
+    let trimmed = token.trim_end_matches(['"', '\'', ')', ']']);
+    trimmed.ends_with(['.', '!', '?', '…'])
+        && !trimmed.trim_end_matches(['.', '!', '?', '…']).contains('.')
+}
+
+fn capitalize_first_letter(token: &str) -> String {
+    let mut capitalized = String::with_capacity(token.len());
+    let mut done = false;
+    for ch in token.chars() {
+        if !done && ch.is_alphabetic() {
+            capitalized.extend(ch.to_uppercase());
+            done = true;
+        } else {
+            capitalized.push(ch);
+        }
+    }
+    capitalized
+}
+
+// Capitalizes sentence starts and a standalone "i", and can close a final sentence with a period.
+// Sentences containing anything that looks technical (commands, flags, paths, numbers) are left
+// exactly as whisper produced them.
+fn auto_format_transcript(text: &str, options: AutoFormatOptions) -> String {
+    if !options.enabled || text.is_empty() {
+        return text.to_string();
+    }
+
+    let tokens: Vec<&str> = text.split(' ').collect();
+    let mut formatted: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut last_sentence_is_plain = false;
+    let mut last_sentence_words = 0;
+    let mut start = 0;
+    while start < tokens.len() {
+        let end = tokens[start..]
+            .iter()
+            .position(|token| auto_format_token_ends_sentence(token))
+            .map(|offset| start + offset + 1)
+            .unwrap_or(tokens.len());
+        let sentence = &tokens[start..end];
+        let plain = !sentence
+            .iter()
+            .any(|token| auto_format_token_is_technical(token));
+        for (index, token) in sentence.iter().enumerate() {
+            let core = auto_format_word_core(token);
+            let is_pronoun_i = matches!(core, "i" | "i'm" | "i've" | "i'll" | "i'd");
+            if plain && (index == 0 || is_pronoun_i) {
+                formatted.push(capitalize_first_letter(token));
+            } else {
+                formatted.push(token.to_string());
+            }
+        }
+        last_sentence_is_plain = plain;
+        last_sentence_words = sentence.len();
+        start = end;
+    }
+
+    let mut output = formatted.join(" ");
+    if options.append_period
+        && last_sentence_is_plain
+        && last_sentence_words >= AUTO_FORMAT_MIN_SENTENCE_WORDS
+        && output.ends_with(char::is_alphanumeric)
+    {
+        output.push('.');
+    }
+    output
+}
+
+// Whisper often attaches its own punctuation or capitals to a spoken command ("Comma,"), so
+// matching ignores both; apostrophes and hyphens inside a word are kept.
+fn spoken_command_core(token: &str) -> String {
+    token
+        .trim_matches(|ch: char| !ch.is_alphanumeric())
+        .to_lowercase()
+}
+
+// (attach to the previous word, attach to the next word)
+fn spoken_command_spacing(replacement: &str) -> (bool, bool) {
+    (
+        replacement.starts_with([',', '.', ';', ':', '?', '!', ')', ']', '\n']),
+        replacement.ends_with(['(', '[', '\n']),
+    )
+}
+
+// Commands only match whole tokens, so "commander" or "periodic" are never touched. Sentence
+// punctuation replaces any punctuation whisper already put on the previous word instead of
+// stacking ("hello, comma" -> "hello,").
+fn apply_spoken_commands(text: &str, commands: &[SpokenCommand]) -> String {
+    if commands.is_empty() || text.is_empty() {
+        return text.to_string();
+    }
+
+    let tokens: Vec<&str> = text.split(' ').collect();
+    let cores: Vec<String> = tokens
+        .iter()
+        .map(|token| spoken_command_core(token))
+        .collect();
+    let mut output = String::with_capacity(text.len());
+    let mut attach_next = false;
+    let mut index = 0;
+    while index < tokens.len() {
+        let matched = commands.iter().find(|command| {
+            cores
+                .get(index..index + command.phrase.len())
+                .is_some_and(|window| window == command.phrase.as_slice())
+        });
+        let (piece, attach_previous, attaches_next, consumed) = match matched {
+            Some(command) => {
+                let (attach_previous, attaches_next) = spoken_command_spacing(&command.replacement);
+                (
+                    command.replacement.as_str(),
+                    attach_previous,
+                    attaches_next,
+                    command.phrase.len(),
+                )
+            }
+            None => (tokens[index], false, false, 1),
+        };
+        if matched.is_some() && attach_previous && !piece.starts_with(['\n', ')', ']']) {
+            let kept = output.trim_end_matches([',', '.', ';', ':']).len();
+            output.truncate(kept);
+        }
+        if !output.is_empty() && !attach_previous && !attach_next {
+            output.push(' ');
+        }
+        output.push_str(piece);
+        attach_next = attaches_next;
+        index += consumed;
+    }
+    output
+}
+
+fn is_profanity_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '\''
+}
+
+// Masks whole words only, so "dickens" survives a "dick" entry. Punctuation around a word is kept
+// and apostrophes at the edges of a token (quotes) are not part of the match.
+fn mask_profanity(text: &str, words: &[String]) -> String {
+    if words.is_empty() {
+        return text.to_string();
+    }
+
+    let mut masked = String::with_capacity(text.len());
+    let mut token = String::new();
+    let flush = |token: &mut String, masked: &mut String| {
+        if token.is_empty() {
+            return;
+        }
+        let core = token.trim_matches('\'');
+        let lead = token.len() - token.trim_start_matches('\'').len();
+        if !core.is_empty() && words.contains(&core.to_lowercase()) {
+            masked.push_str(&token[..lead]);
+            masked.push_str(&"*".repeat(core.chars().count()));
+            masked.push_str(&token[lead + core.len()..]);
+        } else {
+            masked.push_str(token);
+        }
+        token.clear();
+    };
+    for ch in text.chars() {
+        if is_profanity_word_char(ch) {
+            token.push(ch);
+        } else {
+            flush(&mut token, &mut masked);
+            masked.push(ch);
+        }
+    }
+    flush(&mut token, &mut masked);
+    masked
+}
+
+fn filler_token_core(token: &str) -> String {
+    token
+        .trim_matches(|ch: char| !ch.is_alphanumeric())
+        .to_lowercase()
+}
+
+// Drops filler words from the two ends only: "Um, so we went, uh." becomes "So we went." The
+// capital and the closing punctuation of the removed edge tokens carry over to what remains.
+fn strip_edge_filler_words(text: &str, words: &[String]) -> String {
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut token_start = None;
+    for (index, ch) in text.char_indices() {
+        if !ch.is_whitespace() {
+            token_start.get_or_insert(index);
+        } else if let Some(start) = token_start.take() {
+            spans.push((start, index));
+        }
+    }
+    if let Some(start) = token_start {
+        spans.push((start, text.len()));
+    }
+    let is_filler =
+        |&(start, end): &(usize, usize)| words.contains(&filler_token_core(&text[start..end]));
+    let first = spans.iter().take_while(|span| is_filler(span)).count();
+    let last = spans.len()
+        - spans[first..]
+            .iter()
+            .rev()
+            .take_while(|span| is_filler(span))
+            .count();
+    if first == 0 && last == spans.len() {
+        return text.to_string();
+    }
+    if first == last {
+        return String::new();
+    }
+
+    let mut stripped = text[spans[first].0..spans[last - 1].1].to_string();
+    if last < spans.len() {
+        let (start, end) = spans[spans.len() - 1];
+        let removed = &text[start..end];
+        let closing = &removed[removed.trim_end_matches(['.', '!', '?']).len()..];
+        if !closing.is_empty() && !stripped.ends_with(['.', '!', '?']) {
+            let kept = stripped.trim_end_matches([',', ';', ':']).len();
+            stripped.truncate(kept);
+            stripped.push_str(closing);
+        }
+    }
+    if first > 0 && text.starts_with(char::is_uppercase) {
+        let mut chars = stripped.chars();
+        if let Some(head) = chars.next() {
+            stripped = head.to_uppercase().chain(chars).collect();
+        }
+    }
+    stripped
+}
+
+// Removes listed phrases while they make up the final sentence ("... today. Thank you."), so a
+// sentence that merely ends with "thank you" is kept.
+fn strip_trailing_filler_phrases(text: &str, phrases: &[String]) -> String {
+    let mut current = text.trim_end();
+    loop {
+        let body = current.trim_end_matches(|ch: char| {
+            matches!(ch, '.' | '!' | '?' | ',' | '…') || ch.is_whitespace()
+        });
+        let start = body.rfind(['.', '!', '?', '…']).map_or(0, |index| {
+            index + body[index..].chars().next().map_or(1, char::len_utf8)
+        });
+        if body.is_empty() || !phrases.contains(&normalize_filler_entry(&body[start..])) {
+            break;
+        }
+        current = body[..start].trim_end();
+    }
+    if current.len() == text.trim_end().len() {
+        text.to_string()
+    } else {
+        current.to_string()
+    }
+}
+
+fn min_transcribe_seconds(settings: &LocalSettings) -> f32 {
+    settings
+        .min_transcribe_seconds
+        .filter(|value| MIN_TRANSCRIBE_SECONDS_RANGE.contains(value))
+        .unwrap_or(DEFAULT_MIN_TRANSCRIBE_SECONDS)
+}
+
+fn too_short_message(captured_seconds: f32, min_seconds: f32) -> String {
+    format!(
+        "That was only {captured_seconds:.1}s. Hold a bit longer - at least {min_seconds:.1}s - and try again."
+    )
+}
+
+fn dedup_max_repeats(settings: &LocalSettings) -> u32 {
+    settings
+        .dedup_max_repeats
+        .filter(|value| DEDUP_MAX_REPEATS_RANGE.contains(value))
+        .unwrap_or(DEFAULT_DEDUP_MAX_REPEATS)
+}
+
+// Keeps the kept copy's own casing but takes the closing punctuation of the last repeat, so
+// "you, you, you, you." becomes "you.".
+fn with_trailing_punctuation_of(word: &str, last: &str) -> String {
+    let core = word.trim_end_matches(|ch: char| !ch.is_alphanumeric());
+    let last_core = last.trim_end_matches(|ch: char| !ch.is_alphanumeric());
+    format!("{core}{}", &last[last_core.len()..])
+}
+
+// Collapses whisper's decode loops ("you you you you you") to one copy. Matching ignores case
+// and punctuation and tries the shortest n-gram first; runs at or under `max_repeats` are kept.
+fn collapse_repeated_ngrams(text: &str, max_repeats: usize) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let comparable: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.trim_matches(|ch: char| !ch.is_alphanumeric())
+                .to_lowercase()
+        })
+        .collect();
+    let mut kept: Vec<String> = Vec::with_capacity(words.len());
+    let mut collapsed = false;
+    let mut index = 0;
+    'words: while index < words.len() {
+        for n in 1..=DEDUP_MAX_NGRAM_WORDS {
+            if index + n > words.len() {
+                break;
+            }
+            let gram = &comparable[index..index + n];
+            if gram.iter().any(String::is_empty) {
+                continue;
+            }
+            let mut count = 1;
+            while index + (count + 1) * n <= words.len()
+                && comparable[index + count * n..index + (count + 1) * n] == *gram
+            {
+                count += 1;
+            }
+            if count > max_repeats {
+                let last_word = words[index + count * n - 1];
+                kept.extend(
+                    words[index..index + n - 1]
+                        .iter()
+                        .map(|word| word.to_string()),
+                );
+                kept.push(with_trailing_punctuation_of(
+                    words[index + n - 1],
+                    last_word,
+                ));
+                index += count * n;
+                collapsed = true;
+                continue 'words;
+            }
+        }
+        kept.push(words[index].to_string());
+        index += 1;
+    }
+    if collapsed {
+        kept.join(" ")
+    } else {
+        text.to_string()
+    }
+}
+
+fn strip_fillers(text: &str, words: &[String], trailing_phrases: &[String]) -> String {
+    if words.is_empty() && trailing_phrases.is_empty() {
+        return text.to_string();
+    }
+    // Words first so "Um, thank you." is seen as the phrase, then again for "... um. Thank you."
+    let text = strip_edge_filler_words(text, words);
+    let text = strip_trailing_filler_phrases(&text, trailing_phrases);
+    strip_edge_filler_words(&text, words)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum UtteranceCommand {
+    // "scratch that" on its own: the utterance is dropped.
+    Scratch,
+    // "command copy <text>": the text goes to the clipboard instead of being inserted.
+    Copy(String),
+}
+
+impl UtteranceCommand {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scratch => "scratch",
+            Self::Copy(_) => "copy",
+        }
+    }
+}
+
+fn split_leading_word(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim_start();
+    if text.is_empty() {
+        return None;
+    }
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    Some((&text[..end], &text[end..]))
+}
+
+// Only the first two words are matched, case-insensitively and ignoring the punctuation whisper
+// attaches ("Scratch that!", "Command, copy: ..."), so the phrases inside a sentence stay text.
+fn parse_utterance_command(transcript: &str) -> Option<UtteranceCommand> {
+    let comparable = |word: &str| {
+        word.trim_matches(|ch: char| !ch.is_alphanumeric())
+            .to_lowercase()
+    };
+    let (first, rest) = split_leading_word(transcript)?;
+    let (second, rest) = split_leading_word(rest)?;
+    match (comparable(first).as_str(), comparable(second).as_str()) {
+        ("scratch", "that") if rest.trim().is_empty() => Some(UtteranceCommand::Scratch),
+        ("command", "copy") => Some(UtteranceCommand::Copy(
+            rest.trim_start_matches(|ch: char| {
+                ch.is_whitespace() || matches!(ch, ',' | ':' | ';' | '-')
+            })
+            .trim_end()
+            .to_string(),
+        )),
+        _ => None,
+    }
+}
+
+fn apply_trailing_punctuation(text: String, mode: TrailingPunctuation) -> String {
+    match mode {
+        TrailingPunctuation::Keep => text,
+        TrailingPunctuation::Period => {
+            let trimmed = text.trim_end_matches([',', ';', ':']);
+            if trimmed.ends_with(['.', '!', '?', '…']) {
+                trimmed.to_string()
+            } else {
+                format!("{trimmed}.")
+            }
+        }
+        // Only a lone final period is dropped; questions, exclamations, and ellipses carry meaning.
+        TrailingPunctuation::None => match text.strip_suffix('.') {
+            Some(stripped) if !stripped.is_empty() && !stripped.ends_with('.') => {
+                stripped.trim_end().to_string()
+            }
+            _ => text,
+        },
+    }
+}
+
+// Long recordings legitimately take longer, so the configured timeout is a floor that grows with
+// the amount of audio handed to whisper.
+fn effective_whisper_timeout(timeout_seconds: u64, audio_seconds: f32) -> Duration {
+    let scaled = (audio_seconds.max(0.0) * WHISPER_TIMEOUT_SECONDS_PER_AUDIO_SECOND).ceil() as u64;
+    Duration::from_secs(timeout_seconds.saturating_add(scaled))
+}
+
+// whisper-cli's --print-progress lines look like "whisper_print_progress_callback: progress =  45%".
+// Anything else returns None so an unfamiliar build just produces no progress events.
+fn parse_whisper_progress_line(line: &str) -> Option<u8> {
+    let (_, rest) = line.split_once("progress =")?;
+    let percent = rest.trim().strip_suffix('%')?.trim().parse::<u8>().ok()?;
+    (percent <= 100).then_some(percent)
+}
+
+fn whisper_timeout_error(timeout: Duration) -> String {
+    format!(
+        "whisper-cli did not finish within {}s and was stopped. The model file may be corrupt or too heavy for this machine; try a smaller model or raise whisper_timeout_seconds.",
+        timeout.as_secs()
+    )
+}
+
+type StderrLineObserver = Box<dyn FnMut(&str) + Send>;
+
+// When `on_stderr_line` is given, each stderr line is handed to it as it arrives; the full stderr
+// is still collected for error reporting either way.
+fn run_command_with_timeout(
+    mut command: Command,
+    timeout: Duration,
+    mut on_stderr_line: Option<StderrLineObserver>,
+) -> Result<Output, CommandWaitError> {
+    let mut child = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(CommandWaitError::Spawn)?;
+
+    // Drain pipes on helper threads so a chatty child cannot block on a full pipe buffer.
+    let stdout_reader = child.stdout.take().map(|mut pipe| {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = std::io::Read::read_to_end(&mut pipe, &mut buffer);
+            buffer
+        })
+    });
+    let stderr_reader = child.stderr.take().map(|pipe| {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let Some(on_line) = on_stderr_line.as_mut() else {
+                let mut pipe = pipe;
+                let _ = std::io::Read::read_to_end(&mut pipe, &mut buffer);
+                return buffer;
+            };
+            let mut reader = std::io::BufReader::new(pipe);
+            let mut line = Vec::new();
+            while matches!(
+                std::io::BufRead::read_until(&mut reader, b'\n', &mut line),
+                Ok(read) if read > 0
+            ) {
+                on_line(String::from_utf8_lossy(&line).trim_end());
+                buffer.append(&mut line);
+            }
+            buffer
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CommandWaitError::TimedOut);
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(WHISPER_PROCESS_POLL_INTERVAL_MS)),
+            Err(error) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CommandWaitError::Spawn(error));
+            }
+        }
+    };
+
+    let stdout = stdout_reader
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    let stderr = stderr_reader
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+// Allocation failures from whisper.cpp / ggml across the CPU, Metal, and CUDA backends.
+fn whisper_error_is_oom(detail: &str) -> bool {
+    let lower = detail.to_ascii_lowercase();
+    [
+        "out of memory",
+        "failed to allocate",
+        "unable to allocate",
+        "cannot allocate memory",
+        "memory allocation failed",
+        "bad_alloc",
+        "not enough space in the",
+        "insufficient memory",
+    ]
+    .iter()
+    .any(|pattern| lower.contains(pattern))
+}
+
+fn run_whisper_cli(
+    whisper_cli_path: &str,
+    model_path: &Path,
+    wav_path: &Path,
+    out_prefix: &Path,
+    audio_seconds: f32,
+    options: &WhisperRunOptions,
+    profile: WhisperDecodeProfile,
+) -> Result<WhisperRunOutput, String> {
+    let output_path = out_prefix.with_extension(options.output_format.extension());
+    let json_path = (options.token_confidence && options.output_format == WhisperOutputFormat::Txt)
+        .then(|| out_prefix.with_extension("json"));
+    let capabilities = probe_whisper_cli_capabilities(&options.capability_cache, whisper_cli_path);
+    let (acceleration, acceleration_args) =
+        resolve_acceleration_args(options.acceleration, capabilities, model_path);
+    log::info!(
+        "run_whisper_cli: acceleration requested={} resolved={}",
+        options.acceleration.as_str(),
+        acceleration.as_str()
+    );
+
+    let timeout = effective_whisper_timeout(options.timeout_seconds, audio_seconds);
+    let warm_eligible = options.output_format == WhisperOutputFormat::Txt
+        && options.extra_args.is_empty()
+        && options.progress.is_none()
+        && !options.token_confidence;
+    if let Some(worker) = options.warm_worker.as_ref().filter(|_| warm_eligible) {
+        if let Some(server_path) = whisper_server_path(whisper_cli_path) {
+            let mut server_args: Vec<String> = ["-l", WHISPER_LANGUAGE]
+                .into_iter()
+                .chain(acceleration_args.iter().copied())
+                .map(str::to_string)
+                .collect();
+            if let Some(threads) = options.threads {
+                server_args.extend(["-t".to_string(), threads.to_string()]);
+            }
+            let config = WarmWhisperConfig {
+                server_path,
+                model_path: model_path.to_path_buf(),
+                server_args,
+            };
+            if let Some(text) =
+                transcribe_with_warm_whisper(worker, config, wav_path, profile, timeout)
+            {
+                return Ok(WhisperRunOutput {
+                    text,
+                    token_confidence: None,
+                });
+            }
+        }
+    }
+
+    let mut command = Command::new(whisper_cli_path);
+    command
+        .arg("-m")
+        .arg(model_path)
+        .arg("-f")
+        .arg(wav_path)
+        .arg("-l")
+        .arg(WHISPER_LANGUAGE)
+        .arg(options.output_format.flag());
+    // Subtitles need segment timestamps; plain text is cleaner without them.
+    if options.output_format == WhisperOutputFormat::Txt {
+        command.arg("-nt");
+    }
+    if json_path.is_some() {
+        command.arg("-ojf");
+    }
+    command
+        .arg("-bs")
+        .arg(profile.beam_size.to_string())
+        .arg("-bo")
+        .arg(profile.best_of.to_string())
+        .args(&acceleration_args)
+        .arg("-of")
+        .arg(out_prefix);
+    if let Some(threads) = options.threads {
+        command.arg("-t").arg(threads.to_string());
+    }
+    command.args(&options.extra_args);
+    if options.progress.is_some() {
+        command.arg("-pp");
+    }
+
+    let on_stderr_line = options.progress.clone().map(|progress| {
+        Box::new(move |line: &str| {
+            if let Some(percent) = parse_whisper_progress_line(line) {
+                let _ = progress.send(percent);
+            }
+        }) as StderrLineObserver
+    });
+    let output = match run_command_with_timeout(command, timeout, on_stderr_line) {
+        Ok(output) => output,
+        Err(CommandWaitError::Spawn(e)) => {
+            return Err(format!(
+                "Failed to execute whisper cli '{whisper_cli_path}': {e}. Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH."
+            ));
+        }
+        Err(CommandWaitError::TimedOut) => {
+            let _ = std::fs::remove_file(&output_path);
+            if let Some(json_path) = json_path.as_ref() {
+                let _ = std::fs::remove_file(json_path);
+            }
+            return Err(whisper_timeout_error(timeout));
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let mut detail = String::new();
+        if !stderr.is_empty() {
+            detail.push_str(&stderr);
+        }
+        if detail.is_empty() && !stdout.is_empty() {
+            detail.push_str(&stdout);
+        }
+        if detail.is_empty() {
+            detail.push_str("no error output");
+        }
+        let _ = std::fs::remove_file(&output_path);
+        if let Some(json_path) = json_path.as_ref() {
+            let _ = std::fs::remove_file(json_path);
+        }
+        if whisper_error_is_oom(&detail) {
+            return Err(format!(
+                "whisper-cli ran out of memory with {}: {detail}. Select a smaller model.",
+                model_path.display()
+            ));
+        }
+        if !options.extra_args.is_empty() {
+            return Err(format!(
+                "whisper-cli transcription failed: {detail}. Custom whisper arguments are in use ({}); this whisper-cli build may not support one of them, so remove it in settings and retry.",
+                options.extra_args.join(" ")
+            ));
+        }
+        return Err(format!("whisper-cli transcription failed: {detail}"));
+    }
+    if let Some((load_ms, total_ms)) =
+        parse_whisper_timings(&String::from_utf8_lossy(&output.stderr))
+    {
+        log::info!(
+            "run_whisper_cli: model load {load_ms:.0}ms, inference {:.0}ms",
+            (total_ms - load_ms).max(0.0)
+        );
+    }
+
+    let transcript = std::fs::read_to_string(&output_path).map_err(|e| {
+        format!(
+            "whisper-cli ran but transcript file is missing at {}: {e}",
+            output_path.display()
+        )
+    })?;
+    let _ = std::fs::remove_file(&output_path);
+    let token_confidence = json_path.and_then(|json_path| {
+        let contents = std::fs::read_to_string(&json_path).ok();
+        let _ = std::fs::remove_file(&json_path);
+        contents.and_then(|contents| whisper_json_token_confidence(&contents))
+    });
+
+    Ok(WhisperRunOutput {
+        text: transcript,
+        token_confidence,
+    })
+}
+
+// Averages token probabilities from whisper's `-ojf` output. Special tokens (`[_BEG_]`,
+// timestamps) are skipped since they say nothing about the recognized words.
+fn whisper_json_token_confidence(contents: &str) -> Option<f32> {
+    let value = serde_json::from_str::<serde_json::Value>(contents).ok()?;
+    let probabilities: Vec<f64> = value
+        .get("transcription")?
+        .as_array()?
+        .iter()
+        .filter_map(|segment| segment.get("tokens").and_then(serde_json::Value::as_array))
+        .flatten()
+        .filter(|token| {
+            token
+                .get("text")
+                .and_then(serde_json::Value::as_str)
+                .is_some_and(|text| !text.trim_start().starts_with("[_"))
+        })
+        .filter_map(|token| token.get("p").and_then(serde_json::Value::as_f64))
+        .collect();
+    if probabilities.is_empty() {
+        return None;
+    }
+    Some((probabilities.iter().sum::<f64>() / probabilities.len() as f64) as f32)
+}
+
+// whisper.cpp ends each run with `whisper_print_timings:` lines; returns (load, total) in ms so the
+// per-run model load cost can be compared with the warm server path.
+fn parse_whisper_timings(stderr: &str) -> Option<(f32, f32)> {
+    let timing = |label: &str| {
+        stderr.lines().find_map(|line| {
+            let (_, rest) = line.split_once(label)?;
+            rest.trim_start()
+                .strip_prefix('=')?
+                .trim()
+                .strip_suffix("ms")?
+                .trim()
+                .parse::<f32>()
+                .ok()
+        })
+    };
+    Some((timing("load time")?, timing("total time")?))
+}
+
+fn whisper_server_path(whisper_cli_path: &str) -> Option<PathBuf> {
+    let server_path = validate_whisper_cli_candidate(whisper_cli_path)
+        .ok()?
+        .with_file_name(WHISPER_SERVER_FILE_NAME);
+    server_path.is_file().then_some(server_path)
+}
+
+fn transcribe_with_warm_whisper(
+    worker: &mpsc::Sender<WarmWhisperMessage>,
+    config: WarmWhisperConfig,
+    wav_path: &Path,
+    profile: WhisperDecodeProfile,
+    timeout: Duration,
+) -> Option<String> {
+    let (reply, reply_rx) = mpsc::channel();
+    worker
+        .send(WarmWhisperMessage::Transcribe(WarmWhisperRequest {
+            config,
+            wav_path: wav_path.to_path_buf(),
+            profile,
+            timeout,
+            reply,
+        }))
+        .ok()?;
+    reply_rx.recv().ok().flatten()
+}
+
+fn start_warm_whisper_server(
+    config: WarmWhisperConfig,
+    timeout: Duration,
+) -> Result<WarmWhisperServer, String> {
+    let started = Instant::now();
+    let limit = WARM_WHISPER_START_TIMEOUT.min(timeout);
+    let mut attempt = 1;
+    loop {
+        let mut server = spawn_warm_whisper_server(config.clone())?;
+        match wait_for_warm_whisper_server(&mut server, started, limit) {
+            Ok(()) => {
+                log::info!(
+                    "warm whisper: loaded {} in {}ms",
+                    server.config.model_path.display(),
+                    started.elapsed().as_millis()
+                );
+                return Ok(server);
+            }
+            Err(Some(status)) if attempt < WARM_WHISPER_START_ATTEMPTS => {
+                log::warn!(
+                    "warm whisper: server on port {} exited while loading ({status}); retrying on a new port",
+                    server.port
+                );
+                attempt += 1;
+            }
+            Err(Some(status)) => {
+                return Err(format!(
+                    "whisper-server exited while loading the model ({status})."
+                ));
+            }
+            Err(None) => {
+                return Err(format!(
+                    "whisper-server did not finish loading {} within {}s.",
+                    server.config.model_path.display(),
+                    limit.as_secs()
+                ));
+            }
+        }
+    }
+}
+
+fn spawn_warm_whisper_server(config: WarmWhisperConfig) -> Result<WarmWhisperServer, String> {
+    let port = std::net::TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("Failed to reserve a local port for whisper-server: {e}"))?;
+    let child = Command::new(&config.server_path)
+        .arg("-m")
+        .arg(&config.model_path)
+        .arg("--host")
+        .arg("127.0.0.1")
+        .arg("--port")
+        .arg(port.to_string())
+        .args(&config.server_args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Failed to start whisper-server {}: {e}",
+                config.server_path.display()
+            )
+        })?;
+    Ok(WarmWhisperServer {
+        config,
+        child,
+        port,
+    })
+}
+
+// The server only binds its port once the model is loaded. Err(Some) means it exited first
+// (possibly because the port was taken), Err(None) that `limit` passed since `started`.
+fn wait_for_warm_whisper_server(
+    server: &mut WarmWhisperServer,
+    started: Instant,
+    limit: Duration,
+) -> Result<(), Option<std::process::ExitStatus>> {
+    loop {
+        let connected = std::net::TcpStream::connect(("127.0.0.1", server.port)).is_ok();
+        // Checked after connecting too: a listener that took the port first must not count.
+        if let Ok(Some(status)) = server.child.try_wait() {
+            return Err(Some(status));
+        }
+        if connected {
+            return Ok(());
+        }
+        if started.elapsed() >= limit {
+            return Err(None);
+        }
+        thread::sleep(Duration::from_millis(WHISPER_PROCESS_POLL_INTERVAL_MS));
+    }
+}
+
+fn warm_whisper_inference(
+    server: &WarmWhisperServer,
+    wav_path: &Path,
+    profile: WhisperDecodeProfile,
+    timeout: Duration,
+) -> Result<String, String> {
+    let started = Instant::now();
+    let mut command = Command::new("curl");
+    command
+        .arg("-sS")
+        .arg("-f")
+        .arg("-F")
+        .arg(format!("file=@{}", wav_path.display()))
+        .arg("-F")
+        .arg("response_format=text")
+        .arg("-F")
+        .arg("temperature=0.0")
+        .arg("-F")
+        .arg(format!("beam_size={}", profile.beam_size))
+        .arg("-F")
+        .arg(format!("best_of={}", profile.best_of))
+        .arg(format!("http://127.0.0.1:{}/inference", server.port));
+    let output = match run_command_with_timeout(command, timeout, None) {
+        Ok(output) => output,
+        Err(CommandWaitError::Spawn(e)) => {
+            return Err(format!("Failed to run curl for whisper-server: {e}"))
+        }
+        Err(CommandWaitError::TimedOut) => return Err(whisper_timeout_error(timeout)),
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "whisper-server request failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    log::info!(
+        "warm whisper: inference {}ms (model already loaded)",
+        started.elapsed().as_millis()
+    );
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// Serializes warm transcriptions on one thread that owns the whisper-server process. The server is
+// started on first use and restarted when the model or launch args change; any failure falls back
+// to the spawn path, and a config that failed to start is not retried until it changes.
+fn spawn_warm_whisper_worker() -> mpsc::Sender<WarmWhisperMessage> {
+    let (tx, rx) = mpsc::channel::<WarmWhisperMessage>();
+    thread::spawn(move || {
+        let mut server: Option<WarmWhisperServer> = None;
+        let mut failed_config: Option<WarmWhisperConfig> = None;
+        for message in rx {
+            let request = match message {
+                WarmWhisperMessage::Transcribe(request) => request,
+                WarmWhisperMessage::Shutdown(ack) => {
+                    server = None;
+                    let _ = ack.send(());
+                    continue;
+                }
+            };
+            if failed_config.as_ref() == Some(&request.config) {
+                let _ = request.reply.send(None);
+                continue;
+            }
+            if server.as_ref().map(|server| &server.config) != Some(&request.config) {
+                server = None;
+                match start_warm_whisper_server(request.config.clone(), request.timeout) {
+                    Ok(started) => server = Some(started),
+                    Err(error) => {
+                        log::warn!("warm whisper unavailable, spawning whisper-cli: {error}");
+                        failed_config = Some(request.config);
+                        let _ = request.reply.send(None);
+                        continue;
+                    }
+                }
+            }
+            let Some(running) = server.as_ref() else {
+                let _ = request.reply.send(None);
+                continue;
+            };
+            match warm_whisper_inference(
+                running,
+                &request.wav_path,
+                request.profile,
+                request.timeout,
+            ) {
+                Ok(text) => {
+                    let _ = request.reply.send(Some(text));
+                }
+                Err(error) => {
+                    log::warn!("warm whisper request failed, spawning whisper-cli: {error}");
+                    server = None;
+                    let _ = request.reply.send(None);
+                }
+            }
+        }
+    });
+    tx
+}
+
+fn warm_whisper_worker(app: &tauri::AppHandle) -> Option<mpsc::Sender<WarmWhisperMessage>> {
+    let state = app.try_state::<WarmWhisperState>()?;
+    let tx = state.tx.lock().ok()?;
+    Some(tx.clone())
+}
+
+fn stop_warm_whisper(app: &tauri::AppHandle, wait: bool) {
+    let Some(worker) = warm_whisper_worker(app) else {
+        return;
+    };
+    let (ack, ack_rx) = mpsc::channel();
+    if worker.send(WarmWhisperMessage::Shutdown(ack)).is_ok() && wait {
+        let _ = ack_rx.recv_timeout(WARM_WHISPER_SHUTDOWN_WAIT);
+    }
+}
+
+// Shorter buffers are padded with silence so every frame carries all channels.
+fn interleave_channels(buffers: &[Vec<f32>]) -> Vec<f32> {
+    let frames = buffers.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = Vec::with_capacity(frames * buffers.len());
+    for frame in 0..frames {
+        for buffer in buffers {
+            out.push(buffer.get(frame).copied().unwrap_or(0.0));
+        }
+    }
+    out
+}
+
+struct PreparedCapture {
+    samples: Vec<f32>,
+    resampled: bool,
+    captured_seconds: f32,
+    // Length after resampling, before silence trimming.
+    untrimmed_seconds: f32,
+    // Set when transcription would stop here; `samples` is then whatever reached the failing gate.
+    rejection: Option<String>,
+}
+
+// Everything captured audio goes through before level normalization and whisper: resampling, the
+// too-quiet gate, silence trimming, the optional noise gate, and the edge fade. Shared by
+// transcription and `preview_capture`.
+fn prepare_captured_audio(
+    samples: Vec<f32>,
+    input: &OpenedInput,
+    audio: AudioPrepOptions,
+) -> PreparedCapture {
+    let sample_rate = input.sample_rate;
+    let captured_seconds = if sample_rate == 0 {
+        0.0
+    } else {
+        samples.len() as f32 / sample_rate as f32
+    };
+    let resampled = sample_rate != WHISPER_SAMPLE_RATE;
+    let prepared = if !resampled {
+        samples
+    } else {
+        resample_linear(&samples, sample_rate, WHISPER_SAMPLE_RATE)
+    };
+    let untrimmed_seconds = prepared.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+    let rejected = |samples: Vec<f32>, rejection: String| PreparedCapture {
+        samples,
+        resampled,
+        captured_seconds,
+        untrimmed_seconds,
+        rejection: Some(rejection),
+    };
+
+    if prepared.is_empty() {
+        return rejected(
+            prepared,
+            "No audio captured. Check microphone input and try again.".to_string(),
+        );
+    }
+
+    let signal = analyze_audio_signal(&prepared, WHISPER_SAMPLE_RATE);
+    if audio_signal_is_too_quiet(signal) {
+        return rejected(prepared, quiet_audio_error(signal, &input.device_name));
+    }
+    let prepared = if audio.trim_silence {
+        trim_silence_edges(prepared, WHISPER_SAMPLE_RATE)
+    } else {
+        prepared
+    };
+    if prepared.is_empty() {
+        return rejected(
+            prepared,
+            "No speech detected. The capture only contained background noise; speak closer to the microphone and try again."
+                .to_string(),
+        );
+    }
+    let prepared = match audio.noise_gate {
+        Some(threshold) => apply_noise_gate(prepared, WHISPER_SAMPLE_RATE, threshold),
+        None => prepared,
+    };
+    PreparedCapture {
+        samples: apply_edge_fade(prepared, WHISPER_SAMPLE_RATE),
+        resampled,
+        captured_seconds,
+        untrimmed_seconds,
+        rejection: None,
+    }
+}
+
+fn capture_preview_payload(
+    samples: Vec<f32>,
+    input: &OpenedInput,
+    audio: AudioPrepOptions,
+) -> CapturePreviewPayload {
+    let prepared = prepare_captured_audio(samples, input, audio);
+    let signal = analyze_audio_signal(&prepared.samples, WHISPER_SAMPLE_RATE);
+    let prepared_seconds = if prepared.rejection.is_some() {
+        0.0
+    } else {
+        signal.duration_secs
+    };
+    CapturePreviewPayload {
+        device_name: input.device_name.clone(),
+        input_sample_rate: input.sample_rate,
+        input_channels: input.channels,
+        resampled: prepared.resampled,
+        captured_seconds: prepared.captured_seconds,
+        prepared_seconds,
+        trimmed_seconds: if prepared.rejection.is_some() {
+            0.0
+        } else {
+            (prepared.untrimmed_seconds - prepared_seconds).max(0.0)
+        },
+        trim_silence: audio.trim_silence,
+        peak: signal.peak_abs,
+        rms: signal.rms,
+        passes_speech_gate: prepared.rejection.is_none(),
+        rejection: prepared.rejection,
+    }
+}
+
+fn transcribe_samples(
+    model_path: PathBuf,
+    whisper_cli_path: String,
+    samples: Vec<f32>,
+    channel_samples: Vec<Vec<f32>>,
+    input: OpenedInput,
+    options: TranscriptionOptions,
+) -> Result<TranscriptionOutcome, String> {
+    let sample_rate = input.sample_rate;
+    // Measure before resampling: interpolation smooths flat-topped peaks below full scale.
+    let clipping_warning = clipping_warning(clipping_fraction(&samples));
+    let prepared = prepare_captured_audio(samples, &input, options.audio);
+    if let Some(rejection) = prepared.rejection {
+        return Err(rejection);
+    }
+    let signal = analyze_audio_signal(&prepared.samples, WHISPER_SAMPLE_RATE);
+    let audio_info = AudioInfoPayload {
+        input_sample_rate: sample_rate,
+        input_channels: input.channels,
+        resampled: prepared.resampled,
+        captured_seconds: prepared.captured_seconds,
+        prepared_seconds: signal.duration_secs,
+        peak: signal.peak_abs,
+        rms: signal.rms,
+    };
+    let prepared = normalize_audio_level(prepared.samples, signal, options.audio);
+    if let Some(cache) = options.capture_cache.as_ref() {
+        cache_last_capture(cache, &prepared);
+    }
+
+    let wav_path = std::env::temp_dir().join(format!("{}.wav", temp_artifact_base_name()));
+    write_wav(&wav_path, &prepared, 1, WHISPER_SAMPLE_RATE)?;
+
+    let chunks = silence_chunk_bounds(&prepared, WHISPER_SAMPLE_RATE, options.chunk_seconds);
+    let decode = |model_path: &Path| {
+        if chunks.len() > 1 {
+            transcribe_prepared_chunks(&whisper_cli_path, model_path, &prepared, &chunks, &options)
+        } else {
+            transcribe_prepared_wav(
+                &whisper_cli_path,
+                model_path,
+                &wav_path,
+                signal.duration_secs,
+                &options,
+            )
+        }
+    };
+    let mut result = decode(&model_path);
+    let mut model_path = model_path;
+    let mut fallback_model = None;
+    if let (Err(error), Some(fallback_path)) = (&result, options.oom_fallback.as_ref()) {
+        if whisper_error_is_oom(error) {
+            log::warn!(
+                "transcribe_samples: {} ran out of memory; retrying with {}",
+                model_path.display(),
+                fallback_path.display()
+            );
+            result = decode(fallback_path);
+            model_path = fallback_path.clone();
+            fallback_model = Some(fallback_path.clone());
+        }
+    }
+    if let Err(error) = result.as_mut() {
+        if whisper_error_is_oom(error) {
+            error.push(' ');
+            error.push_str(&oom_fallback_hint(
+                options.fallback_on_oom,
+                fallback_model.as_deref(),
+            ));
+        }
+    }
+
+    // Failed runs are archived too: those are exactly the clips worth replaying when debugging.
+    if let Some(archive) = options.recordings.as_ref() {
+        let channels = ChannelRecording {
+            buffers: &channel_samples,
+            sample_rate,
+        };
+        if let Err(error) = archive_recording(
+            archive,
+            &wav_path,
+            &channels,
+            &model_path,
+            result.as_ref().ok().map(|outcome| &outcome.transcript),
+        ) {
+            log::warn!("transcribe_samples: failed to save recording: {error}");
+        }
+    }
+    let _ = std::fs::remove_file(&wav_path);
+
+    let fallback_warning = fallback_model.as_deref().map(oom_fallback_warning);
+    let warning = match (clipping_warning, fallback_warning) {
+        (Some(clipping), Some(fallback)) => Some(format!("{fallback} {clipping}")),
+        (clipping, fallback) => fallback.or(clipping),
+    };
+    result.map(|outcome| TranscriptionOutcome {
+        warning,
+        audio_info: Some(audio_info),
+        fallback_model,
+        ..outcome
+    })
+}
+
+// A timer per capture drops it once the retention window passes, unless a newer capture has
+// already replaced it, so an idle app does not hold minutes of audio indefinitely.
+fn cache_last_capture(cache: &LastCaptureCache, samples: &[f32]) {
+    let captured_at = Instant::now();
+    let Ok(mut slot) = cache.slot.lock() else {
+        return;
+    };
+    *slot = Some(CachedCapture {
+        samples: samples.to_vec(),
+        captured_at,
+    });
+    drop(slot);
+
+    let slot = Arc::clone(&cache.slot);
+    let retention = cache.retention;
+    thread::spawn(move || {
+        thread::sleep(retention);
+        if let Ok(mut slot) = slot.lock() {
+            if slot
+                .as_ref()
+                .is_some_and(|capture| capture.captured_at == captured_at)
+            {
+                *slot = None;
+            }
+        }
+    });
+}
+
+fn take_cached_capture_samples(
+    slot: &Mutex<Option<CachedCapture>>,
+    retention: Duration,
+) -> Result<Vec<f32>, String> {
+    let mut slot = slot
+        .lock()
+        .map_err(|_| "Failed to lock the last recording".to_string())?;
+    if slot
+        .as_ref()
+        .is_some_and(|capture| capture.captured_at.elapsed() > retention)
+    {
+        *slot = None;
+    }
+    slot.as_ref()
+        .map(|capture| capture.samples.clone())
+        .ok_or_else(|| {
+            if retention.is_zero() {
+                "Keeping the last recording is turned off (last_recording_retention_seconds is 0)."
+                    .to_string()
+            } else {
+                format!(
+                    "No recent recording to export. Recordings are kept for {} seconds after dictating; scratch takes are never kept.",
+                    retention.as_secs()
+                )
+            }
+        })
+}
+
+// Appended to an out-of-memory error so it only suggests fallback_on_oom when that would help.
+fn oom_fallback_hint(fallback_on_oom: bool, tried_fallback: Option<&Path>) -> String {
+    match (fallback_on_oom, tried_fallback) {
+        (false, _) => {
+            "Or enable fallback_on_oom to retry with a smaller installed model automatically."
+                .to_string()
+        }
+        (true, None) => {
+            "fallback_on_oom is on, but there is no smaller installed model to fall back to."
+                .to_string()
+        }
+        (true, Some(path)) => format!(
+            "fallback_on_oom already retried with the smaller {} model, which ran out of memory too.",
+            recording_model_id(path)
+        ),
+    }
+}
+
+fn oom_fallback_warning(fallback_path: &Path) -> String {
+    format!(
+        "The selected model ran out of memory, so this transcript used the smaller {} model. Select it (or free memory) to avoid the slower retry.",
+        recording_model_id(fallback_path)
+    )
+}
+
+fn temp_artifact_base_name() -> String {
+    let tick = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    format!("dicktaint-{}-{tick}", std::process::id())
+}
+
+// Only names shaped like `temp_artifact_base_name` output (`dicktaint-<pid>-<millis>...`) are
+// ours; anything else under the `dicktaint-` prefix is left alone.
+fn temp_artifact_owner_pid(file_name: &str) -> Option<u32> {
+    let rest = file_name.strip_prefix("dicktaint-")?;
+    let (pid, rest) = rest.split_once('-')?;
+    let tick_len = rest.bytes().take_while(u8::is_ascii_digit).count();
+    if pid.is_empty() || !pid.bytes().all(|b| b.is_ascii_digit()) || tick_len == 0 {
+        return None;
+    }
+    if !matches!(rest.as_bytes().get(tick_len), Some(b'.' | b'-')) {
+        return None;
+    }
+    pid.parse().ok()
+}
+
+// `None` when liveness cannot be checked on this platform; callers fall back to file age.
+// EPERM means the pid exists but belongs to another user, so it still counts as running.
+#[cfg(unix)]
+fn process_is_alive(pid: u32) -> Option<bool> {
+    // Pid 0 would address our own process group rather than a process.
+    let pid = libc::pid_t::try_from(pid).ok().filter(|pid| *pid > 0)?;
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EPERM) => Some(true),
+        Some(libc::ESRCH) => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn process_is_alive(_pid: u32) -> Option<bool> {
+    None
+}
+
+// Removes transcription temp files a crashed run left behind. A file is removed only when it is
+// older than `max_age` and its owning process is not this one and (where checkable) not running.
+fn cleanup_temp_artifacts(
+    temp_dir: &Path,
+    max_age: Duration,
+    is_alive: &dyn Fn(u32) -> Option<bool>,
+) -> Result<TempFileCleanup, String> {
+    let entries = fs::read_dir(temp_dir)
+        .map_err(|e| format!("Failed to read temp directory {}: {e}", temp_dir.display()))?;
+    let own_pid = std::process::id();
+    let now = SystemTime::now();
+    let mut cleanup = TempFileCleanup::default();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(pid) = temp_artifact_owner_pid(&file_name) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if age < max_age {
+            continue;
+        }
+        if pid == own_pid || is_alive(pid) == Some(true) {
+            cleanup.skipped_in_use += 1;
+            continue;
+        }
+        let path = entry.path();
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                cleanup.bytes_freed = cleanup.bytes_freed.saturating_add(metadata.len());
+                cleanup.removed.push(path.display().to_string());
+            }
+            Err(error) => log::warn!(
+                "Failed to remove stale temp file {}: {error}",
+                path.display()
+            ),
+        }
+    }
+    Ok(cleanup)
+}
+
+// Rough measure of how much real content a transcript carries for its audio length: the share of
+// distinct words scaled by speaking density. Loops ("you you you") and near-empty output on long
+// clips both score low.
+fn transcript_information_score(text: &str, audio_seconds: f32) -> f32 {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|ch: char| !ch.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+
+    let unique = words.iter().collect::<HashSet<_>>().len() as f32;
+    let unique_ratio = unique / words.len() as f32;
+    if audio_seconds <= 0.0 {
+        return unique_ratio;
+    }
+    let density = (words.len() as f32 / audio_seconds / EXPECTED_WORDS_PER_SECOND).min(1.0);
+    unique_ratio * density
+}
+
+fn transcript_looks_low_confidence(
+    text: &str,
+    audio_seconds: f32,
+    retry: LowConfidenceRetryOptions,
+) -> bool {
+    retry.enabled
+        && audio_seconds >= LOW_CONFIDENCE_RETRY_SECONDS
+        && transcript_information_score(text, audio_seconds) < retry.score_threshold
+}
+
+fn run_decode_pass(
+    whisper_cli_path: &str,
+    model_path: &Path,
+    wav_path: &Path,
+    audio_seconds: f32,
+    options: &TranscriptionOptions,
+    profile: WhisperDecodeProfile,
+) -> Result<WhisperRunOutput, String> {
+    let out_prefix = std::env::temp_dir().join(format!("{}-transcript", temp_artifact_base_name()));
+    run_whisper_cli(
+        whisper_cli_path,
+        model_path,
+        wav_path,
+        &out_prefix,
+        audio_seconds,
+        &options.whisper,
+        profile,
+    )
+    .map(|output| WhisperRunOutput {
+        text: normalize_transcript_text(&output.text, &options.artifact_tokens),
+        token_confidence: output.token_confidence,
+    })
+}
+
+// Token confidence when whisper reported it, otherwise the heuristic information score.
+fn transcript_confidence(text: &str, audio_seconds: f32, token_confidence: Option<f32>) -> f32 {
+    token_confidence.unwrap_or_else(|| transcript_information_score(text, audio_seconds))
+}
+
+fn min_confidence_error(confidence: f32, min_confidence: f32) -> String {
+    format!(
+        "Transcript discarded: confidence {confidence:.2} is below the minimum of {min_confidence:.2}. Speak clearly and try again, or lower the minimum confidence in settings."
+    )
+}
+
+// Runs whisper on a WAV that is already 16 kHz mono and level-normalized, then cleans the output.
+// The caller owns the WAV; only whisper's artifacts are removed.
+fn transcribe_prepared_wav(
+    whisper_cli_path: &str,
+    model_path: &Path,
+    wav_path: &Path,
+    audio_seconds: f32,
+    options: &TranscriptionOptions,
+) -> Result<TranscriptionOutcome, String> {
+    let (transcript, info) = decode_prepared_wav(
+        whisper_cli_path,
+        model_path,
+        wav_path,
+        audio_seconds,
+        options,
+    )?;
+    finish_transcript(transcript, info, options)
+}
+
+// Without `two_pass_decode` the per-model fast profiles are not used at all.
+fn first_decode_pass_for(
+    two_pass_decode: bool,
+    overrides: DecodeOverrides,
+    spec: Option<&WhisperModelSpec>,
+) -> (WhisperDecodeProfile, DecodePath) {
+    if two_pass_decode {
+        (primary_decode_profile(overrides, spec), DecodePath::Fast)
+    } else {
+        (
+            WhisperDecodeProfile {
+                beam_size: overrides
+                    .beam_size
+                    .unwrap_or(ACCURATE_DECODE_PROFILE.beam_size),
+                best_of: overrides.best_of.unwrap_or(ACCURATE_DECODE_PROFILE.best_of),
+            },
+            DecodePath::Single,
+        )
+    }
+}
+
+fn first_decode_pass(
+    options: &TranscriptionOptions,
+    model_path: &Path,
+) -> (WhisperDecodeProfile, DecodePath) {
+    first_decode_pass_for(
+        options.two_pass_decode,
+        options.decode_overrides,
+        catalog_spec_for_model_path(model_path).as_ref(),
+    )
+}
+
+// By default this is one pass with whisper-cli's own profile. With `two_pass_decode` a fast pass
+// runs first; low-information results get one beam-search retry and the higher-scoring
+// transcript wins. Returns the cleaned but not yet formatted transcript.
+fn decode_prepared_wav(
+    whisper_cli_path: &str,
+    model_path: &Path,
+    wav_path: &Path,
+    audio_seconds: f32,
+    options: &TranscriptionOptions,
+) -> Result<(String, TranscriptionInfo), String> {
+    let (profile, decode_path) = first_decode_pass(options, model_path);
+    let fast = run_decode_pass(
+        whisper_cli_path,
+        model_path,
+        wav_path,
+        audio_seconds,
+        options,
+        profile,
+    )?;
+    let mut transcript = fast.text;
+    let fast_score = transcript_information_score(&transcript, audio_seconds);
+    let mut info = TranscriptionInfo {
+        decode_path,
+        fast_score,
+        retry_score: None,
+        audio_seconds,
+        token_confidence: fast.token_confidence,
+    };
+
+    if decode_path == DecodePath::Fast
+        && transcript_looks_low_confidence(&transcript, audio_seconds, options.low_confidence_retry)
+    {
+        match run_decode_pass(
+            whisper_cli_path,
+            model_path,
+            wav_path,
+            audio_seconds,
+            options,
+            ACCURATE_DECODE_PROFILE,
+        ) {
+            Ok(retry) => {
+                let retry_score = transcript_information_score(&retry.text, audio_seconds);
+                info.retry_score = Some(retry_score);
+                if retry_score > fast_score {
+                    transcript = retry.text;
+                    info.decode_path = DecodePath::Retry;
+                    info.token_confidence = retry.token_confidence;
+                }
+            }
+            Err(error) => {
+                log::warn!(
+                    "transcribe_prepared_wav: accurate retry failed, keeping fast pass: {error}"
+                );
+            }
+        }
+    }
+
+    Ok((transcript, info))
+}
+
+// Spoken commands, auto-format, and profanity masking; partial transcripts get the same text
+// processing as the final one so masked words never flash on screen.
+fn format_transcript_text(transcript: &str, options: &TranscriptionOptions) -> String {
+    mask_profanity(
+        &auto_format_transcript(
+            &apply_spoken_commands(transcript, &options.spoken_commands),
+            options.auto_format,
+        ),
+        &options.profanity_words,
+    )
+}
+
+// Shared tail of single-WAV and chunked transcription: strips edge fillers, rejects empty or
+// low-confidence output, then applies spoken commands, auto-format, profanity masking, and
+// trailing punctuation.
+fn finish_transcript(
+    transcript: String,
+    info: TranscriptionInfo,
+    options: &TranscriptionOptions,
+) -> Result<TranscriptionOutcome, String> {
+    let transcript = match options.dedup_max_repeats {
+        Some(max_repeats) => collapse_repeated_ngrams(&transcript, max_repeats),
+        None => transcript,
+    };
+    let transcript = strip_fillers(
+        &transcript,
+        &options.filler_words,
+        &options.filler_trailing_phrases,
+    );
+    if transcript.is_empty() {
+        return Err("No speech detected in the recorded audio.".to_string());
+    }
+    if let Some(min_confidence) = options.min_confidence {
+        let confidence =
+            transcript_confidence(&transcript, info.audio_seconds, info.token_confidence);
+        if confidence < min_confidence {
+            return Err(min_confidence_error(confidence, min_confidence));
+        }
+    }
+    Ok(TranscriptionOutcome {
+        transcript: apply_trailing_punctuation(
+            format_transcript_text(&transcript, options),
+            options.trailing_punctuation,
+        ),
+        info,
+        warning: None,
+        audio_info: None,
+        fallback_model: None,
+    })
+}
+
+// Joins chunk transcripts with a space; each part carries its chunk's `cut_in_pause`. After a
+// fallback cut, a word repeated on both sides (whisper sometimes finishes a trailing word in one
+// chunk and restarts it in the next) is kept once. A cut inside a pause never splits a word, so a
+// repeat there ("had / had") is real speech and stays.
+fn join_chunk_transcripts(parts: &[(String, bool)]) -> String {
+    let comparable = |word: &str| {
+        word.trim_matches(|ch: char| !ch.is_alphanumeric())
+            .to_lowercase()
+    };
+    let mut joined = String::new();
+    for (part, cut_in_pause) in parts
+        .iter()
+        .map(|(part, cut_in_pause)| (part.trim(), *cut_in_pause))
+        .filter(|(part, _)| !part.is_empty())
+    {
+        let mut rest = part;
+        if let (false, Some(last), Some(first)) = (
+            cut_in_pause,
+            joined.split_whitespace().next_back(),
+            part.split_whitespace().next(),
+        ) {
+            if !comparable(first).is_empty() && comparable(last) == comparable(first) {
+                rest = part[part.find(first).unwrap_or(0) + first.len()..].trim_start();
+            }
+        }
+        if rest.is_empty() {
+            continue;
+        }
+        if !joined.is_empty() {
+            joined.push(' ');
+        }
+        joined.push_str(rest);
+    }
+    joined
+}
+
+// Decodes each chunk in order from its own temp WAV. Progress is rescaled so the bar advances
+// once across all chunks instead of restarting per chunk.
+fn transcribe_prepared_chunks(
+    whisper_cli_path: &str,
+    model_path: &Path,
+    samples: &[f32],
+    chunks: &[AudioChunk],
+    options: &TranscriptionOptions,
+) -> Result<TranscriptionOutcome, String> {
+    let total_seconds = samples.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+    let mut parts = Vec::with_capacity(chunks.len());
+    let mut info = TranscriptionInfo {
+        decode_path: first_decode_pass(options, model_path).1,
+        fast_score: 0.0,
+        retry_score: None,
+        audio_seconds: total_seconds,
+        token_confidence: Some(0.0),
+    };
+    let mut best_score_sum = 0.0;
+    let mut retried = false;
+    for (index, chunk) in chunks.iter().enumerate() {
+        let chunk_seconds = chunk.range.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+        let mut chunk_options = options.clone();
+        chunk_options.whisper.progress = options.whisper.progress.clone().map(|overall| {
+            let (tx, rx) = mpsc::channel::<u8>();
+            let (index, count) = (index as u32, chunks.len() as u32);
+            thread::spawn(move || {
+                for percent in rx {
+                    let scaled = (index * 100 + u32::from(percent)) / count;
+                    let _ = overall.send(scaled.min(100) as u8);
+                }
+            });
+            tx
+        });
+
+        let wav_path =
+            std::env::temp_dir().join(format!("{}-chunk{index}.wav", temp_artifact_base_name()));
+        write_wav(
+            &wav_path,
+            &samples[chunk.range.clone()],
+            1,
+            WHISPER_SAMPLE_RATE,
+        )?;
+        let decoded = decode_prepared_wav(
+            whisper_cli_path,
+            model_path,
+            &wav_path,
+            chunk_seconds,
+            &chunk_options,
+        );
+        let _ = std::fs::remove_file(&wav_path);
+        let (transcript, chunk_info) = decoded?;
+
+        let weight = chunk_seconds / total_seconds.max(f32::EPSILON);
+        info.fast_score += chunk_info.fast_score * weight;
+        best_score_sum += chunk_info.retry_score.unwrap_or(chunk_info.fast_score) * weight;
+        retried |= chunk_info.retry_score.is_some();
+        if chunk_info.decode_path == DecodePath::Retry {
+            info.decode_path = DecodePath::Retry;
+        }
+        info.token_confidence = info
+            .token_confidence
+            .zip(chunk_info.token_confidence)
+            .map(|(sum, confidence)| sum + confidence * weight);
+        parts.push((transcript, chunk.cut_in_pause));
+        if let Some(partial) = options
+            .partial
+            .as_ref()
+            .filter(|_| index + 1 < chunks.len())
+        {
+            let _ = partial.send(PartialTranscript {
+                text: format_transcript_text(&join_chunk_transcripts(&parts), options),
+                chunks_done: index + 1,
+                chunk_count: chunks.len(),
+            });
+        }
+    }
+    if retried {
+        info.retry_score = Some(best_score_sum);
+    }
+    log::info!(
+        "transcribe_prepared_chunks: transcribed {total_seconds:.1}s in {} chunks",
+        chunks.len()
+    );
+
+    finish_transcript(join_chunk_transcripts(&parts), info, options)
+}
+
+fn prepared_wav_duration_secs(wav_path: &Path) -> Result<f32, String> {
+    let reader = hound::WavReader::open(wav_path)
+        .map_err(|e| format!("Failed to read recording {}: {e}", wav_path.display()))?;
+    let spec = reader.spec();
+    if spec.sample_rate != WHISPER_SAMPLE_RATE || spec.channels != 1 {
+        return Err(format!(
+            "Recording {} is {} Hz / {} channel(s); only 16 kHz mono recordings saved by dicktaint can be re-transcribed.",
+            wav_path.display(),
+            spec.sample_rate,
+            spec.channels
+        ));
+    }
+    Ok(reader.duration() as f32 / spec.sample_rate as f32)
+}
+
+// Only files inside the recordings folder are accepted so the command cannot be pointed at
+// arbitrary paths on disk.
+fn resolve_saved_recording_path(recordings_dir: &Path, path: &str) -> Result<PathBuf, String> {
+    let not_found = || format!("Saved recording not found: {}", path.trim());
+    let recordings_dir = recordings_dir.canonicalize().map_err(|_| not_found())?;
+    let candidate = PathBuf::from(path.trim());
+    let candidate = if candidate.is_absolute() {
+        candidate
+    } else {
+        recordings_dir.join(candidate)
+    };
+    let resolved = candidate.canonicalize().map_err(|_| not_found())?;
+    if !resolved.starts_with(&recordings_dir) || RecordingFormat::from_path(&resolved).is_none() {
+        return Err(format!(
+            "{} is not a saved recording in {}.",
+            path.trim(),
+            recordings_dir.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+fn recording_model_id(model_path: &Path) -> String {
+    let file_name = model_path
+        .file_name()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if let Some(spec) = WHISPER_MODEL_CATALOG
+        .iter()
+        .find(|spec| spec.file_name == file_name)
+    {
+        return spec.id.to_string();
+    }
+
+    let stem = model_path
+        .file_stem()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let sanitized: String = stem
+        .trim_start_matches("ggml-")
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '.' || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if sanitized.is_empty() {
+        "custom".to_string()
+    } else {
+        sanitized
+    }
+}
+
+fn recording_file_name(recorded_at_ms: u64, model_id: &str) -> String {
+    format!("{recorded_at_ms:013}-{model_id}.wav")
+}
+
+fn parse_recording_file_name(file_name: &str) -> Option<(u64, String)> {
+    let stem = RecordingFormat::ALL
+        .iter()
+        .find_map(|format| file_name.strip_suffix(&format!(".{}", format.as_str())))?;
+    let (timestamp, model_id) = stem.split_once('-')?;
+    if model_id.is_empty() {
+        return None;
+    }
+    Some((timestamp.parse().ok()?, model_id.to_string()))
+}
+
+// Raw per-channel capture at the device rate; empty buffers mean mono-only capture.
+struct ChannelRecording<'a> {
+    buffers: &'a [Vec<f32>],
+    sample_rate: u32,
+}
+
+// The multi-channel companion always stays WAV, whatever format the main clip uses.
+fn channel_recording_path(recordings_dir: &Path, file_name: &str) -> PathBuf {
+    recordings_dir
+        .join(CHANNEL_RECORDINGS_DIR_NAME)
+        .join(file_name)
+        .with_extension(RecordingFormat::Wav.as_str())
+}
+
+fn archive_recording(
+    archive: &RecordingArchiveOptions,
+    wav_path: &Path,
+    channels: &ChannelRecording<'_>,
+    model_path: &Path,
+    transcript: Option<&String>,
+) -> Result<PathBuf, String> {
+    fs::create_dir_all(&archive.dir).map_err(|e| {
+        format!(
+            "Failed to create recordings directory {}: {e}",
+            archive.dir.display()
+        )
+    })?;
+
+    let recorded_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let wav_target = archive.dir.join(recording_file_name(
+        recorded_at_ms,
+        &recording_model_id(model_path),
+    ));
+    let target = wav_target.with_extension(archive.format.as_str());
+    // A failed FLAC/Opus encode still keeps the clip, as WAV.
+    let target = match encode_recording(wav_path, &target, archive.format) {
+        Ok(()) => target,
+        Err(error) if archive.format != RecordingFormat::Wav => {
+            log::warn!(
+                "archive_recording: {} encode failed, keeping WAV: {error}",
+                archive.format.as_str()
+            );
+            encode_recording(wav_path, &wav_target, RecordingFormat::Wav)?;
+            wav_target
+        }
+        Err(error) => return Err(error),
+    };
+    if let Some(transcript) = transcript {
+        let _ = fs::write(target.with_extension("txt"), transcript);
+    }
+    // A single preserved channel is just the mono clip again, so only true multi-channel input
+    // gets a companion file.
+    if channels.buffers.len() >= 2 {
+        let file_name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let channel_path = channel_recording_path(&archive.dir, &file_name);
+        let written = channel_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .map_err(|e| format!("Failed to create channel recordings directory: {e}"))
+            .and_then(|_| {
+                write_wav(
+                    &channel_path,
+                    &interleave_channels(channels.buffers),
+                    channels.buffers.len() as u16,
+                    channels.sample_rate,
+                )
+            });
+        if let Err(error) = written {
+            log::warn!("archive_recording: failed to save channel recording: {error}");
+        }
+    }
+
+    prune_recordings(
+        &archive.dir,
+        archive.max_recordings,
+        archive.max_total_bytes,
+    )?;
+    Ok(target)
+}
+
+fn list_recordings(recordings_dir: &Path) -> Result<Vec<RecordingEntry>, String> {
+    let read_dir = match fs::read_dir(recordings_dir) {
+        Ok(value) => value,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(format!(
+                "Failed to read recordings directory {}: {error}",
+                recordings_dir.display()
+            ))
+        }
+    };
+
+    let mut entries: Vec<RecordingEntry> = read_dir
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let (recorded_at_ms, model_id) = parse_recording_file_name(&file_name)?;
+            let path = entry.path();
+            let size_bytes = entry.metadata().ok()?.len();
+            let transcript = fs::read_to_string(path.with_extension("txt")).ok();
+            Some(RecordingEntry {
+                file_name,
+                path: path.to_string_lossy().to_string(),
+                model_id,
+                recorded_at_ms,
+                size_bytes,
+                transcript,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.recorded_at_ms
+            .cmp(&a.recorded_at_ms)
+            .then_with(|| b.file_name.cmp(&a.file_name))
+    });
+    Ok(entries)
+}
+
+// Keeps the newest recordings that fit both the count and total-size budgets; the most recent
+// clip is always kept even if it alone exceeds the size budget.
+fn prune_recordings(
+    recordings_dir: &Path,
+    max_recordings: u32,
+    max_total_bytes: u64,
+) -> Result<usize, String> {
+    let mut kept = 0_u32;
+    let mut kept_bytes = 0_u64;
+    let mut removed = 0;
+    // Once one clip falls outside the budget every older clip goes too, even a smaller one that
+    // would still fit, so the kept set is always the newest run of recordings.
+    let mut over_budget = false;
+    for entry in list_recordings(recordings_dir)? {
+        over_budget = over_budget
+            || (kept > 0
+                && (kept >= max_recordings
+                    || kept_bytes.saturating_add(entry.size_bytes) > max_total_bytes));
+        if !over_budget {
+            kept += 1;
+            kept_bytes = kept_bytes.saturating_add(entry.size_bytes);
+            continue;
+        }
+
+        let path = PathBuf::from(&entry.path);
+        fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove old recording {}: {e}", path.display()))?;
+        for extension in ["txt", "srt", "vtt"] {
+            let _ = fs::remove_file(path.with_extension(extension));
+        }
+        let _ = fs::remove_file(channel_recording_path(recordings_dir, &entry.file_name));
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+// Builds the onboarding payload and (re-)registers the hotkey it names, so the
+// payload reflects the runtime actually in effect.
+fn registered_onboarding_payload(
+    app: &tauri::AppHandle,
+    config: &AppConfig,
+    model_state: &LocalModelState,
+    hotkey_state: &GlobalHotkeyState,
+    context: &str,
+) -> Result<DictationOnboardingPayload, String> {
+    let mut payload = build_onboarding_payload(config, model_state, hotkey_state)?;
+    match apply_registered_hotkey(app, hotkey_state, payload.dictation_trigger.as_deref()) {
+        Ok(runtime) => {
+            payload.dictation_trigger_mode = runtime.mode.as_str().to_string();
+            payload.dictation_trigger_status = runtime.status;
+            payload.dictation_trigger_permission_hint = runtime.permission_hint;
+            payload.fn_listener_mode = fn_listener_mode(hotkey_state).to_string();
+        }
+        Err(error) => {
+            log::warn!("{context}: failed to apply global hotkey: {error}");
+        }
+    }
+    Ok(payload)
+}
+
+// Read-only: reports the saved trigger against whatever is registered right now without
+// touching the global shortcut. `apply_dictation_hotkey` is the explicit (re-)registration.
+#[tauri::command]
+fn get_dictation_onboarding(
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<DictationOnboardingPayload, DictationError> {
+    build_onboarding_payload(config.inner(), model_state.inner(), hotkey_state.inner())
+        .map_err(DictationError::from)
+}
+
+#[tauri::command]
+fn apply_dictation_hotkey(
+    app: tauri::AppHandle,
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<DictationOnboardingPayload, DictationError> {
+    registered_onboarding_payload(
+        &app,
+        config.inner(),
+        model_state.inner(),
+        hotkey_state.inner(),
+        "apply_dictation_hotkey",
+    )
+    .map_err(DictationError::from)
+}
+
+#[tauri::command]
+fn reset_dictation_settings(
+    app: tauri::AppHandle,
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<DictationOnboardingPayload, DictationError> {
+    let previous_trigger = current_registered_hotkey(hotkey_state.inner())?;
+    // Drop whatever is registered first so a broken trigger cannot linger; the
+    // default trigger is registered again when the payload is rebuilt below.
+    apply_registered_hotkey(&app, hotkey_state.inner(), None)?;
+
+    {
+        let settings_path = model_state.settings_path();
+        let mut settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let defaults = LocalSettings::default();
+        if let Err(error) = save_local_settings(&settings_path, &defaults) {
+            drop(settings);
+            if let Err(restore_error) =
+                apply_registered_hotkey(&app, hotkey_state.inner(), previous_trigger.as_deref())
+            {
+                log::warn!("reset_dictation_settings: failed to restore previous hotkey after save error: {restore_error}");
+            }
+            return Err(error.into());
+        }
+        *settings = defaults;
+        apply_hotkey_activation(hotkey_state.inner(), &settings);
+    }
+
+    registered_onboarding_payload(
+        &app,
+        config.inner(),
+        model_state.inner(),
+        hotkey_state.inner(),
+        "reset_dictation_settings",
+    )
+    .map_err(DictationError::from)
+}
+
+#[derive(Serialize)]
+struct SettingsImportPayload {
+    onboarding: DictationOnboardingPayload,
+    model_missing: bool,
+    missing_model: Option<String>,
+    dropped_fields: Vec<String>,
+}
+
+fn export_settings_json(settings: &LocalSettings) -> Result<String, String> {
+    let versioned = LocalSettings {
+        schema_version: Some(SETTINGS_SCHEMA_VERSION),
+        ..settings.clone()
+    };
+    serde_json::to_string_pretty(&versioned)
+        .map_err(|e| format!("Failed to serialize dictation settings: {e}"))
+}
+
+// Validates an exported settings blob and rebases machine-specific paths onto
+// this machine. Returns the settings to apply plus any fields that were dropped.
+fn prepare_imported_settings(
+    contents: &str,
+    models_dir: &Path,
+) -> Result<(LocalSettings, Vec<String>), String> {
+    let value = serde_json::from_str::<serde_json::Value>(contents)
+        .map_err(|e| format!("Settings file is not valid JSON: {e}"))?;
+    if settings_schema_version(&value) > SETTINGS_SCHEMA_VERSION {
+        return Err(format!(
+            "Settings were exported by a newer dicktaint (schema v{}); this build understands up to v{SETTINGS_SCHEMA_VERSION}.",
+            settings_schema_version(&value)
+        ));
+    }
+    let migration = migrate_settings(value)?;
+    let mut settings = migration.settings;
+    let mut dropped_fields = migration.dropped_fields;
+
+    if let Some(trigger) = settings
+        .dictation_trigger
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        settings.dictation_trigger = Some(
+            normalize_dictation_trigger(trigger)
+                .map_err(|e| format!("Imported dictation trigger is invalid: {e}"))?,
+        );
+    }
+    if let Some(args) = settings.extra_whisper_args.as_deref() {
+        settings.extra_whisper_args = Some(
+            validate_extra_whisper_args(args)
+                .map_err(|e| format!("Imported extra whisper arguments are invalid: {e}"))?,
+        );
+    }
+    if let Some(paths) = settings.extra_whisper_cli_paths.as_deref() {
+        settings.extra_whisper_cli_paths = Some(normalize_extra_whisper_cli_paths(paths)?);
+    }
+    // Same check as `set_whisper_cli_path`; an override that does not validate here is
+    // dropped rather than trusted to pick the binary for the next dictation.
+    if let Some(path) = settings.whisper_cli_path_override.as_deref() {
+        if let Err(error) = ensure_whisper_cli_available(path) {
+            log::warn!(
+                "Dropping imported whisper_cli_path_override {path}: {}",
+                error.message
+            );
+            settings.whisper_cli_path_override = None;
+            dropped_fields.push("whisper_cli_path_override".to_string());
+        }
+    }
+    if let Some(model) = settings
+        .imported_models
+        .iter()
+        .flatten()
+        .find(|model| !is_imported_model_id(&model.id))
+    {
+        return Err(format!(
+            "Imported model id {:?} is invalid; ids must be `{IMPORTED_MODEL_ID_PREFIX}` followed by lowercase letters, digits, and dashes.",
+            model.id
+        ));
+    }
+
+    // Catalog models live in this machine's models dir regardless of where the
+    // exporting machine kept them.
+    if let Some(spec) = settings
+        .selected_model_id
+        .as_deref()
+        .and_then(find_whisper_model_spec)
+    {
+        settings.selected_model_path = Some(
+            model_path_for_spec(models_dir, spec)
+                .to_string_lossy()
+                .to_string(),
+        );
+    }
+
+    Ok((settings, dropped_fields))
+}
+
+fn imported_model_missing(settings: &LocalSettings) -> Option<String> {
+    let path = settings
+        .selected_model_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())?;
+    if Path::new(path).exists() {
+        return None;
+    }
+    Some(
+        settings
+            .selected_model_id
+            .clone()
+            .unwrap_or_else(|| path.to_string()),
+    )
+}
+
+#[tauri::command]
+fn export_last_recording_wav(
+    path: String,
+    model_state: State<'_, LocalModelState>,
+    dictation: State<'_, DictationState>,
+) -> Result<String, DictationError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("Choose a file to export the recording to."
+            .to_string()
+            .into());
+    }
+    let retention = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        Duration::from_secs(last_recording_retention_seconds(&settings))
+    };
+    let samples = take_cached_capture_samples(&dictation.last_capture, retention)?;
+    write_wav(Path::new(path), &samples, 1, WHISPER_SAMPLE_RATE)?;
+
+    Ok(path.to_string())
+}
+
+#[tauri::command]
+fn set_last_recording_retention_seconds(
+    seconds: Option<u64>,
+    model_state: State<'_, LocalModelState>,
+    dictation: State<'_, DictationState>,
+) -> Result<u64, DictationError> {
+    if seconds.is_some_and(|value| value > MAX_LAST_RECORDING_RETENTION_SECONDS) {
+        return Err(format!(
+            "Last recording retention must be between 0 and {MAX_LAST_RECORDING_RETENTION_SECONDS} seconds."
+        )
+        .into());
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.last_recording_retention_seconds;
+    settings.last_recording_retention_seconds = seconds;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.last_recording_retention_seconds = previous;
+        return Err(error.into());
+    }
+
+    let retention = last_recording_retention_seconds(&settings);
+    // A shorter window applies to the capture already held; the next export drops it if stale.
+    if retention == 0 {
+        if let Ok(mut slot) = dictation.last_capture.lock() {
+            *slot = None;
+        }
+    }
+    Ok(retention)
+}
+
+#[tauri::command]
+fn export_settings(
+    path: Option<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let json = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        export_settings_json(&settings)?
+    };
+    if let Some(path) = path
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        fs::write(path, &json).map_err(|e| format!("Failed to write settings to {path}: {e}"))?;
+    }
+    Ok(json)
+}
+
+fn write_debug_report(dir: &Path, report: &DebugReport) -> Result<PathBuf, String> {
+    let path = dir.join(DEBUG_REPORT_FILE);
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize debug report: {e}"))?;
+    fs::write(&path, json)
+        .map_err(|e| format!("Failed to write debug report to {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+// Everything a support thread usually asks for in one dump. `save` also writes it to
+// `debug-report.json` beside the settings file so it can be attached to an issue.
+#[tauri::command]
+async fn get_debug_report(
+    app: tauri::AppHandle,
+    save: bool,
+) -> Result<DebugReport, DictationError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<DebugReport, String> {
+        let config = app.state::<AppConfig>();
+        let model_state = app.state::<LocalModelState>();
+        let hotkey_state = app.state::<GlobalHotkeyState>();
+        let dictation = app.state::<DictationState>();
+        let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+        let whisper_cli_error = ensure_whisper_cli_available(&whisper_cli_path)
+            .err()
+            .map(|error| error.message);
+        let model_path = resolve_active_model_path(config.inner(), model_state.inner());
+        let runtime = current_trigger_runtime_details(hotkey_state.inner()).unwrap_or_default();
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?
+            .clone();
+
+        let mut report = DebugReport {
+            generated_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            app_version: env!("CARGO_PKG_VERSION"),
+            device: build_device_profile(),
+            whisper_cli_path,
+            whisper_cli_error,
+            selected_model_id: if config.whisper_model_path_override.is_some() {
+                Some("env-override".to_string())
+            } else {
+                settings.selected_model_id.clone()
+            },
+            model_path: model_path
+                .as_ref()
+                .ok()
+                .map(|path| path.to_string_lossy().to_string()),
+            model_error: model_path.err().map(|error| error.message),
+            dictation_trigger: resolve_effective_dictation_trigger(&settings),
+            dictation_trigger_mode: runtime.mode.as_str().to_string(),
+            dictation_trigger_status: runtime.status,
+            audio_host: audio_host(&settings).id().name().to_string(),
+            settings_path: model_state.settings_path().to_string_lossy().to_string(),
+            models_dir: model_state.models_dir().to_string_lossy().to_string(),
+            recordings_dir: model_state.recordings_dir().to_string_lossy().to_string(),
+            insert_target_app: last_insert_target_app(dictation.inner()),
+            settings: LocalSettings {
+                schema_version: Some(SETTINGS_SCHEMA_VERSION),
+                ..settings
+            },
+            saved_path: None,
+        };
+        if save {
+            let settings_path = model_state.settings_path();
+            let dir = settings_path
+                .parent()
+                .ok_or_else(|| "Settings path has no parent directory".to_string())?;
+            let path = write_debug_report(dir, &report)?;
+            report.saved_path = Some(path.to_string_lossy().to_string());
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Failed to run debug report task: {e}"))?
+    .map_err(DictationError::from)
+}
+
+#[tauri::command]
+fn import_settings(
+    app: tauri::AppHandle,
+    contents: Option<String>,
+    path: Option<String>,
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<SettingsImportPayload, DictationError> {
+    let contents = match (contents, path.as_deref().map(str::trim)) {
+        (Some(contents), _) => contents,
+        (None, Some(path)) if !path.is_empty() => fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read settings from {path}: {e}"))?,
+        _ => {
+            return Err("Provide settings contents or a file path to import."
+                .to_string()
+                .into())
+        }
+    };
+    let (imported, dropped_fields) =
+        prepare_imported_settings(&contents, &model_state.models_dir())?;
+    let missing_model = imported_model_missing(&imported);
+
+    {
+        let settings_path = model_state.settings_path();
+        let mut settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        save_local_settings(&settings_path, &imported)?;
+        *settings = imported;
+        apply_hotkey_activation(hotkey_state.inner(), &settings);
+    }
+    if let Ok(mut cache) = config.whisper_cli_capabilities.lock() {
+        cache.clear();
+    }
+
+    let onboarding = registered_onboarding_payload(
+        &app,
+        config.inner(),
+        model_state.inner(),
+        hotkey_state.inner(),
+        "import_settings",
+    )?;
+    Ok(SettingsImportPayload {
+        onboarding,
+        model_missing: missing_model.is_some(),
+        missing_model,
+        dropped_fields,
+    })
+}
+
+#[tauri::command]
+fn recommend_model_for_ram(
+    gb: u64,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ModelRecommendationPayload, DictationError> {
+    if !HYPOTHETICAL_RAM_GB_RANGE.contains(&gb) {
+        return Err(format!(
+            "RAM amount must be between {} and {} GB.",
+            HYPOTHETICAL_RAM_GB_RANGE.start(),
+            HYPOTHETICAL_RAM_GB_RANGE.end()
+        )
+        .into());
+    }
+    let bias = model_state
+        .settings
+        .lock()
+        .map(|settings| recommendation_bias(&settings))
+        .unwrap_or_default();
+    Ok(model_recommendation_for_ram(
+        gb,
+        logical_cpu_cores(),
+        host_simd_support(),
+        bias,
+    ))
+}
+
+#[tauri::command]
+fn get_model_runnability(
+    model_id: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ModelRunnability, DictationError> {
+    let settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?
+        .clone();
+    let model_id = model_id.trim();
+    build_model_options(
+        &model_state.models_dir(),
+        system_memory_gb(),
+        logical_cpu_cores(),
+        host_simd_support(),
+        None,
+        &settings,
+    )
+    .into_iter()
+    .find(|option| option.id == model_id)
+    .map(|option| option.runnability)
+    .ok_or_else(|| DictationError::from(format!("Unknown model '{model_id}'.")))
+}
+
+#[tauri::command]
+fn set_recommendation_bias(
+    bias: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let parsed = RecommendationBias::parse(&bias).ok_or_else(|| {
+        format!(
+            "Unsupported recommendation bias '{}'. Use speed, balanced, or accuracy.",
+            bias.trim()
+        )
+    })?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.recommendation_bias.clone();
+    settings.recommendation_bias = Some(parsed.as_str().to_string());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.recommendation_bias = previous;
+        return Err(error.into());
+    }
+
+    Ok(parsed.as_str().to_string())
+}
+
+#[tauri::command]
+fn get_dictation_trigger(
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<DictationTriggerPayload, DictationError> {
+    let settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?
+        .clone();
+    let runtime = current_trigger_runtime_details(hotkey_state.inner())?;
+    Ok(dictation_trigger_payload(
+        &settings,
+        runtime,
+        fn_listener_mode(hotkey_state.inner()),
+    ))
+}
+
+#[tauri::command]
+fn set_dictation_trigger(
+    app: tauri::AppHandle,
+    trigger: String,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<DictationTriggerPayload, DictationError> {
+    let normalized = normalize_dictation_trigger(&trigger)?;
+    let (previous_trigger, previous_trigger_raw, previous_trigger_enabled) = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        (
+            resolve_effective_dictation_trigger(&settings),
+            settings.dictation_trigger.clone(),
+            settings.dictation_trigger_enabled,
+        )
+    };
+
+    let runtime = apply_registered_hotkey(&app, hotkey_state.inner(), Some(&normalized))?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    settings.dictation_trigger = Some(normalized.clone());
+    settings.dictation_trigger_enabled = Some(true);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.dictation_trigger = previous_trigger_raw;
+        settings.dictation_trigger_enabled = previous_trigger_enabled;
+        drop(settings);
+        if let Err(restore_error) =
+            apply_registered_hotkey(&app, hotkey_state.inner(), previous_trigger.as_deref())
+        {
+            log::warn!("set_dictation_trigger: failed to restore previous hotkey after save error: {restore_error}");
+        }
+        return Err(error.into());
+    }
+    Ok(dictation_trigger_payload(
+        &settings,
+        runtime,
+        fn_listener_mode(hotkey_state.inner()),
+    ))
+}
+
+#[tauri::command]
+fn get_supported_trigger_keys() -> SupportedTriggerKeysPayload {
+    supported_trigger_keys()
+}
+
+#[tauri::command]
+fn validate_dictation_trigger(
+    app: tauri::AppHandle,
+    trigger: String,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> DictationTriggerValidationPayload {
+    let normalized = match normalize_dictation_trigger(&trigger) {
+        Ok(normalized) => normalized,
+        Err(error) => {
+            return DictationTriggerValidationPayload {
+                trigger: None,
+                valid: false,
+                available: false,
+                error: Some(error),
+            };
+        }
+    };
+    let availability = probe_global_hotkey_available(&app, hotkey_state.inner(), &normalized);
+    DictationTriggerValidationPayload {
+        trigger: Some(normalized),
+        valid: true,
+        available: availability.is_ok(),
+        error: availability.err(),
+    }
+}
+
+#[tauri::command]
+fn clear_dictation_trigger(
+    app: tauri::AppHandle,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<DictationTriggerPayload, DictationError> {
+    let (previous_trigger, previous_trigger_raw, previous_trigger_enabled) = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        (
+            resolve_effective_dictation_trigger(&settings),
+            settings.dictation_trigger.clone(),
+            settings.dictation_trigger_enabled,
+        )
+    };
+
+    let runtime = apply_registered_hotkey(&app, hotkey_state.inner(), None)?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    settings.dictation_trigger = None;
+    settings.dictation_trigger_enabled = Some(false);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.dictation_trigger = previous_trigger_raw;
+        settings.dictation_trigger_enabled = previous_trigger_enabled;
+        drop(settings);
+        if let Err(restore_error) =
+            apply_registered_hotkey(&app, hotkey_state.inner(), previous_trigger.as_deref())
+        {
+            log::warn!(
+                "clear_dictation_trigger: failed to restore previous hotkey after save error: {restore_error}"
+            );
+        }
+        return Err(error.into());
+    }
+    Ok(dictation_trigger_payload(
+        &settings,
+        runtime,
+        fn_listener_mode(hotkey_state.inner()),
+    ))
+}
+
+// Recovery for "my hotkey stopped working": releases and re-registers the saved trigger even when
+// it looks registered already. With no saved trigger this just reports the disabled state.
+#[tauri::command]
+fn reregister_hotkey(
+    app: tauri::AppHandle,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<DictationTriggerPayload, DictationError> {
+    let trigger = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        resolve_effective_dictation_trigger(&settings)
+    };
+
+    release_registered_hotkey(&app, hotkey_state.inner())?;
+    let runtime = apply_registered_hotkey(&app, hotkey_state.inner(), trigger.as_deref())
+        .map_err(|error| format!("Could not re-register the dictation hotkey: {error}"))?;
+
+    let settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    Ok(dictation_trigger_payload(
+        &settings,
+        runtime,
+        fn_listener_mode(hotkey_state.inner()),
+    ))
+}
+
+fn save_focused_field_insert_enabled(
+    model_state: &LocalModelState,
+    next: impl FnOnce(bool) -> bool,
+) -> Result<FocusedFieldInsertPayload, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let enabled = next(focused_field_insert_enabled(&settings));
+    let permission = focused_field_insert_permission_status(enabled, enabled);
+    let previous = settings.focused_field_insert_enabled;
+    settings.focused_field_insert_enabled = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.focused_field_insert_enabled = previous;
+        return Err(error.into());
+    }
+    Ok(FocusedFieldInsertPayload {
+        mode: output_mode_name(enabled),
+        enabled,
+        permission_granted: permission.granted,
+        permission_status: permission.status,
+    })
+}
+
+#[tauri::command]
+fn set_focused_field_insert_enabled(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<FocusedFieldInsertPayload, DictationError> {
+    save_focused_field_insert_enabled(model_state.inner(), |_| enabled)
+}
+
+// Flips between pasting into the focused app and showing the text only, without opening
+// settings. The new mode goes out on `dictation:output-mode` and, while idle, on the pill.
+#[tauri::command]
+fn toggle_output_mode(
+    app: tauri::AppHandle,
+    model_state: State<'_, LocalModelState>,
+) -> Result<FocusedFieldInsertPayload, DictationError> {
+    let payload = save_focused_field_insert_enabled(model_state.inner(), |enabled| !enabled)?;
+    app.emit(DICTATION_OUTPUT_MODE_EVENT, payload.clone()).ok();
+    if !dictation_is_running(&app)? {
+        emit_pill_status(
+            &app,
+            output_mode_pill_message(payload.enabled),
+            "idle",
+            true,
+        );
+    }
+    Ok(payload)
+}
+
+#[tauri::command]
+fn set_preferred_input_device(
+    device_name: Option<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<Option<String>, DictationError> {
+    let normalized = device_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string());
+    let available_devices = list_input_devices(&saved_audio_host(model_state.inner()));
+    if let Some(name) = normalized.as_deref() {
+        if !available_devices.iter().any(|device| device.name == name) {
+            if device_name_is_loopback(name) {
+                return Err(loopback_unavailable_message(name).into());
+            }
+            return Err(format!(
+                "Microphone '{}' is not currently available on this machine.",
+                name
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.preferred_input_device.clone();
+    settings.preferred_input_device = normalized.clone();
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.preferred_input_device = previous;
+        return Err(error.into());
+    }
+
+    Ok(settings.preferred_input_device.clone())
+}
+
+#[tauri::command]
+fn list_audio_hosts(
+    model_state: State<'_, LocalModelState>,
+) -> Result<AudioHostsPayload, DictationError> {
+    let settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    Ok(audio_hosts_payload(&settings))
+}
+
+// Takes effect on the next dictation; a recording in progress keeps its stream.
+#[tauri::command]
+fn set_audio_host(
+    host: Option<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<AudioHostsPayload, DictationError> {
+    let normalized = host
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            let id = find_audio_host_id(value).ok_or_else(|| {
+                format!(
+                    "Unknown audio host '{value}'. Available: {}.",
+                    cpal::available_hosts()
+                        .iter()
+                        .map(|id| id.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+            cpal::host_from_id(id)
+                .map(|_| id.name().to_string())
+                .map_err(|e| format!("Audio host '{}' is not available: {e}", id.name()))
+        })
+        .transpose()?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.audio_host.clone();
+    settings.audio_host = normalized;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.audio_host = previous;
+        return Err(error.into());
+    }
+
+    Ok(audio_hosts_payload(&settings))
+}
+
+fn audio_normalization_payload(settings: &LocalSettings) -> AudioNormalizationPayload {
+    let options = audio_prep_options(settings);
+    AudioNormalizationPayload {
+        mode: options.normalization_mode.as_str().to_string(),
+        target_level: options.normalization_target_level,
+        target_rms: normalization_target_rms(settings),
+    }
+}
+
+#[tauri::command]
+fn set_audio_normalization(
+    mode: String,
+    target_level: Option<f32>,
+    target_rms: Option<f32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<AudioNormalizationPayload, DictationError> {
+    let parsed = AudioNormalizationMode::parse(&mode).ok_or_else(|| {
+        format!(
+            "Unsupported normalization mode '{}'. Use peak, rms, or off.",
+            mode.trim()
+        )
+    })?;
+    if let Some(level) = target_level {
+        if !NORMALIZATION_TARGET_LEVEL_RANGE.contains(&level) {
+            return Err(format!(
+                "Normalization target level must be between {} and {}.",
+                NORMALIZATION_TARGET_LEVEL_RANGE.start(),
+                NORMALIZATION_TARGET_LEVEL_RANGE.end()
+            )
+            .into());
+        }
+    }
+    if let Some(rms) = target_rms {
+        if !NORMALIZATION_TARGET_RMS_RANGE.contains(&rms) {
+            return Err(format!(
+                "Normalization target RMS must be between {} and {}.",
+                NORMALIZATION_TARGET_RMS_RANGE.start(),
+                NORMALIZATION_TARGET_RMS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_mode = settings.normalization_mode.clone();
+    let previous_level = settings.normalization_target_level;
+    let previous_rms = settings.normalization_target_rms;
+    settings.normalization_mode = Some(parsed.as_str().to_string());
+    settings.normalization_target_level = target_level;
+    settings.normalization_target_rms = target_rms;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.normalization_mode = previous_mode;
+        settings.normalization_target_level = previous_level;
+        settings.normalization_target_rms = previous_rms;
+        return Err(error.into());
+    }
+
+    Ok(audio_normalization_payload(&settings))
+}
+
+fn memory_guard_payload(settings: &LocalSettings) -> MemoryGuardPayload {
+    let (mode, margin_mb) = memory_guard_from_settings(settings);
+    MemoryGuardPayload {
+        mode: mode.as_str().to_string(),
+        margin_mb,
+    }
+}
+
+#[tauri::command]
+fn set_memory_guard(
+    mode: String,
+    margin_mb: Option<u64>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<MemoryGuardPayload, DictationError> {
+    let parsed = MemoryGuardMode::parse(&mode).ok_or_else(|| {
+        format!(
+            "Unsupported memory guard mode '{}'. Use off, warn, or block.",
+            mode.trim()
+        )
+    })?;
+    if let Some(value) = margin_mb {
+        if !MEMORY_GUARD_MARGIN_MB_RANGE.contains(&value) {
+            return Err(format!(
+                "Memory guard margin must be between {} and {} MB.",
+                MEMORY_GUARD_MARGIN_MB_RANGE.start(),
+                MEMORY_GUARD_MARGIN_MB_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_mode = settings.memory_guard_mode.clone();
+    let previous_margin = settings.memory_guard_margin_mb;
+    settings.memory_guard_mode = Some(parsed.as_str().to_string());
+    settings.memory_guard_margin_mb = margin_mb;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.memory_guard_mode = previous_mode;
+        settings.memory_guard_margin_mb = previous_margin;
+        return Err(error.into());
+    }
+
+    Ok(memory_guard_payload(&settings))
+}
+
+fn profanity_filter_payload(settings: &LocalSettings) -> ProfanityFilterPayload {
+    ProfanityFilterPayload {
+        enabled: settings.profanity_filter.unwrap_or(false),
+        words: profanity_words(settings),
+        using_default_words: settings.profanity_words.is_none(),
+    }
+}
+
+fn dedup_repeats_payload(settings: &LocalSettings) -> DedupRepeatsPayload {
+    DedupRepeatsPayload {
+        enabled: settings.dedup_repeats.unwrap_or(false),
+        max_repeats: dedup_max_repeats(settings),
+    }
+}
+
+#[tauri::command]
+fn set_min_transcribe_seconds(
+    seconds: Option<f32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<f32, DictationError> {
+    if let Some(value) = seconds {
+        if !MIN_TRANSCRIBE_SECONDS_RANGE.contains(&value) {
+            return Err(format!(
+                "Minimum duration must be between {} and {} seconds.",
+                MIN_TRANSCRIBE_SECONDS_RANGE.start(),
+                MIN_TRANSCRIBE_SECONDS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.min_transcribe_seconds;
+    settings.min_transcribe_seconds = seconds;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.min_transcribe_seconds = previous;
+        return Err(error.into());
+    }
+
+    Ok(min_transcribe_seconds(&settings))
+}
+
+#[tauri::command]
+fn set_dedup_repeats(
+    enabled: bool,
+    max_repeats: Option<u32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<DedupRepeatsPayload, DictationError> {
+    if let Some(value) = max_repeats {
+        if !DEDUP_MAX_REPEATS_RANGE.contains(&value) {
+            return Err(format!(
+                "Repeat limit must be between {} and {}.",
+                DEDUP_MAX_REPEATS_RANGE.start(),
+                DEDUP_MAX_REPEATS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = (settings.dedup_repeats, settings.dedup_max_repeats);
+    settings.dedup_repeats = Some(enabled);
+    settings.dedup_max_repeats = max_repeats;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        (settings.dedup_repeats, settings.dedup_max_repeats) = previous;
+        return Err(error.into());
+    }
+
+    Ok(dedup_repeats_payload(&settings))
+}
+
+fn sound_cues_payload(settings: &LocalSettings) -> SoundCuesPayload {
+    let disabled = disabled_sound_cues(settings);
+    SoundCuesPayload {
+        enabled: settings.sound_cues.unwrap_or(false),
+        disabled_cues: SoundCue::ALL
+            .iter()
+            .filter(|cue| disabled.contains(cue))
+            .map(|cue| cue.as_str().to_string())
+            .collect(),
+        available_cues: SoundCue::ALL
+            .iter()
+            .map(|cue| cue.as_str().to_string())
+            .collect(),
+    }
+}
+
+// `disabled_cues: None` turns every cue back on.
+#[tauri::command]
+fn set_sound_cues(
+    enabled: bool,
+    disabled_cues: Option<Vec<String>>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<SoundCuesPayload, DictationError> {
+    let disabled = match disabled_cues {
+        Some(names) => {
+            let mut canonical = Vec::new();
+            for name in &names {
+                let cue = SoundCue::parse(name).ok_or_else(|| {
+                    format!(
+                        "Unknown sound cue '{}'. Use one of: start, stop, success, error.",
+                        name.trim()
+                    )
+                })?;
+                let value = cue.as_str().to_string();
+                if !canonical.contains(&value) {
+                    canonical.push(value);
+                }
+            }
+            (!canonical.is_empty()).then_some(canonical)
+        }
+        None => None,
+    };
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = (settings.sound_cues, settings.disabled_sound_cues.clone());
+    settings.sound_cues = Some(enabled);
+    settings.disabled_sound_cues = disabled;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        (settings.sound_cues, settings.disabled_sound_cues) = previous;
+        return Err(error.into());
+    }
+
+    Ok(sound_cues_payload(&settings))
+}
+
+fn filler_stripping_payload(settings: &LocalSettings) -> FillerStrippingPayload {
+    FillerStrippingPayload {
+        enabled: settings.strip_fillers.unwrap_or(false),
+        words: filler_words(settings),
+        trailing_phrases: filler_trailing_phrases(settings),
+        using_default_words: settings.filler_words.is_none(),
+        using_default_phrases: settings.filler_trailing_phrases.is_none(),
+    }
+}
+
+#[tauri::command]
+fn set_strip_fillers(
+    enabled: bool,
+    words: Option<Vec<String>>,
+    trailing_phrases: Option<Vec<String>>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<FillerStrippingPayload, DictationError> {
+    let words = words
+        .as_deref()
+        .map(|words| validate_filler_entries(words, "words", true))
+        .transpose()?;
+    let trailing_phrases = trailing_phrases
+        .as_deref()
+        .map(|phrases| validate_filler_entries(phrases, "phrases", false))
+        .transpose()?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = (
+        settings.strip_fillers,
+        settings.filler_words.clone(),
+        settings.filler_trailing_phrases.clone(),
+    );
+    settings.strip_fillers = Some(enabled);
+    settings.filler_words = words;
+    settings.filler_trailing_phrases = trailing_phrases;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        (
+            settings.strip_fillers,
+            settings.filler_words,
+            settings.filler_trailing_phrases,
+        ) = previous;
+        return Err(error.into());
+    }
+
+    Ok(filler_stripping_payload(&settings))
+}
+
+#[tauri::command]
+fn set_profanity_filter(
+    enabled: bool,
+    words: Option<Vec<String>>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ProfanityFilterPayload, DictationError> {
+    let words = words.as_deref().map(validate_profanity_words).transpose()?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_enabled = settings.profanity_filter;
+    let previous_words = settings.profanity_words.clone();
+    settings.profanity_filter = Some(enabled);
+    settings.profanity_words = words;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.profanity_filter = previous_enabled;
+        settings.profanity_words = previous_words;
+        return Err(error.into());
+    }
+
+    Ok(profanity_filter_payload(&settings))
+}
+
+// Saves extra non-speech markers to strip (replacing any previous extras) and returns the full
+// list in effect, defaults first. An empty list clears the extras.
+#[tauri::command]
+fn set_transcript_artifact_tokens(
+    tokens: Vec<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<Vec<String>, DictationError> {
+    let tokens = validate_transcript_artifact_tokens(&tokens)?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.transcript_artifact_tokens.clone();
+    settings.transcript_artifact_tokens = (!tokens.is_empty()).then_some(tokens);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.transcript_artifact_tokens = previous;
+        return Err(error.into());
+    }
+
+    Ok(transcript_artifact_tokens(&settings))
+}
+
+fn spoken_commands_payload(settings: &LocalSettings) -> SpokenCommandsPayload {
+    SpokenCommandsPayload {
+        enabled: settings.spoken_commands.unwrap_or(false),
+        locale: spoken_commands_locale(settings).to_string(),
+        locales: SPOKEN_COMMAND_LOCALES
+            .iter()
+            .map(|locale| locale.to_string())
+            .collect(),
+        commands: spoken_command_map(settings),
+        custom_commands: custom_spoken_command_map(settings),
+    }
+}
+
+// `None` for `locale` or `commands` goes back to the English defaults / no custom entries.
+#[tauri::command]
+fn set_spoken_commands(
+    enabled: bool,
+    locale: Option<String>,
+    commands: Option<BTreeMap<String, String>>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<SpokenCommandsPayload, DictationError> {
+    let locale = locale
+        .as_deref()
+        .map(|value| {
+            SPOKEN_COMMAND_LOCALES
+                .iter()
+                .find(|locale| locale.eq_ignore_ascii_case(value.trim()))
+                .map(|locale| locale.to_string())
+                .ok_or_else(|| {
+                    format!(
+                        "Unsupported spoken command locale '{}'. Use {}.",
+                        value.trim(),
+                        SPOKEN_COMMAND_LOCALES.join(", ")
+                    )
+                })
+        })
+        .transpose()?;
+    let commands = commands
+        .as_ref()
+        .map(validate_spoken_command_map)
+        .transpose()?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_enabled = settings.spoken_commands;
+    let previous_locale = settings.spoken_commands_locale.clone();
+    let previous_commands = settings.spoken_command_map.clone();
+    settings.spoken_commands = Some(enabled);
+    settings.spoken_commands_locale = locale;
+    settings.spoken_command_map = commands;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.spoken_commands = previous_enabled;
+        settings.spoken_commands_locale = previous_locale;
+        settings.spoken_command_map = previous_commands;
+        return Err(error.into());
+    }
+
+    Ok(spoken_commands_payload(&settings))
+}
+
+fn pill_overlay_payload(settings: &LocalSettings) -> PillOverlayPayload {
+    let layout = pill_layout_from_settings(settings);
+    PillOverlayPayload {
+        position: layout.position.as_str().to_string(),
+        margin: layout.margin,
+        enabled: layout.enabled,
+        visibility: layout.visibility.as_str().to_string(),
+    }
+}
+
+#[tauri::command]
+fn set_pill_overlay(
+    app: tauri::AppHandle,
+    position: String,
+    margin: Option<u32>,
+    enabled: Option<bool>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<PillOverlayPayload, DictationError> {
+    let parsed = PillPosition::parse(&position).ok_or_else(|| {
+        format!(
+            "Unsupported pill position '{}'. Use bottom-center, bottom-left, bottom-right, top-center, top-left, or top-right.",
+            position.trim()
+        )
+    })?;
+    if let Some(value) = margin {
+        if !PILL_WINDOW_MARGIN_RANGE.contains(&value) {
+            return Err(format!(
+                "Pill margin must be between {} and {} pixels.",
+                PILL_WINDOW_MARGIN_RANGE.start(),
+                PILL_WINDOW_MARGIN_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let payload = {
+        let settings_path = model_state.settings_path();
+        let mut settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let previous = (
+            settings.pill_position.clone(),
+            settings.pill_margin,
+            settings.pill_enabled,
+        );
+        let previous_visibility = settings.pill_visibility.clone();
+        settings.pill_position = Some(parsed.as_str().to_string());
+        settings.pill_margin = margin;
+        settings.pill_enabled = enabled;
+        // Explicitly enabling the pill undoes a saved `hidden` visibility.
+        if enabled == Some(true) && pill_visibility(&settings) == PillVisibility::Hidden {
+            settings.pill_visibility = None;
+        }
+        if let Err(error) = save_local_settings(&settings_path, &settings) {
+            (
+                settings.pill_position,
+                settings.pill_margin,
+                settings.pill_enabled,
+            ) = previous;
+            settings.pill_visibility = previous_visibility;
+            return Err(error.into());
+        }
+        pill_overlay_payload(&settings)
+    };
+
+    if let Err(error) = create_pill_overlay_windows(&app) {
+        log::warn!("set_pill_overlay: failed to apply pill layout: {error}");
+    } else if payload.enabled {
+        sync_pill_after_main_window_hide(&app);
+    }
+    Ok(payload)
+}
+
+#[tauri::command]
+fn set_pill_visibility(
+    app: tauri::AppHandle,
+    visibility: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<PillOverlayPayload, DictationError> {
+    let parsed = PillVisibility::parse(&visibility).ok_or_else(|| {
+        format!(
+            "Unsupported pill visibility '{}'. Use always, recording_only, or hidden.",
+            visibility.trim()
+        )
+    })?;
+
+    let payload = {
+        let settings_path = model_state.settings_path();
+        let mut settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let previous = (settings.pill_visibility.clone(), settings.pill_enabled);
+        settings.pill_visibility = Some(parsed.as_str().to_string());
+        settings.pill_enabled = None;
+        if let Err(error) = save_local_settings(&settings_path, &settings) {
+            (settings.pill_visibility, settings.pill_enabled) = previous;
+            return Err(error.into());
+        }
+        pill_overlay_payload(&settings)
+    };
+
+    if let Err(error) = create_pill_overlay_windows(&app) {
+        log::warn!("set_pill_visibility: failed to apply pill visibility: {error}");
+    } else if payload.enabled {
+        // Pills hidden under recording_only come back for `always`; in recording_only mode the
+        // sync below shows or hides them for the current dictation state.
+        if parsed == PillVisibility::Always {
+            set_pill_windows_visible(&app, true);
+        }
+        sync_pill_after_main_window_hide(&app);
+    }
+    Ok(payload)
+}
+
+#[derive(Serialize)]
+struct HotkeyActivationPayload {
+    mode: String,
+    double_tap_ms: u64,
+    min_hold_ms: u64,
+}
+
+#[tauri::command]
+fn set_hotkey_activation(
+    app: tauri::AppHandle,
+    mode: String,
+    double_tap_ms: Option<u64>,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<HotkeyActivationPayload, DictationError> {
+    let parsed = HotkeyActivationMode::parse(&mode).ok_or_else(|| {
+        format!(
+            "Unsupported hotkey activation '{}'. Use single or double.",
+            mode.trim()
+        )
+    })?;
+    if let Some(value) = double_tap_ms {
+        if !DOUBLE_TAP_MS_RANGE.contains(&value) {
+            return Err(format!(
+                "Double-tap window must be between {}ms and {}ms.",
+                DOUBLE_TAP_MS_RANGE.start(),
+                DOUBLE_TAP_MS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = (settings.hotkey_activation.clone(), settings.double_tap_ms);
+    settings.hotkey_activation = Some(parsed.as_str().to_string());
+    settings.double_tap_ms = double_tap_ms;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        (settings.hotkey_activation, settings.double_tap_ms) = previous;
+        return Err(error.into());
+    }
+
+    apply_hotkey_activation(hotkey_state.inner(), &settings);
+    let payload = hotkey_activation_payload(&settings);
+    drop(settings);
+    // Re-render the current state's copy (the hint names the activation mode) without
+    // resetting a live or transcribing session's pill to idle.
+    let state = current_dictation_state_name(app.state::<DictationState>().inner());
+    sync_pill_for_dictation_state(&app, state, None);
+    Ok(payload)
+}
+
+fn hotkey_activation_payload(settings: &LocalSettings) -> HotkeyActivationPayload {
+    let (mode, double_tap_ms) = hotkey_activation_from_settings(settings);
+    HotkeyActivationPayload {
+        mode: mode.as_str().to_string(),
+        double_tap_ms,
+        min_hold_ms: min_hold_ms(settings),
+    }
+}
+
+// `None` restores the default; 0 turns the short-hold cancel off.
+#[tauri::command]
+fn set_min_hold_ms(
+    min_hold_ms: Option<u64>,
+    model_state: State<'_, LocalModelState>,
+    hotkey_state: State<'_, GlobalHotkeyState>,
+) -> Result<HotkeyActivationPayload, DictationError> {
+    if let Some(value) = min_hold_ms {
+        if !MIN_HOLD_MS_RANGE.contains(&value) {
+            return Err(format!(
+                "Minimum hold must be between {}ms and {}ms.",
+                MIN_HOLD_MS_RANGE.start(),
+                MIN_HOLD_MS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.min_hold_ms;
+    settings.min_hold_ms = min_hold_ms;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.min_hold_ms = previous;
+        return Err(error.into());
+    }
+
+    apply_hotkey_activation(hotkey_state.inner(), &settings);
+    Ok(hotkey_activation_payload(&settings))
+}
+
+// Returns the whisper-cli path now in effect, which is still the env override when one is set.
+#[tauri::command]
+fn set_whisper_cli_path(
+    path: Option<String>,
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let path = path
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(candidate) = path.as_deref() {
+        ensure_whisper_cli_available(candidate)?;
+    }
+
+    {
+        let settings_path = model_state.settings_path();
+        let mut settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let previous = settings.whisper_cli_path_override.clone();
+        settings.whisper_cli_path_override = path;
+        if let Err(error) = save_local_settings(&settings_path, &settings) {
+            settings.whisper_cli_path_override = previous;
+            return Err(error.into());
+        }
+    }
+    if let Ok(mut cache) = config.whisper_cli_capabilities.lock() {
+        cache.clear();
+    }
+
+    active_whisper_cli_path(config.inner(), model_state.inner()).map_err(DictationError::from)
+}
+
+// Probes every whisper-cli location in resolution order so "whisper-cli not found" comes with
+// the list of what was checked and why each one was rejected.
+#[tauri::command]
+async fn diagnose_whisper_cli(
+    app: tauri::AppHandle,
+) -> Result<WhisperCliDiagnosis, DictationError> {
+    let (override_path, saved_path, bundled_path, extra_paths) = {
+        let config = app.state::<AppConfig>();
+        let model_state = app.state::<LocalModelState>();
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        (
+            config.whisper_cli_path_override.clone(),
+            settings.whisper_cli_path_override.clone(),
+            config.bundled_whisper_cli_path.clone(),
+            extra_whisper_cli_paths(&settings),
+        )
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let candidates = whisper_cli_search_order(
+            override_path.as_deref(),
+            saved_path.as_deref(),
+            bundled_path.as_deref(),
+            &extra_paths,
+        )
+        .iter()
+        .map(|candidate| diagnose_whisper_cli_candidate(candidate))
+        .collect();
+        WhisperCliDiagnosis {
+            active_path: resolve_whisper_cli_path(
+                override_path.as_deref(),
+                saved_path.as_deref(),
+                bundled_path.as_deref(),
+                &extra_paths,
+            ),
+            candidates,
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to run whisper-cli diagnosis task: {e}").into())
+}
+
+#[tauri::command]
+fn set_extra_whisper_cli_paths(
+    paths: Vec<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<Vec<String>, DictationError> {
+    let cleaned = normalize_extra_whisper_cli_paths(&paths)?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.extra_whisper_cli_paths.clone();
+    settings.extra_whisper_cli_paths = (!cleaned.is_empty()).then_some(cleaned);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.extra_whisper_cli_paths = previous;
+        return Err(error.into());
+    }
+
+    Ok(extra_whisper_cli_paths(&settings))
+}
+
+#[tauri::command]
+fn set_extra_whisper_args(
+    args: Vec<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<Vec<String>, DictationError> {
+    let cleaned = validate_extra_whisper_args(&args)?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.extra_whisper_args.clone();
+    settings.extra_whisper_args = (!cleaned.is_empty()).then_some(cleaned);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.extra_whisper_args = previous;
+        return Err(error.into());
+    }
+
+    Ok(extra_whisper_args(&settings))
+}
+
+#[tauri::command]
+fn set_show_progress(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.show_progress;
+    settings.show_progress = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.show_progress = previous;
+        return Err(error.into());
+    }
+
+    Ok(enabled)
+}
+
+#[tauri::command]
+fn set_command_mode(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.command_mode;
+    settings.command_mode = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.command_mode = previous;
+        return Err(error.into());
+    }
+
+    Ok(enabled)
+}
+
+#[tauri::command]
+fn set_fallback_on_oom(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.fallback_on_oom;
+    settings.fallback_on_oom = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.fallback_on_oom = previous;
+        return Err(error.into());
+    }
+
+    Ok(enabled)
+}
+
+#[tauri::command]
+fn set_partial_transcripts(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.partial_transcripts;
+    settings.partial_transcripts = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.partial_transcripts = previous;
+        return Err(error.into());
+    }
+
+    Ok(enabled)
+}
+
+#[tauri::command]
+fn set_debug_audio_info(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.debug_audio_info;
+    settings.debug_audio_info = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.debug_audio_info = previous;
+        return Err(error.into());
+    }
+
+    Ok(enabled)
+}
+
+// Turning the option off stops any running whisper-server so its model memory is released.
+#[tauri::command]
+fn set_keep_whisper_warm(
+    app: tauri::AppHandle,
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.keep_whisper_warm;
+    settings.keep_whisper_warm = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.keep_whisper_warm = previous;
+        return Err(error.into());
+    }
+    drop(settings);
+
+    if !enabled {
+        stop_warm_whisper(&app, false);
+    }
+    Ok(enabled)
+}
+
+// Transcribes a second of silence with the active model so the first real dictation does not pay
+// for reading the model from disk (or, with keep_whisper_warm, for starting whisper-server).
+#[tauri::command]
+async fn preload_model(app: tauri::AppHandle) -> Result<ModelPreloadPayload, DictationError> {
+    if dictation_is_running(&app)? {
+        return Err("Stop dictation before preloading the model."
+            .to_string()
+            .into());
+    }
+    let (whisper_cli_path, model_path, options) = {
+        let config = app.state::<AppConfig>();
+        let model_state = app.state::<LocalModelState>();
+        let model_path = resolve_active_model_path(config.inner(), model_state.inner())?;
+        let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let mut options = transcription_options(&settings, &model_state.recordings_dir());
+        options.whisper.capability_cache = config.whisper_cli_capabilities.clone();
+        if settings.keep_whisper_warm.unwrap_or(false) {
+            options.whisper.warm_worker = warm_whisper_worker(&app);
+        }
+        (whisper_cli_path, model_path, options)
+    };
+    ensure_whisper_cli_available(&whisper_cli_path)?;
+    let warm_server = options.whisper.warm_worker.is_some();
+    let key = (model_path.clone(), warm_server);
+    {
+        let dictation = app.state::<DictationState>();
+        let mut preloaded = dictation
+            .preloaded_model
+            .lock()
+            .map_err(|_| "Failed to lock dictation state".to_string())?;
+        if preloaded.as_ref() == Some(&key) {
+            return Ok(ModelPreloadPayload {
+                model_path: model_path.to_string_lossy().to_string(),
+                already_warm: true,
+                warm_server,
+                elapsed_ms: 0,
+            });
+        }
+        *preloaded = Some(key.clone());
+    }
+
+    let result = tauri::async_runtime::spawn_blocking(move || -> Result<u64, String> {
+        let started = Instant::now();
+        let wav_path =
+            std::env::temp_dir().join(format!("{}-preload.wav", temp_artifact_base_name()));
+        write_wav(
+            &wav_path,
+            &vec![0.0; WHISPER_SAMPLE_RATE as usize],
+            1,
+            WHISPER_SAMPLE_RATE,
+        )?;
+        let result = run_decode_pass(
+            &whisper_cli_path,
+            &model_path,
+            &wav_path,
+            1.0,
+            &options,
+            first_decode_pass(&options, &model_path).0,
+        );
+        let _ = std::fs::remove_file(&wav_path);
+        result.map(|_| started.elapsed().as_millis() as u64)
+    })
+    .await
+    .map_err(|e| format!("Failed to run model preload task: {e}"))
+    .and_then(|result| result);
+
+    match result {
+        Ok(elapsed_ms) => Ok(ModelPreloadPayload {
+            model_path: key.0.to_string_lossy().to_string(),
+            already_warm: false,
+            warm_server,
+            elapsed_ms,
+        }),
+        Err(error) => {
+            // Let a later call try again.
+            if let Ok(mut preloaded) = app.state::<DictationState>().preloaded_model.lock() {
+                if preloaded.as_ref() == Some(&key) {
+                    *preloaded = None;
+                }
+            }
+            Err(error.into())
+        }
+    }
+}
+
+#[tauri::command]
+fn set_chunk_seconds(
+    seconds: Option<u32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<u32, DictationError> {
+    if let Some(value) = seconds {
+        if !CHUNK_SECONDS_RANGE.contains(&value) {
+            return Err(format!(
+                "Chunk length must be between {} and {} seconds.",
+                CHUNK_SECONDS_RANGE.start(),
+                CHUNK_SECONDS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.chunk_seconds;
+    settings.chunk_seconds = seconds;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.chunk_seconds = previous;
+        return Err(error.into());
+    }
+
+    Ok(chunk_seconds(&settings))
+}
+
+#[tauri::command]
+fn set_trim_silence(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.trim_silence;
+    settings.trim_silence = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.trim_silence = previous;
+        return Err(error.into());
+    }
+
+    Ok(enabled)
+}
+
+fn noise_gate_payload(settings: &LocalSettings) -> NoiseGatePayload {
+    NoiseGatePayload {
+        enabled: settings.noise_gate.unwrap_or(false),
+        threshold: noise_gate_threshold(settings),
+    }
+}
+
+#[tauri::command]
+fn set_noise_gate(
+    enabled: bool,
+    threshold: Option<f32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<NoiseGatePayload, DictationError> {
+    if let Some(value) = threshold {
+        if !NOISE_GATE_THRESHOLD_RANGE.contains(&value) {
+            return Err(format!(
+                "Noise gate threshold must be between {} and {}.",
+                NOISE_GATE_THRESHOLD_RANGE.start(),
+                NOISE_GATE_THRESHOLD_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_enabled = settings.noise_gate;
+    let previous_threshold = settings.noise_gate_threshold;
+    settings.noise_gate = Some(enabled);
+    settings.noise_gate_threshold = threshold;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.noise_gate = previous_enabled;
+        settings.noise_gate_threshold = previous_threshold;
+        return Err(error.into());
+    }
+
+    Ok(noise_gate_payload(&settings))
+}
+
+#[tauri::command]
+fn set_downmix_mode(
+    mode: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let parsed = DownmixMode::parse(&mode).ok_or_else(|| {
+        format!(
+            "Unsupported downmix mode '{}'. Use average, dominant, left, or right.",
+            mode.trim()
+        )
+    })?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.downmix_mode.clone();
+    settings.downmix_mode = Some(parsed.as_str().to_string());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.downmix_mode = previous;
+        return Err(error.into());
+    }
+
+    Ok(parsed.as_str().to_string())
+}
+
+#[tauri::command]
+fn set_preserve_channels(
+    channels: u16,
+    model_state: State<'_, LocalModelState>,
+) -> Result<u16, DictationError> {
+    if channels == 1 || channels > MAX_PRESERVED_CHANNELS {
+        return Err(format!(
+            "Preserved channel count must be 0 (off) or 2-{MAX_PRESERVED_CHANNELS}."
+        )
+        .into());
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.preserve_channels;
+    settings.preserve_channels = (channels > 0).then_some(channels);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.preserve_channels = previous;
+        return Err(error.into());
+    }
+
+    Ok(channels)
+}
+
+#[tauri::command]
+fn set_preferred_sample_rate(
+    rate: Option<u32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<Option<u32>, DictationError> {
+    if let Some(value) = rate {
+        if !PREFERRED_SAMPLE_RATE_RANGE.contains(&value) {
+            return Err(format!(
+                "Preferred sample rate must be between {} and {} Hz.",
+                PREFERRED_SAMPLE_RATE_RANGE.start(),
+                PREFERRED_SAMPLE_RATE_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.preferred_sample_rate;
+    settings.preferred_sample_rate = rate;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.preferred_sample_rate = previous;
+        return Err(error.into());
+    }
+
+    Ok(rate)
+}
+
+#[tauri::command]
+fn set_whisper_timeout_seconds(
+    seconds: Option<u64>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<u64, DictationError> {
+    if let Some(value) = seconds {
+        if !WHISPER_TIMEOUT_SECONDS_RANGE.contains(&value) {
+            return Err(format!(
+                "Whisper timeout must be between {} and {} seconds.",
+                WHISPER_TIMEOUT_SECONDS_RANGE.start(),
+                WHISPER_TIMEOUT_SECONDS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.whisper_timeout_seconds;
+    settings.whisper_timeout_seconds = seconds;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.whisper_timeout_seconds = previous;
+        return Err(error.into());
+    }
+
+    Ok(whisper_timeout_seconds(&settings))
+}
+
+fn whisper_threads_payload(settings: &LocalSettings) -> WhisperThreadsPayload {
+    let battery_saver = settings.battery_saver.unwrap_or(false);
+    let on_battery = if battery_saver {
+        running_on_battery()
+    } else {
+        None
+    };
+    let threads = configured_whisper_threads(settings);
+    WhisperThreadsPayload {
+        threads,
+        max_threads: logical_cpu_cores(),
+        battery_saver,
+        on_battery,
+        effective_threads: effective_whisper_threads(threads, on_battery == Some(true)),
+    }
+}
+
+#[tauri::command]
+fn set_whisper_threads(
+    threads: Option<usize>,
+    battery_saver: Option<bool>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<WhisperThreadsPayload, DictationError> {
+    if let Some(value) = threads {
+        let max_threads = logical_cpu_cores();
+        if !(1..=max_threads).contains(&value) {
+            return Err(format!(
+                "Whisper thread count must be between 1 and {max_threads} on this machine."
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_threads = settings.whisper_threads;
+    let previous_battery_saver = settings.battery_saver;
+    settings.whisper_threads = threads;
+    settings.battery_saver = battery_saver;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.whisper_threads = previous_threads;
+        settings.battery_saver = previous_battery_saver;
+        return Err(error.into());
+    }
+
+    Ok(whisper_threads_payload(&settings))
+}
+
+// `effective_*` are for the selected catalog model; a different model would use its own defaults.
+fn decode_overrides_payload(settings: &LocalSettings) -> DecodeOverridesPayload {
+    let overrides = decode_overrides(settings);
+    let selected = settings
+        .selected_model_id
+        .as_deref()
+        .and_then(find_whisper_model_spec);
+    let (effective, _) = first_decode_pass_for(
+        settings.two_pass_decode.unwrap_or(false),
+        overrides,
+        selected.as_ref(),
+    );
+    DecodeOverridesPayload {
+        beam_size: overrides.beam_size,
+        best_of: overrides.best_of,
+        effective_beam_size: effective.beam_size,
+        effective_best_of: effective.best_of,
+    }
+}
+
+#[tauri::command]
+fn set_decode_overrides(
+    beam_size: Option<u8>,
+    best_of: Option<u8>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<DecodeOverridesPayload, DictationError> {
+    for (name, value) in [("Beam size", beam_size), ("Best-of", best_of)] {
+        if let Some(value) = value.filter(|value| !DECODE_OVERRIDE_RANGE.contains(value)) {
+            return Err(format!(
+                "{name} must be between {} and {} (got {value}).",
+                DECODE_OVERRIDE_RANGE.start(),
+                DECODE_OVERRIDE_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = (settings.beam_size, settings.best_of);
+    settings.beam_size = beam_size;
+    settings.best_of = best_of;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        (settings.beam_size, settings.best_of) = previous;
+        return Err(error.into());
+    }
+
+    Ok(decode_overrides_payload(&settings))
+}
+
+#[tauri::command]
+fn set_whisper_acceleration(
+    acceleration: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let parsed = WhisperAcceleration::parse(&acceleration).ok_or_else(|| {
+        format!(
+            "Unsupported acceleration '{}'. Use auto, cpu, metal, coreml, or cuda.",
+            acceleration.trim()
+        )
+    })?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.acceleration.clone();
+    settings.acceleration = Some(parsed.as_str().to_string());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.acceleration = previous;
+        return Err(error.into());
+    }
+
+    Ok(whisper_acceleration(&settings).as_str().to_string())
+}
+
+#[derive(Serialize)]
+struct RecordingArchivePayload {
+    enabled: bool,
+    max_recordings: u32,
+    max_total_mb: u64,
+    recordings_dir: String,
+    format: String,
+}
+
+fn recording_archive_payload(
+    settings: &LocalSettings,
+    recordings_dir: &Path,
+) -> RecordingArchivePayload {
+    let options = recording_archive_options(settings, recordings_dir);
+    RecordingArchivePayload {
+        enabled: settings.save_recordings.unwrap_or(false),
+        max_recordings: options.max_recordings,
+        max_total_mb: options.max_total_bytes / (1024 * 1024),
+        recordings_dir: recordings_dir.to_string_lossy().to_string(),
+        format: options.format.as_str().to_string(),
+    }
+}
+
+// Applies to recordings saved from now on; existing files keep their format.
+#[tauri::command]
+fn set_recording_format(
+    format: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<RecordingArchivePayload, DictationError> {
+    let parsed = RecordingFormat::parse(&format).ok_or_else(|| {
+        format!(
+            "Unsupported recording format '{}'. Use wav, flac, or opus.",
+            format.trim()
+        )
+    })?;
+    if parsed == RecordingFormat::Opus {
+        detect_ffmpeg_path().map_err(|e| format!("Opus recordings need ffmpeg. {e}"))?;
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.recording_format.clone();
+    settings.recording_format = Some(parsed.as_str().to_string());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.recording_format = previous;
+        return Err(error.into());
+    }
+
+    Ok(recording_archive_payload(
+        &settings,
+        &model_state.recordings_dir(),
+    ))
+}
+
+#[tauri::command]
+fn set_save_recordings(
+    enabled: bool,
+    max_recordings: Option<u32>,
+    max_total_mb: Option<u64>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<RecordingArchivePayload, DictationError> {
+    if let Some(value) = max_recordings {
+        if !MAX_RECORDINGS_RANGE.contains(&value) {
+            return Err(format!(
+                "max_recordings must be between {} and {}.",
+                MAX_RECORDINGS_RANGE.start(),
+                MAX_RECORDINGS_RANGE.end()
+            )
+            .into());
+        }
+    }
+    if let Some(value) = max_total_mb {
+        if !MAX_RECORDINGS_TOTAL_MB_RANGE.contains(&value) {
+            return Err(format!(
+                "max_total_mb must be between {} and {}.",
+                MAX_RECORDINGS_TOTAL_MB_RANGE.start(),
+                MAX_RECORDINGS_TOTAL_MB_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = (
+        settings.save_recordings,
+        settings.max_recordings,
+        settings.max_recordings_total_mb,
+    );
+    settings.save_recordings = Some(enabled);
+    settings.max_recordings = max_recordings;
+    settings.max_recordings_total_mb = max_total_mb;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        (
+            settings.save_recordings,
+            settings.max_recordings,
+            settings.max_recordings_total_mb,
+        ) = previous;
+        return Err(error.into());
+    }
+
+    Ok(recording_archive_payload(
+        &settings,
+        &model_state.recordings_dir(),
+    ))
+}
+
+// An explicit catalog id must already be installed; otherwise the active model is used.
+fn resolve_retranscription_model_path(
+    config: &AppConfig,
+    model_state: &LocalModelState,
+    model_id: Option<String>,
+) -> Result<PathBuf, String> {
+    match model_id.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        Some(id) => {
+            let spec =
+                find_whisper_model_spec(id).ok_or_else(|| format!("Unknown model id '{id}'."))?;
+            let path = model_path_for_spec(&model_state.models_dir(), spec);
+            if !path.exists() {
+                return Err(format!(
+                    "Model '{}' is not installed. Download it in onboarding first.",
+                    spec.id
+                ));
+            }
+            Ok(path)
+        }
+        None => resolve_active_model_path(config, model_state).map_err(String::from),
+    }
+}
+
+#[derive(Serialize)]
+struct SubtitlesPayload {
+    format: String,
+    contents: String,
+    // Set when `save` was requested: the subtitle file next to the recording.
+    saved_path: Option<String>,
+}
+
+// Subtitles skip the transcript cleanup and low-confidence retry: cue timing comes straight
+// from whisper's segments, so the text is returned as written.
+#[tauri::command]
+async fn transcribe_to_subtitles(
+    app: tauri::AppHandle,
+    path: String,
+    format: String,
+    save: Option<bool>,
+    model_id: Option<String>,
+) -> Result<SubtitlesPayload, DictationError> {
+    let output_format = WhisperOutputFormat::parse_subtitle(&format).ok_or_else(|| {
+        format!(
+            "Unsupported subtitle format '{}'. Use srt or vtt.",
+            format.trim()
+        )
+    })?;
+    let config = app.state::<AppConfig>();
+    let model_state = app.state::<LocalModelState>();
+    let recording_path = resolve_saved_recording_path(&model_state.recordings_dir(), &path)?;
+    let model_path =
+        resolve_retranscription_model_path(config.inner(), model_state.inner(), model_id)?;
+    let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+    ensure_whisper_cli_available(&whisper_cli_path)?;
+    let (mut options, profile) = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        (
+            whisper_run_options(&settings),
+            first_decode_pass_for(
+                settings.two_pass_decode.unwrap_or(false),
+                decode_overrides(&settings),
+                catalog_spec_for_model_path(&model_path).as_ref(),
+            )
+            .0,
+        )
+    };
+    options.output_format = output_format;
+    options.capability_cache = config.whisper_cli_capabilities.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (wav_path, temporary) = saved_recording_wav(&recording_path)?;
+        let out_prefix =
+            std::env::temp_dir().join(format!("{}-subtitles", temp_artifact_base_name()));
+        let result = prepared_wav_duration_secs(&wav_path).and_then(|audio_seconds| {
+            run_whisper_cli(
+                &whisper_cli_path,
+                &model_path,
+                &wav_path,
+                &out_prefix,
+                audio_seconds,
+                &options,
+                profile,
+            )
+        });
+        if temporary {
+            let _ = fs::remove_file(&wav_path);
+        }
+        let contents = result?.text;
+        if contents.trim().is_empty() {
+            return Err("No speech detected in the recorded audio.".to_string());
+        }
+
+        let saved_path = if save.unwrap_or(false) {
+            let subtitle_path = recording_path.with_extension(output_format.extension());
+            fs::write(&subtitle_path, &contents).map_err(|e| {
+                format!(
+                    "Failed to save subtitles to {}: {e}",
+                    subtitle_path.display()
+                )
+            })?;
+            Some(subtitle_path.to_string_lossy().to_string())
+        } else {
+            None
+        };
+        Ok(SubtitlesPayload {
+            format: output_format.extension().to_string(),
+            contents,
+            saved_path,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to run subtitle task: {e}"))?
+    .map_err(DictationError::from)
+}
+
+#[tauri::command]
+async fn retranscribe_recording(
+    app: tauri::AppHandle,
+    path: String,
+    model_id: Option<String>,
+) -> Result<String, DictationError> {
+    let config = app.state::<AppConfig>();
+    let model_state = app.state::<LocalModelState>();
+    let recording_path = resolve_saved_recording_path(&model_state.recordings_dir(), &path)?;
+    let model_path =
+        resolve_retranscription_model_path(config.inner(), model_state.inner(), model_id)?;
+    let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+    ensure_whisper_cli_available(&whisper_cli_path)?;
+    let options = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let mut options = transcription_options(&settings, &model_state.recordings_dir());
+        options.whisper.capability_cache = config.whisper_cli_capabilities.clone();
+        // The source clip is already archived; re-runs must not spawn duplicate recordings.
+        options.recordings = None;
+        if settings.keep_whisper_warm.unwrap_or(false) {
+            options.whisper.warm_worker = warm_whisper_worker(&app);
+        }
+        options
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (wav_path, temporary) = saved_recording_wav(&recording_path)?;
+        let result = prepared_wav_duration_secs(&wav_path).and_then(|audio_seconds| {
+            transcribe_prepared_wav(
+                &whisper_cli_path,
+                &model_path,
+                &wav_path,
+                audio_seconds,
+                &options,
+            )
+        });
+        if temporary {
+            let _ = fs::remove_file(&wav_path);
+        }
+        result.map(|outcome| outcome.transcript)
+    })
+    .await
+    .map_err(|e| format!("Failed to run transcription task: {e}"))?
+    .map_err(DictationError::from)
+}
+
+// Runs an existing audio file through the same preparation and decode as a live capture. Like
+// `retranscribe_recording` it emits no dictation state events and never archives the input.
+#[tauri::command]
+async fn transcribe_file(app: tauri::AppHandle, path: String) -> Result<String, DictationError> {
+    let source = PathBuf::from(path.trim());
+    if !source.is_file() {
+        return Err(format!("No audio file found at {}", source.display()).into());
+    }
+    let config = app.state::<AppConfig>();
+    let model_state = app.state::<LocalModelState>();
+    let model_path = resolve_active_model_path(config.inner(), model_state.inner())?;
+    let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+    ensure_whisper_cli_available(&whisper_cli_path)?;
+    let options = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let mut options = transcription_options(&settings, &model_state.recordings_dir());
+        options.whisper.capability_cache = config.whisper_cli_capabilities.clone();
+        options.recordings = None;
+        if settings.keep_whisper_warm.unwrap_or(false) {
+            options.whisper.warm_worker = warm_whisper_worker(&app);
+        }
+        options
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (samples, sample_rate) = decode_audio_file(&source)?;
+        let file_name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| source.display().to_string());
+        // The capture gate's hint is about microphones; a silent file gets its own message.
+        if audio_signal_is_too_quiet(analyze_audio_signal(&samples, sample_rate)) {
+            return Err(format!(
+                "'{file_name}' is silent or too quiet to transcribe."
+            ));
+        }
+        let input = OpenedInput {
+            sample_rate,
+            channels: 1,
+            device_name: file_name,
+        };
+        transcribe_samples(
+            model_path,
+            whisper_cli_path,
+            samples,
+            Vec::new(),
+            input,
+            options,
+        )
+        .map(|outcome| outcome.transcript)
+    })
+    .await
+    .map_err(|e| format!("Failed to run transcription task: {e}"))?
+    .map_err(DictationError::from)
+}
+
+#[tauri::command]
+fn get_storage_dir(
+    app: tauri::AppHandle,
+    model_state: State<'_, LocalModelState>,
+) -> Result<StorageLocationPayload, DictationError> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve the app data directory: {e}"))?;
+    Ok(storage_location_payload(
+        &model_state.paths(),
+        &app_data_dir,
+    ))
+}
+
+// Moves the whole `.dicktaint` folder (settings, models, recordings, reports) under `path`, or
+// back to the app data dir when `path` is None. The copy can take minutes across filesystems, so
+// it runs off the main thread without the settings lock; the in-memory settings are written to
+// the new folder afterwards, which keeps any change saved to the old folder mid-copy.
+#[tauri::command]
+async fn set_storage_dir(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<StorageLocationPayload, DictationError> {
+    tauri::async_runtime::spawn_blocking(move || move_storage_to(&app, path.as_deref()))
+        .await
+        .map_err(|e| format!("Failed to run storage move task: {e}"))?
+}
+
+fn move_storage_to(
+    app: &tauri::AppHandle,
+    path: Option<&str>,
+) -> Result<StorageLocationPayload, DictationError> {
+    if dictation_is_running(app)? {
+        return Err("Stop dictation before moving storage.".to_string().into());
+    }
+    let model_state = app.state::<LocalModelState>();
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve the app data directory: {e}"))?;
+    let target_base = match path.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => {
+            let candidate = PathBuf::from(value);
+            if !candidate.is_absolute() || !candidate.is_dir() {
+                return Err(
+                    format!("Storage folder {value} must be an existing absolute folder.").into(),
+                );
+            }
+            candidate
+        }
+        None => app_data_dir.clone(),
+    };
+
+    let _move_guard = model_state.begin_storage_move()?;
+    let current = model_state.paths();
+    let current_app_dir = current
+        .settings_path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "Settings path has no parent directory".to_string())?;
+    let current_base = current_app_dir.parent().map(Path::to_path_buf);
+    let target_app_dir = target_base.join(APP_SETTINGS_DIR);
+    if target_app_dir == current_app_dir {
+        return Ok(storage_location_payload(&current, &app_data_dir));
+    }
+    if target_app_dir.starts_with(&current_app_dir) {
+        return Err(
+            "Choose a folder outside the current dicktaint storage folder."
+                .to_string()
+                .into(),
+        );
+    }
+    if fs::read_dir(&target_app_dir).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(format!(
+            "{} already contains dicktaint data. Choose another folder or move that one away first.",
+            target_app_dir.display()
+        )
+        .into());
+    }
+
+    move_storage_dir(&current_app_dir, &target_app_dir)?;
+    let move_back = |error: String| -> DictationError {
+        let previous_base = current_base
+            .as_deref()
+            .filter(|base| *base != app_data_dir.as_path());
+        if let Err(restore_error) = save_storage_location(&app_data_dir, previous_base) {
+            log::error!(
+                "Failed to restore the storage location after a failed switch: {restore_error}"
+            );
+        }
+        if let Err(restore_error) = move_storage_dir(&target_app_dir, &current_app_dir) {
+            log::error!("Failed to move storage back after a failed switch: {restore_error}");
+        }
+        error.into()
+    };
+    let saved_base = (target_base != app_data_dir).then_some(target_base.as_path());
+    if let Err(error) = save_storage_location(&app_data_dir, saved_base) {
+        return Err(move_back(error));
+    }
+    let (models_dir, settings_path, recordings_dir) = match resolve_local_paths(&target_base) {
+        Ok(paths) => paths,
+        Err(error) => return Err(move_back(error)),
+    };
+
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let mut moved_settings = settings.clone();
+    if let Some(rebased) = moved_settings
+        .selected_model_path
+        .as_deref()
+        .and_then(|selected| rebase_path(selected, &current.models_dir, &models_dir))
+    {
+        moved_settings.selected_model_path = Some(rebased);
+    }
+    // Without this write the rebased model path would be lost and a restart would point at
+    // the old, now-removed folder.
+    if let Err(error) = save_local_settings(&settings_path, &moved_settings) {
+        drop(settings);
+        return Err(move_back(format!(
+            "Failed to save settings in the new storage folder: {error}"
+        )));
+    }
+    *settings = moved_settings;
+    let paths = StoragePaths {
+        settings_path,
+        models_dir,
+        recordings_dir,
+    };
+    *model_state
+        .paths
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = paths.clone();
+    drop(settings);
+    log::info!(
+        "Moved dicktaint storage from {} to {}",
+        current_app_dir.display(),
+        target_app_dir.display()
+    );
+
+    Ok(storage_location_payload(&paths, &app_data_dir))
+}
+
+#[tauri::command]
+fn get_recordings(
+    model_state: State<'_, LocalModelState>,
+) -> Result<Vec<RecordingEntry>, DictationError> {
+    list_recordings(&model_state.recordings_dir()).map_err(DictationError::from)
+}
+
+#[tauri::command]
+fn open_recordings_folder(model_state: State<'_, LocalModelState>) -> Result<(), DictationError> {
+    let recordings_dir = &model_state.recordings_dir();
+    fs::create_dir_all(recordings_dir).map_err(|e| {
+        format!(
+            "Failed to create recordings directory {}: {e}",
+            recordings_dir.display()
+        )
+    })?;
 
     #[cfg(target_os = "macos")]
-    let runtime = if let Some(next_trigger) = next.as_deref() {
-        if next_trigger == "Fn" {
-            match set_macos_fn_listener_enabled(app, hotkey_state, true) {
-                Ok(()) => {
-                    runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalHold)
-                }
-                Err(error) => {
-                    log::warn!(
-                        "Global Fn listener unavailable; falling back to in-app Fn hotkey handling: {error}"
-                    );
-                    runtime_details_for_trigger(
-                        Some(next_trigger),
-                        HotkeyDeliveryMode::FocusedWindowHold,
-                    )
-                }
+    let mut command = Command::new("open");
+
+    #[cfg(target_os = "linux")]
+    let mut command = Command::new("xdg-open");
+
+    #[cfg(target_os = "windows")]
+    let mut command = Command::new("explorer");
+
+    command.arg(recordings_dir);
+    let status = command
+        .status()
+        .map_err(|e| format!("Failed to open recordings folder: {e}"))?;
+    // explorer.exe reports a non-zero exit code even when it opens the folder.
+    if !status.success() && !cfg!(target_os = "windows") {
+        return Err(format!(
+            "Could not open recordings folder automatically. Open {} manually.",
+            recordings_dir.display()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+fn unsupported_trailing_punctuation_error(value: &str) -> String {
+    format!(
+        "Unsupported trailing punctuation '{}'. Use keep, period, or none.",
+        value.trim()
+    )
+}
+
+fn auto_format_payload(settings: &LocalSettings) -> AutoFormatPayload {
+    let options = auto_format_options(settings);
+    AutoFormatPayload {
+        enabled: options.enabled,
+        append_period: options.append_period,
+    }
+}
+
+#[tauri::command]
+fn set_auto_format(
+    enabled: bool,
+    append_period: Option<bool>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<AutoFormatPayload, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_enabled = settings.auto_format;
+    let previous_append_period = settings.auto_format_append_period;
+    settings.auto_format = Some(enabled);
+    settings.auto_format_append_period = append_period;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.auto_format = previous_enabled;
+        settings.auto_format_append_period = previous_append_period;
+        return Err(error.into());
+    }
+
+    Ok(auto_format_payload(&settings))
+}
+
+fn low_confidence_retry_payload(settings: &LocalSettings) -> LowConfidenceRetryPayload {
+    let options = low_confidence_retry_options(settings);
+    LowConfidenceRetryPayload {
+        enabled: options.enabled,
+        threshold: options.score_threshold,
+    }
+}
+
+#[tauri::command]
+fn set_two_pass_decode(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<bool, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.two_pass_decode;
+    settings.two_pass_decode = Some(enabled);
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.two_pass_decode = previous;
+        return Err(error.into());
+    }
+
+    Ok(enabled)
+}
+
+#[tauri::command]
+fn set_low_confidence_retry(
+    enabled: bool,
+    threshold: Option<f32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<LowConfidenceRetryPayload, DictationError> {
+    if let Some(value) = threshold {
+        if !LOW_CONFIDENCE_SCORE_THRESHOLD_RANGE.contains(&value) {
+            return Err(format!(
+                "Low-confidence threshold must be between {} and {}.",
+                LOW_CONFIDENCE_SCORE_THRESHOLD_RANGE.start(),
+                LOW_CONFIDENCE_SCORE_THRESHOLD_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_enabled = settings.retry_on_low_confidence;
+    let previous_threshold = settings.low_confidence_threshold;
+    settings.retry_on_low_confidence = Some(enabled);
+    settings.low_confidence_threshold = threshold;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.retry_on_low_confidence = previous_enabled;
+        settings.low_confidence_threshold = previous_threshold;
+        return Err(error.into());
+    }
+
+    Ok(low_confidence_retry_payload(&settings))
+}
+
+#[tauri::command]
+fn set_min_confidence(
+    threshold: Option<f32>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<Option<f32>, DictationError> {
+    if let Some(value) = threshold {
+        if !MIN_CONFIDENCE_RANGE.contains(&value) {
+            return Err(format!(
+                "Minimum confidence must be between {} and {}.",
+                MIN_CONFIDENCE_RANGE.start(),
+                MIN_CONFIDENCE_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.min_confidence;
+    settings.min_confidence = threshold;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.min_confidence = previous;
+        return Err(error.into());
+    }
+
+    Ok(min_confidence(&settings))
+}
+
+#[tauri::command]
+fn set_output_suffix(
+    suffix: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let parsed = OutputSuffix::parse(&suffix).ok_or_else(|| {
+        format!(
+            "Unsupported output suffix '{}'. Use none, space, or newline.",
+            suffix.trim()
+        )
+    })?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.output_suffix.clone();
+    settings.output_suffix = Some(parsed.as_str().to_string());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.output_suffix = previous;
+        return Err(error.into());
+    }
+
+    Ok(parsed.as_str().to_string())
+}
+
+#[tauri::command]
+fn set_insert_wrap(
+    mode: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let parsed = InsertWrap::parse(&mode).ok_or_else(|| unsupported_insert_wrap_error(&mode))?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.insert_wrap.clone();
+    settings.insert_wrap = Some(parsed.as_str().to_string());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.insert_wrap = previous;
+        return Err(error.into());
+    }
+
+    Ok(parsed.as_str().to_string())
+}
+
+// Not persisted: the override lasts until it is cleared with `None` or the app quits.
+// Returns the mode the next transcript will use.
+#[tauri::command]
+fn set_session_insert_wrap(
+    mode: Option<String>,
+    dictation: State<'_, DictationState>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let parsed = mode
+        .as_deref()
+        .map(|value| InsertWrap::parse(value).ok_or_else(|| unsupported_insert_wrap_error(value)))
+        .transpose()?;
+    *dictation
+        .insert_wrap_override
+        .lock()
+        .map_err(|_| "Failed to lock dictation state".to_string())? = parsed;
+
+    let effective = match parsed {
+        Some(wrap) => wrap,
+        None => {
+            let settings = model_state
+                .settings
+                .lock()
+                .map_err(|_| "Failed to lock local model settings".to_string())?;
+            insert_wrap(&settings)
+        }
+    };
+    Ok(effective.as_str().to_string())
+}
+
+#[tauri::command]
+fn set_trailing_punctuation(
+    mode: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<String, DictationError> {
+    let parsed = TrailingPunctuation::parse(&mode)
+        .ok_or_else(|| unsupported_trailing_punctuation_error(&mode))?;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.trailing_punctuation.clone();
+    settings.trailing_punctuation = Some(parsed.as_str().to_string());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.trailing_punctuation = previous;
+        return Err(error.into());
+    }
+
+    Ok(trailing_punctuation(&settings).as_str().to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn write_text_to_general_pasteboard(
+    text: &str,
+) -> Result<(Retained<NSPasteboard>, Option<String>), String> {
+    let pasteboard = NSPasteboard::generalPasteboard();
+    let snapshot =
+        unsafe { pasteboard.stringForType(NSPasteboardTypeString) }.map(|value| value.to_string());
+
+    let _ = pasteboard.clearContents();
+    let ns_text = NSString::from_str(text);
+    if !unsafe { pasteboard.setString_forType(&ns_text, NSPasteboardTypeString) } {
+        return Err("Failed to place dictated text on the macOS pasteboard.".to_string());
+    }
+
+    Ok((pasteboard, snapshot))
+}
+
+#[cfg(target_os = "macos")]
+fn restore_general_pasteboard(
+    pasteboard: &NSPasteboard,
+    snapshot: Option<String>,
+) -> Result<(), String> {
+    let Some(previous_text) = snapshot else {
+        return Ok(());
+    };
+
+    let _ = pasteboard.clearContents();
+    let ns_text = NSString::from_str(previous_text.as_str());
+    if !unsafe { pasteboard.setString_forType(&ns_text, NSPasteboardTypeString) } {
+        return Err(
+            "Failed to restore the previous macOS pasteboard text after dictation paste."
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn copy_text_to_clipboard(text: &str) -> Result<(), String> {
+    write_text_to_general_pasteboard(text).map(|_| ())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn copy_text_to_clipboard(_text: &str) -> Result<(), String> {
+    Err(
+        "Copying dictation to the clipboard is currently supported on macOS desktop only."
+            .to_string(),
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn post_keyboard_event(keycode: u16, key_down: bool, flags: CGEventFlags) -> Result<(), String> {
+    let event = unsafe { CGEventCreateKeyboardEvent(std::ptr::null(), keycode, key_down) };
+    if event.is_null() {
+        return Err(format!(
+            "Failed to create macOS keyboard event for keycode {keycode}."
+        ));
+    }
+
+    unsafe {
+        CGEventSetFlags(event, flags);
+        CGEventPost(CG_EVENT_TAP_LOCATION_HID, event);
+        CFRelease(event as *const c_void);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn post_command_v_paste() -> Result<(), String> {
+    post_keyboard_event(KEYCODE_COMMAND, true, MACOS_COMMAND_FLAG_MASK)?;
+    post_keyboard_event(KEYCODE_V, true, MACOS_COMMAND_FLAG_MASK)?;
+    post_keyboard_event(KEYCODE_V, false, MACOS_COMMAND_FLAG_MASK)?;
+    post_keyboard_event(KEYCODE_COMMAND, false, 0)?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn frontmost_application() -> Option<InsertTarget> {
+    let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    let pid = app.processIdentifier();
+    if pid <= 0 {
+        return None;
+    }
+    let name = app
+        .localizedName()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("pid {pid}"));
+    Some(InsertTarget { pid, name })
+}
+
+// Brings the session's target app back to the front when something else (the pill, the main
+// window, a permission prompt) took focus, then waits insert_delay_ms for its window to settle.
+// Refuses to paste while dicktaint itself is frontmost, since the text would land in our window.
+#[cfg(target_os = "macos")]
+fn prepare_insert_target(target: Option<&InsertTarget>, delay: Duration) -> Result<(), String> {
+    if let Some(target) = target {
+        let frontmost_pid = frontmost_application().map(|app| app.pid);
+        if frontmost_pid != Some(target.pid) {
+            let activated =
+                NSRunningApplication::runningApplicationWithProcessIdentifier(target.pid)
+                    .is_some_and(|app| {
+                        app.activateWithOptions(NSApplicationActivationOptions::empty())
+                    });
+            if !activated {
+                log::warn!(
+                    "Could not re-activate {} before pasting dictation.",
+                    target.name
+                );
             }
-        } else {
-            runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalToggle)
         }
-    } else {
-        TriggerRuntimeDetails::default()
-    };
+    }
+    thread::sleep(delay);
 
-    #[cfg(not(target_os = "macos"))]
-    let runtime = if let Some(next_trigger) = next.as_deref() {
-        runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalToggle)
-    } else {
-        TriggerRuntimeDetails::default()
-    };
+    if frontmost_application().is_some_and(|app| app.pid as u32 == std::process::id()) {
+        return Err(
+            "dicktaint is the frontmost app, so the paste would land in its own window. Click into the target text field and retry."
+                .to_string(),
+        );
+    }
+    Ok(())
+}
 
-    update_hotkey_state(hotkey_state, next, runtime.clone())?;
-    Ok(runtime)
+#[cfg(target_os = "macos")]
+fn last_insert_target_app(dictation: &DictationState) -> Option<String> {
+    dictation
+        .insert_target
+        .lock()
+        .ok()
+        .and_then(|target| target.as_ref().map(|target| target.name.clone()))
 }
 
-#[cfg(any(target_os = "android", target_os = "ios"))]
-fn apply_registered_hotkey(
-    _app: &tauri::AppHandle,
-    hotkey_state: &GlobalHotkeyState,
-    trigger: Option<&str>,
-) -> Result<TriggerRuntimeDetails, String> {
-    let next = match trigger.map(str::trim).filter(|value| !value.is_empty()) {
-        Some(value) => Some(normalize_dictation_trigger(value)?),
-        None => None,
-    };
-    let runtime = if let Some(next_trigger) = next.as_deref() {
-        runtime_details_for_trigger(Some(next_trigger), HotkeyDeliveryMode::GlobalToggle)
-    } else {
-        TriggerRuntimeDetails::default()
-    };
-    update_hotkey_state(hotkey_state, next, runtime.clone())?;
-    Ok(runtime)
+#[cfg(not(target_os = "macos"))]
+fn last_insert_target_app(_dictation: &DictationState) -> Option<String> {
+    None
 }
 
-fn resolve_whisper_cli_path(override_path: Option<&str>, bundled_path: Option<&str>) -> String {
-    let preferred = if let Some(path) = override_path.map(str::trim).filter(|v| !v.is_empty()) {
-        path.to_string()
-    } else if let Some(path) = bundled_path.map(str::trim).filter(|v| !v.is_empty()) {
-        path.to_string()
-    } else {
-        DEFAULT_WHISPER_CLI_PATH.to_string()
+#[cfg(target_os = "macos")]
+fn paste_into_insert_target(
+    text: &str,
+    target: Option<&InsertTarget>,
+    delay: Duration,
+) -> Result<(), String> {
+    let permission = focused_field_insert_permission_status(true, true);
+    if !permission.granted {
+        return Err(permission.status);
+    }
+
+    prepare_insert_target(target, delay)?;
+
+    let (pasteboard, snapshot) = write_text_to_general_pasteboard(text)?;
+    let paste_result = post_command_v_paste();
+    thread::sleep(Duration::from_millis(80));
+    let restore_result = restore_general_pasteboard(&pasteboard, snapshot);
+
+    if let Err(error) = restore_result {
+        log::warn!("{error}");
+    }
+
+    paste_result.map_err(|error| {
+        format!(
+            "Focused field insertion failed while sending native paste keystrokes. Allow Accessibility for dicktaint in System Settings > Privacy & Security > Accessibility, then retry. Details: {error}"
+        )
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn insert_text_into_focused_field_impl(
+    text: &str,
+    dictation: &DictationState,
+    delay: Duration,
+) -> Result<(), String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Ok(());
+    }
+
+    let target = dictation
+        .insert_target
+        .lock()
+        .map_err(|_| "Failed to lock dictation state".to_string())?
+        .clone();
+    paste_into_insert_target(trimmed, target.as_ref(), delay)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn insert_text_into_focused_field_impl(
+    _text: &str,
+    _dictation: &DictationState,
+    _delay: Duration,
+) -> Result<(), String> {
+    Err("Focused field insertion is currently supported on macOS desktop only.".to_string())
+}
+
+// Clicking a test button leaves dicktaint frontmost, so the test then targets the app the latest
+// session captured; the session's own target is left untouched either way.
+#[cfg(target_os = "macos")]
+fn test_insert_impl(
+    dictation: &DictationState,
+    delay: Duration,
+) -> Result<(String, i32, &'static str), String> {
+    let (target, source) = match frontmost_application()
+        .filter(|app| app.pid as u32 != std::process::id())
+    {
+        Some(app) => (app, "frontmost"),
+        None => {
+            let last = dictation
+                .insert_target
+                .lock()
+                .map_err(|_| "Failed to lock dictation state".to_string())?
+                .clone();
+            let app = last.ok_or_else(|| {
+                    "dicktaint is the frontmost app and no dictation session has captured a paste target yet. Switch to the target app and trigger the test from there, or dictate once first.".to_string()
+                })?;
+            (app, "last_session")
+        }
     };
+    paste_into_insert_target(INSERT_TEST_MARKER, Some(&target), delay)?;
+    Ok((target.name, target.pid, source))
+}
 
-    detect_whisper_cli_path(&preferred).unwrap_or(preferred)
+#[cfg(not(target_os = "macos"))]
+fn test_insert_impl(
+    _dictation: &DictationState,
+    _delay: Duration,
+) -> Result<(String, i32, &'static str), String> {
+    Err("Focused field insertion is currently supported on macOS desktop only.".to_string())
 }
 
-fn ensure_whisper_cli_available(whisper_cli_path: &str) -> Result<(), String> {
-    let executable = validate_whisper_cli_candidate(whisper_cli_path).map_err(|detail| {
-        format!(
-            "Could not execute '{whisper_cli_path}': {detail}. Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH."
+// Pastes INSERT_TEST_MARKER the way a finished dictation would, whether or not focused-field
+// insertion is enabled, and reports which app it aimed at.
+#[tauri::command]
+async fn test_insert(app: tauri::AppHandle) -> Result<InsertTestPayload, DictationError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<InsertTestPayload, String> {
+        let model_state = app.state::<LocalModelState>();
+        let dictation = app.state::<DictationState>();
+        let delay_ms = {
+            let settings = model_state
+                .settings
+                .lock()
+                .map_err(|_| "Failed to lock local model settings".to_string())?;
+            insert_delay_ms(&settings)
+        };
+        let (target_app, target_pid, source) =
+            test_insert_impl(dictation.inner(), Duration::from_millis(delay_ms))?;
+        Ok(InsertTestPayload {
+            target_app,
+            target_pid,
+            source,
+            marker: INSERT_TEST_MARKER,
+            delay_ms,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to run insert test task: {e}"))?
+    .map_err(DictationError::from)
+}
+
+// Runs off the main thread: the activate/wait/paste steps sleep for insert_delay_ms, and the
+// main run loop has to stay free to deliver the frontmost-app change being waited for.
+#[tauri::command]
+async fn insert_text_into_focused_field(
+    app: tauri::AppHandle,
+    text: String,
+) -> Result<(), DictationError> {
+    let (focused_field_insert_enabled, delay_ms) = {
+        let state = app.state::<LocalModelState>();
+        let settings = state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        (
+            focused_field_insert_enabled(&settings),
+            insert_delay_ms(&settings),
         )
-    })?;
-    let output = run_help_probe(&executable).map_err(|e| {
-        format!(
-            "Could not execute '{whisper_cli_path}' (resolved to {}): {e}. Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH.",
-            executable.display()
+    };
+    if !focused_field_insert_enabled {
+        return Err(
+            "Focused-field insertion is disabled in settings. Enable \"Dictate Into Focused Field\" to use this command."
+                .to_string().into(),
+        );
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let dictation = app.state::<DictationState>();
+        insert_text_into_focused_field_impl(
+            &text,
+            dictation.inner(),
+            Duration::from_millis(delay_ms),
         )
-    })?;
-    if help_probe_looks_like_whisper_cli(&output) {
-        return Ok(());
+    })
+    .await
+    .map_err(|e| format!("Failed to run focused-field insert task: {e}"))?
+    .map_err(DictationError::from)
+}
+
+// `None` restores the default delay.
+#[tauri::command]
+fn set_insert_delay_ms(
+    delay_ms: Option<u64>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<u64, DictationError> {
+    if let Some(value) = delay_ms {
+        if !INSERT_DELAY_MS_RANGE.contains(&value) {
+            return Err(format!(
+                "Insert delay must be between {}ms and {}ms.",
+                INSERT_DELAY_MS_RANGE.start(),
+                INSERT_DELAY_MS_RANGE.end()
+            )
+            .into());
+        }
+    }
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.insert_delay_ms;
+    settings.insert_delay_ms = delay_ms;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.insert_delay_ms = previous;
+        return Err(error.into());
+    }
+
+    Ok(insert_delay_ms(&settings))
+}
+
+#[tauri::command]
+async fn install_dictation_model(
+    app: tauri::AppHandle,
+    model: String,
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<DictationModelSelection, DictationError> {
+    let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+    ensure_whisper_cli_available(&whisper_cli_path)?;
+    let trimmed_id = model.trim();
+    if trimmed_id.is_empty() {
+        return Err("Missing model id".to_string().into());
     }
 
-    let probe_summary = help_probe_summary(&output);
-    Err(format!(
-        "Could not execute '{whisper_cli_path}' (resolved to {}): probe exited with status {} and did not return recognizable whisper-cli help output ({probe_summary}). Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH.",
-        executable.display(),
-        output.status
-    ))
+    let model_spec = find_whisper_model_spec(trimmed_id).ok_or_else(|| {
+        let ids = whisper_model_catalog()
+            .iter()
+            .map(|spec| spec.id)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("Unsupported dictation model '{trimmed_id}'. Available models: {ids}")
+    })?;
+    let download_guard = model_state.begin_download()?;
+    let models_dir = model_state.models_dir();
+    let settings_path = model_state.settings_path();
+    let settings = Arc::clone(&model_state.settings);
+    let sources = {
+        let settings = settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        model_download_sources(&settings)
+    };
+
+    let install_task =
+        tauri::async_runtime::spawn_blocking(move || -> Result<DictationModelSelection, String> {
+            let _download_guard = download_guard;
+            fs::create_dir_all(&models_dir).map_err(|e| {
+                format!(
+                    "Failed to create model directory {}: {e}",
+                    models_dir.display()
+                )
+            })?;
+
+            let target_path = model_path_for_spec(&models_dir, model_spec);
+            let mut download_source = None;
+            if !target_path.exists() {
+                let on_retry = |retry: &ModelDownloadRetryPayload| {
+                    let _ = app.emit(MODEL_DOWNLOAD_RETRY_EVENT, retry);
+                };
+                download_source = Some(download_whisper_model(
+                    model_spec,
+                    &target_path,
+                    &sources,
+                    &on_retry,
+                )?);
+                if !target_path.exists() {
+                    return Err(format!(
+                        "Model download completed but file is still missing at {}.",
+                        target_path.display()
+                    ));
+                }
+            }
+
+            let selected_model_path = target_path.to_string_lossy().to_string();
+            {
+                let mut settings = settings
+                    .lock()
+                    .map_err(|_| "Failed to lock local model settings".to_string())?;
+                settings.selected_model_id = Some(model_spec.id.to_string());
+                settings.selected_model_path = Some(selected_model_path.clone());
+                save_local_settings(&settings_path, &settings)?;
+            }
+
+            Ok(DictationModelSelection {
+                selected_model_id: model_spec.id.to_string(),
+                selected_model_path,
+                installed: true,
+                download_source,
+            })
+        });
+
+    install_task
+        .await
+        .map_err(|e| format!("Model install task failed: {e}"))?
+        .map_err(DictationError::from)
 }
 
-fn can_execute_command(executable: &str) -> bool {
-    let path = match validate_whisper_cli_candidate(executable) {
-        Ok(path) => path,
-        Err(_) => return false,
-    };
-    run_help_probe(&path)
-        .map(|output| help_probe_looks_like_whisper_cli(&output))
-        .unwrap_or(false)
+fn model_download_mirror_payload(settings: &LocalSettings) -> ModelDownloadMirrorPayload {
+    ModelDownloadMirrorPayload {
+        custom_mirror: custom_model_download_mirror(settings),
+        repo: model_repo(settings),
+        revision: model_revision(settings),
+        sources: model_download_sources(settings),
+    }
 }
 
-fn run_help_probe(executable: &Path) -> Result<Output, std::io::Error> {
-    Command::new(executable).arg("--help").output()
-}
+#[tauri::command]
+fn set_model_download_mirror(
+    url: Option<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ModelDownloadMirrorPayload, DictationError> {
+    let mirror = url
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(validate_model_download_mirror)
+        .transpose()?;
 
-fn help_probe_looks_like_whisper_cli(output: &Output) -> bool {
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    whisper_help_text_looks_valid(&stdout, &stderr)
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.model_download_mirror.clone();
+    settings.model_download_mirror = mirror;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.model_download_mirror = previous;
+        return Err(error.into());
+    }
+
+    Ok(model_download_mirror_payload(&settings))
 }
 
-fn help_probe_summary(output: &Output) -> String {
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if let Some(line) = stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
-        return line.to_string();
+// `None` (or blank) restores the default for that part.
+#[tauri::command]
+fn set_model_source(
+    repo: Option<String>,
+    revision: Option<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ModelDownloadMirrorPayload, DictationError> {
+    let repo = repo
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(validate_model_repo)
+        .transpose()?;
+    let revision = revision
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(validate_model_revision)
+        .transpose()?;
+    for host in KNOWN_MODEL_DOWNLOAD_HOSTS {
+        model_repo_download_base(
+            host,
+            repo.as_deref().unwrap_or(DEFAULT_MODEL_REPO),
+            revision.as_deref().unwrap_or(DEFAULT_MODEL_REVISION),
+        )?;
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if let Some(line) = stdout.lines().map(str::trim).find(|line| !line.is_empty()) {
-        return line.to_string();
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous_repo = settings.model_repo.clone();
+    let previous_revision = settings.model_revision.clone();
+    settings.model_repo = repo;
+    settings.model_revision = revision;
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.model_repo = previous_repo;
+        settings.model_revision = previous_revision;
+        return Err(error.into());
     }
 
-    "no output".to_string()
+    Ok(model_download_mirror_payload(&settings))
 }
 
-fn whisper_help_text_looks_valid(stdout: &str, stderr: &str) -> bool {
-    let normalized = format!("{stdout}\n{stderr}").trim().to_ascii_lowercase();
-    if normalized.is_empty() {
-        return false;
+#[tauri::command]
+async fn benchmark_model(
+    app: tauri::AppHandle,
+    model: String,
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ModelBenchmarkResult, DictationError> {
+    let trimmed_id = model.trim();
+    let spec = find_whisper_model_spec(trimmed_id)
+        .ok_or_else(|| format!("Unknown model id '{trimmed_id}'."))?;
+    let model_path = model_path_for_spec(&model_state.models_dir(), spec);
+    if !model_path.exists() {
+        return Err(format!(
+            "Model '{}' is not installed. Download it in onboarding first.",
+            spec.id
+        )
+        .into());
     }
+    let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+    ensure_whisper_cli_available(&whisper_cli_path)?;
+    let options = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let mut options = transcription_options(&settings, &model_state.recordings_dir());
+        options.whisper.capability_cache = config.whisper_cli_capabilities.clone();
+        options.recordings = None;
+        options.profanity_words = Vec::new();
+        options.dedup_max_repeats = None;
+        options.filler_words = Vec::new();
+        options.filler_trailing_phrases = Vec::new();
+        options.spoken_commands = Vec::new();
+        options.min_confidence = None;
+        options.whisper.token_confidence = false;
+        options
+    };
+    let resource_dir = app_resource_dir(&app)?;
 
-    if normalized.contains("placeholder")
-        && normalized.contains("replace")
-        && normalized.contains("whisper-cli")
-    {
-        return false;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let clip_path = bundled_benchmark_clip(&resource_dir)?;
+        let audio_seconds = prepared_wav_duration_secs(&clip_path)?;
+        let started = Instant::now();
+        let outcome = transcribe_prepared_wav(
+            &whisper_cli_path,
+            &model_path,
+            &clip_path,
+            audio_seconds,
+            &options,
+        )?;
+        let measured_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Ok::<_, String>(benchmark_result(
+            spec.id,
+            outcome.transcript,
+            audio_seconds,
+            started.elapsed(),
+            measured_at_ms,
+        ))
+    })
+    .await
+    .map_err(|e| format!("Failed to run benchmark task: {e}"))??;
+
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.model_benchmarks.clone();
+    settings
+        .model_benchmarks
+        .get_or_insert_with(BTreeMap::new)
+        .insert(spec.id.to_string(), result.clone());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.model_benchmarks = previous;
+        return Err(error.into());
     }
 
-    let has_usage = normalized.contains("usage") || normalized.contains("options");
-    let has_model_flag = normalized.contains("--model")
-        || normalized.contains("\n-m ")
-        || normalized.contains(" -m ");
-    has_usage && has_model_flag
+    Ok(result)
 }
 
-fn validate_whisper_cli_candidate(candidate: &str) -> Result<PathBuf, String> {
-    let resolved_path = resolve_command_path(candidate).ok_or_else(|| {
-        if is_explicit_path(candidate) {
-            format!(
-                "whisper-cli file not found at {}",
-                Path::new(candidate).display()
-            )
-        } else {
-            format!("whisper-cli command '{candidate}' was not found in PATH")
-        }
-    })?;
-
-    let metadata = fs::metadata(&resolved_path).map_err(|e| {
-        format!(
-            "failed to read file metadata for {}: {e}",
-            resolved_path.display()
-        )
-    })?;
-    if !metadata.is_file() {
-        return Err(format!(
-            "{} exists but is not a file",
-            resolved_path.display()
-        ));
+#[tauri::command]
+fn select_dictation_model(
+    model_id: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<DictationModelSelection, DictationError> {
+    let trimmed_id = model_id.trim();
+    if trimmed_id.is_empty() {
+        return Err("Missing model id".to_string().into());
     }
 
-    #[cfg(unix)]
-    {
-        if metadata.permissions().mode() & 0o111 == 0 {
-            return Err(format!(
-                "{} is not executable (missing execute permission bits)",
-                resolved_path.display()
-            ));
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let (selected_id, target_path) = match find_whisper_model_spec(trimmed_id) {
+        Some(spec) => (
+            spec.id.to_string(),
+            model_path_for_spec(&model_state.models_dir(), spec),
+        ),
+        None => {
+            let model = find_imported_model(&settings, trimmed_id)
+                .ok_or_else(|| format!("Unknown model id '{trimmed_id}'."))?;
+            (
+                model.id.clone(),
+                imported_model_path(&model_state.models_dir(), &model.id),
+            )
         }
+    };
+    if !target_path.exists() {
+        return Err(format!(
+            "Model '{selected_id}' is not downloaded yet. Install it first, then select it."
+        )
+        .into());
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        let extension = resolved_path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.to_ascii_lowercase())
-            .unwrap_or_default();
-        let has_executable_extension = matches!(extension.as_str(), "exe" | "com" | "bat" | "cmd");
-        if !has_executable_extension {
-            return Err(format!(
-                "{} is not an executable file (expected .exe/.com/.bat/.cmd)",
-                resolved_path.display()
-            ));
-        }
+    let selected_model_path = target_path.to_string_lossy().to_string();
+    let previous_id = settings.selected_model_id.clone();
+    let previous_path = settings.selected_model_path.clone();
+    settings.selected_model_id = Some(selected_id.clone());
+    settings.selected_model_path = Some(selected_model_path.clone());
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.selected_model_id = previous_id;
+        settings.selected_model_path = previous_path;
+        return Err(error.into());
     }
 
-    Ok(resolved_path)
+    Ok(DictationModelSelection {
+        selected_model_id: selected_id,
+        selected_model_path,
+        installed: true,
+        download_source: None,
+    })
 }
 
-fn resolve_command_path(candidate: &str) -> Option<PathBuf> {
-    let trimmed = candidate.trim();
-    if trimmed.is_empty() {
-        return None;
+#[tauri::command]
+async fn delete_dictation_model(
+    model: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<DictationModelDeletion, DictationError> {
+    let trimmed_id = model.trim();
+    if trimmed_id.is_empty() {
+        return Err("Missing model id".to_string().into());
     }
 
-    if is_explicit_path(trimmed) {
-        return Some(PathBuf::from(trimmed));
-    }
+    let Some(model_spec) = find_whisper_model_spec(trimmed_id) else {
+        return delete_imported_model(model_state.inner(), trimmed_id)
+            .map_err(DictationError::from);
+    };
 
-    let path_var = std::env::var_os("PATH")?;
+    let models_dir = model_state.models_dir();
+    let settings_path = model_state.settings_path();
+    let settings = Arc::clone(&model_state.settings);
+    let total_memory_gb = system_memory_gb();
 
-    #[cfg(target_os = "windows")]
-    {
-        let has_extension = Path::new(trimmed).extension().is_some();
-        let extensions = std::env::var("PATHEXT")
-            .ok()
-            .filter(|value| !value.trim().is_empty())
-            .unwrap_or_else(|| ".COM;.EXE;.BAT;.CMD".to_string())
-            .split(';')
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .map(|value| {
-                if value.starts_with('.') {
-                    value.to_string()
-                } else {
-                    format!(".{value}")
+    let delete_task =
+        tauri::async_runtime::spawn_blocking(move || -> Result<DictationModelDeletion, String> {
+            let target_path = model_path_for_spec(&models_dir, model_spec);
+            if let Err(e) = fs::remove_file(&target_path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(format!(
+                        "Failed to delete model '{}' at {}: {e}",
+                        model_spec.id,
+                        target_path.display()
+                    ));
                 }
-            })
-            .collect::<Vec<_>>();
+            }
 
-        for dir in std::env::split_paths(&path_var) {
-            if has_extension {
-                let candidate_path = dir.join(trimmed);
-                if candidate_path.exists() {
-                    return Some(candidate_path);
+            let target_path_string = target_path.to_string_lossy().to_string();
+            let (selected_model_id, selected_model_path) = {
+                let mut settings = settings
+                    .lock()
+                    .map_err(|_| "Failed to lock local model settings".to_string())?;
+
+                let deleted_selected_model = settings.selected_model_id.as_deref()
+                    == Some(model_spec.id)
+                    || settings
+                        .selected_model_path
+                        .as_deref()
+                        .is_some_and(|path| path == target_path_string);
+
+                if deleted_selected_model {
+                    if let Some((fallback_spec, fallback_path)) =
+                        pick_best_installed_model(&models_dir, total_memory_gb, Some(model_spec.id))
+                    {
+                        settings.selected_model_id = Some(fallback_spec.id.to_string());
+                        settings.selected_model_path =
+                            Some(fallback_path.to_string_lossy().to_string());
+                    } else {
+                        settings.selected_model_id = None;
+                        settings.selected_model_path = None;
+                    }
+                    save_local_settings(&settings_path, &settings)?;
                 }
-                continue;
-            }
 
-            for extension in &extensions {
-                let candidate_path = dir.join(format!("{trimmed}{extension}"));
-                if candidate_path.exists() {
-                    return Some(candidate_path);
-                }
-            }
+                (
+                    settings.selected_model_id.clone(),
+                    settings.selected_model_path.clone(),
+                )
+            };
+
+            Ok(DictationModelDeletion {
+                deleted_model_id: model_spec.id.to_string(),
+                selected_model_id,
+                selected_model_path,
+            })
+        });
+
+    delete_task
+        .await
+        .map_err(|e| format!("Model delete task failed: {e}"))?
+        .map_err(DictationError::from)
+}
+
+fn delete_imported_model(
+    model_state: &LocalModelState,
+    model_id: &str,
+) -> Result<DictationModelDeletion, String> {
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    if find_imported_model(&settings, model_id).is_none() {
+        let ids = whisper_model_catalog()
+            .iter()
+            .map(|spec| spec.id)
+            .chain(
+                settings
+                    .imported_models
+                    .iter()
+                    .flatten()
+                    .map(|model| model.id.as_str()),
+            )
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(format!(
+            "Unsupported dictation model '{model_id}'. Available models: {ids}"
+        ));
+    }
+
+    let target_path = imported_model_path(&model_state.models_dir(), model_id);
+    if let Err(e) = fs::remove_file(&target_path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(format!(
+                "Failed to delete model '{model_id}' at {}: {e}",
+                target_path.display()
+            ));
         }
-        return None;
     }
 
-    #[cfg(not(target_os = "windows"))]
+    let previous = settings.clone();
+    if let Some(models) = settings.imported_models.as_mut() {
+        models.retain(|model| model.id != model_id);
+    }
+    if settings.imported_models.as_ref().is_some_and(Vec::is_empty) {
+        settings.imported_models = None;
+    }
+    let target_path_string = target_path.to_string_lossy().to_string();
+    if settings.selected_model_id.as_deref() == Some(model_id)
+        || settings.selected_model_path.as_deref() == Some(target_path_string.as_str())
     {
-        for dir in std::env::split_paths(&path_var) {
-            let candidate_path = dir.join(trimmed);
-            if candidate_path.exists() {
-                return Some(candidate_path);
+        match pick_best_installed_model(&model_state.models_dir(), system_memory_gb(), None) {
+            Some((fallback_spec, fallback_path)) => {
+                settings.selected_model_id = Some(fallback_spec.id.to_string());
+                settings.selected_model_path = Some(fallback_path.to_string_lossy().to_string());
+            }
+            None => {
+                settings.selected_model_id = None;
+                settings.selected_model_path = None;
             }
         }
     }
+    if let Err(error) = save_local_settings(&model_state.settings_path(), &settings) {
+        *settings = previous;
+        return Err(error);
+    }
 
-    None
+    Ok(DictationModelDeletion {
+        deleted_model_id: model_id.to_string(),
+        selected_model_id: settings.selected_model_id.clone(),
+        selected_model_path: settings.selected_model_path.clone(),
+    })
 }
 
-fn is_explicit_path(value: &str) -> bool {
-    Path::new(value).is_absolute() || value.contains('/') || value.contains('\\')
-}
+// Copies a ggml model from anywhere on disk into the models directory under a new `imported-` id.
+// The copy is staged as a `.part` file so a failed import never leaves a half-written model.
+#[tauri::command]
+async fn import_dictation_model(
+    path: String,
+    display_name: Option<String>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ImportedModel, DictationError> {
+    let source_path = PathBuf::from(path.trim());
+    if source_path.as_os_str().is_empty() {
+        return Err("Missing model file path".to_string().into());
+    }
+    check_model_file_readable(&source_path)?;
+    let display_name = match display_name.as_deref() {
+        Some(name) => validate_imported_model_name(name)?,
+        None => validate_imported_model_name(
+            &source_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        )
+        .unwrap_or_else(|_| "Imported model".to_string()),
+    };
 
-fn preferred_arch_variants() -> Vec<&'static str> {
-    let primary = std::env::consts::ARCH;
-    let mut variants = vec![primary];
+    let models_dir = model_state.models_dir();
+    let settings_path = model_state.settings_path();
+    let settings = Arc::clone(&model_state.settings);
+    let import_task =
+        tauri::async_runtime::spawn_blocking(move || -> Result<ImportedModel, String> {
+            let added_at_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            let staging_path = partial_download_path(&imported_model_path(
+                &models_dir,
+                &format!("pending-{}-{added_at_ms}", std::process::id()),
+            ));
+            if let Some(parent) = staging_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    format!("Failed to create model directory {}: {e}", parent.display())
+                })?;
+            }
+            fs::copy(&source_path, &staging_path).map_err(|e| {
+                let _ = fs::remove_file(&staging_path);
+                format!(
+                    "Failed to copy model {} into {}: {e}",
+                    source_path.display(),
+                    models_dir.display()
+                )
+            })?;
 
-    #[cfg(target_os = "macos")]
-    {
-        for fallback in ["aarch64", "x86_64"] {
-            if !variants.contains(&fallback) {
-                variants.push(fallback);
+            let mut settings = settings
+                .lock()
+                .map_err(|_| "Failed to lock local model settings".to_string())?;
+            let model = ImportedModel {
+                id: new_imported_model_id(&source_path, &settings),
+                display_name,
+                source_path: source_path.to_string_lossy().to_string(),
+                added_at_ms,
+            };
+            let target_path = imported_model_path(&models_dir, &model.id);
+            if let Err(e) = fs::rename(&staging_path, &target_path) {
+                let _ = fs::remove_file(&staging_path);
+                return Err(format!(
+                    "Failed to move imported model into {}: {e}",
+                    target_path.display()
+                ));
             }
-        }
-    }
+            let previous = settings.imported_models.clone();
+            settings
+                .imported_models
+                .get_or_insert_with(Vec::new)
+                .push(model.clone());
+            if let Err(error) = save_local_settings(&settings_path, &settings) {
+                settings.imported_models = previous;
+                let _ = fs::remove_file(&target_path);
+                return Err(error);
+            }
+            Ok(model)
+        });
 
-    variants
+    import_task
+        .await
+        .map_err(|e| format!("Model import task failed: {e}"))?
+        .map_err(DictationError::from)
 }
 
-fn preferred_whisper_cli_names() -> Vec<String> {
-    let os = std::env::consts::OS;
-    let mut names = Vec::<String>::new();
+#[tauri::command]
+fn rename_imported_model(
+    model_id: String,
+    display_name: String,
+    model_state: State<'_, LocalModelState>,
+) -> Result<ImportedModel, DictationError> {
+    let display_name = validate_imported_model_name(&display_name)?;
+    let model_id = model_id.trim();
 
-    if os == "windows" {
-        for arch in preferred_arch_variants() {
-            names.push(format!("whisper-cli-{arch}-pc-windows-msvc.exe"));
-        }
-        names.push("whisper-cli.exe".to_string());
-    } else if os == "macos" {
-        for arch in preferred_arch_variants() {
-            names.push(format!("whisper-cli-{arch}-apple-darwin"));
-        }
-        names.push("whisper-cli".to_string());
-    } else if os == "linux" {
-        for arch in preferred_arch_variants() {
-            names.push(format!("whisper-cli-{arch}-unknown-linux-gnu"));
-        }
-        names.push("whisper-cli".to_string());
-    } else {
-        names.push("whisper-cli".to_string());
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = settings.imported_models.clone();
+    let Some(model) = settings
+        .imported_models
+        .iter_mut()
+        .flatten()
+        .find(|model| model.id == model_id)
+    else {
+        return Err(format!("Unknown imported model id '{model_id}'.").into());
+    };
+    model.display_name = display_name;
+    let renamed = model.clone();
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        settings.imported_models = previous;
+        return Err(error.into());
     }
 
-    names
+    Ok(renamed)
 }
 
-fn find_whisper_cli_in_dir(dir: &Path) -> Option<PathBuf> {
-    for name in preferred_whisper_cli_names() {
-        let preferred = dir.join(name);
-        if preferred.is_file() {
-            return Some(preferred);
-        }
-    }
-    None
+#[tauri::command]
+async fn cleanup_temp_files(
+    older_than_minutes: Option<u64>,
+) -> Result<TempFileCleanup, DictationError> {
+    let max_age = older_than_minutes
+        .map(|minutes| Duration::from_secs(minutes.saturating_mul(60)))
+        .unwrap_or(TEMP_ARTIFACT_STALE_AGE);
+    tauri::async_runtime::spawn_blocking(move || {
+        cleanup_temp_artifacts(&std::env::temp_dir(), max_age, &process_is_alive)
+    })
+    .await
+    .map_err(|e| format!("Temp file cleanup task failed: {e}"))?
+    .map_err(DictationError::from)
 }
 
-fn resolve_bundled_whisper_cli_path(app: &tauri::AppHandle) -> Option<String> {
-    let mut candidate_dirs = Vec::<PathBuf>::new();
-
-    if let Ok(resource_dir) = app.path().resource_dir() {
-        candidate_dirs.push(resource_dir.clone());
-        candidate_dirs.push(resource_dir.join("bin"));
-        candidate_dirs.push(resource_dir.join("binaries"));
+#[tauri::command]
+async fn delete_all_models(
+    config: State<'_, AppConfig>,
+    model_state: State<'_, LocalModelState>,
+) -> Result<AllModelsDeletion, DictationError> {
+    // Deleting now would pull the `.part` file out from under the running download.
+    if model_state.download_active() {
+        return Err(
+            "Wait for the model download to finish before deleting all models."
+                .to_string()
+                .into(),
+        );
     }
+    let models_dir = model_state.models_dir();
+    let settings_path = model_state.settings_path();
+    let settings = Arc::clone(&model_state.settings);
+    let protected_path = config
+        .whisper_model_path_override
+        .clone()
+        .map(PathBuf::from);
 
-    if let Ok(exe) = std::env::current_exe() {
-        if let Some(parent) = exe.parent() {
-            candidate_dirs.push(parent.to_path_buf());
-            if cfg!(target_os = "macos") {
-                candidate_dirs.push(parent.join("../Resources"));
-                candidate_dirs.push(parent.join("../Resources/bin"));
+    let delete_task =
+        tauri::async_runtime::spawn_blocking(move || -> Result<AllModelsDeletion, String> {
+            let mut deletion = delete_catalog_model_files(&models_dir, protected_path.as_deref());
+            for file in &deletion.not_deleted {
+                log::warn!(
+                    "delete_all_models: kept {} ({}): {}",
+                    file.path,
+                    file.model_id,
+                    file.reason
+                );
             }
-        }
-    }
-
-    // In tauri:dev, sidecar binaries usually live in src-tauri/binaries.
-    candidate_dirs.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("binaries"));
-
-    let mut deduped = Vec::<PathBuf>::new();
-    for dir in candidate_dirs {
-        if !deduped.iter().any(|seen| seen == &dir) {
-            deduped.push(dir);
-        }
-    }
-
-    for dir in deduped {
-        if let Some(path) = find_whisper_cli_in_dir(&dir) {
-            return Some(path.to_string_lossy().to_string());
-        }
-    }
 
-    None
-}
+            let mut settings = settings
+                .lock()
+                .map_err(|_| "Failed to lock local model settings".to_string())?;
+            // Cleared even when the selected file survived (imported, protected, or failed to
+            // delete), so the next dictation goes through model onboarding.
+            if settings.selected_model_id.is_some() || settings.selected_model_path.is_some() {
+                settings.selected_model_id = None;
+                settings.selected_model_path = None;
+                save_local_settings(&settings_path, &settings)?;
+            }
+            deletion.selected_model_id = None;
+            deletion.selected_model_path = None;
+            Ok(deletion)
+        });
 
-fn local_dev_sidecar_candidates() -> Vec<String> {
-    let binaries_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("binaries");
-    preferred_whisper_cli_names()
-        .into_iter()
-        .map(|name| binaries_dir.join(name).to_string_lossy().to_string())
-        .collect()
+    delete_task
+        .await
+        .map_err(|e| format!("Model delete task failed: {e}"))?
+        .map_err(DictationError::from)
 }
 
-fn candidate_whisper_cli_paths(configured_path: &str) -> Vec<String> {
-    let mut candidates = Vec::<String>::new();
+fn start_native_dictation_inner(
+    app: &tauri::AppHandle,
+    options: DictationSessionOptions,
+) -> Result<u64, DictationError> {
+    let config = app.state::<AppConfig>();
+    let model_state = app.state::<LocalModelState>();
+    let dictation = app.state::<DictationState>();
 
-    if !configured_path.trim().is_empty() {
-        candidates.push(configured_path.trim().to_string());
-    }
-    candidates.extend(local_dev_sidecar_candidates());
-    if configured_path.trim() != DEFAULT_WHISPER_CLI_PATH {
-        candidates.push(DEFAULT_WHISPER_CLI_PATH.to_string());
+    // Starts are idempotent: a second trigger re-announces the live session instead of failing,
+    // so the pill and frontend resync from the event alone.
+    if let Some(session_id) = current_active_session_id(app)? {
+        emit_dictation_state(app, "listening", None, None, Some(session_id));
+        return Ok(session_id);
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        candidates.push("/opt/homebrew/bin/whisper-cli".to_string());
-        candidates.push("/usr/local/bin/whisper-cli".to_string());
-        candidates.push("/opt/homebrew/opt/whisper-cpp/bin/whisper-cli".to_string());
+    ensure_microphone_access_authorized(app)?;
+    let model_path = match resolve_active_model_path(config.inner(), model_state.inner()) {
+        Ok(path) => path,
+        Err(error) => {
+            if let Ok(settings) = model_state.settings.lock() {
+                let reason = model_onboarding_reason(
+                    &settings,
+                    config.whisper_model_path_override.is_some(),
+                );
+                drop(settings);
+                emit_needs_onboarding(app, reason, &error.message);
+            }
+            return Err(error);
+        }
+    };
+    enforce_memory_guard(model_state.inner(), &model_path)?;
+    let whisper_cli_path = active_whisper_cli_path(config.inner(), model_state.inner())?;
+    if let Err(error) = ensure_whisper_cli_available(&whisper_cli_path) {
+        emit_needs_onboarding(app, OnboardingReason::CliMissing, &error.message);
+        return Err(error);
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        candidates.push("/usr/local/bin/whisper-cli".to_string());
-        candidates.push("/usr/bin/whisper-cli".to_string());
+    play_start_cue_before_capture(app);
+    let mut guard = dictation
+        .active_recording
+        .lock()
+        .map_err(|_| "Failed to lock dictation state".to_string())?;
+    if let Some(recording) = guard.as_ref() {
+        let session_id = recording.session_id;
+        let state = recording_state_name(recording);
+        drop(guard);
+        emit_dictation_state(app, state, None, None, Some(session_id));
+        return Ok(session_id);
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        candidates.push("C:\\Program Files\\whisper.cpp\\whisper-cli.exe".to_string());
-        candidates.push("C:\\Program Files (x86)\\whisper.cpp\\whisper-cli.exe".to_string());
+    let session_id = dictation.next_session_id.fetch_add(1, Ordering::SeqCst);
+    // Captured before the microphone opens, since a permission prompt can take focus.
+    #[cfg(target_os = "macos")]
+    if let Ok(mut target) = dictation.insert_target.lock() {
+        *target = frontmost_application().filter(|app| app.pid as u32 != std::process::id());
     }
+    let samples = Arc::new(Mutex::new(Vec::<f32>::new()));
+    let channel_capture = {
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        channel_capture(&settings)
+    };
+    let (capture, opened) = spawn_recording_thread(
+        Arc::clone(&samples),
+        channel_capture.clone(),
+        app.clone(),
+        session_id,
+    )?;
+    *guard = Some(ActiveRecording {
+        session_id,
+        input: opened,
+        capture: Some(capture),
+        samples,
+        channel_capture,
+        options,
+    });
+    drop(guard);
 
-    let mut deduped = Vec::new();
-    for candidate in candidates {
-        if !deduped.contains(&candidate) {
-            deduped.push(candidate);
-        }
-    }
-    deduped
+    emit_dictation_state(app, "listening", None, None, Some(session_id));
+    Ok(session_id)
 }
 
-fn detect_whisper_cli_path(configured_path: &str) -> Option<String> {
-    candidate_whisper_cli_paths(configured_path)
-        .into_iter()
-        .find(|candidate| can_execute_command(candidate))
+// The forwarder exits once every sender is dropped, i.e. when the transcription options go away.
+fn spawn_progress_forwarder(app: tauri::AppHandle, session_id: u64) -> mpsc::Sender<u8> {
+    let (tx, rx) = mpsc::channel::<u8>();
+    thread::spawn(move || {
+        let mut last = None;
+        for percent in rx {
+            if last == Some(percent) {
+                continue;
+            }
+            last = Some(percent);
+            let _ = app.emit(
+                DICTATION_PROGRESS_EVENT,
+                DictationProgressPayload {
+                    session_id,
+                    percent,
+                },
+            );
+        }
+    });
+    tx
 }
 
-fn resolve_local_paths(base_data_dir: &Path) -> Result<(PathBuf, PathBuf), String> {
-    let app_dir = base_data_dir.join(APP_SETTINGS_DIR);
-    let models_dir = app_dir.join(APP_MODELS_DIR);
-    let settings_path = app_dir.join(APP_SETTINGS_FILE);
+fn slow_processing_threshold(timeout: Duration) -> Duration {
+    DICTATION_SLOW_AFTER.min(timeout / 2)
+}
 
-    fs::create_dir_all(&app_dir).map_err(|e| {
-        format!(
-            "Failed to create local app settings directory {}: {e}",
-            app_dir.display()
-        )
-    })?;
-    fs::create_dir_all(&models_dir).map_err(|e| {
-        format!(
-            "Failed to create local model directory {}: {e}",
-            models_dir.display()
+// Fires `dictation:slow` once if the session is still transcribing when the threshold passes;
+// dropping the returned sender disarms it.
+fn spawn_slow_processing_watcher(
+    app: tauri::AppHandle,
+    session_id: u64,
+    timeout: Duration,
+) -> mpsc::Sender<()> {
+    let (tx, rx) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        let threshold = slow_processing_threshold(timeout);
+        if rx.recv_timeout(threshold) != Err(mpsc::RecvTimeoutError::Timeout) {
+            return;
+        }
+        let still_processing = app
+            .state::<DictationState>()
+            .processing_session
+            .lock()
+            .map(|processing| *processing == Some(session_id))
+            .unwrap_or(false);
+        if !still_processing {
+            return;
+        }
+        app.emit(
+            DICTATION_SLOW_EVENT,
+            DictationSlowPayload {
+                session_id,
+                elapsed_ms: threshold.as_millis() as u64,
+                timeout_ms: timeout.as_millis() as u64,
+            },
         )
-    })?;
+        .ok();
+        emit_pill_status(&app, "Still transcribing - cancel to stop", "working", true);
+    });
+    tx
+}
 
-    Ok((models_dir, settings_path))
+fn spawn_partial_forwarder(
+    app: tauri::AppHandle,
+    session_id: u64,
+) -> mpsc::Sender<PartialTranscript> {
+    let (tx, rx) = mpsc::channel::<PartialTranscript>();
+    thread::spawn(move || {
+        for partial in rx {
+            let _ = app.emit(
+                DICTATION_PARTIAL_EVENT,
+                DictationPartialPayload {
+                    session_id,
+                    text: partial.text,
+                    chunks_done: partial.chunks_done,
+                    chunk_count: partial.chunk_count,
+                },
+            );
+        }
+    });
+    tx
 }
 
-fn load_local_settings(settings_path: &Path) -> LocalSettings {
-    let raw = match fs::read_to_string(settings_path) {
-        Ok(value) => value,
-        Err(_) => return LocalSettings::default(),
+// Stops are idempotent: with nothing recording, the current state (`processing` for a session
+// still transcribing, otherwise `idle`) is re-emitted and an empty transcript is returned.
+async fn stop_native_dictation_inner(app: tauri::AppHandle) -> Result<String, DictationError> {
+    let (recording, processing) = {
+        let dictation = app.state::<DictationState>();
+        let mut guard = dictation
+            .active_recording
+            .lock()
+            .map_err(|_| "Failed to lock dictation state".to_string())?;
+        let mut processing = dictation
+            .processing_session
+            .lock()
+            .map_err(|_| "Failed to lock dictation state".to_string())?;
+        let recording = guard.take();
+        if let Some(recording) = recording.as_ref() {
+            *processing = Some(recording.session_id);
+        }
+        (recording, *processing)
     };
+    let Some(recording) = recording else {
+        match processing {
+            Some(session_id) => {
+                emit_dictation_state(&app, "processing", None, None, Some(session_id))
+            }
+            None => emit_dictation_state(&app, "idle", None, None, None),
+        }
+        return Ok(String::new());
+    };
+    let session_id = recording.session_id;
+    let result = finish_stopped_recording(app.clone(), recording).await;
 
-    match serde_json::from_str::<LocalSettings>(&raw) {
-        Ok(settings) => settings,
-        Err(error) => {
-            log::warn!(
-                "load_local_settings: failed to parse LocalSettings from {}: {}",
-                settings_path.display(),
-                error
-            );
-            LocalSettings::default()
+    if let Ok(mut processing) = app.state::<DictationState>().processing_session.lock() {
+        if *processing == Some(session_id) {
+            *processing = None;
         }
     }
+    result
 }
 
-fn save_local_settings(settings_path: &Path, settings: &LocalSettings) -> Result<(), String> {
-    let parent = settings_path.parent().ok_or_else(|| {
-        format!(
-            "Failed to determine settings directory for {}",
-            settings_path.display()
-        )
-    })?;
-    fs::create_dir_all(parent).map_err(|e| {
-        format!(
-            "Failed to create settings directory {}: {e}",
-            parent.display()
-        )
-    })?;
-
-    let serialized = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize dictation settings: {e}"))?;
+async fn finish_stopped_recording(
+    app: tauri::AppHandle,
+    mut recording: ActiveRecording,
+) -> Result<String, DictationError> {
+    let session_id = recording.session_id;
 
-    let timestamp_nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_nanos())
-        .unwrap_or(0);
-    let target_name = settings_path
-        .file_name()
-        .and_then(|name| name.to_str())
-        .unwrap_or("dictation-settings.json");
-    // Write-then-rename keeps settings updates atomic across crashes/interruption.
-    let temp_path = parent.join(format!(
-        ".{}.tmp-{}-{}",
-        target_name,
-        std::process::id(),
-        timestamp_nanos
-    ));
+    if !recording.capture.take().map_or(true, CaptureThread::stop) {
+        emit_dictation_state(
+            &app,
+            "error",
+            Some("Audio capture thread crashed.".into()),
+            None,
+            Some(session_id),
+        );
+        return Err("Audio capture thread crashed.".to_string().into());
+    }
 
-    let mut temp_file = fs::OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(&temp_path)
-        .map_err(|e| {
-            format!(
-                "Failed to create temp settings file {}: {e}",
-                temp_path.display()
-            )
-        })?;
-    temp_file.write_all(serialized.as_bytes()).map_err(|e| {
-        format!(
-            "Failed to write temp settings file {}: {e}",
-            temp_path.display()
-        )
-    })?;
-    temp_file.flush().map_err(|e| {
-        format!(
-            "Failed to flush temp settings file {}: {e}",
-            temp_path.display()
-        )
-    })?;
-    temp_file.sync_all().map_err(|e| {
-        format!(
-            "Failed to sync temp settings file {}: {e}",
-            temp_path.display()
+    let captured_samples = recording
+        .samples
+        .lock()
+        .map_err(|_| "Failed to read captured audio".to_string())?
+        .clone();
+    let captured_channels = match recording.channel_capture.as_ref() {
+        Some(capture) => capture
+            .buffers
+            .lock()
+            .map_err(|_| "Failed to read captured audio".to_string())?
+            .clone(),
+        None => Vec::new(),
+    };
+    let model_path = {
+        let config = app.state::<AppConfig>();
+        let model_state = app.state::<LocalModelState>();
+        resolve_active_model_path(config.inner(), model_state.inner())?
+    };
+    let whisper_cli_path = {
+        let config = app.state::<AppConfig>();
+        let model_state = app.state::<LocalModelState>();
+        active_whisper_cli_path(config.inner(), model_state.inner())?
+    };
+    let (options, (wrap, suffix), debug_audio_info, processing_timeout, command_mode, too_short) = {
+        let model_state = app.state::<LocalModelState>();
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let mut options = transcription_options(&settings, &model_state.recordings_dir());
+        options.whisper.capability_cache =
+            app.state::<AppConfig>().whisper_cli_capabilities.clone();
+        if settings.keep_whisper_warm.unwrap_or(false) {
+            options.whisper.warm_worker = warm_whisper_worker(&app);
+        }
+        if let Some(trailing_punctuation) = recording.options.trailing_punctuation {
+            options.trailing_punctuation = trailing_punctuation;
+        }
+        if recording.options.scratch {
+            options.recordings = None;
+        }
+        if settings.show_progress.unwrap_or(false) {
+            options.whisper.progress = Some(spawn_progress_forwarder(app.clone(), session_id));
+        }
+        if settings.partial_transcripts.unwrap_or(false) {
+            options.partial = Some(spawn_partial_forwarder(app.clone(), session_id));
+        }
+        let retention = last_recording_retention_seconds(&settings);
+        if !recording.options.scratch && retention > 0 {
+            options.capture_cache = Some(LastCaptureCache {
+                slot: Arc::clone(&app.state::<DictationState>().last_capture),
+                retention: Duration::from_secs(retention),
+            });
+        }
+        if settings.fallback_on_oom.unwrap_or(false) {
+            options.oom_fallback =
+                pick_oom_fallback_model(&model_state.models_dir(), system_memory_gb(), &model_path)
+                    .map(|(_, path)| path);
+        }
+        let wrap = app
+            .state::<DictationState>()
+            .insert_wrap_override
+            .lock()
+            .ok()
+            .and_then(|guard| *guard)
+            .unwrap_or_else(|| insert_wrap(&settings));
+        let captured_seconds = if recording.input.sample_rate == 0 {
+            0.0
+        } else {
+            captured_samples.len() as f32 / recording.input.sample_rate as f32
+        };
+        let min_seconds = min_transcribe_seconds(&settings);
+        (
+            options,
+            (wrap, output_suffix(&settings)),
+            settings.debug_audio_info.unwrap_or(false),
+            effective_whisper_timeout(whisper_timeout_seconds(&settings), captured_seconds),
+            settings.command_mode.unwrap_or(false),
+            (captured_seconds < min_seconds).then_some((captured_seconds, min_seconds)),
         )
-    })?;
-    drop(temp_file);
+    };
+    let scratch = recording.options.scratch;
 
-    fs::rename(&temp_path, settings_path).map_err(|e| {
-        format!(
-            "Failed to replace dictation settings file {} with temp file {}: {e}",
-            settings_path.display(),
-            temp_path.display()
+    // Not an error: the hint goes out on its own event and the session ends as a quiet `idle`.
+    if let Some((captured_seconds, min_seconds)) = too_short {
+        log::info!(
+            "Skipping transcription of session {session_id}: {captured_seconds:.2}s is under the {min_seconds:.2}s minimum"
+        );
+        app.emit(
+            DICTATION_TOO_SHORT_EVENT,
+            DictationTooShortPayload {
+                code: "too_short",
+                session_id,
+                captured_seconds,
+                min_seconds,
+                message: too_short_message(captured_seconds, min_seconds),
+            },
         )
-    })?;
-
-    Ok(())
-}
+        .ok();
+        emit_dictation_state(&app, "idle", None, None, Some(session_id));
+        emit_pill_status(&app, "Too short - hold a bit longer", "idle", true);
+        return Ok(String::new());
+    }
 
-fn whisper_model_catalog() -> &'static [WhisperModelSpec] {
-    &WHISPER_MODEL_CATALOG
-}
+    emit_dictation_state(&app, "processing", None, None, Some(session_id));
+    let slow_watcher = spawn_slow_processing_watcher(app.clone(), session_id, processing_timeout);
 
-fn find_whisper_model_spec(id: &str) -> Option<WhisperModelSpec> {
-    whisper_model_catalog()
-        .iter()
-        .copied()
-        .find(|spec| spec.id == id)
-}
+    let model_path_for_info = model_path.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        transcribe_samples(
+            model_path,
+            whisper_cli_path,
+            captured_samples,
+            captured_channels,
+            recording.input,
+            options,
+        )
+    })
+    .await
+    .map_err(|e| {
+        emit_dictation_state(&app, "error", Some(e.to_string()), None, Some(session_id));
+        format!("Failed to run transcription task: {e}")
+    })?;
+    drop(slow_watcher);
 
-fn total_memory_bytes() -> Option<u64> {
-    #[cfg(target_os = "macos")]
-    {
-        let output = Command::new("sysctl")
-            .args(["-n", "hw.memsize"])
-            .output()
-            .ok()?;
-        if !output.status.success() {
-            return None;
-        }
-        let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        return raw.parse::<u64>().ok();
+    // `cancel_native_dictation` during processing already reported `idle`; whisper ran to the
+    // end (or its timeout) regardless, and its result is dropped.
+    let cancelled = app
+        .state::<DictationState>()
+        .processing_session
+        .lock()
+        .map(|processing| *processing != Some(session_id))
+        .unwrap_or(false);
+    if cancelled {
+        log::info!("Dropping the transcript of session {session_id}, cancelled while processing");
+        return Ok(String::new());
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        let content = fs::read_to_string("/proc/meminfo").ok()?;
-        let line = content
-            .lines()
-            .find(|entry| entry.starts_with("MemTotal:"))?;
-        let kib = line
-            .split_whitespace()
-            .nth(1)
-            .and_then(|value| value.parse::<u64>().ok())?;
-        return Some(kib.saturating_mul(1024));
+    let command = match &result {
+        Ok(outcome) if command_mode => parse_utterance_command(&outcome.transcript),
+        _ => None,
+    };
+    if let Some(command) = command {
+        return run_utterance_command(&app, session_id, command).map_err(DictationError::from);
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        let output = Command::new("powershell.exe")
-            .args([
-                "-NoProfile",
-                "-Command",
-                "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory",
-            ])
-            .output()
-            .ok()?;
-        if !output.status.success() {
-            return None;
+    match result {
+        Ok(outcome) => {
+            // The archive already holds the bare transcript; only the copy handed to the
+            // frontend (draft, history, focused-field paste) carries the wrap and separator.
+            let word_count = transcript_word_count(&outcome.transcript);
+            let audio_seconds = outcome.info.audio_seconds;
+            let transcript =
+                apply_output_suffix(apply_insert_wrap(outcome.transcript, wrap), suffix);
+            let warning = outcome.warning;
+            let retried = outcome.info.retry_score.is_some();
+            let audio_info = outcome.audio_info.filter(|_| debug_audio_info);
+            if !scratch {
+                if let Ok(mut last) = app.state::<DictationState>().last_transcription.lock() {
+                    *last = Some(LastTranscriptionInfo {
+                        session_id,
+                        model_id: recording_model_id(
+                            outcome
+                                .fallback_model
+                                .as_deref()
+                                .unwrap_or(&model_path_for_info),
+                        ),
+                        info: outcome.info,
+                    });
+                }
+                record_usage(
+                    app.state::<LocalModelState>().inner(),
+                    word_count,
+                    audio_seconds,
+                );
+            }
+            emit_dictation_state_payload(
+                &app,
+                DictationStatePayload {
+                    state: "idle".to_string(),
+                    error: None,
+                    transcript: Some(transcript.clone()),
+                    session_id: Some(session_id),
+                    scratch,
+                    warning,
+                    retried,
+                    audio_info,
+                    word_count: Some(word_count),
+                    audio_seconds: Some(audio_seconds),
+                    command: None,
+                },
+            );
+            Ok(transcript)
+        }
+        Err(e) => {
+            emit_dictation_state(&app, "error", Some(e.clone()), None, Some(session_id));
+            Err(e.into())
         }
-        let value = String::from_utf8(output.stdout)
-            .ok()?
-            .trim_matches(|c| c == '\r' || c == '\n' || c == ' ')
-            .to_string();
-        return value.parse::<u64>().ok();
     }
-
-    #[allow(unreachable_code)]
-    None
 }
 
-fn system_memory_gb() -> u64 {
-    let total_bytes = total_memory_bytes().unwrap_or(8 * 1_073_741_824);
-    (((total_bytes as f64) / 1_073_741_824.0).round() as u64).max(1)
+// A spoken command replaces the utterance's output: nothing is pasted, archived as the last
+// transcription, or counted in usage, and `idle` carries the command name instead of text.
+fn run_utterance_command(
+    app: &tauri::AppHandle,
+    session_id: u64,
+    command: UtteranceCommand,
+) -> Result<String, String> {
+    if let UtteranceCommand::Copy(text) = &command {
+        let copied = if text.is_empty() {
+            Err("Nothing to copy: say the text after \"command copy\".".to_string())
+        } else {
+            copy_text_to_clipboard(text)
+        };
+        if let Err(error) = copied {
+            emit_dictation_state(app, "error", Some(error.clone()), None, Some(session_id));
+            return Err(error);
+        }
+    }
+    log::info!(
+        "Session {session_id} ran the spoken `{}` command",
+        command.as_str()
+    );
+    emit_dictation_state_payload(
+        app,
+        DictationStatePayload {
+            state: "idle".to_string(),
+            error: None,
+            transcript: None,
+            session_id: Some(session_id),
+            scratch: false,
+            warning: None,
+            retried: false,
+            audio_info: None,
+            word_count: None,
+            audio_seconds: None,
+            command: Some(command.as_str().to_string()),
+        },
+    );
+    Ok(String::new())
 }
 
-fn build_device_profile() -> DeviceProfile {
-    let logical_cpu_cores = std::thread::available_parallelism()
-        .map(|n| n.get())
-        .unwrap_or(1);
+fn cancel_native_dictation_inner(app: &tauri::AppHandle) -> Result<(), String> {
+    let recording = {
+        let dictation = app.state::<DictationState>();
+        let mut guard = dictation
+            .active_recording
+            .lock()
+            .map_err(|_| "Failed to lock dictation state".to_string())?;
+        guard.take()
+    };
+    let mut session_id = recording.as_ref().map(|value| value.session_id);
 
-    DeviceProfile {
-        total_memory_gb: system_memory_gb(),
-        logical_cpu_cores,
-        architecture: std::env::consts::ARCH.to_string(),
-        os: std::env::consts::OS.to_string(),
+    if let Some(capture) = recording.and_then(|recording| recording.capture) {
+        capture.stop();
     }
+    // With nothing recording, cancel abandons a session that is still transcribing.
+    if session_id.is_none() {
+        session_id = app
+            .state::<DictationState>()
+            .processing_session
+            .lock()
+            .map_err(|_| "Failed to lock dictation state".to_string())?
+            .take();
+    }
+
+    emit_dictation_state(app, "idle", None, None, session_id);
+    Ok(())
 }
 
-fn model_path_for_spec(models_dir: &Path, spec: WhisperModelSpec) -> PathBuf {
-    models_dir.join(spec.file_name)
-}
-
-fn model_fit_level(spec: WhisperModelSpec, total_memory_gb: u64) -> u8 {
-    if total_memory_gb >= spec.recommended_ram_gb {
-        2
-    } else if total_memory_gb >= spec.min_ram_gb {
-        1
+fn recording_state_name(recording: &ActiveRecording) -> &'static str {
+    if recording.capture.is_some() {
+        "listening"
     } else {
-        0
+        "paused"
     }
 }
 
-fn pick_recommended_model_id(total_memory_gb: u64) -> Option<&'static str> {
-    whisper_model_catalog()
-        .iter()
-        .copied()
-        .filter(|spec| model_fit_level(*spec, total_memory_gb) > 0)
-        .max_by(|a, b| {
-            // Prefer strongest runnable model for the machine, not merely the smallest.
-            let a_key = (
-                model_fit_level(*a, total_memory_gb),
-                a.recommended_ram_gb,
-                a.approx_size_gb.to_bits(),
-            );
-            let b_key = (
-                model_fit_level(*b, total_memory_gb),
-                b.recommended_ram_gb,
-                b.approx_size_gb.to_bits(),
-            );
-            a_key.cmp(&b_key)
-        })
-        .map(|spec| spec.id)
+fn current_dictation_state_name(dictation: &DictationState) -> &'static str {
+    if let Some(state) = dictation
+        .active_recording
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(recording_state_name))
+    {
+        return state;
+    }
+    let processing = dictation
+        .processing_session
+        .lock()
+        .map(|processing| processing.is_some())
+        .unwrap_or(false);
+    if processing {
+        "processing"
+    } else {
+        "idle"
+    }
 }
 
-fn build_model_options(
-    models_dir: &Path,
-    total_memory_gb: u64,
-    selected_model_id: Option<&str>,
-) -> Vec<DictationModelOption> {
-    let recommended_model_id = pick_recommended_model_id(total_memory_gb);
-
-    whisper_model_catalog()
-        .iter()
-        .map(|spec| {
-            let path = model_path_for_spec(models_dir, *spec);
-            let installed = path.exists();
-            let likely_runnable = total_memory_gb >= spec.min_ram_gb;
-            let recommended = recommended_model_id.is_some_and(|id| id == spec.id);
-            let is_selected = selected_model_id.is_some_and(|id| id == spec.id);
+// Pausing drops the input stream instead of muting it, so the pause window adds no
+// silence to the buffer for trim/VAD to deal with later; resume appends to the same samples.
+fn pause_native_dictation_inner(app: &tauri::AppHandle) -> Result<(), DictationError> {
+    let dictation = app.state::<DictationState>();
+    let mut guard = dictation
+        .active_recording
+        .lock()
+        .map_err(|_| "Failed to lock dictation state".to_string())?;
+    let Some(recording) = guard.as_mut() else {
+        return Err("Dictation is not running.".to_string().into());
+    };
+    let session_id = recording.session_id;
+    let stopped = recording.capture.take().map_or(true, CaptureThread::stop);
+    drop(guard);
 
-            DictationModelOption {
-                id: spec.id.to_string(),
-                display_name: if is_selected {
-                    format!("{} (Selected)", spec.display_name)
-                } else {
-                    spec.display_name.to_string()
-                },
-                whisper_ref: spec.whisper_ref.to_string(),
-                file_name: spec.file_name.to_string(),
-                path: path.to_string_lossy().to_string(),
-                installed,
-                likely_runnable,
-                recommended,
-                approx_size_gb: spec.approx_size_gb,
-                min_ram_gb: spec.min_ram_gb,
-                recommended_ram_gb: spec.recommended_ram_gb,
-                speed_note: spec.speed_note.to_string(),
-                quality_note: spec.quality_note.to_string(),
-            }
-        })
-        .collect()
+    if !stopped {
+        return Err("Audio capture thread crashed.".to_string().into());
+    }
+    emit_dictation_state(app, "paused", None, None, Some(session_id));
+    Ok(())
 }
 
-fn pick_best_installed_model(
-    models_dir: &Path,
-    total_memory_gb: u64,
-    exclude_model_id: Option<&str>,
-) -> Option<(WhisperModelSpec, PathBuf)> {
-    whisper_model_catalog()
-        .iter()
-        .copied()
-        .filter(|spec| !exclude_model_id.is_some_and(|exclude| exclude == spec.id))
-        .filter_map(|spec| {
-            let path = model_path_for_spec(models_dir, spec);
-            if path.exists() {
-                Some((spec, path))
-            } else {
-                None
-            }
-        })
-        .max_by(|(a, _), (b, _)| {
-            let a_key = (
-                model_fit_level(*a, total_memory_gb),
-                a.recommended_ram_gb,
-                a.approx_size_gb.to_bits(),
-            );
-            let b_key = (
-                model_fit_level(*b, total_memory_gb),
-                b.recommended_ram_gb,
-                b.approx_size_gb.to_bits(),
-            );
-            a_key.cmp(&b_key)
-        })
-}
+// The stream is opened without holding `active_recording`, so stop, cancel, and hotkey handlers
+// are not blocked for the open timeout. The session is re-checked afterwards: if it ended or
+// another resume already reopened capture, the new stream is closed again.
+fn resume_native_dictation_inner(app: &tauri::AppHandle) -> Result<(), DictationError> {
+    let dictation = app.state::<DictationState>();
+    let (session_id, samples, channel_capture, sample_rate, channels) = {
+        let guard = dictation
+            .active_recording
+            .lock()
+            .map_err(|_| "Failed to lock dictation state".to_string())?;
+        let Some(recording) = guard.as_ref() else {
+            return Err("Dictation is not running.".to_string().into());
+        };
+        if recording.capture.is_some() {
+            let session_id = recording.session_id;
+            drop(guard);
+            emit_dictation_state(app, "listening", None, None, Some(session_id));
+            return Ok(());
+        }
+        (
+            recording.session_id,
+            Arc::clone(&recording.samples),
+            recording.channel_capture.clone(),
+            recording.input.sample_rate,
+            recording.input.channels,
+        )
+    };
 
-fn resolve_active_model_path(
-    config: &AppConfig,
-    model_state: &LocalModelState,
-) -> Result<PathBuf, String> {
-    if let Some(path) = &config.whisper_model_path_override {
-        return resolve_whisper_model_path(Some(path.as_str()));
+    play_start_cue_before_capture(app);
+    let (capture, opened) =
+        spawn_recording_thread(samples, channel_capture, app.clone(), session_id)?;
+    // The buffer has no per-segment format, so a device that reopens differently can't be appended.
+    if opened.sample_rate != sample_rate || opened.channels != channels {
+        capture.stop();
+        return Err(DictationError::new(
+            ErrorCode::MicOpenFailed,
+            format!(
+                "Microphone reopened as {} ({} Hz, {} ch) but this dictation started at {sample_rate} Hz, {channels} ch. Stop to transcribe what was captured.",
+                opened.device_name,
+                opened.sample_rate,
+                opened.channels,
+            ),
+        ));
     }
 
-    let settings = model_state
-        .settings
-        .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?;
-    let saved_path = settings
-        .selected_model_path
-        .as_deref()
-        .map(str::trim)
-        .filter(|v| !v.is_empty())
-        .ok_or_else(|| {
-            "No local dictation model selected yet. Install one in onboarding before starting dictation."
-                .to_string()
-        })?;
-
-    let path = PathBuf::from(saved_path);
-    if !path.exists() {
-        return Err(format!(
-            "Selected dictation model file is missing: {}. Reinstall/select a model in onboarding.",
-            path.display()
-        ));
+    let (unused_capture, session_ended) = {
+        let mut guard = dictation
+            .active_recording
+            .lock()
+            .map_err(|_| "Failed to lock dictation state".to_string())?;
+        match guard
+            .as_mut()
+            .filter(|recording| recording.session_id == session_id)
+        {
+            Some(recording) if recording.capture.is_none() => {
+                recording.capture = Some(capture);
+                (None, false)
+            }
+            Some(_) => (Some(capture), false),
+            None => (Some(capture), true),
+        }
+    };
+    if let Some(capture) = unused_capture {
+        capture.stop();
+    }
+    if session_ended {
+        return Err("Dictation stopped before the microphone reopened."
+            .to_string()
+            .into());
     }
 
-    Ok(path)
+    emit_dictation_state(app, "listening", None, None, Some(session_id));
+    Ok(())
 }
 
-fn download_whisper_model(model_spec: WhisperModelSpec, target_path: &Path) -> Result<(), String> {
-    let target_str = target_path.to_string_lossy().to_string();
-    let model_url = format!(
-        "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}",
-        model_spec.file_name
-    );
+fn transcript_word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
 
-    #[cfg(target_os = "windows")]
-    let output = Command::new("powershell")
-        .args([
-            "-NoProfile",
-            "-Command",
-            "Invoke-WebRequest",
-            "-Uri",
-            &model_url,
-            "-OutFile",
-            &target_str,
-        ])
-        .output();
+fn add_usage(totals: &mut UsageTotals, words: usize, audio_seconds: f32) {
+    totals.words = totals.words.saturating_add(words as u64);
+    totals.audio_seconds += f64::from(audio_seconds.max(0.0));
+    totals.sessions = totals.sessions.saturating_add(1);
+}
 
-    #[cfg(not(target_os = "windows"))]
-    let output = Command::new("curl")
-        .args(["-L", "--fail", "--output", &target_str, &model_url])
-        .output();
+// A failed save only costs this session's numbers, so it is logged rather than surfaced.
+fn record_usage(model_state: &LocalModelState, words: usize, audio_seconds: f32) {
+    let Ok(mut settings) = model_state.settings.lock() else {
+        return;
+    };
+    if !settings.usage_stats.unwrap_or(false) {
+        return;
+    }
+    let previous = settings.usage_totals.clone();
+    add_usage(
+        settings
+            .usage_totals
+            .get_or_insert_with(UsageTotals::default),
+        words,
+        audio_seconds,
+    );
+    if let Err(error) = save_local_settings(&model_state.settings_path(), &settings) {
+        settings.usage_totals = previous;
+        log::warn!("Failed to save usage stats: {error}");
+    }
+}
 
-    match output {
-        Ok(result) if result.status.success() && target_path.exists() => Ok(()),
-        Ok(result) => {
-            let stderr = String::from_utf8_lossy(&result.stderr).trim().to_string();
-            let stdout = String::from_utf8_lossy(&result.stdout).trim().to_string();
-            let detail = if !stderr.is_empty() {
-                stderr
-            } else if !stdout.is_empty() {
-                stdout
-            } else {
-                "no output".to_string()
-            };
-            Err(format!(
-                "Could not download whisper model '{}' from {}: {}",
-                model_spec.id, model_url, detail
-            ))
-        }
-        Err(e) => Err(format!(
-            "Could not start model download command. Install curl or PowerShell support and retry: {e}"
-        )),
+fn usage_stats_payload(settings: &LocalSettings) -> UsageStatsPayload {
+    let totals = settings.usage_totals.clone().unwrap_or_default();
+    UsageStatsPayload {
+        enabled: settings.usage_stats.unwrap_or(false),
+        words: totals.words,
+        audio_seconds: totals.audio_seconds,
+        sessions: totals.sessions,
     }
 }
 
-fn build_onboarding_payload(
-    config: &AppConfig,
-    model_state: &LocalModelState,
-    hotkey_state: &GlobalHotkeyState,
-) -> Result<DictationOnboardingPayload, String> {
-    let device = build_device_profile();
+#[tauri::command]
+fn get_usage_stats(
+    model_state: State<'_, LocalModelState>,
+) -> Result<UsageStatsPayload, DictationError> {
     let settings = model_state
         .settings
         .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?
-        .clone();
-    let override_model_path = config
-        .whisper_model_path_override
-        .as_deref()
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-        .map(|value| value.to_string());
-    let override_model_exists = override_model_path
-        .as_deref()
-        .map(|value| Path::new(value).exists())
-        .unwrap_or(false);
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    Ok(usage_stats_payload(&settings))
+}
 
-    let selected_model_path = if override_model_path.is_some() {
-        override_model_path.clone()
-    } else {
-        settings.selected_model_path.clone()
-    };
-    let selected_model_exists = if override_model_path.is_some() {
-        override_model_exists
-    } else {
-        settings
-            .selected_model_path
-            .as_deref()
-            .map(|value| Path::new(value).exists())
-            .unwrap_or(false)
-    };
-    let selected_model_id = if override_model_path.is_some() {
-        Some("env-override".to_string())
-    } else {
-        settings.selected_model_id.clone()
-    };
-    let dictation_trigger = resolve_effective_dictation_trigger(&settings);
-    let registered_trigger = current_registered_hotkey(hotkey_state).ok().flatten();
-    let registered_runtime = current_trigger_runtime_details(hotkey_state).ok();
-    let trigger_runtime = onboarding_runtime_details(
-        dictation_trigger.as_deref(),
-        registered_trigger.as_deref(),
-        registered_runtime.as_ref(),
-    );
-    let list_selected_model_id = if override_model_path.is_some() {
-        None
-    } else {
-        settings.selected_model_id.as_deref()
-    };
-    let models = build_model_options(
-        &model_state.models_dir,
-        device.total_memory_gb,
-        list_selected_model_id,
-    );
-    let configured_whisper_cli_path = resolve_whisper_cli_path(
-        config.whisper_cli_path_override.as_deref(),
-        config.bundled_whisper_cli_path.as_deref(),
-    );
-    let detected_whisper_cli_path = detect_whisper_cli_path(&configured_whisper_cli_path);
-    let whisper_cli_available = detected_whisper_cli_path.is_some();
-    let onboarding_required = !selected_model_exists || !whisper_cli_available;
-    let focused_field_permission =
-        focused_field_insert_permission_status(focused_field_insert_enabled(&settings), false);
-    let available_input_devices = list_input_devices();
+// Turning stats off also forgets the saved totals.
+#[tauri::command]
+fn set_usage_stats(
+    enabled: bool,
+    model_state: State<'_, LocalModelState>,
+) -> Result<UsageStatsPayload, DictationError> {
+    let settings_path = model_state.settings_path();
+    let mut settings = model_state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock local model settings".to_string())?;
+    let previous = (settings.usage_stats, settings.usage_totals.clone());
+    settings.usage_stats = Some(enabled);
+    if !enabled {
+        settings.usage_totals = None;
+    }
+    if let Err(error) = save_local_settings(&settings_path, &settings) {
+        (settings.usage_stats, settings.usage_totals) = previous;
+        return Err(error.into());
+    }
 
-    Ok(DictationOnboardingPayload {
-        onboarding_required,
-        selected_model_id,
-        selected_model_path,
-        selected_model_exists,
-        available_input_devices,
-        preferred_input_device: settings.preferred_input_device.clone(),
-        dictation_trigger,
-        default_dictation_trigger: default_dictation_trigger(),
-        dictation_trigger_mode: trigger_runtime.mode.as_str().to_string(),
-        dictation_trigger_status: trigger_runtime.status,
-        dictation_trigger_permission_hint: trigger_runtime.permission_hint,
-        focused_field_insert_enabled: focused_field_insert_enabled(&settings),
-        focused_field_insert_permission_granted: focused_field_permission.granted,
-        focused_field_insert_permission_status: focused_field_permission.status,
-        whisper_cli_available,
-        whisper_cli_path: detected_whisper_cli_path.unwrap_or(configured_whisper_cli_path),
-        models_dir: model_state.models_dir.to_string_lossy().to_string(),
-        device,
-        models,
-    })
+    Ok(usage_stats_payload(&settings))
 }
 
 #[tauri::command]
-fn open_whisper_setup_page() -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    let mut command = {
-        let mut cmd = Command::new("open");
-        cmd.arg(WHISPER_CPP_SETUP_URL);
-        cmd
-    };
+fn get_last_transcription_info(
+    dictation: State<'_, DictationState>,
+) -> Result<Option<TranscriptionInfoPayload>, DictationError> {
+    let last = dictation
+        .last_transcription
+        .lock()
+        .map_err(|_| "Failed to lock dictation state".to_string())?;
+    Ok(last.as_ref().map(|last| TranscriptionInfoPayload {
+        session_id: last.session_id,
+        model_id: last.model_id.clone(),
+        decode_path: last.info.decode_path.as_str().to_string(),
+        fast_score: last.info.fast_score,
+        retry_score: last.info.retry_score,
+        retried: last.info.retry_score.is_some(),
+        audio_seconds: last.info.audio_seconds,
+        token_confidence: last.info.token_confidence,
+    }))
+}
 
-    #[cfg(target_os = "linux")]
-    let mut command = {
-        let mut cmd = Command::new("xdg-open");
-        cmd.arg(WHISPER_CPP_SETUP_URL);
-        cmd
-    };
+#[tauri::command]
+async fn request_microphone_permission(
+    app: tauri::AppHandle,
+) -> Result<MicrophonePermissionPayload, DictationError> {
+    if dictation_is_running(&app)? {
+        return Err("Stop dictation before requesting microphone access."
+            .to_string()
+            .into());
+    }
 
-    #[cfg(target_os = "windows")]
-    let mut command = {
-        let mut cmd = Command::new("cmd");
-        cmd.args(["/C", "start", "", WHISPER_CPP_SETUP_URL]);
-        cmd
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = ensure_microphone_access_authorized(&app)
+            .map_err(String::from)
+            .and_then(|_| {
+                probe_default_input_stream(&saved_audio_host(&app.state::<LocalModelState>()))
+            });
+        match result {
+            Ok(input_device_name) => MicrophonePermissionPayload {
+                granted: true,
+                status: format!("Microphone access granted ({input_device_name})."),
+                input_device_name: Some(input_device_name),
+            },
+            Err(status) => MicrophonePermissionPayload {
+                granted: false,
+                status,
+                input_device_name: None,
+            },
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to run microphone permission task: {e}").into())
+}
+
+// Runs each setup stage in order and reports all of them instead of stopping at the first error;
+// the transcription stage is skipped when whisper-cli or the model is unusable.
+// Records a short clip from the saved input and runs it through capture preparation only, so the
+// user can check levels and silence trimming without loading a model.
+#[tauri::command]
+async fn preview_capture(
+    app: tauri::AppHandle,
+    seconds: Option<f32>,
+) -> Result<CapturePreviewPayload, DictationError> {
+    let seconds = seconds.unwrap_or(DEFAULT_CAPTURE_PREVIEW_SECONDS);
+    if !CAPTURE_PREVIEW_SECONDS_RANGE.contains(&seconds) {
+        return Err(format!(
+            "Preview length must be between {} and {} seconds.",
+            CAPTURE_PREVIEW_SECONDS_RANGE.start(),
+            CAPTURE_PREVIEW_SECONDS_RANGE.end()
+        )
+        .into());
+    }
+    if dictation_is_running(&app)? {
+        return Err("Stop dictation before previewing the microphone."
+            .to_string()
+            .into());
+    }
+    ensure_microphone_access_authorized(&app)?;
+    let audio = {
+        let model_state = app.state::<LocalModelState>();
+        let settings = model_state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock local model settings".to_string())?;
+        audio_prep_options(&settings)
     };
+    let session_id = app
+        .state::<DictationState>()
+        .next_session_id
+        .fetch_add(1, Ordering::SeqCst);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (capture, input) = spawn_recording_thread(Arc::clone(&samples), None, app, session_id)?;
+        thread::sleep(Duration::from_secs_f32(seconds));
+        capture.stop();
+        let samples = std::mem::take(
+            &mut *samples
+                .lock()
+                .map_err(|_| "Failed to lock preview samples".to_string())?,
+        );
+        Ok(capture_preview_payload(samples, &input, audio))
+    })
+    .await
+    .map_err(|e| format!("Failed to run capture preview task: {e}"))?
+}
 
-    let status = command
-        .status()
-        .map_err(|e| format!("Failed to open download page: {e}"))?;
-    if !status.success() {
+// End-to-end microphone check that never touches whisper: records a few seconds from the saved
+// input and plays the raw capture straight back on the default output.
+#[tauri::command]
+async fn record_and_playback(
+    app: tauri::AppHandle,
+    seconds: Option<f32>,
+) -> Result<MicPlaybackPayload, DictationError> {
+    let seconds = seconds.unwrap_or(DEFAULT_MIC_PLAYBACK_SECONDS);
+    if !MIC_PLAYBACK_SECONDS_RANGE.contains(&seconds) {
         return Err(format!(
-            "Could not open setup page automatically. Open {WHISPER_CPP_SETUP_URL} manually."
+            "Test recording length must be between {} and {} seconds.",
+            MIC_PLAYBACK_SECONDS_RANGE.start(),
+            MIC_PLAYBACK_SECONDS_RANGE.end()
+        )
+        .into());
+    }
+    if dictation_is_running(&app)? {
+        return Err("Stop dictation before testing the microphone."
+            .to_string()
+            .into());
+    }
+    ensure_microphone_access_authorized(&app)?;
+    let session_id = app
+        .state::<DictationState>()
+        .next_session_id
+        .fetch_add(1, Ordering::SeqCst);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (capture, input) =
+            spawn_recording_thread(Arc::clone(&samples), None, app.clone(), session_id)?;
+        thread::sleep(Duration::from_secs_f32(seconds));
+        capture.stop();
+        let samples = std::mem::take(
+            &mut *samples
+                .lock()
+                .map_err(|_| "Failed to lock test recording samples".to_string())?,
+        );
+        if samples.is_empty() {
+            return Err(DictationError::new(
+                ErrorCode::MicOpenFailed,
+                format!(
+                    "'{}' delivered no audio. Check that the microphone is connected and not muted.",
+                    input.device_name
+                ),
+            ));
+        }
+        let signal = analyze_audio_signal(&samples, input.sample_rate);
+        let host = {
+            let model_state = app.state::<LocalModelState>();
+            let settings = model_state
+                .settings
+                .lock()
+                .map_err(|_| "Failed to lock local model settings".to_string())?;
+            audio_host(&settings)
+        };
+        let output_device_name =
+            play_samples_on_default_output(&host, &samples, input.sample_rate)?;
+        Ok(MicPlaybackPayload {
+            input_device_name: input.device_name,
+            output_device_name,
+            captured_seconds: signal.duration_secs,
+            peak: signal.peak_abs,
+            rms: signal.rms,
+            too_quiet: audio_signal_is_too_quiet(signal),
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to run microphone test task: {e}"))?
+}
+
+// Never downloads: the bundled reference clip is used when it is present, otherwise whisper
+// decodes a generated second of silence, which still proves the CLI can load the model and run.
+fn self_test_transcription(
+    whisper_cli_path: &str,
+    model_path: &Path,
+    reference_clip: Option<&Path>,
+    options: &TranscriptionOptions,
+) -> Result<String, String> {
+    let started = Instant::now();
+    if let Some(clip_path) = reference_clip {
+        let audio_seconds = prepared_wav_duration_secs(clip_path)?;
+        let outcome = transcribe_prepared_wav(
+            whisper_cli_path,
+            model_path,
+            clip_path,
+            audio_seconds,
+            options,
+        )?;
+        return Ok(format!(
+            "Transcribed the {audio_seconds:.1}s reference clip in {:.1}s: \"{}\"",
+            started.elapsed().as_secs_f32(),
+            outcome.transcript
         ));
     }
 
-    Ok(())
+    let wav_path =
+        std::env::temp_dir().join(format!("{}-self-test.wav", temp_artifact_base_name()));
+    write_wav(
+        &wav_path,
+        &vec![0.0; WHISPER_SAMPLE_RATE as usize],
+        1,
+        WHISPER_SAMPLE_RATE,
+    )?;
+    let result = run_decode_pass(
+        whisper_cli_path,
+        model_path,
+        &wav_path,
+        1.0,
+        options,
+        first_decode_pass(options, model_path).0,
+    );
+    let _ = fs::remove_file(&wav_path);
+    result.map(|_| {
+        format!(
+            "Decoded a generated 1.0s silent clip in {:.1}s. Run a model benchmark once to cache the speech reference clip for a fuller check.",
+            started.elapsed().as_secs_f32()
+        )
+    })
 }
 
-fn downmix_samples<T, F>(data: &[T], channels: usize, to_f32: F) -> Vec<f32>
-where
-    T: Copy,
-    F: Fn(T) -> f32,
-{
-    if channels == 0 || data.is_empty() {
-        return Vec::new();
+#[tauri::command]
+async fn run_self_test(app: tauri::AppHandle) -> Result<SelfTestReport, DictationError> {
+    if dictation_is_running(&app)? {
+        return Err("Stop dictation before running the self-test."
+            .to_string()
+            .into());
     }
 
-    let mut mono = Vec::with_capacity(data.len() / channels.max(1));
-    for frame in data.chunks(channels) {
-        let sum: f32 = frame.iter().map(|sample| to_f32(*sample)).sum();
-        mono.push(sum / frame.len() as f32);
-    }
+    let (whisper_cli_path, model_path, options, reference_clip) = {
+        let config = app.state::<AppConfig>();
+        let model_state = app.state::<LocalModelState>();
+        let options = {
+            let settings = model_state
+                .settings
+                .lock()
+                .map_err(|_| "Failed to lock local model settings".to_string())?;
+            let mut options = transcription_options(&settings, &model_state.recordings_dir());
+            options.whisper.capability_cache = config.whisper_cli_capabilities.clone();
+            options.recordings = None;
+            options.profanity_words = Vec::new();
+            options.dedup_max_repeats = None;
+            options.filler_words = Vec::new();
+            options.filler_trailing_phrases = Vec::new();
+            options.spoken_commands = Vec::new();
+            options.min_confidence = None;
+            options.whisper.token_confidence = false;
+            options
+        };
+        (
+            active_whisper_cli_path(config.inner(), model_state.inner()),
+            resolve_active_model_path(config.inner(), model_state.inner()),
+            options,
+            app_resource_dir(&app)
+                .and_then(|dir| bundled_benchmark_clip(&dir))
+                .ok(),
+        )
+    };
 
-    mono
+    tauri::async_runtime::spawn_blocking(move || {
+        let cli_result = whisper_cli_path.and_then(|path| {
+            ensure_whisper_cli_available(&path)?;
+            Ok(path)
+        });
+        let model_result = model_path.and_then(|path| {
+            check_model_file_readable(&path)?;
+            Ok(path)
+        });
+        let mut stages = vec![
+            self_test_stage_from_result(
+                "whisper-cli",
+                cli_result
+                    .as_ref()
+                    .map(|path| format!("whisper-cli is available at {path}."))
+                    .map_err(Clone::clone),
+            ),
+            self_test_stage_from_result(
+                "model",
+                model_result
+                    .as_ref()
+                    .map(|path| format!("Model file {} is readable.", path.display()))
+                    .map_err(|error| error.message.clone()),
+            ),
+            self_test_stage_from_result(
+                "microphone",
+                ensure_microphone_access_authorized(&app)
+                    .map_err(String::from)
+                    .and_then(|_| {
+                        probe_default_input_stream(&saved_audio_host(
+                            &app.state::<LocalModelState>(),
+                        ))
+                    })
+                    .map(|device| format!("Opened and closed an input stream on {device}.")),
+            ),
+        ];
+
+        stages.push(match (cli_result, model_result) {
+            (Ok(whisper_cli_path), Ok(model_path)) => self_test_stage_from_result(
+                "transcription",
+                self_test_transcription(
+                    &whisper_cli_path,
+                    &model_path,
+                    reference_clip.as_deref(),
+                    &options,
+                ),
+            ),
+            _ => self_test_stage(
+                "transcription",
+                SelfTestStatus::Skip,
+                "Skipped until whisper-cli and the model pass.",
+            ),
+        });
+
+        self_test_report(stages)
+    })
+    .await
+    .map_err(|e| format!("Failed to run self-test task: {e}").into())
 }
 
-fn store_captured_samples(target: &Arc<Mutex<Vec<f32>>>, samples: &[f32]) {
-    if samples.is_empty() {
-        return;
-    }
+// Async so the start cue, which plays before the microphone opens, does not block the main thread.
+#[tauri::command]
+async fn start_native_dictation(
+    app: tauri::AppHandle,
+    trailing_punctuation: Option<String>,
+    scratch: Option<bool>,
+) -> Result<(), DictationError> {
+    let trailing_punctuation = trailing_punctuation
+        .as_deref()
+        .map(|value| {
+            TrailingPunctuation::parse(value)
+                .ok_or_else(|| unsupported_trailing_punctuation_error(value))
+        })
+        .transpose()?;
+    let options = DictationSessionOptions {
+        trailing_punctuation,
+        scratch: scratch.unwrap_or(false),
+    };
+    tauri::async_runtime::spawn_blocking(move || start_native_dictation_inner(&app, options))
+        .await
+        .map_err(|e| format!("Failed to run dictation start task: {e}"))?
+        .map(|_| ())
+}
 
-    if let Ok(mut guard) = target.lock() {
-        guard.extend_from_slice(samples);
-    }
+#[tauri::command]
+async fn stop_native_dictation(app: tauri::AppHandle) -> Result<String, DictationError> {
+    stop_native_dictation_inner(app).await
 }
 
-fn audio_level_from_stats(stats: AudioSignalStats) -> f32 {
-    let peak = (stats.peak_abs / 0.18).clamp(0.0, 1.0);
-    let rms = (stats.rms / 0.06).clamp(0.0, 1.0);
-    ((peak * 0.68) + (rms * 0.32)).clamp(0.0, 1.0)
+#[tauri::command]
+fn cancel_native_dictation(app: tauri::AppHandle) -> Result<(), DictationError> {
+    cancel_native_dictation_inner(&app).map_err(DictationError::from)
 }
 
-fn waveform_bins_from_samples(samples: &[f32], count: usize) -> Vec<f32> {
-    if count == 0 {
-        return Vec::new();
-    }
-    if samples.is_empty() {
-        return vec![0.0; count];
-    }
+#[tauri::command]
+fn pause_native_dictation(app: tauri::AppHandle) -> Result<(), DictationError> {
+    pause_native_dictation_inner(&app)
+}
 
-    let chunk_len = (samples.len() / count).max(1);
-    let mut bins = Vec::with_capacity(count);
-    for index in 0..count {
-        let start = index * chunk_len;
-        let end = ((index + 1) * chunk_len).min(samples.len());
-        let slice = if start < samples.len() {
-            &samples[start..end.max(start + 1).min(samples.len())]
-        } else {
-            &samples[samples.len().saturating_sub(1)..]
-        };
-        let peak = slice
-            .iter()
-            .map(|sample| sample.abs())
-            .fold(0.0_f32, f32::max);
-        let normalized = (peak / 0.18).sqrt().clamp(0.0, 1.0);
-        bins.push(normalized);
+#[tauri::command]
+async fn resume_native_dictation(app: tauri::AppHandle) -> Result<(), DictationError> {
+    tauri::async_runtime::spawn_blocking(move || resume_native_dictation_inner(&app))
+        .await
+        .map_err(|e| format!("Failed to run dictation resume task: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capture_preview_payload;
+    use super::collapse_repeated_ngrams;
+    use super::delete_catalog_model_files;
+    use super::fill_playback_buffer;
+    use super::parse_whisper_progress_line;
+    use super::parse_whisper_timings;
+    use super::AudioInfoPayload;
+    use super::HoldCancelDetector;
+    use super::SimdSupport;
+    use super::WhisperOutputFormat;
+    use super::MAX_DICTATION_TRIGGER_LENGTH;
+    use super::{add_usage, transcript_word_count, usage_stats_payload, UsageTotals};
+    use super::{
+        analyze_audio_signal, audio_signal_is_too_quiet, default_dictation_trigger,
+        effective_whisper_timeout, focused_field_insert_enabled, normalize_audio_gain,
+        normalize_dictation_trigger, onboarding_runtime_details, preferred_whisper_cli_names,
+        quiet_audio_error, resample_linear, resolve_acceleration_args,
+        resolve_effective_dictation_trigger, runtime_details_for_trigger,
+        wait_for_non_silent_input, whisper_acceleration, whisper_help_text_capabilities,
+        whisper_help_text_looks_valid, whisper_timeout_seconds, HotkeyDeliveryMode, LocalSettings,
+        WhisperAcceleration, WhisperCliCapabilities,
+    };
+    use super::{
+        apply_edge_fade, frame_zero_crossing_rate, trim_silence_edges, EDGE_FADE_MS,
+        SILENCE_TRIM_PAD_MS, VAD_VOICED_MAX_ZCR,
+    };
+    use super::{apply_insert_wrap, insert_wrap, InsertWrap};
+    use super::{
+        apply_noise_gate, DEFAULT_NOISE_GATE_THRESHOLD, NOISE_GATE_ATTENUATION, NOISE_GATE_HOLD_MS,
+    };
+    use super::{apply_output_suffix, output_suffix, OutputSuffix};
+    use super::{apply_spoken_commands, spoken_commands, validate_spoken_command_map};
+    use super::{apply_trailing_punctuation, TrailingPunctuation};
+    use super::{
+        archive_recording, channel_recording_path, store_channel_samples, write_wav,
+        ChannelCapture, ChannelRecording, RecordingArchiveOptions, WHISPER_SAMPLE_RATE,
+    };
+    use super::{audio_host, find_audio_host_id};
+    use super::{
+        audio_prep_options, normalization_target_rms, soft_limit_sample,
+        DEFAULT_TARGET_TRANSCRIPTION_AUDIO_RMS, RMS_LIMITER_THRESHOLD,
+    };
+    use super::{auto_format_options, auto_format_transcript, AutoFormatOptions};
+    use super::{
+        benchmark_result, benchmark_word_accuracy, bundled_benchmark_clip,
+        BENCHMARK_CLIP_FILE_NAME, BENCHMARK_EXPECTED_TEXT, BENCHMARK_MATCH_ACCURACY,
+    };
+    use super::{build_device_profile, write_debug_report, DebugReport, DEBUG_REPORT_FILE};
+    use super::{
+        build_model_options, closest_catalog_spec_by_size, imported_model_path,
+        new_imported_model_id, validate_imported_model_name, ImportedModel,
+    };
+    use super::{candidate_whisper_cli_paths, normalize_extra_whisper_cli_paths};
+    use super::{canonicalize_trigger_key, canonicalize_trigger_modifier, supported_trigger_keys};
+    use super::{
+        catalog_spec_for_model_path, decode_overrides, decode_overrides_payload,
+        primary_decode_profile, DecodeOverrides, WhisperDecodeProfile, FAST_DECODE_PROFILE,
+    };
+    use super::{
+        check_memory_guard, memory_guard_from_settings, model_memory_requirement_bytes,
+        MemoryGuardMode, DEFAULT_MEMORY_GUARD_MARGIN_MB, MODEL_RUNTIME_OVERHEAD_MB,
+    };
+    use super::{
+        check_model_file_readable, self_test_report, self_test_stage, self_test_stage_from_result,
+        SelfTestStatus,
+    };
+    use super::{clamp_window_geometry, MonitorRect, WindowGeometry};
+    use super::{cleanup_temp_artifacts, process_is_alive, SystemTime, TEMP_ARTIFACT_STALE_AGE};
+    use super::{clipping_fraction, clipping_warning};
+    use super::{configured_whisper_threads, effective_whisper_threads};
+    use super::{copy_dir_recursive, move_storage_dir, rebase_path};
+    use super::{decode_audio_file, ffmpeg_version_looks_valid};
+    use super::{diagnose_whisper_cli_candidate, whisper_cli_search_order};
+    use super::{dictation_state_shows_recording_pill, pill_visibility, PillVisibility};
+    use super::{
+        download_error_http_status, download_failure_is_retryable, model_download_retry_delay,
+        powershell_download_script, MODEL_DOWNLOAD_RETRY_BASE_DELAY,
+    };
+    use super::{downmix_samples, DownmixMode};
+    use super::{ensure_whisper_cli_available, whisper_cli_is_placeholder};
+    use super::{estimated_real_time_factor, keeps_up_with_speech};
+    use super::{export_settings_json, imported_model_missing, prepare_imported_settings};
+    use super::{extra_whisper_args, validate_extra_whisper_args};
+    use super::{find_whisper_model_spec, model_size_is_suspect};
+    use super::{first_decode_pass, DecodePath, ACCURATE_DECODE_PROFILE};
+    use super::{hotkey_activation_from_settings, DoubleTapDetector, HotkeyActivationMode};
+    use super::{input_config_for_rate, preferred_sample_rate, SampleFormat};
+    use super::{input_device_entry, loopback_unavailable_message, LOOPBACK_DEVICE_PREFIX};
+    use super::{insert_delay_ms, DEFAULT_INSERT_DELAY_MS};
+    use super::{join_chunk_transcripts, silence_chunk_bounds, AudioChunk};
+    use super::{
+        known_model_download_mirrors, model_download_sources, model_download_url,
+        validate_model_download_mirror, validate_model_repo, validate_model_revision,
+    };
+    use super::{last_recording_retention_seconds, take_cached_capture_samples, CachedCapture};
+    use super::{
+        list_recordings, parse_recording_file_name, prune_recordings, recording_file_name,
+        recording_model_id, resolve_saved_recording_path,
+    };
+    use super::{
+        load_local_settings, migrate_settings, settings_backup_path, SETTINGS_SCHEMA_VERSION,
+    };
+    use super::{
+        low_confidence_retry_options, transcript_information_score,
+        transcript_looks_low_confidence, LowConfidenceRetryOptions,
+    };
+    use super::{
+        mask_profanity, profanity_words, transcription_options, validate_profanity_words,
+        DEFAULT_PROFANITY_WORDS,
+    };
+    use super::{min_transcribe_seconds, too_short_message, DEFAULT_MIN_TRANSCRIBE_SECONDS};
+    use super::{model_onboarding_reason, OnboardingReason};
+    use super::{
+        model_path_for_spec, partial_download_path, reconcile_selected_model,
+        remove_partial_downloads,
+    };
+    use super::{
+        model_recommendation_for_ram, pick_recommended_model_id, RecommendationBias,
+        SPEED_BIAS_MAX_RELATIVE_COMPUTE,
+    };
+    use super::{model_runnability, parse_df_available_kib, CheckStatus};
+    use super::{
+        normalize_audio_level, AudioNormalizationMode, AudioPrepOptions,
+        TARGET_TRANSCRIPTION_AUDIO_PEAK,
+    };
+    use super::{
+        normalize_transcript_text, transcript_artifact_tokens, validate_transcript_artifact_tokens,
+    };
+    use super::{
+        oom_fallback_hint, oom_fallback_warning, pick_oom_fallback_model, whisper_error_is_oom,
+    };
+    use super::{parse_utterance_command, UtteranceCommand};
+    use super::{
+        pill_layout_from_settings, pill_window_origin, PillLayout, PillPosition,
+        DEFAULT_PILL_WINDOW_MARGIN,
+    };
+    use super::{prepared_wav_duration_secs, saved_recording_wav, RecordingFormat};
+    use super::{probe_whisper_cli_path, resolve_whisper_cli_path};
+    use super::{recording_state_name, ActiveRecording, CaptureThread, OpenedInput};
+    use super::{
+        render_sound_cue, sound_cue_enabled, sound_cue_for_transition, SoundCue, SOUND_CUE_LEVEL,
+    };
+    use super::{slow_processing_threshold, DICTATION_SLOW_AFTER};
+    use super::{
+        strip_fillers, validate_filler_entries, DEFAULT_FILLER_TRAILING_PHRASES,
+        DEFAULT_FILLER_WORDS,
+    };
+    use super::{transcript_confidence, whisper_json_token_confidence};
+    use super::{DictationSessionOptions, DictationStatePayload};
+    use super::{PermissionKind, PermissionState};
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    #[cfg(target_os = "macos")]
+    use super::should_focus_main_window_for_microphone_prompt;
+    #[cfg(unix)]
+    use super::{run_command_with_timeout, CommandWaitError};
+    #[cfg(target_os = "macos")]
+    use objc2_av_foundation::AVAuthorizationStatus;
+
+    #[test]
+    fn resample_linear_returns_same_when_rate_matches() {
+        let source = vec![0.0_f32, 0.5, -0.5, 1.0];
+        let out = resample_linear(&source, 16_000, 16_000);
+        assert_eq!(out, source);
     }
 
-    bins
-}
+    #[test]
+    fn resample_linear_produces_output_when_rate_changes() {
+        let source = vec![0.0_f32, 1.0, 0.0, -1.0];
+        let out = resample_linear(&source, 8_000, 16_000);
+        assert!(out.len() > source.len());
+        assert!(out.iter().all(|sample| sample.is_finite()));
+    }
 
-impl LiveAudioMeter {
-    fn emit_samples(&self, samples: &[f32], sample_rate: u32) {
-        if samples.is_empty() || sample_rate == 0 {
-            return;
-        }
+    #[test]
+    fn analyze_audio_signal_reports_peak_rms_and_duration() {
+        let samples = vec![0.0_f32, 0.25, -0.5, 0.5];
+        let stats = analyze_audio_signal(&samples, 8_000);
+        assert!((stats.peak_abs - 0.5).abs() < 0.0001);
+        assert!(stats.rms > 0.0);
+        assert!(stats.duration_secs > 0.0);
+    }
 
-        let now = Instant::now();
-        let should_emit = if let Ok(mut guard) = self.last_emitted_at.lock() {
-            match *guard {
-                Some(last)
-                    if now.duration_since(last)
-                        < Duration::from_millis(LIVE_AUDIO_EMIT_INTERVAL_MS) =>
-                {
-                    false
-                }
-                _ => {
-                    *guard = Some(now);
-                    true
-                }
-            }
-        } else {
-            false
-        };
+    fn white_noise(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
 
-        if !should_emit {
-            return;
-        }
+    #[test]
+    fn trim_silence_edges_keeps_tone_and_pads_edges() {
+        let rate = 16_000_u32;
+        let tone = (0..rate)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / rate as f32).sin() * 0.3)
+            .collect::<Vec<_>>();
+        let mut samples = vec![0.0_f32; rate as usize];
+        samples.extend(&tone);
+        samples.extend(vec![0.0_f32; rate as usize]);
+
+        let trimmed = trim_silence_edges(samples, rate);
+        let pad = (rate * SILENCE_TRIM_PAD_MS / 1_000) as usize;
+        assert_eq!(trimmed.len(), tone.len() + 2 * pad);
+        assert!(trim_silence_edges(tone.clone(), rate).len() == tone.len());
+    }
 
-        let stats = analyze_audio_signal(samples, sample_rate);
-        let payload = DictationAudioLevelPayload {
-            session_id: self.session_id,
-            peak_abs: stats.peak_abs,
-            rms: stats.rms,
-            level: audio_level_from_stats(stats),
-            bars: waveform_bins_from_samples(samples, LIVE_AUDIO_BAR_COUNT),
+    #[test]
+    fn silence_chunk_bounds_cut_inside_pauses() {
+        let rate = 1_000_u32;
+        let tone = |seconds: usize| {
+            (0..seconds * rate as usize)
+                .map(|i| (i as f32 * 100.0 * std::f32::consts::TAU / rate as f32).sin() * 0.3)
+                .collect::<Vec<_>>()
         };
-        self.app.emit(DICTATION_AUDIO_LEVEL_EVENT, payload).ok();
+        let silence = |seconds: usize| vec![0.0_f32; seconds * rate as usize];
+        let mut samples = tone(20);
+        samples.extend(silence(2));
+        samples.extend(tone(20));
+        samples.extend(silence(1));
+        samples.extend(tone(10));
+
+        let bounds = silence_chunk_bounds(&samples, rate, 30);
+        assert_eq!(
+            bounds
+                .iter()
+                .map(|chunk| chunk.range.clone())
+                .collect::<Vec<_>>(),
+            vec![0..21_000, 21_000..42_500, 42_500..53_000]
+        );
+        assert_eq!(
+            bounds
+                .iter()
+                .map(|chunk| chunk.cut_in_pause)
+                .collect::<Vec<_>>(),
+            vec![false, true, true]
+        );
+        assert_eq!(
+            silence_chunk_bounds(&samples, rate, 60),
+            vec![AudioChunk {
+                range: 0..53_000,
+                cut_in_pause: false,
+            }]
+        );
+
+        // Without a pause the cut still lands inside the back half of the chunk.
+        let continuous = tone(45);
+        let bounds = silence_chunk_bounds(&continuous, rate, 30);
+        assert_eq!(bounds.len(), 2);
+        assert!((15_000..=30_000).contains(&bounds[0].range.end));
+        assert_eq!(bounds[1].range.end, 45_000);
+        assert!(!bounds[1].cut_in_pause);
     }
-}
 
-fn handle_input_chunk<T, F>(
-    data: &[T],
-    channels: usize,
-    target: &Arc<Mutex<Vec<f32>>>,
-    sample_rate: u32,
-    meter: &LiveAudioMeter,
-    to_f32: F,
-) where
-    T: Copy,
-    F: Fn(T) -> f32,
-{
-    let mono = downmix_samples(data, channels, to_f32);
-    if mono.is_empty() {
-        return;
+    #[test]
+    fn chunk_transcripts_join_without_duplicated_boundary_words() {
+        let parts = [
+            ("We shipped the".to_string(), false),
+            ("The release on Friday.".to_string(), false),
+            ("".to_string(), false),
+            ("Then we rested.".to_string(), false),
+        ];
+        assert_eq!(
+            join_chunk_transcripts(&parts),
+            "We shipped the release on Friday. Then we rested."
+        );
+        assert_eq!(
+            join_chunk_transcripts(&[("one".to_string(), false), ("one".to_string(), false)]),
+            "one"
+        );
     }
 
-    store_captured_samples(target, &mono);
-    meter.emit_samples(&mono, sample_rate);
-}
+    #[test]
+    fn chunk_transcripts_keep_real_repeats_across_a_pause() {
+        let parts = [
+            ("I knew that".to_string(), false),
+            ("That is why we had".to_string(), true),
+            ("had to leave.".to_string(), true),
+        ];
+        assert_eq!(
+            join_chunk_transcripts(&parts),
+            "I knew that That is why we had had to leave."
+        );
+    }
 
-fn sample_format_rank(sample_format: SampleFormat) -> u8 {
-    match sample_format {
-        SampleFormat::F32 => 3,
-        SampleFormat::I16 => 2,
-        SampleFormat::U16 => 1,
-        _ => 0,
+    #[test]
+    fn edge_fade_ramps_both_ends_toward_zero() {
+        let rate = 16_000;
+        let faded = apply_edge_fade(vec![0.5; rate as usize], rate);
+        let fade_len = (rate * EDGE_FADE_MS / 1_000) as usize;
+
+        assert_eq!(faded[0], 0.0);
+        assert_eq!(faded[faded.len() - 1], 0.0);
+        assert!(faded[1] < faded[fade_len / 2] && faded[fade_len / 2] < faded[fade_len - 1]);
+        let tail = faded.len() - 1;
+        assert!(faded[tail - 1] < faded[tail - fade_len / 2]);
+        assert_eq!(faded[fade_len], 0.5);
+        assert_eq!(faded[tail - fade_len], 0.5);
+
+        let short = apply_edge_fade(vec![1.0; 6], rate);
+        assert_eq!(
+            short,
+            vec![0.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, 1.0 / 3.0, 0.0]
+        );
     }
-}
 
-fn choose_input_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig, String> {
-    if let Ok(default_config) = device.default_input_config() {
-        return Ok(default_config);
+    #[test]
+    fn noise_gate_attenuates_gaps_and_clicks_but_keeps_speech() {
+        let rate = 16_000_u32;
+        let tone = |len: usize| -> Vec<f32> {
+            (0..len)
+                .map(|i| (i as f32 * 220.0 * std::f32::consts::TAU / rate as f32).sin() * 0.3)
+                .collect()
+        };
+        let word = rate as usize * 3 / 10;
+        let mut gap = white_noise(word, 0.004);
+        // A 10ms keyboard click in the middle of the pause.
+        let click_at = word / 2;
+        gap[click_at..click_at + 160].fill(0.5);
+        let mut samples = tone(word);
+        samples.extend(gap);
+        samples.extend(tone(word));
+
+        let gated = apply_noise_gate(samples.clone(), rate, DEFAULT_NOISE_GATE_THRESHOLD);
+        assert_eq!(gated.len(), samples.len());
+        let speech = 1_000..word - 2_000;
+        assert_eq!(gated[speech.clone()], samples[speech]);
+        let hold = (rate * NOISE_GATE_HOLD_MS / 1_000) as usize;
+        let pause = word + hold + 320..2 * word - hold - 320;
+        let rms = |slice: &[f32]| analyze_audio_signal(slice, rate).rms;
+        assert!(
+            rms(&gated[pause.clone()])
+                <= rms(&samples[pause.clone()]) * NOISE_GATE_ATTENUATION + 1e-6
+        );
+        let click = word + click_at..word + click_at + 160;
+        assert!(gated[click]
+            .iter()
+            .all(|sample| sample.abs() <= 0.5 * NOISE_GATE_ATTENUATION + 1e-6));
+
+        let settings = LocalSettings {
+            noise_gate: Some(true),
+            noise_gate_threshold: Some(5.0),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            audio_prep_options(&settings).noise_gate,
+            Some(DEFAULT_NOISE_GATE_THRESHOLD)
+        );
+        assert_eq!(
+            audio_prep_options(&LocalSettings::default()).noise_gate,
+            None
+        );
     }
 
-    let mut best: Option<(u8, u32, cpal::SupportedStreamConfig)> = None;
-    let ranges = device
-        .supported_input_configs()
-        .map_err(|e| format!("Failed to query supported input configs: {e}"))?;
+    #[test]
+    fn trim_silence_edges_drops_white_noise_only_capture() {
+        let noise = white_noise(32_000, 0.2);
+        assert!(frame_zero_crossing_rate(&noise) > VAD_VOICED_MAX_ZCR);
+        assert!(trim_silence_edges(noise, 16_000).is_empty());
+    }
 
-    for range in ranges {
-        let candidate = range.with_max_sample_rate();
-        let format_rank = sample_format_rank(candidate.sample_format());
-        if format_rank == 0 {
-            continue;
-        }
-        let candidate_rate = candidate.sample_rate().0;
+    #[test]
+    fn quiet_audio_detection_flags_near_silent_capture() {
+        let samples = vec![0.0002_f32; 16_000];
+        let stats = analyze_audio_signal(&samples, 16_000);
+        assert!(audio_signal_is_too_quiet(stats));
+        assert!(quiet_audio_error(stats, "MacBook Pro Microphone").contains("too quiet"));
+    }
 
-        let replace = match &best {
-            Some((best_rank, best_rate, _)) => {
-                format_rank > *best_rank
-                    || (format_rank == *best_rank && candidate_rate > *best_rate)
-            }
-            None => true,
+    #[test]
+    fn capture_preview_reports_trim_and_speech_gate() {
+        let rate = 16_000_u32;
+        let input = OpenedInput {
+            sample_rate: rate,
+            channels: 1,
+            device_name: "Test Mic".to_string(),
         };
-        if replace {
-            best = Some((format_rank, candidate_rate, candidate));
-        }
+        let mut samples = vec![0.0_f32; rate as usize];
+        samples.extend(
+            (0..rate).map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / rate as f32).sin() * 0.3),
+        );
+        samples.extend(vec![0.0_f32; rate as usize]);
+
+        let trimmed = audio_prep_options(&LocalSettings::default());
+        let preview = capture_preview_payload(samples.clone(), &input, trimmed);
+        assert!(preview.passes_speech_gate);
+        assert_eq!(preview.rejection, None);
+        assert!(!preview.resampled);
+        assert!((preview.captured_seconds - 3.0).abs() < 0.001);
+        let pad_seconds = 2.0 * SILENCE_TRIM_PAD_MS as f32 / 1_000.0;
+        assert!((preview.prepared_seconds - (1.0 + pad_seconds)).abs() < 0.001);
+        assert!((preview.trimmed_seconds - (2.0 - pad_seconds)).abs() < 0.001);
+        assert!((preview.peak - 0.3).abs() < 0.01);
+
+        let untrimmed = audio_prep_options(&LocalSettings {
+            trim_silence: Some(false),
+            ..LocalSettings::default()
+        });
+        let untrimmed = capture_preview_payload(samples, &input, untrimmed);
+        assert_eq!(untrimmed.trimmed_seconds, 0.0);
+        assert!((untrimmed.prepared_seconds - 3.0).abs() < 0.001);
+
+        let quiet = capture_preview_payload(vec![0.0002_f32; rate as usize], &input, trimmed);
+        assert!(!quiet.passes_speech_gate);
+        assert!(quiet.rejection.unwrap().contains("too quiet"));
+        assert_eq!(quiet.prepared_seconds, 0.0);
     }
 
-    best.map(|(_, _, config)| config).ok_or_else(|| {
-        "No compatible microphone input config found. Try a different input device.".to_string()
-    })
-}
+    #[test]
+    fn normalize_audio_gain_boosts_quiet_but_valid_audio() {
+        let samples = vec![0.01_f32, -0.02, 0.03, -0.04];
+        let stats = analyze_audio_signal(&samples, 16_000);
+        assert!(!audio_signal_is_too_quiet(stats));
+        let boosted = normalize_audio_gain(samples.clone(), stats, TARGET_TRANSCRIPTION_AUDIO_PEAK);
+        let boosted_peak = boosted
+            .iter()
+            .map(|sample| sample.abs())
+            .fold(0.0_f32, f32::max);
+        let original_peak = samples
+            .iter()
+            .map(|sample| sample.abs())
+            .fold(0.0_f32, f32::max);
+        assert!(boosted_peak > original_peak);
+        assert!(boosted_peak <= 0.85);
+    }
 
-fn device_name(device: &cpal::Device, fallback: &str) -> String {
-    device.name().unwrap_or_else(|_| fallback.to_string())
-}
+    #[test]
+    fn rms_normalization_brings_varying_levels_to_consistent_rms() {
+        let options = AudioPrepOptions {
+            normalization_mode: AudioNormalizationMode::Rms,
+            normalization_target_level: 0.1,
+            trim_silence: true,
+            noise_gate: None,
+        };
+        for amplitude in [0.02_f32, 0.1, 0.4] {
+            let samples: Vec<f32> = (0..1_600)
+                .map(|index| (index as f32 * 0.05).sin() * amplitude)
+                .collect();
+            let stats = analyze_audio_signal(&samples, 16_000);
+            let normalized = normalize_audio_level(samples, stats, options);
+            let normalized_rms = analyze_audio_signal(&normalized, 16_000).rms;
+            assert!(
+                (normalized_rms - 0.1).abs() < 0.005,
+                "amplitude {amplitude} normalized to rms {normalized_rms}"
+            );
+        }
+    }
 
-fn list_input_devices() -> Vec<DictationInputDevice> {
-    let host = cpal::default_host();
-    let default_name = host
-        .default_input_device()
-        .map(|device| device_name(&device, "default input"));
-    let mut devices = Vec::<DictationInputDevice>::new();
-    let mut seen_names = HashSet::<String>::new();
+    #[test]
+    fn normalization_off_leaves_samples_untouched() {
+        let samples = vec![0.01_f32, -0.02, 0.03, -0.04];
+        let stats = analyze_audio_signal(&samples, 16_000);
+        let options = AudioPrepOptions {
+            normalization_mode: AudioNormalizationMode::Off,
+            normalization_target_level: 0.5,
+            trim_silence: true,
+            noise_gate: None,
+        };
+        assert_eq!(
+            normalize_audio_level(samples.clone(), stats, options),
+            samples
+        );
+    }
 
-    if let Some(name) = default_name.clone() {
-        seen_names.insert(name.clone());
-        devices.push(DictationInputDevice {
-            is_default: true,
-            name,
-        });
+    #[test]
+    fn trailing_period_mode_terminates_unpunctuated_text() {
+        assert_eq!(
+            apply_trailing_punctuation("send the report".into(), TrailingPunctuation::Period),
+            "send the report."
+        );
+        assert_eq!(
+            apply_trailing_punctuation("send the report,".into(), TrailingPunctuation::Period),
+            "send the report."
+        );
+        assert_eq!(
+            apply_trailing_punctuation("is it done?".into(), TrailingPunctuation::Period),
+            "is it done?"
+        );
     }
 
-    if let Ok(inputs) = host.input_devices() {
-        for device in inputs {
-            let name = device_name(&device, "unknown input");
-            if !seen_names.insert(name.clone()) {
-                continue;
-            }
-            devices.push(DictationInputDevice {
-                is_default: default_name.as_deref() == Some(name.as_str()),
-                name,
-            });
-        }
+    #[test]
+    fn trailing_none_mode_drops_only_a_lone_final_period() {
+        assert_eq!(
+            apply_trailing_punctuation("on my way.".into(), TrailingPunctuation::None),
+            "on my way"
+        );
+        assert_eq!(
+            apply_trailing_punctuation("wait...".into(), TrailingPunctuation::None),
+            "wait..."
+        );
+        assert_eq!(
+            apply_trailing_punctuation("really?".into(), TrailingPunctuation::None),
+            "really?"
+        );
+        assert_eq!(
+            apply_trailing_punctuation("on my way.".into(), TrailingPunctuation::Keep),
+            "on my way."
+        );
     }
 
-    devices
-}
+    #[test]
+    fn trailing_punctuation_accepts_profile_shorthands() {
+        assert_eq!(
+            TrailingPunctuation::parse("chat"),
+            Some(TrailingPunctuation::None)
+        );
+        assert_eq!(
+            TrailingPunctuation::parse(" Docs "),
+            Some(TrailingPunctuation::Period)
+        );
+        assert_eq!(TrailingPunctuation::parse("semicolon"), None);
+    }
 
-fn create_input_stream_for_device(
-    device: &cpal::Device,
-    device_name: &str,
-    samples: Arc<Mutex<Vec<f32>>>,
-    meter: LiveAudioMeter,
-) -> Result<(Stream, u32), String> {
-    let supported_config = device
-        .default_input_config()
-        .or_else(|_| choose_input_config(device))
-        .map_err(|e| format!("Failed to resolve input config: {e}"))?;
-    let sample_rate = supported_config.sample_rate().0;
-    let channels = supported_config.channels() as usize;
-    let config: cpal::StreamConfig = supported_config.clone().into();
-    let probe_start_len = samples.lock().map(|guard| guard.len()).unwrap_or(0);
-    let err_fn = |err| {
-        eprintln!("microphone stream error: {err}");
-    };
+    #[test]
+    fn recording_file_names_round_trip_timestamp_and_model_id() {
+        let name = recording_file_name(1_700_000_000_123, "base-en");
+        assert_eq!(name, "1700000000123-base-en.wav");
+        assert_eq!(
+            parse_recording_file_name(&name),
+            Some((1_700_000_000_123, "base-en".to_string()))
+        );
+        assert_eq!(parse_recording_file_name("notes.txt"), None);
+        assert_eq!(parse_recording_file_name("abc-base.wav"), None);
+        assert_eq!(
+            parse_recording_file_name("1700000000123-base-en.flac"),
+            Some((1_700_000_000_123, "base-en".to_string()))
+        );
+    }
 
-    let stream = match supported_config.sample_format() {
-        SampleFormat::F32 => {
-            let sink = Arc::clone(&samples);
-            let live_meter = meter.clone();
-            device
-                .build_input_stream(
-                    &config,
-                    move |data: &[f32], _| {
-                        handle_input_chunk(data, channels, &sink, sample_rate, &live_meter, |v| v);
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| format!("Failed to open f32 input stream: {e}"))?
-        }
-        SampleFormat::I16 => {
-            let sink = Arc::clone(&samples);
-            let live_meter = meter.clone();
-            device
-                .build_input_stream(
-                    &config,
-                    move |data: &[i16], _| {
-                        handle_input_chunk(data, channels, &sink, sample_rate, &live_meter, |v| {
-                            v as f32 / i16::MAX as f32
-                        });
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| format!("Failed to open i16 input stream: {e}"))?
-        }
-        SampleFormat::U16 => {
-            let sink = Arc::clone(&samples);
-            let live_meter = meter.clone();
-            device
-                .build_input_stream(
-                    &config,
-                    move |data: &[u16], _| {
-                        handle_input_chunk(data, channels, &sink, sample_rate, &live_meter, |v| {
-                            (v as f32 / u16::MAX as f32) * 2.0 - 1.0
-                        });
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| format!("Failed to open u16 input stream: {e}"))?
+    #[test]
+    fn flac_recordings_archive_and_decode_back_to_whisper_wav() {
+        let root = std::env::temp_dir().join(format!("dicktaint-flac-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).expect("create test dir");
+        let archive = RecordingArchiveOptions {
+            dir: root.join("recordings"),
+            max_recordings: 5,
+            max_total_bytes: u64::MAX,
+            format: RecordingFormat::Flac,
+        };
+        let samples: Vec<f32> = (0..4_000).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let wav = root.join("clip.wav");
+        write_wav(&wav, &samples, 1, WHISPER_SAMPLE_RATE).expect("write clip");
+        let channels = ChannelRecording {
+            buffers: &[],
+            sample_rate: WHISPER_SAMPLE_RATE,
+        };
+        let target = archive_recording(&archive, &wav, &channels, Path::new("ggml-tiny.bin"), None)
+            .expect("archive recording");
+        assert_eq!(
+            RecordingFormat::from_path(&target),
+            Some(RecordingFormat::Flac)
+        );
+        assert_eq!(list_recordings(&archive.dir).expect("list").len(), 1);
+
+        let (decoded, rate) = decode_audio_file(&target).expect("decode flac");
+        assert_eq!(rate, WHISPER_SAMPLE_RATE);
+        assert_eq!(decoded.len(), samples.len());
+        assert!(decoded
+            .iter()
+            .zip(&samples)
+            .all(|(a, b)| (a - b).abs() < 0.001));
+
+        let (wav_path, temporary) = saved_recording_wav(&target).expect("temp wav");
+        assert!(temporary);
+        assert_eq!(
+            prepared_wav_duration_secs(&wav_path).expect("duration"),
+            0.25
+        );
+        let _ = std::fs::remove_file(&wav_path);
+        assert_eq!(RecordingFormat::parse(".OGG"), Some(RecordingFormat::Opus));
+        assert_eq!(RecordingFormat::parse("mp3"), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn recording_model_id_prefers_catalog_id() {
+        assert_eq!(
+            recording_model_id(Path::new("/models/ggml-base.en.bin")),
+            "base-en"
+        );
+        assert_eq!(
+            recording_model_id(Path::new("/custom/ggml-my model.bin")),
+            "my-model"
+        );
+    }
+
+    #[test]
+    fn prune_recordings_enforces_count_and_size_budgets() {
+        let dir = std::env::temp_dir().join(format!("dicktaint-prune-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create test dir");
+        for (timestamp, len) in [(1_u64, 100_usize), (2, 100), (3, 100), (4, 300)] {
+            let path = dir.join(recording_file_name(timestamp, "tiny"));
+            std::fs::write(&path, vec![0_u8; len]).expect("write recording");
+            std::fs::write(path.with_extension("txt"), "hello").expect("write transcript");
         }
-        sample_format => {
-            return Err(format!(
-                "Unsupported input sample format: {sample_format:?}. Try a different input device."
-            ));
+
+        assert_eq!(prune_recordings(&dir, 3, u64::MAX), Ok(1));
+        assert_eq!(prune_recordings(&dir, 3, 450), Ok(1));
+        let remaining: Vec<u64> = list_recordings(&dir)
+            .expect("list recordings")
+            .into_iter()
+            .map(|entry| entry.recorded_at_ms)
+            .collect();
+        assert_eq!(remaining, vec![4, 3]);
+        assert!(!dir
+            .join(recording_file_name(1, "tiny"))
+            .with_extension("txt")
+            .exists());
+
+        // The newest clip survives even when it alone exceeds the size budget.
+        assert_eq!(prune_recordings(&dir, 3, 10), Ok(1));
+        assert_eq!(list_recordings(&dir).expect("list recordings").len(), 1);
+
+        // An older clip small enough to fit is still removed once a newer one overflowed.
+        for (timestamp, len) in [(2_u64, 50_usize), (5, 300), (6, 100)] {
+            std::fs::write(
+                dir.join(recording_file_name(timestamp, "tiny")),
+                vec![0_u8; len],
+            )
+            .expect("write recording");
         }
-    };
+        assert_eq!(prune_recordings(&dir, 10, 450), Ok(2));
+        let remaining: Vec<u64> = list_recordings(&dir)
+            .expect("list recordings")
+            .into_iter()
+            .map(|entry| entry.recorded_at_ms)
+            .collect();
+        assert_eq!(remaining, vec![6, 5]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-    stream
-        .play()
-        .map_err(|e| format!("Failed to start microphone stream: {e}"))?;
+    #[test]
+    fn cleanup_temp_artifacts_removes_only_orphaned_app_files() {
+        let dir = std::env::temp_dir().join(format!("dicktaint-temp-sweep-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create test dir");
+        let own = std::process::id();
+        let names = [
+            "dicktaint-41-1700000000000.wav".to_string(),
+            "dicktaint-41-1700000000000-transcript.txt".to_string(),
+            "dicktaint-42-1700000000000.wav".to_string(),
+            format!("dicktaint-{own}-1700000000000.wav"),
+            "dicktaint-prune-test-41".to_string(),
+            "other-41-1700000000000.wav".to_string(),
+        ];
+        let old = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        for name in &names {
+            let file = std::fs::File::create(dir.join(name)).expect("create temp file");
+            file.set_modified(old).expect("age temp file");
+        }
+        std::fs::write(dir.join("dicktaint-41-1800000000000.wav"), "fresh").expect("write");
 
-    if let Err(error) =
-        wait_for_non_silent_input(&samples, probe_start_len, sample_rate, device_name)
-    {
-        if let Ok(mut guard) = samples.lock() {
-            guard.truncate(probe_start_len);
+        let cleanup = cleanup_temp_artifacts(&dir, TEMP_ARTIFACT_STALE_AGE, &|pid| Some(pid == 42))
+            .expect("sweep temp dir");
+        let mut removed: Vec<String> = cleanup
+            .removed
+            .iter()
+            .map(|path| {
+                Path::new(path)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        removed.sort();
+        assert_eq!(removed, vec![names[1].clone(), names[0].clone()]);
+        assert_eq!(cleanup.skipped_in_use, 2);
+        for kept in &names[2..] {
+            assert!(dir.join(kept).exists(), "{kept} should be kept");
         }
-        drop(stream);
-        return Err(error);
+        assert!(dir.join("dicktaint-41-1800000000000.wav").exists());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
-    Ok((stream, sample_rate))
-}
+    #[cfg(unix)]
+    #[test]
+    fn process_liveness_counts_running_processes_of_any_owner() {
+        assert_eq!(process_is_alive(std::process::id()), Some(true));
+        // init runs as root, so unprivileged test runs exercise the EPERM path.
+        assert_eq!(process_is_alive(1), Some(true));
+        assert_eq!(process_is_alive(0), None);
+    }
 
-fn wait_for_non_silent_input(
-    samples: &Arc<Mutex<Vec<f32>>>,
-    start_len: usize,
-    sample_rate: u32,
-    device_name: &str,
-) -> Result<(), String> {
-    if sample_rate == 0 {
-        return Ok(());
+    #[test]
+    fn store_channel_samples_keeps_up_to_max_channels() {
+        let capture = ChannelCapture {
+            max_channels: 2,
+            buffers: Arc::new(Mutex::new(Vec::new())),
+        };
+        store_channel_samples(&capture, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3, |v| v);
+        store_channel_samples(&capture, &[7.0, 8.0, 9.0], 3, |v| v);
+        assert_eq!(
+            *capture.buffers.lock().expect("lock buffers"),
+            vec![vec![1.0, 4.0, 7.0], vec![2.0, 5.0, 8.0]]
+        );
     }
 
-    let min_samples =
-        ((sample_rate as u64 * INPUT_STREAM_PROBE_MIN_DURATION_MS as u64) / 1000).max(32) as usize;
-    let deadline = Instant::now() + Duration::from_millis(INPUT_STREAM_PROBE_TIMEOUT_MS);
-    let mut saw_any_frames = false;
+    #[test]
+    fn archive_recording_writes_multichannel_companion() {
+        let root =
+            std::env::temp_dir().join(format!("dicktaint-channels-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).expect("create test dir");
+        let archive = RecordingArchiveOptions {
+            dir: root.join("recordings"),
+            max_recordings: 1,
+            max_total_bytes: u64::MAX,
+            format: RecordingFormat::Wav,
+        };
+        let buffers = vec![vec![0.5, 0.25], vec![-0.5]];
+        let channels = ChannelRecording {
+            buffers: &buffers,
+            sample_rate: 48_000,
+        };
 
-    loop {
-        let observed = if let Ok(guard) = samples.lock() {
-            if guard.len() <= start_len {
-                None
-            } else {
-                let captured = &guard[start_len..];
-                Some((
-                    captured.len(),
-                    analyze_audio_signal(captured, sample_rate).peak_abs,
-                ))
-            }
-        } else {
-            None
+        let mono = root.join("mono.wav");
+        write_wav(&mono, &[0.0, 0.0], 1, WHISPER_SAMPLE_RATE).expect("write mono");
+        let target =
+            archive_recording(&archive, &mono, &channels, Path::new("ggml-tiny.bin"), None)
+                .expect("archive recording");
+        let file_name = target
+            .file_name()
+            .expect("file name")
+            .to_string_lossy()
+            .to_string();
+        let companion = channel_recording_path(&archive.dir, &file_name);
+
+        let reader = hound::WavReader::open(&companion).expect("open companion");
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 48_000);
+        let samples: Vec<i16> = reader
+            .into_samples::<i16>()
+            .map(|sample| sample.expect("read sample"))
+            .collect();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[3], 0);
+        assert_eq!(
+            list_recordings(&archive.dir)
+                .expect("list recordings")
+                .len(),
+            1
+        );
+
+        // Pruning the mono clip takes its companion with it.
+        let next = root.join("next.wav");
+        write_wav(&next, &[0.0], 1, WHISPER_SAMPLE_RATE).expect("write mono");
+        std::thread::sleep(Duration::from_millis(2));
+        archive_recording(&archive, &next, &channels, Path::new("ggml-tiny.bin"), None)
+            .expect("archive recording");
+        assert!(!companion.exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn saved_recording_paths_must_stay_inside_recordings_dir() {
+        let root = std::env::temp_dir().join(format!(
+            "dicktaint-retranscribe-test-{}",
+            std::process::id()
+        ));
+        let recordings_dir = root.join("recordings");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&recordings_dir).expect("create test dir");
+        let inside = recordings_dir.join(recording_file_name(5, "tiny"));
+        let outside = root.join("elsewhere.wav");
+        std::fs::write(&inside, b"wav").expect("write inside");
+        std::fs::write(&outside, b"wav").expect("write outside");
+
+        let resolved = resolve_saved_recording_path(&recordings_dir, "0000000000005-tiny.wav")
+            .expect("relative name resolves");
+        assert_eq!(resolved, inside.canonicalize().expect("canonical inside"));
+        assert!(resolve_saved_recording_path(&recordings_dir, &outside.to_string_lossy()).is_err());
+        assert!(resolve_saved_recording_path(&recordings_dir, "../elsewhere.wav").is_err());
+        assert!(resolve_saved_recording_path(&recordings_dir, "missing.wav").is_err());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn information_score_penalizes_loops_and_sparse_output() {
+        let natural = transcript_information_score("please send the quarterly report today", 3.0);
+        let looped = transcript_information_score("you you you you you you", 3.0);
+        let sparse = transcript_information_score("okay", 6.0);
+        assert!(natural > 0.9, "natural speech scored {natural}");
+        assert!(looped < 0.2, "looped output scored {looped}");
+        assert!(sparse < 0.2, "sparse output scored {sparse}");
+        assert_eq!(transcript_information_score("", 3.0), 0.0);
+    }
+
+    #[test]
+    fn low_confidence_retry_skips_short_clips() {
+        let retry = LowConfidenceRetryOptions::default();
+        assert!(transcript_looks_low_confidence(
+            "you you you you",
+            4.0,
+            retry
+        ));
+        assert!(transcript_looks_low_confidence("", 4.0, retry));
+        assert!(!transcript_looks_low_confidence("yes", 0.8, retry));
+        assert!(!transcript_looks_low_confidence(
+            "please send the quarterly report today",
+            4.0,
+            retry
+        ));
+    }
+
+    #[test]
+    fn default_decode_is_one_pass_with_whisper_cli_profile() {
+        let dir = std::env::temp_dir();
+        let model = Path::new("ggml-tiny.en.bin");
+        let default = transcription_options(&LocalSettings::default(), &dir);
+        assert_eq!(
+            first_decode_pass(&default, model),
+            (ACCURATE_DECODE_PROFILE, DecodePath::Single)
+        );
+        let two_pass = transcription_options(
+            &LocalSettings {
+                two_pass_decode: Some(true),
+                ..LocalSettings::default()
+            },
+            &dir,
+        );
+        assert_eq!(first_decode_pass(&two_pass, model).1, DecodePath::Fast);
+        let tuned = transcription_options(
+            &LocalSettings {
+                beam_size: Some(2),
+                ..LocalSettings::default()
+            },
+            &dir,
+        );
+        assert_eq!(
+            first_decode_pass(&tuned, model),
+            (
+                WhisperDecodeProfile {
+                    beam_size: 2,
+                    best_of: 5,
+                },
+                DecodePath::Single
+            )
+        );
+    }
+
+    #[test]
+    fn low_confidence_retry_honors_toggle_and_threshold() {
+        let disabled = LowConfidenceRetryOptions {
+            enabled: false,
+            ..LowConfidenceRetryOptions::default()
         };
+        assert!(!transcript_looks_low_confidence(
+            "you you you you",
+            4.0,
+            disabled
+        ));
 
-        if let Some((captured_len, peak_abs)) = observed {
-            saw_any_frames = true;
-            if peak_abs > 0.0 {
-                return Ok(());
-            }
-            if captured_len >= min_samples {
-                return Err(format!(
-                    "Microphone '{}' opened but only produced silent audio frames. On macOS this usually means the selected input route is stale or muted. In Sound > Input, switch to another microphone and back, or choose System Default and retry.",
-                    device_name
-                ));
-            }
+        let sensitive = LowConfidenceRetryOptions {
+            enabled: true,
+            score_threshold: 0.95,
+        };
+        assert!(transcript_looks_low_confidence(
+            "please send the quarterly report today",
+            8.0,
+            sensitive
+        ));
+
+        let settings = LocalSettings {
+            low_confidence_threshold: Some(2.0),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            low_confidence_retry_options(&settings),
+            LowConfidenceRetryOptions::default()
+        );
+    }
+
+    #[test]
+    fn extra_whisper_args_reject_managed_flags() {
+        let args = vec![
+            " --entropy-thold ".to_string(),
+            "2.6".to_string(),
+            String::new(),
+            "--split-on-word".to_string(),
+        ];
+        assert_eq!(
+            validate_extra_whisper_args(&args),
+            Ok(vec![
+                "--entropy-thold".to_string(),
+                "2.6".to_string(),
+                "--split-on-word".to_string()
+            ])
+        );
+        for managed in [
+            "-m",
+            "--file",
+            "-of",
+            "-otxt",
+            "--output-file=/tmp/x",
+            "-oj",
+            "-l",
+            "--language=de",
+            "-t",
+            "--beam-size",
+            "-bo",
+            "-nt",
+            "--no-prints",
+            "-ng",
+        ] {
+            assert!(
+                validate_extra_whisper_args(&[managed.to_string()]).is_err(),
+                "{managed} should be rejected"
+            );
         }
+    }
 
-        if Instant::now() >= deadline {
-            if !saw_any_frames {
-                return Err(format!(
-                    "Microphone '{}' did not deliver any audio frames after opening. In macOS Settings > Privacy & Security > Microphone, allow this app and retry.",
-                    device_name
-                ));
+    #[test]
+    fn subtitle_formats_map_to_whisper_output_flags() {
+        assert_eq!(
+            WhisperOutputFormat::parse_subtitle(" SRT "),
+            Some(WhisperOutputFormat::Srt)
+        );
+        assert_eq!(
+            WhisperOutputFormat::parse_subtitle("webvtt"),
+            Some(WhisperOutputFormat::Vtt)
+        );
+        assert_eq!(WhisperOutputFormat::parse_subtitle("txt"), None);
+        assert_eq!(WhisperOutputFormat::Vtt.flag(), "-ovtt");
+        assert_eq!(WhisperOutputFormat::Srt.extension(), "srt");
+        assert_eq!(WhisperOutputFormat::default().flag(), "-otxt");
+    }
+
+    #[test]
+    fn decode_overrides_replace_model_default_profile_within_range() {
+        let none = DecodeOverrides::default();
+        let tiny = find_whisper_model_spec("tiny-en");
+        let large = find_whisper_model_spec("large-v3");
+        assert_eq!(primary_decode_profile(none, None), FAST_DECODE_PROFILE);
+        assert_eq!(
+            primary_decode_profile(none, large.as_ref()),
+            FAST_DECODE_PROFILE
+        );
+        assert_eq!(
+            primary_decode_profile(none, tiny.as_ref()),
+            WhisperDecodeProfile {
+                beam_size: 3,
+                best_of: 3,
             }
-            return Err(format!(
-                "Microphone '{}' opened but only produced silent audio frames. On macOS this usually means microphone access is blocked or the input route is stale. In Privacy & Security > Microphone, allow this app, then check Sound > Input and retry.",
-                device_name
-            ));
-        }
+        );
+        let tuned = LocalSettings {
+            beam_size: Some(8),
+            best_of: Some(9),
+            selected_model_id: Some("tiny-en".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            primary_decode_profile(decode_overrides(&tuned), tiny.as_ref()),
+            WhisperDecodeProfile {
+                beam_size: 8,
+                best_of: 3,
+            }
+        );
+        assert_eq!(
+            catalog_spec_for_model_path(Path::new("/models/ggml-tiny.en.bin")).map(|spec| spec.id),
+            Some("tiny-en")
+        );
+        let payload = decode_overrides_payload(&tuned);
+        assert_eq!(payload.best_of, None);
+        assert_eq!(payload.effective_beam_size, 8);
+        assert_eq!(payload.effective_best_of, 3);
+    }
 
-        thread::sleep(Duration::from_millis(INPUT_STREAM_PROBE_POLL_INTERVAL_MS));
+    #[test]
+    fn invalid_saved_extra_whisper_args_are_ignored() {
+        let settings = LocalSettings {
+            extra_whisper_args: Some(vec!["--max-context".into(), "0".into(), "-m".into()]),
+            ..LocalSettings::default()
+        };
+        assert!(extra_whisper_args(&settings).is_empty());
     }
-}
 
-#[cfg(target_os = "macos")]
-fn microphone_media_type() -> Result<&'static objc2_av_foundation::AVMediaType, String> {
-    unsafe {
-        AVMediaTypeAudio.ok_or_else(|| {
-            "AVFoundation did not expose AVMediaTypeAudio on this macOS build.".to_string()
-        })
+    #[test]
+    fn extra_whisper_cli_paths_are_probed_first() {
+        let extra = vec![
+            "/mnt/tools/whisper-cli".to_string(),
+            "/opt/custom/whisper-cli".to_string(),
+        ];
+        let candidates = candidate_whisper_cli_paths("whisper-cli", &extra);
+        assert_eq!(&candidates[..2], extra.as_slice());
+        assert!(candidates.iter().any(|path| path == "whisper-cli"));
+        assert_eq!(
+            candidates
+                .iter()
+                .filter(|path| path.as_str() == "/mnt/tools/whisper-cli")
+                .count(),
+            1
+        );
     }
-}
 
-#[cfg(target_os = "macos")]
-fn microphone_permission_denied_error() -> String {
-    "Microphone permission is denied for this app. In macOS Settings > Privacy & Security > Microphone, allow dicktaint and relaunch the app.".to_string()
-}
+    #[test]
+    fn extra_whisper_cli_paths_are_trimmed_and_deduped() {
+        let input = vec![
+            " /opt/a/whisper-cli ".to_string(),
+            String::new(),
+            "/opt/a/whisper-cli".to_string(),
+            "/opt/b/whisper-cli".to_string(),
+        ];
+        assert_eq!(
+            normalize_extra_whisper_cli_paths(&input),
+            Ok(vec![
+                "/opt/a/whisper-cli".to_string(),
+                "/opt/b/whisper-cli".to_string()
+            ])
+        );
+        let too_many: Vec<String> = (0..17).map(|index| format!("/opt/{index}")).collect();
+        assert!(normalize_extra_whisper_cli_paths(&too_many).is_err());
+    }
 
-#[cfg(target_os = "macos")]
-fn microphone_permission_restricted_error() -> String {
-    "Microphone access is restricted by macOS for this app. Check Privacy & Security > Microphone or system policy restrictions and retry.".to_string()
-}
+    #[test]
+    fn hold_cancel_detector_flags_releases_before_min_hold() {
+        let min_hold = Duration::from_millis(150);
+        let start = Instant::now();
+        let mut detector = HoldCancelDetector::default();
 
-#[cfg(target_os = "macos")]
-fn should_focus_main_window_for_microphone_prompt(status: AVAuthorizationStatus) -> bool {
-    status == AVAuthorizationStatus::NotDetermined
-}
+        detector.press(start);
+        assert!(detector.release(start + Duration::from_millis(60), min_hold));
 
-#[cfg(target_os = "macos")]
-fn ensure_microphone_access_authorized(app: &tauri::AppHandle) -> Result<(), String> {
-    let (tx, rx) = mpsc::channel::<Result<(), String>>();
-    let tx_main = tx.clone();
-    let app_handle = app.clone();
+        detector.press(start);
+        assert!(!detector.release(start + Duration::from_millis(400), min_hold));
 
-    app.run_on_main_thread(move || {
-        let media_type = match microphone_media_type() {
-            Ok(value) => value,
-            Err(error) => {
-                let _ = tx_main.send(Err(error));
-                return;
-            }
-        };
+        // A release with no recorded press (e.g. after settings reset) is never a cancel.
+        assert!(!detector.release(start + Duration::from_millis(10), min_hold));
 
-        let status = unsafe { AVCaptureDevice::authorizationStatusForMediaType(media_type) };
-        if status == AVAuthorizationStatus::Authorized {
-            let _ = tx_main.send(Ok(()));
-            return;
-        }
-        if status == AVAuthorizationStatus::Denied {
-            let _ = tx_main.send(Err(microphone_permission_denied_error()));
-            return;
-        }
-        if status == AVAuthorizationStatus::Restricted {
-            let _ = tx_main.send(Err(microphone_permission_restricted_error()));
-            return;
-        }
-        if !should_focus_main_window_for_microphone_prompt(status) {
-            let _ = tx_main.send(Err(format!(
-                "Microphone access returned an unknown AVFoundation authorization state ({}).",
-                status.0
-            )));
-            return;
-        }
+        detector.press(start);
+        assert!(!detector.release(start + Duration::from_millis(10), Duration::ZERO));
+    }
 
-        show_main_window(&app_handle);
+    #[test]
+    fn double_tap_detector_fires_on_second_quick_tap() {
+        let window = Duration::from_millis(350);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut detector = DoubleTapDetector::default();
+
+        detector.press(at(0), window);
+        assert!(!detector.release(at(80), window));
+        detector.press(at(200), window);
+        assert!(detector.release(at(260), window));
+
+        // A lone tap followed by a late second tap starts over instead of firing.
+        detector.press(at(1_000), window);
+        assert!(!detector.release(at(1_050), window));
+        detector.press(at(1_600), window);
+        assert!(!detector.release(at(1_650), window));
+    }
 
-        let tx_request = tx_main.clone();
-        let handler = RcBlock::new(move |granted| {
-            let _ = tx_request.send(if bool::from(granted) {
-                Ok(())
-            } else {
-                Err(microphone_permission_denied_error())
-            });
-        });
-        unsafe {
-            AVCaptureDevice::requestAccessForMediaType_completionHandler(media_type, &handler);
-        }
-    })
-    .map_err(|e| format!("Failed to request microphone access on the macOS main thread: {e}"))?;
+    #[test]
+    fn double_tap_detector_ignores_hold_after_first_tap() {
+        let window = Duration::from_millis(350);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut detector = DoubleTapDetector::default();
+
+        detector.press(at(0), window);
+        assert!(!detector.release(at(60), window));
+        detector.press(at(150), window);
+        assert!(!detector.release(at(900), window));
+
+        // The hold also clears the pending tap, so the next quick tap is only a first tap.
+        detector.press(at(950), window);
+        assert!(!detector.release(at(1_000), window));
+    }
 
-    match rx.recv_timeout(Duration::from_secs(15)) {
-        Ok(result) => result,
-        Err(_) => Err(
-            "Timed out waiting for macOS microphone permission. Bring dicktaint to the foreground, approve access in Privacy & Security > Microphone, then retry."
-                .to_string(),
-        ),
+    #[test]
+    fn hotkey_activation_settings_fall_back_to_single_tap() {
+        let settings = LocalSettings {
+            hotkey_activation: Some("double".into()),
+            double_tap_ms: Some(5),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            hotkey_activation_from_settings(&settings),
+            (HotkeyActivationMode::Double, 350)
+        );
+        assert_eq!(
+            hotkey_activation_from_settings(&LocalSettings::default()),
+            (HotkeyActivationMode::Single, 350)
+        );
     }
-}
-
-#[cfg(not(target_os = "macos"))]
-fn ensure_microphone_access_authorized(_app: &tauri::AppHandle) -> Result<(), String> {
-    Ok(())
-}
 
-fn create_input_stream(
-    samples: Arc<Mutex<Vec<f32>>>,
-    meter: LiveAudioMeter,
-) -> Result<(Stream, u32, String), String> {
-    let host = cpal::default_host();
-    let preferred_input_name = meter
-        .app
-        .state::<LocalModelState>()
-        .settings
-        .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?
-        .preferred_input_device
-        .clone();
-    let mut candidate_devices: Vec<(String, cpal::Device)> = Vec::new();
+    #[test]
+    fn dictation_state_payload_always_reports_scratch_flag() {
+        let payload = DictationStatePayload {
+            state: "idle".to_string(),
+            error: None,
+            transcript: Some("hello".to_string()),
+            session_id: Some(7),
+            scratch: true,
+            warning: None,
+            retried: false,
+            audio_info: None,
+            word_count: None,
+            audio_seconds: None,
+            command: None,
+        };
+        let value = serde_json::to_value(payload).expect("payload should serialize");
+        assert_eq!(value["scratch"], serde_json::Value::Bool(true));
+        assert!(!DictationSessionOptions::default().scratch);
+    }
 
-    let default_name = host
-        .default_input_device()
-        .map(|device| device_name(&device, "default input"));
+    #[test]
+    fn usage_totals_accumulate_words_and_audio_time() {
+        assert_eq!(
+            transcript_word_count("  Hello there,\n general   Kenobi. "),
+            4
+        );
+        assert_eq!(transcript_word_count(""), 0);
 
-    if let Some(preferred_name) = preferred_input_name.as_deref() {
-        if let Ok(devices) = host.input_devices() {
-            for device in devices {
-                let name = device_name(&device, "unknown input");
-                if name == preferred_name {
-                    candidate_devices.push((name, device));
-                }
+        let mut totals = UsageTotals::default();
+        add_usage(&mut totals, 4, 2.5);
+        add_usage(&mut totals, 10, 1.5);
+        assert_eq!(
+            totals,
+            UsageTotals {
+                words: 14,
+                audio_seconds: 4.0,
+                sessions: 2,
             }
-        }
+        );
+
+        let payload = usage_stats_payload(&LocalSettings::default());
+        assert!(!payload.enabled);
+        assert_eq!((payload.words, payload.sessions), (0, 0));
     }
 
-    if let Some(default_device) = host.default_input_device() {
-        let name = device_name(&default_device, "default input");
-        candidate_devices.push((name, default_device));
+    #[test]
+    fn model_onboarding_reason_separates_unset_from_missing() {
+        assert_eq!(
+            model_onboarding_reason(&LocalSettings::default(), false),
+            OnboardingReason::NoModel
+        );
+        let stale = LocalSettings {
+            selected_model_path: Some("/gone/ggml-base.en.bin".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            model_onboarding_reason(&stale, false).as_str(),
+            "model_missing"
+        );
+        assert_eq!(
+            model_onboarding_reason(&LocalSettings::default(), true),
+            OnboardingReason::ModelMissing
+        );
     }
 
-    if let Ok(devices) = host.input_devices() {
-        for device in devices {
-            candidate_devices.push((device_name(&device, "unknown input"), device));
-        }
+    #[test]
+    fn dictation_state_payload_reports_audio_info_fields() {
+        let payload = DictationStatePayload {
+            state: "idle".to_string(),
+            error: None,
+            transcript: Some("hello".to_string()),
+            session_id: Some(7),
+            scratch: false,
+            warning: None,
+            retried: false,
+            audio_info: Some(AudioInfoPayload {
+                input_sample_rate: 48_000,
+                input_channels: 2,
+                resampled: true,
+                captured_seconds: 1.5,
+                prepared_seconds: 1.5,
+                peak: 0.5,
+                rms: 0.25,
+            }),
+            word_count: Some(1),
+            audio_seconds: Some(1.5),
+            command: None,
+        };
+        let value = serde_json::to_value(payload).expect("payload should serialize");
+        assert_eq!(value["audio_info"]["input_sample_rate"], 48_000);
+        assert_eq!(value["audio_info"]["input_channels"], 2);
+        assert_eq!(value["audio_info"]["resampled"], true);
     }
 
-    if candidate_devices.is_empty() {
-        return Err(
-            "No microphone input device found. In macOS Settings > Sound > Input, select a microphone and retry."
-                .to_string(),
+    #[test]
+    fn migrate_settings_upgrades_v0_and_keeps_fields() {
+        let v0 = serde_json::json!({
+            "selected_model_id": "base-en",
+            "dictation_trigger": "CmdOrCtrl+Shift+K",
+            "focused_field_insert_enabled": "true",
+            "whisper_timeout_seconds": "90",
+            "normalization_target_level": "0.2",
+            "extra_whisper_args": "--prompt-file",
+            "max_recordings": "lots",
+            "removed_setting": 3
+        });
+        let migration = migrate_settings(v0).expect("v0 settings should migrate");
+        let settings = migration.settings;
+        assert_eq!(migration.from_version, 0);
+        assert_eq!(migration.dropped_fields, vec!["max_recordings".to_string()]);
+        assert_eq!(settings.schema_version, Some(SETTINGS_SCHEMA_VERSION));
+        assert_eq!(settings.selected_model_id.as_deref(), Some("base-en"));
+        assert_eq!(
+            settings.dictation_trigger.as_deref(),
+            Some("CmdOrCtrl+Shift+K")
+        );
+        assert_eq!(settings.focused_field_insert_enabled, Some(true));
+        assert_eq!(settings.whisper_timeout_seconds, Some(90));
+        assert_eq!(settings.normalization_target_level, Some(0.2));
+        assert_eq!(
+            settings.extra_whisper_args,
+            Some(vec!["--prompt-file".to_string()])
         );
+        assert_eq!(settings.max_recordings, None);
+        assert!(migrate_settings(serde_json::json!(["not", "an", "object"])).is_err());
     }
 
-    let mut attempts: Vec<String> = Vec::new();
-    for (name, device) in candidate_devices {
-        match create_input_stream_for_device(&device, &name, Arc::clone(&samples), meter.clone()) {
-            Ok((stream, sample_rate)) => return Ok((stream, sample_rate, name)),
-            Err(err) => attempts.push(format!("{name}: {err}")),
-        }
-    }
+    #[test]
+    fn load_local_settings_backs_up_and_rewrites_v0_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "dicktaint-settings-migration-test-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create test dir");
+        let settings_path = dir.join("dictation-settings.json");
+        let v0 = r#"{"selected_model_id":"tiny-en","whisper_timeout_seconds":"45"}"#;
+        std::fs::write(&settings_path, v0).expect("write v0 settings");
 
-    let preferred_detail = preferred_input_name
-        .as_deref()
-        .map(|name| format!(" Preferred input: {name}."))
-        .unwrap_or_default();
-    let default_detail = default_name
-        .as_deref()
-        .map(|name| format!(" Default input: {name}."))
-        .unwrap_or_default();
+        let settings = load_local_settings(&settings_path);
+        assert_eq!(settings.selected_model_id.as_deref(), Some("tiny-en"));
+        assert_eq!(settings.whisper_timeout_seconds, Some(45));
 
-    Err(format!(
-        "Could not open microphone input on this machine. Tried: {}. \
-In macOS Settings > Privacy & Security > Microphone, allow this app/terminal, then pick an input device in Settings > Sound > Input and retry.{}{}",
-        attempts.join(" | "),
-        preferred_detail,
-        default_detail
-    ))
-}
+        let backup_path = settings_backup_path(&settings_path, 0);
+        assert_eq!(
+            std::fs::read_to_string(&backup_path).expect("read backup"),
+            v0
+        );
+        let rewritten: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(&settings_path).expect("read migrated settings"),
+        )
+        .expect("migrated settings should be JSON");
+        assert_eq!(
+            rewritten["schema_version"],
+            serde_json::json!(SETTINGS_SCHEMA_VERSION)
+        );
+        assert_eq!(rewritten["whisper_timeout_seconds"], serde_json::json!(45));
 
-fn spawn_recording_thread(
-    samples: Arc<Mutex<Vec<f32>>>,
-    app: tauri::AppHandle,
-    session_id: u64,
-) -> Result<(mpsc::Sender<()>, thread::JoinHandle<()>, u32, String), String> {
-    let (stop_tx, stop_rx) = mpsc::channel::<()>();
-    let (init_tx, init_rx) = mpsc::channel::<Result<(u32, String), String>>();
-    let capture_samples = Arc::clone(&samples);
-    let meter = LiveAudioMeter {
-        app,
-        session_id,
-        last_emitted_at: Arc::new(Mutex::new(None)),
-    };
+        let reloaded = load_local_settings(&settings_path);
+        assert_eq!(reloaded.whisper_timeout_seconds, Some(45));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-    let handle = thread::spawn(move || {
-        let stream_result = create_input_stream(capture_samples, meter);
-        match stream_result {
-            Ok((stream, sample_rate, input_device_name)) => {
-                let _ = init_tx.send(Ok((sample_rate, input_device_name)));
-                let _ = stop_rx.recv();
-                drop(stream);
-            }
-            Err(e) => {
-                let _ = init_tx.send(Err(e));
-            }
-        }
-    });
+    #[test]
+    fn memory_guard_blocks_or_warns_below_requirement_plus_margin() {
+        let gib = 1_073_741_824_u64;
+        assert_eq!(
+            check_memory_guard(MemoryGuardMode::Block, 1024, gib, Some(3 * gib)),
+            Ok(None)
+        );
+        let error = check_memory_guard(MemoryGuardMode::Block, 1024, 2 * gib, Some(2 * gib))
+            .expect_err("block mode should refuse when the margin is not met");
+        assert!(error.contains("2048 MB"));
+        assert!(error.contains("1024 MB safety margin"));
+        assert!(matches!(
+            check_memory_guard(MemoryGuardMode::Warn, 1024, 2 * gib, Some(2 * gib)),
+            Ok(Some(_))
+        ));
+        assert_eq!(
+            check_memory_guard(MemoryGuardMode::Off, 1024, 2 * gib, Some(0)),
+            Ok(None)
+        );
+        assert_eq!(
+            check_memory_guard(MemoryGuardMode::Block, 1024, 2 * gib, None),
+            Ok(None)
+        );
+    }
 
-    let (sample_rate, input_device_name) = match init_rx.recv_timeout(Duration::from_secs(5)) {
-        Ok(Ok(value)) => value,
-        Ok(Err(e)) => {
-            let _ = handle.join();
-            return Err(e);
-        }
-        Err(_) => {
-            let _ = stop_tx.send(());
-            let _ = handle.join();
-            return Err("Timed out while opening microphone stream.".to_string());
-        }
-    };
+    #[test]
+    fn memory_guard_settings_fall_back_to_defaults() {
+        let settings = LocalSettings {
+            memory_guard_mode: Some("hard".to_string()),
+            memory_guard_margin_mb: Some(1_000_000),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            memory_guard_from_settings(&settings),
+            (MemoryGuardMode::Block, DEFAULT_MEMORY_GUARD_MARGIN_MB)
+        );
+        assert_eq!(
+            memory_guard_from_settings(&LocalSettings::default()),
+            (MemoryGuardMode::Off, DEFAULT_MEMORY_GUARD_MARGIN_MB)
+        );
+        assert!(
+            model_memory_requirement_bytes(Path::new("/models/ggml-base.en.bin"))
+                > MODEL_RUNTIME_OVERHEAD_MB * 1_048_576
+        );
+    }
 
-    Ok((stop_tx, handle, sample_rate, input_device_name))
-}
+    #[test]
+    fn model_recommendation_for_ram_matches_picker_and_explains_fit() {
+        let roomy =
+            model_recommendation_for_ram(32, 8, SimdSupport::Full, RecommendationBias::Balanced);
+        assert_eq!(
+            roomy.recommended_model_id.as_deref(),
+            pick_recommended_model_id(32, 8, SimdSupport::Full, RecommendationBias::Balanced)
+        );
+        assert!(roomy.rationale.contains("32 GB"));
+        assert!(roomy
+            .comfortable_model_ids
+            .iter()
+            .all(|id| roomy.runnable_model_ids.contains(id)));
 
-fn resample_linear(samples: &[f32], source_rate: u32, target_rate: u32) -> Vec<f32> {
-    if samples.is_empty() || source_rate == 0 {
-        return Vec::new();
+        let tight =
+            model_recommendation_for_ram(2, 8, SimdSupport::Full, RecommendationBias::Balanced);
+        assert_eq!(tight.recommended_model_id, None);
+        assert!(tight.runnable_model_ids.is_empty());
+        assert!(tight.rationale.contains("below the 4 GB minimum"));
     }
-    if source_rate == target_rate {
-        return samples.to_vec();
+
+    #[test]
+    fn recommendation_bias_steers_toward_smaller_or_larger_models() {
+        let pick = |ram_gb, bias| {
+            pick_recommended_model_id(ram_gb, 8, SimdSupport::Full, bias)
+                .and_then(find_whisper_model_spec)
+                .expect("a model runs")
+        };
+        let balanced = pick(32, RecommendationBias::Balanced);
+        let speed = pick(32, RecommendationBias::Speed);
+        let accuracy = pick(32, RecommendationBias::Accuracy);
+        assert!(speed.relative_compute <= SPEED_BIAS_MAX_RELATIVE_COMPUTE);
+        assert!(speed.relative_compute < balanced.relative_compute);
+        assert_eq!(accuracy.id, "large-v3");
+        assert!(accuracy.recommended_ram_gb > balanced.recommended_ram_gb);
+        // Accuracy still stays within the RAM minimum.
+        assert!(pick(20, RecommendationBias::Accuracy).min_ram_gb <= 20);
+
+        assert_eq!(
+            RecommendationBias::parse(" Quality "),
+            Some(RecommendationBias::Accuracy)
+        );
+        assert!(RecommendationBias::parse("huge").is_none());
+        assert!(
+            model_recommendation_for_ram(32, 8, SimdSupport::Full, RecommendationBias::Speed)
+                .rationale
+                .contains("toward speed")
+        );
     }
 
-    let ratio = target_rate as f32 / source_rate as f32;
-    let out_len = ((samples.len() as f32) * ratio).round().max(1.0) as usize;
-    let mut out = Vec::with_capacity(out_len);
+    #[test]
+    fn low_core_machines_get_a_model_that_keeps_up_with_speech() {
+        let large = find_whisper_model_spec("large-v3").expect("large-v3 is in the catalog");
+        let tiny = find_whisper_model_spec("tiny-en").expect("tiny-en is in the catalog");
+        assert!(estimated_real_time_factor(large, 2, SimdSupport::Full) < 1.0);
+        assert!(
+            estimated_real_time_factor(tiny, 2, SimdSupport::Full)
+                > estimated_real_time_factor(large, 2, SimdSupport::Full)
+        );
+        assert_eq!(
+            estimated_real_time_factor(large, 64, SimdSupport::Full),
+            estimated_real_time_factor(large, 8, SimdSupport::Full)
+        );
 
-    for i in 0..out_len {
-        let src_pos = i as f32 / ratio;
-        let idx = src_pos.floor() as usize;
-        let frac = src_pos - idx as f32;
+        let pick = |cores| {
+            pick_recommended_model_id(64, cores, SimdSupport::Full, RecommendationBias::Balanced)
+                .and_then(find_whisper_model_spec)
+                .expect("64 GB runs every model")
+        };
+        assert!(keeps_up_with_speech(pick(2), 2, SimdSupport::Full));
+        assert!(pick(2).relative_compute < pick(8).relative_compute);
+
+        let capped =
+            model_recommendation_for_ram(64, 2, SimdSupport::Full, RecommendationBias::Balanced);
+        assert_eq!(capped.logical_cpu_cores, 2);
+        assert!(capped
+            .rationale
+            .contains("slower than real time on 2 CPU cores"));
+    }
 
-        let a = samples.get(idx).copied().unwrap_or(0.0);
-        let b = samples.get(idx + 1).copied().unwrap_or(a);
-        out.push(a + (b - a) * frac);
+    #[test]
+    fn cpus_without_vector_support_get_smaller_models() {
+        assert_eq!(
+            SimdSupport::detect("x86_64", &["avx", "avx2", "fma"]),
+            SimdSupport::Full
+        );
+        assert_eq!(
+            SimdSupport::detect("x86_64", &["avx"]),
+            SimdSupport::Partial
+        );
+        assert_eq!(SimdSupport::detect("x86", &["sse4.1"]), SimdSupport::None);
+        assert_eq!(SimdSupport::detect("aarch64", &["neon"]), SimdSupport::Full);
+        assert_eq!(SimdSupport::detect("arm", &[]), SimdSupport::None);
+        assert_eq!(SimdSupport::detect("riscv64", &[]), SimdSupport::Partial);
+
+        let pick = |simd| {
+            pick_recommended_model_id(64, 8, simd, RecommendationBias::Balanced)
+                .and_then(find_whisper_model_spec)
+                .expect("64 GB runs a model")
+        };
+        assert!(
+            pick(SimdSupport::None).relative_compute < pick(SimdSupport::Full).relative_compute
+        );
+
+        let full =
+            model_recommendation_for_ram(64, 2, SimdSupport::Full, RecommendationBias::Balanced);
+        assert!(full.runnable_model_ids.contains(&"large-v3".to_string()));
+        let scalar =
+            model_recommendation_for_ram(64, 2, SimdSupport::None, RecommendationBias::Balanced);
+        assert!(!scalar.runnable_model_ids.contains(&"large-v3".to_string()));
+        assert!(scalar.runnable_model_ids.contains(&"tiny-en".to_string()));
+        assert!(scalar.rationale.contains("no AVX or NEON"));
     }
 
-    out
-}
+    #[test]
+    fn imported_settings_rebase_catalog_model_and_flag_missing_file() {
+        let exported = export_settings_json(&LocalSettings {
+            selected_model_id: Some("base-en".to_string()),
+            selected_model_path: Some("/Users/elsewhere/models/ggml-base.en.bin".to_string()),
+            dictation_trigger: Some("cmdorctrl+shift+k".to_string()),
+            whisper_timeout_seconds: Some(120),
+            ..LocalSettings::default()
+        })
+        .expect("settings should export");
+        let models_dir = Path::new("/nonexistent/dicktaint-models");
 
-fn write_wav(path: &PathBuf, samples: &[f32], sample_rate: u32) -> Result<(), String> {
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
+        let (settings, dropped) =
+            prepare_imported_settings(&exported, models_dir).expect("export should import");
+        assert!(dropped.is_empty());
+        assert_eq!(
+            settings.selected_model_path.as_deref(),
+            Some("/nonexistent/dicktaint-models/ggml-base.en.bin")
+        );
+        assert_eq!(
+            settings.dictation_trigger.as_deref(),
+            Some("CmdOrCtrl+Shift+K")
+        );
+        assert_eq!(settings.whisper_timeout_seconds, Some(120));
+        assert_eq!(
+            imported_model_missing(&settings),
+            Some("base-en".to_string())
+        );
+    }
 
-    let mut writer = hound::WavWriter::create(path, spec)
-        .map_err(|e| format!("Failed to create wav file {}: {e}", path.display()))?;
-    for sample in samples {
-        let clipped = sample.clamp(-1.0, 1.0);
-        let pcm = (clipped * i16::MAX as f32) as i16;
-        writer
-            .write_sample(pcm)
-            .map_err(|e| format!("Failed to write wav sample: {e}"))?;
+    #[test]
+    fn imported_settings_reject_invalid_or_newer_files() {
+        let models_dir = Path::new("/nonexistent/dicktaint-models");
+        assert!(prepare_imported_settings("not json", models_dir).is_err());
+        assert!(prepare_imported_settings(r#"{"dictation_trigger":"K"}"#, models_dir).is_err());
+        assert!(
+            prepare_imported_settings(r#"{"extra_whisper_args":["-m","x.bin"]}"#, models_dir)
+                .is_err()
+        );
+        assert!(prepare_imported_settings(r#"{"schema_version":99}"#, models_dir).is_err());
+        for id in [
+            "imported-../../evil",
+            "imported-",
+            "other-model",
+            "imported-Caps",
+            "imported-a--b",
+        ] {
+            let contents = format!(
+                r#"{{"imported_models":[{{"id":"{id}","display_name":"x","source_path":"/x.bin","added_at_ms":0}}]}}"#
+            );
+            assert!(
+                prepare_imported_settings(&contents, models_dir).is_err(),
+                "{id} should be rejected"
+            );
+        }
     }
-    writer
-        .finalize()
-        .map_err(|e| format!("Failed to finalize wav file: {e}"))?;
 
-    Ok(())
-}
+    #[test]
+    fn imported_settings_keep_valid_model_ids_and_drop_unusable_whisper_cli_override() {
+        let models_dir = Path::new("/nonexistent/dicktaint-models");
+        let (settings, dropped) = prepare_imported_settings(
+            r#"{"whisper_cli_path_override":"/nonexistent/bin/whisper-cli","imported_models":[{"id":"imported-my-fine-tune-v2-2","display_name":"x","source_path":"/x.bin","added_at_ms":0}]}"#,
+            models_dir,
+        )
+        .expect("settings should import");
+        assert_eq!(settings.whisper_cli_path_override, None);
+        assert_eq!(dropped, vec!["whisper_cli_path_override".to_string()]);
+        assert_eq!(settings.imported_models.map(|models| models.len()), Some(1));
+    }
 
-fn analyze_audio_signal(samples: &[f32], sample_rate: u32) -> AudioSignalStats {
-    if samples.is_empty() || sample_rate == 0 {
-        return AudioSignalStats {
-            peak_abs: 0.0,
-            rms: 0.0,
-            duration_secs: 0.0,
+    #[test]
+    fn pill_window_origin_anchors_to_work_area_edges() {
+        let layout = |position| PillLayout {
+            position,
+            margin: 14,
+            enabled: true,
+            visibility: PillVisibility::Always,
         };
+        let work = ((100, 50), (1000, 800));
+        let size = (100, 26);
+        assert_eq!(
+            pill_window_origin(layout(PillPosition::BottomCenter), work.0, work.1, size),
+            (550, 810)
+        );
+        assert_eq!(
+            pill_window_origin(layout(PillPosition::TopLeft), work.0, work.1, size),
+            (114, 64)
+        );
+        assert_eq!(
+            pill_window_origin(layout(PillPosition::BottomRight), work.0, work.1, size),
+            (986, 810)
+        );
+        assert_eq!(
+            pill_window_origin(layout(PillPosition::TopCenter), (0, 0), (80, 20), size),
+            (0, 0)
+        );
     }
 
-    let mut peak_abs = 0.0_f32;
-    let mut energy = 0.0_f64;
-    for sample in samples {
-        let abs = sample.abs();
-        if abs > peak_abs {
-            peak_abs = abs;
+    #[test]
+    fn pill_visibility_honors_legacy_flag_and_recording_states() {
+        let mut settings = LocalSettings {
+            pill_visibility: Some("recording-only".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(pill_visibility(&settings), PillVisibility::RecordingOnly);
+        assert!(pill_layout_from_settings(&settings).enabled);
+        settings.pill_enabled = Some(false);
+        assert_eq!(pill_visibility(&settings), PillVisibility::Hidden);
+        assert!(!pill_layout_from_settings(&settings).enabled);
+        settings.pill_enabled = None;
+        settings.pill_visibility = Some("sometimes".to_string());
+        assert_eq!(pill_visibility(&settings), PillVisibility::Always);
+
+        for state in ["listening", "paused", "processing"] {
+            assert!(dictation_state_shows_recording_pill(state));
+        }
+        for state in ["idle", "error"] {
+            assert!(!dictation_state_shows_recording_pill(state));
         }
-        energy += f64::from(*sample) * f64::from(*sample);
     }
 
-    AudioSignalStats {
-        peak_abs,
-        rms: (energy / samples.len() as f64).sqrt() as f32,
-        duration_secs: samples.len() as f32 / sample_rate as f32,
+    #[test]
+    fn pill_layout_settings_fall_back_to_bottom_center() {
+        let settings = LocalSettings {
+            pill_position: Some("top_right".to_string()),
+            pill_margin: Some(5_000),
+            pill_enabled: Some(false),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            pill_layout_from_settings(&settings),
+            PillLayout {
+                position: PillPosition::TopRight,
+                margin: DEFAULT_PILL_WINDOW_MARGIN,
+                enabled: false,
+                visibility: PillVisibility::Hidden,
+            }
+        );
+        assert_eq!(
+            pill_layout_from_settings(&LocalSettings::default()).position,
+            PillPosition::BottomCenter
+        );
+        assert_eq!(PillPosition::parse("middle"), None);
     }
-}
 
-fn audio_signal_is_too_quiet(stats: AudioSignalStats) -> bool {
-    stats.peak_abs < MIN_TRANSCRIPTION_AUDIO_PEAK && stats.rms < MIN_TRANSCRIPTION_AUDIO_RMS
-}
+    #[test]
+    fn permission_states_distinguish_unasked_from_denied() {
+        assert_eq!(PermissionState::NotDetermined.as_str(), "not-determined");
+        assert_eq!(PermissionState::Denied.as_str(), "denied");
+        assert!(!PermissionState::NotDetermined.blocks_use());
+        assert!(PermissionState::Denied.blocks_use());
+        assert!(PermissionState::Restricted.blocks_use());
+        assert_eq!(
+            PermissionKind::parse("Input_Monitoring"),
+            Some(PermissionKind::InputMonitoring)
+        );
+        assert_eq!(PermissionKind::parse("camera"), None);
+    }
 
-fn normalize_audio_gain(samples: Vec<f32>, stats: AudioSignalStats) -> Vec<f32> {
-    if stats.peak_abs <= 0.0 {
-        return samples;
+    #[test]
+    fn downmix_average_matches_per_frame_mean() {
+        let stereo = [0.2_f32, 0.6, -0.4, 0.0, 1.0, -1.0];
+        assert_eq!(
+            downmix_samples(&stereo, 2, DownmixMode::Average, |v| v),
+            vec![0.4, -0.2, 0.0]
+        );
     }
 
-    let gain = (TARGET_TRANSCRIPTION_AUDIO_PEAK / stats.peak_abs).min(MAX_TRANSCRIPTION_AUDIO_GAIN);
-    if gain <= 1.0 {
-        return samples;
+    #[test]
+    fn downmix_channel_modes_pick_a_single_channel() {
+        let stereo = [0.1_f32, 0.5, 0.0, -0.6, 0.1, 0.4];
+        assert_eq!(
+            downmix_samples(&stereo, 2, DownmixMode::Left, |v| v),
+            vec![0.1, 0.0, 0.1]
+        );
+        assert_eq!(
+            downmix_samples(&stereo, 2, DownmixMode::Right, |v| v),
+            vec![0.5, -0.6, 0.4]
+        );
+        assert_eq!(
+            downmix_samples(&stereo, 2, DownmixMode::Dominant, |v| v),
+            vec![0.5, -0.6, 0.4]
+        );
+        assert_eq!(
+            downmix_samples(&[0.3_f32, -0.3], 1, DownmixMode::Right, |v| v),
+            vec![0.3, -0.3]
+        );
     }
 
-    samples
-        .into_iter()
-        .map(|sample| (sample * gain).clamp(-1.0, 1.0))
-        .collect()
-}
+    #[test]
+    fn clipping_warning_flags_hot_input_only() {
+        let mut samples = vec![0.3_f32; 10_000];
+        samples[0] = 1.0;
+        assert!(clipping_warning(clipping_fraction(&samples)).is_none());
 
-fn quiet_audio_error(stats: AudioSignalStats, input_device_name: &str) -> String {
-    format!(
-        "Captured audio from '{}' was too quiet to transcribe (peak {:.4}, rms {:.4}, {:.1}s). Check macOS Sound > Input, confirm the selected microphone, and retry.",
-        input_device_name, stats.peak_abs, stats.rms, stats.duration_secs
-    )
-}
+        for sample in samples.iter_mut().take(50) {
+            *sample = -1.0;
+        }
+        let warning = clipping_warning(clipping_fraction(&samples)).expect("hot input should warn");
+        assert!(warning.contains("0.5%"));
+        assert!(warning.contains("lower your mic gain"));
+        assert_eq!(clipping_fraction(&[]), 0.0);
+    }
 
-fn is_transcript_artifact_token(token: &str) -> bool {
-    let normalized = token.trim_matches(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_');
-    let upper = normalized.to_ascii_uppercase();
-    matches!(
-        upper.as_str(),
-        "BLANK_AUDIO" | "NOISE" | "MUSIC" | "SILENCE"
-    )
-}
+    #[test]
+    fn transcript_artifacts_strip_bracketed_markers_and_saved_extras() {
+        let mut settings = LocalSettings::default();
+        let defaults = transcript_artifact_tokens(&settings);
+        assert_eq!(defaults, ["BLANK_AUDIO", "NOISE", "MUSIC", "SILENCE"]);
+        assert_eq!(
+            normalize_transcript_text(
+                "[BLANK_AUDIO] Hello (music) world [APPLAUSE]. [_TT_150] (Sighs) [door closes]",
+                &defaults
+            ),
+            "Hello world"
+        );
+        assert_eq!(
+            normalize_transcript_text("Blank_Audio so f(x) is (s)he [Applause], right", &defaults),
+            "so f(x) is (s)he right"
+        );
 
-fn normalize_transcript_text(raw: &str) -> String {
-    raw.split_whitespace()
-        .filter(|token| !is_transcript_artifact_token(token))
-        .collect::<Vec<_>>()
-        .join(" ")
-}
+        assert_eq!(
+            validate_transcript_artifact_tokens(&[
+                " [Laughter] ".to_string(),
+                "music".to_string(),
+                "laughter".to_string(),
+            ]),
+            Ok(vec!["LAUGHTER".to_string()])
+        );
+        assert!(validate_transcript_artifact_tokens(&["two words".to_string()]).is_err());
+        settings.transcript_artifact_tokens = Some(vec!["LAUGHTER".to_string()]);
+        let tokens = transcript_artifact_tokens(&settings);
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(normalize_transcript_text("laughter okay", &tokens), "okay");
+        assert_eq!(
+            transcription_options(&settings, Path::new("/tmp")).artifact_tokens,
+            tokens
+        );
+    }
 
-fn transcribe_samples(
-    model_path: PathBuf,
-    whisper_cli_path: String,
-    samples: Vec<f32>,
-    sample_rate: u32,
-    input_device_name: String,
-) -> Result<String, String> {
-    let prepared = if sample_rate == WHISPER_SAMPLE_RATE {
-        samples
-    } else {
-        resample_linear(&samples, sample_rate, WHISPER_SAMPLE_RATE)
-    };
+    #[test]
+    fn spoken_commands_replace_standalone_phrases_only() {
+        let mut settings = LocalSettings {
+            spoken_commands: Some(true),
+            ..LocalSettings::default()
+        };
+        let commands = spoken_commands(&settings);
+        assert_eq!(
+            apply_spoken_commands(
+                "Dear team, comma the commander said Period. open paren see notes close paren new line Thanks",
+                &commands,
+            ),
+            "Dear team, the commander said. (see notes)\nThanks"
+        );
+        assert_eq!(
+            apply_spoken_commands("is it done question mark", &commands),
+            "is it done?"
+        );
+        assert_eq!(
+            apply_spoken_commands("periodic commas", &commands),
+            "periodic commas"
+        );
 
-    if prepared.is_empty() {
-        return Err("No audio captured. Check microphone input and try again.".to_string());
+        settings.spoken_commands_locale = Some("DE".to_string());
+        settings.spoken_command_map = Some(BTreeMap::from([
+            ("Komma".to_string(), String::new()),
+            ("smiley".to_string(), "🙂".to_string()),
+        ]));
+        let commands = spoken_commands(&settings);
+        assert_eq!(
+            apply_spoken_commands("ja komma gut Punkt smiley", &commands),
+            "ja komma gut. 🙂"
+        );
+        assert!(
+            transcription_options(&LocalSettings::default(), Path::new("/tmp"))
+                .spoken_commands
+                .is_empty()
+        );
+        assert!(validate_spoken_command_map(&BTreeMap::from([(
+            "one two three four five".to_string(),
+            "x".to_string()
+        )]))
+        .is_err());
+        assert!(validate_spoken_command_map(&BTreeMap::from([(
+            "tab,".to_string(),
+            "x".to_string()
+        )]))
+        .is_err());
     }
 
-    let signal = analyze_audio_signal(&prepared, WHISPER_SAMPLE_RATE);
-    if audio_signal_is_too_quiet(signal) {
-        return Err(quiet_audio_error(signal, &input_device_name));
-    }
-    let prepared = normalize_audio_gain(prepared, signal);
+    #[test]
+    fn strip_fillers_only_touches_the_edges() {
+        let words: Vec<String> = DEFAULT_FILLER_WORDS.iter().map(|w| w.to_string()).collect();
+        let phrases: Vec<String> = DEFAULT_FILLER_TRAILING_PHRASES
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let strip = |text: &str| strip_fillers(text, &words, &phrases);
 
-    let tick = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_millis())
-        .unwrap_or(0);
-    let temp_dir = std::env::temp_dir();
-    let base_name = format!("dicktaint-{}-{tick}", std::process::id());
-    let wav_path = temp_dir.join(format!("{base_name}.wav"));
-    let out_prefix = temp_dir.join(format!("{base_name}-transcript"));
-    let txt_path = out_prefix.with_extension("txt");
-
-    write_wav(&wav_path, &prepared, WHISPER_SAMPLE_RATE)?;
-
-    let output = Command::new(&whisper_cli_path)
-    .arg("-m")
-    .arg(&model_path)
-    .arg("-f")
-    .arg(&wav_path)
-    .arg("-l")
-    .arg("en")
-    .arg("-otxt")
-    .arg("-nt")
-    .arg("-of")
-    .arg(&out_prefix)
-    .output()
-    .map_err(|e| {
-      format!(
-        "Failed to execute whisper cli '{whisper_cli_path}': {e}. Install whisper.cpp (whisper-cli) or set WHISPER_CLI_PATH."
-      )
-    })?;
+        assert_eq!(strip("Um, so we went, uh."), "So we went.");
+        assert_eq!(
+            strip("We went um there uh today"),
+            "We went um there uh today"
+        );
+        assert_eq!(strip("Let's ship it. Thank you."), "Let's ship it.");
+        assert_eq!(
+            strip("Let's ship it. Thanks for watching! Thank you."),
+            "Let's ship it."
+        );
+        assert_eq!(strip("Uh, thank you."), "");
+        assert_eq!(strip("I said thank you."), "I said thank you.");
+        assert_eq!(strip_fillers("Um, hi.", &[], &[]), "Um, hi.");
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let mut detail = String::new();
-        if !stderr.is_empty() {
-            detail.push_str(&stderr);
-        }
-        if detail.is_empty() && !stdout.is_empty() {
-            detail.push_str(&stdout);
-        }
-        if detail.is_empty() {
-            detail.push_str("no error output");
-        }
-        let _ = std::fs::remove_file(&wav_path);
-        let _ = std::fs::remove_file(&txt_path);
-        return Err(format!("whisper-cli transcription failed: {detail}"));
+        assert_eq!(
+            validate_filler_entries(&[" Thank  YOU! ".to_string()], "phrases", false),
+            Ok(vec!["thank you".to_string()])
+        );
+        assert!(validate_filler_entries(&["you know".to_string()], "words", true).is_err());
+        let off = transcription_options(&LocalSettings::default(), Path::new("/tmp"));
+        assert!(off.filler_words.is_empty() && off.filler_trailing_phrases.is_empty());
     }
 
-    let transcript = std::fs::read_to_string(&txt_path).map_err(|e| {
-        format!(
-            "whisper-cli ran but transcript file is missing at {}: {e}",
-            txt_path.display()
-        )
-    })?;
+    #[test]
+    fn move_storage_dir_carries_nested_files_and_rebases_model_paths() {
+        let root =
+            std::env::temp_dir().join(format!("dicktaint-storage-move-{}", std::process::id()));
+        let source = root.join("old").join(".dicktaint");
+        let target = root.join("new").join(".dicktaint");
+        std::fs::create_dir_all(source.join("whisper-models")).unwrap();
+        std::fs::write(source.join("dictation-settings.json"), "{}").unwrap();
+        std::fs::write(source.join("whisper-models").join("ggml-tiny.bin"), "model").unwrap();
+
+        move_storage_dir(&source, &target).unwrap();
+        assert!(!source.exists());
+        assert_eq!(
+            std::fs::read_to_string(target.join("whisper-models").join("ggml-tiny.bin")).unwrap(),
+            "model"
+        );
 
-    let _ = std::fs::remove_file(&wav_path);
-    let _ = std::fs::remove_file(&txt_path);
+        let copied = root.join("copy");
+        copy_dir_recursive(&target, &copied).unwrap();
+        assert!(copied.join("dictation-settings.json").is_file());
+        assert!(target.join("dictation-settings.json").is_file());
 
-    let cleaned = normalize_transcript_text(&transcript);
-    if cleaned.is_empty() {
-        return Err("No speech detected in the recorded audio.".to_string());
+        assert_eq!(
+            rebase_path(
+                "/a/models/ggml-tiny.bin",
+                Path::new("/a/models"),
+                Path::new("/b/models")
+            ),
+            Some("/b/models/ggml-tiny.bin".to_string())
+        );
+        assert_eq!(
+            rebase_path(
+                "/elsewhere/ggml.bin",
+                Path::new("/a/models"),
+                Path::new("/b/models")
+            ),
+            None
+        );
+        let _ = std::fs::remove_dir_all(&root);
     }
 
-    Ok(cleaned)
-}
-
-#[tauri::command]
-fn get_dictation_onboarding(
-    app: tauri::AppHandle,
-    config: State<'_, AppConfig>,
-    model_state: State<'_, LocalModelState>,
-    hotkey_state: State<'_, GlobalHotkeyState>,
-) -> Result<DictationOnboardingPayload, String> {
-    let mut payload =
-        build_onboarding_payload(config.inner(), model_state.inner(), hotkey_state.inner())?;
-    match apply_registered_hotkey(
-        &app,
-        hotkey_state.inner(),
-        payload.dictation_trigger.as_deref(),
-    ) {
-        Ok(runtime) => {
-            payload.dictation_trigger_mode = runtime.mode.as_str().to_string();
-            payload.dictation_trigger_status = runtime.status;
-            payload.dictation_trigger_permission_hint = runtime.permission_hint;
-        }
-        Err(error) => {
-            log::warn!("get_dictation_onboarding: failed to apply global hotkey: {error}");
-        }
+    #[test]
+    fn decode_audio_file_downmixes_wav_and_rejects_unknown_formats() {
+        let dir =
+            std::env::temp_dir().join(format!("dicktaint-test-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wav = dir.join("memo.wav");
+        write_wav(&wav, &[0.5, 0.0, -0.5, -0.5], 2, 8_000).unwrap();
+        let (mono, rate) = decode_audio_file(&wav).unwrap();
+        assert_eq!(rate, 8_000);
+        assert_eq!(mono.len(), 2);
+        assert!((mono[0] - 0.25).abs() < 0.001 && (mono[1] + 0.5).abs() < 0.001);
+
+        let unknown = decode_audio_file(&dir.join("notes.txt")).unwrap_err();
+        assert!(unknown.starts_with("Cannot transcribe '.txt' files."));
+        assert!(ffmpeg_version_looks_valid("ffmpeg version 7.1 Copyright"));
+        assert!(!ffmpeg_version_looks_valid("usage: whisper-cli"));
+        let _ = std::fs::remove_dir_all(&dir);
     }
-    Ok(payload)
-}
-
-#[tauri::command]
-fn get_dictation_trigger(
-    model_state: State<'_, LocalModelState>,
-    hotkey_state: State<'_, GlobalHotkeyState>,
-) -> Result<DictationTriggerPayload, String> {
-    let settings = model_state
-        .settings
-        .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?
-        .clone();
-    let runtime = current_trigger_runtime_details(hotkey_state.inner())?;
-    Ok(dictation_trigger_payload(&settings, runtime))
-}
 
-#[tauri::command]
-fn set_dictation_trigger(
-    app: tauri::AppHandle,
-    trigger: String,
-    model_state: State<'_, LocalModelState>,
-    hotkey_state: State<'_, GlobalHotkeyState>,
-) -> Result<DictationTriggerPayload, String> {
-    let normalized = normalize_dictation_trigger(&trigger)?;
-    let (previous_trigger, previous_trigger_raw, previous_trigger_enabled) = {
-        let settings = model_state
-            .settings
-            .lock()
-            .map_err(|_| "Failed to lock local model settings".to_string())?;
-        (
-            resolve_effective_dictation_trigger(&settings),
-            settings.dictation_trigger.clone(),
-            settings.dictation_trigger_enabled,
-        )
-    };
+    #[test]
+    fn fill_playback_buffer_copies_mono_to_every_channel_then_pads_silence() {
+        let frames = [0.5_f32, -2.0];
+        let mut data = [1.0_f32; 6];
+        let next = fill_playback_buffer(&mut data, 2, &frames, 0);
+        assert_eq!(next, 3);
+        assert_eq!(data, [0.5, 0.5, -1.0, -1.0, 0.0, 0.0]);
+
+        let mut pcm = [7_i16; 2];
+        assert_eq!(fill_playback_buffer(&mut pcm, 1, &frames, 5), 7);
+        assert_eq!(pcm, [0, 0]);
+    }
 
-    let runtime = apply_registered_hotkey(&app, hotkey_state.inner(), Some(&normalized))?;
+    #[test]
+    fn cached_capture_expires_after_the_retention_window() {
+        let slot = Mutex::new(Some(CachedCapture {
+            samples: vec![0.25, -0.25],
+            captured_at: Instant::now(),
+        }));
+        assert_eq!(
+            take_cached_capture_samples(&slot, Duration::from_secs(300)),
+            Ok(vec![0.25, -0.25])
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(take_cached_capture_samples(&slot, Duration::from_millis(1)).is_err());
+        assert!(slot.lock().unwrap().is_none());
 
-    let settings_path = model_state.settings_path.clone();
-    let mut settings = model_state
-        .settings
-        .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?;
-    settings.dictation_trigger = Some(normalized.clone());
-    settings.dictation_trigger_enabled = Some(true);
-    if let Err(error) = save_local_settings(&settings_path, &settings) {
-        settings.dictation_trigger = previous_trigger_raw;
-        settings.dictation_trigger_enabled = previous_trigger_enabled;
-        drop(settings);
-        if let Err(restore_error) =
-            apply_registered_hotkey(&app, hotkey_state.inner(), previous_trigger.as_deref())
-        {
-            log::warn!("set_dictation_trigger: failed to restore previous hotkey after save error: {restore_error}");
-        }
-        return Err(error);
+        assert_eq!(
+            last_recording_retention_seconds(&LocalSettings::default()),
+            300
+        );
+        let capped = LocalSettings {
+            last_recording_retention_seconds: Some(90_000),
+            ..LocalSettings::default()
+        };
+        assert_eq!(last_recording_retention_seconds(&capped), 3_600);
     }
-    Ok(dictation_trigger_payload(&settings, runtime))
-}
-
-#[tauri::command]
-fn clear_dictation_trigger(
-    app: tauri::AppHandle,
-    model_state: State<'_, LocalModelState>,
-    hotkey_state: State<'_, GlobalHotkeyState>,
-) -> Result<DictationTriggerPayload, String> {
-    let (previous_trigger, previous_trigger_raw, previous_trigger_enabled) = {
-        let settings = model_state
-            .settings
-            .lock()
-            .map_err(|_| "Failed to lock local model settings".to_string())?;
-        (
-            resolve_effective_dictation_trigger(&settings),
-            settings.dictation_trigger.clone(),
-            settings.dictation_trigger_enabled,
-        )
-    };
 
-    let runtime = apply_registered_hotkey(&app, hotkey_state.inner(), None)?;
+    #[test]
+    fn min_transcribe_seconds_defaults_and_ignores_out_of_range_values() {
+        let mut settings = LocalSettings::default();
+        assert_eq!(
+            min_transcribe_seconds(&settings),
+            DEFAULT_MIN_TRANSCRIBE_SECONDS
+        );
+        settings.min_transcribe_seconds = Some(0.15);
+        assert_eq!(min_transcribe_seconds(&settings), 0.15);
+        settings.min_transcribe_seconds = Some(0.01);
+        assert_eq!(
+            min_transcribe_seconds(&settings),
+            DEFAULT_MIN_TRANSCRIBE_SECONDS
+        );
+        assert_eq!(
+            too_short_message(0.18, 0.3),
+            "That was only 0.2s. Hold a bit longer - at least 0.3s - and try again."
+        );
+    }
 
-    let settings_path = model_state.settings_path.clone();
-    let mut settings = model_state
-        .settings
-        .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?;
-    settings.dictation_trigger = None;
-    settings.dictation_trigger_enabled = Some(false);
-    if let Err(error) = save_local_settings(&settings_path, &settings) {
-        settings.dictation_trigger = previous_trigger_raw;
-        settings.dictation_trigger_enabled = previous_trigger_enabled;
-        drop(settings);
-        if let Err(restore_error) =
-            apply_registered_hotkey(&app, hotkey_state.inner(), previous_trigger.as_deref())
-        {
-            log::warn!(
-                "clear_dictation_trigger: failed to restore previous hotkey after save error: {restore_error}"
-            );
-        }
-        return Err(error);
+    #[test]
+    fn sound_cues_fire_once_per_transition_and_respect_disabled_cues() {
+        let payload =
+            |state: &str, transcript: Option<&str>, error: Option<&str>| DictationStatePayload {
+                state: state.to_string(),
+                error: error.map(str::to_string),
+                transcript: transcript.map(str::to_string),
+                session_id: Some(1),
+                scratch: false,
+                warning: None,
+                retried: false,
+                audio_info: None,
+                word_count: None,
+                audio_seconds: None,
+                command: None,
+            };
+        assert_eq!(
+            sound_cue_for_transition(Some("idle"), &payload("listening", None, None)),
+            None
+        );
+        assert_eq!(
+            sound_cue_for_transition(Some("listening"), &payload("listening", None, None)),
+            None
+        );
+        assert_eq!(
+            sound_cue_for_transition(Some("listening"), &payload("processing", None, None)),
+            Some(SoundCue::Stop)
+        );
+        assert_eq!(
+            sound_cue_for_transition(Some("processing"), &payload("idle", Some("hello"), None)),
+            Some(SoundCue::Success)
+        );
+        assert_eq!(
+            sound_cue_for_transition(Some("listening"), &payload("idle", None, None)),
+            None
+        );
+        assert_eq!(
+            sound_cue_for_transition(Some("processing"), &payload("error", None, Some("boom"))),
+            Some(SoundCue::Error)
+        );
+
+        let mut settings = LocalSettings::default();
+        assert!(!sound_cue_enabled(&settings, SoundCue::Start));
+        settings.sound_cues = Some(true);
+        settings.disabled_sound_cues = Some(vec!["success".to_string()]);
+        assert!(sound_cue_enabled(&settings, SoundCue::Start));
+        assert!(!sound_cue_enabled(&settings, SoundCue::Success));
+
+        let samples = render_sound_cue(SoundCue::Stop, 48_000);
+        assert_eq!(samples.len(), 48_000 * 110 / 1000);
+        assert!(samples.iter().all(|sample| sample.abs() <= SOUND_CUE_LEVEL));
+        assert!(samples[0].abs() < 0.001);
     }
-    Ok(dictation_trigger_payload(&settings, runtime))
-}
 
-#[tauri::command]
-fn set_focused_field_insert_enabled(
-    enabled: bool,
-    model_state: State<'_, LocalModelState>,
-) -> Result<FocusedFieldInsertPayload, String> {
-    let permission = focused_field_insert_permission_status(enabled, enabled);
-    let settings_path = model_state.settings_path.clone();
-    let mut settings = model_state
-        .settings
-        .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?;
-    let previous = settings.focused_field_insert_enabled;
-    settings.focused_field_insert_enabled = Some(enabled);
-    if let Err(error) = save_local_settings(&settings_path, &settings) {
-        settings.focused_field_insert_enabled = previous;
-        return Err(error);
+    #[test]
+    fn collapse_repeated_ngrams_keeps_short_legitimate_repetition() {
+        assert_eq!(collapse_repeated_ngrams("you you you you you", 3), "you");
+        assert_eq!(
+            collapse_repeated_ngrams("That was very very good.", 3),
+            "That was very very good."
+        );
+        assert_eq!(
+            collapse_repeated_ngrams("Thank you. Thank you. Thank you. Thank you.", 3),
+            "Thank you."
+        );
+        assert_eq!(
+            collapse_repeated_ngrams("So I I I I think, you, you, you, you.", 3),
+            "So I think, you."
+        );
+        assert_eq!(collapse_repeated_ngrams("no no no no", 5), "no no no no");
+        let off = transcription_options(&LocalSettings::default(), Path::new("/tmp"));
+        assert_eq!(off.dedup_max_repeats, None);
     }
-    Ok(FocusedFieldInsertPayload {
-        enabled: focused_field_insert_enabled(&settings),
-        permission_granted: permission.granted,
-        permission_status: permission.status,
-    })
-}
 
-#[tauri::command]
-fn set_preferred_input_device(
-    device_name: Option<String>,
-    model_state: State<'_, LocalModelState>,
-) -> Result<Option<String>, String> {
-    let normalized = device_name
-        .as_deref()
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-        .map(|value| value.to_string());
-    let available_devices = list_input_devices();
-    if let Some(name) = normalized.as_deref() {
-        if !available_devices.iter().any(|device| device.name == name) {
-            return Err(format!(
-                "Microphone '{}' is not currently available on this machine.",
-                name
-            ));
-        }
+    #[test]
+    fn parse_utterance_command_reads_only_leading_phrases() {
+        assert_eq!(
+            parse_utterance_command("Scratch that."),
+            Some(UtteranceCommand::Scratch)
+        );
+        assert_eq!(parse_utterance_command("Scratch that idea, go on."), None);
+        assert_eq!(
+            parse_utterance_command("Command, copy: the meeting moved to noon."),
+            Some(UtteranceCommand::Copy(
+                "the meeting moved to noon.".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_utterance_command("Command copy."),
+            Some(UtteranceCommand::Copy(String::new()))
+        );
+        assert_eq!(parse_utterance_command("Please command copy this."), None);
+        assert_eq!(parse_utterance_command("Scratch"), None);
     }
 
-    let settings_path = model_state.settings_path.clone();
-    let mut settings = model_state
-        .settings
-        .lock()
-        .map_err(|_| "Failed to lock local model settings".to_string())?;
-    let previous = settings.preferred_input_device.clone();
-    settings.preferred_input_device = normalized.clone();
-    if let Err(error) = save_local_settings(&settings_path, &settings) {
-        settings.preferred_input_device = previous;
-        return Err(error);
+    #[test]
+    fn mask_profanity_matches_whole_words_case_insensitively() {
+        let words = vec!["shit".to_string(), "dick".to_string()];
+        assert_eq!(
+            mask_profanity("Shit, Dick Dickens said 'shit'. Oh shit!", &words),
+            "****, **** Dickens said '****'. Oh ****!"
+        );
+        assert_eq!(mask_profanity("shitake shit's", &words), "shitake shit's");
+        assert_eq!(mask_profanity("Shit happens.", &[]), "Shit happens.");
     }
 
-    Ok(settings.preferred_input_device.clone())
-}
+    #[test]
+    fn profanity_words_validate_and_fall_back_to_defaults() {
+        assert_eq!(
+            validate_profanity_words(&[" Heck ".to_string(), "heck".to_string(), "".to_string()]),
+            Ok(vec!["heck".to_string()])
+        );
+        assert!(validate_profanity_words(&["two words".to_string()]).is_err());
 
-#[cfg(target_os = "macos")]
-fn write_text_to_general_pasteboard(
-    text: &str,
-) -> Result<(Retained<NSPasteboard>, Option<String>), String> {
-    let pasteboard = NSPasteboard::generalPasteboard();
-    let snapshot =
-        unsafe { pasteboard.stringForType(NSPasteboardTypeString) }.map(|value| value.to_string());
+        let mut settings = LocalSettings::default();
+        assert_eq!(
+            profanity_words(&settings).len(),
+            DEFAULT_PROFANITY_WORDS.len()
+        );
+        assert!(transcription_options(&settings, Path::new("/tmp"))
+            .profanity_words
+            .is_empty());
 
-    let _ = pasteboard.clearContents();
-    let ns_text = NSString::from_str(text);
-    if !unsafe { pasteboard.setString_forType(&ns_text, NSPasteboardTypeString) } {
-        return Err("Failed to place dictated text on the macOS pasteboard.".to_string());
+        settings.profanity_filter = Some(true);
+        settings.profanity_words = Some(vec!["bad phrase".to_string()]);
+        assert_eq!(
+            profanity_words(&settings).len(),
+            DEFAULT_PROFANITY_WORDS.len()
+        );
+        settings.profanity_words = Some(vec!["heck".to_string()]);
+        assert_eq!(
+            transcription_options(&settings, Path::new("/tmp")).profanity_words,
+            vec!["heck".to_string()]
+        );
     }
 
-    Ok((pasteboard, snapshot))
-}
+    #[test]
+    fn parse_whisper_progress_line_reads_percentages_only() {
+        assert_eq!(
+            parse_whisper_progress_line("whisper_print_progress_callback: progress =  45%"),
+            Some(45)
+        );
+        assert_eq!(parse_whisper_progress_line("progress = 100%\r"), Some(100));
+        assert_eq!(parse_whisper_progress_line("progress = 250%"), None);
+        assert_eq!(
+            parse_whisper_progress_line("whisper_full_with_state: auto-detected language: en"),
+            None
+        );
+    }
 
-#[cfg(target_os = "macos")]
-fn restore_general_pasteboard(
-    pasteboard: &NSPasteboard,
-    snapshot: Option<String>,
-) -> Result<(), String> {
-    let Some(previous_text) = snapshot else {
-        return Ok(());
-    };
+    #[cfg(unix)]
+    #[test]
+    fn run_command_with_timeout_streams_stderr_lines_and_keeps_output() {
+        let mut command = std::process::Command::new("sh");
+        command
+            .arg("-c")
+            .arg("echo 'progress = 10%' >&2; echo done >&2");
+        let (tx, rx) = std::sync::mpsc::channel();
+        let Ok(output) = run_command_with_timeout(
+            command,
+            Duration::from_secs(5),
+            Some(Box::new(move |line: &str| {
+                let _ = tx.send(line.to_string());
+            })),
+        ) else {
+            panic!("command should run");
+        };
+        let lines: Vec<String> = rx.try_iter().collect();
+        assert_eq!(
+            lines,
+            vec!["progress = 10%".to_string(), "done".to_string()]
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&output.stderr),
+            "progress = 10%\ndone\n"
+        );
+    }
 
-    let _ = pasteboard.clearContents();
-    let ns_text = NSString::from_str(previous_text.as_str());
-    if !unsafe { pasteboard.setString_forType(&ns_text, NSPasteboardTypeString) } {
-        return Err(
-            "Failed to restore the previous macOS pasteboard text after dictation paste."
-                .to_string(),
+    #[test]
+    fn benchmark_word_accuracy_ignores_case_and_punctuation() {
+        assert_eq!(
+            benchmark_word_accuracy(
+                "and so, my fellow Americans: ask not what your country can do for you; ask what you can do for your country",
+                BENCHMARK_EXPECTED_TEXT
+            ),
+            1.0
+        );
+        let accuracy = benchmark_word_accuracy(
+            "And so my fellow Americans ask what your country can do for me",
+            BENCHMARK_EXPECTED_TEXT,
         );
+        assert!(accuracy > 0.0 && accuracy < BENCHMARK_MATCH_ACCURACY);
+        assert_eq!(benchmark_word_accuracy("", BENCHMARK_EXPECTED_TEXT), 0.0);
     }
 
-    Ok(())
-}
+    #[test]
+    fn benchmark_result_reports_real_time_factor() {
+        let result = benchmark_result(
+            "base-en",
+            BENCHMARK_EXPECTED_TEXT.to_string(),
+            11.0,
+            Duration::from_millis(2_750),
+            42,
+        );
+        assert!((result.real_time_factor - 4.0).abs() < 1e-4);
+        assert!(result.matches_expected);
+        assert_eq!(result.measured_at_ms, 42);
 
-#[cfg(target_os = "macos")]
-fn post_keyboard_event(keycode: u16, key_down: bool, flags: CGEventFlags) -> Result<(), String> {
-    let event = unsafe { CGEventCreateKeyboardEvent(std::ptr::null(), keycode, key_down) };
-    if event.is_null() {
-        return Err(format!(
-            "Failed to create macOS keyboard event for keycode {keycode}."
-        ));
+        let instant = benchmark_result("base-en", String::new(), 11.0, Duration::ZERO, 0);
+        assert_eq!(instant.real_time_factor, 0.0);
+        assert!(!instant.matches_expected);
     }
 
-    unsafe {
-        CGEventSetFlags(event, flags);
-        CGEventPost(CG_EVENT_TAP_LOCATION_HID, event);
-        CFRelease(event as *const c_void);
+    #[test]
+    fn bundled_benchmark_clip_rejects_the_placeholder_and_other_clips() {
+        let dir =
+            std::env::temp_dir().join(format!("dicktaint-benchmark-clip-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("temp dir should be created");
+        let clip_path = dir.join(BENCHMARK_CLIP_FILE_NAME);
+        assert!(bundled_benchmark_clip(&dir).is_err());
+
+        std::fs::write(&clip_path, "dicktaint benchmark clip placeholder\n").expect("write stub");
+        let error = bundled_benchmark_clip(&dir).expect_err("placeholder should be rejected");
+        assert!(error.contains("placeholder"));
+
+        write_wav(&clip_path, &vec![0.0; 16_000], 1, 16_000).expect("write short clip");
+        assert!(bundled_benchmark_clip(&dir).is_err());
+
+        write_wav(&clip_path, &vec![0.0; 176_000], 1, 16_000).expect("write reference-length clip");
+        assert_eq!(bundled_benchmark_clip(&dir), Ok(clip_path));
+        let _ = std::fs::remove_dir_all(&dir);
     }
-    Ok(())
-}
 
-#[cfg(target_os = "macos")]
-fn post_command_v_paste() -> Result<(), String> {
-    post_keyboard_event(KEYCODE_COMMAND, true, MACOS_COMMAND_FLAG_MASK)?;
-    post_keyboard_event(KEYCODE_V, true, MACOS_COMMAND_FLAG_MASK)?;
-    post_keyboard_event(KEYCODE_V, false, MACOS_COMMAND_FLAG_MASK)?;
-    post_keyboard_event(KEYCODE_COMMAND, false, 0)?;
-    Ok(())
-}
+    #[test]
+    fn debug_report_is_written_beside_the_settings_file() {
+        let dir =
+            std::env::temp_dir().join(format!("dicktaint-debug-report-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be created");
+        let report = DebugReport {
+            generated_at_ms: 1,
+            app_version: env!("CARGO_PKG_VERSION"),
+            device: build_device_profile(),
+            whisper_cli_path: "whisper-cli".to_string(),
+            whisper_cli_error: Some("not found".to_string()),
+            selected_model_id: Some("base-en".to_string()),
+            model_path: None,
+            model_error: Some("missing".to_string()),
+            dictation_trigger: Some("CmdOrCtrl+Shift+D".to_string()),
+            dictation_trigger_mode: "global-toggle".to_string(),
+            dictation_trigger_status: String::new(),
+            audio_host: "ALSA".to_string(),
+            settings_path: dir
+                .join("dictation-settings.json")
+                .to_string_lossy()
+                .to_string(),
+            models_dir: String::new(),
+            recordings_dir: String::new(),
+            insert_target_app: Some("TextEdit".to_string()),
+            settings: LocalSettings {
+                selected_model_id: Some("base-en".to_string()),
+                ..LocalSettings::default()
+            },
+            saved_path: None,
+        };
 
-#[cfg(target_os = "macos")]
-fn insert_text_into_focused_field_impl(text: &str) -> Result<(), String> {
-    let trimmed = text.trim();
-    if trimmed.is_empty() {
-        return Ok(());
+        let path = write_debug_report(&dir, &report).expect("report should be written");
+        assert_eq!(
+            path.file_name().and_then(|name| name.to_str()),
+            Some(DEBUG_REPORT_FILE)
+        );
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).expect("read report"))
+                .expect("report should be JSON");
+        assert_eq!(written["whisper_cli_error"], "not found");
+        assert_eq!(written["insert_target_app"], "TextEdit");
+        assert_eq!(written["settings"]["selected_model_id"], "base-en");
+        assert!(written["device"]["logical_cpu_cores"].as_u64().is_some());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
-    let permission = focused_field_insert_permission_status(true, true);
-    if !permission.granted {
-        return Err(permission.status);
+    #[test]
+    fn model_download_mirror_requires_https_and_trims_slashes() {
+        assert_eq!(
+            validate_model_download_mirror(" https://models.example.com/whisper/ "),
+            Ok("https://models.example.com/whisper".to_string())
+        );
+        assert!(validate_model_download_mirror("http://models.example.com").is_err());
+        assert!(validate_model_download_mirror("https://").is_err());
+        assert!(validate_model_download_mirror("https://bad host/x").is_err());
+        assert_eq!(
+            model_download_url("https://models.example.com/whisper/", "ggml-base.en.bin"),
+            "https://models.example.com/whisper/ggml-base.en.bin"
+        );
     }
 
-    let (pasteboard, snapshot) = write_text_to_general_pasteboard(trimmed)?;
-    let paste_result = post_command_v_paste();
-    thread::sleep(Duration::from_millis(80));
-    let restore_result = restore_general_pasteboard(&pasteboard, snapshot);
+    #[test]
+    fn model_download_sources_put_custom_mirror_first_without_duplicates() {
+        let mut settings = LocalSettings::default();
+        let known = known_model_download_mirrors(&settings);
+        assert_eq!(
+            known,
+            vec![
+                "https://huggingface.co/ggerganov/whisper.cpp/resolve/main",
+                "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main",
+            ]
+        );
+        assert_eq!(model_download_sources(&settings), known);
 
-    if let Err(error) = restore_result {
-        log::warn!("{error}");
-    }
+        settings.model_download_mirror = Some("https://models.example.com/".to_string());
+        let sources = model_download_sources(&settings);
+        assert_eq!(sources[0], "https://models.example.com");
+        assert_eq!(sources.len(), known.len() + 1);
 
-    paste_result.map_err(|error| {
-        format!(
-            "Focused field insertion failed while sending native paste keystrokes. Allow Accessibility for dicktaint in System Settings > Privacy & Security > Accessibility, then retry. Details: {error}"
-        )
-    })
-}
+        settings.model_download_mirror = Some(known[1].clone());
+        let sources = model_download_sources(&settings);
+        assert_eq!(sources[0], known[1]);
+        assert_eq!(sources.len(), known.len());
 
-#[cfg(not(target_os = "macos"))]
-fn insert_text_into_focused_field_impl(_text: &str) -> Result<(), String> {
-    Err("Focused field insertion is currently supported on macOS desktop only.".to_string())
-}
+        settings.model_download_mirror = Some("ftp://models.example.com".to_string());
+        assert_eq!(model_download_sources(&settings), known);
+    }
 
-#[tauri::command]
-fn insert_text_into_focused_field(
-    state: State<'_, LocalModelState>,
-    text: String,
-) -> Result<(), String> {
-    let focused_field_insert_enabled = {
-        let settings = state
-            .settings
-            .lock()
-            .map_err(|_| "Failed to lock local model settings".to_string())?;
-        focused_field_insert_enabled(&settings)
-    };
-    if !focused_field_insert_enabled {
-        return Err(
-            "Focused-field insertion is disabled in settings. Enable \"Dictate Into Focused Field\" to use this command."
-                .to_string(),
+    #[test]
+    fn powershell_download_resumes_from_the_partial_length() {
+        let fresh = powershell_download_script(
+            "https://example.com/model.bin",
+            "C:\\Users\\o'neil\\model.bin.part",
+            0,
         );
+        assert!(fresh.contains("Create('https://example.com/model.bin')"));
+        assert!(fresh.contains("Open('C:\\Users\\o''neil\\model.bin.part'"));
+        assert!(fresh.contains("if (0 -gt 0)"));
+
+        let resumed =
+            powershell_download_script("https://example.com/model.bin", "model.part", 4096);
+        assert!(resumed.contains("AddRange([int64]4096)"));
+        assert!(resumed.contains("-eq 206) { 'Append' } else { 'Create' }"));
     }
-    insert_text_into_focused_field_impl(&text)
-}
 
-#[tauri::command]
-async fn install_dictation_model(
-    model: String,
-    config: State<'_, AppConfig>,
-    model_state: State<'_, LocalModelState>,
-) -> Result<DictationModelSelection, String> {
-    let configured_whisper_cli_path = resolve_whisper_cli_path(
-        config.whisper_cli_path_override.as_deref(),
-        config.bundled_whisper_cli_path.as_deref(),
-    );
-    let whisper_cli_path = detect_whisper_cli_path(&configured_whisper_cli_path)
-        .unwrap_or(configured_whisper_cli_path);
-    ensure_whisper_cli_available(&whisper_cli_path)?;
-    let trimmed_id = model.trim();
-    if trimmed_id.is_empty() {
-        return Err("Missing model id".to_string());
+    #[test]
+    fn model_download_retries_only_transient_failures() {
+        assert_eq!(
+            download_error_http_status("curl: (22) The requested URL returned error: 404"),
+            Some(404)
+        );
+        assert_eq!(
+            download_error_http_status(
+                "The remote server returned an error: (503) Server Unavailable."
+            ),
+            Some(503)
+        );
+        assert_eq!(
+            download_error_http_status("curl: (6) Could not resolve host"),
+            None
+        );
+
+        assert!(!download_failure_is_retryable(
+            Some(22),
+            "curl: (22) The requested URL returned error: 404"
+        ));
+        assert!(download_failure_is_retryable(
+            Some(22),
+            "curl: (22) The requested URL returned error: 502"
+        ));
+        assert!(download_failure_is_retryable(
+            Some(22),
+            "curl: (22) The requested URL returned error: 429"
+        ));
+        if !cfg!(target_os = "windows") {
+            assert!(download_failure_is_retryable(
+                Some(56),
+                "curl: (56) Recv failure"
+            ));
+            assert!(!download_failure_is_retryable(
+                Some(23),
+                "curl: (23) Failure writing output"
+            ));
+        }
+
+        assert_eq!(
+            model_download_retry_delay(1),
+            MODEL_DOWNLOAD_RETRY_BASE_DELAY
+        );
+        assert_eq!(
+            model_download_retry_delay(2),
+            MODEL_DOWNLOAD_RETRY_BASE_DELAY * 2
+        );
     }
 
-    let model_spec = find_whisper_model_spec(trimmed_id).ok_or_else(|| {
-        let ids = whisper_model_catalog()
-            .iter()
-            .map(|spec| spec.id)
-            .collect::<Vec<_>>()
-            .join(", ");
-        format!("Unsupported dictation model '{trimmed_id}'. Available models: {ids}")
-    })?;
-    let models_dir = model_state.models_dir.clone();
-    let settings_path = model_state.settings_path.clone();
-    let settings = Arc::clone(&model_state.settings);
+    #[test]
+    fn model_repo_and_revision_feed_the_download_sources() {
+        assert_eq!(
+            validate_model_repo(" distil-whisper/distil-large-v3-ggml/ "),
+            Ok("distil-whisper/distil-large-v3-ggml".to_string())
+        );
+        for bad in ["whisper.cpp", "a/b/c", "../x", "owner/na me", "/name"] {
+            assert!(
+                validate_model_repo(bad).is_err(),
+                "{bad} should be rejected"
+            );
+        }
+        assert!(validate_model_revision("5359861c739e955e79d9a303bcbc70fb988958b1").is_ok());
+        for bad in ["", "main/", "../main", "v1?x=1"] {
+            assert!(
+                validate_model_revision(bad).is_err(),
+                "{bad} should be rejected"
+            );
+        }
+
+        let settings = LocalSettings {
+            model_repo: Some("distil-whisper/distil-large-v3-ggml".to_string()),
+            model_revision: Some("refs/pr/1".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            known_model_download_mirrors(&settings)[0],
+            "https://huggingface.co/distil-whisper/distil-large-v3-ggml/resolve/refs%2Fpr%2F1"
+        );
 
-    let install_task =
-        tauri::async_runtime::spawn_blocking(move || -> Result<DictationModelSelection, String> {
-            fs::create_dir_all(&models_dir).map_err(|e| {
-                format!(
-                    "Failed to create model directory {}: {e}",
-                    models_dir.display()
-                )
-            })?;
+        let stale = LocalSettings {
+            model_repo: Some("not a repo".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            known_model_download_mirrors(&stale),
+            known_model_download_mirrors(&LocalSettings::default())
+        );
+    }
 
-            let target_path = model_path_for_spec(&models_dir, model_spec);
-            if !target_path.exists() {
-                download_whisper_model(model_spec, &target_path)?;
-                if !target_path.exists() {
-                    return Err(format!(
-                        "Model download completed but file is still missing at {}.",
-                        target_path.display()
-                    ));
+    #[test]
+    fn auto_format_capitalizes_sentences_and_closes_the_last_one() {
+        let on = AutoFormatOptions {
+            enabled: true,
+            append_period: true,
+        };
+        assert_eq!(
+            auto_format_transcript("so i think we ship it. then what? we wait for it", on),
+            "So I think we ship it. Then what? We wait for it."
+        );
+        assert_eq!(
+            auto_format_transcript("\"hello there\" she said, like this", on),
+            "\"Hello there\" she said, like this."
+        );
+        assert_eq!(auto_format_transcript("okay", on), "Okay");
+        assert_eq!(auto_format_transcript("are you sure?", on), "Are you sure?");
+        assert_eq!(
+            auto_format_transcript(
+                "we ship it",
+                AutoFormatOptions {
+                    append_period: false,
+                    ..on
                 }
-            }
+            ),
+            "We ship it"
+        );
+        assert_eq!(
+            auto_format_transcript("so i think we ship it", AutoFormatOptions::default()),
+            "so i think we ship it"
+        );
+    }
 
-            let selected_model_path = target_path.to_string_lossy().to_string();
-            {
-                let mut settings = settings
-                    .lock()
-                    .map_err(|_| "Failed to lock local model settings".to_string())?;
-                settings.selected_model_id = Some(model_spec.id.to_string());
-                settings.selected_model_path = Some(selected_model_path.clone());
-                save_local_settings(&settings_path, &settings)?;
+    #[test]
+    fn auto_format_leaves_technical_sentences_alone() {
+        let on = AutoFormatOptions {
+            enabled: true,
+            append_period: true,
+        };
+        assert_eq!(
+            auto_format_transcript("npm install -g typescript", on),
+            "npm install -g typescript"
+        );
+        assert_eq!(
+            auto_format_transcript("open src/main.rs now. it is long", on),
+            "open src/main.rs now. It is long."
+        );
+        assert_eq!(
+            auto_format_transcript("use iPhone mode for the test", on),
+            "use iPhone mode for the test"
+        );
+        assert_eq!(
+            auto_format_options(&LocalSettings::default()),
+            AutoFormatOptions {
+                enabled: false,
+                append_period: true,
             }
+        );
+    }
 
-            Ok(DictationModelSelection {
-                selected_model_id: model_spec.id.to_string(),
-                selected_model_path,
-                installed: true,
-            })
-        });
+    #[test]
+    fn insert_wrap_formats_transcripts_for_markdown() {
+        assert_eq!(
+            apply_insert_wrap("let x = 1".to_string(), InsertWrap::InlineCode),
+            "`let x = 1`"
+        );
+        assert_eq!(
+            apply_insert_wrap("run `make` first".to_string(), InsertWrap::InlineCode),
+            "``run `make` first``"
+        );
+        assert_eq!(
+            apply_insert_wrap(" fn main() {} ".to_string(), InsertWrap::CodeBlock),
+            "```\nfn main() {}\n```"
+        );
+        assert_eq!(
+            apply_insert_wrap("first\n\nsecond".to_string(), InsertWrap::Quote),
+            "> first\n>\n> second"
+        );
+        assert_eq!(
+            apply_insert_wrap("left alone".to_string(), InsertWrap::None),
+            "left alone"
+        );
+        assert_eq!(apply_insert_wrap(" ".to_string(), InsertWrap::Quote), " ");
+        assert_eq!(insert_wrap(&LocalSettings::default()), InsertWrap::None);
+        assert_eq!(InsertWrap::parse("Code-Block"), Some(InsertWrap::CodeBlock));
+        assert_eq!(InsertWrap::parse("fenced"), None);
+    }
 
-    install_task
-        .await
-        .map_err(|e| format!("Model install task failed: {e}"))?
-}
+    #[test]
+    fn output_suffix_appends_exactly_one_separator() {
+        assert_eq!(
+            apply_output_suffix("Hello there.".to_string(), OutputSuffix::None),
+            "Hello there."
+        );
+        assert_eq!(
+            apply_output_suffix("Hello there.".to_string(), OutputSuffix::Space),
+            "Hello there. "
+        );
+        assert_eq!(
+            apply_output_suffix("Hello there.\n ".to_string(), OutputSuffix::Newline),
+            "Hello there.\n"
+        );
+        assert_eq!(apply_output_suffix(String::new(), OutputSuffix::Space), "");
+        assert_eq!(output_suffix(&LocalSettings::default()), OutputSuffix::None);
+        assert_eq!(
+            OutputSuffix::parse(" Newline "),
+            Some(OutputSuffix::Newline)
+        );
+        assert_eq!(OutputSuffix::parse("tab"), None);
+    }
 
-#[tauri::command]
-async fn delete_dictation_model(
-    model: String,
-    model_state: State<'_, LocalModelState>,
-) -> Result<DictationModelDeletion, String> {
-    let trimmed_id = model.trim();
-    if trimmed_id.is_empty() {
-        return Err("Missing model id".to_string());
+    #[test]
+    fn self_test_report_fails_only_on_failed_stages() {
+        let report = self_test_report(vec![
+            self_test_stage("whisper-cli", SelfTestStatus::Pass, "ok"),
+            self_test_stage("transcription", SelfTestStatus::Skip, "skipped"),
+        ]);
+        assert!(report.passed);
+
+        let report = self_test_report(vec![
+            self_test_stage_from_result("model", Err("missing".to_string())),
+            self_test_stage("transcription", SelfTestStatus::Skip, "skipped"),
+        ]);
+        assert!(!report.passed);
+        assert_eq!(report.stages[0].status, "fail");
+        assert_eq!(report.stages[0].message, "missing");
     }
 
-    let model_spec = find_whisper_model_spec(trimmed_id).ok_or_else(|| {
-        let ids = whisper_model_catalog()
-            .iter()
-            .map(|spec| spec.id)
-            .collect::<Vec<_>>()
-            .join(", ");
-        format!("Unsupported dictation model '{trimmed_id}'. Available models: {ids}")
-    })?;
+    #[test]
+    fn check_model_file_readable_requires_ggml_header() {
+        let dir = std::env::temp_dir().join(format!("dicktaint-self-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be created");
+        let valid = dir.join("ggml-valid.bin");
+        let invalid = dir.join("ggml-invalid.bin");
+        std::fs::write(&valid, [b'l', b'm', b'g', b'g', 0, 0]).expect("write model");
+        std::fs::write(&invalid, b"<html>").expect("write model");
+
+        assert!(check_model_file_readable(&valid).is_ok());
+        assert!(check_model_file_readable(&invalid)
+            .unwrap_err()
+            .contains("not a ggml whisper model"));
+        assert!(check_model_file_readable(&dir.join("missing.bin")).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-    let models_dir = model_state.models_dir.clone();
-    let settings_path = model_state.settings_path.clone();
-    let settings = Arc::clone(&model_state.settings);
-    let total_memory_gb = system_memory_gb();
+    #[cfg(unix)]
+    #[test]
+    fn whisper_cli_is_placeholder_reports_the_stub_sidecar_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir =
+            std::env::temp_dir().join(format!("dicktaint-placeholder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be created");
+        let stub = dir.join("whisper-cli-stub");
+        std::fs::write(
+            &stub,
+            "#!/bin/sh\necho \"Bundled whisper-cli placeholder. Replace with a real whisper-cli sidecar binary.\" >&2\nexit 1\n",
+        )
+        .expect("write stub");
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod stub");
+        let stub_path = stub.to_string_lossy().to_string();
+
+        let resolved = whisper_cli_is_placeholder(&stub_path).expect("stub should be detected");
+        assert_eq!(resolved.file_name(), stub.file_name());
+        let error = ensure_whisper_cli_available(&stub_path).unwrap_err();
+        assert_eq!(error.code, "WHISPER_CLI_PLACEHOLDER");
+        assert!(error.message.contains("placeholder whisper-cli sidecar"));
+        assert!(whisper_cli_is_placeholder(&dir.join("missing").to_string_lossy()).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-    let delete_task =
-        tauri::async_runtime::spawn_blocking(move || -> Result<DictationModelDeletion, String> {
-            let target_path = model_path_for_spec(&models_dir, model_spec);
-            if let Err(e) = fs::remove_file(&target_path) {
-                if e.kind() != std::io::ErrorKind::NotFound {
-                    return Err(format!(
-                        "Failed to delete model '{}' at {}: {e}",
-                        model_spec.id,
-                        target_path.display()
-                    ));
-                }
-            }
+    #[test]
+    fn input_config_for_rate_prefers_a_matching_range_then_the_nearest_rate() {
+        let range = |min: u32, max: u32, format: SampleFormat| {
+            cpal::SupportedStreamConfigRange::new(
+                2,
+                cpal::SampleRate(min),
+                cpal::SampleRate(max),
+                cpal::SupportedBufferSize::Unknown,
+                format,
+            )
+        };
+        let ranges = vec![
+            range(96_000, 192_000, SampleFormat::F32),
+            range(44_100, 48_000, SampleFormat::I16),
+            range(8_000, 16_000, SampleFormat::U8),
+        ];
+
+        let exact = input_config_for_rate(ranges.clone(), 48_000).expect("48k is supported");
+        assert_eq!(exact.sample_rate().0, 48_000);
+        assert_eq!(exact.sample_format(), SampleFormat::I16);
+        let nearest = input_config_for_rate(ranges.clone(), 88_200).expect("a nearest rate");
+        assert_eq!(nearest.sample_rate().0, 96_000);
+        let low = input_config_for_rate(ranges, 16_000).expect("unranked formats are skipped");
+        assert_eq!(low.sample_rate().0, 44_100);
+        assert!(input_config_for_rate(Vec::new(), 48_000).is_none());
 
-            let target_path_string = target_path.to_string_lossy().to_string();
-            let (selected_model_id, selected_model_path) = {
-                let mut settings = settings
-                    .lock()
-                    .map_err(|_| "Failed to lock local model settings".to_string())?;
+        let settings = LocalSettings {
+            preferred_sample_rate: Some(4_000),
+            ..LocalSettings::default()
+        };
+        assert_eq!(preferred_sample_rate(&settings), None);
+    }
 
-                let deleted_selected_model = settings.selected_model_id.as_deref()
-                    == Some(model_spec.id)
-                    || settings
-                        .selected_model_path
-                        .as_deref()
-                        .is_some_and(|path| path == target_path_string);
+    #[cfg(unix)]
+    #[test]
+    fn diagnose_whisper_cli_candidate_reports_each_outcome() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("dicktaint-diagnose-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be created");
+        let write_stub = |name: &str, body: &str, mode: u32| {
+            let path = dir.join(name);
+            std::fs::write(&path, body).expect("write stub");
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))
+                .expect("chmod stub");
+            path.to_string_lossy().to_string()
+        };
+        let working = write_stub(
+            "whisper-cli-ok",
+            "#!/bin/sh\necho \"usage: whisper-cli [options] file0.wav\"\necho \"  -m FNAME, --model FNAME\"\n",
+            0o755,
+        );
+        let wrong = write_stub("whisper-cli-wrong", "#!/bin/sh\necho \"hello\"\n", 0o755);
+        let locked = write_stub("whisper-cli-locked", "#!/bin/sh\n", 0o644);
+        let missing = dir.join("missing").to_string_lossy().to_string();
+
+        let ok = diagnose_whisper_cli_candidate(&working);
+        assert_eq!(ok.status, "ok");
+        assert_eq!(ok.resolved_path.as_deref(), Some(working.as_str()));
+        let wrong = diagnose_whisper_cli_candidate(&wrong);
+        assert_eq!(wrong.status, "wrong_help_output");
+        assert_eq!(wrong.probe_summary.as_deref(), Some("hello"));
+        assert_eq!(
+            diagnose_whisper_cli_candidate(&locked).status,
+            "not_executable"
+        );
+        let not_found = diagnose_whisper_cli_candidate(&missing);
+        assert_eq!(not_found.status, "not_found");
+        assert!(not_found.resolved_path.is_none());
 
-                if deleted_selected_model {
-                    if let Some((fallback_spec, fallback_path)) =
-                        pick_best_installed_model(&models_dir, total_memory_gb, Some(model_spec.id))
-                    {
-                        settings.selected_model_id = Some(fallback_spec.id.to_string());
-                        settings.selected_model_path =
-                            Some(fallback_path.to_string_lossy().to_string());
-                    } else {
-                        settings.selected_model_id = None;
-                        settings.selected_model_path = None;
-                    }
-                    save_local_settings(&settings_path, &settings)?;
-                }
+        let order = whisper_cli_search_order(
+            Some(&missing),
+            Some(&working),
+            None,
+            std::slice::from_ref(&working),
+        );
+        assert_eq!(order[0], missing);
+        assert_eq!(order[1], working);
+        assert_eq!(order.iter().filter(|path| **path == working).count(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-                (
-                    settings.selected_model_id.clone(),
-                    settings.selected_model_path.clone(),
-                )
-            };
+    #[cfg(unix)]
+    #[test]
+    fn saved_whisper_cli_path_beats_bundled_path_while_it_runs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("dicktaint-saved-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be created");
+        let stub = dir.join("whisper-cli-custom");
+        std::fs::write(
+            &stub,
+            "#!/bin/sh\necho \"usage: whisper-cli [options] file0.wav\"\necho \"  -m FNAME, --model FNAME\"\n",
+        )
+        .expect("write stub");
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod stub");
+        let stub_path = stub.to_string_lossy().to_string();
+        let bundled = dir.join("missing-sidecar").to_string_lossy().to_string();
 
-            Ok(DictationModelDeletion {
-                deleted_model_id: model_spec.id.to_string(),
-                selected_model_id,
-                selected_model_path,
-            })
-        });
+        assert_eq!(
+            resolve_whisper_cli_path(None, Some(&stub_path), Some(&bundled), &[]),
+            stub_path
+        );
+        assert_eq!(
+            probe_whisper_cli_path(None, Some(&stub_path), Some(&bundled), &[]),
+            (stub_path.clone(), true)
+        );
+        let stale = dir.join("deleted-build").to_string_lossy().to_string();
+        assert_ne!(
+            resolve_whisper_cli_path(None, Some(&stale), Some(&bundled), &[]),
+            stale
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-    delete_task
-        .await
-        .map_err(|e| format!("Model delete task failed: {e}"))?
-}
+    #[test]
+    fn rms_normalization_hits_target_rms_and_limits_transients() {
+        let mut samples: Vec<f32> = (0..16_000)
+            .map(|index| (index as f32 * 0.05).sin() * 0.05)
+            .collect();
+        for index in (0..samples.len()).step_by(1_000) {
+            samples[index] = 0.5;
+        }
+        let settings = LocalSettings {
+            normalization_mode: Some("rms".to_string()),
+            normalization_target_rms: Some(0.12),
+            ..LocalSettings::default()
+        };
+        let options = audio_prep_options(&settings);
+        assert_eq!(options.normalization_target_level, 0.12);
 
-fn start_native_dictation_inner(app: &tauri::AppHandle) -> Result<u64, String> {
-    let config = app.state::<AppConfig>();
-    let model_state = app.state::<LocalModelState>();
-    let dictation = app.state::<DictationState>();
+        let stats = analyze_audio_signal(&samples, 16_000);
+        let normalized = normalize_audio_level(samples, stats, options);
+        let normalized_stats = analyze_audio_signal(&normalized, 16_000);
+        assert!(
+            (normalized_stats.rms - 0.12).abs() < 0.01,
+            "normalized rms {}",
+            normalized_stats.rms
+        );
+        assert!(normalized_stats.peak_abs < 1.0);
+        assert!(normalized_stats.peak_abs > RMS_LIMITER_THRESHOLD);
+    }
 
-    ensure_microphone_access_authorized(app)?;
-    resolve_active_model_path(config.inner(), model_state.inner())?;
-    let configured_whisper_cli_path = resolve_whisper_cli_path(
-        config.whisper_cli_path_override.as_deref(),
-        config.bundled_whisper_cli_path.as_deref(),
-    );
-    let whisper_cli_path = detect_whisper_cli_path(&configured_whisper_cli_path)
-        .unwrap_or(configured_whisper_cli_path);
-    ensure_whisper_cli_available(&whisper_cli_path)?;
+    #[test]
+    fn normalization_target_rms_falls_back_to_legacy_level_in_rms_mode() {
+        let legacy = LocalSettings {
+            normalization_mode: Some("rms".to_string()),
+            normalization_target_level: Some(0.2),
+            ..LocalSettings::default()
+        };
+        assert_eq!(normalization_target_rms(&legacy), 0.2);
 
-    let mut guard = dictation
-        .active_recording
-        .lock()
-        .map_err(|_| "Failed to lock dictation state".to_string())?;
-    if guard.is_some() {
-        return Err("Dictation already running.".to_string());
+        let peak = LocalSettings {
+            normalization_target_level: Some(0.6),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            normalization_target_rms(&peak),
+            DEFAULT_TARGET_TRANSCRIPTION_AUDIO_RMS
+        );
+        assert_eq!(audio_prep_options(&peak).normalization_target_level, 0.6);
+        assert_eq!(soft_limit_sample(-0.5), -0.5);
+        assert!(soft_limit_sample(-4.0) > -1.0);
     }
 
-    let session_id = dictation.next_session_id.fetch_add(1, Ordering::SeqCst);
-    let samples = Arc::new(Mutex::new(Vec::<f32>::new()));
-    let (stop_tx, thread_handle, sample_rate, input_device_name) =
-        spawn_recording_thread(Arc::clone(&samples), app.clone(), session_id)?;
-    *guard = Some(ActiveRecording {
-        session_id,
-        input_device_name,
-        stop_tx,
-        thread_handle,
-        samples,
-        sample_rate,
-    });
-    drop(guard);
-
-    emit_dictation_state(app, "listening", None, None, Some(session_id));
-    Ok(session_id)
-}
+    #[test]
+    fn trim_silence_defaults_on_and_can_be_disabled() {
+        assert!(audio_prep_options(&LocalSettings::default()).trim_silence);
+        let raw = LocalSettings {
+            trim_silence: Some(false),
+            ..LocalSettings::default()
+        };
+        let options = audio_prep_options(&raw);
+        assert!(!options.trim_silence);
+        assert_eq!(options.normalization_mode, AudioNormalizationMode::Peak);
+    }
 
-async fn stop_native_dictation_inner(app: tauri::AppHandle) -> Result<String, String> {
-    let recording = {
-        let dictation = app.state::<DictationState>();
-        let mut guard = dictation
-            .active_recording
-            .lock()
-            .map_err(|_| "Failed to lock dictation state".to_string())?;
-        guard
-            .take()
-            .ok_or_else(|| "Dictation is not running.".to_string())?
-    };
-    let session_id = recording.session_id;
+    #[test]
+    fn imported_models_get_unique_ids_and_list_after_the_catalog() {
+        let dir = std::env::temp_dir().join(format!("dicktaint-imported-{}", std::process::id()));
+        let source = Path::new("/tmp/My Fine_Tune.v2.bin");
+        let mut settings = LocalSettings::default();
+        assert_eq!(
+            new_imported_model_id(source, &settings),
+            "imported-my-fine-tune-v2"
+        );
+        settings.imported_models = Some(vec![ImportedModel {
+            id: "imported-my-fine-tune-v2".to_string(),
+            display_name: "Fine tune".to_string(),
+            source_path: source.to_string_lossy().to_string(),
+            added_at_ms: 7,
+        }]);
+        assert_eq!(
+            new_imported_model_id(source, &settings),
+            "imported-my-fine-tune-v2-2"
+        );
+        assert_eq!(
+            new_imported_model_id(Path::new("/tmp/---.bin"), &settings),
+            "imported-model"
+        );
+        assert!(validate_imported_model_name("   ").is_err());
+        assert_eq!(
+            validate_imported_model_name(" Legal  terms "),
+            Ok("Legal  terms".to_string())
+        );
 
-    let _ = recording.stop_tx.send(());
-    if recording.thread_handle.join().is_err() {
-        emit_dictation_state(
-            &app,
-            "error",
-            Some("Audio capture thread crashed.".into()),
-            None,
-            Some(session_id),
+        let path = imported_model_path(&dir, "imported-my-fine-tune-v2");
+        std::fs::create_dir_all(path.parent().expect("imported dir")).expect("dir");
+        std::fs::write(&path, b"lmgg").expect("model file");
+        let options = build_model_options(
+            &dir,
+            16,
+            8,
+            SimdSupport::Full,
+            Some("imported-my-fine-tune-v2"),
+            &settings,
+        );
+        let imported = options.last().expect("imported model is listed");
+        assert!(imported.imported && imported.installed && !imported.recommended);
+        assert_eq!(imported.display_name, "Fine tune (Selected)");
+        assert_eq!(
+            imported.source_path.as_deref(),
+            Some("/tmp/My Fine_Tune.v2.bin")
         );
-        return Err("Audio capture thread crashed.".to_string());
+        assert_eq!(options.iter().filter(|option| option.imported).count(), 1);
+        assert_eq!(
+            closest_catalog_spec_by_size(4).map(|spec| spec.relative_compute),
+            Some(1.0)
+        );
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
-    let captured_samples = recording
-        .samples
-        .lock()
-        .map_err(|_| "Failed to read captured audio".to_string())?
-        .clone();
-    let model_path = {
-        let config = app.state::<AppConfig>();
-        let model_state = app.state::<LocalModelState>();
-        resolve_active_model_path(config.inner(), model_state.inner())?
-    };
-    let configured_whisper_cli_path = {
-        let config = app.state::<AppConfig>();
-        resolve_whisper_cli_path(
-            config.whisper_cli_path_override.as_deref(),
-            config.bundled_whisper_cli_path.as_deref(),
-        )
-    };
-    let whisper_cli_path = detect_whisper_cli_path(&configured_whisper_cli_path)
-        .unwrap_or(configured_whisper_cli_path);
+    #[test]
+    fn model_runnability_explains_ram_cpu_and_disk_verdicts() {
+        let large = find_whisper_model_spec("large-v3").expect("large-v3 spec");
+        let tiny = find_whisper_model_spec("tiny-en").expect("tiny-en spec");
+        let plenty = Some(100 * 1_073_741_824);
 
-    emit_dictation_state(&app, "processing", None, None, Some(session_id));
+        let no_ram = model_runnability(Some(large), false, 4, 8, SimdSupport::Full, plenty);
+        assert_eq!(no_ram.ram.status, CheckStatus::Fail);
+        assert_eq!(no_ram.verdict, CheckStatus::Fail);
+        assert_eq!(no_ram.summary, "Won't fit in RAM");
 
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        transcribe_samples(
-            model_path,
-            whisper_cli_path,
-            captured_samples,
-            recording.sample_rate,
-            recording.input_device_name,
-        )
-    })
-    .await
-    .map_err(|e| {
-        emit_dictation_state(&app, "error", Some(e.to_string()), None, Some(session_id));
-        format!("Failed to run transcription task: {e}")
-    })?;
+        let slow = model_runnability(Some(large), true, 64, 2, SimdSupport::Full, None);
+        assert_eq!(slow.cpu.status, CheckStatus::Warn);
+        assert_eq!(slow.disk.status, CheckStatus::Pass);
+        assert_eq!(slow.summary, "Runnable but slow");
 
-    match result {
-        Ok(transcript) => {
-            emit_dictation_state(
-                &app,
-                "idle",
-                None,
-                Some(transcript.clone()),
-                Some(session_id),
-            );
-            Ok(transcript)
-        }
-        Err(e) => {
-            emit_dictation_state(&app, "error", Some(e.clone()), None, Some(session_id));
-            Err(e)
-        }
+        let no_disk = model_runnability(Some(large), false, 64, 8, SimdSupport::Full, Some(1024));
+        assert_eq!(no_disk.disk.status, CheckStatus::Fail);
+        assert_eq!(no_disk.summary, "Not enough disk to download");
+
+        let fine = model_runnability(Some(tiny), false, 16, 8, SimdSupport::Full, plenty);
+        assert_eq!(fine.verdict, CheckStatus::Pass);
+        assert_eq!(fine.summary, "Runs well on this machine");
+
+        let missing_import = model_runnability(None, false, 16, 8, SimdSupport::Full, plenty);
+        assert_eq!(missing_import.summary, "Model file missing");
+
+        assert_eq!(
+            parse_df_available_kib(
+                "Filesystem 1024-blocks Used Available Capacity Mounted on\n/dev/disk3s5 482797652 401234567 81563085 84% /System/Volumes/Data\n"
+            ),
+            Some(81_563_085)
+        );
+        assert_eq!(parse_df_available_kib("garbage"), None);
     }
-}
 
-fn cancel_native_dictation_inner(app: &tauri::AppHandle) -> Result<(), String> {
-    let recording = {
-        let dictation = app.state::<DictationState>();
-        let mut guard = dictation
-            .active_recording
-            .lock()
-            .map_err(|_| "Failed to lock dictation state".to_string())?;
-        guard.take()
-    };
-    let session_id = recording.as_ref().map(|value| value.session_id);
+    #[test]
+    fn oom_failures_fall_back_to_a_smaller_installed_model() {
+        assert!(whisper_error_is_oom(
+            "ggml_backend_metal_buffer_type_alloc_buffer: error: failed to allocate buffer"
+        ));
+        assert!(whisper_error_is_oom("CUDA error: out of memory"));
+        assert!(whisper_error_is_oom(
+            "terminate called after throwing std::bad_alloc"
+        ));
+        assert!(!whisper_error_is_oom("error: failed to open 'missing.wav'"));
+
+        let dir = std::env::temp_dir().join(format!("dicktaint-oom-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be created");
+        let write_model = |id: &str, size: usize| {
+            let spec = find_whisper_model_spec(id).expect("catalog model");
+            let path = model_path_for_spec(&dir, spec);
+            std::fs::write(&path, vec![0_u8; size]).expect("model file");
+            path
+        };
+        let tiny = write_model("tiny-en", 10);
+        write_model("base-en", 20);
+        let large = write_model("large-v3", 100);
+
+        let (fallback, fallback_path) =
+            pick_oom_fallback_model(&dir, 16, &large).expect("smaller models are installed");
+        assert_eq!(fallback.id, "base-en");
+        assert!(oom_fallback_warning(&fallback_path).contains("base-en"));
+        assert!(oom_fallback_hint(false, None).contains("enable fallback_on_oom"));
+        assert!(oom_fallback_hint(true, None).contains("no smaller installed model"));
+        assert!(oom_fallback_hint(true, Some(&fallback_path)).contains("base-en"));
+        assert!(!oom_fallback_hint(true, Some(&fallback_path)).contains("enable"));
+        assert!(pick_oom_fallback_model(&dir, 16, &tiny).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-    if let Some(recording) = recording {
-        let _ = recording.stop_tx.send(());
-        let _ = recording.thread_handle.join();
+    #[test]
+    fn model_size_is_suspect_flags_truncated_downloads() {
+        let spec = find_whisper_model_spec("base-en").expect("base-en is in the catalog");
+        assert!(!model_size_is_suspect(spec, 147_964_211));
+        assert!(model_size_is_suspect(spec, 12_582_912));
+        assert!(model_size_is_suspect(spec, 0));
     }
 
-    emit_dictation_state(app, "idle", None, None, session_id);
-    Ok(())
-}
+    #[test]
+    fn whisper_threads_override_and_battery_saver_cap() {
+        assert_eq!(effective_whisper_threads(None, false), None);
+        assert_eq!(effective_whisper_threads(Some(6), false), Some(6));
+        assert_eq!(effective_whisper_threads(None, true), Some(2));
+        assert_eq!(effective_whisper_threads(Some(6), true), Some(2));
+        assert_eq!(effective_whisper_threads(Some(1), true), Some(1));
+
+        let oversized = LocalSettings {
+            whisper_threads: Some(usize::MAX),
+            ..LocalSettings::default()
+        };
+        assert_eq!(configured_whisper_threads(&oversized), None);
+        let single = LocalSettings {
+            whisper_threads: Some(1),
+            ..LocalSettings::default()
+        };
+        assert_eq!(configured_whisper_threads(&single), Some(1));
+    }
 
-#[tauri::command]
-fn start_native_dictation(app: tauri::AppHandle) -> Result<(), String> {
-    start_native_dictation_inner(&app).map(|_| ())
-}
+    #[test]
+    fn reconcile_removes_partials_and_repoints_missing_selection() {
+        let models_dir =
+            std::env::temp_dir().join(format!("dicktaint-reconcile-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&models_dir);
+        std::fs::create_dir_all(&models_dir).expect("create test dir");
+        let tiny = find_whisper_model_spec("tiny-en").expect("tiny-en is in the catalog");
+        let tiny_path = model_path_for_spec(&models_dir, tiny);
+        std::fs::write(&tiny_path, b"model").expect("write model");
+        let partial = partial_download_path(&models_dir.join("ggml-base.en.bin"));
+        std::fs::write(&partial, b"half").expect("write partial");
+
+        assert_eq!(remove_partial_downloads(&models_dir), vec![partial.clone()]);
+        assert!(!partial.exists());
+        assert!(tiny_path.exists());
+
+        let mut settings = LocalSettings {
+            selected_model_id: Some("base-en".to_string()),
+            selected_model_path: Some(
+                models_dir
+                    .join("ggml-base.en.bin")
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            ..LocalSettings::default()
+        };
+        assert!(reconcile_selected_model(&mut settings, &models_dir, 16));
+        assert_eq!(settings.selected_model_id.as_deref(), Some("tiny-en"));
+        assert!(!reconcile_selected_model(&mut settings, &models_dir, 16));
+
+        std::fs::remove_file(&tiny_path).expect("remove model");
+        assert!(reconcile_selected_model(&mut settings, &models_dir, 16));
+        assert_eq!(settings.selected_model_path, None);
+        let _ = std::fs::remove_dir_all(&models_dir);
+    }
 
-#[tauri::command]
-async fn stop_native_dictation(app: tauri::AppHandle) -> Result<String, String> {
-    stop_native_dictation_inner(app).await
-}
+    #[test]
+    fn delete_catalog_model_files_reports_freed_bytes_and_skips_override() {
+        let models_dir =
+            std::env::temp_dir().join(format!("dicktaint-delete-all-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&models_dir);
+        std::fs::create_dir_all(&models_dir).expect("create test dir");
+        let tiny = find_whisper_model_spec("tiny-en").expect("tiny-en is in the catalog");
+        let base = find_whisper_model_spec("base-en").expect("base-en is in the catalog");
+        let tiny_path = model_path_for_spec(&models_dir, tiny);
+        let base_path = model_path_for_spec(&models_dir, base);
+        std::fs::write(&tiny_path, vec![0_u8; 10]).expect("write model");
+        std::fs::write(partial_download_path(&tiny_path), vec![0_u8; 5]).expect("write partial");
+        std::fs::write(&base_path, vec![0_u8; 20]).expect("write model");
+        std::fs::write(models_dir.join("benchmark-jfk.wav"), b"clip").expect("write clip");
+
+        let deletion = delete_catalog_model_files(&models_dir, Some(&base_path));
+        assert_eq!(deletion.bytes_freed, 15);
+        assert_eq!(deletion.deleted.len(), 2);
+        assert_eq!(deletion.not_deleted.len(), 1);
+        assert_eq!(deletion.not_deleted[0].model_id, "base-en");
+        assert!(!tiny_path.exists());
+        assert!(base_path.exists());
+        assert!(models_dir.join("benchmark-jfk.wav").exists());
+        let _ = std::fs::remove_dir_all(&models_dir);
+    }
 
-#[tauri::command]
-fn cancel_native_dictation(app: tauri::AppHandle) -> Result<(), String> {
-    cancel_native_dictation_inner(&app)
-}
+    #[test]
+    fn window_geometry_is_clamped_onto_a_connected_monitor() {
+        let primary = MonitorRect {
+            x: 0,
+            y: 0,
+            width: 1440,
+            height: 900,
+        };
+        let external = MonitorRect {
+            x: 1440,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        };
+        let on_external = WindowGeometry {
+            x: 1600,
+            y: 100,
+            width: 1000,
+            height: 800,
+        };
+        assert_eq!(
+            clamp_window_geometry(on_external, &[primary, external]),
+            Some(on_external)
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::{
-        analyze_audio_signal, audio_signal_is_too_quiet, default_dictation_trigger,
-        focused_field_insert_enabled, normalize_audio_gain, normalize_dictation_trigger,
-        onboarding_runtime_details, preferred_whisper_cli_names, quiet_audio_error,
-        resample_linear, resolve_effective_dictation_trigger, runtime_details_for_trigger,
-        wait_for_non_silent_input, whisper_help_text_looks_valid, HotkeyDeliveryMode,
-        LocalSettings,
-    };
-    use std::sync::{Arc, Mutex};
+        let overhanging = WindowGeometry {
+            x: 1000,
+            y: 500,
+            width: 1000,
+            height: 800,
+        };
+        assert_eq!(
+            clamp_window_geometry(overhanging, &[primary]),
+            Some(WindowGeometry {
+                x: 440,
+                y: 100,
+                width: 1000,
+                height: 800
+            })
+        );
 
-    #[cfg(target_os = "macos")]
-    use super::should_focus_main_window_for_microphone_prompt;
-    #[cfg(target_os = "macos")]
-    use objc2_av_foundation::AVAuthorizationStatus;
+        // The external display is gone: the window is centered on the remaining one.
+        assert_eq!(
+            clamp_window_geometry(on_external, &[primary]),
+            Some(WindowGeometry {
+                x: 220,
+                y: 50,
+                width: 1000,
+                height: 800
+            })
+        );
+
+        let oversized = WindowGeometry {
+            x: 3000,
+            y: 0,
+            width: 2000,
+            height: 1200,
+        };
+        assert_eq!(
+            clamp_window_geometry(oversized, &[primary]),
+            Some(WindowGeometry {
+                x: 0,
+                y: 0,
+                width: 1440,
+                height: 900
+            })
+        );
+        assert_eq!(clamp_window_geometry(on_external, &[]), None);
+    }
 
     #[test]
-    fn resample_linear_returns_same_when_rate_matches() {
-        let source = vec![0.0_f32, 0.5, -0.5, 1.0];
-        let out = resample_linear(&source, 16_000, 16_000);
-        assert_eq!(out, source);
+    fn token_confidence_averages_spoken_tokens_from_full_json() {
+        let json = r#"{"transcription":[{"text":" Hello there","tokens":[
+            {"text":"[_BEG_]","p":0.1},
+            {"text":" Hello","p":0.9},
+            {"text":" there","p":0.7},
+            {"text":"[_TT_50]","p":0.2}
+        ]}]}"#;
+        let confidence = whisper_json_token_confidence(json).expect("token confidence");
+        assert!((confidence - 0.8).abs() < 1e-6);
+        assert_eq!(
+            whisper_json_token_confidence(r#"{"transcription":[]}"#),
+            None
+        );
+        assert_eq!(whisper_json_token_confidence("not json"), None);
+
+        assert_eq!(transcript_confidence("hm", 3.0, Some(0.42)), 0.42);
+        // Without JSON the heuristic score decides: one word over 4s is low information.
+        assert!(transcript_confidence("hm", 4.0, None) < 0.35);
     }
 
     #[test]
-    fn resample_linear_produces_output_when_rate_changes() {
-        let source = vec![0.0_f32, 1.0, 0.0, -1.0];
-        let out = resample_linear(&source, 8_000, 16_000);
-        assert!(out.len() > source.len());
-        assert!(out.iter().all(|sample| sample.is_finite()));
+    fn whisper_timings_report_load_and_total_milliseconds() {
+        let stderr = "whisper_print_timings:     load time =   812.34 ms\n\
+whisper_print_timings:   encode time =   300.00 ms\n\
+whisper_print_timings:    total time =  1500.50 ms\n";
+        assert_eq!(parse_whisper_timings(stderr), Some((812.34, 1500.5)));
+        assert_eq!(parse_whisper_timings("load time = 10.0 ms"), None);
+        assert_eq!(parse_whisper_timings(""), None);
     }
 
     #[test]
-    fn analyze_audio_signal_reports_peak_rms_and_duration() {
-        let samples = vec![0.0_f32, 0.25, -0.5, 0.5];
-        let stats = analyze_audio_signal(&samples, 8_000);
-        assert!((stats.peak_abs - 0.5).abs() < 0.0001);
-        assert!(stats.rms > 0.0);
-        assert!(stats.duration_secs > 0.0);
+    fn paused_recording_keeps_its_samples_until_capture_resumes() {
+        let samples = Arc::new(Mutex::new(vec![0.25_f32; 4]));
+        let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+        let sink = Arc::clone(&samples);
+        let handle = std::thread::spawn(move || {
+            let _ = stop_rx.recv();
+            sink.lock().expect("samples lock").push(0.5);
+        });
+        let mut recording = ActiveRecording {
+            session_id: 1,
+            input: OpenedInput {
+                sample_rate: 48_000,
+                channels: 1,
+                device_name: "Test Mic".to_string(),
+            },
+            capture: Some(CaptureThread { stop_tx, handle }),
+            samples: Arc::clone(&samples),
+            channel_capture: None,
+            options: DictationSessionOptions::default(),
+        };
+        assert_eq!(recording_state_name(&recording), "listening");
+
+        assert!(recording.capture.take().is_some_and(CaptureThread::stop));
+        assert_eq!(recording_state_name(&recording), "paused");
+        assert_eq!(samples.lock().expect("samples lock").len(), 5);
     }
 
     #[test]
-    fn quiet_audio_detection_flags_near_silent_capture() {
-        let samples = vec![0.0002_f32; 16_000];
-        let stats = analyze_audio_signal(&samples, 16_000);
-        assert!(audio_signal_is_too_quiet(stats));
-        assert!(quiet_audio_error(stats, "MacBook Pro Microphone").contains("too quiet"));
+    fn input_device_entry_labels_loopback_sources() {
+        let mic = input_device_entry("MacBook Pro Microphone".to_string(), true);
+        assert!(!mic.loopback);
+        assert_eq!(mic.label, "MacBook Pro Microphone");
+
+        let virtual_device = input_device_entry("BlackHole 2ch".to_string(), false);
+        assert!(virtual_device.loopback);
+        assert_eq!(virtual_device.label, "System audio (BlackHole 2ch)");
+
+        let output = input_device_entry(format!("{LOOPBACK_DEVICE_PREFIX}Speakers"), false);
+        assert!(output.loopback);
+        assert_eq!(output.label, "System audio: Speakers");
+        assert!(loopback_unavailable_message(&output.name).contains("'Speakers'"));
     }
 
     #[test]
-    fn normalize_audio_gain_boosts_quiet_but_valid_audio() {
-        let samples = vec![0.01_f32, -0.02, 0.03, -0.04];
-        let stats = analyze_audio_signal(&samples, 16_000);
-        assert!(!audio_signal_is_too_quiet(stats));
-        let boosted = normalize_audio_gain(samples.clone(), stats);
-        let boosted_peak = boosted
-            .iter()
-            .map(|sample| sample.abs())
-            .fold(0.0_f32, f32::max);
-        let original_peak = samples
-            .iter()
-            .map(|sample| sample.abs())
-            .fold(0.0_f32, f32::max);
-        assert!(boosted_peak > original_peak);
-        assert!(boosted_peak <= 0.85);
+    fn audio_host_falls_back_to_the_default_host() {
+        let default_id = cpal::default_host().id();
+        let settings = LocalSettings {
+            audio_host: Some("NoSuchHost".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(audio_host(&settings).id(), default_id);
+        assert_eq!(
+            find_audio_host_id(&format!(" {} ", default_id.name().to_uppercase())),
+            Some(default_id)
+        );
+        assert_eq!(find_audio_host_id("NoSuchHost"), None);
     }
 
     #[test]
@@ -3889,6 +19912,104 @@ mod tests {
         assert!(!whisper_help_text_looks_valid("", stderr));
     }
 
+    #[test]
+    fn whisper_help_text_capabilities_detect_gpu_flags() {
+        let stdout = "usage: whisper-cli [options] file0.wav\n  -m FNAME, --model FNAME\n  -ng, --no-gpu [false] disable GPU\n  -fa, --flash-attn [false] flash attention";
+        let capabilities = whisper_help_text_capabilities(stdout, "");
+        assert!(capabilities.gpu_toggle);
+        assert!(capabilities.flash_attention);
+
+        let placeholder =
+            "Bundled whisper-cli placeholder. Replace with a real whisper-cli sidecar binary.";
+        assert_eq!(
+            whisper_help_text_capabilities("", placeholder),
+            WhisperCliCapabilities::default()
+        );
+    }
+
+    #[test]
+    fn acceleration_cpu_disables_gpu_when_supported() {
+        let capabilities = WhisperCliCapabilities {
+            gpu_toggle: true,
+            flash_attention: false,
+        };
+        let (resolved, args) = resolve_acceleration_args(
+            WhisperAcceleration::Cpu,
+            capabilities,
+            Path::new("/tmp/ggml-base.en.bin"),
+        );
+        assert_eq!(resolved, WhisperAcceleration::Cpu);
+        assert_eq!(args, vec!["-ng"]);
+    }
+
+    #[test]
+    fn acceleration_falls_back_to_auto_without_backend_support() {
+        let (resolved, args) = resolve_acceleration_args(
+            WhisperAcceleration::Cuda,
+            WhisperCliCapabilities::default(),
+            Path::new("/tmp/ggml-base.en.bin"),
+        );
+        assert_eq!(resolved, WhisperAcceleration::Auto);
+        assert!(args.is_empty());
+
+        let (resolved, _) = resolve_acceleration_args(
+            WhisperAcceleration::CoreMl,
+            WhisperCliCapabilities::default(),
+            Path::new("/tmp/dicktaint-missing/ggml-base.en.bin"),
+        );
+        assert_eq!(resolved, WhisperAcceleration::Auto);
+    }
+
+    #[test]
+    fn whisper_acceleration_ignores_unknown_saved_value() {
+        let settings = LocalSettings {
+            acceleration: Some("quantum".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(whisper_acceleration(&settings), WhisperAcceleration::Auto);
+    }
+
+    #[test]
+    fn whisper_timeout_scales_with_audio_length() {
+        assert_eq!(effective_whisper_timeout(60, 0.0), Duration::from_secs(60));
+        assert_eq!(
+            effective_whisper_timeout(60, 300.0),
+            Duration::from_secs(660)
+        );
+    }
+
+    #[test]
+    fn slow_processing_warning_lands_before_the_whisper_timeout() {
+        assert_eq!(
+            slow_processing_threshold(effective_whisper_timeout(60, 5.0)),
+            DICTATION_SLOW_AFTER
+        );
+        assert_eq!(
+            slow_processing_threshold(Duration::from_secs(12)),
+            Duration::from_secs(6)
+        );
+    }
+
+    #[test]
+    fn whisper_timeout_setting_falls_back_when_out_of_range() {
+        let settings = LocalSettings {
+            whisper_timeout_seconds: Some(1),
+            ..LocalSettings::default()
+        };
+        assert_eq!(whisper_timeout_seconds(&settings), 60);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_command_with_timeout_kills_hung_process() {
+        let mut command = std::process::Command::new("sleep");
+        command.arg("5");
+        let started = std::time::Instant::now();
+        let result = run_command_with_timeout(command, Duration::from_millis(100), None);
+        assert!(matches!(result, Err(CommandWaitError::TimedOut)));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
     #[test]
     fn normalize_dictation_trigger_accepts_valid_combo() {
         assert_eq!(
@@ -3898,6 +20019,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(target_os = "macos")]
     fn normalize_dictation_trigger_accepts_fn_key() {
         assert_eq!(normalize_dictation_trigger("fn").unwrap(), "Fn".to_string());
         assert_eq!(
@@ -3906,11 +20028,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn supported_trigger_keys_round_trip_through_normalization() {
+        let supported = supported_trigger_keys();
+        for key in &supported.keys {
+            assert_eq!(canonicalize_trigger_key(key).as_deref(), Some(key.as_str()));
+            let combo = if supported.standalone_keys.contains(key) {
+                key.clone()
+            } else {
+                format!("Shift+{key}")
+            };
+            assert!(normalize_dictation_trigger(&combo).is_ok(), "{combo}");
+        }
+        for modifier in &supported.modifiers {
+            assert_eq!(
+                canonicalize_trigger_modifier(modifier),
+                Some(modifier.as_str())
+            );
+        }
+        assert_eq!(
+            supported.keys.iter().any(|key| key == "Fn"),
+            supported.fn_trigger_supported
+        );
+    }
+
     #[test]
     fn normalize_dictation_trigger_rejects_fn_with_modifiers() {
         assert!(normalize_dictation_trigger("Shift+Fn").is_err());
     }
 
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn normalize_dictation_trigger_rejects_fn_off_macos() {
+        let error = normalize_dictation_trigger("Fn").unwrap_err();
+        assert!(error.contains("only supported on macOS"));
+        assert!(normalize_dictation_trigger("globe").is_err());
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn resolve_effective_trigger_falls_back_when_persisted_fn_is_unsupported() {
+        let settings = LocalSettings {
+            dictation_trigger: Some("Fn".to_string()),
+            ..LocalSettings::default()
+        };
+        assert_eq!(
+            resolve_effective_dictation_trigger(&settings),
+            Some(default_dictation_trigger())
+        );
+    }
+
     #[test]
     fn normalize_dictation_trigger_rejects_missing_modifier() {
         assert!(normalize_dictation_trigger("D").is_err());
@@ -3921,6 +20088,20 @@ mod tests {
         assert!(normalize_dictation_trigger("Ctrl+K+J").is_err());
     }
 
+    #[test]
+    fn normalize_dictation_trigger_counts_length_in_characters() {
+        // 45 characters but 85 bytes: rejected as a bad key, not as too long.
+        let under_limit = format!("Ctrl+{}", "é".repeat(40));
+        assert!(under_limit.len() > MAX_DICTATION_TRIGGER_LENGTH);
+        let error = normalize_dictation_trigger(&under_limit).unwrap_err();
+        assert!(!error.contains("too long"), "{error}");
+
+        let over_limit = format!("Ctrl+{}", "é".repeat(60));
+        assert!(normalize_dictation_trigger(&over_limit)
+            .unwrap_err()
+            .contains("too long"));
+    }
+
     #[test]
     fn resolve_effective_trigger_defaults_when_unset() {
         let settings = LocalSettings::default();
@@ -3967,6 +20148,21 @@ mod tests {
         assert!(focused_field_insert_enabled(&settings));
     }
 
+    #[test]
+    fn insert_delay_defaults_and_ignores_out_of_range_values() {
+        assert_eq!(
+            insert_delay_ms(&LocalSettings::default()),
+            DEFAULT_INSERT_DELAY_MS
+        );
+        let mut settings = LocalSettings {
+            insert_delay_ms: Some(0),
+            ..LocalSettings::default()
+        };
+        assert_eq!(insert_delay_ms(&settings), 0);
+        settings.insert_delay_ms = Some(60_000);
+        assert_eq!(insert_delay_ms(&settings), DEFAULT_INSERT_DELAY_MS);
+    }
+
     #[test]
     fn runtime_details_report_fn_permission_fallback() {
         let runtime =
@@ -4029,9 +20225,7 @@ fn main() {
     let builder = builder.plugin(
         tauri_plugin_global_shortcut::Builder::new()
             .with_handler(|app, _shortcut, event| {
-                if event.state() == ShortcutState::Pressed {
-                    dispatch_backend_hotkey_action(app, BackendHotkeyAction::Toggle);
-                }
+                route_hotkey_edge(app, event.state() == ShortcutState::Pressed, false);
             })
             .build(),
     );
@@ -4044,27 +20238,52 @@ fn main() {
                     "Failed to resolve Tauri app data directory while initializing local dictation paths: {e}"
                 )
             })?;
-            let (models_dir, settings_path) = resolve_local_paths(&app_data_dir).map_err(|e| {
+            let storage_base_dir = load_storage_base_dir(&app_data_dir);
+            let (models_dir, settings_path, recordings_dir) = resolve_local_paths(&storage_base_dir).map_err(|e| {
                 format!(
                     "Failed to initialize local dictation model paths under {}: {e}",
-                    app_data_dir.display()
+                    storage_base_dir.display()
                 )
             })?;
-            let initial_settings = load_local_settings(&settings_path);
+            let mut initial_settings = load_local_settings(&settings_path);
+            reconcile_local_models(&models_dir, &settings_path, &mut initial_settings);
             let initial_dictation_trigger = resolve_effective_dictation_trigger(&initial_settings);
 
             app.manage(AppConfig {
                 whisper_model_path_override: whisper_model_path_override.clone(),
                 whisper_cli_path_override: whisper_cli_path_override.clone(),
                 bundled_whisper_cli_path,
+                whisper_cli_capabilities: WhisperCliCapabilityCache::default(),
             });
             app.manage(LocalModelState {
-                settings_path,
-                models_dir,
+                paths: Mutex::new(StoragePaths {
+                    settings_path,
+                    models_dir,
+                    recordings_dir,
+                }),
                 settings: Arc::new(Mutex::new(initial_settings)),
+                active_downloads: Arc::default(),
+                storage_moving: Arc::default(),
             });
             app.manage(DictationState::default());
             app.manage(GlobalHotkeyState::default());
+            // setup runs on the main thread, which AppKit requires for local event monitors.
+            #[cfg(target_os = "macos")]
+            match MacFnLocalMonitor::install(app.handle()) {
+                Ok(monitor) => {
+                    if let Ok(mut guard) = app
+                        .state::<GlobalHotkeyState>()
+                        .macos_fn_local_monitor
+                        .lock()
+                    {
+                        *guard = Some(monitor);
+                    }
+                }
+                Err(error) => log::warn!("{error}"),
+            }
+            if let Ok(settings) = app.state::<LocalModelState>().settings.lock() {
+                apply_hotkey_activation(app.state::<GlobalHotkeyState>().inner(), &settings);
+            }
 
             if let Err(error) = apply_registered_hotkey(
                 app.handle(),
@@ -4074,6 +20293,20 @@ fn main() {
                 log::warn!("Failed to apply initial global hotkey: {error}");
             }
 
+            let saved_window_geometry = app
+                .state::<LocalModelState>()
+                .settings
+                .lock()
+                .ok()
+                .and_then(|settings| settings.window_geometry);
+            restore_main_window_geometry(app.handle(), saved_window_geometry);
+            app.manage(WindowGeometryState {
+                tx: Mutex::new(spawn_window_geometry_saver(app.handle())),
+            });
+            app.manage(WarmWhisperState {
+                tx: Mutex::new(spawn_warm_whisper_worker()),
+            });
+
             if should_start_hidden() {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.hide();
@@ -4083,6 +20316,22 @@ fn main() {
             if let Err(error) = create_pill_overlay_windows(app.handle()) {
                 log::warn!("Failed to create pill overlay windows: {error}");
             }
+            spawn_pill_monitor_watcher(app.handle());
+            std::thread::spawn(|| {
+                match cleanup_temp_artifacts(
+                    &std::env::temp_dir(),
+                    TEMP_ARTIFACT_STALE_AGE,
+                    &process_is_alive,
+                ) {
+                    Ok(cleanup) if !cleanup.removed.is_empty() => log::info!(
+                        "Removed {} stale temp files ({} bytes)",
+                        cleanup.removed.len(),
+                        cleanup.bytes_freed
+                    ),
+                    Ok(_) => {}
+                    Err(error) => log::warn!("Startup temp file sweep failed: {error}"),
+                }
+            });
 
             Ok(())
         })
@@ -4090,31 +20339,136 @@ fn main() {
             if window.label() != "main" {
                 return;
             }
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                api.prevent_close();
-                sync_pill_after_main_window_hide(window.app_handle());
-                let _ = window.hide();
+            match event {
+                tauri::WindowEvent::CloseRequested { api, .. } => {
+                    api.prevent_close();
+                    sync_pill_after_main_window_hide(window.app_handle());
+                    let _ = window.hide();
+                }
+                tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+                    let Some(geometry) = main_window_geometry(window) else {
+                        return;
+                    };
+                    if let Some(state) = window.try_state::<WindowGeometryState>() {
+                        if let Ok(tx) = state.tx.lock() {
+                            let _ = tx.send(geometry);
+                        }
+                    }
+                }
+                _ => {}
             }
         })
         .invoke_handler(tauri::generate_handler![
             get_dictation_onboarding,
+            apply_dictation_hotkey,
             get_dictation_trigger,
             set_dictation_trigger,
+            validate_dictation_trigger,
+            get_supported_trigger_keys,
             clear_dictation_trigger,
+            reregister_hotkey,
             set_preferred_input_device,
+            list_audio_hosts,
+            set_audio_host,
             set_focused_field_insert_enabled,
+            toggle_output_mode,
+            set_whisper_acceleration,
+            set_whisper_timeout_seconds,
+            set_whisper_threads,
+            set_decode_overrides,
+            set_extra_whisper_args,
+            set_whisper_cli_path,
+            set_extra_whisper_cli_paths,
+            diagnose_whisper_cli,
+            set_hotkey_activation,
+            set_min_hold_ms,
+            set_audio_normalization,
+            set_memory_guard,
+            set_downmix_mode,
+            set_preserve_channels,
+            set_preferred_sample_rate,
+            set_show_progress,
+            set_command_mode,
+            export_last_recording_wav,
+            set_last_recording_retention_seconds,
+            set_partial_transcripts,
+            set_debug_audio_info,
+            set_keep_whisper_warm,
+            set_chunk_seconds,
+            set_trim_silence,
+            set_noise_gate,
+            benchmark_model,
+            set_model_download_mirror,
+            set_model_source,
+            set_auto_format,
+            set_output_suffix,
+            set_insert_wrap,
+            set_session_insert_wrap,
+            set_low_confidence_retry,
+            set_two_pass_decode,
+            set_min_confidence,
+            set_profanity_filter,
+            set_dedup_repeats,
+            set_min_transcribe_seconds,
+            set_sound_cues,
+            set_strip_fillers,
+            set_spoken_commands,
+            recommend_model_for_ram,
+            set_recommendation_bias,
+            get_model_runnability,
+            set_pill_overlay,
+            check_permissions,
+            open_permission_settings,
+            export_settings,
+            get_debug_report,
+            reset_dictation_settings,
+            import_settings,
+            set_trailing_punctuation,
+            set_save_recordings,
+            set_recording_format,
+            get_recordings,
+            retranscribe_recording,
+            transcribe_file,
+            get_storage_dir,
+            set_storage_dir,
+            transcribe_to_subtitles,
+            open_recordings_folder,
             open_whisper_setup_page,
             insert_text_into_focused_field,
+            test_insert,
+            set_insert_delay_ms,
             install_dictation_model,
+            select_dictation_model,
+            run_self_test,
             delete_dictation_model,
+            delete_all_models,
+            cleanup_temp_files,
+            request_microphone_permission,
+            get_last_transcription_info,
+            get_usage_stats,
+            set_usage_stats,
             start_native_dictation,
             stop_native_dictation,
-            cancel_native_dictation
+            cancel_native_dictation,
+            pause_native_dictation,
+            resume_native_dictation,
+            preview_capture,
+            record_and_playback,
+            set_transcript_artifact_tokens,
+            import_dictation_model,
+            rename_imported_model,
+            set_fallback_on_oom,
+            set_pill_visibility,
+            preload_model
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
     app.run(|app_handle, event| {
+        // A warm whisper-server is a separate process; it must not outlive the app.
+        if let tauri::RunEvent::Exit = event {
+            stop_warm_whisper(app_handle, true);
+        }
         #[cfg(target_os = "macos")]
         if let tauri::RunEvent::Reopen { .. } = event {
             show_main_window(app_handle);
@@ -124,6 +124,7 @@ function createMockDom({ nativeDesktop = false, onboardingPayload = null } = {})
     'setupSteps',
     'startDictation',
     'stopDictation',
+    'scratchDictation',
     'clearTranscript',
     'dictationWaveform',
     'dictationWaveformLevel',
@@ -162,6 +163,7 @@ function createMockDom({ nativeDesktop = false, onboardingPayload = null } = {})
     'saveDictationHotkey',
     'resetDictationHotkey',
     'clearDictationHotkey',
+    'reregisterDictationHotkey',
     'dictationHotkeyStatus',
     'dictationHotkeyPresets',
     'focusedFieldInsertCard',
@@ -231,7 +233,7 @@ function createMockDom({ nativeDesktop = false, onboardingPayload = null } = {})
     __TAURI__: nativeDesktop ? {
       core: {
         invoke: async (command) => {
-          if (command === 'get_dictation_onboarding') {
+          if (command === 'get_dictation_onboarding' || command === 'apply_dictation_hotkey') {
             return onboardingPayload || {
               onboarding_required: false,
               selected_model_id: 'base-en',
@@ -457,6 +459,25 @@ describe('dictation frontend hotkey polish', () => {
     expect(document.getElementById('dictationHotkeyStatus').textContent).toContain('focused');
   });
 
+  it('tracks the backend in-app fn monitor mode', () => {
+    api.applyDictationHotkeyPayload({
+      trigger: 'Fn',
+      default_trigger: 'Fn',
+      fn_listener_mode: 'local',
+      trigger_mode: 'focused-window-hold',
+      trigger_status: 'Hold Fn to dictate while dicktaint is focused.'
+    });
+    expect(api.getState().fnListenerMode).toBe('local');
+
+    api.applyDictationHotkeyPayload({
+      trigger: 'Fn',
+      default_trigger: 'Fn',
+      trigger_mode: 'global-hold',
+      trigger_status: 'Hold Fn anywhere to dictate, then release to transcribe.'
+    });
+    expect(api.getState().fnListenerMode).toBe('unavailable');
+  });
+
   it('renders pill messaging from the active hotkey mode', () => {
     api.applyDictationHotkeyPayload({
       trigger: 'CmdOrCtrl+Shift+D',
@@ -502,6 +523,164 @@ describe('dictation frontend hotkey polish', () => {
     expect(state.currentDraftText).toBe('first session');
   });
 
+  it('blocks starting dictation when microphone permission is denied', () => {
+    api.applyPermissionsPayload({
+      microphone: 'denied',
+      input_monitoring: 'not-determined',
+      accessibility: 'not-granted',
+      can_record: false
+    });
+    expect(document.getElementById('startDictation').disabled).toBe(true);
+
+    api.applyPermissionsPayload({
+      microphone: 'not-determined',
+      input_monitoring: 'granted',
+      accessibility: 'granted',
+      can_record: true
+    });
+    expect(api.getState().microphonePermissionBlocked).toBe(false);
+  });
+
+  it('does not keep scratch transcripts in the draft or history', () => {
+    api.handleNativeDictationStatePayload({
+      state: 'listening',
+      session_id: 3
+    });
+    api.handleNativeDictationStatePayload({
+      state: 'idle',
+      session_id: 3,
+      transcript: 'just testing',
+      scratch: true
+    });
+
+    const state = api.getState();
+    expect(state.isDictating).toBe(false);
+    expect(state.currentDraftText).toBe('');
+    expect(state.dictationHistory).toHaveLength(0);
+    expect(document.getElementById('status').textContent).toContain('just testing');
+  });
+
+  it('surfaces the clipping warning alongside the completed transcript', () => {
+    api.handleNativeDictationStatePayload({
+      state: 'listening',
+      session_id: 5
+    });
+    api.handleNativeDictationStatePayload({
+      state: 'idle',
+      session_id: 5,
+      transcript: 'too loud',
+      scratch: false,
+      warning: 'Input was clipping; lower your mic gain for better accuracy.'
+    });
+
+    expect(api.getState().currentDraftText).toBe('too loud');
+    expect(document.getElementById('status').textContent).toContain('lower your mic gain');
+  });
+
+  it('keeps a paused native session active until it resumes or stops', () => {
+    api.handleNativeDictationStatePayload({
+      state: 'listening',
+      session_id: 9
+    });
+    api.handleNativeDictationStatePayload({
+      state: 'paused',
+      session_id: 9
+    });
+
+    expect(api.getState().isDictating).toBe(true);
+    expect(api.getState().isNativeDictationPaused).toBe(true);
+    expect(document.body.dataset.mode).toBe('paused');
+    expect(document.getElementById('status').textContent).toContain('Paused');
+
+    api.handleNativeDictationStatePayload({
+      state: 'listening',
+      session_id: 9
+    });
+    expect(api.getState().isNativeDictationPaused).toBe(false);
+
+    api.handleNativeDictationStatePayload({
+      state: 'idle',
+      session_id: 9,
+      transcript: 'before and after the call'
+    });
+    expect(api.getState().isDictating).toBe(false);
+    expect(api.getState().currentDraftText).toBe('before and after the call');
+  });
+
+  it('notes when the backend re-checked a transcript with the accurate pass', () => {
+    api.handleNativeDictationStatePayload({
+      state: 'listening',
+      session_id: 8
+    });
+    api.handleNativeDictationStatePayload({
+      state: 'idle',
+      session_id: 8,
+      transcript: 'checked twice',
+      scratch: false,
+      warning: null,
+      retried: true
+    });
+
+    expect(api.getState().currentDraftText).toBe('checked twice');
+    expect(document.getElementById('status').textContent).toContain('Re-checked for accuracy.');
+  });
+
+  it('shows whisper progress for the active session and ignores stale progress', () => {
+    api.handleNativeDictationStatePayload({
+      state: 'processing',
+      session_id: 6
+    });
+    api.handleNativeDictationProgressPayload({ session_id: 6, percent: 45 });
+    expect(document.getElementById('status').textContent).toBe('Transcribing captured audio... 45%');
+
+    api.handleNativeDictationProgressPayload({ session_id: 2, percent: 90 });
+    expect(document.getElementById('status').textContent).toContain('45%');
+  });
+
+  it('shows cumulative partial transcripts until the final transcript lands', () => {
+    api.handleNativeDictationStatePayload({
+      state: 'processing',
+      session_id: 7
+    });
+    api.handleNativeDictationPartialPayload({
+      session_id: 7,
+      text: 'first paragraph',
+      chunks_done: 1,
+      chunk_count: 3
+    });
+    expect(document.getElementById('status').textContent).toBe('Transcribing captured audio... (1/3) So far: first paragraph');
+
+    api.handleNativeDictationProgressPayload({ session_id: 7, percent: 50 });
+    expect(document.getElementById('status').textContent).toBe('Transcribing captured audio... 50% So far: first paragraph');
+
+    api.handleNativeDictationPartialPayload({ session_id: 4, text: 'stale', chunks_done: 1, chunk_count: 2 });
+    expect(document.getElementById('status').textContent).not.toContain('stale');
+
+    api.handleNativeDictationStatePayload({
+      state: 'idle',
+      session_id: 7,
+      transcript: 'first paragraph second paragraph'
+    });
+    expect(api.getState().currentDraftText).toBe('first paragraph second paragraph');
+  });
+
+  it('opens onboarding from the needs-onboarding event', () => {
+    api.handleNativeNeedsOnboardingPayload({ reason: 'no_model', error: '' });
+    expect(document.body.dataset.screen).toBe('onboarding');
+    expect(document.getElementById('status').textContent).toBe('Install a local dictation model to start dictating.');
+
+    api.handleNativeNeedsOnboardingPayload({ reason: 'cli_missing', error: 'whisper-cli not found.' });
+    expect(document.getElementById('status').textContent).toBe('whisper-cli not found.');
+  });
+
+  it('reads structured command errors and falls back for plain strings', () => {
+    const error = { code: 'MIC_NOT_FOUND', message: 'No microphone input device found.' };
+    expect(api.getErrorCode(error)).toBe('MIC_NOT_FOUND');
+    expect(api.getErrorMessage(error)).toBe('No microphone input device found.');
+    expect(api.getErrorCode('Dictation is not running.')).toBe('');
+    expect(api.getErrorCode(null)).toBe('');
+  });
+
   it('renders live mic levels from native audio payloads and ignores stale sessions', () => {
     api.handleNativeDictationStatePayload({
       state: 'listening',